@@ -1,3 +1,7 @@
 fn main() {
+    // protoc comes with the build rather than having to be installed.
+    let protoc = protoc_bin_vendored::protoc_bin_path().expect("no bundled protoc for this platform");
+    std::env::set_var("PROTOC", protoc);
+    tonic_build::compile_protos("proto/rememex.proto").expect("failed to compile proto/rememex.proto");
     tauri_build::build()
 }
@@ -0,0 +1,112 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use log::{debug, warn};
+use tauri::WebviewWindow;
+
+use crate::config::{ThemeMode, WindowLayoutConfig, WindowMode};
+
+/// Whether a native backdrop (Mica, acrylic, macOS vibrancy) is behind the webview.
+/// When it is not, the frontend switches to opaque surfaces so the transparent
+/// window does not show the desktop through.
+pub struct VibrancyState(pub AtomicBool);
+
+impl VibrancyState {
+    pub fn is_active(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Resizes the main window and sets the webview zoom. Values are clamped first so a
+/// typo in config.json cannot produce an unusable window.
+pub fn apply_window_layout(window: &WebviewWindow, layout: &WindowLayoutConfig) {
+    let layout = layout.clamped();
+    if let Err(e) = window.set_size(tauri::LogicalSize::new(layout.width, layout.height)) {
+        warn!("Failed to resize window: {}", e);
+    }
+    let _ = window.center();
+    if let Err(e) = window.set_zoom(layout.ui_scale) {
+        warn!("Failed to set UI scale: {}", e);
+    }
+    debug!("Window layout {}x{} @ {:.2}x", layout.width, layout.height, layout.ui_scale);
+}
+
+/// Switches the main window between the borderless popup and a docked
+/// window with native decorations. `always_on_top` only applies to the popup.
+pub fn apply_window_mode(window: &WebviewWindow, mode: WindowMode, always_on_top: bool) {
+    let docked = mode == WindowMode::Docked;
+    if let Err(e) = window.set_decorations(docked) {
+        warn!("Failed to set window decorations: {}", e);
+    }
+    let _ = window.set_resizable(docked);
+    let _ = window.set_skip_taskbar(!docked);
+    let _ = window.set_always_on_top(!docked && always_on_top);
+    debug!("Window mode {:?}", mode);
+}
+
+#[cfg(target_os = "windows")]
+pub fn apply_window_effects(window: &WebviewWindow, theme: ThemeMode) -> bool {
+    use window_vibrancy::{apply_acrylic, apply_mica, clear_acrylic, clear_mica};
+
+    let dark = match theme {
+        ThemeMode::Dark => Some(true),
+        ThemeMode::Light => Some(false),
+        ThemeMode::System => None,
+    };
+
+    let _ = clear_mica(window);
+    let _ = clear_acrylic(window);
+
+    match apply_mica(window, dark) {
+        Ok(()) => {
+            debug!("Applied Mica backdrop (theme={:?})", theme);
+            return true;
+        }
+        Err(e) => debug!("Mica unavailable, trying acrylic: {}", e),
+    }
+
+    let tint = if dark == Some(false) { (243, 243, 243, 160) } else { (32, 32, 32, 160) };
+    match apply_acrylic(window, Some(tint)) {
+        Ok(()) => {
+            debug!("Applied acrylic backdrop (theme={:?})", theme);
+            true
+        }
+        Err(e) => {
+            warn!("No window backdrop available, using opaque surfaces: {}", e);
+            false
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+pub fn apply_window_effects(window: &WebviewWindow, theme: ThemeMode) -> bool {
+    use window_vibrancy::{apply_vibrancy, clear_vibrancy, NSVisualEffectMaterial};
+
+    // The blur takes its light or dark look from the window's appearance,
+    // except HudWindow, which is always dark.
+    let (material, appearance) = match theme {
+        ThemeMode::Dark => (NSVisualEffectMaterial::HudWindow, Some(tauri::Theme::Dark)),
+        ThemeMode::Light => (NSVisualEffectMaterial::Popover, Some(tauri::Theme::Light)),
+        ThemeMode::System => (NSVisualEffectMaterial::Popover, None),
+    };
+    if let Err(e) = window.set_theme(appearance) {
+        debug!("Failed to set window appearance: {}", e);
+    }
+    let _ = clear_vibrancy(window);
+
+    match apply_vibrancy(window, material, None, Some(10.0)) {
+        Ok(()) => {
+            debug!("Applied vibrancy (theme={:?})", theme);
+            true
+        }
+        Err(e) => {
+            warn!("Vibrancy unavailable, using opaque surfaces: {}", e);
+            false
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+pub fn apply_window_effects(_window: &WebviewWindow, theme: ThemeMode) -> bool {
+    debug!("No native backdrop on this platform (theme={:?})", theme);
+    false
+}
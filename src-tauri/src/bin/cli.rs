@@ -0,0 +1,147 @@
+//! Searches a bundle exported from the app ("Export searchable bundle"):
+//!
+//! ```text
+//! rememex-cli search --bundle <dir> [--k 10] [--format text|json|alfred|powertoys] <query>
+//! rememex-cli info --bundle <dir>
+//! ```
+
+use std::path::{Path, PathBuf};
+
+use log::debug;
+use mimalloc::MiMalloc;
+
+use rememex_lib::config::{app_data_dir, get_embedding_model, EmbeddingProviderConfig};
+use rememex_lib::editor;
+use rememex_lib::indexer;
+use rememex_lib::indexer::bundle::{self, Manifest};
+use rememex_lib::indexer::embedding_provider::{EmbeddingProvider, LocalProvider, RemoteProvider};
+use rememex_lib::launcher::{self, LauncherFormat, LauncherItem};
+
+#[global_allocator]
+static GLOBAL: MiMalloc = MiMalloc;
+
+const VALUE_FLAGS: &[&str] = &["--bundle", "--k", "--format", "--models"];
+/// A remote provider's key, which the bundle doesn't carry.
+const API_KEY_VAR: &str = "REMEMEX_API_KEY";
+
+const USAGE: &str = "\
+usage:
+  rememex-cli search --bundle <dir> [--k 10] [--format text|json|alfred|powertoys] [--models <dir>] <query>
+  rememex-cli info --bundle <dir>";
+
+fn arg_value(args: &[String], name: &str) -> Option<String> {
+    let inline = format!("{}=", name);
+    args.iter()
+        .find_map(|a| a.strip_prefix(&inline).map(str::to_string))
+        .or_else(|| args.iter().position(|a| a == name).and_then(|i| args.get(i + 1).cloned()))
+}
+
+/// The query doesn't need quoting.
+fn query_words(args: &[String]) -> String {
+    let mut words = Vec::new();
+    let mut rest = args.iter().skip(2);
+    while let Some(arg) = rest.next() {
+        if VALUE_FLAGS.contains(&arg.as_str()) {
+            rest.next();
+        } else if !arg.starts_with("--") {
+            words.push(arg.as_str());
+        }
+    }
+    words.join(" ")
+}
+
+fn load_provider(manifest: &Manifest, models: PathBuf) -> Result<Box<dyn EmbeddingProvider>, Box<dyn std::error::Error>> {
+    Ok(match &manifest.embedding_provider {
+        EmbeddingProviderConfig::Local { model } => {
+            let model_enum = get_embedding_model(model);
+            let loaded = indexer::load_model(model_enum.clone(), models.clone())?;
+            Box::new(LocalProvider::new(loaded, model_enum, models))
+        }
+        EmbeddingProviderConfig::Remote(remote) => {
+            let mut remote = remote.clone();
+            remote.api_key = std::env::var(API_KEY_VAR).ok().filter(|key| !key.is_empty());
+            Box::new(RemoteProvider::new(remote))
+        }
+    })
+}
+
+fn print_info(dir: &Path, manifest: &Manifest) {
+    let created = chrono::DateTime::from_timestamp(manifest.created_at, 0)
+        .map(|at| at.format("%Y-%m-%d %H:%M UTC").to_string())
+        .unwrap_or_default();
+    println!("{} ({})", manifest.container, dir.display());
+    if !manifest.description.is_empty() {
+        println!("{}", manifest.description);
+    }
+    println!("{} files, {} chunks, exported {} by rememex {}", manifest.files, manifest.chunks, created, manifest.app_version);
+    println!("model: {}", manifest.embedding_provider.provider_label());
+    for root in &manifest.roots {
+        println!("  {}", root);
+    }
+}
+
+fn print_text(items: &[LauncherItem]) {
+    if items.is_empty() {
+        println!("no results");
+    }
+    for item in items {
+        println!("{:.3}  {}", item.score, item.path);
+        if !item.context.is_empty() {
+            println!("       {}", item.context);
+        }
+        for line in item.snippet.lines().map(str::trim).filter(|l| !l.is_empty()).take(3) {
+            println!("       {}", line);
+        }
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("warn"))
+        .target(env_logger::Target::Stderr)
+        .init();
+
+    let args: Vec<String> = std::env::args().collect();
+    let command = args.get(1).map(String::as_str);
+    let Some(dir) = arg_value(&args, "--bundle").map(PathBuf::from) else {
+        eprintln!("{}", USAGE);
+        std::process::exit(2);
+    };
+    let manifest = bundle::open(&dir)?;
+    debug!("Bundle '{}' at {}", manifest.container, dir.display());
+
+    match command {
+        Some("info") => print_info(&dir, &manifest),
+        Some("search") => {
+            let query = query_words(&args);
+            if query.trim().is_empty() {
+                eprintln!("{}", USAGE);
+                std::process::exit(2);
+            }
+            let k = arg_value(&args, "--k").and_then(|k| k.parse().ok()).unwrap_or(10usize).clamp(1, 50);
+            let format = arg_value(&args, "--format").unwrap_or_else(|| "text".to_string());
+            let models = arg_value(&args, "--models").map(PathBuf::from).unwrap_or_else(|| app_data_dir().join("models"));
+
+            let provider = load_provider(&manifest, models)?;
+            let items: Vec<LauncherItem> = bundle::search(&dir, provider.as_ref(), &query, k)
+                .await?
+                .into_iter()
+                .map(|(path, snippet, score)| {
+                    let (context, _) = indexer::pipeline::split_context(&snippet);
+                    LauncherItem { snippet: editor::snippet_body(&snippet), path, context, score, line: None }
+                })
+                .collect();
+            if format == "text" {
+                print_text(&items);
+            } else {
+                let format: LauncherFormat = format.parse()?;
+                println!("{}", launcher::render(format, &items));
+            }
+        }
+        _ => {
+            eprintln!("{}", USAGE);
+            std::process::exit(2);
+        }
+    }
+    Ok(())
+}
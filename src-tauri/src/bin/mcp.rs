@@ -1,3 +1,4 @@
+use std::collections::{BTreeSet, HashMap};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
@@ -14,11 +15,22 @@ use rmcp::{tool_handler, tool_router, schemars, ErrorData as McpError, ServerHan
 use serde::Deserialize;
 use tokio::sync::Mutex;
 
-use rememex_lib::config::{get_embedding_model, get_table_name, load_config, Config, EmbeddingProviderConfig};
+use rememex_lib::config::{
+    app_data_dir, apply_read_only_flag, get_embedding_model, get_table_name, is_path_excluded, load_config,
+    resolve_storage_options, set_offline, Config, EmbeddingProviderConfig, INDEX_FLAG,
+};
+use rememex_lib::editor::{self, RpcError};
+use rememex_lib::launcher::{self, LauncherFormat, LauncherItem};
+use rememex_lib::os_auth;
 use rememex_lib::indexer;
 use rememex_lib::indexer::annotations;
 use rememex_lib::indexer::embedding_provider::{EmbeddingProvider, LocalProvider, RemoteProvider};
-use rememex_lib::state::ModelState;
+use rememex_lib::indexer::eval::{self, EvalCase, EvalReport};
+use rememex_lib::redact::Redactor;
+use rememex_lib::remote::{self, proto};
+use rememex_lib::shared_index;
+use rememex_lib::state::{WatcherStatus, WATCHER_STATUS_FILE};
+use rememex_lib::tokens;
 
 #[global_allocator]
 static GLOBAL: MiMalloc = MiMalloc;
@@ -28,8 +40,16 @@ struct AppState {
     provider: Arc<Mutex<Box<dyn EmbeddingProvider>>>,
     reranker: Arc<Mutex<Option<fastembed::TextRerank>>>,
     config: Config,
+    app_data: PathBuf,
+    /// Connections to containers with their own `db_uri`, by URI.
+    shared_dbs: Mutex<HashMap<String, lancedb::Connection>>,
 }
 
+/// Largest file `rememex_read_file` returns; the indexer stops at the same.
+const MAX_READ_BYTES: u64 = 10 * 1024 * 1024;
+/// Bytes of a binary file shown as a hexdump.
+const BINARY_PREVIEW_BYTES: usize = 256;
+
 #[derive(Clone)]
 pub struct RememexServer {
     state: Arc<AppState>,
@@ -46,10 +66,32 @@ struct SearchParams {
     file_extensions: Option<Vec<String>>,
     #[schemars(description = "Filter by path prefix, e.g. \"src/indexer\"")]
     path_prefix: Option<String>,
-    #[schemars(description = "Max snippet size in bytes (default 1500, max 10000)")]
+    #[schemars(description = "Filter by detected document language, ISO 639-1 code, e.g. \"en\", \"de\", \"ja\"")]
+    language: Option<String>,
+    #[schemars(description = "Filter by file size on disk, e.g. \">10mb\", \"<=4kb\". Units b, kb, mb, gb; bytes without one")]
+    size: Option<String>,
+    #[schemars(description = "Filter by line count of the file's text, e.g. \">1000\", \"<50\"")]
+    lines: Option<String>,
+    #[schemars(description = "Max snippet size in bytes (default 1500, max 10000). Longer snippets keep the part around the best match")]
     context_bytes: Option<usize>,
     #[schemars(description = "Minimum relevance score (0-100). Results below this are filtered out. Default: no filtering.")]
     min_score: Option<f32>,
+    #[schemars(description = "Search the files as they were at this point: a date (\"2024-05-01\"), an RFC 3339 timestamp, or a time ago (\"30d\", \"2w\"). Needs version history turned on in Rememex; vector search only.")]
+    as_of: Option<String>,
+    #[schemars(description = "Also search files deleted in the last 30 days (or the configured retention). Their results carry \"deleted\": true.")]
+    include_deleted: Option<bool>,
+    #[schemars(description = "\"best\" (default): one result per matching chunk. \"merged\": one result per file, its matching chunks joined in file order up to context_bytes.")]
+    snippet_strategy: Option<SnippetStrategy>,
+    #[schemars(description = "Cap the whole response at this many tokens (cl100k_base). Results past it are left out, the last one may be cut short with \"truncated\": true.")]
+    max_tokens: Option<usize>,
+}
+
+#[derive(Deserialize, schemars::JsonSchema, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+enum SnippetStrategy {
+    #[default]
+    Best,
+    Merged,
 }
 
 
@@ -61,6 +103,8 @@ struct ReadFileParams {
     start_line: Option<u32>,
     #[schemars(description = "End line (1-indexed, inclusive). Omit to read to end.")]
     end_line: Option<u32>,
+    #[schemars(description = "Cap the text at this many tokens (cl100k_base), cut at a line break, with a note saying where to read on.")]
+    max_tokens: Option<usize>,
 }
 
 #[derive(Deserialize, schemars::JsonSchema)]
@@ -70,6 +114,8 @@ struct ListFilesParams {
     path_prefix: Option<String>,
     #[schemars(description = "Filter by file extensions, e.g. [\"rs\", \"ts\"]")]
     extensions: Option<Vec<String>>,
+    #[schemars(description = "Filter by detected document language, ISO 639-1 code, e.g. \"en\"")]
+    language: Option<String>,
 }
 
 #[derive(Deserialize, schemars::JsonSchema)]
@@ -95,6 +141,36 @@ struct RelatedParams {
     top_k: Option<usize>,
 }
 
+#[derive(Deserialize, schemars::JsonSchema)]
+struct TopicsParams {
+    container: Option<String>,
+    #[schemars(description = "Topic ID to list the files of. Leave out to list every topic.")]
+    topic: Option<usize>,
+    #[schemars(description = "Files per topic to return, closest to the topic's middle first (default 5 when listing topics, 50 for one topic, max 200)")]
+    limit: Option<usize>,
+    #[schemars(description = "Work the topics out again instead of using the last ones (default false). Slow on big containers.")]
+    refresh: Option<bool>,
+}
+
+#[derive(Deserialize, schemars::JsonSchema)]
+struct EntityParams {
+    #[schemars(description = "Entity to look up, any case: a person (\"Ada Lovelace\", \"@ada\"), a project (\"owner/repo\") or a tool (\"PostgreSQL\"). Lists the files mentioning it.")]
+    name: Option<String>,
+    #[schemars(description = "Absolute path of a file. Lists the entities it mentions, if no name is given.")]
+    path: Option<String>,
+    container: Option<String>,
+    #[schemars(description = "Files to return for a name (default 20, max 100)")]
+    limit: Option<usize>,
+}
+
+#[derive(Deserialize, schemars::JsonSchema)]
+struct AskFileParams {
+    #[schemars(description = "Absolute path of a file within an indexed container, e.g. one rememex_search found.")]
+    path: String,
+    #[schemars(description = "What to find out from the file, in any language.")]
+    question: String,
+}
+
 #[derive(Deserialize, schemars::JsonSchema)]
 struct AnnotateParams {
     #[schemars(description = "Absolute path to the file to annotate.")]
@@ -111,6 +187,23 @@ struct AnnotationsParams {
     container: Option<String>,
 }
 
+#[derive(Deserialize, schemars::JsonSchema)]
+struct EvalParams {
+    #[schemars(description = "Path to a JSON array or JSON Lines file of {\"query\": ..., \"expected\": path or [paths]} cases. Paths may be relative to the indexed folder, e.g. \"src/config.rs\".")]
+    cases_file: Option<String>,
+    #[schemars(description = "Inline cases, used when cases_file is not given")]
+    cases: Option<Vec<EvalCaseParam>>,
+    container: Option<String>,
+    #[schemars(description = "Cutoff for recall@k and MRR (default 10, max 50)")]
+    k: Option<usize>,
+}
+
+#[derive(Deserialize, schemars::JsonSchema)]
+struct EvalCaseParam {
+    query: String,
+    expected: Vec<String>,
+}
+
 #[derive(Deserialize, schemars::JsonSchema)]
 struct DeleteAnnotationParams {
     #[schemars(description = "The annotation ID to delete (e.g. 'ann_...'). Get IDs from rememex_annotations.")]
@@ -118,6 +211,41 @@ struct DeleteAnnotationParams {
     container: Option<String>,
 }
 
+#[derive(Deserialize)]
+struct EditorSearchParams {
+    query: String,
+    workspace: Option<String>,
+    container: Option<String>,
+    top_k: Option<usize>,
+    file_extensions: Option<Vec<String>>,
+    language: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct EditorLocateParams {
+    path: String,
+    snippet: String,
+}
+
+#[derive(Deserialize)]
+struct EditorAnnotateParams {
+    path: String,
+    note: String,
+    container: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct EditorIndexParams {
+    path: String,
+    /// The Rememex executable, when it isn't next to this one.
+    app: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct EditorStatusParams {
+    workspace: Option<String>,
+}
+
 fn is_path_within_container(file_path: &Path, config: &Config, container_name: &str) -> bool {
     let canonical = match std::fs::canonicalize(file_path) {
         Ok(p) => p,
@@ -135,6 +263,38 @@ fn is_path_within_container(file_path: &Path, config: &Config, container_name: &
     false
 }
 
+async fn file_paths(table: &lancedb::Table, filter: Option<String>) -> Result<BTreeSet<String>, McpError> {
+    use arrow_array::StringArray;
+    use futures::TryStreamExt;
+    use lancedb::query::{ExecutableQuery, QueryBase};
+
+    let mut query = table.query().select(lancedb::query::Select::Columns(vec!["path".to_string()]));
+    if let Some(filter) = filter {
+        query = query.only_if(filter);
+    }
+
+    let results = query
+        .execute()
+        .await
+        .map_err(|e| McpError::internal_error(e.to_string(), None))?
+        .try_collect::<Vec<_>>()
+        .await
+        .map_err(|e| McpError::internal_error(e.to_string(), None))?;
+
+    let mut paths = BTreeSet::new();
+    for batch in results {
+        if let Some(path_array) = batch
+            .column_by_name("path")
+            .and_then(|c| c.as_any().downcast_ref::<StringArray>())
+        {
+            for i in 0..batch.num_rows() {
+                paths.insert(path_array.value(i).to_string());
+            }
+        }
+    }
+    Ok(paths)
+}
+
 fn parse_duration(s: &str) -> Option<u64> {
     let s = s.trim().to_lowercase();
     let (num_str, multiplier) = if let Some(n) = s.strip_suffix('s') {
@@ -153,6 +313,72 @@ fn parse_duration(s: &str) -> Option<u64> {
     num_str.trim().parse::<u64>().ok().map(|n| n * multiplier)
 }
 
+/// One result per file for `snippet_strategy: "merged"`, at its best
+/// chunk's rank and score: the file's matching chunks, best first while
+/// they fit in `context_bytes`, stitched together in the order they sit in
+/// the file. Annotations and summaries stay results of their own.
+fn merge_chunks(
+    scored: Vec<indexer::pipeline::ScoredResult>,
+    top_k: usize,
+    context_bytes: usize,
+) -> Vec<indexer::pipeline::ScoredResult> {
+    let stands_alone = |item: &indexer::pipeline::ScoredResult| {
+        item.snippet.starts_with("[annotation] ") || item.snippet.starts_with("[summary] ")
+    };
+    let mut files: Vec<(indexer::pipeline::ScoredResult, Vec<String>)> = Vec::new();
+    for item in scored {
+        if stands_alone(&item) {
+            files.push((item, Vec::new()));
+            continue;
+        }
+        let body = editor::snippet_body(&item.snippet);
+        match files.iter_mut().find(|(first, _)| first.path == item.path && !stands_alone(first)) {
+            Some((_, bodies)) => bodies.push(body),
+            None => files.push((item, vec![body])),
+        }
+    }
+    files.truncate(top_k);
+
+    files
+        .into_iter()
+        .map(|(mut first, bodies)| {
+            if bodies.is_empty() {
+                return first;
+            }
+            let mut used = 0;
+            let mut chosen: Vec<&str> = Vec::new();
+            for body in &bodies {
+                if chosen.is_empty() || used + body.len() <= context_bytes {
+                    used += body.len();
+                    chosen.push(body);
+                }
+            }
+            // Where the file can't be read or a chunk isn't found in it, rank
+            // order stands in for file order.
+            if let Ok(text) = std::fs::read_to_string(&first.path) {
+                chosen.sort_by_cached_key(|body| editor::locate(&text, body).map_or(usize::MAX, |loc| loc.line));
+            }
+            first.snippet = indexer::snippet::stitch(&chosen);
+            first
+        })
+        .collect()
+}
+
+/// Unix seconds for an `as_of` value. A bare date means the end of that day
+/// (UTC), so "2024-05-01" includes edits made during it.
+fn parse_as_of(s: &str) -> Option<i64> {
+    let s = s.trim();
+    let now = chrono::Utc::now();
+    if let Some(ago) = parse_duration(s) {
+        return Some(now.timestamp() - ago as i64);
+    }
+    if let Ok(t) = chrono::DateTime::parse_from_rfc3339(s) {
+        return Some(t.timestamp());
+    }
+    let date = chrono::NaiveDate::parse_from_str(s, "%Y-%m-%d").ok()?;
+    Some(date.and_hms_opt(23, 59, 59)?.and_utc().timestamp())
+}
+
 #[tool_router]
 impl RememexServer {
     fn new(state: Arc<AppState>) -> Self {
@@ -162,31 +388,94 @@ impl RememexServer {
         }
     }
 
-    #[tool(
-        description = "Search indexed files using semantic + keyword hybrid search. Returns ranked results with file paths, relevant snippets, and relevance scores."
-    )]
-    async fn rememex_search(
-        &self,
-        Parameters(SearchParams { query, container, top_k, file_extensions, path_prefix, context_bytes, min_score }): Parameters<SearchParams>,
-    ) -> Result<CallToolResult, McpError> {
-        debug!("rememex_search: query=\"{}\", container={:?}, top_k={:?}", query, container, top_k);
-        let container =
-            container.unwrap_or_else(|| self.state.config.active_container.clone());
-        let table_name = get_table_name(&container);
+    /// The connection holding a container's index: its own `db_uri` when it
+    /// has one, the app's database otherwise. Opened once per URI.
+    async fn db_for(&self, container: &str) -> Result<lancedb::Connection, McpError> {
+        self.ensure_unlocked(container)?;
+        let Some(info) = self.state.config.containers.get(container).filter(|info| info.db_uri.is_some()) else {
+            return Ok(self.state.db.clone());
+        };
+        let uri = info.db_uri.clone().unwrap_or_default();
+        let mut shared = self.state.shared_dbs.lock().await;
+        if let Some(db) = shared.get(&uri) {
+            return Ok(db.clone());
+        }
+        let options = resolve_storage_options(&info.storage_options)
+            .map_err(|e| McpError::internal_error(e, None))?;
+        let db = indexer::db::connect_shared(&uri, options)
+            .await
+            .map_err(|e| McpError::internal_error(format!("Opening shared index {} failed: {}", uri, e), None))?;
+        shared.insert(uri, db.clone());
+        Ok(db)
+    }
 
-        let top_k = top_k.unwrap_or(10).clamp(1, 50);
-        let context_bytes = context_bytes.unwrap_or(1500).clamp(100, 10000);
+    /// A locked container is only open to agents while it's unlocked in the
+    /// app; nothing here asks for Windows Hello or Touch ID itself.
+    fn ensure_unlocked(&self, container: &str) -> Result<(), McpError> {
+        os_auth::ensure_unlocked(&self.state.config, container).map_err(|e| {
+            McpError::invalid_request(format!("{}. ask the user to unlock it in the Rememex app, then try again.", e), None)
+        })
+    }
 
-        let table_check = self.state.db.table_names().execute().await
-            .map_err(|e| McpError::internal_error(e.to_string(), None))?;
-        if !table_check.iter().any(|t| t == &table_name) {
-            return Ok(CallToolResult::success(vec![Content::text(
-                format!("no index found for container '{}'. open Rememex and index some folders first.", container),
-            )]));
+    /// A file read from disk is as locked as the containers holding it.
+    fn ensure_path_unlocked(&self, path: &Path) -> Result<(), McpError> {
+        let config = &self.state.config;
+        config
+            .containers
+            .keys()
+            .filter(|name| is_path_within_container(path, config, name))
+            .try_for_each(|name| self.ensure_unlocked(name))
+    }
+
+    /// The container's redaction, when it's turned on.
+    fn redactor(&self, container: &str) -> Option<Redactor> {
+        self.state.config.containers.get(container).and_then(|info| Redactor::new(&info.redaction))
+    }
+
+    /// Redaction for a file read from disk: that of the first container
+    /// holding it that has it on.
+    fn redactor_for_path(&self, path: &Path) -> Option<Redactor> {
+        let config = &self.state.config;
+        config
+            .containers
+            .iter()
+            .filter(|(name, _)| is_path_within_container(path, config, name))
+            .find_map(|(_, info)| Redactor::new(&info.redaction))
+    }
+
+    fn ensure_writable(&self, container: &str) -> Result<(), McpError> {
+        if !self.state.config.can_write(container) {
+            return Err(McpError::invalid_request(
+                "this container is read-only here (read-only mode or a reader of a shared index), annotations can't be changed",
+                None,
+            ));
         }
+        let uri = self.state.config.containers.get(container).and_then(|info| info.db_uri.as_deref());
+        if let Some(host) = uri.and_then(shared_index::other_writer) {
+            return Err(McpError::invalid_request(format!("{} is writing to this shared index, try again later", host), None));
+        }
+        Ok(())
+    }
 
+    /// The full search pipeline (router, HyDE, hybrid retrieval, annotations,
+    /// reranker, MMR) as configured for `container`, shared by search and eval.
+    #[allow(clippy::too_many_arguments)]
+    async fn ranked_search(
+        &self,
+        db: &lancedb::Connection,
+        container: &str,
+        query: &str,
+        top_k: usize,
+        path_prefix: Option<&str>,
+        query_filters: Option<&indexer::QueryFilters>,
+        file_extensions: Option<&[String]>,
+        language: Option<&str>,
+        scope: indexer::SearchScope,
+    ) -> Result<Vec<indexer::pipeline::ScoredResult>, McpError> {
+        let table_name = &get_table_name(container);
+        let retrieval = self.state.config.retrieval_for(container);
         let query_weights = if self.state.config.query_router_enabled {
-            indexer::query_router::classify_and_weigh(&query)
+            indexer::query_router::classify_and_weigh(query)
         } else {
             debug!("mcp search: query_router disabled, using default weights");
             indexer::query_router::QueryWeights { vector_weight: 1.0, fts_weight: 1.0, use_hyde: false }
@@ -194,13 +483,13 @@ impl RememexServer {
 
         debug!("mcp search: features: router={}, mmr={} (λ={:.2}), hyde={}",
             self.state.config.query_router_enabled,
-            self.state.config.mmr_enabled,
-            self.state.config.mmr_lambda,
-            self.state.config.hyde.as_ref().is_some_and(|h| h.enabled));
+            retrieval.mmr_enabled,
+            retrieval.mmr_lambda,
+            retrieval.hyde.as_ref().is_some_and(|h| h.enabled));
 
         let hyde_doc = indexer::hyde::maybe_generate(
-            self.state.config.hyde.as_ref(),
-            &query,
+            retrieval.hyde.as_ref(),
+            query,
             query_weights.use_hyde,
         ).await;
 
@@ -214,24 +503,29 @@ impl RememexServer {
                 vecs.into_iter().next()
                     .ok_or_else(|| McpError::internal_error("HyDE embedding empty".to_string(), None))?
             } else {
-                guard.embed_query(&query).await
+                guard.embed_query(query).await
                     .map_err(|e| McpError::internal_error(e.to_string(), None))?
             }
         };
 
         let search_limit = top_k * 3;
 
-        let pp_ref = path_prefix.as_deref();
-        let fe_ref = file_extensions.as_deref();
+        let expansion = indexer::QueryExpansion::new(self.state.config.fts_stemming, &self.state.config.synonyms);
 
         let (mut merged, used_hybrid) = indexer::search_pipeline(
-            &self.state.db, &table_name, &query, &query_vector, search_limit, pp_ref, fe_ref,
-            query_weights.vector_weight, query_weights.fts_weight,
+            db, table_name, query, &query_vector, search_limit, path_prefix, query_filters, file_extensions, language, &expansion,
+            query_weights.vector_weight, query_weights.fts_weight, scope,
         )
         .await
         .map_err(|e| McpError::internal_error(e.to_string(), None))?;
 
-        if let Ok(ann_results) = annotations::search_annotations(&self.state.db, &table_name, &query_vector, 10).await {
+        if let Ok(mut ann_results) = annotations::search_annotations(db, table_name, &query_vector, 10).await {
+            let summaries = indexer::summaries::search_summaries(db, table_name, &query_vector, indexer::summaries::SEARCH_HITS)
+                .await
+                .unwrap_or_default();
+            ann_results.extend(summaries);
+            ann_results.sort_by(|a, b| a.2.partial_cmp(&b.2).unwrap_or(std::cmp::Ordering::Equal));
+            ann_results.retain(|(path, _, _)| query_filters.is_none_or(|f| f.matches_path(path)));
             if used_hybrid {
                 for (rank, (path, note, _dist)) in ann_results.into_iter().enumerate() {
                     let rrf_score = 1.0 / (60.0 + rank as f32 + 1.0);
@@ -245,18 +539,24 @@ impl RememexServer {
                 merged.sort_by(|a, b| a.2.partial_cmp(&b.2).unwrap_or(std::cmp::Ordering::Equal));
             }
         }
+        indexer::identity::dedupe_results(&mut merged, self.state.config.indexing.display_path);
 
         let rerank_input: Vec<(String, String, f32)> =
             merged.into_iter().take(top_k * 2).collect();
 
+        // The reranker is always loaded here; only a container's own
+        // setting turns it off.
+        let rerank = self.state.config.containers.get(container).and_then(|info| info.search.use_reranker).unwrap_or(true);
         let (final_results, used_reranker) = {
-            let reranker = {
+            let reranker = if rerank {
                 let mut guard = self.state.reranker.lock().await;
                 guard.take()
+            } else {
+                None
             };
             if let Some(reranker) = reranker {
                 let (reranker_back, results, used) =
-                    indexer::safe_rerank(reranker, query.clone(), rerank_input.clone()).await;
+                    indexer::safe_rerank(reranker, query.to_string(), rerank_input.clone()).await;
                 {
                     let mut guard = self.state.reranker.lock().await;
                     *guard = reranker_back;
@@ -272,35 +572,158 @@ impl RememexServer {
         };
 
         let scored = indexer::pipeline::score_results(final_results, used_reranker, used_hybrid, top_k * 2);
-        let mut scored = if self.state.config.mmr_enabled {
-            indexer::pipeline::mmr_select(scored, top_k, self.state.config.mmr_lambda)
+        Ok(if retrieval.mmr_enabled {
+            indexer::pipeline::mmr_select(scored, top_k, retrieval.mmr_lambda)
         } else {
             scored.into_iter().take(top_k).collect()
+        })
+    }
+
+    /// Runs every case through `ranked_search` and scores where the expected
+    /// files land. Several chunks of one file count as one result.
+    async fn evaluate(&self, container: &str, cases: &[EvalCase], k: usize) -> Result<EvalReport, McpError> {
+        let db = self.db_for(container).await?;
+        let mut results = Vec::with_capacity(cases.len());
+        for case in cases {
+            let scored = self.ranked_search(&db, container, &case.query, k * 3, None, None, None, None, Default::default()).await?;
+            let mut paths: Vec<String> = Vec::new();
+            for item in scored {
+                if !paths.contains(&item.path) {
+                    paths.push(item.path);
+                }
+            }
+            results.push(eval::score_case(case, &paths, k));
+        }
+        let report = EvalReport::new(k, results);
+        info!("eval: {} cases, recall@{} {:.3}, MRR {:.3}", report.cases, k, report.recall_at_k, report.mrr);
+        Ok(report)
+    }
+
+    #[tool(
+        description = "Search indexed files using semantic + keyword hybrid search. Returns ranked results with file paths, relevant snippets, and relevance scores."
+    )]
+    async fn rememex_search(
+        &self,
+        Parameters(SearchParams { query, container, top_k, file_extensions, path_prefix, language, size, lines, context_bytes, min_score, as_of, include_deleted, snippet_strategy, max_tokens }): Parameters<SearchParams>,
+    ) -> Result<CallToolResult, McpError> {
+        debug!("rememex_search: query=\"{}\", container={:?}, top_k={:?}", query, container, top_k);
+        let container =
+            container.unwrap_or_else(|| self.state.config.active_container.clone());
+        let table_name = get_table_name(&container);
+        let db = self.db_for(&container).await?;
+
+        let top_k = top_k.unwrap_or(10).clamp(1, 50);
+        let as_of = match as_of.as_deref().filter(|s| !s.trim().is_empty()) {
+            Some(s) => Some(parse_as_of(s).ok_or_else(|| {
+                McpError::invalid_params(format!("invalid as_of '{}'. use a date like '2024-05-01' or a time ago like '30d'", s), None)
+            })?),
+            None => None,
         };
+        let scope = indexer::SearchScope { as_of, include_deleted: include_deleted.unwrap_or(false) };
+        let mut query_filters = indexer::QueryFilters::default();
+        for (name, column, expr) in [("size", indexer::db::SIZE_COLUMN, &size), ("lines", indexer::db::LINES_COLUMN, &lines)] {
+            if let Some(expr) = expr.as_deref().filter(|s| !s.trim().is_empty()) {
+                let comparison = indexer::query_filters::Comparison::parse(column, expr).ok_or_else(|| {
+                    McpError::invalid_params(format!("invalid {} '{}'. use a comparison like '>10mb' or '<200'", name, expr), None)
+                })?;
+                query_filters.comparisons.push(comparison);
+            }
+        }
+        let context_bytes = context_bytes.unwrap_or(1500).clamp(100, 10000);
+        let strategy = snippet_strategy.unwrap_or_default();
+
+        let table_check = db.table_names().execute().await
+            .map_err(|e| McpError::internal_error(e.to_string(), None))?;
+        if !table_check.iter().any(|t| t == &table_name) {
+            return Ok(CallToolResult::success(vec![Content::text(
+                format!("no index found for container '{}'. open Rememex and index some folders first.", container),
+            )]));
+        }
+
+        // Merging needs more than one chunk per file to work with.
+        let pool = if strategy == SnippetStrategy::Merged { top_k * 3 } else { top_k };
+        let mut scored = self
+            .ranked_search(&db, &container, &query, pool, path_prefix.as_deref(), Some(&query_filters), file_extensions.as_deref(), language.as_deref(), scope)
+            .await?;
         scored.retain(|item| item.score >= min_score.unwrap_or(0.0));
+        if strategy == SnippetStrategy::Merged {
+            scored = merge_chunks(scored, top_k, context_bytes);
+        }
+        // Before cutting snippets down, so a secret isn't cut in half and
+        // missed.
+        if let Some(redactor) = self.redactor(&container) {
+            for item in &mut scored {
+                item.snippet = redactor.redact(&item.snippet);
+            }
+        }
 
+        // Over budget, keep the part around the best match rather than the
+        // start, then cut to the byte limit for text that isn't ASCII.
+        let expansion = indexer::QueryExpansion::new(self.state.config.fts_stemming, &self.state.config.synonyms);
+        let terms = indexer::snippet::query_terms(&query, &expansion, language.as_deref());
         for item in &mut scored {
             if item.snippet.len() > context_bytes {
-                let mut end = context_bytes;
-                while end > 0 && !item.snippet.is_char_boundary(end) {
+                let body = indexer::snippet::body(&item.snippet);
+                let header = &item.snippet[..item.snippet.len() - body.len()];
+                let excerpt = indexer::snippet::excerpt(body, &terms, context_bytes.saturating_sub(header.len()).max(100));
+                let mut snippet = format!("{}{}", header, excerpt);
+                let mut end = context_bytes.min(snippet.len());
+                while end > 0 && !snippet.is_char_boundary(end) {
                     end -= 1;
                 }
-                item.snippet = item.snippet[..end].to_string();
+                snippet.truncate(end);
+                item.snippet = snippet;
             }
         }
 
-        let json = serde_json::to_string_pretty(&scored)
+        let mut value = serde_json::to_value(&scored)
+            .map_err(|e| McpError::internal_error(e.to_string(), None))?;
+        if scope.include_deleted {
+            for item in value.as_array_mut().into_iter().flatten() {
+                let gone = item["path"].as_str().is_some_and(|p| !Path::new(p).exists());
+                if let (true, Some(obj)) = (gone, item.as_object_mut()) {
+                    obj.insert("deleted".to_string(), serde_json::Value::Bool(true));
+                }
+            }
+        }
+        if let (Some(max_tokens), serde_json::Value::Array(items)) = (max_tokens, &mut value) {
+            *items = tokens::fit_items(std::mem::take(items), "snippet", max_tokens);
+        }
+        let json = serde_json::to_string_pretty(&value)
             .map_err(|e| McpError::internal_error(e.to_string(), None))?;
 
         Ok(CallToolResult::success(vec![Content::text(json)]))
     }
 
+    #[tool(
+        description = "Measure retrieval quality: run (query, expected path) cases through the current search pipeline and report recall@k and MRR, plus the rank of each case. Use it to compare settings like chunk size, MMR, reranker or HyDE."
+    )]
+    async fn rememex_eval(
+        &self,
+        Parameters(EvalParams { cases_file, cases, container, k }): Parameters<EvalParams>,
+    ) -> Result<CallToolResult, McpError> {
+        debug!("rememex_eval: cases_file={:?}, container={:?}, k={:?}", cases_file, container, k);
+        let container = container.unwrap_or_else(|| self.state.config.active_container.clone());
+        let cases = match (cases_file, cases) {
+            (Some(file), _) => eval::load_cases(Path::new(&file)).map_err(|e| McpError::invalid_params(e, None))?,
+            (None, Some(cases)) if !cases.is_empty() => cases
+                .into_iter()
+                .map(|c| EvalCase { query: c.query, expected: c.expected })
+                .collect(),
+            _ => return Err(McpError::invalid_params("Give cases_file or cases".to_string(), None)),
+        };
+        let report = self.evaluate(&container, &cases, k.unwrap_or(10).clamp(1, 50)).await?;
+        let json = serde_json::to_string_pretty(&report)
+            .map_err(|e| McpError::internal_error(e.to_string(), None))?;
+        Ok(CallToolResult::success(vec![Content::text(json)]))
+    }
+
     #[tool(
         description = "Read file content by path. Supports optional line range. The file must be within an indexed container."
     )]
     async fn rememex_read_file(
         &self,
-        Parameters(ReadFileParams { path, start_line, end_line }): Parameters<ReadFileParams>,
+        Parameters(ReadFileParams { path, start_line, end_line, max_tokens }): Parameters<ReadFileParams>,
     ) -> Result<CallToolResult, McpError> {
         debug!("rememex_read_file: path={}, lines={:?}-{:?}", path, start_line, end_line);
         let file_path = PathBuf::from(&path);
@@ -317,6 +740,7 @@ impl RememexServer {
                 "access denied: file is not within any indexed container path.",
             )]));
         }
+        self.ensure_path_unlocked(&file_path)?;
 
         if !file_path.is_file() {
             return Ok(CallToolResult::success(vec![Content::text(
@@ -324,8 +748,36 @@ impl RememexServer {
             )]));
         }
 
-        let content = std::fs::read_to_string(&file_path)
+        let size = std::fs::metadata(&file_path).map(|m| m.len()).unwrap_or(0);
+        if size > MAX_READ_BYTES {
+            return Ok(CallToolResult::success(vec![Content::text(format!(
+                "{} is {:.1} MB, over the {} MB read_file returns. use rememex_search to find the part you need.",
+                path,
+                size as f64 / (1024.0 * 1024.0),
+                MAX_READ_BYTES / (1024 * 1024),
+            ))]));
+        }
+        let bytes = std::fs::read(&file_path)
             .map_err(|e| McpError::internal_error(format!("failed to read file: {}", e), None))?;
+        let (content, encoding) = match indexer::encoding::decode(&bytes) {
+            indexer::encoding::Decoded::Text { text, encoding } => (text, encoding),
+            indexer::encoding::Decoded::Binary => {
+                let preview = &bytes[..bytes.len().min(BINARY_PREVIEW_BYTES)];
+                return Ok(CallToolResult::success(vec![Content::text(format!(
+                    "{} is a binary file ({} bytes). first {} bytes:\n{}",
+                    path,
+                    size,
+                    preview.len(),
+                    indexer::encoding::hexdump(preview),
+                ))]));
+            }
+        };
+        let content = match self.redactor_for_path(&file_path) {
+            Some(redactor) => redactor.redact(&content),
+            None => content,
+        };
+        // Said after the text, so line numbers in it stay as they are.
+        let decoded_note = encoding.map(|name| Content::text(format!("[decoded from {}]", name)));
 
         let output = match (start_line, end_line) {
             (Some(start), Some(end)) => {
@@ -365,7 +817,19 @@ impl RememexServer {
             (None, None) => content,
         };
 
-        Ok(CallToolResult::success(vec![Content::text(output)]))
+        if let Some(max_tokens) = max_tokens {
+            let cut = tokens::truncate(&output, max_tokens);
+            if cut.len() < output.len() {
+                // Lines shown whole; a line cut partway is read again.
+                let whole = cut.matches('\n').count() + usize::from(output[cut.len()..].starts_with('\n'));
+                let next = start_line.map_or(1, |s| (s as usize).max(1)) + whole;
+                let note = format!("[... truncated at {} tokens, read on with start_line={}]", max_tokens, next);
+                let text = Content::text(format!("{}\n{}", cut, note));
+                return Ok(CallToolResult::success(std::iter::once(text).chain(decoded_note).collect()));
+            }
+        }
+
+        Ok(CallToolResult::success(std::iter::once(Content::text(output)).chain(decoded_note).collect()))
     }
 
     #[tool(
@@ -373,17 +837,14 @@ impl RememexServer {
     )]
     async fn rememex_list_files(
         &self,
-        Parameters(ListFilesParams { container, path_prefix, extensions }): Parameters<ListFilesParams>,
+        Parameters(ListFilesParams { container, path_prefix, extensions, language }): Parameters<ListFilesParams>,
     ) -> Result<CallToolResult, McpError> {
-        use arrow_array::StringArray;
-        use futures::TryStreamExt;
-        use lancedb::query::{ExecutableQuery, QueryBase};
-
         let container =
             container.unwrap_or_else(|| self.state.config.active_container.clone());
         let table_name = get_table_name(&container);
+        let db = self.db_for(&container).await?;
 
-        let table = match self.state.db.open_table(&table_name).execute().await {
+        let table = match indexer::snapshot::open(&db, &table_name).await {
             Ok(t) => t,
             Err(_) => {
                 return Ok(CallToolResult::success(vec![Content::text(
@@ -392,31 +853,13 @@ impl RememexServer {
             }
         };
 
-        let mut query = table.query().select(lancedb::query::Select::Columns(vec!["path".to_string()]));
-
-        if let Some(filter) = indexer::build_filter_expr(path_prefix.as_deref(), extensions.as_deref()) {
-            query = query.only_if(filter);
+        if language.is_some() {
+            indexer::db::ensure_columns(&table).await
+                .map_err(|e| McpError::internal_error(e.to_string(), None))?;
         }
 
-        let results = query
-            .execute()
-            .await
-            .map_err(|e| McpError::internal_error(e.to_string(), None))?
-            .try_collect::<Vec<_>>()
-            .await
-            .map_err(|e| McpError::internal_error(e.to_string(), None))?;
-
-        let mut paths = std::collections::BTreeSet::new();
-        for batch in results {
-            if let Some(path_array) = batch
-                .column_by_name("path")
-                .and_then(|c| c.as_any().downcast_ref::<StringArray>())
-            {
-                for i in 0..batch.num_rows() {
-                    paths.insert(path_array.value(i).to_string());
-                }
-            }
-        }
+        let filter = indexer::build_filter_expr(path_prefix.as_deref(), None, extensions.as_deref(), language.as_deref());
+        let paths = file_paths(&table, filter).await?;
 
         let file_list: Vec<serde_json::Value> = paths
             .iter()
@@ -453,6 +896,7 @@ impl RememexServer {
         let container =
             container.unwrap_or_else(|| self.state.config.active_container.clone());
         let table_name = get_table_name(&container);
+        let db = self.db_for(&container).await?;
 
         let container_info = self.state.config.containers.get(&container);
         let indexed_paths: Vec<String> = container_info
@@ -462,7 +906,7 @@ impl RememexServer {
             .map(|info| info.description.clone())
             .unwrap_or_default();
 
-        let table = match self.state.db.open_table(&table_name).execute().await {
+        let table = match indexer::snapshot::open(&db, &table_name).await {
             Ok(t) => t,
             Err(_) => {
                 let json = serde_json::to_string_pretty(&serde_json::json!({
@@ -503,12 +947,20 @@ impl RememexServer {
             }
         }
 
+        let languages: serde_json::Map<String, serde_json::Value> = indexer::db::get_language_counts(&table)
+            .await
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(lang, files)| (lang, files.into()))
+            .collect();
+
         let json = serde_json::to_string_pretty(&serde_json::json!({
             "container": container,
             "description": description,
             "indexed_paths": indexed_paths,
             "total_files": unique_paths.len(),
             "total_chunks": total_chunks,
+            "languages": languages,
             "has_index": true,
         }))
         .map_err(|e| McpError::internal_error(e.to_string(), None))?;
@@ -530,6 +982,7 @@ impl RememexServer {
         let container =
             container.unwrap_or_else(|| self.state.config.active_container.clone());
         let table_name = get_table_name(&container);
+        let db = self.db_for(&container).await?;
         let show_diff = show_diff.unwrap_or(true);
 
         let seconds = parse_duration(&since).ok_or_else(|| {
@@ -542,7 +995,7 @@ impl RememexServer {
             .as_secs() as i64
             - seconds as i64;
 
-        let table = match self.state.db.open_table(&table_name).execute().await {
+        let table = match indexer::snapshot::open(&db, &table_name).await {
             Ok(t) => t,
             Err(_) => {
                 return Ok(CallToolResult::success(vec![Content::text(
@@ -592,6 +1045,7 @@ impl RememexServer {
             )]));
         }
 
+        let redactor = self.redactor(&container);
         let mut changed_files: Vec<serde_json::Value> = Vec::new();
         for (path, mtime) in &file_mtimes {
             let mut entry = serde_json::json!({
@@ -603,7 +1057,10 @@ impl RememexServer {
                 let file_path = PathBuf::from(path);
                 if file_path.is_file() {
                     if let Ok(content) = std::fs::read_to_string(&file_path) {
-                        let preview: String = content.lines().take(50).collect::<Vec<_>>().join("\n");
+                        let mut preview: String = content.lines().take(50).collect::<Vec<_>>().join("\n");
+                        if let Some(redactor) = &redactor {
+                            preview = redactor.redact(&preview);
+                        }
                         let total_lines = content.lines().count();
                         entry["preview"] = serde_json::json!(preview);
                         entry["total_lines"] = serde_json::json!(total_lines);
@@ -633,16 +1090,13 @@ impl RememexServer {
         &self,
         Parameters(RelatedParams { path, container, top_k }): Parameters<RelatedParams>,
     ) -> Result<CallToolResult, McpError> {
-        use arrow_array::{Float32Array, StringArray};
-        use futures::TryStreamExt;
-        use lancedb::query::{ExecutableQuery, QueryBase};
-
         let container =
             container.unwrap_or_else(|| self.state.config.active_container.clone());
         let table_name = get_table_name(&container);
+        let db = self.db_for(&container).await?;
         let top_k = top_k.unwrap_or(10).clamp(1, 30);
 
-        let table = match self.state.db.open_table(&table_name).execute().await {
+        let table = match indexer::snapshot::open(&db, &table_name).await {
             Ok(t) => t,
             Err(_) => {
                 return Ok(CallToolResult::success(vec![Content::text(
@@ -651,112 +1105,27 @@ impl RememexServer {
             }
         };
 
-        let safe_path = path.replace('\'', "''");
-        let chunks = table
-            .query()
-            .only_if(format!("path = '{}'", safe_path))
-            .execute()
+        let related = match indexer::search::find_related(&table, &path, top_k)
             .await
             .map_err(|e| McpError::internal_error(e.to_string(), None))?
-            .try_collect::<Vec<_>>()
-            .await
-            .map_err(|e| McpError::internal_error(e.to_string(), None))?;
-
-        let mut avg_vector: Option<Vec<f32>> = None;
-        let mut chunk_count = 0usize;
-
-        for batch in &chunks {
-            if let Some(vector_col) = batch.column_by_name("vector") {
-                use arrow_array::FixedSizeListArray;
-                if let Some(fsl) = vector_col.as_any().downcast_ref::<FixedSizeListArray>() {
-                    for i in 0..batch.num_rows() {
-                        let values = fsl.value(i);
-                        if let Some(float_arr) = values.as_any().downcast_ref::<Float32Array>() {
-                            let vec: Vec<f32> = (0..float_arr.len()).map(|j| float_arr.value(j)).collect();
-                            match &mut avg_vector {
-                                Some(avg) => {
-                                    for (k, v) in avg.iter_mut().enumerate() {
-                                        *v += vec[k];
-                                    }
-                                }
-                                None => avg_vector = Some(vec),
-                            }
-                            chunk_count += 1;
-                        }
-                    }
-                }
-            }
-        }
-
-        let query_vector = match avg_vector {
-            Some(mut avg) if chunk_count > 0 => {
-                for v in avg.iter_mut() {
-                    *v /= chunk_count as f32;
-                }
-                avg
-            }
-            _ => {
+        {
+            Some(related) => related,
+            None => {
                 return Ok(CallToolResult::success(vec![Content::text(
                     format!("file '{}' not found in index. make sure it's been indexed.", path),
                 )]));
             }
         };
 
-        let search_limit = (top_k + 1) * 3;
-        let vq = table
-            .vector_search(query_vector.as_slice())
-            .map_err(|e| McpError::internal_error(e.to_string(), None))?;
-        let results = vq
-            .distance_type(lancedb::DistanceType::Cosine)
-            .select(lancedb::query::Select::Columns(vec!["path".to_string(), "content".to_string()]))
-            .limit(search_limit)
-            .execute()
-            .await
-            .map_err(|e| McpError::internal_error(e.to_string(), None))?
-            .try_collect::<Vec<_>>()
-            .await
-            .map_err(|e| McpError::internal_error(e.to_string(), None))?;
-
-        let mut best_per_file: std::collections::HashMap<String, (String, f32)> = std::collections::HashMap::new();
-        for batch in results {
-            let path_array = batch
-                .column_by_name("path")
-                .and_then(|c| c.as_any().downcast_ref::<StringArray>());
-            let content_array = batch
-                .column_by_name("content")
-                .and_then(|c| c.as_any().downcast_ref::<StringArray>());
-            let dist_array = batch
-                .column_by_name("_distance")
-                .and_then(|c| c.as_any().downcast_ref::<Float32Array>());
-
-            if let (Some(paths), Some(contents), Some(dists)) = (path_array, content_array, dist_array) {
-                for i in 0..batch.num_rows() {
-                    let p = paths.value(i).to_string();
-                    if p == path {
-                        continue;
-                    }
-                    let dist = dists.value(i);
-                    match best_per_file.get(&p) {
-                        Some((_, existing_dist)) if *existing_dist <= dist => {}
-                        _ => {
-                            best_per_file.insert(p, (contents.value(i).to_string(), dist));
-                        }
-                    }
-                }
-            }
-        }
-
-        let mut related: Vec<(String, String, f32)> = best_per_file
-            .into_iter()
-            .map(|(p, (snippet, dist))| (p, snippet, dist))
-            .collect();
-        related.sort_by(|a, b| a.2.partial_cmp(&b.2).unwrap_or(std::cmp::Ordering::Equal));
-        related.truncate(top_k);
-
+        let redactor = self.redactor(&container);
         let items: Vec<serde_json::Value> = related
             .into_iter()
             .map(|(p, snippet, dist)| {
                 let similarity = ((1.0 - dist).clamp(0.0, 1.0) * 100.0) as u32;
+                let snippet = match &redactor {
+                    Some(redactor) => redactor.redact(&snippet),
+                    None => snippet,
+                };
                 serde_json::json!({
                     "path": p,
                     "snippet": snippet,
@@ -775,6 +1144,183 @@ impl RememexServer {
         Ok(CallToolResult::success(vec![Content::text(json)]))
     }
 
+    #[tool(
+        description = "Map of what a container is about: its files clustered by meaning into topics, each labelled with its most telling words. Lists every topic with a few files, or all files of one topic. Use it to get your bearings in an unfamiliar index before searching."
+    )]
+    async fn rememex_topics(
+        &self,
+        Parameters(TopicsParams { container, topic, limit, refresh }): Parameters<TopicsParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let container =
+            container.unwrap_or_else(|| self.state.config.active_container.clone());
+        let table_name = get_table_name(&container);
+        let db = self.db_for(&container).await?;
+        let excluded = self
+            .state
+            .config
+            .containers
+            .get(&container)
+            .map(|info| info.excluded_paths.clone())
+            .unwrap_or_default();
+
+        let map = match indexer::topics::load(&table_name) {
+            Some(map) if !refresh.unwrap_or(false) => map,
+            _ => match indexer::topics::build(&db, &table_name).await {
+                Ok(map) => map,
+                Err(e) => {
+                    return Ok(CallToolResult::success(vec![Content::text(e.to_string())]));
+                }
+            },
+        };
+        let stale = indexer::topics::is_stale(&db, &table_name, &map).await;
+        let default_limit = if topic.is_some() { 50 } else { 5 };
+        let limit = limit.unwrap_or(default_limit).clamp(1, 200);
+
+        let topics: Vec<serde_json::Value> = map
+            .topics
+            .iter()
+            .filter(|t| topic.is_none_or(|id| t.id == id))
+            .map(|t| {
+                let files: Vec<&String> = t.files.iter().filter(|p| !is_path_excluded(&excluded, p)).collect();
+                serde_json::json!({
+                    "id": t.id,
+                    "terms": t.terms,
+                    "total_files": files.len(),
+                    "files": files.into_iter().take(limit).collect::<Vec<_>>(),
+                })
+            })
+            .collect();
+        if let (Some(id), true) = (topic, topics.is_empty()) {
+            return Ok(CallToolResult::success(vec![Content::text(
+                format!("topic {} not found. list the topics without `topic` first.", id),
+            )]));
+        }
+
+        let json = serde_json::to_string_pretty(&serde_json::json!({
+            "container": container,
+            "built_at": map.built_at,
+            "stale": stale,
+            "topics": topics,
+        }))
+        .map_err(|e| McpError::internal_error(e.to_string(), None))?;
+
+        Ok(CallToolResult::success(vec![Content::text(json)]))
+    }
+
+    #[tool(
+        description = "Follow names across files. With a name (person, project or tool), lists the files mentioning it and other entities those files mention too. With a path, lists the entities that file mentions and how many files mention each. Entities come from prose files (notes, docs, mail), not source code."
+    )]
+    async fn rememex_entity(
+        &self,
+        Parameters(EntityParams { name, path, container, limit }): Parameters<EntityParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let container =
+            container.unwrap_or_else(|| self.state.config.active_container.clone());
+        let table_name = get_table_name(&container);
+        let db = self.db_for(&container).await?;
+        let excluded = self
+            .state
+            .config
+            .containers
+            .get(&container)
+            .map(|info| info.excluded_paths.clone())
+            .unwrap_or_default();
+
+        let json = match (name, path) {
+            (Some(name), _) => {
+                let limit = limit.unwrap_or(20).clamp(1, 100);
+                let found = indexer::entities::files_with(&db, &table_name, &name, usize::MAX)
+                    .await
+                    .map_err(|e| McpError::internal_error(e.to_string(), None))?;
+                let Some(mut found) = found else {
+                    return Ok(CallToolResult::success(vec![Content::text(
+                        format!("no indexed file mentions '{}'.", name),
+                    )]));
+                };
+                found.files.retain(|f| !is_path_excluded(&excluded, &f.path));
+                let total_files = found.files.len();
+                found.files.truncate(limit);
+                serde_json::json!({
+                    "name": found.name,
+                    "kind": found.kind,
+                    "total_files": total_files,
+                    "files": found.files,
+                    "related": found.related,
+                })
+            }
+            (None, Some(path)) => {
+                let entities = indexer::entities::in_file(&db, &table_name, &path)
+                    .await
+                    .map_err(|e| McpError::internal_error(e.to_string(), None))?;
+                serde_json::json!({
+                    "path": path,
+                    "entities": entities,
+                })
+            }
+            (None, None) => {
+                return Err(McpError::invalid_params("give a name or a path", None));
+            }
+        };
+
+        let json = serde_json::to_string_pretty(&json)
+            .map_err(|e| McpError::internal_error(e.to_string(), None))?;
+        Ok(CallToolResult::success(vec![Content::text(json)]))
+    }
+
+    #[tool(
+        description = "Ask a question about one file. The file is read and chunked fresh, the passages closest to the question picked, and the configured chat endpoint (summaries or HyDE) answers from them with quotes citing [n]. Returns the answer and the passages with line numbers or pages; without a chat endpoint, only the passages."
+    )]
+    async fn rememex_ask_file(
+        &self,
+        Parameters(AskFileParams { path, question }): Parameters<AskFileParams>,
+    ) -> Result<CallToolResult, McpError> {
+        debug!("rememex_ask_file: path={}, question={}", path, question);
+        let config = &self.state.config;
+        let container = config.containers.iter().find(|(name, info)| {
+            is_path_within_container(Path::new(&path), config, name) && !is_path_excluded(&info.excluded_paths, &path)
+        });
+        let Some((container, _)) = container else {
+            return Ok(CallToolResult::success(vec![Content::text(
+                "access denied: file is not within any indexed container path.",
+            )]));
+        };
+        self.ensure_path_unlocked(Path::new(&path))?;
+        let indexing = config.indexing_for(container);
+        let retrieval = config.retrieval_for(container);
+        let endpoint = indexer::ask::ChatEndpoint::pick(&config.indexing.summaries, retrieval.hyde.as_ref());
+
+        let mut passages = {
+            let guard = self.state.provider.lock().await;
+            match indexer::ask::passages(guard.as_ref(), Path::new(&path), &question, &indexing).await {
+                Ok(passages) => passages,
+                Err(e) => return Ok(CallToolResult::success(vec![Content::text(e.to_string())])),
+            }
+        };
+        // The answer only sees what the agent may see.
+        if let Some(redactor) = self.redactor_for_path(Path::new(&path)) {
+            for passage in &mut passages {
+                passage.text = redactor.redact(&passage.text);
+            }
+        }
+        let (answer, note) = match endpoint {
+            _ if passages.is_empty() => (None, Some("the file has no text to answer from.".to_string())),
+            None => (None, Some("no chat endpoint set up (summaries or hyde in config.json); read the passages instead.".to_string())),
+            Some(endpoint) => match indexer::ask::answer(&endpoint, &path, &question, &passages).await {
+                Ok(answer) => (Some(answer), None),
+                Err(e) => (None, Some(e.to_string())),
+            },
+        };
+
+        let json = serde_json::to_string_pretty(&serde_json::json!({
+            "path": path,
+            "answer": answer,
+            "note": note,
+            "passages": passages,
+        }))
+        .map_err(|e| McpError::internal_error(e.to_string(), None))?;
+        Ok(CallToolResult::success(vec![Content::text(json)]))
+    }
+
     #[tool(
         description = "List all search containers (collections of indexed folders) with their names, descriptions, and indexed paths."
     )]
@@ -791,7 +1337,8 @@ impl RememexServer {
                     "name": name,
                     "description": info.description,
                     "indexed_paths": info.indexed_paths,
-                    "active": name == &self.state.config.active_container
+                    "active": name == &self.state.config.active_container,
+                    "locked": info.locked && !os_auth::is_unlocked(name)
                 })
             })
             .collect();
@@ -803,20 +1350,48 @@ impl RememexServer {
     }
 
     #[tool(
-        description = "Add a searchable annotation (note) to a file. The note is embedded and will appear in future search results. Use this to leave context, warnings, or explanations for yourself or other agents."
+        description = "Check whether live indexing is working: the folders the Rememex app is watching, how many file events it has seen and indexed, when the last one arrived, how many files are queued, and whether the app is still running (stale = no heartbeat for 3+ minutes)."
     )]
-    async fn rememex_annotate(
+    async fn rememex_watch_status(
         &self,
-        Parameters(AnnotateParams { path, note, container }): Parameters<AnnotateParams>,
     ) -> Result<CallToolResult, McpError> {
-        let container_name = container
-            .as_deref()
-            .unwrap_or(&self.state.config.active_container);
+        let Some(result) = self.watcher_status()? else {
+            return Ok(CallToolResult::success(vec![Content::text(
+                "no watcher status found. the rememex app hasn't run since live indexing status was added.",
+            )]));
+        };
+
+        let json = serde_json::to_string_pretty(&result)
+            .map_err(|e| McpError::internal_error(e.to_string(), None))?;
+        Ok(CallToolResult::success(vec![Content::text(json)]))
+    }
+
+    fn watcher_status(&self) -> Result<Option<serde_json::Value>, McpError> {
+        let path = self.state.app_data.join(WATCHER_STATUS_FILE);
+        let Ok(raw) = std::fs::read_to_string(&path) else {
+            return Ok(None);
+        };
+        let status: WatcherStatus = serde_json::from_str(&raw)
+            .map_err(|e| McpError::internal_error(format!("invalid watcher status: {}", e), None))?;
+
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs() as i64;
+        let mut result = serde_json::to_value(&status)
+            .map_err(|e| McpError::internal_error(e.to_string(), None))?;
+        result["stale"] = serde_json::json!(status.running && now - status.updated_at > 180);
+        Ok(Some(result))
+    }
+
+    /// The caller checks the container is writable.
+    async fn add_note(&self, container_name: &str, path: &str, note: &str, source: &str) -> Result<serde_json::Value, McpError> {
         let table_name = get_table_name(container_name);
+        let db = self.db_for(container_name).await?;
 
         let vector = {
             let provider = self.state.provider.lock().await;
-            provider.embed_passages(vec![note.clone()]).await
+            provider.embed_passages(vec![note.to_string()]).await
                 .map_err(|e| McpError::internal_error(format!("Embedding failed: {}", e), None))?
                 .into_iter()
                 .next()
@@ -825,7 +1400,7 @@ impl RememexServer {
 
         let dim = vector.len();
         let ann_table_name = format!("{}_annotations", table_name);
-        let ann_table = if let Ok(t) = self.state.db.open_table(&ann_table_name).execute().await {
+        let ann_table = if let Ok(t) = db.open_table(&ann_table_name).execute().await {
             t
         } else {
             use arrow_schema::{DataType, Field, Schema};
@@ -841,7 +1416,7 @@ impl RememexServer {
                 ), false),
                 Field::new("created_at", DataType::Int64, false),
             ]));
-            self.state.db.create_table(&ann_table_name, RecordBatchIterator::new(vec![], schema))
+            db.create_table(&ann_table_name, RecordBatchIterator::new(vec![], schema))
                 .execute().await
                 .map_err(|e| McpError::internal_error(e.to_string(), None))?
         };
@@ -875,9 +1450,9 @@ impl RememexServer {
             schema.clone(),
             vec![
                 std::sync::Arc::new(StringArray::from(vec![id.as_str()])),
-                std::sync::Arc::new(StringArray::from(vec![path.as_str()])),
-                std::sync::Arc::new(StringArray::from(vec![note.as_str()])),
-                std::sync::Arc::new(StringArray::from(vec!["agent"])),
+                std::sync::Arc::new(StringArray::from(vec![path])),
+                std::sync::Arc::new(StringArray::from(vec![note])),
+                std::sync::Arc::new(StringArray::from(vec![source])),
                 std::sync::Arc::new(vector_array),
                 std::sync::Arc::new(Int64Array::from(vec![created_at])),
             ],
@@ -887,13 +1462,27 @@ impl RememexServer {
             .execute().await
             .map_err(|e| McpError::internal_error(e.to_string(), None))?;
 
-        let result = serde_json::json!({
+        Ok(serde_json::json!({
             "id": id,
             "path": path,
             "note": note,
-            "source": "agent",
+            "source": source,
             "created_at": created_at
-        });
+        }))
+    }
+
+    #[tool(
+        description = "Add a searchable annotation (note) to a file. The note is embedded and will appear in future search results. Use this to leave context, warnings, or explanations for yourself or other agents."
+    )]
+    async fn rememex_annotate(
+        &self,
+        Parameters(AnnotateParams { path, note, container }): Parameters<AnnotateParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let container_name = container
+            .as_deref()
+            .unwrap_or(&self.state.config.active_container);
+        self.ensure_writable(container_name)?;
+        let result = self.add_note(container_name, &path, &note, "agent").await?;
         Ok(CallToolResult::success(vec![Content::text(result.to_string())]))
     }
 
@@ -908,8 +1497,9 @@ impl RememexServer {
             .as_deref()
             .unwrap_or(&self.state.config.active_container);
         let table_name = get_table_name(container_name);
+        let db = self.db_for(container_name).await?;
 
-        let result = annotations::get_annotations(&self.state.db, &table_name, path.as_deref())
+        let result = annotations::get_annotations(&db, &table_name, path.as_deref())
             .await
             .map_err(|e| McpError::internal_error(e.to_string(), None))?;
 
@@ -929,9 +1519,11 @@ impl RememexServer {
         let container_name = container
             .as_deref()
             .unwrap_or(&self.state.config.active_container);
+        self.ensure_writable(container_name)?;
         let table_name = get_table_name(container_name);
+        let db = self.db_for(container_name).await?;
 
-        let all = annotations::get_annotations(&self.state.db, &table_name, None)
+        let all = annotations::get_annotations(&db, &table_name, None)
             .await
             .map_err(|e| McpError::internal_error(e.to_string(), None))?;
 
@@ -952,7 +1544,7 @@ impl RememexServer {
             )]));
         }
 
-        annotations::delete_annotation(&self.state.db, &table_name, &annotation_id)
+        annotations::delete_annotation(&db, &table_name, &annotation_id)
             .await
             .map_err(|e| McpError::internal_error(e.to_string(), None))?;
 
@@ -978,16 +1570,21 @@ impl ServerHandler for RememexServer {
             },
             instructions: Some(
                 "Rememex: local semantic file search for AI agents. \
-                 Use rememex_search to find files by meaning with filtering (top_k, file_extensions, path_prefix, context_bytes, min_score). \
+                 Use rememex_search to find files by meaning with filtering (top_k, file_extensions, path_prefix, language, size, lines, context_bytes, min_score); as_of searches files as they were at an earlier date. \
                  Use rememex_read_file to read file content by path (with optional line range). \
                  Use rememex_list_files to browse indexed file paths. \
                  Use rememex_index_status to check index health and stats. \
                  Use rememex_diff to see what files changed recently (e.g. '2h', '1d'). Start conversations with this. \
                  Use rememex_related to find semantically similar files to a given file path. \
+                 Use rememex_topics to see what a container is about, as topics of files clustered by meaning. \
+                 Use rememex_entity to find the files mentioning a person, project or tool, or the ones a file mentions. \
+                 Use rememex_ask_file to ask a question about one file and get an answer quoting it. \
                  Use rememex_annotate to add searchable notes to files (they appear in future searches). \
                  Use rememex_annotations to list existing annotations. \
                  Use rememex_delete_annotation to remove outdated agent-created annotations by ID (user annotations are protected). \
-                 Use rememex_list_containers to see available search scopes."
+                 Use rememex_list_containers to see available search scopes. \
+                 Use rememex_watch_status to check whether live indexing is running and keeping up. \
+                 Use rememex_eval to measure search quality against known (query, expected file) pairs."
                     .into(),
             ),
             capabilities: ServerCapabilities::builder().enable_tools().build(),
@@ -995,14 +1592,407 @@ impl ServerHandler for RememexServer {
     }
 }
 
-fn get_app_data_dir() -> std::path::PathBuf {
-    let base = std::env::var("APPDATA")
-        .or_else(|_| std::env::var("XDG_DATA_HOME"))
-        .unwrap_or_else(|_| {
-            let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
-            format!("{}/.local/share", home)
+fn rpc_error(e: McpError) -> RpcError {
+    RpcError { code: i64::from(e.code.0), message: e.message.to_string() }
+}
+
+/// The app executable next to this one. Bundles name it after the product,
+/// `cargo build` after the crate.
+fn default_app_path() -> Option<PathBuf> {
+    let exe = std::env::current_exe().ok()?;
+    ["Rememex", "rememex"]
+        .iter()
+        .map(|name| exe.with_file_name(format!("{}{}", name, std::env::consts::EXE_SUFFIX)))
+        .find(|path| path.is_file())
+}
+
+/// The editor protocol (`--editor`), see EDITOR.md.
+impl RememexServer {
+    fn editor_container(&self, container: Option<String>, workspace: Option<&str>) -> String {
+        container
+            .or_else(|| workspace.and_then(|w| editor::workspace_container(&self.state.config, w)))
+            .unwrap_or_else(|| self.state.config.active_container.clone())
+    }
+
+    fn ensure_readable(&self, path: &str) -> Result<(), RpcError> {
+        let file_path = Path::new(path);
+        if self.state.config.containers.keys().any(|name| is_path_within_container(file_path, &self.state.config, name)) {
+            Ok(())
+        } else {
+            Err(RpcError::invalid_params(format!("{} is not within any indexed folder", path)))
+        }
+    }
+
+    async fn editor_call(&self, method: &str, params: serde_json::Value) -> Result<serde_json::Value, RpcError> {
+        match method {
+            "initialize" => {
+                let mut containers: Vec<&String> = self.state.config.containers.keys().collect();
+                containers.sort();
+                Ok(serde_json::json!({
+                    "name": "rememex",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "protocol": editor::PROTOCOL_VERSION,
+                    "methods": editor::METHODS,
+                    "containers": containers,
+                    "active_container": self.state.config.active_container,
+                }))
+            }
+            "search" => self.editor_search(editor::params(method, params)?).await,
+            "locate" => {
+                let EditorLocateParams { path, snippet } = editor::params(method, params)?;
+                self.ensure_readable(&path)?;
+                let text = std::fs::read_to_string(&path)
+                    .map_err(|e| RpcError::invalid_params(format!("can't read {}: {}", path, e)))?;
+                serde_json::to_value(editor::locate(&text, &snippet)).map_err(|e| RpcError::internal(e.to_string()))
+            }
+            "annotate" => {
+                let EditorAnnotateParams { path, note, container } = editor::params(method, params)?;
+                if note.trim().is_empty() {
+                    return Err(RpcError::invalid_params("annotate: note is empty"));
+                }
+                let container = self.editor_container(container, Some(&path));
+                self.ensure_writable(&container).map_err(rpc_error)?;
+                self.add_note(&container, &path, note.trim(), "user").await.map_err(rpc_error)
+            }
+            "index_workspace" => self.editor_index(editor::params(method, params)?),
+            "status" => self.editor_status(editor::params(method, params)?).await,
+            _ => Err(RpcError::method_not_found(method)),
+        }
+    }
+
+    async fn editor_search(&self, params: EditorSearchParams) -> Result<serde_json::Value, RpcError> {
+        let EditorSearchParams { query, workspace, container, top_k, file_extensions, language } = params;
+        let workspace = workspace.as_deref().map(|w| w.trim_end_matches(['/', '\\']));
+        let container = self.editor_container(container, workspace);
+        let table_name = get_table_name(&container);
+        let db = self.db_for(&container).await.map_err(rpc_error)?;
+        let tables = db.table_names().execute().await.map_err(|e| RpcError::internal(e.to_string()))?;
+        if !tables.contains(&table_name) {
+            return Ok(serde_json::json!({ "container": container, "results": [] }));
+        }
+
+        let prefix = workspace.map(|w| format!("{}{}", w, std::path::MAIN_SEPARATOR));
+        let top_k = top_k.unwrap_or(20).clamp(1, 50);
+        let scored = self
+            .ranked_search(&db, &container, &query, top_k, prefix.as_deref(), None, file_extensions.as_deref(), language.as_deref(), Default::default())
+            .await
+            .map_err(rpc_error)?;
+
+        let mut texts: HashMap<String, Option<String>> = HashMap::new();
+        let results: Vec<serde_json::Value> = scored
+            .into_iter()
+            .map(|item| {
+                let (context, _) = indexer::pipeline::split_context(&item.snippet);
+                let text = texts.entry(item.path.clone()).or_insert_with(|| std::fs::read_to_string(&item.path).ok());
+                let location = text.as_deref().and_then(|t| editor::locate(t, &item.snippet));
+                let page = indexer::pipeline::page_number(&item.path, &context);
+                serde_json::json!({
+                    "line": location.map(|l| l.line),
+                    "end_line": location.map(|l| l.end_line),
+                    "page": page,
+                    "context": context,
+                    "snippet": editor::snippet_body(&item.snippet),
+                    "annotation": item.snippet.starts_with("[annotation] "),
+                    "summary": item.snippet.starts_with("[summary] "),
+                    "score": item.score,
+                    "path": item.path,
+                })
+            })
+            .collect();
+        Ok(serde_json::json!({ "container": container, "results": results }))
+    }
+
+    /// Indexing happens in the app, through `--index`, so there's one writer
+    /// and its watcher follows the folder afterwards.
+    fn editor_index(&self, params: EditorIndexParams) -> Result<serde_json::Value, RpcError> {
+        let EditorIndexParams { path, app } = params;
+        let container = self.start_indexing(&path, app.map(PathBuf::from))?;
+        Ok(serde_json::json!({ "started": true, "path": path, "container": container }))
+    }
+
+    /// Starts the app indexing `path` into the active container, see
+    /// `editor_index`. Returns the container.
+    fn start_indexing(&self, path: &str, app: Option<PathBuf>) -> Result<String, RpcError> {
+        let dir = Path::new(path);
+        if !dir.is_absolute() || !dir.is_dir() {
+            return Err(RpcError::invalid_params(format!("{} is not an absolute path to a folder", path)));
+        }
+        let active = &self.state.config.active_container;
+        if !self.state.config.can_write(active) {
+            return Err(RpcError::invalid_params(format!("'{}' is read-only here, it can't index new folders", active)));
+        }
+        let app = app
+            .or_else(default_app_path)
+            .ok_or_else(|| RpcError::invalid_params("can't find the Rememex app next to rememex-mcp, pass its path as \"app\""))?;
+        let mut child = std::process::Command::new(&app)
+            .arg(INDEX_FLAG)
+            .arg(path)
+            .stdin(std::process::Stdio::null())
+            .stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::null())
+            .spawn()
+            .map_err(|e| RpcError::internal(format!("can't start {}: {}", app.display(), e)))?;
+        std::thread::spawn(move || child.wait());
+        info!("asked {} to index {}", app.display(), path);
+        Ok(active.clone())
+    }
+
+    async fn editor_status(&self, params: EditorStatusParams) -> Result<serde_json::Value, RpcError> {
+        let config = load_config(&self.state.app_data.join("config.json"));
+        let workspace = params.workspace.as_deref().map(|w| w.trim_end_matches(['/', '\\']));
+        let container = workspace
+            .and_then(|w| editor::workspace_container(&config, w))
+            .unwrap_or_else(|| config.active_container.clone());
+        let indexed = match (workspace, config.containers.get(&container)) {
+            (Some(w), Some(info)) => is_path_excluded(&info.indexed_paths, w),
+            (None, Some(info)) => !info.indexed_paths.is_empty(),
+            _ => false,
+        };
+
+        let db = self.db_for(&container).await.map_err(rpc_error)?;
+        let files = match indexer::snapshot::open(&db, &get_table_name(&container)).await {
+            Ok(table) => {
+                let prefix = workspace.map(|w| format!("{}{}", w, std::path::MAIN_SEPARATOR));
+                let filter = indexer::build_filter_expr(prefix.as_deref(), None, None, None);
+                file_paths(&table, filter).await.map_err(rpc_error)?.len()
+            }
+            Err(_) => 0,
+        };
+        let watcher = self.watcher_status().map_err(rpc_error)?;
+        Ok(serde_json::json!({
+            "container": container,
+            "indexed": indexed,
+            "files": files,
+            "watcher": watcher,
+        }))
+    }
+}
+
+async fn serve_editor(server: RememexServer) -> Result<(), Box<dyn std::error::Error>> {
+    use std::io::{BufRead, Write};
+
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<String>();
+    std::thread::spawn(move || {
+        for line in std::io::stdin().lock().lines() {
+            let Ok(line) = line else { break };
+            if tx.send(line).is_err() {
+                break;
+            }
+        }
+    });
+
+    info!("Editor protocol ready on stdio");
+    let mut stdout = std::io::stdout();
+    while let Some(line) = rx.recv().await {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let reply = match serde_json::from_str::<editor::Request>(&line) {
+            Ok(request) => {
+                debug!("editor: {}", request.method);
+                let result = server.editor_call(&request.method, request.params).await;
+                if let Err(e) = &result {
+                    debug!("editor: {} failed: {}", request.method, e.message);
+                }
+                request.id.map(|id| editor::response(id, result))
+            }
+            Err(e) => Some(editor::response(serde_json::Value::Null, Err(RpcError::parse_error(e.to_string())))),
+        };
+        if let Some(reply) = reply {
+            writeln!(stdout, "{}", reply)?;
+            stdout.flush()?;
+        }
+    }
+    Ok(())
+}
+
+/// Where `--grpc` listens without an address.
+const GRPC_ADDR: &str = "127.0.0.1:50151";
+/// Texts one `Embed` call takes.
+const MAX_EMBED_TEXTS: usize = 256;
+
+fn grpc_status(e: RpcError) -> tonic::Status {
+    match e.code {
+        -32602 => tonic::Status::invalid_argument(e.message),
+        -32600 => tonic::Status::failed_precondition(e.message),
+        _ => tonic::Status::internal(e.message),
+    }
+}
+
+/// The gRPC service (`--grpc`): the app's embedding model, search and
+/// indexing for other programs and other machines' remote containers, typed by
+/// `proto/rememex.proto`. Searches go through `ranked_search` and redaction
+/// like the MCP tools'.
+struct Grpc(RememexServer);
+
+#[tonic::async_trait]
+impl proto::rememex_service_server::RememexService for Grpc {
+    async fn embed(&self, request: tonic::Request<proto::EmbedRequest>) -> Result<tonic::Response<proto::EmbedResponse>, tonic::Status> {
+        let proto::EmbedRequest { texts, query } = request.into_inner();
+        if texts.is_empty() || texts.len() > MAX_EMBED_TEXTS {
+            return Err(tonic::Status::invalid_argument(format!("send 1 to {} texts", MAX_EMBED_TEXTS)));
+        }
+        let provider = self.0.state.provider.lock().await;
+        let vectors = if query {
+            let mut vectors = Vec::with_capacity(texts.len());
+            for text in &texts {
+                vectors.push(provider.embed_query(text).await.map_err(|e| tonic::Status::internal(e.to_string()))?);
+            }
+            vectors
+        } else {
+            provider.embed_passages(texts).await.map_err(|e| tonic::Status::internal(e.to_string()))?
+        };
+        Ok(tonic::Response::new(proto::EmbedResponse {
+            embeddings: vectors.into_iter().map(|values| proto::Embedding { values }).collect(),
+            model: self.0.state.config.embedding_provider.provider_label(),
+        }))
+    }
+
+    async fn search(&self, request: tonic::Request<proto::SearchRequest>) -> Result<tonic::Response<proto::SearchResponse>, tonic::Status> {
+        let proto::SearchRequest { query, container, top_k, path_prefix, file_extensions } = request.into_inner();
+        if query.trim().is_empty() {
+            return Err(tonic::Status::invalid_argument("query is empty"));
+        }
+        let container = if container.is_empty() { self.0.state.config.active_container.clone() } else { container };
+        if !self.0.state.config.containers.contains_key(&container) {
+            return Err(tonic::Status::not_found(format!("no container named '{}'", container)));
+        }
+        let db = self.0.db_for(&container).await.map_err(|e| grpc_status(rpc_error(e)))?;
+        let tables = db.table_names().execute().await.map_err(|e| tonic::Status::internal(e.to_string()))?;
+        if !tables.contains(&get_table_name(&container)) {
+            return Ok(tonic::Response::new(proto::SearchResponse { container, results: vec![] }));
+        }
+
+        let top_k = (if top_k == 0 { 10 } else { top_k as usize }).clamp(1, 50);
+        let path_prefix = (!path_prefix.is_empty()).then_some(path_prefix);
+        let file_extensions = (!file_extensions.is_empty()).then_some(file_extensions);
+        let scored = self
+            .0
+            .ranked_search(&db, &container, &query, top_k, path_prefix.as_deref(), None, file_extensions.as_deref(), None, Default::default())
+            .await
+            .map_err(|e| grpc_status(rpc_error(e)))?;
+        let redactor = self.0.redactor(&container);
+        let results = scored
+            .into_iter()
+            .map(|item| {
+                let snippet = match &redactor {
+                    Some(redactor) => redactor.redact(&item.snippet),
+                    None => item.snippet,
+                };
+                let (context, _) = indexer::pipeline::split_context(&snippet);
+                proto::SearchResult { path: item.path, context, snippet: editor::snippet_body(&snippet), score: item.score }
+            })
+            .collect();
+        Ok(tonic::Response::new(proto::SearchResponse { container, results }))
+    }
+
+    async fn index(&self, request: tonic::Request<proto::IndexRequest>) -> Result<tonic::Response<proto::IndexResponse>, tonic::Status> {
+        let proto::IndexRequest { path, app } = request.into_inner();
+        let app = (!app.is_empty()).then(|| PathBuf::from(app));
+        let container = self.0.start_indexing(&path, app).map_err(grpc_status)?;
+        Ok(tonic::Response::new(proto::IndexResponse { container }))
+    }
+
+    async fn list_containers(
+        &self,
+        _request: tonic::Request<proto::ListContainersRequest>,
+    ) -> Result<tonic::Response<proto::ListContainersResponse>, tonic::Status> {
+        let config = &self.0.state.config;
+        let mut containers: Vec<proto::Container> = config
+            .containers
+            .iter()
+            .map(|(name, info)| proto::Container {
+                name: name.clone(),
+                description: info.description.clone(),
+                active: *name == config.active_container,
+                locked: os_auth::ensure_unlocked(config, name).is_err(),
+            })
+            .collect();
+        containers.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(tonic::Response::new(proto::ListContainersResponse { containers }))
+    }
+}
+
+/// Serves the gRPC service on `addr`. Whatever can connect searches every
+/// unlocked container, so other machines (`remote_containers`) are only let
+/// in with `REMEMEX_GRPC_TOKEN` set, and then every call has to carry it.
+async fn serve_grpc(server: RememexServer, addr: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let addr: std::net::SocketAddr = addr.parse()?;
+    let token = std::env::var("REMEMEX_GRPC_TOKEN").ok().filter(|token| !token.is_empty());
+    if token.is_none() && !addr.ip().is_loopback() {
+        return Err(format!("{} isn't a loopback address; set REMEMEX_GRPC_TOKEN to listen beyond this machine", addr).into());
+    }
+    info!("gRPC service ready on {}{}", addr, if token.is_some() { " (token required)" } else { "" });
+    let service = proto::rememex_service_server::RememexServiceServer::with_interceptor(Grpc(server), move |request: tonic::Request<()>| {
+        let Some(token) = &token else { return Ok(request) };
+        let authorization = request.metadata().get("authorization").and_then(|value| value.to_str().ok());
+        if remote::authorized(authorization, token) {
+            Ok(request)
+        } else {
+            Err(tonic::Status::unauthenticated("missing or wrong token"))
+        }
+    });
+    tonic::transport::Server::builder().add_service(service).serve(addr).await?;
+    Ok(())
+}
+
+/// Flags of the command line modes that take a value.
+const VALUE_FLAGS: &[&str] = &["--eval", "--k", "--container", "--format"];
+
+/// `--name value` or `--name=value` from the command line, for the eval mode
+/// (`rememex-mcp --eval cases.jsonl [--k 10] [--container Work]`) and the
+/// launcher search (`rememex-mcp search QUERY --format alfred`).
+fn arg_value(name: &str) -> Option<String> {
+    let args: Vec<String> = std::env::args().collect();
+    let inline = format!("{}=", name);
+    args.iter()
+        .find_map(|a| a.strip_prefix(&inline).map(str::to_string))
+        .or_else(|| args.iter().position(|a| a == name).and_then(|i| args.get(i + 1).cloned()))
+}
+
+/// The words after `search` that aren't flags, so the query doesn't need
+/// quoting.
+fn search_query(args: &[String]) -> String {
+    let mut words = Vec::new();
+    let mut rest = args.iter().skip_while(|a| a.as_str() != "search").skip(1);
+    while let Some(arg) = rest.next() {
+        if VALUE_FLAGS.contains(&arg.as_str()) {
+            rest.next();
+        } else if !arg.starts_with("--") {
+            words.push(arg.as_str());
+        }
+    }
+    words.join(" ")
+}
+
+/// Best chunk per file for a launcher, with the line it starts at.
+async fn launcher_items(server: &RememexServer, query: &str, container: &str, k: usize) -> Result<Vec<LauncherItem>, McpError> {
+    let table_name = get_table_name(container);
+    let db = server.db_for(container).await?;
+    let tables = db.table_names().execute().await.map_err(|e| McpError::internal_error(e.to_string(), None))?;
+    if !tables.contains(&table_name) {
+        return Err(McpError::invalid_request(format!("nothing indexed in '{}' yet", container), None));
+    }
+    let scored = server.ranked_search(&db, container, query, k, None, None, None, None, Default::default()).await?;
+    let mut items: Vec<LauncherItem> = Vec::new();
+    for item in scored {
+        if items.iter().any(|i| i.path == item.path) {
+            continue;
+        }
+        let (context, _) = indexer::pipeline::split_context(&item.snippet);
+        let line = std::fs::read_to_string(&item.path)
+            .ok()
+            .and_then(|text| editor::locate(&text, &item.snippet))
+            .map(|l| l.line);
+        items.push(LauncherItem {
+            snippet: editor::snippet_body(&item.snippet),
+            path: item.path,
+            context,
+            score: item.score,
+            line,
         });
-    std::path::PathBuf::from(base).join("com.rememex.app")
+    }
+    Ok(items)
 }
 
 #[tokio::main]
@@ -1012,7 +2002,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .init();
 
     info!("MCP server starting...");
-    let app_data = get_app_data_dir();
+    apply_read_only_flag();
+    let app_data = app_data_dir();
     let models_path = app_data.join("models");
 
     let db_path = app_data.join("lancedb");
@@ -1022,18 +2013,14 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     let config_path = app_data.join("config.json");
     let config = load_config(&config_path);
+    set_offline(config.offline);
     info!("Config loaded, active container: {}", config.active_container);
 
     let provider: Box<dyn EmbeddingProvider> = match &config.embedding_provider {
         EmbeddingProviderConfig::Local { model } => {
             let model_enum = get_embedding_model(model);
-            let model = indexer::load_model(model_enum, models_path.clone())?;
-            let model_state = Arc::new(Mutex::new(ModelState {
-                model: Some(model),
-                init_error: None,
-                cached_dim: None,
-            }));
-            Box::new(LocalProvider { model_state })
+            let model = indexer::load_model(model_enum.clone(), models_path.clone())?;
+            Box::new(LocalProvider::new(model, model_enum, models_path.clone()))
         }
         EmbeddingProviderConfig::Remote(rc) => {
             Box::new(RemoteProvider::new(rc.clone()))
@@ -1048,9 +2035,48 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         provider: Arc::new(Mutex::new(provider)),
         reranker: Arc::new(Mutex::new(reranker)),
         config,
+        app_data,
+        shared_dbs: Mutex::new(HashMap::new()),
     });
 
     let server = RememexServer::new(state);
+
+    if let Some(cases_file) = arg_value("--eval") {
+        let cases = eval::load_cases(Path::new(&cases_file))?;
+        let container = arg_value("--container").unwrap_or_else(|| server.state.config.active_container.clone());
+        let k = arg_value("--k").and_then(|k| k.parse().ok()).unwrap_or(10usize).clamp(1, 50);
+        let report = server.evaluate(&container, &cases, k).await.map_err(|e| e.message.to_string())?;
+        print!("{}", report.summary());
+        return Ok(());
+    }
+
+    if std::env::args().nth(1).as_deref() == Some("search") {
+        let format: LauncherFormat = arg_value("--format").as_deref().unwrap_or("json").parse()?;
+        let args: Vec<String> = std::env::args().collect();
+        let query = search_query(&args);
+        let output = if query.trim().is_empty() {
+            launcher::message(format, "Search your files", "Type what you're looking for")
+        } else {
+            let container = arg_value("--container").unwrap_or_else(|| server.state.config.active_container.clone());
+            let k = arg_value("--k").and_then(|k| k.parse().ok()).unwrap_or(10usize).clamp(1, 50);
+            match launcher_items(&server, &query, &container, k).await {
+                Ok(items) => launcher::render(format, &items),
+                Err(e) => launcher::message(format, "Search failed", &e.message),
+            }
+        };
+        println!("{}", output);
+        return Ok(());
+    }
+
+    if std::env::args().any(|a| a == "--editor") {
+        return serve_editor(server).await;
+    }
+
+    if std::env::args().any(|a| a == "--grpc") {
+        let addr = arg_value("--grpc").filter(|addr| !addr.starts_with("--")).unwrap_or_else(|| GRPC_ADDR.to_string());
+        return serve_grpc(server, &addr).await;
+    }
+
     let service = server.serve(stdio()).await?;
     service.waiting().await?;
 
@@ -0,0 +1,340 @@
+use serde::Serialize;
+
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct QuickAnswer {
+    pub expression: String,
+    pub value: String,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Dimension {
+    Length,
+    Mass,
+    Volume,
+    Time,
+    Data,
+    Temperature,
+}
+
+/// Unit aliases with their size in the dimension's base unit (meter, gram, liter,
+/// second, byte). Temperature is handled separately since it has offsets.
+const UNITS: &[(&[&str], Dimension, f64)] = &[
+    (&["mm", "millimeter", "millimeters"], Dimension::Length, 0.001),
+    (&["cm", "centimeter", "centimeters"], Dimension::Length, 0.01),
+    (&["m", "meter", "meters", "metre", "metres"], Dimension::Length, 1.0),
+    (&["km", "kilometer", "kilometers"], Dimension::Length, 1000.0),
+    (&["in", "inch", "inches"], Dimension::Length, 0.0254),
+    (&["ft", "foot", "feet"], Dimension::Length, 0.3048),
+    (&["yd", "yard", "yards"], Dimension::Length, 0.9144),
+    (&["mi", "mile", "miles"], Dimension::Length, 1609.344),
+    (&["mg", "milligram", "milligrams"], Dimension::Mass, 0.001),
+    (&["g", "gram", "grams"], Dimension::Mass, 1.0),
+    (&["kg", "kilogram", "kilograms"], Dimension::Mass, 1000.0),
+    (&["t", "tonne", "tonnes"], Dimension::Mass, 1_000_000.0),
+    (&["oz", "ounce", "ounces"], Dimension::Mass, 28.349523125),
+    (&["lb", "lbs", "pound", "pounds"], Dimension::Mass, 453.59237),
+    (&["ml", "milliliter", "milliliters"], Dimension::Volume, 0.001),
+    (&["l", "liter", "liters", "litre", "litres"], Dimension::Volume, 1.0),
+    (&["gal", "gallon", "gallons"], Dimension::Volume, 3.785411784),
+    (&["cup", "cups"], Dimension::Volume, 0.2365882365),
+    (&["ms", "millisecond", "milliseconds"], Dimension::Time, 0.001),
+    (&["s", "sec", "second", "seconds"], Dimension::Time, 1.0),
+    (&["min", "minute", "minutes"], Dimension::Time, 60.0),
+    (&["h", "hr", "hour", "hours"], Dimension::Time, 3600.0),
+    (&["d", "day", "days"], Dimension::Time, 86400.0),
+    (&["wk", "week", "weeks"], Dimension::Time, 604800.0),
+    (&["b", "byte", "bytes"], Dimension::Data, 1.0),
+    (&["kb", "kilobyte", "kilobytes"], Dimension::Data, 1e3),
+    (&["mb", "megabyte", "megabytes"], Dimension::Data, 1e6),
+    (&["gb", "gigabyte", "gigabytes"], Dimension::Data, 1e9),
+    (&["tb", "terabyte", "terabytes"], Dimension::Data, 1e12),
+    (&["kib"], Dimension::Data, 1024.0),
+    (&["mib"], Dimension::Data, 1_048_576.0),
+    (&["gib"], Dimension::Data, 1_073_741_824.0),
+    (&["tib"], Dimension::Data, 1_099_511_627_776.0),
+    (&["c", "°c", "celsius"], Dimension::Temperature, 0.0),
+    (&["f", "°f", "fahrenheit"], Dimension::Temperature, 1.0),
+    (&["k", "kelvin"], Dimension::Temperature, 2.0),
+];
+
+fn find_unit(name: &str) -> Option<(Dimension, f64, &'static str)> {
+    let name = name.to_lowercase();
+    UNITS
+        .iter()
+        .find(|(aliases, _, _)| aliases.contains(&name.as_str()))
+        .map(|(aliases, dim, factor)| (*dim, *factor, aliases[0]))
+}
+
+fn to_kelvin(value: f64, scale: f64) -> f64 {
+    match scale as u8 {
+        0 => value + 273.15,
+        1 => (value - 32.0) * 5.0 / 9.0 + 273.15,
+        _ => value,
+    }
+}
+
+fn from_kelvin(value: f64, scale: f64) -> f64 {
+    match scale as u8 {
+        0 => value - 273.15,
+        1 => (value - 273.15) * 9.0 / 5.0 + 32.0,
+        _ => value,
+    }
+}
+
+fn format_number(value: f64) -> String {
+    if value == 0.0 {
+        return "0".to_string();
+    }
+    if value.abs() >= 1e15 || value.abs() < 1e-9 {
+        return format!("{:e}", value);
+    }
+    let rounded = format!("{:.10}", value);
+    let trimmed = rounded.trim_end_matches('0').trim_end_matches('.');
+    if trimmed == "-0" {
+        "0".to_string()
+    } else {
+        trimmed.to_string()
+    }
+}
+
+/// "5 km to mi", "100 f in c", "2.5gb to mib"
+fn convert_units(query: &str) -> Option<QuickAnswer> {
+    let lower = query.to_lowercase();
+    let (lhs, rhs) = lower
+        .split_once(" to ")
+        .or_else(|| lower.split_once(" in "))?;
+    let lhs = lhs.trim();
+    let split = lhs
+        .find(|c: char| !(c.is_ascii_digit() || c == '.' || c == '-' || c == ','))
+        .filter(|&i| i > 0)?;
+    let amount: f64 = lhs[..split].replace(',', "").trim().parse().ok()?;
+    let (from_dim, from_factor, _) = find_unit(lhs[split..].trim())?;
+    let (to_dim, to_factor, to_name) = find_unit(rhs.trim())?;
+    if from_dim != to_dim {
+        return None;
+    }
+
+    let result = if from_dim == Dimension::Temperature {
+        from_kelvin(to_kelvin(amount, from_factor), to_factor)
+    } else {
+        amount * from_factor / to_factor
+    };
+    Some(QuickAnswer {
+        expression: query.trim().to_string(),
+        value: format!("{} {}", format_number(result), to_name),
+    })
+}
+
+struct Parser<'a> {
+    chars: std::iter::Peekable<std::str::Chars<'a>>,
+    operations: usize,
+}
+
+impl Parser<'_> {
+    fn skip_ws(&mut self) {
+        while self.chars.peek().is_some_and(|c| c.is_whitespace()) {
+            self.chars.next();
+        }
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.skip_ws();
+        self.chars.peek().copied()
+    }
+
+    fn expr(&mut self) -> Option<f64> {
+        let mut value = self.term()?;
+        while let Some(op) = self.peek().filter(|c| matches!(c, '+' | '-')) {
+            self.chars.next();
+            self.operations += 1;
+            let rhs = self.term()?;
+            value = if op == '+' { value + rhs } else { value - rhs };
+        }
+        Some(value)
+    }
+
+    fn term(&mut self) -> Option<f64> {
+        let mut value = self.power()?;
+        while let Some(op) = self.peek().filter(|c| matches!(c, '*' | '/' | '%' | 'x' | '×' | '÷')) {
+            self.chars.next();
+            self.operations += 1;
+            let rhs = self.power()?;
+            value = match op {
+                '/' | '÷' => value / rhs,
+                '%' => value % rhs,
+                _ => value * rhs,
+            };
+        }
+        Some(value)
+    }
+
+    fn power(&mut self) -> Option<f64> {
+        let base = self.unary()?;
+        if self.peek() == Some('^') {
+            self.chars.next();
+            self.operations += 1;
+            let exponent = self.power()?;
+            return Some(base.powf(exponent));
+        }
+        Some(base)
+    }
+
+    fn unary(&mut self) -> Option<f64> {
+        match self.peek()? {
+            '-' => {
+                self.chars.next();
+                Some(-self.unary()?)
+            }
+            '+' => {
+                self.chars.next();
+                self.unary()
+            }
+            _ => self.atom(),
+        }
+    }
+
+    fn atom(&mut self) -> Option<f64> {
+        let c = self.peek()?;
+        if c == '(' {
+            self.chars.next();
+            let value = self.expr()?;
+            if self.peek()? != ')' {
+                return None;
+            }
+            self.chars.next();
+            return Some(value);
+        }
+        if c.is_ascii_digit() || c == '.' {
+            let mut number = String::new();
+            while let Some(&d) = self.chars.peek() {
+                if d.is_ascii_digit() || d == '.' {
+                    number.push(d);
+                } else if d != ',' && d != '_' {
+                    break;
+                }
+                self.chars.next();
+            }
+            return number.parse().ok();
+        }
+        if c.is_ascii_alphabetic() {
+            let mut name = String::new();
+            while let Some(&d) = self.chars.peek() {
+                if !d.is_ascii_alphabetic() {
+                    break;
+                }
+                name.push(d);
+                self.chars.next();
+            }
+            return match name.to_lowercase().as_str() {
+                "pi" => Some(std::f64::consts::PI),
+                "e" => Some(std::f64::consts::E),
+                func => {
+                    let arg = self.atom()?;
+                    self.operations += 1;
+                    match func {
+                        "sqrt" => Some(arg.sqrt()),
+                        "abs" => Some(arg.abs()),
+                        "ln" => Some(arg.ln()),
+                        "log" => Some(arg.log10()),
+                        "sin" => Some(arg.sin()),
+                        "cos" => Some(arg.cos()),
+                        "tan" => Some(arg.tan()),
+                        "round" => Some(arg.round()),
+                        "floor" => Some(arg.floor()),
+                        "ceil" => Some(arg.ceil()),
+                        _ => None,
+                    }
+                }
+            };
+        }
+        None
+    }
+}
+
+/// Dates ("2024-05-16", "12/05/2024") and ranges or ticket numbers
+/// ("2371-2372"): numbers joined by `-` or `/` without spaces.
+fn is_date_or_range(expression: &str) -> bool {
+    ['-', '/'].into_iter().any(|separator| {
+        let parts: Vec<&str> = expression.split(separator).collect();
+        (2..=3).contains(&parts.len()) && parts.iter().all(|part| !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit()))
+    })
+}
+
+fn evaluate(query: &str) -> Option<QuickAnswer> {
+    let query = query.trim();
+    let expression = query.trim_start_matches('=').trim();
+    // A leading `=` still calculates them.
+    if expression.is_empty() || (!query.starts_with('=') && is_date_or_range(expression)) {
+        return None;
+    }
+    let mut parser = Parser { chars: expression.chars().peekable(), operations: 0 };
+    let value = parser.expr()?;
+    // A bare number or constant is a search term, not a calculation.
+    if parser.peek().is_some() || parser.operations == 0 || !value.is_finite() {
+        return None;
+    }
+    Some(QuickAnswer {
+        expression: expression.to_string(),
+        value: format_number(value),
+    })
+}
+
+/// Answers arithmetic ("(3+4)*2", "sqrt 2") and unit conversions ("5 km to mi")
+/// straight from the query. `None` means the query should go to search as usual.
+pub fn quick_answer(query: &str) -> Option<QuickAnswer> {
+    if query.len() > 200 {
+        return None;
+    }
+    evaluate(query).or_else(|| convert_units(query))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn value(query: &str) -> Option<String> {
+        quick_answer(query).map(|a| a.value)
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        assert_eq!(value("1 + 2 * 3").as_deref(), Some("7"));
+        assert_eq!(value("(1 + 2) * 3").as_deref(), Some("9"));
+        assert_eq!(value("2^10").as_deref(), Some("1024"));
+        assert_eq!(value("-4 + 1").as_deref(), Some("-3"));
+        assert_eq!(value("10 / 4").as_deref(), Some("2.5"));
+        assert_eq!(value("= 1,000 * 3").as_deref(), Some("3000"));
+        assert_eq!(value("sqrt(16)").as_deref(), Some("4"));
+    }
+
+    #[test]
+    fn test_plain_searches_are_ignored() {
+        assert_eq!(value("2024"), None);
+        assert_eq!(value("pi"), None);
+        assert_eq!(value("config parser"), None);
+        assert_eq!(value("1 / 0"), None);
+        assert_eq!(value("rust 2021 edition"), None);
+        assert_eq!(value("(1 + 2"), None);
+    }
+
+    #[test]
+    fn test_dates_and_ranges_are_searched() {
+        assert_eq!(value("2024-05-16"), None);
+        assert_eq!(value("12/05/2024"), None);
+        assert_eq!(value("2371-2372"), None);
+        assert_eq!(value("10/4"), None);
+        assert_eq!(value("=10/4").as_deref(), Some("2.5"));
+        assert_eq!(value("2371 - 2372").as_deref(), Some("-1"));
+    }
+
+    #[test]
+    fn test_unit_conversion() {
+        assert_eq!(value("1 km to m").as_deref(), Some("1000 m"));
+        assert_eq!(value("100 c to f").as_deref(), Some("212 f"));
+        assert_eq!(value("32 F in celsius").as_deref(), Some("0 c"));
+        assert_eq!(value("1 gib to mb").as_deref(), Some("1073.741824 mb"));
+        assert_eq!(value("5kg to lb").as_deref(), Some("11.0231131092 lb"));
+        assert_eq!(value("5 kg to m"), None);
+        assert_eq!(value("notes in docs"), None);
+    }
+}
@@ -1,19 +1,60 @@
+use std::collections::HashSet;
 use std::sync::Arc;
 
-use log::{info, error, debug};
+use log::{info, error, debug, warn};
 
 use serde::{Deserialize, Serialize};
 use tauri::{Emitter, Manager};
 use tokio::sync::Mutex;
 
-use crate::config::{get_table_name, ConfigState, EmbeddingProviderConfig};
+use crate::appearance::{self, VibrancyState};
+use crate::calculator::{self, QuickAnswer};
+use crate::container_templates;
+use crate::context_block::{self, ContextBlock, ContextItem};
+use crate::deep_link::{DeepLink, PendingSearch};
+use crate::desktop_search;
+use crate::diagnostics;
+use crate::endpoint_health::{self, EndpointHealth};
+use crate::config::{get_table_name, is_path_excluded, parse_hex_color, validate_hotkey, Config, ConfigState, ContainerRole, DesktopSearch, EmbeddingProviderConfig, QuotaPolicy, ResultActionsConfig, ThemeMode, Webhook, WindowMode, WindowPlacement};
+use crate::hotkey;
+use crate::locales;
+use crate::model_download;
+use crate::notifications;
+use crate::os_auth;
+use crate::placement;
+use crate::privacy_report::{self, PrivacyReport};
+use crate::remote::{self, RemoteSource};
+use crate::tray;
 use crate::indexer;
 use crate::indexer::annotations;
+use crate::indexer::history;
+use crate::indexer::journal;
+use crate::indexer::quota;
+use crate::indexer::rebuild::rebuild_table_name;
 use crate::indexer::embedding_provider::RemoteProviderConfig;
 use crate::state::{
-    ContainerListItem, DbState, IndexingProgress, ProviderState, RerankerState, SearchResult,
+    CompletionState, ContainerListItem, DbState, IndexVersion, IndexingProgress, ProviderState,
+    RerankerState, ResultCache, SearchResult, SpellState, WatcherStatus,
 };
 use crate::watcher;
+use crate::post_index;
+use crate::webhooks::{self, IndexEvent};
+
+/// Commands that write to the index refuse to run in read-only mode, on a
+/// reader of a shared index, or while another machine holds its writer lock.
+async fn ensure_writable(config_state: &ConfigState) -> Result<(), String> {
+    let config = config_state.config.lock().await;
+    if config.is_read_only() {
+        return Err("Read-only mode: indexing and annotations are disabled".to_string());
+    }
+    if !config.can_write(&config.active_container) {
+        return Err("This container searches a shared index as a reader and can't change it".to_string());
+    }
+    if let Some(host) = crate::shared_index::blocked_by() {
+        return Err(format!("{} is writing to this shared index, it's read-only here until it stops", host));
+    }
+    Ok(())
+}
 
 #[tauri::command]
 pub async fn get_containers(
@@ -21,15 +62,15 @@ pub async fn get_containers(
 ) -> Result<(Vec<ContainerListItem>, String), String> {
     let config = config_state.config.lock().await;
     let list: Vec<ContainerListItem> = config.containers.iter().map(|(name, info)| {
-        let provider_label = info.embedding_provider
-            .as_ref()
-            .map(|p| p.provider_label())
-            .unwrap_or_else(|| config.embedding_provider.provider_label());
+        let provider_label = config.provider_label(name);
         ContainerListItem {
             name: name.clone(),
             description: info.description.clone(),
             indexed_paths: info.indexed_paths.clone(),
             provider_label,
+            db_uri: info.db_uri.clone(),
+            role: info.role,
+            locked: info.locked,
         }
     }).collect();
     Ok((list, config.active_container.clone()))
@@ -38,6 +79,7 @@ pub async fn get_containers(
 #[allow(clippy::too_many_arguments)]
 #[tauri::command]
 pub async fn create_container(
+    app: tauri::AppHandle,
     name: String,
     description: String,
     provider_type: String,
@@ -46,9 +88,13 @@ pub async fn create_container(
     remote_api_key: Option<String>,
     remote_model: Option<String>,
     remote_dimensions: Option<usize>,
+    db_uri: Option<String>,
+    role: Option<ContainerRole>,
+    template: Option<String>,
     config_state: tauri::State<'_, ConfigState>,
 ) -> Result<(), String> {
-    info!("create_container: name=\"{}\" provider_type={}", name, provider_type);
+    info!("create_container: name=\"{}\" provider_type={} db_uri={:?} template={:?}", name, provider_type, db_uri, template);
+    let db_uri = db_uri.map(|u| u.trim().to_string()).filter(|u| !u.is_empty());
     let mut config = config_state.config.lock().await;
     if config.containers.contains_key(&name) {
         return Err("Container already exists".to_string());
@@ -72,20 +118,42 @@ pub async fn create_container(
         description,
         indexed_paths: Vec::new(),
         embedding_provider: Some(provider),
+        excluded_paths: Vec::new(),
+        pinned_paths: Vec::new(),
+        role: if db_uri.is_some() { role.unwrap_or_default() } else { ContainerRole::Writer },
+        db_uri,
+        storage_options: Default::default(),
+        quota_mb: None,
+        quota_policy: Default::default(),
+        indexing: template
+            .and_then(|id| container_templates::find(&id))
+            .map(|t| t.indexing)
+            .unwrap_or_default(),
+        search: Default::default(),
+        redaction: Default::default(),
+        locked: false,
     });
     drop(config);
     config_state.save().await?;
+    tray::refresh(&app).await;
     Ok(())
 }
 
+#[tauri::command]
+pub fn get_container_templates() -> Vec<container_templates::ContainerTemplate> {
+    container_templates::builtin()
+}
+
 #[tauri::command]
 pub async fn delete_container(
+    app: tauri::AppHandle,
     name: String,
     config_state: tauri::State<'_, ConfigState>,
     db_state: tauri::State<'_, Arc<Mutex<DbState>>>,
 ) -> Result<(), String> {
+    ensure_writable(&config_state).await?;
     info!("delete_container: name=\"{}\"", name);
-    {
+    let removed = {
         let mut config = config_state.config.lock().await;
         if name == "Default" {
             return Err("Cannot delete Default container".to_string());
@@ -93,18 +161,89 @@ pub async fn delete_container(
         if config.active_container == name {
             config.active_container = "Default".to_string();
         }
-        config.containers.remove(&name);
-    }
+        config.containers.remove(&name)
+    };
 
     config_state.save().await?;
+    os_auth::forget(&name);
+
+    // A shared index belongs to everyone using it, so only the local
+    // config entry goes.
+    if removed.is_some_and(|info| info.db_uri.is_none()) {
+        let db = {
+            let guard = db_state.lock().await;
+            guard.connection()?
+        };
+        drop_container_tables(&db, &name).await;
+    }
+    crate::connect_active_db(&app).await;
+    tray::refresh(&app).await;
 
-    let db = {
-        let guard = db_state.lock().await;
-        guard.db.clone()
+    Ok(())
+}
+
+/// Drops a deleted container's index, history and any rebuild of it.
+pub(crate) async fn drop_container_tables(db: &lancedb::Connection, name: &str) {
+    let table_name = get_table_name(name);
+    let _ = indexer::reset_index(db, &table_name).await;
+    let _ = history::clear_history(db, &table_name).await;
+    let _ = indexer::reset_index(db, &rebuild_table_name(&table_name)).await;
+    journal::finish(&table_name);
+    journal::finish(&rebuild_table_name(&table_name));
+}
+
+/// Asks for Windows Hello or Touch ID and opens a locked container for
+/// `unlock_timeout_mins`.
+#[tauri::command]
+pub async fn unlock_container(
+    name: String,
+    config_state: tauri::State<'_, ConfigState>,
+) -> Result<(), String> {
+    let minutes = {
+        let config = config_state.config.lock().await;
+        if !config.containers.contains_key(&name) {
+            return Err("Container does not exist".to_string());
+        }
+        config.unlock_timeout_mins
     };
-    let table_name = get_table_name(&name);
-    let _ = db.drop_table(&table_name, &[]).await;
+    os_auth::verify(&format!("unlock \"{}\"", name)).await.map_err(|e| e.to_string())?;
+    os_auth::unlock(&name, minutes);
+    Ok(())
+}
 
+/// Locks a container or takes its lock off, either way after the OS's
+/// sign-in check.
+#[tauri::command]
+pub async fn set_container_locked(
+    name: String,
+    locked: bool,
+    config_state: tauri::State<'_, ConfigState>,
+) -> Result<(), String> {
+    let minutes = {
+        let config = config_state.config.lock().await;
+        if !config.containers.contains_key(&name) {
+            return Err("Container does not exist".to_string());
+        }
+        config.unlock_timeout_mins
+    };
+    if locked && !os_auth::available() {
+        return Err("No Windows Hello or Touch ID set up to lock it with".to_string());
+    }
+    let reason = if locked { format!("lock \"{}\"", name) } else { format!("remove the lock on \"{}\"", name) };
+    os_auth::verify(&reason).await.map_err(|e| e.to_string())?;
+    {
+        let mut config = config_state.config.lock().await;
+        let info = config.containers.get_mut(&name).ok_or("Container does not exist")?;
+        info.locked = locked;
+    }
+    info!("set_container_locked: name=\"{}\" locked={}", name, locked);
+    config_state.save().await?;
+    // Just verified, so it stays open for now.
+    if locked {
+        os_auth::unlock(&name, minutes);
+    } else {
+        os_auth::forget(&name);
+    }
     Ok(())
 }
 
@@ -130,6 +269,7 @@ pub async fn set_active_container(
 
     drop(config);
     config_state.save().await?;
+    tray::refresh(&app).await;
 
     let ps = provider_state.inner().clone();
     let app_clone = app.clone();
@@ -139,26 +279,24 @@ pub async fn set_active_container(
         guard.provider = None;
         guard.init_error = None;
     }
+    endpoint_health::record(&app, EndpointHealth::default());
 
     match provider_config {
         EmbeddingProviderConfig::Local { ref model } => {
             let model_enum = crate::config::get_embedding_model(model);
-            let app_data = app_clone.path().app_data_dir().map_err(|e| e.to_string())?;
+            let app_data = crate::config::app_data_dir();
             let models_path = app_data.join("models");
+            let (load_model_enum, load_models_path) = (model_enum.clone(), models_path.clone());
+            let download = model_download::watch(&app, model, models_path.clone());
             let load_result = tokio::task::spawn_blocking(move || {
-                indexer::load_model(model_enum, models_path)
+                indexer::load_model(load_model_enum, load_models_path)
             }).await.map_err(|e| e.to_string())?;
+            drop(download);
 
             match load_result {
                 Ok(model) => {
                     use crate::indexer::embedding_provider::LocalProvider;
-                    use crate::state::ModelState;
-                    let model_state = Arc::new(Mutex::new(ModelState {
-                        model: Some(model),
-                        init_error: None,
-                        cached_dim: None,
-                    }));
-                    let provider = LocalProvider { model_state };
+                    let provider = LocalProvider::new(model, model_enum, models_path);
                     let mut guard = ps.lock().await;
                     guard.provider = Some(Box::new(provider));
                     guard.init_error = None;
@@ -180,41 +318,397 @@ pub async fn set_active_container(
             guard.init_error = None;
             let _ = app.emit("model-loaded", ());
             info!("Provider switched to remote: {}", rc.model);
+            endpoint_health::check_soon(ps.clone(), app.clone());
         }
     }
 
+    crate::connect_active_db(&app).await;
     let db = {
         let guard = db_state.lock().await;
-        guard.db.clone()
+        guard.connection()?
     };
     watcher::restart(
         watcher_state.inner(),
         config_state.inner(),
         db,
         provider_state.inner().clone(),
-        app,
+        app.clone(),
     ).await;
 
+    // Brings in the annotations and folders other devices added to it.
+    if !config_state.config.lock().await.sync.folder.is_empty() {
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) = crate::sync::run(&app).await {
+                warn!("Sync failed: {}", e);
+            }
+        });
+    }
+
     Ok(())
 }
 
+fn pinned_paths(config: &Config, container: &str) -> Vec<String> {
+    config.containers.get(container)
+        .map(|info| info.pinned_paths.clone())
+        .unwrap_or_default()
+}
+
+/// Whether `path` ends in one of `exts`, given with or without the dot.
+fn has_extension(path: &str, exts: &[String]) -> bool {
+    let ext = std::path::Path::new(path).extension().and_then(|e| e.to_str()).unwrap_or("");
+    exts.iter().any(|e| e.trim_start_matches('.').eq_ignore_ascii_case(ext))
+}
+
+fn to_search_results(
+    scored: Vec<indexer::pipeline::ScoredResult>,
+    pinned: &[String],
+    terms: &[String],
+    snippet_length: usize,
+) -> Vec<SearchResult> {
+    scored
+        .into_iter()
+        .map(|r| {
+            let (context, snippet) = indexer::pipeline::split_context(&r.snippet);
+            SearchResult {
+                pinned: pinned.contains(&r.path),
+                page: indexer::pipeline::page_number(&r.path, &context),
+                path: r.path,
+                excerpt: indexer::snippet::excerpt(indexer::snippet::body(&snippet), terms, snippet_length),
+                snippet,
+                context,
+                score: r.score,
+                deleted: false,
+                unindexed: false,
+                remote: None,
+            }
+        })
+        .collect()
+}
+
+#[tauri::command]
+pub async fn record_open(
+    path: String,
+    snippet: String,
+    db_state: tauri::State<'_, Arc<Mutex<DbState>>>,
+    config_state: tauri::State<'_, ConfigState>,
+) -> Result<(), String> {
+    let table_name = {
+        let config = config_state.config.lock().await;
+        if !config.recent_files_enabled || !config.can_write(&config.active_container) {
+            return Ok(());
+        }
+        get_table_name(&config.active_container)
+    };
+    let db = {
+        let guard = db_state.lock().await;
+        guard.connection()?
+    };
+    history::record_open(&db, &table_name, &path, &snippet)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_recent(
+    db_state: tauri::State<'_, Arc<Mutex<DbState>>>,
+    config_state: tauri::State<'_, ConfigState>,
+) -> Result<Vec<SearchResult>, String> {
+    let (table_name, pinned, max_results, excluded, snippet_length) = {
+        let config = config_state.config.lock().await;
+        if !config.recent_files_enabled {
+            return Ok(vec![]);
+        }
+        let excluded = config.containers.get(&config.active_container)
+            .map(|info| info.excluded_paths.clone())
+            .unwrap_or_default();
+        (
+            get_table_name(&config.active_container),
+            pinned_paths(&config, &config.active_container),
+            config.window.clamped().max_visible_results,
+            excluded,
+            config.snippet_length,
+        )
+    };
+    let db = {
+        let guard = db_state.lock().await;
+        guard.connection()?
+    };
+    let recent = history::get_recent(&db, &table_name, max_results * 2)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let top = recent.first().map_or(1.0, |r| r.frecency.max(1.0));
+    let scored = recent
+        .into_iter()
+        .filter(|r| !is_path_excluded(&excluded, &r.path) && std::path::Path::new(&r.path).exists())
+        .take(max_results)
+        .map(|r| indexer::pipeline::ScoredResult {
+            score: r.frecency / top * 100.0,
+            path: r.path,
+            snippet: r.snippet,
+        })
+        .collect();
+    Ok(to_search_results(scored, &pinned, &[], snippet_length))
+}
+
+#[tauri::command]
+pub async fn clear_recent(
+    db_state: tauri::State<'_, Arc<Mutex<DbState>>>,
+    config_state: tauri::State<'_, ConfigState>,
+) -> Result<(), String> {
+    ensure_writable(&config_state).await?;
+    let table_name = {
+        let config = config_state.config.lock().await;
+        get_table_name(&config.active_container)
+    };
+    let db = {
+        let guard = db_state.lock().await;
+        guard.connection()?
+    };
+    history::clear_history(&db, &table_name)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn toggle_pin(
+    path: String,
+    config_state: tauri::State<'_, ConfigState>,
+) -> Result<bool, String> {
+    let pinned = {
+        let mut config = config_state.config.lock().await;
+        let active = config.active_container.clone();
+        let info = config.containers.get_mut(&active).ok_or("Active container not found")?;
+        if let Some(pos) = info.pinned_paths.iter().position(|p| p == &path) {
+            info.pinned_paths.remove(pos);
+            false
+        } else {
+            info.pinned_paths.push(path.clone());
+            true
+        }
+    };
+    info!("toggle_pin: path=\"{}\" pinned={}", path, pinned);
+    config_state.save().await?;
+    Ok(pinned)
+}
+
+/// Desktop search matches outside the container's folders, ranked into
+/// `merged` after what's already there the way annotations are, with the
+/// file name as their snippet. Returns the paths that went in.
+fn merge_unindexed(
+    merged: &mut Vec<(String, String, f32)>,
+    candidates: Vec<String>,
+    keep: impl Fn(&str) -> bool,
+) -> HashSet<String> {
+    let known: HashSet<String> = merged.iter().map(|(path, _, _)| path.clone()).collect();
+    let mut added = HashSet::new();
+    for path in candidates.into_iter().filter(|path| !known.contains(path) && keep(path)) {
+        let name = std::path::Path::new(&path).file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+        let rrf_score = 1.0 / (60.0 + added.len() as f32 + 1.0);
+        merged.push((path.clone(), format!("File: {}", name), rrf_score));
+        added.insert(path);
+    }
+    merged.sort_by(|a, b| b.2.partial_cmp(&a.2).unwrap_or(std::cmp::Ordering::Equal));
+    added
+}
+
+type RemoteLookup = tauri::async_runtime::JoinHandle<Result<(RemoteSource, Vec<remote::proto::SearchResult>), String>>;
+
+/// What the remote containers found, as search results. One that fails or
+/// doesn't answer in time is left out.
+async fn remote_results(lookups: Vec<RemoteLookup>, terms: &[String], snippet_length: usize) -> Vec<SearchResult> {
+    let mut results = Vec::new();
+    for lookup in lookups {
+        match lookup.await {
+            Ok(Ok((source, found))) => results.extend(found.into_iter().map(|r| SearchResult {
+                excerpt: indexer::snippet::excerpt(indexer::snippet::body(&r.snippet), terms, snippet_length),
+                path: r.path,
+                snippet: r.snippet,
+                context: r.context,
+                page: None,
+                score: r.score,
+                pinned: false,
+                deleted: false,
+                unindexed: false,
+                remote: Some(source.clone()),
+            })),
+            Ok(Err(e)) => warn!("Remote search failed: {}", e),
+            Err(e) => warn!("Remote search task failed: {}", e),
+        }
+    }
+    results.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
+    results
+}
+
+/// Ranks `remote` into `results` by score, below the pinned ones and
+/// without reordering what's there, `max_results` in all.
+fn merge_remote(results: &mut Vec<SearchResult>, remote: Vec<SearchResult>, max_results: usize) {
+    if remote.is_empty() {
+        return;
+    }
+    let local = std::mem::take(results);
+    let mut remote = remote.into_iter().peekable();
+    for result in local {
+        while let Some(found) = remote.next_if(|found| !result.pinned && found.score > result.score) {
+            results.push(found);
+        }
+        results.push(result);
+    }
+    results.extend(remote);
+    results.truncate(max_results);
+}
+
+async fn note_matches(config_state: &ConfigState, container: &str, results: &[SearchResult]) {
+    let tracked = {
+        let config = config_state.config.lock().await;
+        config.can_write(container)
+            && config.containers.get(container).is_some_and(|info| {
+                info.quota_mb.is_some() && info.quota_policy == QuotaPolicy::Evict
+            })
+    };
+    if tracked && !results.is_empty() {
+        quota::record_matches(&get_table_name(container), results.iter().map(|r| r.path.as_str()));
+    }
+}
+
 #[tauri::command]
 pub async fn search(
+    app: tauri::AppHandle,
     query: String,
+    language: Option<String>,
+    as_of: Option<i64>,
+    include_deleted: Option<bool>,
+    container: Option<String>,
+    path_prefix: Option<String>,
+    file_extensions: Option<Vec<String>>,
     db_state: tauri::State<'_, Arc<Mutex<DbState>>>,
     provider_state: tauri::State<'_, Arc<Mutex<ProviderState>>>,
     reranker_state: tauri::State<'_, Arc<Mutex<RerankerState>>>,
     config_state: tauri::State<'_, ConfigState>,
+    cache_state: tauri::State<'_, Arc<Mutex<ResultCache>>>,
 ) -> Result<Vec<SearchResult>, String> {
-    debug!("search: query=\"{}\"", query);
-    let table_name = {
+    let scope = indexer::SearchScope { as_of, include_deleted: include_deleted.unwrap_or(false) };
+    // `in:src/indexer` or `size:>10mb` in the query narrows this one search.
+    let (query, query_filters) = indexer::query_filters::parse(&query);
+    if query.is_empty() {
+        return Ok(Vec::new());
+    }
+    // A folder chip gives the folder itself; only what's inside it counts.
+    let path_prefix = path_prefix.filter(|p| !p.is_empty()).map(|p| {
+        if p.ends_with(['/', '\\']) { p } else { format!("{}{}", p, std::path::MAIN_SEPARATOR) }
+    });
+    let file_extensions = file_extensions.filter(|e| !e.is_empty());
+    debug!(
+        "search: query=\"{}\" language={:?} scope={:?} container={:?} path_prefix={:?} query_filters={:?} file_extensions={:?}",
+        query, language, scope, container, path_prefix, query_filters, file_extensions
+    );
+    // A container picked by route_container; anything it couldn't have
+    // picked falls back to the active one.
+    let container = {
         let config = config_state.config.lock().await;
-        get_table_name(&config.active_container)
+        let container = container
+            .filter(|name| config.auto_container_candidates().contains(name))
+            .unwrap_or_else(|| config.active_container.clone());
+        // Before the cache, which would hand back what the last unlock saw.
+        os_auth::ensure_unlocked(&config, &container)?;
+        container
+    };
+    let table_name = get_table_name(&container);
+    // Remote containers are searched while this one is. Folders, filters
+    // and history only mean something on this machine.
+    let remote_lookups: Vec<RemoteLookup> = {
+        let config = config_state.config.lock().await;
+        let narrowed = path_prefix.is_some() || !query_filters.is_empty() || scope.as_of.is_some() || scope.include_deleted;
+        if narrowed || crate::config::is_offline() {
+            Vec::new()
+        } else {
+            let top_k = config.window.clamped().max_visible_results;
+            config
+                .remote_containers
+                .iter()
+                .cloned()
+                .map(|container| {
+                    let query = query.clone();
+                    let file_extensions = file_extensions.clone().unwrap_or_default();
+                    tauri::async_runtime::spawn(async move { remote::search(&container, &query, top_k, file_extensions).await })
+                })
+                .collect()
+        }
+    };
+
+    let (retrieval, query_router_enabled, max_results, pinned, expansion, display_path, snippet_length, cache_key) = {
+        let config = config_state.config.lock().await;
+        let retrieval = config.retrieval_for(&container);
+        let normalized = query.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase();
+        let cache_key = format!(
+            "{}\n{}\n{:?}\n{:?}\n{:?}\n{:?}",
+            table_name,
+            normalized,
+            language,
+            scope,
+            (&path_prefix, &query_filters, &file_extensions),
+            (
+                &retrieval,
+                config.query_router_enabled,
+                config.window.clamped().max_visible_results,
+                pinned_paths(&config, &container),
+                config.fts_stemming,
+                &config.synonyms,
+                config.indexing.display_path,
+                config.snippet_length,
+                config.desktop_search,
+            ),
+        );
+        (
+            retrieval,
+            config.query_router_enabled,
+            config.window.clamped().max_visible_results,
+            pinned_paths(&config, &container),
+            indexer::QueryExpansion::new(config.fts_stemming, &config.synonyms),
+            config.indexing.display_path,
+            config.snippet_length,
+            cache_key,
+        )
+    };
+
+    let db = {
+        let guard = db_state.lock().await;
+        guard.connection()?
+    };
+    // While a job runs this is the version from before it, so cached
+    // results stay good until its changes land.
+    let index_version: Option<IndexVersion> = match indexer::snapshot::open(&db, &table_name).await {
+        Ok(table) => match table.version().await {
+            Ok(v) => Some((v, annotations::annotations_version(&db, &table_name).await)),
+            Err(_) => None,
+        },
+        Err(_) => None,
     };
+    if let Some(version) = index_version {
+        if let Some(mut results) = cache_state.lock().await.get(&cache_key, version) {
+            debug!("search: {} results from cache", results.len());
+            note_matches(&config_state, &container, &results).await;
+            let terms = indexer::snippet::query_terms(&query, &expansion, language.as_deref());
+            merge_remote(&mut results, remote_results(remote_lookups, &terms, snippet_length).await, max_results);
+            return Ok(results);
+        }
+    }
 
-    let (hyde_config, query_router_enabled, mmr_enabled, mmr_lambda) = {
+    // File names from a desktop search index are looked up while the
+    // container is searched.
+    let (desktop_lookup, roots, excluded) = {
         let config = config_state.config.lock().await;
-        (config.hyde.clone(), config.query_router_enabled, config.mmr_enabled, config.mmr_lambda)
+        let source = config.desktop_search;
+        let limit = config.window.clamped().max_visible_results;
+        let lookup_query = query.clone();
+        let lookup = (source != DesktopSearch::Off)
+            .then(|| tokio::task::spawn_blocking(move || desktop_search::candidates(source, &lookup_query, limit)));
+        let info = config.containers.get(&container);
+        (
+            lookup,
+            info.map(|info| info.indexed_paths.clone()).unwrap_or_default(),
+            info.map(|info| info.excluded_paths.clone()).unwrap_or_default(),
+        )
     };
 
     let query_weights = if query_router_enabled {
@@ -225,11 +719,11 @@ pub async fn search(
     };
 
     debug!("search: features: router={}, mmr={} (λ={:.2}), hyde={}",
-        query_router_enabled, mmr_enabled, mmr_lambda,
-        hyde_config.as_ref().is_some_and(|h| h.enabled));
+        query_router_enabled, retrieval.mmr_enabled, retrieval.mmr_lambda,
+        retrieval.hyde.as_ref().is_some_and(|h| h.enabled));
 
     let hyde_doc = indexer::hyde::maybe_generate(
-        hyde_config.as_ref(),
+        retrieval.hyde.as_ref(),
         &query,
         query_weights.use_hyde,
     ).await;
@@ -240,37 +734,72 @@ pub async fn search(
             return Err(format!("Embedding provider failed: {}", err));
         }
         let provider = guard.provider.as_ref().ok_or("Embedding provider is loading... Please wait a moment.")?;
+        if !provider.is_resident().await {
+            let _ = app.emit("model-warming", ());
+        }
 
-        if let Some(ref doc) = hyde_doc {
+        let embedded = if let Some(ref doc) = hyde_doc {
             debug!("search: using HyDE embedding for conceptual query");
-            let vecs = provider.embed_passages(vec![doc.clone()]).await
-                .map_err(|e| {
-                    error!("HyDE passage embedding failed: {}", e);
-                    e.to_string()
-                })?;
-            vecs.into_iter().next().ok_or("HyDE embedding returned empty")?
+            provider.embed_passages(vec![doc.clone()]).await.and_then(|vecs| {
+                vecs.into_iter().next().ok_or_else(|| anyhow::anyhow!("HyDE embedding returned empty"))
+            })
         } else {
             provider.embed_query(&query).await
-                .map_err(|e| {
-                    error!("Query embedding failed: {}", e);
-                    e.to_string()
-                })?
+        };
+        if provider.is_remote() && !crate::config::is_offline() {
+            endpoint_health::record(&app, endpoint_health::from_result(&embedded));
+        }
+        match embedded {
+            Ok(vector) => Some(vector),
+            // With the endpoint down, full-text matches beat an error.
+            // Searching the past needs vectors, so that still fails.
+            Err(e) if provider.is_remote() && scope.as_of.is_none() => {
+                warn!("Query embedding failed, searching full text only: {}", e);
+                None
+            }
+            Err(e) => {
+                error!("Query embedding failed: {}", e);
+                return Err(e.to_string());
+            }
         }
     };
 
-    let db = {
-        let guard = db_state.lock().await;
-        guard.db.clone()
+    let (mut merged, used_hybrid) = match query_vector.as_deref() {
+        Some(query_vector) => indexer::search_pipeline(
+            &db, &table_name, &query, query_vector, 50, path_prefix.as_deref(), Some(&query_filters), file_extensions.as_deref(), language.as_deref(), &expansion,
+            query_weights.vector_weight, query_weights.fts_weight, scope,
+        )
+        .await
+        .map_err(|e| e.to_string())?,
+        None => {
+            let results = indexer::search_fts_only(
+                &db, &table_name, &query, 50, path_prefix.as_deref(), Some(&query_filters), file_extensions.as_deref(), language.as_deref(), &expansion,
+            )
+            .await
+            .map_err(|e| e.to_string())?;
+            (results, true)
+        }
     };
 
-    let (mut merged, used_hybrid) = indexer::search_pipeline(
-        &db, &table_name, &query, &query_vector, 50, None, None,
-        query_weights.vector_weight, query_weights.fts_weight,
-    )
-    .await
-    .map_err(|e| e.to_string())?;
-
-    if let Ok(ann_results) = annotations::search_annotations(&db, &table_name, &query_vector, 10).await {
+    let ann_results = match query_vector.as_deref() {
+        Some(query_vector) => {
+            let summaries = indexer::summaries::search_summaries(&db, &table_name, query_vector, indexer::summaries::SEARCH_HITS)
+                .await
+                .unwrap_or_default();
+            annotations::search_annotations(&db, &table_name, query_vector, 10).await.ok().map(|mut found| {
+                found.extend(summaries);
+                found.sort_by(|a, b| a.2.partial_cmp(&b.2).unwrap_or(std::cmp::Ordering::Equal));
+                found
+            })
+        }
+        None => None,
+    };
+    if let Some(mut ann_results) = ann_results {
+        ann_results.retain(|(path, _, _)| {
+            path_prefix.as_deref().is_none_or(|prefix| path.starts_with(prefix))
+                && query_filters.matches_path(path)
+                && file_extensions.as_deref().is_none_or(|exts| has_extension(path, exts))
+        });
         if used_hybrid {
             for (rank, (path, note, _dist)) in ann_results.into_iter().enumerate() {
                 let rrf_score = 1.0 / (60.0 + rank as f32 + 1.0);
@@ -284,17 +813,48 @@ pub async fn search(
             merged.sort_by(|a, b| a.2.partial_cmp(&b.2).unwrap_or(std::cmp::Ordering::Equal));
         }
     }
+    // Only RRF scores leave room for them beside what the index found.
+    let unindexed = match desktop_lookup {
+        Some(lookup) if used_hybrid => {
+            let candidates = lookup.await.unwrap_or_default();
+            merge_unindexed(&mut merged, candidates, |path| {
+                !roots.iter().any(|root| std::path::Path::new(path).starts_with(root))
+                    && !is_path_excluded(&excluded, path)
+                    && path_prefix.as_deref().is_none_or(|prefix| path.starts_with(prefix))
+                    && query_filters.matches_path(path)
+                    && file_extensions.as_deref().is_none_or(|exts| has_extension(path, exts))
+            })
+        }
+        _ => HashSet::new(),
+    };
+    indexer::identity::dedupe_results(&mut merged, display_path);
 
     let rerank_input: Vec<(String, String, f32)> = merged.into_iter().take(15).collect();
 
-    let reranker_enabled = {
-        let config = config_state.config.lock().await;
-        config.use_reranker
-    };
-
-    let (final_results, used_reranker) = if reranker_enabled {
+    let (final_results, used_reranker) = if retrieval.use_reranker {
+        // Nothing loads it at startup when only some containers want it.
+        let loaded_at_startup = config_state.config.lock().await.use_reranker;
         let mut guard = reranker_state.lock().await;
+        let load = guard.unloaded || (!loaded_at_startup && guard.init_error.is_none());
+        if guard.reranker.is_none() && load {
+            let _ = app.emit("model-warming", ());
+            let models_path = crate::config::app_data_dir().join("models");
+            let _download = model_download::watch(&app, model_download::RERANKER, models_path.clone());
+            match tokio::task::spawn_blocking(move || indexer::load_reranker(models_path)).await {
+                Ok(Ok(reranker)) => {
+                    info!("Reranker loaded for search");
+                    guard.reranker = Some(reranker);
+                    guard.unloaded = false;
+                }
+                Ok(Err(e)) => {
+                    warn!("Reranker load failed: {}", e);
+                    guard.init_error = Some(e.to_string());
+                }
+                Err(e) => warn!("Reranker reload task failed: {}", e),
+            }
+        }
         if let Some(reranker) = guard.reranker.take() {
+            guard.last_used = std::time::Instant::now();
             let (reranker_back, results, used) =
                 indexer::safe_rerank(reranker, query.clone(), rerank_input.clone()).await;
             guard.reranker = reranker_back;
@@ -310,22 +870,99 @@ pub async fn search(
         (rerank_input, false)
     };
 
-    let scored = indexer::pipeline::score_results(final_results, used_reranker, used_hybrid, 20);
-    let scored = if mmr_enabled {
-        indexer::pipeline::mmr_select(scored, 10, mmr_lambda)
+    let scored = indexer::pipeline::score_results(final_results, used_reranker, used_hybrid, (max_results * 2).max(20));
+    let (mut scored, rest) = indexer::pipeline::split_pinned(scored, &pinned, indexer::pipeline::PIN_SCORE_FLOOR);
+    scored.truncate(max_results);
+    let remaining = max_results - scored.len();
+    scored.extend(if retrieval.mmr_enabled {
+        indexer::pipeline::mmr_select(rest, remaining, retrieval.mmr_lambda)
     } else {
-        scored.into_iter().take(10).collect()
-    };
+        rest.into_iter().take(remaining).collect()
+    });
     debug!("search: {} results, hybrid={}, reranker={}", scored.len(), used_hybrid, used_reranker);
 
-    Ok(scored
-        .into_iter()
-        .map(|r| SearchResult {
-            path: r.path,
-            snippet: r.snippet,
-            score: r.score,
-        })
-        .collect())
+    let terms = indexer::snippet::query_terms(&query, &expansion, language.as_deref());
+    let mut results = to_search_results(scored, &pinned, &terms, snippet_length);
+    if scope.include_deleted {
+        for result in &mut results {
+            result.deleted = !std::path::Path::new(&result.path).exists();
+        }
+    }
+    for result in &mut results {
+        result.unindexed = unindexed.contains(&result.path);
+    }
+    // Full-text-only results would outlive the outage in the cache, and
+    // files found outside the index can change without the index knowing.
+    if let Some(version) = index_version.filter(|_| query_vector.is_some() && unindexed.is_empty()) {
+        cache_state.lock().await.insert(cache_key, version, results.clone());
+    }
+    note_matches(&config_state, &container, &results).await;
+    merge_remote(&mut results, remote_results(remote_lookups, &terms, snippet_length).await, max_results);
+    Ok(results)
+}
+
+/// The container `query` looks to be about when auto container is on and
+/// it's not the active one. Best effort: None whenever it can't tell.
+#[tauri::command]
+pub async fn route_container(
+    query: String,
+    provider_state: tauri::State<'_, Arc<Mutex<ProviderState>>>,
+    config_state: tauri::State<'_, ConfigState>,
+) -> Result<Option<String>, String> {
+    let (active, model, candidates) = {
+        let config = config_state.config.lock().await;
+        if !config.auto_container || query.trim().is_empty() {
+            return Ok(None);
+        }
+        let active = config.active_container.clone();
+        let model = config.provider_label(&active);
+        (active, model, config.auto_container_candidates())
+    };
+    if candidates.len() < 2 {
+        return Ok(None);
+    }
+
+    let query_vector = {
+        let guard = provider_state.lock().await;
+        let Some(provider) = guard.provider.as_ref() else {
+            return Ok(None);
+        };
+        match provider.embed_query(&query).await {
+            Ok(vector) => vector,
+            Err(e) => {
+                debug!("route_container: query embedding failed: {}", e);
+                return Ok(None);
+            }
+        }
+    };
+    let routed = indexer::centroid::route(&query_vector, &model, &active, &candidates);
+    debug!("route_container: query=\"{}\" routed={:?}", query, routed);
+    Ok(routed)
+}
+
+#[tauri::command]
+pub async fn quick_answer(query: String) -> Result<Option<QuickAnswer>, String> {
+    Ok(calculator::quick_answer(&query))
+}
+
+/// Top results as one markdown block for pasting into a chat, cut to the
+/// configured token budget.
+#[tauri::command]
+pub async fn build_context_block(
+    query: String,
+    items: Vec<ContextItem>,
+    config_state: tauri::State<'_, ConfigState>,
+) -> Result<ContextBlock, String> {
+    let max_tokens = config_state.config.lock().await.context_copy_tokens;
+    let block = context_block::build(&query, &items, max_tokens);
+    debug!("build_context_block: {} of {} results, ~{} tokens", block.included, items.len(), block.tokens);
+    Ok(block)
+}
+
+/// Tells webhooks and the post-index command that a job finished or failed.
+fn job_event(app: &tauri::AppHandle, event: IndexEvent) {
+    post_index::run(app, &event);
+    webhooks::send(app, event);
 }
 
 #[tauri::command]
@@ -337,6 +974,10 @@ pub async fn index_folder(
     config_state: tauri::State<'_, ConfigState>,
     watcher_state: tauri::State<'_, watcher::WatcherState>,
 ) -> Result<String, String> {
+    ensure_writable(&config_state).await?;
+    if let Some(message) = crate::quota_block(&app).await {
+        return Err(message);
+    }
     info!("index_folder: dir=\"{}\"", dir);
     let table_name = {
         let config = config_state.config.lock().await;
@@ -357,28 +998,47 @@ pub async fn index_folder(
 
     let db = {
         let guard = db_state.lock().await;
-        guard.db.clone()
+        guard.connection()?
     };
 
     let ps = provider_state.inner().clone();
     let app_handle = app.clone();
 
-    let indexing_config = {
+    let (indexing_config, excluded_paths) = {
         let config = config_state.config.lock().await;
-        config.indexing.clone()
+        let mut excluded = config.containers.get(&config.active_container)
+            .map(|info| info.excluded_paths.clone())
+            .unwrap_or_default();
+        excluded.extend(quota::load_log(&table_name).evicted);
+        (config.indexing_for(&config.active_container), excluded)
     };
 
-    let count = indexer::index_directory(&dir, &table_name, &db, &ps, &indexing_config, move |current, total, path| {
-        let _ = app_handle.emit("indexing-progress", IndexingProgress { current, total, path });
+    let started = std::time::Instant::now();
+    journal::begin(&table_name, std::slice::from_ref(&dir));
+    let snapshot = indexer::snapshot::pin(&db, &table_name).await;
+    let count = indexer::index_directory(&dir, &table_name, &db, &ps, &indexing_config, &excluded_paths, move |progress| {
+        let _ = app_handle.emit("indexing-progress", progress);
     })
     .await
-    .map_err(|e| e.to_string())?;
+    .map_err(|e| {
+        let e = e.to_string();
+        let _ = app.emit("indexing-failed", e.clone());
+        notifications::job_failed(&app, &dir, &e, started.elapsed());
+        job_event(&app, IndexEvent::Error { folder: dir.clone(), error: e.clone(), elapsed: started.elapsed() });
+        e
+    })?;
+    journal::finish(&table_name);
+    drop(snapshot);
 
     let _ = app.emit("indexing-complete", format!("{} files indexed", count));
+    notifications::job_finished(&app, std::slice::from_ref(&dir), count, started.elapsed());
+    job_event(&app, IndexEvent::Complete { folders: vec![dir.clone()], files: count, elapsed: started.elapsed() });
+    crate::enforce_quota(&app).await;
+    crate::refresh_centroid(&app);
 
     let db2 = {
         let guard = db_state.lock().await;
-        guard.db.clone()
+        guard.connection()?
     };
     watcher::restart(
         watcher_state.inner(),
@@ -396,19 +1056,24 @@ pub async fn reset_index(
     db_state: tauri::State<'_, Arc<Mutex<DbState>>>,
     config_state: tauri::State<'_, ConfigState>,
 ) -> Result<String, String> {
+    ensure_writable(&config_state).await?;
     info!("reset_index");
     let table_name = {
         let config = config_state.config.lock().await;
         get_table_name(&config.active_container)
     };
 
-    let path = {
+    let db = {
         let guard = db_state.lock().await;
-        guard.path.clone()
+        guard.connection()?
     };
-    indexer::reset_index(&path, &table_name)
+    indexer::reset_index(&db, &table_name)
         .await
         .map_err(|e| e.to_string())?;
+    let _ = indexer::reset_index(&db, &rebuild_table_name(&table_name)).await;
+    quota::clear_log(&table_name);
+    journal::finish(&table_name);
+    journal::finish(&rebuild_table_name(&table_name));
     Ok("Index cleared successfully".to_string())
 }
 
@@ -419,6 +1084,10 @@ pub async fn reindex_all(
     provider_state: tauri::State<'_, Arc<Mutex<ProviderState>>>,
     config_state: tauri::State<'_, ConfigState>,
 ) -> Result<String, String> {
+    ensure_writable(&config_state).await?;
+    if let Some(message) = crate::quota_block(&app).await {
+        return Err(message);
+    }
     info!("reindex_all");
     let (table_name, paths) = {
         let config = config_state.config.lock().await;
@@ -431,45 +1100,614 @@ pub async fn reindex_all(
         return Err("No folders to reindex".to_string());
     }
 
+    // Everything is embedded again into a separate table that replaces the
+    // container's only once it's complete. A rebuild a crash left behind is
+    // started over.
+    let rebuild = rebuild_table_name(&table_name);
     let db = {
         let guard = db_state.lock().await;
-        guard.db.clone()
+        guard.connection()?
     };
+    indexer::reset_index(&db, &rebuild).await.map_err(|e| e.to_string())?;
+    journal::finish(&rebuild);
+    journal::begin(&rebuild, &paths);
+    let total = index_folders(&app, &rebuild, &paths, &db_state, &provider_state, &config_state).await?;
+    Ok(format!("Reindexed {} files from {} folders", total, paths.len()))
+}
 
-    let ps = provider_state.inner().clone();
-
-    let indexing_config = {
+/// Indexes `paths` one after another as a single job into `target`, the
+/// active container's table or its rebuild, whose journal `begin` already
+/// started. Folders are ticked off in the journal as they finish, so a
+/// resumed job skips them. A rebuild replaces the container's table at the
+/// end.
+async fn index_folders(
+    app: &tauri::AppHandle,
+    target: &str,
+    paths: &[String],
+    db_state: &Mutex<DbState>,
+    provider_state: &Arc<Mutex<ProviderState>>,
+    config_state: &ConfigState,
+) -> Result<usize, String> {
+    let (table_name, excluded_paths, indexing_config) = {
         let config = config_state.config.lock().await;
-        config.indexing.clone()
+        let info = config.containers.get(&config.active_container)
+            .ok_or("Active container not found")?;
+        (get_table_name(&config.active_container), info.excluded_paths.clone(), config.indexing_for(&config.active_container))
     };
+    let mut excluded_paths = excluded_paths;
+    excluded_paths.extend(quota::load_log(&table_name).evicted);
 
-    let mut total = 0;
-    for dir in &paths {
-        let app_handle = app.clone();
-        let count = indexer::index_directory(dir, &table_name, &db, &ps, &indexing_config, move |current, total, path| {
-            let _ = app_handle.emit("indexing-progress", IndexingProgress { current, total, path });
-        })
-        .await
-        .map_err(|e| e.to_string())?;
-        total += count;
+    let db = {
+        let guard = db_state.lock().await;
+        guard.connection()?
+    };
+
+    let started = std::time::Instant::now();
+    let failed = |folder: &str, e: String| {
+        let _ = app.emit("indexing-failed", e.clone());
+        notifications::job_failed(app, folder, &e, started.elapsed());
+        job_event(app, IndexEvent::Error { folder: folder.to_string(), error: e.clone(), elapsed: started.elapsed() });
+        e
+    };
+    let snapshot = indexer::snapshot::pin(&db, target).await;
+    let app_handle = app.clone();
+    let total = indexer::index_directories(paths, target, &db, provider_state, &indexing_config, &excluded_paths, move |progress| {
+        let _ = app_handle.emit("indexing-progress", progress);
+    })
+    .await
+    .map_err(|e| failed(&e.folder, e.error.to_string()))?;
+    drop(snapshot);
+    if target != table_name {
+        let _ = app.emit("indexing-progress", IndexingProgress::new(total, total, "Swapping in the rebuilt index..."));
+        let _writes = crate::watcher::replay(&db, target, provider_state, config_state).await;
+        indexer::rebuild::swap_in(&db, &table_name)
+            .await
+            .map_err(|e| failed(&paths.join(", "), e.to_string()))?;
     }
+    journal::finish(target);
 
     let _ = app.emit("indexing-complete", format!("{} files reindexed from {} folders", total, paths.len()));
+    notifications::job_finished(app, paths, total, started.elapsed());
+    job_event(app, IndexEvent::Complete { folders: paths.to_vec(), files: total, elapsed: started.elapsed() });
+    crate::enforce_quota(app).await;
+    crate::refresh_centroid(app);
 
-    Ok(format!("Reindexed {} files from {} folders", total, paths.len()))
+    Ok(total)
+}
+
+/// Indexes folders another device added to the active container, see
+/// `sync`, and watches them from then on.
+pub(crate) async fn index_synced_folders(app: &tauri::AppHandle, paths: &[String]) -> Result<usize, String> {
+    let config_state = app.state::<ConfigState>();
+    ensure_writable(&config_state).await?;
+    let table_name = get_table_name(&config_state.config.lock().await.active_container);
+    if let Some(message) = crate::quota_block(app).await {
+        return Err(message);
+    }
+    journal::begin(&table_name, paths);
+    let db_state = app.state::<Arc<Mutex<DbState>>>();
+    let provider_state = app.state::<Arc<Mutex<ProviderState>>>();
+    let total = index_folders(app, &table_name, paths, &db_state, &provider_state, &config_state).await?;
+    crate::restart_watcher_when_ready(app).await;
+    Ok(total)
 }
 
 #[derive(Serialize)]
-pub struct AppConfig {
-    pub always_on_top: bool,
-    pub launch_at_startup: bool,
-    pub hotkey: String,
-    pub use_git_history: bool,
-    pub embedding_model: String,
-    pub chunk_size: Option<usize>,
-    pub chunk_overlap: Option<usize>,
-    pub extra_extensions: Vec<String>,
-    pub excluded_extensions: Vec<String>,
+pub struct InterruptedJob {
+    pub folders: Vec<String>,
+    pub started_at: i64,
+}
+
+/// The journal of the active container's unfinished job, with the table it
+/// writes to: the container's own or its rebuild.
+fn unfinished_job(table_name: &str) -> Option<(String, journal::Journal)> {
+    [table_name.to_string(), rebuild_table_name(table_name)]
+        .into_iter()
+        .find_map(|target| journal::load(&target).map(|job| (target, job)))
+}
+
+#[tauri::command]
+pub async fn get_interrupted_job(config_state: tauri::State<'_, ConfigState>) -> Result<Option<InterruptedJob>, String> {
+    let table_name = {
+        let config = config_state.config.lock().await;
+        if !config.can_write(&config.active_container) {
+            return Ok(None);
+        }
+        get_table_name(&config.active_container)
+    };
+    Ok(unfinished_job(&table_name)
+        .filter(|(target, job)| job.is_interrupted() || (*target != table_name && job.is_stale()))
+        .map(|(_, job)| job)
+        .map(|job| InterruptedJob { folders: job.remaining_folders().to_vec(), started_at: job.started_at }))
+}
+
+/// Files the job had finished are skipped as up to date.
+#[tauri::command]
+pub async fn resume_indexing(
+    app: tauri::AppHandle,
+    db_state: tauri::State<'_, Arc<Mutex<DbState>>>,
+    provider_state: tauri::State<'_, Arc<Mutex<ProviderState>>>,
+    config_state: tauri::State<'_, ConfigState>,
+) -> Result<String, String> {
+    ensure_writable(&config_state).await?;
+    if let Some(message) = crate::quota_block(&app).await {
+        return Err(message);
+    }
+    let table_name = {
+        let config = config_state.config.lock().await;
+        get_table_name(&config.active_container)
+    };
+    let (target, paths) = unfinished_job(&table_name)
+        .map(|(target, job)| (target, job.remaining_folders().to_vec()))
+        .filter(|(target, paths)| !paths.is_empty() || *target != table_name)
+        .ok_or("No interrupted indexing job to resume")?;
+    info!("resume_indexing: {} folders left", paths.len());
+
+    let total = index_folders(&app, &target, &paths, &db_state, &provider_state, &config_state).await?;
+    Ok(format!("Resumed indexing, {} files indexed from {} folders", total, paths.len()))
+}
+
+/// An unfinished rebuild is dropped and the container keeps its old index.
+#[tauri::command]
+pub async fn discard_interrupted_job(
+    db_state: tauri::State<'_, Arc<Mutex<DbState>>>,
+    config_state: tauri::State<'_, ConfigState>,
+) -> Result<(), String> {
+    ensure_writable(&config_state).await?;
+    let table_name = {
+        let config = config_state.config.lock().await;
+        get_table_name(&config.active_container)
+    };
+    info!("discard_interrupted_job");
+    let db = {
+        let guard = db_state.lock().await;
+        guard.connection()?
+    };
+    let rebuild = rebuild_table_name(&table_name);
+    indexer::reset_index(&db, &rebuild).await.map_err(|e| e.to_string())?;
+    journal::finish(&rebuild);
+    if let Ok(table) = db.open_table(&table_name).execute().await {
+        journal::drop_partial(&db, &table, &table_name)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+    journal::finish(&table_name);
+    indexer::snapshot::clear(&table_name);
+    Ok(())
+}
+
+/// Re-embeds the active container's stored text with the current model,
+/// the quick way back to a searchable index after switching models.
+#[tauri::command]
+pub async fn reembed_index(
+    app: tauri::AppHandle,
+    db_state: tauri::State<'_, Arc<Mutex<DbState>>>,
+    provider_state: tauri::State<'_, Arc<Mutex<ProviderState>>>,
+    config_state: tauri::State<'_, ConfigState>,
+) -> Result<String, String> {
+    ensure_writable(&config_state).await?;
+    info!("reembed_index");
+    let table_name = {
+        let config = config_state.config.lock().await;
+        get_table_name(&config.active_container)
+    };
+    let db = {
+        let guard = db_state.lock().await;
+        guard.connection()?
+    };
+    // Shares the rebuild table, so an unfinished rebuild can't be resumed
+    // after this.
+    journal::finish(&rebuild_table_name(&table_name));
+
+    let _writes = crate::watcher::WRITING.lock().await;
+    let app_handle = app.clone();
+    let count = indexer::migrate::reembed(&db, &table_name, provider_state.inner(), move |progress| {
+        let _ = app_handle.emit("indexing-progress", progress);
+    })
+    .await
+    .map_err(|e| {
+        let e = e.to_string();
+        let _ = app.emit("indexing-failed", e.clone());
+        e
+    })?;
+
+    let _ = app.emit("indexing-complete", format!("{} chunks re-embedded", count));
+    crate::refresh_centroid(&app);
+    Ok(format!("Re-embedded {} chunks", count))
+}
+
+#[tauri::command]
+pub async fn remove_indexed_folder(
+    app: tauri::AppHandle,
+    dir: String,
+    db_state: tauri::State<'_, Arc<Mutex<DbState>>>,
+    provider_state: tauri::State<'_, Arc<Mutex<ProviderState>>>,
+    config_state: tauri::State<'_, ConfigState>,
+    watcher_state: tauri::State<'_, watcher::WatcherState>,
+) -> Result<String, String> {
+    ensure_writable(&config_state).await?;
+    info!("remove_indexed_folder: dir=\"{}\"", dir);
+    let (table_name, remaining) = {
+        let mut config = config_state.config.lock().await;
+        let active = config.active_container.clone();
+        let info = config.containers.get_mut(&active).ok_or("Active container not found")?;
+        if !info.indexed_paths.contains(&dir) {
+            return Err("Folder is not indexed in this container".to_string());
+        }
+        info.indexed_paths.retain(|p| p != &dir);
+        (get_table_name(&active), info.indexed_paths.clone())
+    };
+    config_state.save().await?;
+
+    let db = {
+        let guard = db_state.lock().await;
+        guard.connection()?
+    };
+    indexer::delete_folder_from_index(&dir, &remaining, &table_name, &db)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    watcher::restart(
+        watcher_state.inner(),
+        config_state.inner(),
+        db,
+        provider_state.inner().clone(),
+        app,
+    ).await;
+
+    Ok(format!("Removed {} from the index", dir))
+}
+
+#[tauri::command]
+pub async fn reindex_folder(
+    app: tauri::AppHandle,
+    dir: String,
+    db_state: tauri::State<'_, Arc<Mutex<DbState>>>,
+    provider_state: tauri::State<'_, Arc<Mutex<ProviderState>>>,
+    config_state: tauri::State<'_, ConfigState>,
+) -> Result<String, String> {
+    ensure_writable(&config_state).await?;
+    if let Some(message) = crate::quota_block(&app).await {
+        return Err(message);
+    }
+    info!("reindex_folder: dir=\"{}\"", dir);
+    let (table_name, others, excluded_paths, indexing_config) = {
+        let config = config_state.config.lock().await;
+        let info = config.containers.get(&config.active_container)
+            .ok_or("Active container not found")?;
+        if !info.indexed_paths.contains(&dir) {
+            return Err("Folder is not indexed in this container".to_string());
+        }
+        let others: Vec<String> = info.indexed_paths.iter().filter(|p| *p != &dir).cloned().collect();
+        (get_table_name(&config.active_container), others, info.excluded_paths.clone(), config.indexing_for(&config.active_container))
+    };
+    let mut excluded_paths = excluded_paths;
+    excluded_paths.extend(quota::load_log(&table_name).evicted);
+
+    let db = {
+        let guard = db_state.lock().await;
+        guard.connection()?
+    };
+    // Journaled from before the delete, so a crash in between still
+    // resumes by indexing the folder again.
+    journal::begin(&table_name, std::slice::from_ref(&dir));
+    let snapshot = indexer::snapshot::pin(&db, &table_name).await;
+    indexer::delete_folder_from_index(&dir, &others, &table_name, &db)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let ps = provider_state.inner().clone();
+    let app_handle = app.clone();
+    let started = std::time::Instant::now();
+    let count = indexer::index_directory(&dir, &table_name, &db, &ps, &indexing_config, &excluded_paths, move |progress| {
+        let _ = app_handle.emit("indexing-progress", progress);
+    })
+    .await
+    .map_err(|e| {
+        let e = e.to_string();
+        let _ = app.emit("indexing-failed", e.clone());
+        notifications::job_failed(&app, &dir, &e, started.elapsed());
+        job_event(&app, IndexEvent::Error { folder: dir.clone(), error: e.clone(), elapsed: started.elapsed() });
+        e
+    })?;
+    journal::finish(&table_name);
+    drop(snapshot);
+
+    let _ = app.emit("indexing-complete", format!("{} files reindexed", count));
+    notifications::job_finished(&app, std::slice::from_ref(&dir), count, started.elapsed());
+    job_event(&app, IndexEvent::Complete { folders: vec![dir.clone()], files: count, elapsed: started.elapsed() });
+    crate::enforce_quota(&app).await;
+    crate::refresh_centroid(&app);
+
+    Ok(format!("Reindexed {} files", count))
+}
+
+#[tauri::command]
+pub async fn find_similar(
+    path: String,
+    db_state: tauri::State<'_, Arc<Mutex<DbState>>>,
+    config_state: tauri::State<'_, ConfigState>,
+) -> Result<Vec<SearchResult>, String> {
+    debug!("find_similar: path=\"{}\"", path);
+    let (table_name, pinned, snippet_length) = {
+        let config = config_state.config.lock().await;
+        (get_table_name(&config.active_container), pinned_paths(&config, &config.active_container), config.snippet_length)
+    };
+    let db = {
+        let guard = db_state.lock().await;
+        guard.connection()?
+    };
+    let table = indexer::snapshot::open(&db, &table_name).await.map_err(|e| e.to_string())?;
+    let related = indexer::search::find_related(&table, &path, 10)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or("File is not in the index")?;
+
+    let scored = indexer::pipeline::score_results(related, false, false, 10);
+    Ok(to_search_results(scored, &pinned, &[], snippet_length))
+}
+
+/// A corrected query when some of its words appear nowhere in the active
+/// container, for the "did you mean" row. The dictionary is built on first
+/// use and rebuilt whenever the table changes.
+#[tauri::command]
+pub async fn suggest_query(
+    query: String,
+    db_state: tauri::State<'_, Arc<Mutex<DbState>>>,
+    config_state: tauri::State<'_, ConfigState>,
+    spell_state: tauri::State<'_, Arc<Mutex<SpellState>>>,
+) -> Result<Option<String>, String> {
+    let table_name = {
+        let config = config_state.config.lock().await;
+        get_table_name(&config.active_container)
+    };
+    let db = {
+        let guard = db_state.lock().await;
+        guard.connection()?
+    };
+    let Ok(table) = db.open_table(&table_name).execute().await else {
+        return Ok(None);
+    };
+    let version = table.version().await.map_err(|e| e.to_string())?;
+
+    let cached = {
+        let guard = spell_state.lock().await;
+        guard.dictionaries.get(&table_name)
+            .filter(|(v, _)| *v == version)
+            .map(|(_, dict)| dict.clone())
+    };
+    let dict = match cached {
+        Some(dict) => dict,
+        None => {
+            let dict = Arc::new(indexer::spelling::TermDictionary::build(&table).await.map_err(|e| e.to_string())?);
+            debug!("suggest_query: built dictionary for {} v{} ({} terms)", table_name, version, dict.len());
+            spell_state.lock().await.dictionaries.insert(table_name, (version, dict.clone()));
+            dict
+        }
+    };
+
+    tokio::task::spawn_blocking(move || dict.suggest(&query))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Maximum typeahead suggestions returned per keystroke.
+const MAX_COMPLETIONS: usize = 5;
+/// More than for queries: Tab cycles through these to pick a folder.
+const MAX_PATH_COMPLETIONS: usize = 12;
+
+/// File names and symbols from the active container that complete the last
+/// word of the query, for the typeahead list under the search bar. Cached
+/// per table version like the did-you-mean dictionary.
+#[tauri::command]
+pub async fn complete_query(
+    query: String,
+    db_state: tauri::State<'_, Arc<Mutex<DbState>>>,
+    config_state: tauri::State<'_, ConfigState>,
+    completion_state: tauri::State<'_, Arc<Mutex<CompletionState>>>,
+) -> Result<Vec<indexer::completion::Completion>, String> {
+    let table_name = {
+        let config = config_state.config.lock().await;
+        get_table_name(&config.active_container)
+    };
+    let db = {
+        let guard = db_state.lock().await;
+        guard.connection()?
+    };
+    let Ok(table) = db.open_table(&table_name).execute().await else {
+        return Ok(Vec::new());
+    };
+    let version = table.version().await.map_err(|e| e.to_string())?;
+
+    let cached = {
+        let guard = completion_state.lock().await;
+        guard.indexes.get(&table_name)
+            .filter(|(v, _)| *v == version)
+            .map(|(_, index)| index.clone())
+    };
+    let index = match cached {
+        Some(index) => index,
+        None => {
+            let index = Arc::new(indexer::completion::CompletionIndex::build(&table).await.map_err(|e| e.to_string())?);
+            debug!("complete_query: built index for {} v{} ({} names)", table_name, version, index.len());
+            completion_state.lock().await.indexes.insert(table_name, (version, index.clone()));
+            index
+        }
+    };
+
+    Ok(index.complete(&query, MAX_COMPLETIONS))
+}
+
+/// Files and folders completing a path typed into the search bar (`/...`,
+/// `~/...`), among the active container's indexed folders.
+#[tauri::command]
+pub async fn complete_path(
+    app: tauri::AppHandle,
+    partial: String,
+    config_state: tauri::State<'_, ConfigState>,
+) -> Result<Vec<indexer::completion::Completion>, String> {
+    let roots = {
+        let config = config_state.config.lock().await;
+        config.containers.get(&config.active_container).map(|info| info.indexed_paths.clone()).unwrap_or_default()
+    };
+    let home = app.path().home_dir().ok();
+    tokio::task::spawn_blocking(move || {
+        indexer::completion::complete_path(&partial, &roots, home.as_deref(), MAX_PATH_COMPLETIONS)
+    })
+    .await
+    .map_err(|e| e.to_string())
+}
+
+/// Opens a PDF at the given page via a `file://...#page=N` URL, which
+/// browsers and most PDF viewers honour. Falls back to opening the file
+/// normally when the URL can't be built or handed off.
+#[tauri::command]
+pub async fn open_at_page(app: tauri::AppHandle, path: String, page: u32) -> Result<(), String> {
+    use tauri_plugin_opener::OpenerExt;
+
+    if let Ok(mut url) = tauri::Url::from_file_path(&path) {
+        url.set_fragment(Some(&format!("page={}", page)));
+        match app.opener().open_url(url.as_str(), None::<&str>) {
+            Ok(()) => return Ok(()),
+            Err(e) => warn!("Opening {} at page {} failed: {}", path, page, e),
+        }
+    }
+    app.opener().open_path(&path, None::<&str>).map_err(|e| e.to_string())
+}
+
+/// Languages detected in the active container with their file counts, for
+/// the search language filter. Empty until something has been indexed.
+#[tauri::command]
+pub async fn get_languages(
+    db_state: tauri::State<'_, Arc<Mutex<DbState>>>,
+    config_state: tauri::State<'_, ConfigState>,
+) -> Result<Vec<(String, usize)>, String> {
+    let table_name = {
+        let config = config_state.config.lock().await;
+        get_table_name(&config.active_container)
+    };
+    let db = {
+        let guard = db_state.lock().await;
+        guard.connection()?
+    };
+    let Ok(table) = db.open_table(&table_name).execute().await else {
+        return Ok(vec![]);
+    };
+    indexer::db::get_language_counts(&table)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// The active container's most common file extensions with their file
+/// counts, for the filter chips under the search bar.
+#[tauri::command]
+pub async fn get_extensions(
+    db_state: tauri::State<'_, Arc<Mutex<DbState>>>,
+    config_state: tauri::State<'_, ConfigState>,
+) -> Result<Vec<(String, usize)>, String> {
+    const SHOWN: usize = 8;
+    let table_name = {
+        let config = config_state.config.lock().await;
+        get_table_name(&config.active_container)
+    };
+    let db = {
+        let guard = db_state.lock().await;
+        guard.connection()?
+    };
+    let Ok(table) = db.open_table(&table_name).execute().await else {
+        return Ok(vec![]);
+    };
+    let mut counts = indexer::db::get_extension_counts(&table)
+        .await
+        .map_err(|e| e.to_string())?;
+    counts.truncate(SHOWN);
+    Ok(counts)
+}
+
+/// How many deleted files "include deleted" would bring back, 0 when none.
+#[tauri::command]
+pub async fn get_deleted_count(
+    db_state: tauri::State<'_, Arc<Mutex<DbState>>>,
+    config_state: tauri::State<'_, ConfigState>,
+) -> Result<usize, String> {
+    let table_name = {
+        let config = config_state.config.lock().await;
+        get_table_name(&config.active_container)
+    };
+    let db = {
+        let guard = db_state.lock().await;
+        guard.connection()?
+    };
+    indexer::tombstones::deleted_files(&db, &table_name)
+        .await
+        .map(|paths| paths.len())
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn exclude_path(
+    app: tauri::AppHandle,
+    path: String,
+    db_state: tauri::State<'_, Arc<Mutex<DbState>>>,
+    provider_state: tauri::State<'_, Arc<Mutex<ProviderState>>>,
+    config_state: tauri::State<'_, ConfigState>,
+    watcher_state: tauri::State<'_, watcher::WatcherState>,
+) -> Result<(), String> {
+    ensure_writable(&config_state).await?;
+    info!("exclude_path: path=\"{}\"", path);
+    let is_dir = std::path::Path::new(&path).is_dir();
+    let table_name = {
+        let mut config = config_state.config.lock().await;
+        let active = config.active_container.clone();
+        let info = config.containers.get_mut(&active).ok_or("Active container not found")?;
+        if !is_path_excluded(&info.excluded_paths, &path) {
+            // A folder rule makes earlier rules for anything inside it redundant.
+            let folder = [path.clone()];
+            info.excluded_paths.retain(|p| !is_path_excluded(&folder, p));
+            info.excluded_paths.push(path.clone());
+        }
+        get_table_name(&active)
+    };
+    config_state.save().await?;
+
+    let db = {
+        let guard = db_state.lock().await;
+        guard.connection()?
+    };
+    if is_dir {
+        indexer::delete_folder_from_index(&path, &[], &table_name, &db)
+            .await
+            .map_err(|e| e.to_string())?;
+    } else {
+        indexer::delete_file_from_index(&path, &table_name, &db, indexer::Retention::default())
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+
+    watcher::restart(
+        watcher_state.inner(),
+        config_state.inner(),
+        db,
+        provider_state.inner().clone(),
+        app,
+    ).await;
+
+    Ok(())
+}
+
+#[derive(Serialize)]
+pub struct AppConfig {
+    pub always_on_top: bool,
+    pub launch_at_startup: bool,
+    pub hotkey: String,
+    pub use_git_history: bool,
+    pub embedding_model: String,
+    pub chunk_size: Option<usize>,
+    pub chunk_overlap: Option<usize>,
+    pub extra_extensions: Vec<String>,
+    pub excluded_extensions: Vec<String>,
+    pub watch_ignored_dirs: Vec<String>,
+    pub version_history_days: u32,
     pub provider_type: String,
     pub remote_endpoint: String,
     pub remote_api_key: String,
@@ -482,15 +1720,51 @@ pub struct AppConfig {
     pub hyde_model: String,
     pub hyde_api_key: String,
     pub query_router_enabled: bool,
+    pub auto_container: bool,
     pub mmr_enabled: bool,
     pub mmr_lambda: f32,
+    pub fts_stemming: bool,
+    pub synonyms: Vec<String>,
+    pub model_idle_timeout_mins: u64,
+    pub throttle_on_battery: bool,
+    pub throttle_cpu_percent: u8,
+    pub result_actions: ResultActionsConfig,
+    pub search_debounce_ms: u64,
+    pub search_min_query_chars: usize,
+    pub desktop_search: DesktopSearch,
+    pub context_copy_tokens: usize,
+    pub theme: ThemeMode,
+    pub accent_color: String,
+    pub window_width: u32,
+    pub window_height: u32,
+    pub max_visible_results: usize,
+    pub ui_scale: f64,
+    pub window_placement: WindowPlacement,
+    pub remember_window_per_monitor: bool,
+    pub window_mode: WindowMode,
+    pub recent_files_enabled: bool,
+    pub notify_on_complete: bool,
+    pub webhooks: Vec<Webhook>,
+    pub post_index_command: String,
+    pub read_only: bool,
+    pub offline: bool,
+    pub sync_folder: String,
+    /// Set by `--read-only`, which the config toggle can't turn off.
+    pub read_only_session: bool,
+    pub portable: bool,
+    pub index_quota_mb: u64,
+    pub index_quota_policy: QuotaPolicy,
+    pub index_size_mb: Option<u64>,
 }
 
 #[tauri::command]
 pub async fn get_config(
+    db_state: tauri::State<'_, Arc<Mutex<DbState>>>,
     config_state: tauri::State<'_, ConfigState>,
 ) -> Result<AppConfig, String> {
+    let db_uri = db_state.lock().await.uri.clone();
     let config = config_state.config.lock().await;
+    let active = config.containers.get(&config.active_container);
     let (provider_type, remote_endpoint, remote_api_key, remote_model, remote_dimensions) =
         match &config.embedding_provider {
             EmbeddingProviderConfig::Local { .. } => (
@@ -518,6 +1792,8 @@ pub async fn get_config(
         chunk_overlap: config.indexing.chunk_overlap,
         extra_extensions: config.indexing.extra_extensions.clone(),
         excluded_extensions: config.indexing.excluded_extensions.clone(),
+        watch_ignored_dirs: config.indexing.watch_ignored_dirs.clone(),
+        version_history_days: config.indexing.version_history_days,
         provider_type,
         remote_endpoint,
         remote_api_key,
@@ -530,11 +1806,209 @@ pub async fn get_config(
         hyde_model: config.hyde.as_ref().map_or(String::new(), |h| h.model.clone()),
         hyde_api_key: config.hyde.as_ref().and_then(|h| h.api_key.clone()).unwrap_or_default(),
         query_router_enabled: config.query_router_enabled,
+        auto_container: config.auto_container,
         mmr_enabled: config.mmr_enabled,
         mmr_lambda: config.mmr_lambda,
+        fts_stemming: config.fts_stemming,
+        synonyms: config.synonyms.clone(),
+        model_idle_timeout_mins: config.model_idle_timeout_mins,
+        throttle_on_battery: config.throttle_on_battery,
+        throttle_cpu_percent: config.throttle_cpu_percent,
+        result_actions: config.result_actions.clone(),
+        search_debounce_ms: config.search.clamped().debounce_ms,
+        search_min_query_chars: config.search.clamped().min_query_chars,
+        desktop_search: config.desktop_search,
+        context_copy_tokens: config.context_copy_tokens,
+        theme: config.appearance.theme,
+        accent_color: config.appearance.accent_color.clone(),
+        window_width: config.window.width,
+        window_height: config.window.height,
+        max_visible_results: config.window.max_visible_results,
+        ui_scale: config.window.ui_scale,
+        window_placement: config.window.placement,
+        remember_window_per_monitor: config.window.remember_per_monitor,
+        window_mode: config.window.mode,
+        recent_files_enabled: config.recent_files_enabled,
+        notify_on_complete: config.notify_on_complete,
+        webhooks: config.webhooks.clone(),
+        post_index_command: config.post_index_command.clone(),
+        read_only: config.read_only,
+        offline: config.offline,
+        sync_folder: config.sync.folder.clone(),
+        read_only_session: crate::config::read_only_session(),
+        portable: crate::config::portable_data_dir().is_some(),
+        index_quota_mb: active.and_then(|info| info.quota_mb).unwrap_or(0),
+        index_quota_policy: active.map(|info| info.quota_policy).unwrap_or_default(),
+        index_size_mb: quota::index_size(&db_uri, &get_table_name(&config.active_container)).map(quota::to_mb),
+    })
+}
+
+#[tauri::command]
+pub async fn set_hotkey_paused(
+    app: tauri::AppHandle,
+    paused: bool,
+    config_state: tauri::State<'_, ConfigState>,
+) -> Result<(), String> {
+    let hotkey = config_state.config.lock().await.hotkey.clone();
+    hotkey::set_paused(&app, &hotkey, paused)
+}
+
+#[tauri::command]
+pub async fn reset_window_position(app: tauri::AppHandle) -> Result<(), String> {
+    placement::reset_position(&app).await
+}
+
+/// Downloads the local embedding model, and the reranker when it's on,
+/// before anything needs them. fastembed only downloads as part of loading,
+/// so each is loaded once and let go.
+#[tauri::command]
+pub async fn prefetch_models(
+    app: tauri::AppHandle,
+    config_state: tauri::State<'_, ConfigState>,
+) -> Result<(), String> {
+    let (model, use_reranker) = {
+        let config = config_state.config.lock().await;
+        (crate::config::get_local_model_name(&config), config.use_reranker)
+    };
+    let models_path = crate::config::app_data_dir().join("models");
+    std::fs::create_dir_all(&models_path).map_err(|e| e.to_string())?;
+
+    info!("Prefetching {}{}", model, if use_reranker { " and the reranker" } else { "" });
+    let (path, download_app) = (models_path.clone(), app.clone());
+    tokio::task::spawn_blocking(move || {
+        let _download = model_download::watch(&download_app, &model, path.clone());
+        indexer::load_model(crate::config::get_embedding_model(&model), path).map(drop)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+    .map_err(|e| e.to_string())?;
+
+    if use_reranker {
+        tokio::task::spawn_blocking(move || {
+            let _download = model_download::watch(&app, model_download::RERANKER, models_path.clone());
+            indexer::load_reranker(models_path).map(drop)
+        })
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_endpoint_health() -> EndpointHealth {
+    endpoint_health::current()
+}
+
+#[tauri::command]
+pub async fn get_privacy_report(config_state: tauri::State<'_, ConfigState>) -> Result<PrivacyReport, String> {
+    let config = config_state.config.lock().await;
+    Ok(privacy_report::build(&config, &crate::config::app_data_dir().join("models")))
+}
+
+/// Files of the active container secrets turned up in while indexing, by
+/// path.
+#[tauri::command]
+pub async fn get_secret_report(
+    config_state: tauri::State<'_, ConfigState>,
+) -> Result<std::collections::BTreeMap<String, indexer::secrets::Flagged>, String> {
+    let table_name = get_table_name(&config_state.config.lock().await.active_container);
+    Ok(indexer::secrets::report(&table_name))
+}
+
+#[tauri::command]
+pub fn get_watcher_status() -> WatcherStatus {
+    watcher::status()
+}
+
+#[tauri::command]
+pub fn is_vibrancy_active(vibrancy_state: tauri::State<'_, VibrancyState>) -> bool {
+    vibrancy_state.is_active()
+}
+
+/// The last `rememex://search` link, handed out once.
+#[tauri::command]
+pub fn take_deep_link_search(pending: tauri::State<'_, PendingSearch>) -> Option<DeepLink> {
+    pending.0.lock().ok().and_then(|mut link| link.take())
+}
+
+#[tauri::command]
+pub async fn get_user_locales(
+    config_state: tauri::State<'_, ConfigState>,
+) -> Result<locales::UserLocales, String> {
+    let dir = locales::user_locales_dir(&config_state.path);
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    Ok(locales::UserLocales {
+        dir: dir.to_string_lossy().to_string(),
+        locales: locales::load_user_locales(&dir),
+    })
+}
+
+#[tauri::command]
+pub async fn export_diagnostics(
+    app: tauri::AppHandle,
+    dest: String,
+    config_state: tauri::State<'_, ConfigState>,
+    db_state: tauri::State<'_, Arc<Mutex<DbState>>>,
+) -> Result<(), String> {
+    info!("export_diagnostics: dest=\"{}\"", dest);
+    let log_dir = match crate::config::portable_log_dir() {
+        Some(dir) => dir,
+        None => app.path().app_log_dir().map_err(|e| e.to_string())?,
+    };
+    let config = config_state.config.lock().await.clone();
+    let db = db_state.lock().await.connection().ok();
+    diagnostics::export_bundle(std::path::Path::new(&dest), &log_dir, &config, db.as_ref())
+        .await
+        .map_err(|e| {
+            error!("Diagnostics export failed: {}", e);
+            e.to_string()
+        })
+}
+
+/// See `indexer::bundle`.
+#[tauri::command]
+pub async fn export_search_bundle(
+    dest: String,
+    config_state: tauri::State<'_, ConfigState>,
+    db_state: tauri::State<'_, Arc<Mutex<DbState>>>,
+) -> Result<indexer::bundle::Manifest, String> {
+    info!("export_search_bundle: dest=\"{}\"", dest);
+    let (container, description, roots, provider) = {
+        let config = config_state.config.lock().await;
+        let container = config.active_container.clone();
+        os_auth::ensure_unlocked(&config, &container)?;
+        let info = config.containers.get(&container).ok_or("Active container not found")?;
+        let provider = info.embedding_provider.clone().unwrap_or_else(|| config.embedding_provider.clone());
+        (container, info.description.clone(), info.indexed_paths.clone(), provider)
+    };
+    let db = db_state.lock().await.connection()?;
+    indexer::bundle::export(
+        &db,
+        &get_table_name(&container),
+        &container,
+        &description,
+        &roots,
+        &provider,
+        std::path::Path::new(&dest),
+    )
+    .await
+    .map_err(|e| {
+        error!("Bundle export failed: {}", e);
+        e.to_string()
     })
 }
 
+/// Syncs with the sync folder right away, see `sync`.
+#[tauri::command]
+pub async fn sync_now(app: tauri::AppHandle, config_state: tauri::State<'_, ConfigState>) -> Result<crate::sync::SyncReport, String> {
+    if config_state.config.lock().await.is_read_only() {
+        return Err("Read-only mode: sync is off".to_string());
+    }
+    info!("sync_now");
+    crate::sync::run(&app).await
+}
+
 #[derive(Deserialize)]
 pub struct ConfigUpdate {
     pub always_on_top: Option<bool>,
@@ -546,6 +2020,8 @@ pub struct ConfigUpdate {
     pub chunk_overlap: Option<Option<usize>>,
     pub extra_extensions: Option<Vec<String>>,
     pub excluded_extensions: Option<Vec<String>>,
+    pub watch_ignored_dirs: Option<Vec<String>>,
+    pub version_history_days: Option<u32>,
     pub provider_type: Option<String>,
     pub remote_endpoint: Option<String>,
     pub remote_api_key: Option<String>,
@@ -558,8 +2034,135 @@ pub struct ConfigUpdate {
     pub hyde_model: Option<String>,
     pub hyde_api_key: Option<String>,
     pub query_router_enabled: Option<bool>,
+    pub auto_container: Option<bool>,
     pub mmr_enabled: Option<bool>,
     pub mmr_lambda: Option<f32>,
+    pub fts_stemming: Option<bool>,
+    pub synonyms: Option<Vec<String>>,
+    pub model_idle_timeout_mins: Option<u64>,
+    pub throttle_on_battery: Option<bool>,
+    pub throttle_cpu_percent: Option<u8>,
+    pub result_actions: Option<ResultActionsConfig>,
+    pub search_debounce_ms: Option<u64>,
+    pub search_min_query_chars: Option<usize>,
+    pub desktop_search: Option<DesktopSearch>,
+    pub context_copy_tokens: Option<usize>,
+    pub theme: Option<ThemeMode>,
+    pub accent_color: Option<String>,
+    pub window_width: Option<u32>,
+    pub window_height: Option<u32>,
+    pub max_visible_results: Option<usize>,
+    pub ui_scale: Option<f64>,
+    pub window_placement: Option<WindowPlacement>,
+    pub remember_window_per_monitor: Option<bool>,
+    pub window_mode: Option<WindowMode>,
+    pub recent_files_enabled: Option<bool>,
+    pub notify_on_complete: Option<bool>,
+    pub webhooks: Option<Vec<Webhook>>,
+    pub post_index_command: Option<String>,
+    pub read_only: Option<bool>,
+    pub offline: Option<bool>,
+    pub sync_folder: Option<String>,
+    /// Applies to the active container; 0 removes the quota.
+    pub index_quota_mb: Option<u64>,
+    pub index_quota_policy: Option<QuotaPolicy>,
+}
+
+fn validate_url(field: &str, url: &str) -> Result<(), String> {
+    let url = url.trim();
+    if url.is_empty() || url.starts_with("http://") || url.starts_with("https://") {
+        Ok(())
+    } else {
+        Err(format!("{} must start with http:// or https://", field))
+    }
+}
+
+/// Rejects updates that would leave config.json in a state the indexer or search
+/// pipeline cannot use. Runs before anything is applied so a bad field never
+/// half-saves the rest of the update.
+fn validate_config_update(updates: &ConfigUpdate, config: &Config) -> Result<(), String> {
+    if let Some(ref v) = updates.hotkey {
+        validate_hotkey(v)?;
+    }
+
+    let chunk_size = updates.chunk_size.unwrap_or(config.indexing.chunk_size);
+    if let Some(size) = chunk_size {
+        if !(100..=8192).contains(&size) {
+            return Err(format!("Chunk size must be between 100 and 8192, got {}", size));
+        }
+    }
+    let chunk_overlap = updates.chunk_overlap.unwrap_or(config.indexing.chunk_overlap);
+    if let (Some(size), Some(overlap)) = (chunk_size, chunk_overlap) {
+        if overlap >= size {
+            return Err(format!("Chunk overlap ({}) must be smaller than chunk size ({})", overlap, size));
+        }
+    }
+
+    if let Some(v) = updates.version_history_days {
+        if v > 3650 {
+            return Err(format!("Version history must be at most 3650 days, got {}", v));
+        }
+    }
+
+    if let Some(ref v) = updates.remote_endpoint {
+        validate_url("Remote endpoint", v)?;
+    }
+    if let Some(v) = updates.remote_dimensions {
+        if !(1..=8192).contains(&v) {
+            return Err(format!("Embedding dimensions must be between 1 and 8192, got {}", v));
+        }
+    }
+    if let Some(ref v) = updates.hyde_endpoint {
+        validate_url("HyDE endpoint", v)?;
+    }
+    if let Some(ref hooks) = updates.webhooks {
+        for hook in hooks {
+            if hook.url.trim().is_empty() {
+                return Err("Webhook URL cannot be empty".to_string());
+            }
+            validate_url("Webhook URL", &hook.url)?;
+        }
+    }
+    if let Some(v) = updates.throttle_cpu_percent {
+        if v > 100 {
+            return Err(format!("CPU threshold must be between 0 and 100, got {}", v));
+        }
+    }
+    if let Some(v) = updates.context_copy_tokens {
+        if !(200..=200_000).contains(&v) {
+            return Err(format!("Context budget must be between 200 and 200000 tokens, got {}", v));
+        }
+    }
+    if let Some(v) = updates.search_debounce_ms {
+        if v > 2000 {
+            return Err(format!("Search delay must be at most 2000 ms, got {}", v));
+        }
+    }
+    if let Some(v) = updates.search_min_query_chars {
+        if !(1..=20).contains(&v) {
+            return Err(format!("Minimum query length must be between 1 and 20, got {}", v));
+        }
+    }
+
+    if let Some(ref actions) = updates.result_actions {
+        let bindings = [&actions.open, &actions.reveal, &actions.copy_path, &actions.copy_snippet, &actions.copy_context];
+        if bindings.iter().any(|b| b.trim().is_empty()) {
+            return Err("Result action shortcuts cannot be empty".to_string());
+        }
+        for (i, a) in bindings.iter().enumerate() {
+            if bindings[i + 1..].iter().any(|b| b.eq_ignore_ascii_case(a)) {
+                return Err(format!("Shortcut '{}' is assigned to more than one action", a));
+            }
+        }
+    }
+
+    if let Some(ref v) = updates.accent_color {
+        if parse_hex_color(v).is_none() {
+            return Err(format!("Invalid accent color '{}', expected #rrggbb", v));
+        }
+    }
+
+    Ok(())
 }
 
 #[tauri::command]
@@ -568,17 +2171,27 @@ pub async fn update_config(
     updates: ConfigUpdate,
     config_state: tauri::State<'_, ConfigState>,
     provider_state: tauri::State<'_, Arc<Mutex<ProviderState>>>,
+    vibrancy_state: tauri::State<'_, VibrancyState>,
 ) -> Result<(), String> {
     info!("update_config");
     let mut provider_changed = false;
 
     {
         let mut config = config_state.config.lock().await;
+        validate_config_update(&updates, &config)?;
+        if let Some(ref v) = updates.hotkey {
+            hotkey::swap(&app, &config.hotkey, v)?;
+        }
 
         if let Some(v) = updates.always_on_top {
             config.always_on_top = v;
+        }
+        if let Some(v) = updates.window_mode {
+            config.window.mode = v;
+        }
+        if updates.always_on_top.is_some() || updates.window_mode.is_some() {
             if let Some(window) = app.get_webview_window("main") {
-                let _ = window.set_always_on_top(v);
+                appearance::apply_window_mode(&window, config.window.mode, config.always_on_top);
             }
         }
 
@@ -625,6 +2238,14 @@ pub async fn update_config(
             config.indexing.excluded_extensions = v.clone();
         }
 
+        if let Some(ref v) = updates.watch_ignored_dirs {
+            config.indexing.watch_ignored_dirs = v.clone();
+        }
+
+        if let Some(v) = updates.version_history_days {
+            config.indexing.version_history_days = v;
+        }
+
         if let Some(ref pt) = updates.provider_type {
             match pt.as_str() {
                 "local" => {
@@ -693,38 +2314,169 @@ pub async fn update_config(
         if let Some(v) = updates.query_router_enabled {
             config.query_router_enabled = v;
         }
+        if let Some(v) = updates.auto_container {
+            config.auto_container = v;
+        }
         if let Some(v) = updates.mmr_enabled {
             config.mmr_enabled = v;
         }
         if let Some(v) = updates.mmr_lambda {
             config.mmr_lambda = v.clamp(0.0, 1.0);
         }
+        if let Some(v) = updates.fts_stemming {
+            config.fts_stemming = v;
+        }
+        if let Some(ref v) = updates.synonyms {
+            config.synonyms = v.iter().map(|l| l.trim().to_string()).filter(|l| !l.is_empty()).collect();
+        }
+        if let Some(v) = updates.model_idle_timeout_mins {
+            config.model_idle_timeout_mins = v;
+        }
+        if let Some(v) = updates.throttle_on_battery {
+            config.throttle_on_battery = v;
+        }
+        if let Some(v) = updates.throttle_cpu_percent {
+            config.throttle_cpu_percent = v;
+        }
+        if let Some(v) = updates.recent_files_enabled {
+            config.recent_files_enabled = v;
+        }
+        if let Some(v) = updates.notify_on_complete {
+            config.notify_on_complete = v;
+        }
+        if let Some(ref v) = updates.webhooks {
+            config.webhooks = v.clone();
+        }
+        if let Some(ref v) = updates.post_index_command {
+            config.post_index_command = v.trim().to_string();
+        }
+        if let Some(v) = updates.read_only {
+            config.read_only = v;
+        }
+        if let Some(v) = updates.offline {
+            config.offline = v;
+            crate::config::set_offline(v);
+        }
+        if let Some(ref v) = updates.sync_folder {
+            config.sync.folder = v.trim().to_string();
+        }
+        let active = config.active_container.clone();
+        if let Some(info) = config.containers.get_mut(&active) {
+            if let Some(v) = updates.index_quota_mb {
+                info.quota_mb = (v > 0).then_some(v);
+            }
+            if let Some(v) = updates.index_quota_policy {
+                info.quota_policy = v;
+            }
+        }
+        if let Some(ref v) = updates.result_actions {
+            config.result_actions = v.clone();
+        }
+        if let Some(v) = updates.search_debounce_ms {
+            config.search.debounce_ms = v;
+        }
+        if let Some(v) = updates.search_min_query_chars {
+            config.search.min_query_chars = v;
+        }
+        if let Some(v) = updates.desktop_search {
+            config.desktop_search = v;
+        }
+        if let Some(v) = updates.context_copy_tokens {
+            config.context_copy_tokens = v;
+        }
+        if let Some(ref v) = updates.accent_color {
+            config.appearance.accent_color = v.trim().to_lowercase();
+        }
+        if let Some(v) = updates.theme {
+            config.appearance.theme = v;
+            if let Some(window) = app.get_webview_window("main") {
+                let active = appearance::apply_window_effects(&window, v);
+                vibrancy_state.0.store(active, std::sync::atomic::Ordering::Relaxed);
+            }
+        }
+        if let Some(v) = updates.max_visible_results {
+            config.window.max_visible_results = v;
+        }
+        if let Some(v) = updates.window_placement {
+            config.window.placement = v;
+        }
+        if let Some(v) = updates.remember_window_per_monitor {
+            config.window.remember_per_monitor = v;
+        }
+        let layout_changed = updates.window_width.is_some()
+            || updates.window_height.is_some()
+            || updates.ui_scale.is_some();
+        if let Some(v) = updates.window_width {
+            config.window.width = v;
+        }
+        if let Some(v) = updates.window_height {
+            config.window.height = v;
+        }
+        if let Some(v) = updates.ui_scale {
+            config.window.ui_scale = v;
+        }
+        config.window = config.window.clamped();
+        if layout_changed {
+            if let Some(window) = app.get_webview_window("main") {
+                appearance::apply_window_layout(&window, &config.window);
+            }
+        }
     }
 
     config_state.save().await?;
 
+    if updates.watch_ignored_dirs.is_some() || updates.read_only.is_some() || updates.version_history_days.is_some() {
+        crate::restart_watcher_when_ready(&app).await;
+    }
+
+    // A shorter window (or none) applies to history kept so far right away
+    // instead of at the next index run.
+    if let Some(days) = updates.version_history_days {
+        let app = app.clone();
+        tauri::async_runtime::spawn(async move {
+            let (table_name, writable) = {
+                let config = app.state::<ConfigState>().config.lock().await;
+                (get_table_name(&config.active_container), config.can_write(&config.active_container))
+            };
+            let db = app.state::<Arc<Mutex<DbState>>>().lock().await.db.clone();
+            if let (Some(db), true) = (db, writable) {
+                if let Err(e) = indexer::versions::prune(&db, &table_name, days).await {
+                    warn!("Pruning old versions failed: {}", e);
+                }
+            }
+        });
+    }
+
+    if updates.index_quota_mb.is_some() || updates.index_quota_policy.is_some() {
+        let app = app.clone();
+        tauri::async_runtime::spawn(async move { crate::enforce_quota(&app).await });
+    }
+
+    if updates.auto_container == Some(true) {
+        crate::refresh_centroid(&app);
+    }
+
     if provider_changed {
+        endpoint_health::record(&app, EndpointHealth::default());
         let config = config_state.config.lock().await;
         match &config.embedding_provider {
             EmbeddingProviderConfig::Local { model } => {
                 let model_enum = crate::config::get_embedding_model(model);
-                let app_data = app.path().app_data_dir().map_err(|e| e.to_string())?;
+                let app_data = crate::config::app_data_dir();
                 let models_path = app_data.join("models");
+                let model_name = model.clone();
                 drop(config);
 
                 let ps = provider_state.inner().clone();
                 tauri::async_runtime::spawn(async move {
-                    match indexer::load_model(model_enum, models_path) {
+                    let download = model_download::watch(&app, &model_name, models_path.clone());
+                    let load_result = indexer::load_model(model_enum.clone(), models_path.clone());
+                    drop(download);
+                    match load_result {
                         Ok(model) => {
                             use crate::indexer::embedding_provider::LocalProvider;
-                            use crate::state::ModelState;
-                            let model_state = Arc::new(Mutex::new(ModelState {
-                                model: Some(model),
-                                init_error: None,
-                                cached_dim: None,
-                            }));
                             let mut guard = ps.lock().await;
-                            guard.provider = Some(Box::new(LocalProvider { model_state }));
+                            guard.provider = Some(Box::new(LocalProvider::new(model, model_enum, models_path)));
                             guard.init_error = None;
                             let _ = app.emit("model-loaded", ());
                         }
@@ -744,6 +2496,7 @@ pub async fn update_config(
                 guard.init_error = None;
                 drop(config);
                 let _ = app.emit("model-loaded", ());
+                endpoint_health::check_soon(provider_state.inner().clone(), app.clone());
             }
         }
     }
@@ -759,13 +2512,14 @@ pub async fn add_annotation(
     provider_state: tauri::State<'_, Arc<Mutex<ProviderState>>>,
     config_state: tauri::State<'_, ConfigState>,
 ) -> Result<annotations::Annotation, String> {
+    ensure_writable(&config_state).await?;
     let table_name = {
         let config = config_state.config.lock().await;
         get_table_name(&config.active_container)
     };
     let db = {
         let guard = db_state.lock().await;
-        guard.db.clone()
+        guard.connection()?
     };
     annotations::add_annotation(&db, &table_name, &provider_state, &path, &note, "user")
         .await
@@ -784,26 +2538,209 @@ pub async fn get_annotations(
     };
     let db = {
         let guard = db_state.lock().await;
-        guard.db.clone()
+        guard.connection()?
     };
     annotations::get_annotations(&db, &table_name, path.as_deref())
         .await
         .map_err(|e| e.to_string())
 }
 
+#[derive(Serialize)]
+pub struct TopicsView {
+    pub topics: Vec<indexer::topics::Topic>,
+    pub built_at: i64,
+    pub stale: bool,
+}
+
+#[tauri::command]
+pub async fn get_topics(
+    refresh: bool,
+    db_state: tauri::State<'_, Arc<Mutex<DbState>>>,
+    config_state: tauri::State<'_, ConfigState>,
+) -> Result<TopicsView, String> {
+    let (table_name, excluded) = {
+        let config = config_state.config.lock().await;
+        let excluded = config.containers.get(&config.active_container)
+            .map(|info| info.excluded_paths.clone())
+            .unwrap_or_default();
+        (get_table_name(&config.active_container), excluded)
+    };
+    let db = {
+        let guard = db_state.lock().await;
+        guard.connection()?
+    };
+    let map = match indexer::topics::load(&table_name) {
+        Some(map) if !refresh => map,
+        _ => indexer::topics::build(&db, &table_name).await.map_err(|e| e.to_string())?,
+    };
+    let stale = indexer::topics::is_stale(&db, &table_name, &map).await;
+    let topics = map
+        .topics
+        .into_iter()
+        .map(|mut topic| {
+            topic.files.retain(|p| !is_path_excluded(&excluded, p));
+            topic
+        })
+        .filter(|topic| !topic.files.is_empty())
+        .collect();
+    Ok(TopicsView { topics, built_at: map.built_at, stale })
+}
+
+/// The active container's files and annotations by day or week, newest
+/// first, `periods` of them up to `until` (unix seconds).
+#[tauri::command]
+pub async fn get_timeline(
+    period: indexer::timeline::Period,
+    query: Option<String>,
+    until: Option<i64>,
+    periods: usize,
+    db_state: tauri::State<'_, Arc<Mutex<DbState>>>,
+    config_state: tauri::State<'_, ConfigState>,
+) -> Result<Vec<indexer::timeline::TimelinePeriod>, String> {
+    let (table_name, excluded) = {
+        let config = config_state.config.lock().await;
+        let excluded = config.containers.get(&config.active_container)
+            .map(|info| info.excluded_paths.clone())
+            .unwrap_or_default();
+        (get_table_name(&config.active_container), excluded)
+    };
+    let db = {
+        let guard = db_state.lock().await;
+        guard.connection()?
+    };
+    indexer::timeline::build(&db, &table_name, period, query.as_deref(), until, periods.clamp(1, 60), &excluded)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// The people, projects and tools `path` mentions in the active container.
+#[tauri::command]
+pub async fn get_entities(
+    path: String,
+    db_state: tauri::State<'_, Arc<Mutex<DbState>>>,
+    config_state: tauri::State<'_, ConfigState>,
+) -> Result<Vec<indexer::entities::Entity>, String> {
+    let table_name = {
+        let config = config_state.config.lock().await;
+        get_table_name(&config.active_container)
+    };
+    let db = {
+        let guard = db_state.lock().await;
+        guard.connection()?
+    };
+    indexer::entities::in_file(&db, &table_name, &path)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Files in the active container mentioning the entity `name`, with the
+/// entities they mention most besides.
+#[tauri::command]
+pub async fn get_entity_files(
+    name: String,
+    db_state: tauri::State<'_, Arc<Mutex<DbState>>>,
+    config_state: tauri::State<'_, ConfigState>,
+) -> Result<Option<indexer::entities::EntityFiles>, String> {
+    let (table_name, excluded) = {
+        let config = config_state.config.lock().await;
+        let excluded = config.containers.get(&config.active_container)
+            .map(|info| info.excluded_paths.clone())
+            .unwrap_or_default();
+        (get_table_name(&config.active_container), excluded)
+    };
+    let db = {
+        let guard = db_state.lock().await;
+        guard.connection()?
+    };
+    let mut found = indexer::entities::files_with(&db, &table_name, &name, usize::MAX)
+        .await
+        .map_err(|e| e.to_string())?;
+    if let Some(found) = &mut found {
+        found.files.retain(|f| !is_path_excluded(&excluded, &f.path));
+    }
+    Ok(found)
+}
+
+/// Answers `question` from the parts of `path` closest to it, read fresh
+/// from disk. Without a chat endpoint only those parts come back.
+#[tauri::command]
+pub async fn ask_file(
+    path: String,
+    question: String,
+    provider_state: tauri::State<'_, Arc<Mutex<ProviderState>>>,
+    config_state: tauri::State<'_, ConfigState>,
+) -> Result<indexer::ask::FileAnswer, String> {
+    info!("ask_file: path={} question=\"{}\"", path, question);
+    let (indexing, endpoint, covered) = {
+        let config = config_state.config.lock().await;
+        let info = config.containers.get(&config.active_container);
+        let canonical = std::fs::canonicalize(&path).ok();
+        let covered = info.is_some_and(|info| {
+            !is_path_excluded(&info.excluded_paths, &path)
+                && info.indexed_paths.iter().any(|root| {
+                    canonical.as_ref().zip(std::fs::canonicalize(root).ok()).is_some_and(|(file, root)| file.starts_with(root))
+                })
+        });
+        let retrieval = config.retrieval_for(&config.active_container);
+        let endpoint = indexer::ask::ChatEndpoint::pick(&config.indexing.summaries, retrieval.hyde.as_ref());
+        (config.indexing_for(&config.active_container), endpoint, covered)
+    };
+    if !covered {
+        return Err(format!("{} isn't in an indexed folder of this container", path));
+    }
+
+    let passages = {
+        let guard = provider_state.lock().await;
+        let provider = guard.provider.as_ref().ok_or("Embedding provider is loading... Please wait a moment.")?;
+        indexer::ask::passages(provider.as_ref(), std::path::Path::new(&path), &question, &indexing)
+            .await
+            .map_err(|e| e.to_string())?
+    };
+    let answer = match endpoint {
+        Some(endpoint) if !passages.is_empty() => Some(
+            indexer::ask::answer(&endpoint, &path, &question, &passages)
+                .await
+                .map_err(|e| e.to_string())?,
+        ),
+        _ => None,
+    };
+    Ok(indexer::ask::FileAnswer { path, answer, passages })
+}
+
+/// `text` in the language `target` (ISO 639-1), `target_name` being its
+/// English name for the endpoint.
+#[tauri::command]
+pub async fn translate_snippet(
+    text: String,
+    target: String,
+    target_name: String,
+    config_state: tauri::State<'_, ConfigState>,
+) -> Result<indexer::translate::Translation, String> {
+    let endpoint = {
+        let config = config_state.config.lock().await;
+        let retrieval = config.retrieval_for(&config.active_container);
+        indexer::ask::ChatEndpoint::pick(&config.indexing.summaries, retrieval.hyde.as_ref())
+    };
+    let endpoint = endpoint.ok_or("Translating needs a chat endpoint: set up summaries or HyDE in config.json")?;
+    indexer::translate::translate(&endpoint, &text, &target, &target_name)
+        .await
+        .map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 pub async fn delete_annotation(
     annotation_id: String,
     db_state: tauri::State<'_, Arc<Mutex<DbState>>>,
     config_state: tauri::State<'_, ConfigState>,
 ) -> Result<(), String> {
+    ensure_writable(&config_state).await?;
     let table_name = {
         let config = config_state.config.lock().await;
         get_table_name(&config.active_container)
     };
     let db = {
         let guard = db_state.lock().await;
-        guard.db.clone()
+        guard.connection()?
     };
     annotations::delete_annotation(&db, &table_name, &annotation_id)
         .await
@@ -1,4 +1,6 @@
 use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 
 use log::{info, warn};
@@ -7,8 +9,15 @@ use serde::{Deserialize, Serialize};
 use tauri_plugin_global_shortcut::{Code, Modifiers, Shortcut};
 use tokio::sync::Mutex;
 
+use crate::digest::DigestConfig;
+use crate::sync::SyncConfig;
 use crate::indexer::embedding_provider::RemoteProviderConfig;
+use crate::indexer::external::Extractors;
+use crate::indexer::identity::DisplayPath;
+use crate::indexer::secrets::SecretPolicy;
 use crate::indexer::hyde::HydeConfig;
+use crate::indexer::summaries::SummaryConfig;
+use crate::redact::RedactionConfig;
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 #[serde(tag = "type")]
@@ -56,6 +65,62 @@ pub struct IndexingConfig {
     pub chunk_overlap: Option<usize>,
     #[serde(default = "default_true")]
     pub use_git_history: bool,
+    /// Read the text in images with OCR. Off, images are left out.
+    #[serde(default = "default_true")]
+    pub ocr: bool,
+    /// Directory names the file watcher ignores anywhere under an indexed
+    /// path. Build output churns during every build and would otherwise be
+    /// re-embedded over and over.
+    #[serde(default = "default_watch_ignored_dirs")]
+    pub watch_ignored_dirs: Vec<String>,
+    /// Days chunks replaced by an edit or a deletion stay searchable with
+    /// `as_of`. 0 keeps no history.
+    #[serde(default)]
+    pub version_history_days: u32,
+    /// Days the chunks of files the watcher saw deleted stay findable with
+    /// "include deleted". 0 forgets deleted files right away.
+    #[serde(default = "default_deleted_retention_days")]
+    pub deleted_retention_days: u32,
+    /// Folders a multi-folder job reads and chunks at once while the
+    /// current one is embedded. 0 picks from the core count.
+    #[serde(default)]
+    pub parallel_folders: usize,
+    /// `extractors` and `wasm_extractors`: per-extension readers ahead of
+    /// the built-in ones.
+    #[serde(flatten)]
+    pub extractors: Extractors,
+    /// Which path a file reachable under several is indexed and shown as.
+    #[serde(default)]
+    pub display_path: DisplayPath,
+    /// Walk only the folders the NTFS change journal or FSEvents say
+    /// changed since a folder was last indexed.
+    #[serde(default = "default_true")]
+    pub change_journal: bool,
+    #[serde(default)]
+    pub summaries: SummaryConfig,
+    /// Pick the people, projects and tools prose files mention out while
+    /// indexing, for finding other files that mention them.
+    #[serde(default = "default_true")]
+    pub entities: bool,
+    /// What to do with API keys and passwords found in files: mask them,
+    /// leave their chunks out, or only list the files.
+    #[serde(default)]
+    pub secrets: SecretPolicy,
+}
+
+fn default_deleted_retention_days() -> u32 {
+    30
+}
+
+fn default_watch_ignored_dirs() -> Vec<String> {
+    [
+        ".git", "node_modules", "target", "dist", "build", ".next", "__pycache__", ".venv", "obj",
+        // Kept up to date by macOS itself on every volume.
+        ".Spotlight-V100", ".fseventsd", ".Trashes", ".TemporaryItems", ".DocumentRevisions-V100",
+    ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect()
 }
 
 impl Default for IndexingConfig {
@@ -66,16 +131,437 @@ impl Default for IndexingConfig {
             chunk_size: None,
             chunk_overlap: None,
             use_git_history: true,
+            ocr: true,
+            watch_ignored_dirs: default_watch_ignored_dirs(),
+            version_history_days: 0,
+            deleted_retention_days: default_deleted_retention_days(),
+            parallel_folders: 0,
+            extractors: Extractors::default(),
+            display_path: DisplayPath::default(),
+            change_journal: true,
+            summaries: SummaryConfig::default(),
+            entities: true,
+            secrets: SecretPolicy::Off,
+        }
+    }
+}
+
+/// How long the search bar waits for typing to stop, and how much of a
+/// query it waits for, before searching.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct SearchInputConfig {
+    #[serde(default = "default_debounce_ms")]
+    pub debounce_ms: u64,
+    /// Shorter queries show recent files instead. One letter matches
+    /// nearly everything and still costs a remote provider a request.
+    #[serde(default = "default_min_query_chars")]
+    pub min_query_chars: usize,
+}
+
+impl Default for SearchInputConfig {
+    fn default() -> Self {
+        Self {
+            debounce_ms: default_debounce_ms(),
+            min_query_chars: default_min_query_chars(),
+        }
+    }
+}
+
+impl SearchInputConfig {
+    /// Keeps hand-edited values where typing still feels like searching.
+    pub fn clamped(&self) -> Self {
+        Self {
+            debounce_ms: self.debounce_ms.min(2000),
+            min_query_chars: self.min_query_chars.clamp(1, 20),
+        }
+    }
+}
+
+fn default_debounce_ms() -> u64 {
+    300
+}
+
+fn default_min_query_chars() -> usize {
+    1
+}
+
+/// A desktop search index asked for file names matching the query, so
+/// files outside the indexed folders can still turn up. Windows only.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DesktopSearch {
+    #[default]
+    Off,
+    /// voidtools Everything, through its `es.exe` command-line client.
+    Everything,
+    WindowsSearch,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ResultActionsConfig {
+    #[serde(default = "default_action_open")]
+    pub open: String,
+    #[serde(default = "default_action_reveal")]
+    pub reveal: String,
+    #[serde(default = "default_action_copy_path")]
+    pub copy_path: String,
+    #[serde(default = "default_action_copy_snippet")]
+    pub copy_snippet: String,
+    #[serde(default = "default_action_copy_context")]
+    pub copy_context: String,
+}
+
+impl Default for ResultActionsConfig {
+    fn default() -> Self {
+        Self {
+            open: default_action_open(),
+            reveal: default_action_reveal(),
+            copy_path: default_action_copy_path(),
+            copy_snippet: default_action_copy_snippet(),
+            copy_context: default_action_copy_context(),
+        }
+    }
+}
+
+fn default_action_open() -> String {
+    "Enter".to_string()
+}
+
+fn default_action_reveal() -> String {
+    "Ctrl+Enter".to_string()
+}
+
+fn default_action_copy_path() -> String {
+    "Ctrl+C".to_string()
+}
+
+fn default_action_copy_snippet() -> String {
+    "Ctrl+Shift+C".to_string()
+}
+
+fn default_action_copy_context() -> String {
+    "Ctrl+Alt+C".to_string()
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ThemeMode {
+    #[default]
+    Dark,
+    Light,
+    System,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct AppearanceConfig {
+    #[serde(default)]
+    pub theme: ThemeMode,
+    #[serde(default = "default_accent_color")]
+    pub accent_color: String,
+}
+
+impl Default for AppearanceConfig {
+    fn default() -> Self {
+        Self {
+            theme: ThemeMode::default(),
+            accent_color: default_accent_color(),
+        }
+    }
+}
+
+fn default_accent_color() -> String {
+    "#60cdff".to_string()
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum WindowPlacement {
+    /// Reopen wherever the window was last left.
+    #[default]
+    Keep,
+    Primary,
+    Cursor,
+    /// The monitor holding the foreground window of whatever app had focus.
+    Focused,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum WindowMode {
+    /// Borderless and on top, out of the taskbar, toggled with the hotkey.
+    #[default]
+    Popup,
+    /// A normal resizable window with a title bar and a taskbar entry that
+    /// stays open; the hotkey brings it forward instead of hiding it.
+    Docked,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct WindowLayoutConfig {
+    #[serde(default = "default_window_width")]
+    pub width: u32,
+    #[serde(default = "default_window_height")]
+    pub height: u32,
+    #[serde(default = "default_max_visible_results")]
+    pub max_visible_results: usize,
+    #[serde(default = "default_ui_scale")]
+    pub ui_scale: f64,
+    #[serde(default)]
+    pub placement: WindowPlacement,
+    #[serde(default)]
+    pub remember_per_monitor: bool,
+    #[serde(default)]
+    pub mode: WindowMode,
+    /// Last position on each monitor, relative to the monitor's top-left corner.
+    #[serde(default)]
+    pub monitor_positions: HashMap<String, [i32; 2]>,
+    /// The monitor the window was last left on, which `keep` goes back to.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_monitor: Option<String>,
+}
+
+impl Default for WindowLayoutConfig {
+    fn default() -> Self {
+        Self {
+            width: default_window_width(),
+            height: default_window_height(),
+            max_visible_results: default_max_visible_results(),
+            ui_scale: default_ui_scale(),
+            placement: WindowPlacement::default(),
+            remember_per_monitor: false,
+            mode: WindowMode::default(),
+            monitor_positions: HashMap::new(),
+            last_monitor: None,
+        }
+    }
+}
+
+impl WindowLayoutConfig {
+    /// Keeps hand-edited values inside a range where the layout still works.
+    pub fn clamped(&self) -> Self {
+        Self {
+            width: self.width.clamp(480, 2560),
+            height: self.height.clamp(320, 1600),
+            max_visible_results: self.max_visible_results.clamp(1, 50),
+            ui_scale: if self.ui_scale.is_finite() { self.ui_scale.clamp(0.75, 2.0) } else { 1.0 },
+            ..self.clone()
         }
     }
 }
 
+fn default_window_width() -> u32 {
+    800
+}
+
+fn default_window_height() -> u32 {
+    600
+}
+
+fn default_max_visible_results() -> usize {
+    10
+}
+
+fn default_ui_scale() -> f64 {
+    1.0
+}
+
+/// How the hotkey gets registered on Wayland, where an app can't grab keys
+/// for itself.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum HotkeyFallback {
+    /// Ask the desktop through the GlobalShortcuts portal.
+    #[default]
+    Portal,
+    /// Leave it to a desktop shortcut running `rememex --toggle`.
+    Command,
+}
+
+/// Linux desktop quirks. Ignored on other platforms.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct LinuxConfig {
+    /// Show the window as a layer-shell overlay on Wayland compositors that
+    /// support it (Sway, Hyprland, KDE), so it stays on top and centered.
+    #[serde(default = "default_true")]
+    pub layer_shell: bool,
+    #[serde(default)]
+    pub hotkey_fallback: HotkeyFallback,
+}
+
+impl Default for LinuxConfig {
+    fn default() -> Self {
+        Self { layer_shell: true, hotkey_fallback: HotkeyFallback::default() }
+    }
+}
+
+/// Parses a `#rrggbb` accent color. Shorthand and named colors are rejected so
+/// the frontend can derive its translucent variants without a color library.
+pub fn parse_hex_color(s: &str) -> Option<(u8, u8, u8)> {
+    let hex = s.trim().strip_prefix('#')?;
+    if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+    Some((channel(0)?, channel(2)?, channel(4)?))
+}
+
 #[derive(Serialize, Deserialize, Clone)]
 pub struct ContainerInfo {
     pub description: String,
     pub indexed_paths: Vec<String>,
     #[serde(default)]
     pub embedding_provider: Option<EmbeddingProviderConfig>,
+    #[serde(default)]
+    pub excluded_paths: Vec<String>,
+    #[serde(default)]
+    pub pinned_paths: Vec<String>,
+    /// LanceDB directory holding this container's index instead of the
+    /// app's own, e.g. a folder on a network share.
+    #[serde(default)]
+    pub db_uri: Option<String>,
+    #[serde(default)]
+    pub role: ContainerRole,
+    /// Extra LanceDB storage settings for an object-store `db_uri`, like
+    /// `aws_region` or `aws_endpoint`. Values written `env:NAME` or
+    /// `keychain:NAME` are looked up when connecting.
+    #[serde(default)]
+    pub storage_options: HashMap<String, String>,
+    /// In megabytes.
+    #[serde(default)]
+    pub quota_mb: Option<u64>,
+    #[serde(default)]
+    pub quota_policy: QuotaPolicy,
+    /// Indexing settings of its own, usually from the template it was
+    /// created from.
+    #[serde(default, skip_serializing_if = "IndexingOverrides::is_empty")]
+    pub indexing: IndexingOverrides,
+    /// Reranker, HyDE and MMR settings of its own, so code and notes can
+    /// be searched differently.
+    #[serde(default, skip_serializing_if = "SearchOverrides::is_empty")]
+    pub search: SearchOverrides,
+    /// Secrets and personal data masked in what agents get over MCP.
+    #[serde(default, skip_serializing_if = "RedactionConfig::is_empty")]
+    pub redaction: RedactionConfig,
+    /// Searching it takes the OS's sign-in check (Windows Hello, Touch ID)
+    /// first, good for `unlock_timeout_mins`. See `os_auth`.
+    #[serde(default)]
+    pub locked: bool,
+}
+
+/// A container's own take on the app-wide `indexing` settings. Unset
+/// fields keep the app's; extensions add to its lists.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct IndexingOverrides {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub extra_extensions: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub excluded_extensions: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chunk_size: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chunk_overlap: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub use_git_history: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ocr: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub secrets: Option<SecretPolicy>,
+}
+
+impl IndexingOverrides {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// A container's own take on the app-wide search settings. Unset fields
+/// keep the app's.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct SearchOverrides {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub use_reranker: Option<bool>,
+    /// Blank `endpoint`, `model` and `api_key` are taken from the app's
+    /// HyDE settings, so `{ "enabled": false }` is enough to turn it off.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hyde: Option<HydeConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mmr_enabled: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mmr_lambda: Option<f32>,
+}
+
+impl SearchOverrides {
+    pub fn is_empty(&self) -> bool {
+        self.use_reranker.is_none() && self.hyde.is_none() && self.mmr_enabled.is_none() && self.mmr_lambda.is_none()
+    }
+}
+
+/// The search settings in effect for a container.
+#[derive(Clone, Debug)]
+pub struct Retrieval {
+    pub use_reranker: bool,
+    pub hyde: Option<HydeConfig>,
+    pub mmr_enabled: bool,
+    pub mmr_lambda: f32,
+}
+
+/// Whether this machine builds a container's index or only searches one
+/// another machine keeps up to date.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ContainerRole {
+    #[default]
+    Writer,
+    Reader,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum QuotaPolicy {
+    /// Refuse to index more until space is freed or the quota raised.
+    #[default]
+    Stop,
+    /// Drop the files that least recently showed up in search results.
+    Evict,
+}
+
+/// Index events a webhook can be sent for.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum WebhookEvent {
+    IndexingComplete,
+    IndexingError,
+    FilesChanged,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Webhook {
+    pub url: String,
+    /// Which events to POST; empty means all of them.
+    #[serde(default)]
+    pub events: Vec<WebhookEvent>,
+}
+
+impl Webhook {
+    pub fn wants(&self, event: WebhookEvent) -> bool {
+        self.events.is_empty() || self.events.contains(&event)
+    }
+}
+
+/// A container on another machine's rememex, searched through its gRPC
+/// service along with the local one. See `remote`.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct RemoteContainer {
+    /// Shown next to its results.
+    pub name: String,
+    /// Where `rememex-mcp --grpc` listens there, e.g. `http://desktop.lan:50151`.
+    pub url: String,
+    /// Empty for the one that's active there.
+    #[serde(default)]
+    pub container: String,
+    /// Its `REMEMEX_GRPC_TOKEN`; `env:NAME` and `keychain:NAME` work here.
+    #[serde(default)]
+    pub token: String,
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -104,10 +590,74 @@ pub struct Config {
     pub hyde: Option<HydeConfig>,
     #[serde(default = "default_true")]
     pub query_router_enabled: bool,
+    /// Search the container whose contents a query looks closest to, not
+    /// just the active one.
+    #[serde(default)]
+    pub auto_container: bool,
     #[serde(default = "default_true")]
     pub mmr_enabled: bool,
     #[serde(default = "default_mmr_lambda")]
     pub mmr_lambda: f32,
+    #[serde(default = "default_true")]
+    pub fts_stemming: bool,
+    #[serde(default)]
+    pub synonyms: Vec<String>,
+    #[serde(default)]
+    pub model_idle_timeout_mins: u64,
+    /// Index on fewer threads and hold off watcher reindexes while on battery.
+    #[serde(default = "default_true")]
+    pub throttle_on_battery: bool,
+    /// Same, while other programs keep the CPU at least this busy. 0 = off.
+    #[serde(default = "default_throttle_cpu_percent")]
+    pub throttle_cpu_percent: u8,
+    #[serde(default)]
+    pub result_actions: ResultActionsConfig,
+    #[serde(default)]
+    pub search: SearchInputConfig,
+    #[serde(default)]
+    pub desktop_search: DesktopSearch,
+    /// Budget for "Copy as context", in estimated tokens.
+    #[serde(default = "default_context_copy_tokens")]
+    pub context_copy_tokens: usize,
+    /// Characters of a result's text shown under it, around the sentence
+    /// that best matches the query. 0 shows the whole chunk.
+    #[serde(default = "default_snippet_length")]
+    pub snippet_length: usize,
+    #[serde(default)]
+    pub appearance: AppearanceConfig,
+    #[serde(default)]
+    pub window: WindowLayoutConfig,
+    #[serde(default)]
+    pub linux: LinuxConfig,
+    #[serde(default = "default_true")]
+    pub recent_files_enabled: bool,
+    #[serde(default = "default_true")]
+    pub notify_on_complete: bool,
+    #[serde(default)]
+    pub webhooks: Vec<Webhook>,
+    #[serde(default)]
+    pub remote_containers: Vec<RemoteContainer>,
+    /// Shell command run after each indexing job; empty runs nothing.
+    #[serde(default)]
+    pub post_index_command: String,
+    /// Search only: no indexing, watcher, annotations or open history.
+    #[serde(default)]
+    pub read_only: bool,
+    /// Nothing goes over the network: no remote embeddings, HyDE,
+    /// webhooks, model downloads or object-store indexes. See `is_offline`.
+    #[serde(default)]
+    pub offline: bool,
+    /// How long unlocking a locked container holds, in minutes.
+    #[serde(default = "default_unlock_timeout_mins")]
+    pub unlock_timeout_mins: u64,
+    /// A note a day on the files changed in the last day, indexed into a
+    /// container of its own.
+    #[serde(default)]
+    pub digest: DigestConfig,
+    /// Containers and annotations shared with other devices through a
+    /// synced folder. See `sync`.
+    #[serde(default)]
+    pub sync: SyncConfig,
 }
 
 fn default_schema() -> String {
@@ -118,6 +668,22 @@ fn default_hotkey() -> String {
     "Alt+Space".to_string()
 }
 
+fn default_context_copy_tokens() -> usize {
+    4000
+}
+
+fn default_snippet_length() -> usize {
+    200
+}
+
+fn default_unlock_timeout_mins() -> u64 {
+    15
+}
+
+fn default_throttle_cpu_percent() -> u8 {
+    90
+}
+
 fn default_true() -> bool {
     true
 }
@@ -133,6 +699,17 @@ impl Default for Config {
             description: String::new(),
             indexed_paths: Vec::new(),
             embedding_provider: None,
+            excluded_paths: Vec::new(),
+            pinned_paths: Vec::new(),
+            db_uri: None,
+            role: ContainerRole::Writer,
+            storage_options: HashMap::new(),
+            quota_mb: None,
+            quota_policy: QuotaPolicy::Stop,
+            indexing: IndexingOverrides::default(),
+            search: SearchOverrides::default(),
+            redaction: RedactionConfig::default(),
+            locked: false,
         });
         Self {
             schema: default_schema(),
@@ -148,28 +725,128 @@ impl Default for Config {
             use_reranker: true,
             hyde: None,
             query_router_enabled: true,
+            auto_container: false,
             mmr_enabled: true,
             mmr_lambda: 0.7,
+            fts_stemming: true,
+            synonyms: Vec::new(),
+            model_idle_timeout_mins: 0,
+            throttle_on_battery: true,
+            throttle_cpu_percent: default_throttle_cpu_percent(),
+            result_actions: ResultActionsConfig::default(),
+            search: SearchInputConfig::default(),
+            desktop_search: DesktopSearch::Off,
+            context_copy_tokens: default_context_copy_tokens(),
+            snippet_length: default_snippet_length(),
+            appearance: AppearanceConfig::default(),
+            window: WindowLayoutConfig::default(),
+            linux: LinuxConfig::default(),
+            recent_files_enabled: true,
+            notify_on_complete: true,
+            webhooks: Vec::new(),
+            remote_containers: Vec::new(),
+            post_index_command: String::new(),
+            read_only: false,
+            offline: false,
+            unlock_timeout_mins: default_unlock_timeout_mins(),
+            digest: DigestConfig::default(),
+            sync: SyncConfig::default(),
         }
     }
 }
 
-pub fn parse_hotkey(s: &str) -> Shortcut {
-    let parts: Vec<&str> = s.split('+').map(|p| p.trim()).collect();
-    let mut mods = Modifiers::empty();
-    let mut key_str = "";
+impl Config {
+    /// Read-only from config.json or from `--read-only` for this session.
+    pub fn is_read_only(&self) -> bool {
+        self.read_only || read_only_session()
+    }
 
-    for part in &parts {
-        match part.to_lowercase().as_str() {
-            "alt" => mods |= Modifiers::ALT,
-            "ctrl" | "control" => mods |= Modifiers::CONTROL,
-            "shift" => mods |= Modifiers::SHIFT,
-            "super" | "meta" | "win" | "cmd" => mods |= Modifiers::SUPER,
-            _ => key_str = part,
+    /// Whether this machine may change the container's index: not in
+    /// read-only mode and not a reader of a shared index.
+    pub fn can_write(&self, container: &str) -> bool {
+        !self.is_read_only()
+            && self.containers.get(container).is_none_or(|info| info.role == ContainerRole::Writer)
+    }
+
+    /// The embedding model a container is indexed with: its own, or the
+    /// global one.
+    pub fn provider_label(&self, container: &str) -> String {
+        self.containers
+            .get(container)
+            .and_then(|info| info.embedding_provider.as_ref())
+            .unwrap_or(&self.embedding_provider)
+            .provider_label()
+    }
+
+    /// The app's settings with what `container` sets for itself on top.
+    pub fn indexing_for(&self, container: &str) -> IndexingConfig {
+        let mut indexing = self.indexing.clone();
+        let Some(own) = self.containers.get(container).map(|info| &info.indexing) else {
+            return indexing;
+        };
+        for ext in &own.extra_extensions {
+            if !indexing.extra_extensions.contains(ext) {
+                indexing.extra_extensions.push(ext.clone());
+            }
+        }
+        for ext in &own.excluded_extensions {
+            if !indexing.excluded_extensions.contains(ext) {
+                indexing.excluded_extensions.push(ext.clone());
+            }
         }
+        indexing.chunk_size = own.chunk_size.or(indexing.chunk_size);
+        indexing.chunk_overlap = own.chunk_overlap.or(indexing.chunk_overlap);
+        indexing.use_git_history = own.use_git_history.unwrap_or(indexing.use_git_history);
+        indexing.ocr = own.ocr.unwrap_or(indexing.ocr);
+        indexing.secrets = own.secrets.unwrap_or(indexing.secrets);
+        indexing
     }
 
-    let code = match key_str.to_lowercase().as_str() {
+    pub fn retrieval_for(&self, container: &str) -> Retrieval {
+        let own = self.containers.get(container).map(|info| &info.search);
+        let hyde = match own.and_then(|own| own.hyde.as_ref()) {
+            Some(own) => {
+                let global = self.hyde.as_ref();
+                let inherit = |own: &String, global: Option<&String>| {
+                    if own.is_empty() { global.cloned().unwrap_or_default() } else { own.clone() }
+                };
+                Some(HydeConfig {
+                    enabled: own.enabled,
+                    endpoint: inherit(&own.endpoint, global.map(|g| &g.endpoint)),
+                    model: inherit(&own.model, global.map(|g| &g.model)),
+                    api_key: own.api_key.clone().or_else(|| global.and_then(|g| g.api_key.clone())),
+                })
+            }
+            None => self.hyde.clone(),
+        };
+        Retrieval {
+            use_reranker: own.and_then(|own| own.use_reranker).unwrap_or(self.use_reranker),
+            hyde,
+            mmr_enabled: own.and_then(|own| own.mmr_enabled).unwrap_or(self.mmr_enabled),
+            mmr_lambda: own.and_then(|own| own.mmr_lambda).unwrap_or(self.mmr_lambda).clamp(0.0, 1.0),
+        }
+    }
+
+    /// Containers auto container can pick for a query: those in the same
+    /// database as the active one and embedded by the same model, so the
+    /// query's embedding and connection work for them as they are.
+    pub fn auto_container_candidates(&self) -> Vec<String> {
+        let active = self.containers.get(&self.active_container);
+        let db_uri = active.and_then(|info| info.db_uri.as_ref());
+        let model = self.provider_label(&self.active_container);
+        let mut names: Vec<String> = self
+            .containers
+            .iter()
+            .filter(|(name, info)| info.db_uri.as_ref() == db_uri && self.provider_label(name) == model)
+            .map(|(name, _)| name.clone())
+            .collect();
+        names.sort();
+        names
+    }
+}
+
+fn parse_key_code(key: &str) -> Option<Code> {
+    let code = match key.to_lowercase().as_str() {
         "space" => Code::Space,
         "enter" | "return" => Code::Enter,
         "tab" => Code::Tab,
@@ -244,16 +921,207 @@ pub fn parse_hotkey(s: &str) -> Shortcut {
         "." | "period" => Code::Period,
         "/" | "slash" => Code::Slash,
         "`" | "backquote" => Code::Backquote,
-        _ => {
-            warn!("Unrecognized hotkey key: '{}', falling back to Space", key_str);
-            Code::Space
-        }
+        _ => return None,
     };
+    Some(code)
+}
+
+pub fn parse_hotkey(s: &str) -> Shortcut {
+    let parts: Vec<&str> = s.split('+').map(|p| p.trim()).collect();
+    let mut mods = Modifiers::empty();
+    let mut key_str = "";
+
+    for part in &parts {
+        match part.to_lowercase().as_str() {
+            "alt" => mods |= Modifiers::ALT,
+            "ctrl" | "control" => mods |= Modifiers::CONTROL,
+            "shift" => mods |= Modifiers::SHIFT,
+            "super" | "meta" | "win" | "cmd" => mods |= Modifiers::SUPER,
+            _ => key_str = part,
+        }
+    }
+
+    let code = parse_key_code(key_str).unwrap_or_else(|| {
+        warn!("Unrecognized hotkey key: '{}', falling back to Space", key_str);
+        Code::Space
+    });
 
     let mods_opt = if mods.is_empty() { None } else { Some(mods) };
     Shortcut::new(mods_opt, code)
 }
 
+const RESERVED_HOTKEYS: &[(&str, &str)] = &[
+    ("Alt+Tab", "switches windows"),
+    ("Alt+F4", "closes the active window"),
+    ("Alt+Escape", "cycles windows"),
+    ("Ctrl+Escape", "opens the Start menu"),
+    ("Ctrl+Shift+Escape", "opens Task Manager"),
+    ("Ctrl+Alt+Delete", "is the secure attention sequence"),
+    ("Super+L", "locks the computer"),
+    ("Super+D", "shows the desktop"),
+    ("Super+E", "opens File Explorer"),
+    ("Super+R", "opens Run"),
+    ("Super+Tab", "opens Task View"),
+    ("Cmd+Space", "opens Spotlight"),
+    ("Cmd+Tab", "switches apps"),
+    ("Cmd+Q", "quits the active app"),
+];
+
+/// Returns what the OS uses `s` for when it is a well-known system shortcut that
+/// either cannot be registered or would shadow something users rely on.
+pub fn reserved_hotkey(s: &str) -> Option<&'static str> {
+    let shortcut = parse_hotkey(s);
+    RESERVED_HOTKEYS
+        .iter()
+        .find(|(reserved, _)| parse_hotkey(reserved) == shortcut)
+        .map(|(_, reason)| *reason)
+}
+
+/// Checks a hotkey string before it is saved, since `parse_hotkey` silently falls
+/// back to Space for anything it does not recognize.
+pub fn validate_hotkey(s: &str) -> Result<(), String> {
+    let mut key = None;
+    for part in s.split('+').map(|p| p.trim()) {
+        match part.to_lowercase().as_str() {
+            "alt" | "ctrl" | "control" | "shift" | "super" | "meta" | "win" | "cmd" => {}
+            "" => return Err(format!("Invalid hotkey '{}': empty key", s)),
+            _ if key.is_some() => return Err(format!("Invalid hotkey '{}': more than one key", s)),
+            _ => key = Some(part),
+        }
+    }
+    let key = key.ok_or_else(|| format!("Invalid hotkey '{}': missing key", s))?;
+    parse_key_code(key)
+        .map(|_| ())
+        .ok_or_else(|| format!("Invalid hotkey '{}': unknown key '{}'", s, key))
+}
+
+/// Turns a hotkey like `Ctrl+Shift+K` into the trigger syntax of the XDG
+/// shortcuts spec (`CTRL+SHIFT+k`), which the GlobalShortcuts portal takes.
+pub fn portal_trigger(hotkey: &str) -> String {
+    hotkey
+        .split('+')
+        .map(str::trim)
+        .filter(|part| !part.is_empty())
+        .map(|part| match part.to_lowercase().as_str() {
+            "alt" => "ALT".to_string(),
+            "ctrl" | "control" => "CTRL".to_string(),
+            "shift" => "SHIFT".to_string(),
+            "super" | "meta" | "win" | "cmd" => "LOGO".to_string(),
+            "enter" | "return" => "Return".to_string(),
+            "esc" | "escape" => "Escape".to_string(),
+            "backspace" => "BackSpace".to_string(),
+            key if key.len() > 1 && key.starts_with('f') && key[1..].chars().all(|c| c.is_ascii_digit()) => key.to_uppercase(),
+            key if key.chars().count() == 1 || key == "space" => key.to_string(),
+            _ => part.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("+")
+}
+
+const APP_ID: &str = "com.rememex.app";
+pub const PORTABLE_FLAG: &str = "--portable";
+pub const READ_ONLY_FLAG: &str = "--read-only";
+/// `--index <folder>` adds the folder to the active container and indexes it.
+pub const INDEX_FLAG: &str = "--index";
+/// `--toggle` shows or hides the window of the running app, for desktops
+/// where the global hotkey can't be registered. Bind it to a shortcut there.
+pub const TOGGLE_FLAG: &str = "--toggle";
+/// A file with this name next to the executable turns on portable mode
+/// without the flag, for launching straight from a USB stick.
+const PORTABLE_MARKER: &str = "portable";
+const PORTABLE_DATA_DIR: &str = "data";
+
+static READ_ONLY_SESSION: AtomicBool = AtomicBool::new(false);
+
+/// Applies `--read-only` from the command line. Kept out of the config so
+/// a read-only launch doesn't stick once the flag is gone.
+pub fn apply_read_only_flag() {
+    if std::env::args().any(|a| a == READ_ONLY_FLAG) {
+        info!("Read-only mode requested on the command line");
+        READ_ONLY_SESSION.store(true, Ordering::Relaxed);
+    }
+}
+
+pub fn read_only_session() -> bool {
+    READ_ONLY_SESSION.load(Ordering::Relaxed)
+}
+
+static OFFLINE: AtomicBool = AtomicBool::new(false);
+
+/// Mirrors `Config::offline` for code that makes network calls without the
+/// config at hand, like the remote embedding provider. Set on load and
+/// whenever the setting changes.
+pub fn set_offline(offline: bool) {
+    OFFLINE.store(offline, Ordering::Relaxed);
+}
+
+pub fn is_offline() -> bool {
+    OFFLINE.load(Ordering::Relaxed)
+}
+
+/// The error for something that was about to use the network.
+pub fn offline_error(what: &str) -> String {
+    format!("{} needs the network, and offline mode is on", what)
+}
+
+pub fn index_flag_value(args: &[String]) -> Option<String> {
+    let i = args.iter().position(|a| a == INDEX_FLAG)?;
+    args.get(i + 1).filter(|dir| !dir.starts_with("--")).cloned()
+}
+
+/// `data` next to the executable when running portable, None otherwise.
+pub fn portable_data_dir() -> Option<PathBuf> {
+    let exe = std::env::current_exe().ok()?;
+    let exe_dir = exe.parent()?;
+    let portable = std::env::args().any(|a| a == PORTABLE_FLAG) || exe_dir.join(PORTABLE_MARKER).exists();
+    portable.then(|| exe_dir.join(PORTABLE_DATA_DIR))
+}
+
+/// Logs go inside the portable folder too; None means the OS log folder.
+pub fn portable_log_dir() -> Option<PathBuf> {
+    portable_data_dir().map(|dir| dir.join("logs"))
+}
+
+/// Where config, index, models and logs live: the portable folder when
+/// running portable, else the per-user app data folder.
+pub fn app_data_dir() -> PathBuf {
+    if let Some(dir) = portable_data_dir() {
+        return dir;
+    }
+    let base = std::env::var("APPDATA")
+        .or_else(|_| std::env::var("XDG_DATA_HOME"))
+        .unwrap_or_else(|_| {
+            let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
+            format!("{}/.local/share", home)
+        });
+    PathBuf::from(base).join(APP_ID)
+}
+
+/// Keychain service holding `keychain:NAME` storage credentials.
+pub const KEYCHAIN_SERVICE: &str = "rememex";
+
+/// Resolves `env:NAME` and `keychain:NAME` storage option values so
+/// credentials can stay out of config.json; other values pass through.
+pub fn resolve_storage_options(options: &HashMap<String, String>) -> Result<Vec<(String, String)>, String> {
+    options
+        .iter()
+        .map(|(key, value)| Ok((key.clone(), resolve_secret(key, value)?)))
+        .collect()
+}
+
+/// One `env:NAME`, `keychain:NAME` or plain value, `key` naming it in errors.
+pub fn resolve_secret(key: &str, value: &str) -> Result<String, String> {
+    if let Some(name) = value.strip_prefix("env:") {
+        std::env::var(name).map_err(|_| format!("Environment variable {} for {} is not set", name, key))
+    } else if let Some(name) = value.strip_prefix("keychain:") {
+        keyring::Entry::new(KEYCHAIN_SERVICE, name)
+            .and_then(|entry| entry.get_password())
+            .map_err(|e| format!("Keychain entry {} for {} can't be read: {}", name, key, e))
+    } else {
+        Ok(value.to_string())
+    }
+}
+
 pub struct ConfigState {
     pub config: Arc<Mutex<Config>>,
     pub path: std::path::PathBuf,
@@ -269,6 +1137,26 @@ impl ConfigState {
     }
 }
 
+pub fn is_path_excluded(excluded_paths: &[String], path: &str) -> bool {
+    excluded_paths.iter().any(|excluded| {
+        path == excluded.as_str()
+            || path
+                .strip_prefix(excluded.as_str())
+                .is_some_and(|rest| rest.starts_with(['/', '\\']))
+    })
+}
+
+/// Whether `path` sits in (or is) one of the `dirs` by name, looking only at
+/// the part below whichever watched root contains it, so an indexed folder
+/// that itself lives under e.g. `build` still gets watched.
+pub fn is_in_ignored_dir(dirs: &[String], roots: &[String], path: &std::path::Path) -> bool {
+    let relative = roots.iter().find_map(|r| path.strip_prefix(r).ok()).unwrap_or(path);
+    relative.components().any(|c| match c {
+        std::path::Component::Normal(name) => dirs.iter().any(|d| name.eq_ignore_ascii_case(d)),
+        _ => false,
+    })
+}
+
 pub fn get_table_name(container: &str) -> String {
     let sanitized: String = container.chars().map(|c| {
         if c.is_ascii_alphanumeric() || c == '_' || c == '-' || c == '.' {
@@ -327,6 +1215,17 @@ pub fn load_config(config_path: &std::path::Path) -> Config {
                             description: String::new(),
                             indexed_paths: Vec::new(),
                             embedding_provider: None,
+                            excluded_paths: Vec::new(),
+                            pinned_paths: Vec::new(),
+                            db_uri: None,
+                            role: ContainerRole::Writer,
+                            storage_options: HashMap::new(),
+                            quota_mb: None,
+                            quota_policy: QuotaPolicy::Stop,
+                            indexing: IndexingOverrides::default(),
+                            search: SearchOverrides::default(),
+                            redaction: RedactionConfig::default(),
+                            locked: false,
                         });
                     }
                 }
@@ -335,6 +1234,17 @@ pub fn load_config(config_path: &std::path::Path) -> Config {
                         description: String::new(),
                         indexed_paths: Vec::new(),
                         embedding_provider: None,
+                        excluded_paths: Vec::new(),
+                        pinned_paths: Vec::new(),
+                        db_uri: None,
+                        role: ContainerRole::Writer,
+                        storage_options: HashMap::new(),
+                        quota_mb: None,
+                        quota_policy: QuotaPolicy::Stop,
+                        indexing: IndexingOverrides::default(),
+                        search: SearchOverrides::default(),
+                        redaction: RedactionConfig::default(),
+                        locked: false,
                     });
                 }
                 let default_active = containers.keys().next().cloned().unwrap_or_else(|| "Default".to_string());
@@ -343,18 +1253,10 @@ pub fn load_config(config_path: &std::path::Path) -> Config {
                     schema: default_schema(),
                     embedding_model: em.clone(),
                     embedding_provider: EmbeddingProviderConfig::Local { model: em },
-                    indexing: IndexingConfig::default(),
-                    hotkey: default_hotkey(),
-                    always_on_top: true,
-                    launch_at_startup: false,
                     active_container: old.active_container.unwrap_or(default_active),
                     containers,
                     first_run: false,
-                    use_reranker: true,
-                    hyde: None,
-                    query_router_enabled: true,
-                    mmr_enabled: true,
-                    mmr_lambda: 0.7,
+                    ..Config::default()
                 }
             } else {
                 Config::default()
@@ -408,8 +1310,248 @@ mod tests {
         assert!(config.query_router_enabled);
         assert!(config.mmr_enabled);
         assert!((config.mmr_lambda - 0.7).abs() < 0.01);
+        assert!(config.fts_stemming);
+        assert!(config.synonyms.is_empty());
         assert!(config.hyde.is_none());
+        assert!(config.recent_files_enabled);
+        assert!(config.notify_on_complete);
+        assert!(config.containers["Default"].pinned_paths.is_empty());
         assert!(config.use_reranker);
+        assert_eq!(config.model_idle_timeout_mins, 0);
+        assert_eq!(config.result_actions.open, "Enter");
+        assert_eq!(config.appearance.theme, ThemeMode::Dark);
+        assert_eq!(config.appearance.accent_color, "#60cdff");
+        assert_eq!(config.window, WindowLayoutConfig::default());
+        assert_eq!(config.result_actions.copy_snippet, "Ctrl+Shift+C");
+        assert_eq!(config.result_actions.copy_context, "Ctrl+Alt+C");
+        assert_eq!(config.context_copy_tokens, 4000);
+        assert!(!config.auto_container);
+    }
+
+    #[test]
+    fn test_auto_container_candidates() {
+        let json = r#"{
+            "embedding_model": "MultilingualE5Base",
+            "containers": {
+                "Default": { "description": "", "indexed_paths": [] },
+                "Work": { "description": "", "indexed_paths": [] },
+                "Team": { "description": "", "indexed_paths": [], "db_uri": "s3://team/index" },
+                "Papers": {
+                    "description": "", "indexed_paths": [],
+                    "embedding_provider": { "type": "local", "model": "AllMiniLML6V2" }
+                }
+            },
+            "active_container": "Work"
+        }"#;
+        let config: Config = serde_json::from_str(json).unwrap();
+        assert_eq!(config.auto_container_candidates(), ["Default", "Work"]);
+        assert_eq!(config.provider_label("Papers"), "AllMiniLML6V2 (local)");
+    }
+
+    #[test]
+    fn test_indexing_for() {
+        let json = r#"{
+            "embedding_model": "MultilingualE5Base",
+            "indexing": { "excluded_extensions": ["lock"], "chunk_size": 1000 },
+            "containers": {
+                "Default": { "description": "", "indexed_paths": [] },
+                "Notes": {
+                    "description": "", "indexed_paths": [],
+                    "indexing": { "extra_extensions": ["vtt"], "excluded_extensions": ["lock", "log"], "ocr": false }
+                }
+            },
+            "active_container": "Notes"
+        }"#;
+        let config: Config = serde_json::from_str(json).unwrap();
+        let notes = config.indexing_for("Notes");
+        assert_eq!(notes.extra_extensions, ["vtt"]);
+        assert_eq!(notes.excluded_extensions, ["lock", "log"]);
+        assert_eq!(notes.chunk_size, Some(1000));
+        assert!(!notes.ocr && notes.use_git_history);
+        assert!(config.indexing_for("Default").ocr);
+        assert!(config.containers["Default"].indexing.is_empty());
+    }
+
+    #[test]
+    fn test_retrieval_for() {
+        let json = r#"{
+            "embedding_model": "MultilingualE5Base",
+            "use_reranker": false,
+            "hyde": { "enabled": false, "endpoint": "http://localhost:11434/v1/chat/completions", "model": "llama3" },
+            "containers": {
+                "Default": { "description": "", "indexed_paths": [] },
+                "Code": {
+                    "description": "", "indexed_paths": [],
+                    "search": { "use_reranker": true, "hyde": { "enabled": true }, "mmr_lambda": 0.4 }
+                }
+            },
+            "active_container": "Code"
+        }"#;
+        let config: Config = serde_json::from_str(json).unwrap();
+        let code = config.retrieval_for("Code");
+        assert!(code.use_reranker && code.mmr_enabled);
+        assert!((code.mmr_lambda - 0.4).abs() < 0.001);
+        let hyde = code.hyde.unwrap();
+        assert!(hyde.enabled);
+        assert_eq!(hyde.model, "llama3");
+
+        let default = config.retrieval_for("Default");
+        assert!(!default.use_reranker);
+        assert!(!default.hyde.unwrap().enabled);
+        assert!((default.mmr_lambda - 0.7).abs() < 0.001);
+        assert!(config.containers["Default"].search.is_empty());
+    }
+
+    #[test]
+    fn test_result_actions_partial_override() {
+        let json = r#"{ "reveal": "Alt+Enter" }"#;
+        let actions: ResultActionsConfig = serde_json::from_str(json).unwrap();
+        assert_eq!(actions.reveal, "Alt+Enter");
+        assert_eq!(actions.open, "Enter");
+        assert_eq!(actions.copy_path, "Ctrl+C");
+    }
+
+    #[test]
+    fn test_appearance_serde() {
+        let json = r#"{ "theme": "system" }"#;
+        let appearance: AppearanceConfig = serde_json::from_str(json).unwrap();
+        assert_eq!(appearance.theme, ThemeMode::System);
+        assert_eq!(appearance.accent_color, "#60cdff");
+        assert!(serde_json::from_str::<AppearanceConfig>(r#"{ "theme": "neon" }"#).is_err());
+    }
+
+    #[test]
+    fn test_window_layout_clamped() {
+        let layout = WindowLayoutConfig {
+            width: 100,
+            height: 5000,
+            max_visible_results: 0,
+            ui_scale: f64::NAN,
+            ..WindowLayoutConfig::default()
+        };
+        let clamped = layout.clamped();
+        assert_eq!(clamped.width, 480);
+        assert_eq!(clamped.height, 1600);
+        assert_eq!(clamped.max_visible_results, 1);
+        assert_eq!(clamped.ui_scale, 1.0);
+        assert_eq!(WindowLayoutConfig::default().clamped(), WindowLayoutConfig::default());
+    }
+
+    #[test]
+    fn test_search_input_config() {
+        let search: SearchInputConfig = serde_json::from_str(r#"{ "min_query_chars": 3 }"#).unwrap();
+        assert_eq!(search.debounce_ms, 300);
+        assert_eq!(search.min_query_chars, 3);
+
+        let clamped = SearchInputConfig { debounce_ms: 60_000, min_query_chars: 0 }.clamped();
+        assert_eq!(clamped, SearchInputConfig { debounce_ms: 2000, min_query_chars: 1 });
+        assert_eq!(SearchInputConfig::default().clamped(), SearchInputConfig::default());
+    }
+
+    #[test]
+    fn test_desktop_search_serde() {
+        let source: DesktopSearch = serde_json::from_str(r#""windows_search""#).unwrap();
+        assert_eq!(source, DesktopSearch::WindowsSearch);
+        assert_eq!(serde_json::to_string(&DesktopSearch::Everything).unwrap(), r#""everything""#);
+        assert_eq!(Config::default().desktop_search, DesktopSearch::Off);
+    }
+
+    #[test]
+    fn test_window_placement_serde() {
+        let json = r#"{ "placement": "cursor", "monitor_positions": { "DISPLAY2": [120, 80] } }"#;
+        let layout: WindowLayoutConfig = serde_json::from_str(json).unwrap();
+        assert_eq!(layout.placement, WindowPlacement::Cursor);
+        assert!(!layout.remember_per_monitor);
+        assert_eq!(layout.monitor_positions["DISPLAY2"], [120, 80]);
+        assert_eq!(layout.last_monitor, None);
+        assert_eq!(layout.width, 800);
+    }
+
+    #[test]
+    fn test_validate_hotkey() {
+        assert!(validate_hotkey("Alt+Space").is_ok());
+        assert!(validate_hotkey("Ctrl+Shift+K").is_ok());
+        assert!(validate_hotkey("F12").is_ok());
+        assert!(validate_hotkey("Ctrl+Shift").is_err());
+        assert!(validate_hotkey("Alt+Spcae").is_err());
+        assert!(validate_hotkey("Alt+A+B").is_err());
+        assert!(validate_hotkey("Alt++").is_err());
+    }
+
+    #[test]
+    fn test_portal_trigger() {
+        assert_eq!(portal_trigger("Alt+Space"), "ALT+space");
+        assert_eq!(portal_trigger("Ctrl+Shift+K"), "CTRL+SHIFT+k");
+        assert_eq!(portal_trigger("Super+F12"), "LOGO+F12");
+        assert_eq!(portal_trigger("Ctrl + Enter"), "CTRL+Return");
+    }
+
+    #[test]
+    fn test_reserved_hotkey() {
+        assert!(reserved_hotkey("Win+L").is_some());
+        assert!(reserved_hotkey("alt+tab").is_some());
+        assert!(reserved_hotkey("Shift+Ctrl+Escape").is_some());
+        assert!(reserved_hotkey("Alt+Space").is_none());
+        assert!(reserved_hotkey("Ctrl+Shift+K").is_none());
+    }
+
+    #[test]
+    fn test_parse_hex_color() {
+        assert_eq!(parse_hex_color("#60cdff"), Some((0x60, 0xcd, 0xff)));
+        assert_eq!(parse_hex_color(" #FF0000 "), Some((255, 0, 0)));
+        assert_eq!(parse_hex_color("60cdff"), None);
+        assert_eq!(parse_hex_color("#fff"), None);
+        assert_eq!(parse_hex_color("#zzzzzz"), None);
+    }
+
+    #[test]
+    fn test_is_path_excluded() {
+        let excluded = vec![r"C:\proj\vendor".to_string(), "/home/u/notes/todo.md".to_string()];
+        assert!(is_path_excluded(&excluded, r"C:\proj\vendor"));
+        assert!(is_path_excluded(&excluded, r"C:\proj\vendor\lib.rs"));
+        assert!(!is_path_excluded(&excluded, r"C:\proj\vendored.rs"));
+        assert!(is_path_excluded(&excluded, "/home/u/notes/todo.md"));
+        assert!(!is_path_excluded(&excluded, "/home/u/notes/todo.md.bak"));
+        assert!(!is_path_excluded(&[], "/anything"));
+    }
+
+    #[test]
+    fn test_index_flag_value() {
+        let args = |a: &[&str]| a.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        assert_eq!(index_flag_value(&args(&["rememex", "--index", "/home/u/proj"])), Some("/home/u/proj".to_string()));
+        assert_eq!(index_flag_value(&args(&["rememex", "--index", "--read-only"])), None);
+        assert_eq!(index_flag_value(&args(&["rememex", "--index"])), None);
+        assert_eq!(index_flag_value(&args(&["rememex"])), None);
+    }
+
+    #[test]
+    fn test_is_in_ignored_dir() {
+        let dirs = default_watch_ignored_dirs();
+        let roots = vec!["/home/u/build/proj".to_string()];
+        let path = std::path::Path::new;
+        assert!(is_in_ignored_dir(&dirs, &roots, path("/home/u/build/proj/node_modules/x/index.js")));
+        assert!(is_in_ignored_dir(&dirs, &roots, path("/home/u/build/proj/Target")));
+        assert!(!is_in_ignored_dir(&dirs, &roots, path("/home/u/build/proj/src/main.rs")));
+        assert!(!is_in_ignored_dir(&dirs, &roots, path("/home/u/build/proj/dist.md")));
+        assert!(!is_in_ignored_dir(&[], &roots, path("/home/u/build/proj/target/a")));
+    }
+
+    #[test]
+    fn test_resolve_storage_options() {
+        std::env::set_var("REMEMEX_TEST_S3_KEY", "AKIA123");
+        let options = HashMap::from([
+            ("aws_region".to_string(), "eu-west-1".to_string()),
+            ("aws_access_key_id".to_string(), "env:REMEMEX_TEST_S3_KEY".to_string()),
+        ]);
+        let mut resolved = resolve_storage_options(&options).unwrap();
+        resolved.sort();
+        assert_eq!(resolved, vec![
+            ("aws_access_key_id".to_string(), "AKIA123".to_string()),
+            ("aws_region".to_string(), "eu-west-1".to_string()),
+        ]);
+
+        let missing = HashMap::from([("aws_secret_access_key".to_string(), "env:REMEMEX_TEST_UNSET".to_string())]);
+        assert!(resolve_storage_options(&missing).unwrap_err().contains("REMEMEX_TEST_UNSET"));
     }
 
     #[test]
@@ -0,0 +1,73 @@
+//! Starting points for a new container: indexing settings that suit a kind
+//! of folder. The app names and describes each one from its locale files
+//! by id.
+
+use serde::Serialize;
+
+use crate::config::IndexingOverrides;
+
+#[derive(Serialize, Clone, Debug)]
+pub struct ContainerTemplate {
+    pub id: &'static str,
+    pub indexing: IndexingOverrides,
+}
+
+fn extensions(list: &[&str]) -> Vec<String> {
+    list.iter().map(|e| e.to_string()).collect()
+}
+
+pub fn builtin() -> Vec<ContainerTemplate> {
+    vec![
+        // Code already gets chunked per language; history says why it changed.
+        ContainerTemplate {
+            id: "code",
+            indexing: IndexingOverrides {
+                extra_extensions: extensions(&["mdx", "prisma", "thrift", "cue"]),
+                excluded_extensions: extensions(&["lock", "log", "map", "svg"]),
+                use_git_history: Some(true),
+                ocr: Some(false),
+                ..Default::default()
+            },
+        },
+        // Papers argue over long paragraphs; bigger chunks keep a point whole.
+        ContainerTemplate {
+            id: "research",
+            indexing: IndexingOverrides {
+                extra_extensions: extensions(&["ris"]),
+                chunk_size: Some(1500),
+                chunk_overlap: Some(300),
+                use_git_history: Some(false),
+                ocr: Some(true),
+                ..Default::default()
+            },
+        },
+        // Mostly images, with whatever text is in them and in sidecar files.
+        ContainerTemplate {
+            id: "photos",
+            indexing: IndexingOverrides {
+                extra_extensions: extensions(&["xmp"]),
+                chunk_size: Some(600),
+                chunk_overlap: Some(100),
+                use_git_history: Some(false),
+                ocr: Some(true),
+                ..Default::default()
+            },
+        },
+        // Short notes and transcripts; small chunks find the one decision.
+        ContainerTemplate {
+            id: "meetings",
+            indexing: IndexingOverrides {
+                extra_extensions: extensions(&["vtt", "srt", "org"]),
+                chunk_size: Some(600),
+                chunk_overlap: Some(120),
+                use_git_history: Some(false),
+                ocr: Some(false),
+                ..Default::default()
+            },
+        },
+    ]
+}
+
+pub fn find(id: &str) -> Option<ContainerTemplate> {
+    builtin().into_iter().find(|t| t.id == id)
+}
@@ -0,0 +1,186 @@
+use serde::{Deserialize, Serialize};
+
+/// Below this many tokens of room, a result that doesn't fit whole is left
+/// out rather than cut down to a few lines.
+const MIN_PARTIAL_TOKENS: usize = 60;
+const TRUNCATED: &str = "[... truncated]";
+
+/// A search result as the frontend holds it.
+#[derive(Deserialize)]
+pub struct ContextItem {
+    pub path: String,
+    #[serde(default)]
+    pub context: String,
+    pub snippet: String,
+}
+
+#[derive(Serialize, Debug)]
+pub struct ContextBlock {
+    pub text: String,
+    /// Results that made it in, the last one possibly cut short.
+    pub included: usize,
+    pub tokens: usize,
+}
+
+/// Rough token count, about four characters per token. Close enough to
+/// stay under a chat model's limit without shipping a tokenizer.
+pub fn estimate_tokens(text: &str) -> usize {
+    text.chars().count().div_ceil(4)
+}
+
+/// Snippet text without the "File:"/"Context:" header the chunker adds,
+/// since the block puts the path and context above every excerpt anyway.
+/// Notes and folder summaries come with what their header calls them.
+fn body(snippet: &str) -> (&str, Option<&'static str>) {
+    if let Some(note) = snippet.strip_prefix("[annotation] ") {
+        return (note, Some("Note on"));
+    }
+    if let Some(summary) = snippet.strip_prefix("[summary] ") {
+        return (summary, Some("Summary of"));
+    }
+    let mut text = snippet
+        .strip_prefix("File: ")
+        .map_or(snippet, |rest| rest.split_once('\n').map_or("", |(_, b)| b));
+    if let Some(rest) = text.strip_prefix("Context: ") {
+        text = rest.split_once('\n').map_or("", |(_, b)| b);
+    }
+    (text, None)
+}
+
+/// A fence longer than any backtick run in `text`, so excerpts of markdown
+/// with code blocks can't close it early.
+fn fence(text: &str) -> String {
+    let longest = text
+        .split(|c| c != '`')
+        .map(str::len)
+        .max()
+        .unwrap_or(0);
+    "`".repeat(longest.max(2) + 1)
+}
+
+fn section(item: &ContextItem, text: &str, label: Option<&str>) -> String {
+    let header = if let Some(label) = label {
+        format!("### {} {}", label, item.path)
+    } else if item.context.is_empty() {
+        format!("### {}", item.path)
+    } else {
+        format!("### {} ({})", item.path, item.context)
+    };
+    let lang = std::path::Path::new(&item.path)
+        .extension()
+        .and_then(|e| e.to_str())
+        .filter(|_| label.is_none())
+        .unwrap_or("")
+        .to_lowercase();
+    let fence = fence(text);
+    format!("\n{}\n{}{}\n{}\n{}\n", header, fence, lang, text, fence)
+}
+
+/// Cuts `text` to about `tokens` tokens, at a line break when there is one.
+fn truncate(text: &str, tokens: usize) -> &str {
+    let end = text.char_indices().nth(tokens * 4).map_or(text.len(), |(i, _)| i);
+    let cut = &text[..end];
+    match cut.rfind('\n') {
+        Some(i) if i > 0 => &cut[..i],
+        _ => cut,
+    }
+}
+
+/// Results in order, each under a header with its path, until `max_tokens`
+/// is used up. The result that crosses the limit is cut short if enough
+/// room is left for it to be worth reading.
+pub fn build(query: &str, items: &[ContextItem], max_tokens: usize) -> ContextBlock {
+    let query = query.trim();
+    let mut text = if query.is_empty() {
+        "Excerpts from my files, found with Rememex. Each starts with the file it came from.\n".to_string()
+    } else {
+        format!(
+            "Excerpts from my files that match \"{}\", found with Rememex. Each starts with the file it came from.\n",
+            query
+        )
+    };
+    let mut included = 0;
+
+    for item in items {
+        let (raw, label) = body(&item.snippet);
+        let raw = raw.trim_end();
+        if raw.trim().is_empty() {
+            continue;
+        }
+        let whole = section(item, raw, label);
+        let used = estimate_tokens(&text);
+        if used + estimate_tokens(&whole) <= max_tokens {
+            text.push_str(&whole);
+            included += 1;
+            continue;
+        }
+        let overhead = estimate_tokens(&section(item, TRUNCATED, label)) + 1;
+        let room = max_tokens.saturating_sub(used + overhead);
+        if room >= MIN_PARTIAL_TOKENS {
+            let cut = format!("{}\n{}", truncate(raw, room), TRUNCATED);
+            text.push_str(&section(item, &cut, label));
+            included += 1;
+        }
+        break;
+    }
+
+    ContextBlock { tokens: estimate_tokens(&text), text, included }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(path: &str, context: &str, snippet: &str) -> ContextItem {
+        ContextItem { path: path.to_string(), context: context.to_string(), snippet: snippet.to_string() }
+    }
+
+    #[test]
+    fn test_build_formats_sections() {
+        let items = [
+            item("/repo/src/config.rs", "Config > load", "File: config.rs\nContext: Config > load\nfn load() {}"),
+            item("/repo/notes.md", "", "[annotation] check the fallback path"),
+            item("/repo/empty.txt", "", "File: empty.txt\n"),
+            item("/repo/docs", "", "[summary] Setup guides.\nCovers: setup.md"),
+        ];
+        let block = build("load config", &items, 1000);
+        assert_eq!(block.included, 3);
+        assert!(block.text.starts_with("Excerpts from my files that match \"load config\""));
+        assert!(block.text.contains("### /repo/src/config.rs (Config > load)\n```rs\nfn load() {}\n```\n"));
+        assert!(block.text.contains("### Note on /repo/notes.md\n```\ncheck the fallback path\n```\n"));
+        assert!(block.text.contains("### Summary of /repo/docs\n```\nSetup guides.\nCovers: setup.md\n```\n"));
+        assert!(!block.text.contains("File: "));
+        assert!(!block.text.contains("Context: "));
+        assert!(!block.text.contains("empty.txt"));
+        assert_eq!(block.tokens, estimate_tokens(&block.text));
+    }
+
+    #[test]
+    fn test_fence_outlasts_snippet() {
+        let items = [item("/r/README.md", "", "File: README.md\n```sh\nnpm i\n```")];
+        let block = build("", &items, 1000);
+        assert!(block.text.contains("\n````md\n```sh\nnpm i\n```\n````\n"));
+    }
+
+    #[test]
+    fn test_budget() {
+        let long: String = (0..200).map(|i| format!("line {} of the file\n", i)).collect();
+        let items = [
+            item("/r/a.rs", "", &format!("File: a.rs\n{}", "x".repeat(400))),
+            item("/r/b.rs", "", &format!("File: b.rs\n{}", long)),
+            item("/r/c.rs", "", "File: c.rs\nfn c() {}"),
+        ];
+        let block = build("q", &items, 400);
+        assert_eq!(block.included, 2);
+        assert!(block.tokens <= 400);
+        assert!(block.text.contains("/r/b.rs"));
+        assert!(block.text.contains(TRUNCATED));
+        assert!(!block.text.contains("/r/c.rs"));
+        assert!(block.text.contains("of the file\n[... truncated]"));
+
+        // Not enough room left to be worth a partial excerpt.
+        let block = build("q", &items, 170);
+        assert_eq!(block.included, 1);
+        assert!(!block.text.contains(TRUNCATED));
+    }
+}
@@ -0,0 +1,140 @@
+//! `rememex://` links, so notes, bookmarks and other apps can start a
+//! search or open an indexed file through the app.
+//!
+//! - `rememex://search?q=retry%20uploads&container=Work`
+//! - `rememex://open?path=C%3A%5Cnotes%5Cdeploy.pdf&page=3`
+
+use std::path::{Component, Path};
+use std::sync::Mutex;
+
+use serde::Serialize;
+use tauri::Url;
+
+use crate::config::{is_path_excluded, Config};
+
+pub const SCHEME: &str = "rememex";
+
+/// The last `search` link, kept until the frontend takes it so one that
+/// launched the app isn't lost before the window is listening.
+#[derive(Default)]
+pub struct PendingSearch(pub Mutex<Option<DeepLink>>);
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "action", rename_all = "lowercase")]
+pub enum DeepLink {
+    Search { query: String, container: Option<String> },
+    Open { path: String, page: Option<u32> },
+}
+
+/// Links arrive on the command line next to other arguments, and the
+/// single-instance plugin hands over the whole argv.
+pub fn is_link(arg: &str) -> bool {
+    arg.get(..SCHEME.len() + 1).is_some_and(|head| head.eq_ignore_ascii_case("rememex:"))
+}
+
+pub fn parse(link: &str) -> Result<DeepLink, String> {
+    let url = Url::parse(link.trim()).map_err(|e| format!("invalid link '{}': {}", link, e))?;
+    if url.scheme() != SCHEME {
+        return Err(format!("not a {}:// link: {}", SCHEME, link));
+    }
+    // `rememex://search?...` puts the action in the host, `rememex:search?...`
+    // in the path; both show up in the wild.
+    let action = match url.host_str() {
+        Some(host) if !host.is_empty() => host.to_string(),
+        _ => url.path().trim_matches('/').to_string(),
+    };
+    let param = |name: &str| {
+        url.query_pairs()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.trim().to_string())
+            .filter(|value| !value.is_empty())
+    };
+
+    match action.to_lowercase().as_str() {
+        "search" => Ok(DeepLink::Search {
+            query: param("q").or_else(|| param("query")).unwrap_or_default(),
+            container: param("container"),
+        }),
+        "open" => Ok(DeepLink::Open {
+            path: param("path").ok_or("open link without a path")?,
+            page: param("page").and_then(|p| p.parse().ok()).filter(|p| *p > 0),
+        }),
+        other => Err(format!("unknown link action '{}', expected search or open", other)),
+    }
+}
+
+/// What the OS would run instead of open.
+const RUNNABLE_EXTENSIONS: &[&str] = &[
+    "app", "appimage", "bat", "cmd", "com", "command", "cpl", "desktop", "exe", "hta", "jar", "js", "jse", "lnk",
+    "msc", "msi", "pif", "ps1", "psm1", "reg", "run", "scr", "sh", "url", "vbe", "vbs", "wsf", "wsh",
+];
+
+/// Any web page can fire a link, so `open` only reaches files rememex
+/// indexes (inside an indexed folder, not excluded) and never anything
+/// the OS would execute.
+pub fn check_openable(config: &Config, path: &str) -> Result<(), String> {
+    let file = Path::new(path);
+    if file.components().any(|c| c == Component::ParentDir) {
+        return Err(format!("refusing to open {}, it climbs out with '..'", path));
+    }
+    let extension = file.extension().and_then(|e| e.to_str()).unwrap_or("").to_lowercase();
+    if RUNNABLE_EXTENSIONS.contains(&extension.as_str()) {
+        return Err(format!("refusing to open {}, it's a program", path));
+    }
+    let indexed = config.containers.values().any(|info| {
+        is_path_excluded(&info.indexed_paths, path) && !is_path_excluded(&info.excluded_paths, path)
+    });
+    if !indexed {
+        return Err(format!("{} is not in an indexed folder", path));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            parse("rememex://search?q=retry%20uploads&container=Work"),
+            Ok(DeepLink::Search { query: "retry uploads".to_string(), container: Some("Work".to_string()) })
+        );
+        assert_eq!(
+            parse("rememex:search?query=tax+2024"),
+            Ok(DeepLink::Search { query: "tax 2024".to_string(), container: None })
+        );
+        assert_eq!(
+            parse("rememex://open/?path=C%3A%5Cnotes%5Cdeploy.pdf&page=3"),
+            Ok(DeepLink::Open { path: "C:\\notes\\deploy.pdf".to_string(), page: Some(3) })
+        );
+        assert_eq!(
+            parse("rememex://open?path=/home/u/a.md&page=0"),
+            Ok(DeepLink::Open { path: "/home/u/a.md".to_string(), page: None })
+        );
+        assert!(parse("rememex://open?page=2").is_err());
+        assert!(parse("rememex://delete?path=/home/u/a.md").is_err());
+        assert!(parse("https://search?q=x").is_err());
+        assert!(is_link("REMEMEX://search?q=x"));
+        assert!(!is_link("--index"));
+    }
+
+    #[test]
+    fn test_check_openable() {
+        let config: Config = serde_json::from_str(
+            r#"{
+                "containers": {
+                    "Default": { "description": "", "indexed_paths": ["/home/u/notes"], "excluded_paths": ["/home/u/notes/private"] }
+                },
+                "active_container": "Default"
+            }"#,
+        )
+        .unwrap();
+        assert!(check_openable(&config, "/home/u/notes/deploy.md").is_ok());
+        assert!(check_openable(&config, "/home/u/notes/private/keys.txt").is_err());
+        assert!(check_openable(&config, "/home/u/notes/../.ssh/id_rsa").is_err());
+        assert!(check_openable(&config, "/home/u/notes/tools/install.SH").is_err());
+        assert!(check_openable(&config, "/etc/passwd").is_err());
+        assert!(check_openable(&config, "notes/deploy.md").is_err());
+    }
+}
@@ -0,0 +1,141 @@
+//! File names from a desktop search index, for files that match a query
+//! but aren't in the container yet. Everything answers through `es.exe`,
+//! Windows Search through an OLE DB query PowerShell runs. Either one being
+//! missing or slow gives no candidates rather than an error.
+
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use log::{debug, warn};
+
+use crate::config::DesktopSearch;
+use crate::indexer::external::{read_all, spawn};
+
+const TIMEOUT: Duration = Duration::from_secs(2);
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+const MAX_TERMS: usize = 6;
+
+/// The words of `query` a file name could contain, with anything either
+/// index would read as an operator or a wildcard taken out. A leading `-`
+/// would make the word an es.exe option, like `-export-csv`.
+pub fn filename_terms(query: &str) -> Vec<String> {
+    query
+        .split_whitespace()
+        .map(|word| {
+            let word: String = word.chars().filter(|c| c.is_alphanumeric() || matches!(c, '-' | '.')).collect();
+            word.trim_start_matches('-').to_string()
+        })
+        .filter(|word| word.chars().any(char::is_alphanumeric))
+        .take(MAX_TERMS)
+        .collect()
+}
+
+fn everything_args(terms: &[String], limit: usize) -> Vec<String> {
+    let mut args = vec!["es.exe".to_string(), "-n".to_string(), limit.to_string()];
+    args.extend(terms.iter().cloned());
+    args
+}
+
+/// Every term has to be in the file name, like Everything does it.
+fn windows_search_args(terms: &[String], limit: usize) -> Vec<String> {
+    let clauses: Vec<String> = terms.iter().map(|term| format!("System.FileName LIKE '%{}%'", term)).collect();
+    let script = format!(
+        "[Console]::OutputEncoding = [Text.Encoding]::UTF8; \
+         $c = New-Object -ComObject ADODB.Connection; \
+         $c.Open(\"Provider=Search.CollatorDSO;Extended Properties='Application=Windows';\"); \
+         $r = $c.Execute(\"SELECT TOP {} System.ItemPathDisplay FROM SystemIndex WHERE {}\"); \
+         while (-not $r.EOF) {{ $r.Fields.Item(0).Value; $r.MoveNext() }}",
+        limit,
+        clauses.join(" AND ")
+    );
+    ["powershell", "-NoProfile", "-NonInteractive", "-Command", script.as_str()].map(str::to_string).to_vec()
+}
+
+/// What `args` printed, or None when it failed or ran past `TIMEOUT`.
+fn run(args: &[String]) -> Option<String> {
+    let mut child = match spawn(args) {
+        Ok(child) => child,
+        Err(e) => {
+            debug!("Desktop search '{}' unavailable: {}", args[0], e);
+            return None;
+        }
+    };
+    let stdout = read_all(child.stdout.take());
+    let _stderr = read_all(child.stderr.take());
+    let started = Instant::now();
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) if started.elapsed() < TIMEOUT => std::thread::sleep(POLL_INTERVAL),
+            Ok(None) => {
+                let _ = child.kill();
+                let _ = child.wait();
+                warn!("Desktop search '{}' took over {}s", args[0], TIMEOUT.as_secs());
+                return None;
+            }
+            Err(e) => {
+                warn!("Desktop search '{}': {}", args[0], e);
+                return None;
+            }
+        }
+    };
+    if !status.success() {
+        debug!("Desktop search '{}' exited with {}", args[0], status);
+        return None;
+    }
+    Some(String::from_utf8_lossy(&stdout.join().unwrap_or_default()).into_owned())
+}
+
+/// Up to `limit` existing files whose names match `query`, best first.
+/// Blocks for as long as the index takes, up to `TIMEOUT`.
+pub fn candidates(source: DesktopSearch, query: &str, limit: usize) -> Vec<String> {
+    let terms = filename_terms(query);
+    if source == DesktopSearch::Off || !cfg!(windows) || terms.is_empty() {
+        return Vec::new();
+    }
+    let args = match source {
+        DesktopSearch::Everything => everything_args(&terms, limit),
+        DesktopSearch::WindowsSearch => windows_search_args(&terms, limit),
+        DesktopSearch::Off => return Vec::new(),
+    };
+    let Some(out) = run(&args) else {
+        return Vec::new();
+    };
+    let found: Vec<String> = out
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && Path::new(line).is_file())
+        .take(limit)
+        .map(str::to_string)
+        .collect();
+    debug!("Desktop search ({:?}) found {} files for {:?}", source, found.len(), terms);
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_filename_terms() {
+        assert_eq!(filename_terms("budget  2024 q3-report.xlsx"), ["budget", "2024", "q3-report.xlsx"]);
+        assert_eq!(filename_terms("it's *budget* | !draft"), ["its", "budget", "draft"]);
+        assert!(filename_terms("% _ ' -").is_empty());
+        assert_eq!(filename_terms("a b c d e f g h").len(), MAX_TERMS);
+    }
+
+    #[test]
+    fn test_terms_are_never_options() {
+        assert_eq!(filename_terms("-export-csv out.csv --help"), ["export-csv", "out.csv", "help"]);
+        assert_eq!(everything_args(&filename_terms("-n 0 -r .*"), 20), ["es.exe", "-n", "20", "n", "0", "r"]);
+    }
+
+    #[test]
+    fn test_args() {
+        let terms = filename_terms("quarterly report");
+        assert_eq!(everything_args(&terms, 20), ["es.exe", "-n", "20", "quarterly", "report"]);
+        let script = windows_search_args(&terms, 20).pop().unwrap();
+        assert!(script.contains("SELECT TOP 20 System.ItemPathDisplay FROM SystemIndex"));
+        assert!(script.contains("System.FileName LIKE '%quarterly%' AND System.FileName LIKE '%report%'"));
+    }
+}
@@ -0,0 +1,191 @@
+use std::io::Write;
+use std::path::Path;
+
+use anyhow::Result;
+use lancedb::connection::Connection;
+use log::{error, info};
+use serde::Serialize;
+use zip::write::SimpleFileOptions;
+
+use crate::config::{get_table_name, Config};
+
+const REDACTED: &str = "<redacted>";
+
+/// Logs panics before the default hook runs so a crash leaves its message and
+/// location in rememex.log instead of only on a console nobody sees.
+pub fn install_panic_hook() {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let thread = std::thread::current();
+        let location = info
+            .location()
+            .map(|l| format!("{}:{}", l.file(), l.line()))
+            .unwrap_or_else(|| "unknown location".to_string());
+        let message = info
+            .payload()
+            .downcast_ref::<&str>()
+            .map(|s| s.to_string())
+            .or_else(|| info.payload().downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "non-string panic payload".to_string());
+        error!(
+            "Panic on thread '{}' at {}: {}",
+            thread.name().unwrap_or("unnamed"),
+            location,
+            message
+        );
+        log::logger().flush();
+        default_hook(info);
+    }));
+}
+
+fn is_secret_key(key: &str) -> bool {
+    let key = key.to_lowercase();
+    key.contains("api_key")
+        || key.contains("access_key")
+        || key.contains("account_key")
+        || key.contains("token")
+        || key.contains("secret")
+        || key.contains("password")
+}
+
+/// Blanks out anything that looks like a credential, at any depth, so the
+/// config can be attached to a public bug report.
+pub fn redact_config(value: &mut serde_json::Value) {
+    match value {
+        serde_json::Value::Object(map) => {
+            for (key, v) in map.iter_mut() {
+                if is_secret_key(key) && !v.is_null() && v.as_str() != Some("") {
+                    *v = serde_json::Value::String(REDACTED.to_string());
+                } else {
+                    redact_config(v);
+                }
+            }
+        }
+        serde_json::Value::Array(items) => items.iter_mut().for_each(redact_config),
+        _ => {}
+    }
+}
+
+#[derive(Serialize)]
+struct ContainerStats {
+    name: String,
+    indexed_paths: usize,
+    excluded_paths: usize,
+    chunks: Option<usize>,
+}
+
+#[derive(Serialize)]
+struct IndexStats {
+    app_version: &'static str,
+    os: &'static str,
+    arch: &'static str,
+    active_container: String,
+    containers: Vec<ContainerStats>,
+}
+
+async fn collect_stats(config: &Config, db: Option<&Connection>) -> IndexStats {
+    let mut names: Vec<&String> = config.containers.keys().collect();
+    names.sort();
+
+    let mut containers = Vec::with_capacity(names.len());
+    for name in names {
+        let info = &config.containers[name];
+        let chunks = match db {
+            Some(db) => match db.open_table(get_table_name(name)).execute().await {
+                Ok(table) => table.count_rows(None).await.ok(),
+                Err(_) => Some(0),
+            },
+            None => None,
+        };
+        containers.push(ContainerStats {
+            name: name.clone(),
+            indexed_paths: info.indexed_paths.len(),
+            excluded_paths: info.excluded_paths.len(),
+            chunks,
+        });
+    }
+
+    IndexStats {
+        app_version: env!("CARGO_PKG_VERSION"),
+        os: std::env::consts::OS,
+        arch: std::env::consts::ARCH,
+        active_container: config.active_container.clone(),
+        containers,
+    }
+}
+
+/// Writes a zip with every rememex*.log in `log_dir`, the redacted config and
+/// per-container index stats. `db` is optional so a bundle can still be made
+/// when the database failed to open, which is usually when one is needed.
+pub async fn export_bundle(
+    dest: &Path,
+    log_dir: &Path,
+    config: &Config,
+    db: Option<&Connection>,
+) -> Result<()> {
+    let mut config_json = serde_json::to_value(config)?;
+    redact_config(&mut config_json);
+    let stats = collect_stats(config, db).await;
+
+    let file = std::fs::File::create(dest)?;
+    let mut zip = zip::ZipWriter::new(file);
+    let options = SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+
+    zip.start_file("config.json", options)?;
+    zip.write_all(serde_json::to_string_pretty(&config_json)?.as_bytes())?;
+
+    zip.start_file("index_stats.json", options)?;
+    zip.write_all(serde_json::to_string_pretty(&stats)?.as_bytes())?;
+
+    let mut log_count = 0;
+    if let Ok(entries) = std::fs::read_dir(log_dir) {
+        for entry in entries.flatten() {
+            let name = entry.file_name().to_string_lossy().to_string();
+            if !name.starts_with("rememex") || !name.ends_with(".log") {
+                continue;
+            }
+            match std::fs::read(entry.path()) {
+                Ok(bytes) => {
+                    zip.start_file(format!("logs/{}", name), options)?;
+                    zip.write_all(&bytes)?;
+                    log_count += 1;
+                }
+                Err(e) => error!("Diagnostics: could not read {}: {}", name, e),
+            }
+        }
+    }
+
+    zip.finish()?;
+    info!("Diagnostics exported to {} ({} log files)", dest.display(), log_count);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_redact_config() {
+        let mut value = json!({
+            "embedding_provider": { "type": "remote", "api_key": "sk-123", "model": "m" },
+            "hyde": { "api_key": null, "endpoint": "http://localhost" },
+            "containers": {
+                "Work": { "embedding_provider": { "type": "remote", "api_key": "sk-456" } },
+                "Team": {
+                    "db_uri": "s3://team-bucket/rememex",
+                    "storage_options": { "aws_access_key_id": "AKIA123", "aws_region": "eu-west-1" }
+                }
+            },
+            "hotkey": "Alt+Space"
+        });
+        redact_config(&mut value);
+        assert_eq!(value["embedding_provider"]["api_key"], REDACTED);
+        assert_eq!(value["embedding_provider"]["model"], "m");
+        assert!(value["hyde"]["api_key"].is_null());
+        assert_eq!(value["containers"]["Work"]["embedding_provider"]["api_key"], REDACTED);
+        assert_eq!(value["containers"]["Team"]["storage_options"]["aws_access_key_id"], REDACTED);
+        assert_eq!(value["containers"]["Team"]["storage_options"]["aws_region"], "eu-west-1");
+        assert_eq!(value["hotkey"], "Alt+Space");
+    }
+}
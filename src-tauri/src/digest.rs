@@ -0,0 +1,362 @@
+//! A markdown note a day on the files changed in the last 24 hours, written
+//! by the summaries LLM when one is set up and a plain list otherwise. The
+//! notes go in a folder of their own, indexed into a container of their own,
+//! so "what did I touch last Tuesday" is a search away.
+
+use std::collections::{BTreeMap, HashSet};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use arrow_array::{Int64Array, StringArray};
+use chrono::{NaiveDate, TimeZone, Timelike};
+use futures::TryStreamExt;
+use lancedb::connection::Connection;
+use lancedb::query::{ExecutableQuery, QueryBase};
+use log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+use tokio::sync::Mutex;
+
+use crate::config::{get_table_name, Config, ConfigState, ContainerInfo};
+use crate::indexer;
+use crate::state::{DbState, ProviderState};
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct DigestConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Local hour the digest is written at, or at the first check after it
+    /// when the app wasn't running.
+    #[serde(default = "default_hour")]
+    pub hour: u8,
+    /// Container the digests are indexed into, created when missing.
+    #[serde(default = "default_container")]
+    pub container: String,
+    /// Show a notification once the digest is written.
+    #[serde(default = "default_true")]
+    pub notify: bool,
+}
+
+fn default_hour() -> u8 {
+    8
+}
+
+fn default_container() -> String {
+    "Digest".to_string()
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl Default for DigestConfig {
+    fn default() -> Self {
+        Self { enabled: false, hour: default_hour(), container: default_container(), notify: true }
+    }
+}
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(10 * 60);
+const WINDOW_SECS: i64 = 24 * 60 * 60;
+/// Files the prompt shows the start of; the rest are only counted.
+const PROMPT_FILES: usize = 40;
+const EXCERPT_CHARS: usize = 300;
+/// Files listed per container under the summary.
+const LISTED_FILES: usize = 100;
+const MAX_TOKENS: u32 = 300;
+const TIMEOUT: Duration = Duration::from_secs(90);
+
+const PROMPT: &str = "\
+You write a short daily digest for someone's personal search index. Given the start of \
+each file they changed in the last day, grouped by collection, write 3-5 sentences on \
+what they worked on: name the concrete projects, documents and topics. No preamble, \
+no lists, no advice.";
+
+#[derive(Clone, Debug, PartialEq)]
+struct ChangedFile {
+    path: String,
+    mtime: i64,
+    excerpt: String,
+}
+
+pub fn digest_dir() -> PathBuf {
+    crate::config::app_data_dir().join("digests")
+}
+
+fn digest_path(date: NaiveDate) -> PathBuf {
+    digest_dir().join(format!("{}.md", date.format("%Y-%m-%d")))
+}
+
+fn file_name(path: &str) -> &str {
+    path.rsplit(['/', '\\']).next().unwrap_or(path)
+}
+
+/// Files of `table_name` modified at or after `since`, newest first, each
+/// with the start of its first chunk.
+async fn changed_files(db: &Connection, table_name: &str, since: i64, excluded: &[String]) -> Result<Vec<ChangedFile>> {
+    let Ok(table) = indexer::snapshot::open(db, table_name).await else {
+        return Ok(Vec::new());
+    };
+    // Unknown starts (-1) are let through, and the first one per file kept.
+    let filter = format!("mtime >= {} AND {} < 1", since, indexer::db::CHUNK_START_COLUMN);
+    let mut stream = table
+        .query()
+        .only_if(filter)
+        .select(lancedb::query::Select::Columns(vec!["path".to_string(), "mtime".to_string(), "content".to_string()]))
+        .execute()
+        .await?;
+    let mut seen = HashSet::new();
+    let mut files = Vec::new();
+    while let Some(batch) = stream.try_next().await? {
+        let strings = |name: &str| batch.column_by_name(name).and_then(|c| c.as_any().downcast_ref::<StringArray>());
+        let times = batch.column_by_name("mtime").and_then(|c| c.as_any().downcast_ref::<Int64Array>());
+        let (Some(paths), Some(contents), Some(times)) = (strings("path"), strings("content"), times) else {
+            continue;
+        };
+        for i in 0..batch.num_rows() {
+            let path = paths.value(i);
+            if crate::config::is_path_excluded(excluded, path) || !seen.insert(path.to_string()) {
+                continue;
+            }
+            files.push(ChangedFile {
+                path: path.to_string(),
+                mtime: times.value(i),
+                excerpt: contents.value(i).chars().take(EXCERPT_CHARS).collect(),
+            });
+        }
+    }
+    files.sort_by(|a, b| b.mtime.cmp(&a.mtime).then_with(|| a.path.cmp(&b.path)));
+    Ok(files)
+}
+
+fn prompt(changed: &BTreeMap<String, Vec<ChangedFile>>) -> String {
+    let mut prompt = String::new();
+    let mut shown = 0;
+    let total: usize = changed.values().map(Vec::len).sum();
+    for (container, files) in changed {
+        if shown == PROMPT_FILES {
+            break;
+        }
+        prompt.push_str(&format!("Collection: {}\n", container));
+        for file in files.iter().take(PROMPT_FILES.saturating_sub(shown)) {
+            prompt.push_str(&format!("\n---\n{}\n", file.excerpt.trim()));
+            shown += 1;
+        }
+        prompt.push('\n');
+    }
+    if total > shown {
+        prompt.push_str(&format!("({} more files)\n", total - shown));
+    }
+    prompt
+}
+
+/// The digest note: the summary when there is one, then the files per
+/// container with the local time each was changed.
+fn render<Tz: TimeZone>(
+    tz: &Tz,
+    date: NaiveDate,
+    summary: Option<&str>,
+    changed: &BTreeMap<String, Vec<ChangedFile>>,
+) -> String
+where
+    Tz::Offset: std::fmt::Display,
+{
+    let total: usize = changed.values().map(Vec::len).sum();
+    let mut note = format!("# Digest for {}\n\n", date.format("%A, %B %-d, %Y"));
+    match summary {
+        Some(summary) => note.push_str(&format!("{}\n\n", summary.trim())),
+        None if total == 0 => note.push_str("No indexed files changed in the last day.\n"),
+        None => note.push_str(&format!("{} indexed files changed in the last day.\n\n", total)),
+    }
+    for (container, files) in changed.iter().filter(|(_, files)| !files.is_empty()) {
+        note.push_str(&format!("## {}\n\n", container));
+        for file in files.iter().take(LISTED_FILES) {
+            let time = tz
+                .timestamp_opt(file.mtime, 0)
+                .earliest()
+                .map_or_else(String::new, |t| t.format("%H:%M").to_string());
+            note.push_str(&format!("- {} {} ({})\n", time, file_name(&file.path), file.path));
+        }
+        if files.len() > LISTED_FILES {
+            note.push_str(&format!("- and {} more\n", files.len() - LISTED_FILES));
+        }
+        note.push('\n');
+    }
+    note
+}
+
+/// Adds the digest container, indexing the digest folder, when it's missing.
+async fn ensure_container(app: &AppHandle, name: &str) -> Result<(), String> {
+    let config_state = app.state::<ConfigState>();
+    let dir = digest_dir().to_string_lossy().to_string();
+    {
+        let mut config = config_state.config.lock().await;
+        match config.containers.get_mut(name) {
+            Some(info) if info.indexed_paths.contains(&dir) => return Ok(()),
+            Some(info) => info.indexed_paths.push(dir),
+            None => {
+                info!("Creating digest container '{}'", name);
+                config.containers.insert(name.to_string(), ContainerInfo {
+                    description: "Daily digests of changed files".to_string(),
+                    indexed_paths: vec![dir],
+                    embedding_provider: None,
+                    excluded_paths: Vec::new(),
+                    pinned_paths: Vec::new(),
+                    db_uri: None,
+                    role: Default::default(),
+                    storage_options: Default::default(),
+                    quota_mb: None,
+                    quota_policy: Default::default(),
+                    indexing: Default::default(),
+                    search: Default::default(),
+                    redaction: Default::default(),
+                    locked: false,
+                });
+            }
+        }
+    }
+    config_state.save().await?;
+    crate::tray::refresh(app).await;
+    Ok(())
+}
+
+/// Indexes the note into the digest container. Only done while the loaded
+/// model is the one that container embeds with; otherwise the note waits
+/// for the container's next index run.
+async fn index_note(app: &AppHandle, config: &Config, db: &Connection, path: &std::path::Path) -> Result<()> {
+    let name = &config.digest.container;
+    if config.provider_label(name) != config.provider_label(&config.active_container) {
+        info!("Digest written; '{}' embeds with another model, it gets indexed on its next run", name);
+        return Ok(());
+    }
+    let provider_state = app.state::<Arc<Mutex<ProviderState>>>().inner().clone();
+    let indexing = config.indexing_for(name);
+    let table_name = get_table_name(name);
+    indexer::index_single_file(
+        path,
+        &table_name,
+        db,
+        &provider_state,
+        false,
+        false,
+        indexing.chunk_size,
+        indexing.chunk_overlap,
+        indexing.version_history_days,
+        indexing.entities,
+        &indexing.extractors,
+        indexing.secrets,
+    )
+    .await?;
+    indexer::refresh_fts_index(&table_name, db).await
+}
+
+/// Writes and indexes today's digest. Returns the files it covers.
+async fn write(app: &AppHandle, config: &Config, date: NaiveDate, now: i64) -> Result<usize> {
+    let db_path = app.state::<Arc<Mutex<DbState>>>().lock().await.path.clone();
+    let db = indexer::db::connect(&db_path.to_string_lossy()).await?;
+
+    // Containers in other databases would need connections of their own.
+    let mut changed = BTreeMap::new();
+    for (name, info) in &config.containers {
+        if name == &config.digest.container || info.db_uri.is_some() {
+            continue;
+        }
+        let files = changed_files(&db, &get_table_name(name), now - WINDOW_SECS, &info.excluded_paths).await?;
+        if !files.is_empty() {
+            changed.insert(name.clone(), files);
+        }
+    }
+    let total = changed.values().map(Vec::len).sum();
+
+    let llm = &config.indexing.summaries;
+    let summary = if total == 0 || !llm.is_active() || crate::config::is_offline() {
+        None
+    } else {
+        match indexer::hyde::chat(&llm.endpoint, &llm.model, llm.api_key.as_deref(), PROMPT, &prompt(&changed), MAX_TOKENS, TIMEOUT).await {
+            Ok(summary) => Some(summary),
+            Err(e) => {
+                warn!("Digest summary failed, writing the plain list: {}", e);
+                None
+            }
+        }
+    };
+
+    let path = digest_path(date);
+    std::fs::create_dir_all(digest_dir())?;
+    std::fs::write(&path, render(&chrono::Local, date, summary.as_deref(), &changed))?;
+    info!("Digest for {} written: {} files", date, total);
+
+    ensure_container(app, &config.digest.container).await.map_err(anyhow::Error::msg)?;
+    let config = app.state::<ConfigState>().config.lock().await.clone();
+    if let Err(e) = index_note(app, &config, &db, &path).await {
+        warn!("Indexing the digest failed: {}", e);
+    }
+    Ok(total)
+}
+
+/// Checks every few minutes whether today's digest is due and not written
+/// yet. A failed one is tried again on the next check.
+pub fn spawn_scheduler(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(CHECK_INTERVAL);
+        loop {
+            interval.tick().await;
+
+            let config = app.state::<ConfigState>().config.lock().await.clone();
+            if !config.digest.enabled || config.is_read_only() {
+                continue;
+            }
+            let now = chrono::Local::now();
+            if now.hour() < u32::from(config.digest.hour) || digest_path(now.date_naive()).exists() {
+                continue;
+            }
+            debug!("Writing the digest for {}", now.date_naive());
+            match write(&app, &config, now.date_naive(), now.timestamp()).await {
+                Ok(files) if config.digest.notify => crate::notifications::digest_written(&app, files),
+                Ok(_) => {}
+                Err(e) => warn!("Digest failed: {}", e),
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file(path: &str, mtime: i64, excerpt: &str) -> ChangedFile {
+        ChangedFile { path: path.to_string(), mtime, excerpt: excerpt.to_string() }
+    }
+
+    #[test]
+    fn test_render() {
+        let date = NaiveDate::from_ymd_opt(2024, 5, 16).unwrap();
+        let mut changed = BTreeMap::new();
+        changed.insert(
+            "Notes".to_string(),
+            vec![file("/notes/plan.md", 9 * 3600 + 5 * 60, "Q3 plan"), file("/notes/todo.md", 3600, "Todo")],
+        );
+        let note = render(&chrono::Utc, date, None, &changed);
+        assert!(note.starts_with("# Digest for Thursday, May 16, 2024\n\n2 indexed files changed"));
+        assert!(note.contains("## Notes\n\n- 09:05 plan.md (/notes/plan.md)\n- 01:00 todo.md (/notes/todo.md)\n"));
+
+        let note = render(&chrono::Utc, date, Some(" Planned Q3. "), &changed);
+        assert!(note.contains("\n\nPlanned Q3.\n\n## Notes"));
+
+        let note = render(&chrono::Utc, date, None, &BTreeMap::new());
+        assert!(note.ends_with("No indexed files changed in the last day.\n"));
+    }
+
+    #[test]
+    fn test_prompt() {
+        let mut changed = BTreeMap::new();
+        changed.insert("A".to_string(), (0..PROMPT_FILES).map(|i| file("/a.md", 0, &format!("note {}", i))).collect());
+        changed.insert("B".to_string(), vec![file("/b.md", 0, "budget"), file("/c.md", 0, "calendar")]);
+        let prompt = prompt(&changed);
+        assert!(prompt.starts_with("Collection: A\n"));
+        assert!(prompt.contains("note 0"));
+        assert!(!prompt.contains("Collection: B"));
+        assert!(prompt.ends_with("(2 more files)\n"));
+    }
+}
@@ -0,0 +1,182 @@
+//! Parts of the editor protocol (`rememex-mcp --editor`) that don't need the
+//! index. See EDITOR.md.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::config::{is_path_excluded, Config};
+use crate::indexer::pipeline::split_context;
+
+pub const PROTOCOL_VERSION: u32 = 1;
+
+pub const METHODS: &[&str] = &["initialize", "search", "locate", "annotate", "index_workspace", "status"];
+
+/// A missing `id` makes it a notification, which gets no answer.
+#[derive(Deserialize)]
+pub struct Request {
+    pub id: Option<Value>,
+    pub method: String,
+    #[serde(default)]
+    pub params: Value,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RpcError {
+    pub code: i64,
+    pub message: String,
+}
+
+impl RpcError {
+    pub fn parse_error(message: impl Into<String>) -> Self {
+        Self { code: -32700, message: message.into() }
+    }
+
+    pub fn method_not_found(method: &str) -> Self {
+        Self { code: -32601, message: format!("unknown method '{}'", method) }
+    }
+
+    pub fn invalid_params(message: impl Into<String>) -> Self {
+        Self { code: -32602, message: message.into() }
+    }
+
+    pub fn internal(message: impl Into<String>) -> Self {
+        Self { code: -32603, message: message.into() }
+    }
+}
+
+pub fn params<T: for<'de> Deserialize<'de>>(method: &str, params: Value) -> Result<T, RpcError> {
+    let params = if params.is_null() { Value::Object(Default::default()) } else { params };
+    serde_json::from_value(params).map_err(|e| RpcError::invalid_params(format!("{}: {}", method, e)))
+}
+
+pub fn response(id: Value, result: Result<Value, RpcError>) -> String {
+    let body = match result {
+        Ok(result) => serde_json::json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err(error) => serde_json::json!({ "jsonrpc": "2.0", "id": id, "error": error }),
+    };
+    body.to_string()
+}
+
+/// 1-based, inclusive line range of a result in its file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Location {
+    pub line: usize,
+    pub end_line: usize,
+}
+
+pub fn snippet_body(snippet: &str) -> String {
+    let (_, rest) = split_context(snippet);
+    match rest.strip_prefix("File: ") {
+        Some(after) => after.split_once('\n').map_or("", |(_, body)| body).to_string(),
+        None => rest,
+    }
+}
+
+/// Where in `text` the chunk behind `snippet` sits: verbatim when it can be,
+/// else the longest run of matching lines.
+pub fn locate(text: &str, snippet: &str) -> Option<Location> {
+    if snippet.starts_with("[annotation] ") || snippet.starts_with("[summary] ") {
+        return None;
+    }
+    let body = snippet_body(snippet);
+    let body = body.trim();
+    if body.is_empty() {
+        return None;
+    }
+    if let Some(pos) = text.find(body) {
+        let line = text[..pos].matches('\n').count() + 1;
+        return Some(Location { line, end_line: line + body.matches('\n').count() });
+    }
+
+    let wanted: Vec<&str> = body.lines().map(str::trim).filter(|l| !l.is_empty()).collect();
+    let lines: Vec<&str> = text.lines().collect();
+    let mut best: Option<(usize, Location)> = None;
+    for start in 0..lines.len() {
+        let first = lines[start].trim();
+        if first.is_empty() || !first.contains(wanted[0]) && !wanted[0].contains(first) {
+            continue;
+        }
+        let (mut matched, mut end, mut i) = (1, start, start + 1);
+        while matched < wanted.len() && i < lines.len() {
+            let line = lines[i].trim();
+            if !line.is_empty() {
+                if line != wanted[matched] {
+                    break;
+                }
+                matched += 1;
+                end = i;
+            }
+            i += 1;
+        }
+        if best.is_none_or(|(m, _)| matched > m) {
+            best = Some((matched, Location { line: start + 1, end_line: end + 1 }));
+        }
+    }
+    // A single short line matching somewhere is as likely chance as not.
+    best.filter(|(matched, _)| *matched > 1 || wanted.len() == 1).map(|(_, loc)| loc)
+}
+
+/// The active container is preferred when several cover `workspace`.
+pub fn workspace_container(config: &Config, workspace: &str) -> Option<String> {
+    let workspace = workspace.trim_end_matches(['/', '\\']);
+    let covers = |paths: &[String]| {
+        is_path_excluded(paths, workspace) || paths.iter().any(|p| is_path_excluded(&[workspace.to_string()], p))
+    };
+    let active = config.containers.get(&config.active_container);
+    if active.is_some_and(|info| covers(&info.indexed_paths)) {
+        return Some(config.active_container.clone());
+    }
+    let mut names: Vec<&String> = config.containers.keys().collect();
+    names.sort();
+    names.into_iter().find(|name| covers(&config.containers[*name].indexed_paths)).cloned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FILE: &str = "use std::fs;\n\nfn load() {\n    let text = fs::read(\"a\");\n    parse(text)\n}\n\nfn save() {}\n";
+
+    #[test]
+    fn test_locate() {
+        let snippet = "File: config.rs\nContext: load\nfn load() {\n    let text = fs::read(\"a\");\n    parse(text)\n}";
+        assert_eq!(locate(FILE, snippet), Some(Location { line: 3, end_line: 6 }));
+        assert_eq!(locate(FILE, "File: config.rs\nfn save() {}"), Some(Location { line: 8, end_line: 8 }));
+
+        let moved = "File: config.rs\nfn load() {\n  let text = fs::read(\"a\");\n  parse(text)\n}";
+        assert_eq!(locate(FILE, moved), Some(Location { line: 3, end_line: 6 }));
+
+        assert_eq!(locate(FILE, "[annotation] check load"), None);
+        assert_eq!(locate(FILE, "File: config.rs\nfn gone() {}\nfn other() {}"), None);
+        assert_eq!(locate(FILE, "File: config.rs\n"), None);
+    }
+
+    #[test]
+    fn test_workspace_container() {
+        let config: Config = serde_json::from_str(
+            r#"{
+                "containers": {
+                    "Default": { "description": "", "indexed_paths": ["/home/u/notes"] },
+                    "Work": { "description": "", "indexed_paths": ["/home/u/work/api"] }
+                },
+                "active_container": "Default"
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(workspace_container(&config, "/home/u/notes/"), Some("Default".to_string()));
+        assert_eq!(workspace_container(&config, "/home/u/work/api/src"), Some("Work".to_string()));
+        assert_eq!(workspace_container(&config, "/home/u/work"), Some("Work".to_string()));
+        assert_eq!(workspace_container(&config, "/home/u/workshop"), None);
+    }
+
+    #[test]
+    fn test_response() {
+        let ok: Value = serde_json::from_str(&response(Value::from(1), Ok(serde_json::json!({ "found": true })))).unwrap();
+        assert_eq!(ok, serde_json::json!({ "jsonrpc": "2.0", "id": 1, "result": { "found": true } }));
+        let err: Value = serde_json::from_str(&response(Value::from("a"), Err(RpcError::method_not_found("nope")))).unwrap();
+        assert_eq!(err["error"]["code"], -32601);
+        assert_eq!(err["id"], "a");
+        let req: Request = serde_json::from_str(r#"{"jsonrpc":"2.0","method":"status"}"#).unwrap();
+        assert!(req.id.is_none() && req.params.is_null());
+    }
+}
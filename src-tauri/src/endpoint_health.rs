@@ -0,0 +1,95 @@
+use std::sync::{Arc, LazyLock};
+use std::time::Duration;
+
+use log::{info, warn};
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+use tokio::sync::Mutex;
+
+use crate::state::ProviderState;
+
+const PING_INTERVAL: Duration = Duration::from_secs(60);
+
+#[derive(Serialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum EndpointState {
+    /// The provider runs locally, or hasn't been checked yet.
+    #[default]
+    None,
+    Reachable,
+    /// Searches fall back to full-text matches until it answers again.
+    Unreachable,
+}
+
+#[derive(Serialize, Clone, Debug, Default, PartialEq)]
+pub struct EndpointHealth {
+    pub state: EndpointState,
+    pub error: Option<String>,
+}
+
+static HEALTH: LazyLock<std::sync::Mutex<EndpointHealth>> = LazyLock::new(Default::default);
+
+pub fn current() -> EndpointHealth {
+    HEALTH.lock().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// Stores what a ping or a search found out about the remote endpoint, and
+/// tells the window when that changed.
+pub fn record(app: &AppHandle, health: EndpointHealth) {
+    let changed = {
+        let mut current = HEALTH.lock().unwrap_or_else(|e| e.into_inner());
+        let changed = current.state != health.state;
+        *current = health.clone();
+        changed
+    };
+    if !changed {
+        return;
+    }
+    match health.state {
+        EndpointState::Unreachable => {
+            warn!("Embedding endpoint unreachable: {}", health.error.as_deref().unwrap_or("unknown error"))
+        }
+        EndpointState::Reachable => info!("Embedding endpoint reachable"),
+        EndpointState::None => {}
+    }
+    let _ = app.emit("endpoint-health", health);
+}
+
+/// What a failed or successful remote call says about the endpoint.
+pub fn from_result<T>(result: &anyhow::Result<T>) -> EndpointHealth {
+    match result {
+        Ok(_) => EndpointHealth { state: EndpointState::Reachable, error: None },
+        Err(e) => EndpointHealth { state: EndpointState::Unreachable, error: Some(e.to_string()) },
+    }
+}
+
+async fn check(provider_state: &Mutex<ProviderState>, app: &AppHandle) {
+    let result = {
+        let guard = provider_state.lock().await;
+        match guard.provider.as_ref() {
+            Some(provider) => provider.check_reachable().await,
+            None => return,
+        }
+    };
+    let health = result.map(|r| from_result(&r)).unwrap_or_default();
+    record(app, health);
+}
+
+/// Pings the remote embedding endpoint every minute so the status bar can
+/// say it's down before a search finds out.
+pub fn spawn_monitor(provider_state: Arc<Mutex<ProviderState>>, app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(PING_INTERVAL);
+        loop {
+            interval.tick().await;
+            check(&provider_state, &app).await;
+        }
+    });
+}
+
+/// One check right away, after the provider was switched.
+pub fn check_soon(provider_state: Arc<Mutex<ProviderState>>, app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        check(&provider_state, &app).await;
+    });
+}
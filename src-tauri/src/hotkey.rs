@@ -0,0 +1,48 @@
+use log::{info, warn};
+use tauri::AppHandle;
+use tauri_plugin_global_shortcut::GlobalShortcutExt;
+
+use crate::config::{parse_hotkey, reserved_hotkey, validate_hotkey};
+
+/// Replaces the registered global hotkey without a restart. If the new combination
+/// cannot be registered (usually because another app owns it) the old one is put
+/// back so the user is never left without a way to summon the window.
+pub fn swap(app: &AppHandle, old: &str, new: &str) -> Result<(), String> {
+    validate_hotkey(new)?;
+    if let Some(reason) = reserved_hotkey(new) {
+        return Err(format!("{} is reserved by the system ({})", new, reason));
+    }
+
+    let shortcuts = app.global_shortcut();
+    let old_shortcut = parse_hotkey(old);
+    let new_shortcut = parse_hotkey(new);
+    if old_shortcut == new_shortcut {
+        return Ok(());
+    }
+
+    let _ = shortcuts.unregister(old_shortcut);
+    if let Err(e) = shortcuts.register(new_shortcut) {
+        warn!("Failed to register hotkey {}: {}", new, e);
+        if let Err(e) = shortcuts.register(old_shortcut) {
+            warn!("Failed to restore hotkey {}: {}", old, e);
+        }
+        return Err(format!("{} is already in use by another application", new));
+    }
+
+    info!("Global hotkey changed: {} -> {}", old, new);
+    Ok(())
+}
+
+/// Temporarily releases the global hotkey so the settings capture widget can
+/// receive the combination instead of toggling the window.
+pub fn set_paused(app: &AppHandle, hotkey: &str, paused: bool) -> Result<(), String> {
+    let shortcuts = app.global_shortcut();
+    let shortcut = parse_hotkey(hotkey);
+    if paused {
+        shortcuts.unregister(shortcut).map_err(|e| e.to_string())
+    } else if shortcuts.is_registered(shortcut) {
+        Ok(())
+    } else {
+        shortcuts.register(shortcut).map_err(|e| e.to_string())
+    }
+}
@@ -0,0 +1,56 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use log::info;
+use tauri::{AppHandle, Emitter};
+use tokio::sync::Mutex;
+
+use crate::config::Config;
+use crate::state::{ProviderState, RerankerState};
+
+const REAPER_INTERVAL: Duration = Duration::from_secs(30);
+
+pub fn spawn_reaper(
+    config: Arc<Mutex<Config>>,
+    provider_state: Arc<Mutex<ProviderState>>,
+    reranker_state: Arc<Mutex<RerankerState>>,
+    app: AppHandle,
+) {
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(REAPER_INTERVAL);
+        loop {
+            interval.tick().await;
+
+            let timeout_mins = config.lock().await.model_idle_timeout_mins;
+            if timeout_mins == 0 {
+                continue;
+            }
+            let idle = Duration::from_secs(timeout_mins * 60);
+            let mut unloaded = false;
+
+            {
+                let guard = provider_state.lock().await;
+                if let Some(provider) = guard.provider.as_ref() {
+                    if provider.unload_if_idle(idle).await {
+                        info!("Embedding model unloaded after {} min idle", timeout_mins);
+                        unloaded = true;
+                    }
+                }
+            }
+
+            {
+                let mut guard = reranker_state.lock().await;
+                if guard.reranker.is_some() && guard.last_used.elapsed() >= idle {
+                    guard.reranker = None;
+                    guard.unloaded = true;
+                    info!("Reranker unloaded after {} min idle", timeout_mins);
+                    unloaded = true;
+                }
+            }
+
+            if unloaded {
+                let _ = app.emit("model-unloaded", ());
+            }
+        }
+    });
+}
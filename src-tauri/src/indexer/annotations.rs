@@ -11,12 +11,12 @@ use lancedb::connection::Connection;
 use lancedb::query::{ExecutableQuery, QueryBase};
 use lancedb::Table;
 use log::{debug, info};
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use tokio::sync::Mutex;
 
 use crate::state::ProviderState;
 
-#[derive(Serialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct Annotation {
     pub id: String,
     pub path: String,
@@ -25,7 +25,7 @@ pub struct Annotation {
     pub created_at: i64,
 }
 
-fn annotations_table_name(container_table: &str) -> String {
+pub(super) fn annotations_table_name(container_table: &str) -> String {
     format!("{}_annotations", container_table)
 }
 
@@ -96,22 +96,38 @@ pub async fn add_annotation(
     note: &str,
     source: &str,
 ) -> Result<Annotation> {
+    let annotation = Annotation {
+        id: generate_id(),
+        path: path.to_string(),
+        note: note.to_string(),
+        source: source.to_string(),
+        created_at: now_unix(),
+    };
+    insert_annotation(db, container_table, provider_state, &annotation).await?;
+    Ok(annotation)
+}
+
+/// Embeds and stores `annotation` as it is, id and date included, e.g. one
+/// made on another device.
+pub async fn insert_annotation(
+    db: &Connection,
+    container_table: &str,
+    provider_state: &Arc<Mutex<ProviderState>>,
+    annotation: &Annotation,
+) -> Result<()> {
     let vector = {
         let guard = provider_state.lock().await;
         let provider = guard
             .provider
             .as_ref()
             .ok_or_else(|| anyhow!("Embedding provider not initialized"))?;
-        let vectors: Vec<Vec<f32>> = provider.embed_passages(vec![note.to_string()]).await?;
+        let vectors: Vec<Vec<f32>> = provider.embed_passages(vec![annotation.note.clone()]).await?;
         vectors.into_iter().next().ok_or_else(|| anyhow!("Empty embedding result"))?
     };
 
     let dim = vector.len();
     let table = get_or_create_annotations_table(db, container_table, dim).await?;
 
-    let id = generate_id();
-    let created_at = now_unix();
-
     let schema = Arc::new(make_annotations_schema(dim));
     let vector_array = FixedSizeListArray::try_new(
         Arc::new(Field::new("item", DataType::Float32, true)),
@@ -123,12 +139,12 @@ pub async fn add_annotation(
     let batch = RecordBatch::try_new(
         schema.clone(),
         vec![
-            Arc::new(StringArray::from(vec![id.as_str()])),
-            Arc::new(StringArray::from(vec![path])),
-            Arc::new(StringArray::from(vec![note])),
-            Arc::new(StringArray::from(vec![source])),
+            Arc::new(StringArray::from(vec![annotation.id.as_str()])),
+            Arc::new(StringArray::from(vec![annotation.path.as_str()])),
+            Arc::new(StringArray::from(vec![annotation.note.as_str()])),
+            Arc::new(StringArray::from(vec![annotation.source.as_str()])),
             Arc::new(vector_array),
-            Arc::new(Int64Array::from(vec![created_at])),
+            Arc::new(Int64Array::from(vec![annotation.created_at])),
         ],
     )?;
 
@@ -137,15 +153,8 @@ pub async fn add_annotation(
         .execute()
         .await?;
 
-    debug!("Annotation added: id={}, path={}", id, path);
-
-    Ok(Annotation {
-        id,
-        path: path.to_string(),
-        note: note.to_string(),
-        source: source.to_string(),
-        created_at,
-    })
+    debug!("Annotation added: id={}, path={}", annotation.id, annotation.path);
+    Ok(())
 }
 
 pub async fn get_annotations(
@@ -217,6 +226,12 @@ pub async fn delete_annotation(
     Ok(())
 }
 
+/// Version of the container's annotations table, None while it doesn't exist.
+pub async fn annotations_version(db: &Connection, container_table: &str) -> Option<u64> {
+    let table = db.open_table(annotations_table_name(container_table)).execute().await.ok()?;
+    table.version().await.ok()
+}
+
 pub async fn search_annotations(
     db: &Connection,
     container_table: &str,
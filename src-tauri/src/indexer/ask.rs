@@ -0,0 +1,214 @@
+//! Questions about one file: the file is read and chunked on the spot, the
+//! chunks closest to the question picked out, and a chat endpoint answers
+//! from those alone, quoting them. Nothing is written to the index.
+
+use std::path::Path;
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use log::debug;
+use serde::Serialize;
+
+use super::embedding_provider::EmbeddingProvider;
+use super::hyde::HydeConfig;
+use super::summaries::SummaryConfig;
+use crate::config::IndexingConfig;
+
+/// Chunks handed to the endpoint.
+const PASSAGES: usize = 6;
+/// Chunks embedded at most; past this only the start of the file is asked.
+const MAX_CHUNKS: usize = 1024;
+const MAX_TOKENS: u32 = 500;
+const TIMEOUT: Duration = Duration::from_secs(90);
+
+const SYSTEM_PROMPT: &str = "\
+You answer questions about one document using only the numbered excerpts given. \
+Back every claim with a short exact quote from an excerpt in double quotes, followed \
+by its number like [2]. If the excerpts don't answer the question, say so plainly. \
+Answer in the language of the question. Be brief.";
+
+/// An OpenAI-compatible chat endpoint.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ChatEndpoint {
+    pub endpoint: String,
+    pub model: String,
+    pub api_key: Option<String>,
+}
+
+impl ChatEndpoint {
+    /// The summaries endpoint when one is set, HyDE's otherwise. Neither
+    /// has to be turned on for its own job to answer questions.
+    pub fn pick(summaries: &SummaryConfig, hyde: Option<&HydeConfig>) -> Option<Self> {
+        if !summaries.endpoint.is_empty() {
+            return Some(Self {
+                endpoint: summaries.endpoint.clone(),
+                model: summaries.model.clone(),
+                api_key: summaries.api_key.clone(),
+            });
+        }
+        hyde.filter(|hyde| !hyde.endpoint.is_empty()).map(|hyde| Self {
+            endpoint: hyde.endpoint.clone(),
+            model: hyde.model.clone(),
+            api_key: hyde.api_key.clone(),
+        })
+    }
+}
+
+/// A piece of the file the answer may quote.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct Passage {
+    /// What the answer cites it as, `[n]`.
+    pub n: usize,
+    pub text: String,
+    /// Line the passage starts on, when it could be found in the text.
+    pub line: Option<usize>,
+    /// Page label ("p.3") of a paged document.
+    pub page: Option<String>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct FileAnswer {
+    pub path: String,
+    /// None without a chat endpoint to ask; the passages still point at
+    /// where the answer probably is.
+    pub answer: Option<String>,
+    /// In file order.
+    pub passages: Vec<Passage>,
+}
+
+/// The file's text in chunks, each with its line or page.
+fn split(text: &str, ext: &str, indexing: &IndexingConfig) -> Vec<(String, Option<usize>, Option<String>)> {
+    if let Some(slices) = super::chunking::page_slices(text, ext, indexing.chunk_size, indexing.chunk_overlap) {
+        return slices.into_iter().map(|(chunk, page)| (chunk, None, Some(page))).collect();
+    }
+    let chunks = super::chunking::semantic_chunk_with_overrides(text, ext, indexing.chunk_size, indexing.chunk_overlap);
+    let starts = super::chunk_starts(text, &chunks);
+    chunks
+        .into_iter()
+        .zip(starts)
+        .map(|(chunk, start)| {
+            let line = usize::try_from(start).ok().map(|start| text[..start].matches('\n').count() + 1);
+            (chunk, line, None)
+        })
+        .collect()
+}
+
+/// Indexes of the `k` chunks scoring highest, in file order.
+fn top_chunks(scores: &[f32], k: usize) -> Vec<usize> {
+    let mut ranked: Vec<usize> = (0..scores.len()).collect();
+    ranked.sort_by(|&a, &b| scores[b].total_cmp(&scores[a]));
+    ranked.truncate(k);
+    ranked.sort_unstable();
+    ranked
+}
+
+/// The parts of the file at `path` closest to `question`.
+pub async fn passages(
+    provider: &dyn EmbeddingProvider,
+    path: &Path,
+    question: &str,
+    indexing: &IndexingConfig,
+) -> Result<Vec<Passage>> {
+    let text = super::file_io::read_file_content_with_config(path, indexing)
+        .ok_or_else(|| anyhow!("No text could be read from {}", path.display()))?;
+    let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("").to_lowercase();
+    let file_name = path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+
+    let mut chunks = split(&text, &ext, indexing);
+    chunks.retain(|(chunk, _, _)| !chunk.trim().is_empty());
+    if chunks.len() > MAX_CHUNKS {
+        debug!("ask: {} has {} chunks, asking about the first {}", path.display(), chunks.len(), MAX_CHUNKS);
+        chunks.truncate(MAX_CHUNKS);
+    }
+    if chunks.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let query = provider.embed_query(question).await?;
+    let mut scores = Vec::with_capacity(chunks.len());
+    for batch in chunks.chunks(super::EMBED_BATCH_SIZE) {
+        let texts = batch.iter().map(|(chunk, _, _)| super::with_header(&file_name, "", chunk)).collect();
+        for vector in provider.embed_passages(texts).await? {
+            scores.push(super::centroid::cosine(&query, &vector).unwrap_or(0.0));
+        }
+    }
+
+    Ok(top_chunks(&scores, PASSAGES)
+        .into_iter()
+        .enumerate()
+        .map(|(i, at)| {
+            let (text, line, page) = chunks[at].clone();
+            Passage { n: i + 1, text, line, page }
+        })
+        .collect())
+}
+
+fn prompt(file_name: &str, question: &str, passages: &[Passage]) -> String {
+    let mut prompt = format!("Document: {}\n", file_name);
+    for passage in passages {
+        let place = match (&passage.page, passage.line) {
+            (Some(page), _) => format!(" ({})", page),
+            (None, Some(line)) => format!(" (line {})", line),
+            (None, None) => String::new(),
+        };
+        prompt.push_str(&format!("\n[{}]{}\n{}\n", passage.n, place, passage.text.trim()));
+    }
+    prompt.push_str(&format!("\nQuestion: {}", question.trim()));
+    prompt
+}
+
+/// Asks `endpoint` to answer `question` from `passages`.
+pub async fn answer(endpoint: &ChatEndpoint, path: &str, question: &str, passages: &[Passage]) -> Result<String> {
+    if crate::config::is_offline() {
+        return Err(anyhow!(crate::config::offline_error("Asking about a file")));
+    }
+    let file_name = Path::new(path).file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+    let answer = super::hyde::chat(
+        &endpoint.endpoint,
+        &endpoint.model,
+        endpoint.api_key.as_deref(),
+        SYSTEM_PROMPT,
+        &prompt(&file_name, question, passages),
+        MAX_TOKENS,
+        TIMEOUT,
+    )
+    .await?;
+    Ok(answer.trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_top_chunks() {
+        assert_eq!(top_chunks(&[0.1, 0.9, 0.3, 0.8, 0.2], 3), vec![1, 2, 3]);
+        assert_eq!(top_chunks(&[0.5], 3), vec![0]);
+    }
+
+    #[test]
+    fn test_prompt() {
+        let passages = vec![
+            Passage { n: 1, text: "Rent is due on the 1st.\n".to_string(), line: Some(12), page: None },
+            Passage { n: 2, text: "Deposit: two months.".to_string(), line: None, page: Some("p.3".to_string()) },
+        ];
+        let prompt = prompt("lease.pdf", " When is rent due? ", &passages);
+        assert!(prompt.starts_with("Document: lease.pdf\n\n[1] (line 12)\nRent is due on the 1st.\n"));
+        assert!(prompt.contains("\n[2] (p.3)\nDeposit: two months.\n"));
+        assert!(prompt.ends_with("\nQuestion: When is rent due?"));
+    }
+
+    #[test]
+    fn test_pick_endpoint() {
+        let hyde = HydeConfig {
+            enabled: false,
+            endpoint: "http://localhost:11434/v1/chat/completions".to_string(),
+            model: "llama3".to_string(),
+            api_key: None,
+        };
+        assert_eq!(ChatEndpoint::pick(&SummaryConfig::default(), None), None);
+        assert_eq!(ChatEndpoint::pick(&SummaryConfig::default(), Some(&hyde)).map(|e| e.model), Some("llama3".to_string()));
+        let summaries = SummaryConfig { endpoint: "https://llm.example.com".to_string(), model: "gpt".to_string(), ..Default::default() };
+        assert_eq!(ChatEndpoint::pick(&summaries, Some(&hyde)).map(|e| e.model), Some("gpt".to_string()));
+    }
+}
@@ -0,0 +1,184 @@
+//! A container's chunks copied into a folder of their own, with what it
+//! takes to search them in `bundle.json`, for `rememex-cli`.
+
+use std::collections::HashSet;
+use std::path::Path;
+
+use anyhow::{anyhow, Result};
+use arrow_array::{RecordBatchIterator, StringArray};
+use futures::TryStreamExt;
+use lancedb::connection::Connection;
+use lancedb::query::ExecutableQuery;
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+
+use super::embedding_provider::EmbeddingProvider;
+use super::{db, file_vectors, query_router, snapshot, QueryExpansion, SearchScope};
+use crate::config::EmbeddingProviderConfig;
+
+pub const MANIFEST_FILE: &str = "bundle.json";
+const DB_DIR: &str = "lancedb";
+const TABLE: &str = "chunks";
+/// Bumped when the layout changes in a way an older CLI can't read.
+const FORMAT: u32 = 1;
+const SEARCH_LIMIT: usize = 50;
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Manifest {
+    pub format: u32,
+    pub container: String,
+    #[serde(default)]
+    pub description: String,
+    /// A remote provider's API key is left out.
+    pub embedding_provider: EmbeddingProviderConfig,
+    pub roots: Vec<String>,
+    pub files: usize,
+    pub chunks: usize,
+    pub created_at: i64,
+    pub app_version: String,
+}
+
+async fn copy_table(source: &lancedb::Table, dest: &Connection, to: &str) -> Result<(lancedb::Table, HashSet<String>, usize)> {
+    let schema = source.schema().await?;
+    let table = dest.create_table(to, RecordBatchIterator::new(vec![], schema.clone())).execute().await?;
+    let mut paths = HashSet::new();
+    let mut rows = 0;
+    let mut stream = source.query().execute().await?;
+    while let Some(batch) = stream.try_next().await? {
+        if let Some(column) = batch.column_by_name("path").and_then(|c| c.as_any().downcast_ref::<StringArray>()) {
+            paths.extend(column.iter().flatten().map(str::to_string));
+        }
+        rows += batch.num_rows();
+        table.add(RecordBatchIterator::new(vec![Ok(batch)], schema.clone())).execute().await?;
+    }
+    Ok((table, paths, rows))
+}
+
+/// `dest` must be missing or empty.
+pub async fn export(
+    source: &Connection,
+    table_name: &str,
+    container: &str,
+    description: &str,
+    roots: &[String],
+    provider: &EmbeddingProviderConfig,
+    dest: &Path,
+) -> Result<Manifest> {
+    if dest.read_dir().is_ok_and(|mut entries| entries.next().is_some()) {
+        return Err(anyhow!("{} isn't empty", dest.display()));
+    }
+    let chunks = snapshot::open(source, table_name)
+        .await
+        .map_err(|_| anyhow!("Nothing indexed in '{}' yet", container))?;
+    std::fs::create_dir_all(dest)?;
+    let bundle = db::connect(&dest.join(DB_DIR).to_string_lossy()).await?;
+
+    let (table, paths, rows) = copy_table(&chunks, &bundle, TABLE).await?;
+    if rows >= super::ANN_INDEX_THRESHOLD {
+        if let Err(e) = db::build_ann_index(&table).await {
+            warn!("Bundle: vector index failed, searches scan every chunk: {}", e);
+        }
+    }
+    // The CLI never writes to the bundle, so older tables get their missing
+    // columns here.
+    db::update_fts_index(&table).await?;
+    if let Ok(vectors) = source.open_table(file_vectors::file_vectors_table_name(table_name)).execute().await {
+        copy_table(&vectors, &bundle, &file_vectors::file_vectors_table_name(TABLE)).await?;
+    }
+
+    let mut embedding_provider = provider.clone();
+    if let EmbeddingProviderConfig::Remote(remote) = &mut embedding_provider {
+        remote.api_key = None;
+    }
+    let manifest = Manifest {
+        format: FORMAT,
+        container: container.to_string(),
+        description: description.to_string(),
+        embedding_provider,
+        roots: roots.to_vec(),
+        files: paths.len(),
+        chunks: rows,
+        created_at: chrono::Utc::now().timestamp(),
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+    };
+    std::fs::write(dest.join(MANIFEST_FILE), serde_json::to_string_pretty(&manifest)?)?;
+    info!("Bundle: '{}' exported to {} ({} files, {} chunks)", container, dest.display(), manifest.files, rows);
+    Ok(manifest)
+}
+
+pub fn open(dir: &Path) -> Result<Manifest> {
+    let json = std::fs::read_to_string(dir.join(MANIFEST_FILE))
+        .map_err(|_| anyhow!("{} isn't a rememex bundle (no {})", dir.display(), MANIFEST_FILE))?;
+    let manifest: Manifest = serde_json::from_str(&json)?;
+    if manifest.format > FORMAT {
+        return Err(anyhow!("{} was made by a newer rememex ({}), update to search it", dir.display(), manifest.app_version));
+    }
+    Ok(manifest)
+}
+
+/// The best chunk of each of the `k` files closest to `query`.
+pub async fn search(dir: &Path, provider: &dyn EmbeddingProvider, query: &str, k: usize) -> Result<Vec<(String, String, f32)>> {
+    let bundle = db::connect(&dir.join(DB_DIR).to_string_lossy()).await?;
+    let weights = query_router::classify_and_weigh(query);
+    let vector = provider.embed_query(query).await?;
+    let (merged, _) = super::search_pipeline(
+        &bundle,
+        TABLE,
+        query,
+        &vector,
+        SEARCH_LIMIT,
+        None,
+        None,
+        None,
+        None,
+        &QueryExpansion::new(true, &[]),
+        weights.vector_weight,
+        weights.fts_weight,
+        SearchScope::default(),
+    )
+    .await?;
+    Ok(best_per_file(merged, k))
+}
+
+fn best_per_file(ranked: Vec<(String, String, f32)>, k: usize) -> Vec<(String, String, f32)> {
+    let mut seen = HashSet::new();
+    ranked.into_iter().filter(|(path, _, _)| seen.insert(path.clone())).take(k).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_best_per_file() {
+        let ranked = vec![
+            ("a.md".to_string(), "one".to_string(), 0.9),
+            ("b.md".to_string(), "two".to_string(), 0.8),
+            ("a.md".to_string(), "three".to_string(), 0.7),
+            ("c.md".to_string(), "four".to_string(), 0.6),
+        ];
+        let best: Vec<_> = best_per_file(ranked, 2).into_iter().map(|(path, snippet, _)| (path, snippet)).collect();
+        assert_eq!(best, vec![("a.md".to_string(), "one".to_string()), ("b.md".to_string(), "two".to_string())]);
+    }
+
+    #[test]
+    fn test_open_rejects_newer_format() {
+        let dir = std::env::temp_dir().join(format!("rememex-bundle-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        assert!(open(&dir).is_err());
+        let manifest = Manifest {
+            format: FORMAT + 1,
+            container: "Work".to_string(),
+            description: String::new(),
+            embedding_provider: EmbeddingProviderConfig::default(),
+            roots: vec![],
+            files: 1,
+            chunks: 1,
+            created_at: 0,
+            app_version: "9.0.0".to_string(),
+        };
+        std::fs::write(dir.join(MANIFEST_FILE), serde_json::to_string(&manifest).unwrap()).unwrap();
+        assert!(open(&dir).unwrap_err().to_string().contains("newer"));
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
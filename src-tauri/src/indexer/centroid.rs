@@ -0,0 +1,163 @@
+//! One vector per container, the mean of its chunks, for guessing which
+//! container a query is about. Kept up to date after indexing and compared
+//! with the query's embedding when auto container is on.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{LazyLock, Mutex};
+use std::time::Duration;
+
+use anyhow::Result;
+use futures::TryStreamExt;
+use lancedb::query::{ExecutableQuery, QueryBase};
+use lancedb::Connection;
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+
+use super::search::mean_vector;
+
+/// Chunks averaged per container. Plenty to tell a codebase from a folder
+/// of recipes, and a big index doesn't get read end to end after every job.
+const SAMPLE: usize = 20_000;
+/// A centroid younger than this is left alone, so the watcher saving one
+/// file at a time doesn't recompute it on every change.
+const MIN_AGE: Duration = Duration::from_secs(5 * 60);
+/// How much closer another container has to be than the active one to be
+/// picked, so near ties keep searching where the user is.
+const MARGIN: f32 = 0.02;
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Centroid {
+    pub vector: Vec<f32>,
+    /// The embedding model's label; a centroid only compares with queries
+    /// embedded by the same one.
+    pub model: String,
+    /// Unix seconds it was computed.
+    pub at: i64,
+}
+
+static CENTROIDS: LazyLock<Mutex<HashMap<String, Centroid>>> = LazyLock::new(|| Mutex::new(load()));
+
+fn centroids_path() -> PathBuf {
+    crate::config::app_data_dir().join("centroids.json")
+}
+
+fn load() -> HashMap<String, Centroid> {
+    std::fs::read_to_string(centroids_path())
+        .ok()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+fn save(centroids: &HashMap<String, Centroid>) {
+    let path = centroids_path();
+    match serde_json::to_string(centroids) {
+        Ok(json) => {
+            if let Err(e) = std::fs::write(&path, json) {
+                warn!("Failed to write container centroids {:?}: {}", path, e);
+            }
+        }
+        Err(e) => warn!("Failed to serialize container centroids: {}", e),
+    }
+}
+
+/// Recomputes `container`'s centroid from its table unless a recent one for
+/// `model` is there already.
+pub async fn refresh(db: &Connection, container: &str, model: &str) -> Result<()> {
+    let now = chrono::Utc::now().timestamp();
+    let fresh = CENTROIDS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .get(container)
+        .is_some_and(|c| c.model == model && now - c.at < MIN_AGE.as_secs() as i64);
+    if fresh {
+        return Ok(());
+    }
+
+    let table = super::snapshot::open(db, &crate::config::get_table_name(container)).await?;
+    let batches = table
+        .query()
+        .select(lancedb::query::Select::Columns(vec!["vector".to_string()]))
+        .limit(SAMPLE)
+        .execute()
+        .await?
+        .try_collect::<Vec<_>>()
+        .await?;
+    let mut centroids = CENTROIDS.lock().unwrap_or_else(|e| e.into_inner());
+    match mean_vector(&batches) {
+        Some(vector) => {
+            debug!("Container centroid for {} updated", container);
+            centroids.insert(container.to_string(), Centroid { vector, model: model.to_string(), at: now });
+        }
+        None => {
+            centroids.remove(container);
+        }
+    }
+    save(&centroids);
+    Ok(())
+}
+
+/// Whether `container` has a centroid for `model`, however old.
+pub fn has(container: &str, model: &str) -> bool {
+    CENTROIDS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .get(container)
+        .is_some_and(|c| c.model == model)
+}
+
+/// The container out of `candidates` a query embedded as `query` by `model`
+/// is most likely about, when that's not `active`.
+pub fn route(query: &[f32], model: &str, active: &str, candidates: &[String]) -> Option<String> {
+    let centroids = CENTROIDS.lock().unwrap_or_else(|e| e.into_inner());
+    let scored: Vec<(&str, f32)> = candidates
+        .iter()
+        .filter_map(|name| {
+            let centroid = centroids.get(name).filter(|c| c.model == model)?;
+            Some((name.as_str(), cosine(query, &centroid.vector)?))
+        })
+        .collect();
+    pick(&scored, active).map(str::to_string)
+}
+
+/// The best of `scored` when it isn't `active` and beats it by `MARGIN`.
+fn pick<'a>(scored: &[(&'a str, f32)], active: &str) -> Option<&'a str> {
+    let (best, score) = scored.iter().copied().max_by(|a, b| a.1.total_cmp(&b.1))?;
+    let active_score = scored.iter().find(|(name, _)| *name == active).map(|(_, s)| *s);
+    (best != active && active_score.is_none_or(|s| score - s >= MARGIN)).then_some(best)
+}
+
+/// Cosine similarity; None for vectors of different sizes or all zeros.
+pub(super) fn cosine(a: &[f32], b: &[f32]) -> Option<f32> {
+    if a.len() != b.len() {
+        return None;
+    }
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm = |v: &[f32]| v.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let denom = norm(a) * norm(b);
+    (denom > 0.0).then(|| dot / denom)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cosine() {
+        assert_eq!(cosine(&[1.0, 0.0], &[2.0, 0.0]), Some(1.0));
+        assert_eq!(cosine(&[1.0, 0.0], &[0.0, 3.0]), Some(0.0));
+        assert_eq!(cosine(&[1.0, 0.0], &[1.0, 0.0, 0.0]), None);
+        assert_eq!(cosine(&[0.0, 0.0], &[1.0, 0.0]), None);
+    }
+
+    #[test]
+    fn test_pick() {
+        assert_eq!(pick(&[("Work", 0.80), ("Recipes", 0.62)], "Recipes"), Some("Work"));
+        assert_eq!(pick(&[("Work", 0.80), ("Recipes", 0.62)], "Work"), None);
+        // A near tie stays where the user is.
+        assert_eq!(pick(&[("Work", 0.80), ("Recipes", 0.79)], "Recipes"), None);
+        // The active container has no centroid yet.
+        assert_eq!(pick(&[("Work", 0.50)], "Default"), Some("Work"));
+        assert_eq!(pick(&[], "Default"), None);
+    }
+}
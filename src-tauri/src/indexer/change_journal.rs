@@ -0,0 +1,675 @@
+//! What changed under a folder since its last complete pass, from the NTFS
+//! change journal on Windows or FSEvents on macOS, so reindexing walks only
+//! the folders something happened in. Anything the history can't vouch for
+//! gets the full walk.
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
+
+use crate::config::IndexingConfig;
+
+#[cfg(any(windows, target_os = "macos", test))]
+const IGNORE_FILES: &[&str] = &[".gitignore", ".ignore", ".rcignore"];
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Token {
+    Usn { journal_id: u64, usn: i64 },
+    FsEvents { event_id: u64 },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Checkpoint {
+    pub token: Token,
+    pub settings: String,
+}
+
+#[derive(Default, Debug)]
+pub struct Changes {
+    pub dirs: Vec<PathBuf>,
+    pub trees: Vec<PathBuf>,
+}
+
+impl Changes {
+    #[cfg(any(target_os = "macos", test))]
+    fn file(&mut self, path: &Path) {
+        let Some(parent) = path.parent() else {
+            return;
+        };
+        let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+        if IGNORE_FILES.contains(&name) {
+            self.trees.push(parent.to_path_buf());
+        } else {
+            self.dirs.push(parent.to_path_buf());
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Scope {
+    dirs: HashSet<PathBuf>,
+    trees: HashSet<PathBuf>,
+    ancestors: HashSet<PathBuf>,
+}
+
+impl Scope {
+    fn new(root: &Path, changes: Changes) -> Self {
+        let dirs: HashSet<PathBuf> = changes.dirs.iter().filter_map(|d| rebase(root, d)).collect();
+        let trees: HashSet<PathBuf> = changes.trees.iter().filter_map(|t| rebase(root, t)).collect();
+        let ancestors = dirs
+            .iter()
+            .chain(&trees)
+            .flat_map(|p| p.ancestors().skip(1).take_while(|a| a.starts_with(root)))
+            .map(Path::to_path_buf)
+            .collect();
+        Scope { dirs, trees, ancestors }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.dirs.is_empty() && self.trees.is_empty()
+    }
+
+    fn in_tree(&self, path: &Path) -> bool {
+        path.ancestors().any(|a| self.trees.contains(a))
+    }
+
+    pub fn wants_dir(&self, dir: &Path) -> bool {
+        self.dirs.contains(dir) || self.ancestors.contains(dir) || self.in_tree(dir)
+    }
+
+    pub fn wants_file(&self, file: &Path) -> bool {
+        file.parent().is_some_and(|p| self.dirs.contains(p)) || self.in_tree(file)
+    }
+}
+
+fn rebase(root: &Path, path: &Path) -> Option<PathBuf> {
+    let path = path.to_str()?;
+    let path = Path::new(path.strip_prefix(r"\\?\").unwrap_or(path));
+    let mut rest = path.components();
+    for part in root.components() {
+        let other = rest.next()?;
+        if part.as_os_str().to_string_lossy().to_lowercase() != other.as_os_str().to_string_lossy().to_lowercase() {
+            return None;
+        }
+    }
+    let rest = rest.as_path();
+    Some(if rest.as_os_str().is_empty() { root.to_path_buf() } else { root.join(rest) })
+}
+
+fn checkpoints_path(table_name: &str) -> PathBuf {
+    crate::config::app_data_dir().join("change_journal").join(format!("{}.json", table_name))
+}
+
+fn load(table_name: &str) -> HashMap<String, Checkpoint> {
+    std::fs::read_to_string(checkpoints_path(table_name))
+        .ok()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+fn store(table_name: &str, checkpoints: &HashMap<String, Checkpoint>) {
+    let path = checkpoints_path(table_name);
+    if checkpoints.is_empty() {
+        std::fs::remove_file(&path).ok();
+        return;
+    }
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).ok();
+    }
+    match serde_json::to_string(checkpoints) {
+        Ok(json) => {
+            if let Err(e) = std::fs::write(&path, json) {
+                warn!("Failed to write change checkpoints {:?}: {}", path, e);
+            }
+        }
+        Err(e) => warn!("Failed to serialize change checkpoints: {}", e),
+    }
+}
+
+pub fn settings_key(indexing_config: &IndexingConfig, excluded_paths: &[String]) -> String {
+    let json = serde_json::to_string(&(indexing_config, excluded_paths)).unwrap_or_default();
+    blake3::hash(json.as_bytes()).to_hex()[..32].to_string()
+}
+
+pub fn plan(table_name: &str, root_dir: &str, settings: &str) -> (Option<Checkpoint>, Option<Scope>) {
+    let root = Path::new(root_dir);
+    let Some(token) = current(root) else {
+        return (None, None);
+    };
+    let checkpoint = Checkpoint { token, settings: settings.to_string() };
+    let scope = match load(table_name).remove(root_dir) {
+        Some(last) if last.settings == settings => changes_since(root, &last.token).map(|changes| Scope::new(root, changes)),
+        Some(_) => {
+            debug!("Indexing settings changed since {} was last indexed, walking all of it", root_dir);
+            None
+        }
+        None => None,
+    };
+    match &scope {
+        Some(scope) if scope.is_empty() => info!("Nothing changed in {} since it was last indexed", root_dir),
+        Some(scope) => info!("{} folders changed in {} since it was last indexed", scope.dirs.len() + scope.trees.len(), root_dir),
+        None => {}
+    }
+    (Some(checkpoint), scope)
+}
+
+pub fn save(table_name: &str, root_dir: &str, checkpoint: Checkpoint) {
+    let mut checkpoints = load(table_name);
+    checkpoints.insert(root_dir.to_string(), checkpoint);
+    store(table_name, &checkpoints);
+}
+
+pub fn forget(table_name: &str, dir: Option<&str>) {
+    let Some(dir) = dir else {
+        std::fs::remove_file(checkpoints_path(table_name)).ok();
+        return;
+    };
+    let mut checkpoints = load(table_name);
+    let before = checkpoints.len();
+    checkpoints.retain(|root, _| !Path::new(root).starts_with(dir) && !Path::new(dir).starts_with(root));
+    if checkpoints.len() != before {
+        store(table_name, &checkpoints);
+    }
+}
+
+pub fn rename(from: &str, to: &str) {
+    let checkpoints = load(from);
+    store(to, &checkpoints);
+    forget(from, None);
+}
+
+#[cfg(windows)]
+use usn::{changes_since, current};
+
+#[cfg(target_os = "macos")]
+use fsevents::{changes_since, current};
+
+#[cfg(not(any(windows, target_os = "macos")))]
+fn current(_root: &Path) -> Option<Token> {
+    None
+}
+
+#[cfg(not(any(windows, target_os = "macos")))]
+fn changes_since(_root: &Path, _token: &Token) -> Option<Changes> {
+    None
+}
+
+#[cfg(any(windows, test))]
+const USN_REASON_FILE_CREATE: u32 = 0x0000_0100;
+#[cfg(any(windows, test))]
+const USN_REASON_RENAME_NEW_NAME: u32 = 0x0000_2000;
+#[cfg(any(windows, test))]
+const FILE_ATTRIBUTE_DIRECTORY: u32 = 0x10;
+
+#[cfg(any(windows, test))]
+#[derive(Debug, PartialEq)]
+struct UsnRecord {
+    frn: u64,
+    parent: u64,
+    reason: u32,
+    attributes: u32,
+    name: String,
+}
+
+/// A USN_RECORD_V2 has 60 bytes of fields before its UTF-16 name, and its
+/// length covers the name and padding. Stops at anything that isn't a whole
+/// version 2 record, so a short or corrupt buffer is never read past.
+#[cfg(any(windows, test))]
+fn parse_usn_records(buf: &[u8]) -> Vec<UsnRecord> {
+    let u16_at = |b: &[u8], at: usize| u16::from_le_bytes([b[at], b[at + 1]]);
+    let u32_at = |b: &[u8], at: usize| u32::from_le_bytes(b[at..at + 4].try_into().unwrap());
+    let u64_at = |b: &[u8], at: usize| u64::from_le_bytes(b[at..at + 8].try_into().unwrap());
+
+    let mut records = Vec::new();
+    let mut offset = 0;
+    while offset + 60 <= buf.len() {
+        let record = &buf[offset..];
+        let length = u32_at(record, 0) as usize;
+        if length < 60 || length > record.len() || u16_at(record, 4) != 2 {
+            break;
+        }
+        let name_len = u16_at(record, 56) as usize;
+        let name_at = u16_at(record, 58) as usize;
+        if name_at + name_len > length {
+            break;
+        }
+        let name: Vec<u16> = record[name_at..name_at + name_len].chunks_exact(2).map(|c| u16::from_le_bytes([c[0], c[1]])).collect();
+        records.push(UsnRecord {
+            frn: u64_at(record, 8),
+            parent: u64_at(record, 16),
+            reason: u32_at(record, 40),
+            attributes: u32_at(record, 52),
+            name: String::from_utf16_lossy(&name),
+        });
+        offset += length;
+    }
+    records
+}
+
+#[cfg(windows)]
+mod usn {
+    use std::collections::HashSet;
+    use std::ffi::{c_void, OsStr};
+    use std::os::windows::ffi::OsStrExt;
+    use std::path::{Path, PathBuf};
+
+    use log::debug;
+
+    use super::{parse_usn_records, Changes, Token, FILE_ATTRIBUTE_DIRECTORY, IGNORE_FILES, USN_REASON_FILE_CREATE, USN_REASON_RENAME_NEW_NAME};
+
+    type Handle = *mut c_void;
+
+    const INVALID_HANDLE: Handle = -1isize as Handle;
+    const FILE_READ_ATTRIBUTES: u32 = 0x80;
+    const FILE_SHARE_ALL: u32 = 0x7;
+    const OPEN_EXISTING: u32 = 3;
+    const FILE_FLAG_BACKUP_SEMANTICS: u32 = 0x0200_0000;
+    const FSCTL_QUERY_USN_JOURNAL: u32 = 0x0009_00f4;
+    const FSCTL_READ_UNPRIVILEGED_USN_JOURNAL: u32 = 0x0009_03ab;
+    const READ_BUFFER: usize = 64 * 1024;
+
+    #[repr(C)]
+    #[derive(Default)]
+    struct JournalData {
+        journal_id: u64,
+        first_usn: i64,
+        next_usn: i64,
+        _rest: [u64; 5],
+    }
+
+    #[allow(dead_code)]
+    #[repr(C)]
+    struct ReadJournalData {
+        start_usn: i64,
+        reason_mask: u32,
+        return_only_on_close: u32,
+        timeout: u64,
+        bytes_to_wait_for: u64,
+        journal_id: u64,
+        min_major_version: u16,
+        max_major_version: u16,
+    }
+
+    #[allow(dead_code)]
+    #[repr(C)]
+    struct FileIdDescriptor {
+        size: u32,
+        kind: u32,
+        file_id: u64,
+        _rest: u64,
+    }
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn CreateFileW(name: *const u16, access: u32, share: u32, security: *const c_void, disposition: u32, flags: u32, template: Handle) -> Handle;
+        fn DeviceIoControl(
+            device: Handle,
+            code: u32,
+            input: *const c_void,
+            input_size: u32,
+            output: *mut c_void,
+            output_size: u32,
+            returned: *mut u32,
+            overlapped: *mut c_void,
+        ) -> i32;
+        fn OpenFileById(volume_hint: Handle, id: *const FileIdDescriptor, access: u32, share: u32, security: *const c_void, flags: u32) -> Handle;
+        fn GetFinalPathNameByHandleW(file: Handle, path: *mut u16, len: u32, flags: u32) -> u32;
+        fn GetVolumePathNameW(path: *const u16, volume: *mut u16, len: u32) -> i32;
+        fn CloseHandle(handle: Handle) -> i32;
+    }
+
+    struct Owned(Handle);
+
+    impl Drop for Owned {
+        fn drop(&mut self) {
+            unsafe { CloseHandle(self.0) };
+        }
+    }
+
+    fn wide(s: &OsStr) -> Vec<u16> {
+        s.encode_wide().chain(Some(0)).collect()
+    }
+
+    fn open(path: &[u16]) -> Option<Owned> {
+        let handle = unsafe {
+            CreateFileW(path.as_ptr(), FILE_READ_ATTRIBUTES, FILE_SHARE_ALL, std::ptr::null(), OPEN_EXISTING, FILE_FLAG_BACKUP_SEMANTICS, std::ptr::null_mut())
+        };
+        (handle != INVALID_HANDLE).then_some(Owned(handle))
+    }
+
+    fn open_volume(root: &Path) -> Option<Owned> {
+        let mut volume = [0u16; 261];
+        if unsafe { GetVolumePathNameW(wide(root.as_os_str()).as_ptr(), volume.as_mut_ptr(), volume.len() as u32) } == 0 {
+            return None;
+        }
+        let len = volume.iter().position(|&c| c == 0)?;
+        let volume = String::from_utf16_lossy(&volume[..len]);
+        let letter = volume.strip_suffix('\\').filter(|v| v.len() == 2 && v.ends_with(':'))?;
+        open(&wide(OsStr::new(&format!(r"\\.\{}", letter))))
+    }
+
+    fn query(volume: &Owned) -> Option<JournalData> {
+        let mut data = JournalData::default();
+        let mut returned = 0u32;
+        let ok = unsafe {
+            DeviceIoControl(
+                volume.0,
+                FSCTL_QUERY_USN_JOURNAL,
+                std::ptr::null(),
+                0,
+                &mut data as *mut JournalData as *mut c_void,
+                std::mem::size_of::<JournalData>() as u32,
+                &mut returned,
+                std::ptr::null_mut(),
+            )
+        };
+        (ok != 0).then_some(data)
+    }
+
+    fn final_path(volume_hint: &Owned, frn: u64) -> Option<PathBuf> {
+        let id = FileIdDescriptor { size: std::mem::size_of::<FileIdDescriptor>() as u32, kind: 0, file_id: frn, _rest: 0 };
+        let handle = unsafe { OpenFileById(volume_hint.0, &id, FILE_READ_ATTRIBUTES, FILE_SHARE_ALL, std::ptr::null(), FILE_FLAG_BACKUP_SEMANTICS) };
+        if handle == INVALID_HANDLE {
+            return None;
+        }
+        let handle = Owned(handle);
+        let mut buf = vec![0u16; 1024];
+        loop {
+            let len = unsafe { GetFinalPathNameByHandleW(handle.0, buf.as_mut_ptr(), buf.len() as u32, 0) } as usize;
+            match len {
+                0 => return None,
+                len if len < buf.len() => return Some(PathBuf::from(String::from_utf16_lossy(&buf[..len]))),
+                len => buf.resize(len + 1, 0),
+            }
+        }
+    }
+
+    pub fn current(root: &Path) -> Option<Token> {
+        let data = query(&open_volume(root)?)?;
+        Some(Token::Usn { journal_id: data.journal_id, usn: data.next_usn })
+    }
+
+    pub fn changes_since(root: &Path, token: &Token) -> Option<Changes> {
+        let Token::Usn { journal_id, usn } = *token else {
+            return None;
+        };
+        let volume = open_volume(root)?;
+        let data = query(&volume)?;
+        if data.journal_id != journal_id || usn < data.first_usn {
+            debug!("Change journal of {:?} was recreated or wrapped since the last pass", root);
+            return None;
+        }
+
+        let mut dirs = HashSet::new();
+        let mut trees = HashSet::new();
+        let mut read = ReadJournalData {
+            start_usn: usn,
+            reason_mask: u32::MAX,
+            return_only_on_close: 0,
+            timeout: 0,
+            bytes_to_wait_for: 0,
+            journal_id,
+            min_major_version: 2,
+            max_major_version: 2,
+        };
+        let mut buf = vec![0u8; READ_BUFFER];
+        while read.start_usn < data.next_usn {
+            let mut returned = 0u32;
+            let ok = unsafe {
+                DeviceIoControl(
+                    volume.0,
+                    FSCTL_READ_UNPRIVILEGED_USN_JOURNAL,
+                    &read as *const ReadJournalData as *const c_void,
+                    std::mem::size_of::<ReadJournalData>() as u32,
+                    buf.as_mut_ptr() as *mut c_void,
+                    buf.len() as u32,
+                    &mut returned,
+                    std::ptr::null_mut(),
+                )
+            };
+            let returned = returned as usize;
+            if ok == 0 || returned < 8 {
+                return None;
+            }
+            for record in parse_usn_records(&buf[8..returned]) {
+                if record.attributes & FILE_ATTRIBUTE_DIRECTORY == 0 {
+                    if IGNORE_FILES.contains(&record.name.as_str()) {
+                        trees.insert(record.parent);
+                    } else {
+                        dirs.insert(record.parent);
+                    }
+                } else if record.reason & (USN_REASON_FILE_CREATE | USN_REASON_RENAME_NEW_NAME) != 0 {
+                    trees.insert(record.frn);
+                }
+            }
+            let next = i64::from_le_bytes(buf[..8].try_into().unwrap());
+            if returned == 8 || next <= read.start_usn {
+                break;
+            }
+            read.start_usn = next;
+        }
+
+        let hint = open(&wide(root.as_os_str()))?;
+        Some(Changes {
+            dirs: dirs.into_iter().filter_map(|frn| final_path(&hint, frn)).collect(),
+            trees: trees.into_iter().filter_map(|frn| final_path(&hint, frn)).collect(),
+        })
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod fsevents {
+    use std::ffi::{c_char, c_void, CStr, CString, OsStr};
+    use std::os::unix::ffi::OsStrExt;
+    use std::path::Path;
+    use std::time::{Duration, Instant};
+
+    use log::debug;
+
+    use super::{Changes, Token};
+
+    type CFRef = *const c_void;
+    type Stream = *mut c_void;
+    type Callback = extern "C" fn(Stream, *mut c_void, usize, *const *const c_char, *const u32, *const u64);
+
+    const UTF8: u32 = 0x0800_0100;
+    const CREATE_FILE_EVENTS: u32 = 0x10;
+    const MUST_SCAN_SUBDIRS: u32 = 0x1;
+    const USER_DROPPED: u32 = 0x2;
+    const KERNEL_DROPPED: u32 = 0x4;
+    const IDS_WRAPPED: u32 = 0x8;
+    const HISTORY_DONE: u32 = 0x10;
+    const ROOT_CHANGED: u32 = 0x20;
+    const ITEM_CREATED: u32 = 0x100;
+    const ITEM_RENAMED: u32 = 0x800;
+    const ITEM_IS_FILE: u32 = 0x1_0000;
+    const ITEM_IS_DIR: u32 = 0x2_0000;
+    const LOST: u32 = MUST_SCAN_SUBDIRS | USER_DROPPED | KERNEL_DROPPED | IDS_WRAPPED | ROOT_CHANGED;
+    // HISTORY_DONE never comes when the stream stalls; without it the
+    // history is incomplete and the folder gets the full walk.
+    const REPLAY_TIMEOUT: Duration = Duration::from_secs(30);
+
+    #[allow(dead_code)]
+    #[repr(C)]
+    struct StreamContext {
+        version: isize,
+        info: *mut c_void,
+        retain: *const c_void,
+        release: *const c_void,
+        copy_description: *const c_void,
+    }
+
+    #[allow(dead_code)]
+    #[repr(C)]
+    struct ArrayCallBacks {
+        version: isize,
+        retain: *const c_void,
+        release: *const c_void,
+        copy_description: *const c_void,
+        equal: *const c_void,
+    }
+
+    #[link(name = "CoreServices", kind = "framework")]
+    extern "C" {
+        fn FSEventsGetCurrentEventId() -> u64;
+        fn FSEventStreamCreate(allocator: CFRef, callback: Callback, context: *const StreamContext, paths: CFRef, since_when: u64, latency: f64, flags: u32) -> Stream;
+        fn FSEventStreamScheduleWithRunLoop(stream: Stream, run_loop: CFRef, mode: CFRef);
+        fn FSEventStreamStart(stream: Stream) -> u8;
+        fn FSEventStreamStop(stream: Stream);
+        fn FSEventStreamInvalidate(stream: Stream);
+        fn FSEventStreamRelease(stream: Stream);
+    }
+
+    #[link(name = "CoreFoundation", kind = "framework")]
+    extern "C" {
+        static kCFRunLoopDefaultMode: CFRef;
+        static kCFTypeArrayCallBacks: ArrayCallBacks;
+        fn CFRunLoopGetCurrent() -> CFRef;
+        fn CFRunLoopRunInMode(mode: CFRef, seconds: f64, return_after_source_handled: u8) -> i32;
+        fn CFStringCreateWithCString(allocator: CFRef, s: *const c_char, encoding: u32) -> CFRef;
+        fn CFArrayCreate(allocator: CFRef, values: *const CFRef, count: isize, callbacks: *const ArrayCallBacks) -> CFRef;
+        fn CFRelease(cf: CFRef);
+    }
+
+    #[derive(Default)]
+    struct Replay {
+        changes: Changes,
+        done: bool,
+        lost: bool,
+    }
+
+    extern "C" fn collect(_stream: Stream, info: *mut c_void, count: usize, paths: *const *const c_char, flags: *const u32, _ids: *const u64) {
+        let replay = unsafe { &mut *(info as *mut Replay) };
+        for i in 0..count {
+            let (path, flag) = unsafe { (CStr::from_ptr(*paths.add(i)), *flags.add(i)) };
+            let path = Path::new(OsStr::from_bytes(path.to_bytes()));
+            if flag & HISTORY_DONE != 0 {
+                replay.done = true;
+            } else if flag & LOST != 0 {
+                replay.lost = true;
+            } else if flag & ITEM_IS_DIR != 0 {
+                if flag & (ITEM_CREATED | ITEM_RENAMED) != 0 {
+                    replay.changes.trees.push(path.to_path_buf());
+                }
+            } else if flag & ITEM_IS_FILE != 0 {
+                replay.changes.file(path);
+            } else {
+                replay.changes.dirs.push(path.to_path_buf());
+            }
+        }
+    }
+
+    pub fn current(_root: &Path) -> Option<Token> {
+        Some(Token::FsEvents { event_id: unsafe { FSEventsGetCurrentEventId() } })
+    }
+
+    pub fn changes_since(root: &Path, token: &Token) -> Option<Changes> {
+        let Token::FsEvents { event_id } = *token else {
+            return None;
+        };
+        let root = CString::new(root.as_os_str().as_bytes()).ok()?;
+        let mut replay = Replay::default();
+        unsafe {
+            let path = CFStringCreateWithCString(std::ptr::null(), root.as_ptr(), UTF8);
+            if path.is_null() {
+                return None;
+            }
+            let paths = CFArrayCreate(std::ptr::null(), &path, 1, &kCFTypeArrayCallBacks);
+            CFRelease(path);
+            let context = StreamContext {
+                version: 0,
+                info: &mut replay as *mut Replay as *mut c_void,
+                retain: std::ptr::null(),
+                release: std::ptr::null(),
+                copy_description: std::ptr::null(),
+            };
+            let stream = FSEventStreamCreate(std::ptr::null(), collect, &context, paths, event_id, 0.0, CREATE_FILE_EVENTS);
+            CFRelease(paths);
+            if stream.is_null() {
+                return None;
+            }
+            FSEventStreamScheduleWithRunLoop(stream, CFRunLoopGetCurrent(), kCFRunLoopDefaultMode);
+            if FSEventStreamStart(stream) != 0 {
+                let started = Instant::now();
+                while !replay.done && !replay.lost && started.elapsed() < REPLAY_TIMEOUT {
+                    CFRunLoopRunInMode(kCFRunLoopDefaultMode, 0.25, 0);
+                }
+                FSEventStreamStop(stream);
+            }
+            FSEventStreamInvalidate(stream);
+            FSEventStreamRelease(stream);
+        }
+        if !replay.done || replay.lost {
+            debug!("FSEvents history of {:?} incomplete, walking all of it", root);
+            return None;
+        }
+        Some(replay.changes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scope() {
+        let root = Path::new("/data/docs");
+        let changes = Changes {
+            dirs: vec![PathBuf::from("/Data/Docs/a/b"), PathBuf::from("/elsewhere/x")],
+            trees: vec![PathBuf::from("/data/docs/c")],
+        };
+        let scope = Scope::new(root, changes);
+        assert_eq!(scope.dirs.len(), 1);
+        for dir in ["/data/docs", "/data/docs/a", "/data/docs/a/b", "/data/docs/c", "/data/docs/c/d/e"] {
+            assert!(scope.wants_dir(Path::new(dir)), "{}", dir);
+        }
+        for dir in ["/data/docs/a/b/x", "/data/docs/z", "/data/docs/a/c"] {
+            assert!(!scope.wants_dir(Path::new(dir)), "{}", dir);
+        }
+        assert!(scope.wants_file(Path::new("/data/docs/a/b/notes.md")));
+        assert!(scope.wants_file(Path::new("/data/docs/c/d/notes.md")));
+        assert!(!scope.wants_file(Path::new("/data/docs/a/notes.md")));
+        assert!(Scope::new(root, Changes::default()).is_empty());
+    }
+
+    #[test]
+    fn test_ignore_file_change_covers_folder() {
+        let mut changes = Changes::default();
+        changes.file(Path::new("/r/a/.gitignore"));
+        changes.file(Path::new("/r/b/notes.md"));
+        assert_eq!(changes.trees, vec![PathBuf::from("/r/a")]);
+        assert_eq!(changes.dirs, vec![PathBuf::from("/r/b")]);
+    }
+
+    fn usn_record(frn: u64, parent: u64, reason: u32, attributes: u32, name: &str) -> Vec<u8> {
+        let name: Vec<u8> = name.encode_utf16().flat_map(u16::to_le_bytes).collect();
+        let length = (60 + name.len()).next_multiple_of(8);
+        let mut record = vec![0u8; length];
+        record[0..4].copy_from_slice(&(length as u32).to_le_bytes());
+        record[4..6].copy_from_slice(&2u16.to_le_bytes());
+        record[8..16].copy_from_slice(&frn.to_le_bytes());
+        record[16..24].copy_from_slice(&parent.to_le_bytes());
+        record[40..44].copy_from_slice(&reason.to_le_bytes());
+        record[52..56].copy_from_slice(&attributes.to_le_bytes());
+        record[56..58].copy_from_slice(&(name.len() as u16).to_le_bytes());
+        record[58..60].copy_from_slice(&60u16.to_le_bytes());
+        record[60..60 + name.len()].copy_from_slice(&name);
+        record
+    }
+
+    #[test]
+    fn test_parse_usn_records() {
+        let mut buf = usn_record(7, 5, USN_REASON_FILE_CREATE, 0x20, "notes.md");
+        buf.extend(usn_record(9, 5, USN_REASON_RENAME_NEW_NAME, FILE_ATTRIBUTE_DIRECTORY, "Projekte"));
+        let records = parse_usn_records(&buf);
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0], UsnRecord { frn: 7, parent: 5, reason: USN_REASON_FILE_CREATE, attributes: 0x20, name: "notes.md".to_string() });
+        assert_eq!((records[1].frn, records[1].name.as_str()), (9, "Projekte"));
+
+        assert_eq!(parse_usn_records(&buf[..buf.len() - 4]).len(), 1);
+        assert!(parse_usn_records(&[0u8; 16]).is_empty());
+    }
+}
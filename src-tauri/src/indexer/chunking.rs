@@ -1,22 +1,32 @@
 use regex::Regex;
+use rust_stemmers::{Algorithm, Stemmer};
 
 pub struct ChunkConfig {
     pub max_bytes: usize,
     pub overlap_bytes: usize,
 }
 
-pub fn get_chunk_config(ext: &str) -> ChunkConfig {
-    match ext {
+pub fn is_code_ext(ext: &str) -> bool {
+    matches!(
+        ext,
         "rs" | "py" | "pyi" | "pyw" | "js" | "mjs" | "cjs" | "ts" | "mts" | "cts" | "tsx"
         | "jsx" | "go" | "java" | "kt" | "kts" | "scala" | "sc" | "groovy" | "gradle" | "clj"
         | "cljs" | "cljc" | "c" | "cpp" | "cc" | "cxx" | "h" | "hpp" | "hxx" | "hh" | "cs"
         | "fs" | "fsi" | "fsx" | "vb" | "vbs" | "rb" | "erb" | "swift" | "m" | "mm" | "dart"
         | "php" | "pl" | "pm" | "lua" | "r" | "jl" | "ex" | "exs" | "erl" | "hrl" | "hs"
         | "lhs" | "ml" | "mli" | "elm" | "zig" | "nim" | "v" | "d" | "sol" | "move" | "pas"
-        | "lisp" | "el" | "rkt" | "asm" | "s" | "wat" | "vue" | "svelte" | "astro" => ChunkConfig {
+        | "lisp" | "el" | "rkt" | "asm" | "s" | "wat" | "vue" | "svelte" | "astro"
+    )
+}
+
+pub fn get_chunk_config(ext: &str) -> ChunkConfig {
+    if is_code_ext(ext) {
+        return ChunkConfig {
             max_bytes: 1200,
             overlap_bytes: 200,
-        },
+        };
+    }
+    match ext {
         "md" | "markdown" | "txt" | "rst" | "adoc" | "tex" => ChunkConfig {
             max_bytes: 800,
             overlap_bytes: 150,
@@ -244,6 +254,34 @@ pub fn semantic_chunk_with_overrides(
     chunk_with_semantic_config(text, &config, pattern)
 }
 
+/// Separates pages in extracted PDF text, same as pdftotext does.
+pub const PAGE_BREAK: char = '\x0c';
+
+/// Chunks each page of a paged document on its own so no chunk straddles a
+/// page, pairing every chunk with its page label ("p.14"). None when the text
+/// has no page breaks.
+pub fn page_slices(
+    text: &str,
+    ext: &str,
+    chunk_size: Option<usize>,
+    chunk_overlap: Option<usize>,
+) -> Option<Vec<(String, String)>> {
+    if !text.contains(PAGE_BREAK) {
+        return None;
+    }
+    let mut slices = Vec::new();
+    for (i, page) in text.split(PAGE_BREAK).enumerate() {
+        if page.trim().is_empty() {
+            continue;
+        }
+        let label = format!("p.{}", i + 1);
+        for chunk in semantic_chunk_with_overrides(page, ext, chunk_size, chunk_overlap) {
+            slices.push((chunk, label.clone()));
+        }
+    }
+    Some(slices)
+}
+
 pub fn semantic_chunk(text: &str, ext: &str) -> Vec<String> {
     let config = get_chunk_config(ext);
 
@@ -336,6 +374,11 @@ pub fn chunk_with_overlap(text: &str, max_bytes: usize, overlap_bytes: usize) ->
         let slice = &text[start..end];
         let split_at = slice
             .rfind('\n')
+            .or_else(|| {
+                slice
+                    .rfind(super::cjk::SENTENCE_TERMINATORS)
+                    .map(|i| i + slice[i..].chars().next().map_or(1, char::len_utf8) - 1)
+            })
             .or_else(|| slice.rfind(". "))
             .or_else(|| slice.rfind(' '))
             .map(|i| start + i + 1)
@@ -357,7 +400,7 @@ pub fn chunk_with_overlap(text: &str, max_bytes: usize, overlap_bytes: usize) ->
     chunks
 }
 
-const STOP_WORDS: &[&str] = &[
+pub(super) const STOP_WORDS: &[&str] = &[
     "a", "an", "the", "is", "are", "was", "were", "be", "been", "being", "have", "has", "had",
     "do", "does", "did", "will", "would", "could", "should", "may", "might", "shall", "can", "to",
     "of", "in", "for", "on", "with", "at", "by", "from", "as", "into", "about", "between",
@@ -368,7 +411,72 @@ const STOP_WORDS: &[&str] = &[
     "çok", "var",
 ];
 
-pub fn expand_query(query: &str) -> Vec<String> {
+/// Extra keyword-search variants on top of case folding and stop words.
+#[derive(Debug, Clone, Default)]
+pub struct QueryExpansion {
+    pub stemming: bool,
+    pub synonyms: Vec<Vec<String>>,
+}
+
+impl QueryExpansion {
+    pub fn new(stemming: bool, synonym_lines: &[String]) -> Self {
+        Self { stemming, synonyms: parse_synonyms(synonym_lines) }
+    }
+}
+
+/// One group per line, terms separated by `=` or `,`:
+/// "auth = authentication, login". Every term in a group expands to the
+/// others. Lines with fewer than two terms are ignored.
+pub fn parse_synonyms(lines: &[String]) -> Vec<Vec<String>> {
+    lines
+        .iter()
+        .filter_map(|line| {
+            let mut group: Vec<String> = Vec::new();
+            for term in line.split(['=', ',']) {
+                let term = term.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase();
+                if !term.is_empty() && !group.contains(&term) {
+                    group.push(term);
+                }
+            }
+            (group.len() >= 2).then_some(group)
+        })
+        .collect()
+}
+
+/// Snowball stemmer for an ISO 639-1 code, English when the language is
+/// unknown or has no stemmer.
+fn stemmer_for(language: Option<&str>) -> Stemmer {
+    let algorithm = match language.unwrap_or("en") {
+        "ar" => Algorithm::Arabic,
+        "da" => Algorithm::Danish,
+        "de" => Algorithm::German,
+        "el" => Algorithm::Greek,
+        "es" => Algorithm::Spanish,
+        "fi" => Algorithm::Finnish,
+        "fr" => Algorithm::French,
+        "hu" => Algorithm::Hungarian,
+        "it" => Algorithm::Italian,
+        "nb" | "no" => Algorithm::Norwegian,
+        "nl" => Algorithm::Dutch,
+        "pt" => Algorithm::Portuguese,
+        "ro" => Algorithm::Romanian,
+        "ru" => Algorithm::Russian,
+        "sv" => Algorithm::Swedish,
+        "ta" => Algorithm::Tamil,
+        "tr" => Algorithm::Turkish,
+        _ => Algorithm::English,
+    };
+    Stemmer::create(algorithm)
+}
+
+/// Word-bounded containment, so the synonym "db" doesn't fire on "dbus".
+fn contains_phrase(haystack: &str, phrase: &str) -> bool {
+    format!(" {} ", haystack).contains(&format!(" {} ", phrase))
+}
+
+/// FTS query variants, each searched separately and merged. `language` picks
+/// the stemmer and is the search's language filter when one is set.
+pub fn expand_query(query: &str, expansion: &QueryExpansion, language: Option<&str>) -> Vec<String> {
     let mut variants = Vec::new();
     variants.push(query.to_string());
 
@@ -386,6 +494,32 @@ pub fn expand_query(query: &str) -> Vec<String> {
         variants.push(keywords.join(" "));
     }
 
+    if expansion.stemming && !keywords.is_empty() {
+        let stemmer = stemmer_for(language);
+        let stemmed = keywords.iter().map(|w| stemmer.stem(w)).collect::<Vec<_>>().join(" ");
+        if !variants.contains(&stemmed) {
+            variants.push(stemmed);
+        }
+    }
+
+    if !expansion.synonyms.is_empty() {
+        let normalized = lower.split_whitespace().collect::<Vec<_>>().join(" ");
+        let mut extra: Vec<&str> = Vec::new();
+        for group in &expansion.synonyms {
+            if group.iter().any(|term| contains_phrase(&normalized, term)) {
+                for term in group {
+                    if !contains_phrase(&normalized, term) && !extra.contains(&term.as_str()) {
+                        extra.push(term);
+                    }
+                }
+            }
+        }
+        if !extra.is_empty() {
+            let base = if keywords.is_empty() { normalized.clone() } else { keywords.join(" ") };
+            variants.push(format!("{} {}", base, extra.join(" ")));
+        }
+    }
+
     variants
 }
 
@@ -408,6 +542,14 @@ mod tests {
         assert!(chunks.len() >= 2);
     }
 
+    #[test]
+    fn test_chunk_with_overlap_cjk_sentences() {
+        let text = "これは最初の文です。".repeat(20);
+        let chunks = chunk_with_overlap(&text, 100, 0);
+        assert!(chunks.len() > 1);
+        assert!(chunks[..chunks.len() - 1].iter().all(|c| c.ends_with('。')));
+    }
+
     #[test]
     fn test_chunk_short_text() {
         let text = "Short";
@@ -437,6 +579,17 @@ mod tests {
         assert_eq!(cfg.overlap_bytes, 100);
     }
 
+    #[test]
+    fn test_page_slices() {
+        let text = "Intro page\x0c\x0cResults on page three\x0c";
+        let slices = page_slices(text, "pdf", None, None).unwrap();
+        assert_eq!(slices.len(), 2);
+        assert_eq!(slices[0].1, "p.1");
+        assert_eq!(slices[1].1, "p.3");
+        assert!(slices[1].0.contains("page three"));
+        assert_eq!(page_slices("no breaks", "pdf", None, None), None);
+    }
+
     #[test]
     fn test_get_chunk_config_default() {
         let cfg = get_chunk_config("pdf");
@@ -477,29 +630,62 @@ mod tests {
 
     #[test]
     fn test_expand_query_basic() {
-        let variants = expand_query("Hello World");
+        let variants = expand_query("Hello World", &QueryExpansion::default(), None);
         assert!(variants.contains(&"Hello World".to_string()));
         assert!(variants.contains(&"hello world".to_string()));
     }
 
     #[test]
     fn test_expand_query_stop_words() {
-        let variants = expand_query("how to implement search");
+        let variants = expand_query("how to implement search", &QueryExpansion::default(), None);
         assert!(variants.iter().any(|v| v == "implement search"));
     }
 
     #[test]
     fn test_expand_query_already_lowercase() {
-        let variants = expand_query("hello");
+        let variants = expand_query("hello", &QueryExpansion::default(), None);
         assert_eq!(variants.len(), 1);
     }
 
     #[test]
     fn test_expand_query_turkish() {
-        let variants = expand_query("bu dosya için arama");
+        let variants = expand_query("bu dosya için arama", &QueryExpansion::default(), None);
         assert!(variants.iter().any(|v| v == "dosya arama"));
     }
 
+    #[test]
+    fn test_expand_query_stemming() {
+        let expansion = QueryExpansion { stemming: true, synonyms: vec![] };
+        let variants = expand_query("indexing files", &expansion, None);
+        assert!(variants.iter().any(|v| v == "index file"));
+        let variants = expand_query("Suchmaschinen", &expansion, Some("de"));
+        assert!(variants.iter().any(|v| v == "suchmaschin"));
+    }
+
+    #[test]
+    fn test_expand_query_synonyms() {
+        let expansion = QueryExpansion::new(false, &["auth = authentication, login".to_string()]);
+        let variants = expand_query("auth flow", &expansion, None);
+        assert!(variants.iter().any(|v| v == "auth flow authentication login"));
+        let variants = expand_query("author", &expansion, None);
+        assert_eq!(variants.len(), 1);
+    }
+
+    #[test]
+    fn test_parse_synonyms() {
+        let groups = parse_synonyms(&[
+            "Auth = Authentication, login".to_string(),
+            "k8s,  kubernetes ".to_string(),
+            "lonely".to_string(),
+            "single sign on = sso".to_string(),
+        ]);
+        assert_eq!(groups, vec![
+            vec!["auth".to_string(), "authentication".to_string(), "login".to_string()],
+            vec!["k8s".to_string(), "kubernetes".to_string()],
+            vec!["single sign on".to_string(), "sso".to_string()],
+        ]);
+    }
+
     #[test]
     fn test_override_chunk_size_zero_clamps_to_100() {
         let text = "a".repeat(500);
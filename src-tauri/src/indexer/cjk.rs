@@ -0,0 +1,125 @@
+/// Sentence-ending punctuation for scripts that don't put spaces between words.
+pub const SENTENCE_TERMINATORS: &[char] = &['。', '！', '？', '；', '．'];
+
+/// Han, Hiragana and Katakana: the scripts written without word spaces. Hangul
+/// is left out since Korean separates words with spaces already.
+pub fn is_cjk(c: char) -> bool {
+    matches!(c as u32,
+        0x3040..=0x30FF      // Hiragana, Katakana
+        | 0x31F0..=0x31FF    // Katakana phonetic extensions
+        | 0x3400..=0x4DBF    // CJK extension A
+        | 0x4E00..=0x9FFF    // CJK unified ideographs
+        | 0xF900..=0xFAFF    // CJK compatibility ideographs
+        | 0xFF66..=0xFF9F    // Halfwidth Katakana
+        | 0x20000..=0x2FA1F  // CJK extensions B-F, compatibility supplement
+    )
+}
+
+pub fn contains_cjk(text: &str) -> bool {
+    text.chars().any(is_cjk)
+}
+
+/// Rewrites every CJK run in a query as space-separated overlapping bigrams
+/// ("検索エンジン" -> "検索 索エ エン ンジ ジン") so a whitespace tokenizer
+/// produces matchable terms. Non-CJK text passes through untouched.
+pub fn segment(text: &str) -> String {
+    segment_with(text, false)
+}
+
+/// `segment` for the FTS column, with every character on its own as well
+/// so a one-character query (猫) matches inside longer runs.
+pub fn segment_for_index(text: &str) -> String {
+    segment_with(text, true)
+}
+
+fn segment_with(text: &str, unigrams: bool) -> String {
+    if !contains_cjk(text) {
+        return text.to_string();
+    }
+
+    let mut out = String::with_capacity(text.len() * 2);
+    let mut run: Vec<char> = Vec::new();
+
+    let flush = |run: &mut Vec<char>, out: &mut String| {
+        if run.is_empty() {
+            return;
+        }
+        if out.chars().last().is_some_and(|c| !c.is_whitespace()) {
+            out.push(' ');
+        }
+        if run.len() == 1 {
+            out.push(run[0]);
+        } else if unigrams {
+            for (i, c) in run.iter().enumerate() {
+                if i > 0 {
+                    out.push(' ');
+                }
+                out.push(*c);
+                if let Some(next) = run.get(i + 1) {
+                    out.push(' ');
+                    out.push(*c);
+                    out.push(*next);
+                }
+            }
+        } else {
+            for (i, pair) in run.windows(2).enumerate() {
+                if i > 0 {
+                    out.push(' ');
+                }
+                out.push(pair[0]);
+                out.push(pair[1]);
+            }
+        }
+        run.clear();
+    };
+
+    let mut after_run = false;
+    for c in text.chars() {
+        if is_cjk(c) {
+            run.push(c);
+            continue;
+        }
+        if !run.is_empty() {
+            flush(&mut run, &mut out);
+            after_run = true;
+        }
+        if after_run && !c.is_whitespace() {
+            out.push(' ');
+        }
+        after_run = false;
+        out.push(c);
+    }
+    flush(&mut run, &mut out);
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_segment_bigrams() {
+        assert_eq!(segment("検索エンジン"), "検索 索エ エン ンジ ジン");
+        assert_eq!(segment("Rust の本"), "Rust の本");
+        assert_eq!(segment("本 a 字"), "本 a 字");
+        assert_eq!(segment("全文検索FTS"), "全文 文検 検索 FTS");
+    }
+
+    #[test]
+    fn test_segment_leaves_latin_alone() {
+        assert_eq!(segment("hello world"), "hello world");
+        assert_eq!(segment("한국어 검색"), "한국어 검색");
+    }
+
+    #[test]
+    fn test_segment_for_index_has_unigrams() {
+        assert_eq!(segment_for_index("猫が好き"), "猫 猫が が が好 好 好き き");
+        assert_eq!(segment_for_index("本 a 字"), "本 a 字");
+        assert_eq!(segment_for_index("hello world"), "hello world");
+        let indexed = segment_for_index("黒い猫です");
+        let terms: Vec<&str> = indexed.split(' ').collect();
+        assert!(terms.contains(&segment("猫").as_str()));
+        assert!(segment("黒い猫").split(' ').all(|term| terms.contains(&term)));
+    }
+}
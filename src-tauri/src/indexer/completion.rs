@@ -0,0 +1,286 @@
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use anyhow::Result;
+use arrow_array::StringArray;
+use futures::TryStreamExt;
+use lancedb::query::{ExecutableQuery, QueryBase};
+use lancedb::Table;
+use serde::Serialize;
+
+/// Fewer characters than this match too much to be worth suggesting.
+const MIN_PREFIX_LEN: usize = 2;
+/// Long headings make poor completions and bloat the index.
+const MAX_ENTRY_LEN: usize = 64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CompletionKind {
+    File,
+    Symbol,
+    Folder,
+}
+
+/// A suggested query: the typed query with its last word completed.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Completion {
+    pub text: String,
+    pub kind: CompletionKind,
+}
+
+#[derive(Debug)]
+struct Entry {
+    lower: String,
+    display: String,
+    kind: CompletionKind,
+    freq: u32,
+}
+
+/// File names and outline symbols of a container, sorted for prefix lookup.
+/// File names count once per file, symbols once per chunk they head, so
+/// names that come up often rank first.
+#[derive(Debug, Default)]
+pub struct CompletionIndex {
+    entries: Vec<Entry>,
+}
+
+/// Page labels ("p.3") and log windows ("2024-05-01 12:00–12:05") share the
+/// context column with outline paths but aren't names anyone types.
+fn is_symbol(segment: &str) -> bool {
+    let len = segment.chars().count();
+    if !(MIN_PREFIX_LEN..=MAX_ENTRY_LEN).contains(&len) {
+        return false;
+    }
+    if segment.starts_with(|c: char| c.is_ascii_digit()) {
+        return false;
+    }
+    !segment
+        .strip_prefix("p.")
+        .is_some_and(|n| n.chars().all(|c| c.is_ascii_digit()))
+}
+
+fn file_name(path: &str) -> &str {
+    path.rsplit(['/', '\\']).next().unwrap_or(path)
+}
+
+impl CompletionIndex {
+    pub fn from_rows<'a>(rows: impl IntoIterator<Item = (&'a str, &'a str)>) -> Self {
+        let mut counts: HashMap<(String, CompletionKind), u32> = HashMap::new();
+        let mut seen_paths = HashSet::new();
+        for (path, context) in rows {
+            if seen_paths.insert(path) {
+                let name = file_name(path);
+                if (MIN_PREFIX_LEN..=MAX_ENTRY_LEN).contains(&name.chars().count()) {
+                    *counts.entry((name.to_string(), CompletionKind::File)).or_default() += 1;
+                }
+            }
+            for segment in context.split(" > ").map(str::trim).filter(|s| is_symbol(s)) {
+                *counts.entry((segment.to_string(), CompletionKind::Symbol)).or_default() += 1;
+            }
+        }
+
+        let mut entries: Vec<Entry> = counts
+            .into_iter()
+            .map(|((display, kind), freq)| Entry { lower: display.to_lowercase(), display, kind, freq })
+            .collect();
+        entries.sort_by(|a, b| a.lower.cmp(&b.lower));
+        Self { entries }
+    }
+
+    pub async fn build(table: &Table) -> Result<Self> {
+        let batches = table
+            .query()
+            .select(lancedb::query::Select::Columns(vec![
+                "path".to_string(),
+                super::db::CONTEXT_COLUMN.to_string(),
+            ]))
+            .execute()
+            .await?
+            .try_collect::<Vec<_>>()
+            .await?;
+
+        let column = |b: &arrow_array::RecordBatch, name: &str| {
+            b.column_by_name(name)
+                .and_then(|c| c.as_any().downcast_ref::<StringArray>())
+                .cloned()
+        };
+        let columns: Vec<(StringArray, StringArray)> = batches
+            .iter()
+            .filter_map(|b| Some((column(b, "path")?, column(b, super::db::CONTEXT_COLUMN)?)))
+            .collect();
+        Ok(Self::from_rows(columns.iter().flat_map(|(paths, contexts)| {
+            paths.iter().zip(contexts.iter()).filter_map(|(p, c)| Some((p?, c.unwrap_or(""))))
+        })))
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Up to `limit` completions of the query's last word, most frequent
+    /// first. Names the word already spells out exactly are skipped.
+    pub fn complete(&self, query: &str, limit: usize) -> Vec<Completion> {
+        if query.ends_with(char::is_whitespace) {
+            return Vec::new();
+        }
+        let Some(word) = query.split_whitespace().last() else {
+            return Vec::new();
+        };
+        if word.chars().count() < MIN_PREFIX_LEN {
+            return Vec::new();
+        }
+        let prefix = word.to_lowercase();
+        let head = &query[..query.len() - word.len()];
+
+        let start = self.entries.partition_point(|e| e.lower.as_str() < prefix.as_str());
+        let mut matches: Vec<&Entry> = self.entries[start..]
+            .iter()
+            .take_while(|e| e.lower.starts_with(&prefix))
+            .filter(|e| e.lower != prefix)
+            .collect();
+        matches.sort_by(|a, b| b.freq.cmp(&a.freq).then(a.lower.len().cmp(&b.lower.len())).then(a.lower.cmp(&b.lower)));
+
+        let mut seen = HashSet::new();
+        matches
+            .into_iter()
+            .filter(|e| seen.insert(e.display.as_str()))
+            .take(limit)
+            .map(|e| Completion { text: format!("{}{}", head, e.display), kind: e.kind })
+            .collect()
+    }
+}
+
+/// `path` with `/` for separators, and lowercase on Windows where case
+/// doesn't tell paths apart, for comparing with indexed roots.
+fn comparable(path: &str) -> String {
+    let path = path.replace('\\', "/");
+    let path = path.trim_end_matches('/');
+    if cfg!(windows) {
+        path.to_lowercase()
+    } else {
+        path.to_string()
+    }
+}
+
+/// Whether `path` is inside one of `roots`, or, for a folder, on the way
+/// down to one.
+fn near_root(path: &str, is_dir: bool, roots: &[String]) -> bool {
+    let path = comparable(path);
+    roots.iter().map(|root| comparable(root)).any(|root| {
+        path == root || path.starts_with(&format!("{}/", root)) || (is_dir && root.starts_with(&format!("{}/", path)))
+    })
+}
+
+/// Up to `limit` files and folders completing `partial`, a path typed into
+/// the search bar, among the indexed `roots`: what's inside a root and the
+/// folders on the way down to one. A leading `~` is `home` and stays in
+/// the completions; folders end with a separator so the next Tab goes in.
+pub fn complete_path(partial: &str, roots: &[String], home: Option<&Path>, limit: usize) -> Vec<Completion> {
+    let home = home.map(|h| h.to_string_lossy().trim_end_matches(['/', '\\']).to_string());
+    let (expanded, tilde) = match (partial.strip_prefix('~'), &home) {
+        (Some(""), Some(_)) => return vec![Completion { text: "~/".to_string(), kind: CompletionKind::Folder }],
+        (Some(rest), Some(home)) if rest.starts_with(['/', '\\']) => (format!("{}{}", home, rest), true),
+        _ => (partial.to_string(), false),
+    };
+    let Some(cut) = expanded.rfind(['/', '\\']) else {
+        return Vec::new();
+    };
+    let (dir, leaf) = expanded.split_at(cut + 1);
+    let separator = &dir[cut..];
+    let leaf = leaf.to_lowercase();
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let mut found: Vec<(bool, String)> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let lower = name.to_lowercase();
+            if !lower.starts_with(&leaf) || (name.starts_with('.') && !leaf.starts_with('.')) {
+                return None;
+            }
+            let is_dir = entry.file_type().is_ok_and(|t| t.is_dir());
+            let full = format!("{}{}", dir, name);
+            near_root(&full, is_dir, roots).then_some((is_dir, name))
+        })
+        .collect();
+    found.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.to_lowercase().cmp(&b.1.to_lowercase())));
+
+    let shown_dir = match (&home, tilde) {
+        (Some(home), true) => format!("~{}", &dir[home.len()..]),
+        _ => dir.to_string(),
+    };
+    found
+        .into_iter()
+        .take(limit)
+        .map(|(is_dir, name)| Completion {
+            text: format!("{}{}{}", shown_dir, name, if is_dir { separator } else { "" }),
+            kind: if is_dir { CompletionKind::Folder } else { CompletionKind::File },
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_complete() {
+        let index = CompletionIndex::from_rows([
+            ("C:\\src\\config.rs", "Config > load"),
+            ("C:\\src\\config.rs", "Config > load_from"),
+            ("C:\\src\\commands.rs", "update_config"),
+            ("/docs/guide.md", "Setup > Configuration"),
+            ("/docs/manual.pdf", "p.3"),
+            ("/logs/app.log", "2024-05-01 12:00–12:05"),
+        ]);
+
+        let texts = |q: &str| index.complete(q, 5).into_iter().map(|c| c.text).collect::<Vec<_>>();
+        assert_eq!(texts("conf"), vec!["Config", "config.rs", "Configuration"]);
+        assert_eq!(texts("where is lo"), vec!["where is load", "where is load_from"]);
+        assert_eq!(texts("load"), vec!["load_from"]);
+        assert!(texts("p.").is_empty());
+        assert!(texts("20").is_empty());
+        assert!(texts("c").is_empty());
+        assert!(texts("conf ").is_empty());
+
+        let kinds: Vec<_> = index.complete("comm", 5).into_iter().map(|c| c.kind).collect();
+        assert_eq!(kinds, vec![CompletionKind::File]);
+    }
+
+    #[test]
+    fn test_complete_path() {
+        let base = std::env::temp_dir().join(format!("rememex_paths_{}", std::process::id()));
+        let repo = base.join("work").join("repo");
+        std::fs::create_dir_all(repo.join("src")).unwrap();
+        std::fs::create_dir_all(base.join("work").join("private")).unwrap();
+        std::fs::create_dir_all(base.join(".cache")).unwrap();
+        std::fs::write(repo.join("README.md"), "").unwrap();
+        std::fs::write(repo.join("Cargo.toml"), "").unwrap();
+        let roots = vec![repo.to_string_lossy().to_string()];
+        let sep = std::path::MAIN_SEPARATOR;
+        let texts = |partial: String| complete_path(&partial, &roots, Some(&base), 10).into_iter().map(|c| c.text).collect::<Vec<_>>();
+
+        // On the way down to the root, only the folders leading to it.
+        assert_eq!(texts(format!("~{}", sep)), vec![format!("~{}work{}", sep, sep)]);
+        assert_eq!(texts(format!("~{}work{}", sep, sep)), vec![format!("~{}work{}repo{}", sep, sep, sep)]);
+        // Inside it, folders first, then files, matched without case.
+        let inside = format!("{}{}", repo.display(), sep);
+        assert_eq!(
+            texts(inside.clone()),
+            vec![format!("{}src{}", inside, sep), format!("{}Cargo.toml", inside), format!("{}README.md", inside)]
+        );
+        assert_eq!(texts(format!("{}re", inside)), vec![format!("{}README.md", inside)]);
+        assert_eq!(texts("~".to_string()), vec!["~/".to_string()]);
+        assert!(texts("repo".to_string()).is_empty());
+
+        let kinds: Vec<_> = complete_path(&inside, &roots, None, 10).into_iter().map(|c| c.kind).collect();
+        assert_eq!(kinds, vec![CompletionKind::Folder, CompletionKind::File, CompletionKind::File]);
+        std::fs::remove_dir_all(&base).ok();
+    }
+}
@@ -1,5 +1,4 @@
 use std::collections::HashMap;
-use std::path::Path;
 use std::sync::Arc;
 
 use anyhow::{anyhow, Result};
@@ -10,28 +9,92 @@ use arrow_array::{
 use arrow_schema::{DataType, Field, Schema};
 use futures::TryStreamExt;
 use lancedb::connection::Connection;
-use lancedb::index::Index;
+use lancedb::index::{Index, IndexType};
 use lancedb::query::{ExecutableQuery, QueryBase};
+use lancedb::table::{NewColumnTransform, OptimizeAction, OptimizeOptions};
 use lancedb::Table;
 
+use super::cjk;
+
+const FTS_REBUILD_RATIO: f64 = 0.25;
+/// Copy of `content` with CJK runs split into bigrams; the FTS index lives here so
+/// snippets keep their original text.
+pub const FTS_COLUMN: &str = "fts_content";
+/// ISO 639-1 code of the file's predominant language, empty when unknown.
+pub const LANG_COLUMN: &str = "lang";
+/// Heading path or enclosing symbol of the chunk, empty when the file has no
+/// structure to read it from. Also written into `content` as a "Context:" line.
+pub const CONTEXT_COLUMN: &str = "context";
+/// Hash of the chunk text without its header lines, see `dedup`. Empty for
+/// rows indexed before deduplication.
+pub const CHUNK_HASH_COLUMN: &str = "chunk_hash";
+/// Size of the file on disk in bytes, 0 for rows indexed before it was kept.
+pub const SIZE_COLUMN: &str = "size";
+/// Lines in the file's extracted text, 0 for rows indexed before it was kept.
+pub const LINES_COLUMN: &str = "lines";
+/// Byte offset the chunk starts at in its file's extracted text, -1 where
+/// it isn't known: git history, key listings, rows indexed before it was
+/// kept. Tells overlapping chunks of a file from distant ones.
+pub const CHUNK_START_COLUMN: &str = "chunk_start";
+
 pub struct Record {
     pub path: String,
     pub content: String,
+    pub context: String,
     pub vector: Vec<f32>,
     pub mtime: i64,
+    pub lang: String,
+    pub chunk_hash: String,
+    pub size: i64,
+    pub lines: i64,
+    pub start: i64,
 }
 
 pub struct PendingChunk {
     pub path: String,
     pub content: String,
+    pub context: String,
     pub mtime: i64,
+    pub lang: String,
+    pub chunk_hash: String,
+    pub size: i64,
+    pub lines: i64,
+    pub start: i64,
+}
+
+/// How often tables in a shared or object-store index look for versions
+/// written elsewhere. In between, the opened table state is reused instead
+/// of going back to the share or bucket on every query.
+const SHARED_CONSISTENCY_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60);
+
+/// Opens the app's own LanceDB directory.
+pub async fn connect(uri: &str) -> Result<Connection> {
+    Ok(lancedb::connect(uri).execute().await?)
 }
 
-pub async fn reset_index(db_path: &Path, table_name: &str) -> Result<()> {
-    let db = lancedb::connect(&db_path.to_string_lossy())
+/// Opens an index outside the app's own database: a share path or an
+/// object store URI (`s3://bucket/prefix`) with already resolved storage
+/// options.
+pub async fn connect_shared(uri: &str, storage_options: Vec<(String, String)>) -> Result<Connection> {
+    Ok(lancedb::connect(uri)
+        .storage_options(storage_options)
+        .read_consistency_interval(SHARED_CONSISTENCY_INTERVAL)
         .execute()
-        .await?;
+        .await?)
+}
+
+pub async fn reset_index(db: &Connection, table_name: &str) -> Result<()> {
+    super::snapshot::clear(table_name);
     let _ = db.drop_table(table_name, &[]).await;
+    let _ = db.drop_table(&super::dedup::refs_table_name(table_name), &[]).await;
+    let _ = db.drop_table(&super::versions::versions_table_name(table_name), &[]).await;
+    let _ = db.drop_table(&super::tombstones::tombstones_table_name(table_name), &[]).await;
+    let _ = db.drop_table(&super::file_vectors::file_vectors_table_name(table_name), &[]).await;
+    let _ = db.drop_table(&super::summaries::summaries_table_name(table_name), &[]).await;
+    let _ = db.drop_table(&super::entities::entities_table_name(table_name), &[]).await;
+    super::change_journal::forget(table_name, None);
+    super::topics::forget(table_name);
+    super::secrets::forget(table_name, None);
     info!("Index reset: dropped table '{}'", table_name);
     Ok(())
 }
@@ -46,10 +109,85 @@ pub async fn build_ann_index(table: &Table) -> Result<()> {
 }
 
 pub async fn build_fts_index(table: &Table) -> Result<()> {
-    let _ = table
-        .create_index(&["content"], Index::FTS(Default::default()))
+    table
+        .create_index(&[FTS_COLUMN], Index::FTS(Default::default()))
         .execute()
-        .await;
+        .await?;
+    debug!("FTS index built for table");
+    Ok(())
+}
+
+async fn find_fts_index(table: &Table, column: &str) -> Result<Option<String>> {
+    let indices = table.list_indices().await?;
+    Ok(indices
+        .into_iter()
+        .find(|idx| idx.index_type == IndexType::FTS && idx.columns.iter().any(|c| c == column))
+        .map(|idx| idx.name))
+}
+
+/// Columns added after the original schema, with the SQL used to backfill
+/// existing rows. Backfilled rows get real values once their file is re-indexed.
+const DERIVED_COLUMNS: &[(&str, &str)] = &[
+    (FTS_COLUMN, "content"),
+    (LANG_COLUMN, "''"),
+    (CONTEXT_COLUMN, "''"),
+    (CHUNK_HASH_COLUMN, "''"),
+    (SIZE_COLUMN, "CAST(0 AS BIGINT)"),
+    (LINES_COLUMN, "CAST(0 AS BIGINT)"),
+    (CHUNK_START_COLUMN, "CAST(-1 AS BIGINT)"),
+];
+
+/// Adds any missing derived column in place instead of dropping the table. When
+/// the FTS column is added, the old index on `content` is dropped as well.
+pub async fn ensure_columns(table: &Table) -> Result<()> {
+    let schema = table.schema().await?;
+    let missing: Vec<(String, String)> = DERIVED_COLUMNS
+        .iter()
+        .filter(|(name, _)| schema.field_with_name(name).is_err())
+        .map(|(name, expr)| (name.to_string(), expr.to_string()))
+        .collect();
+    if missing.is_empty() {
+        return Ok(());
+    }
+
+    let added_fts = missing.iter().any(|(name, _)| name == FTS_COLUMN);
+    let names: Vec<String> = missing.iter().map(|(name, _)| name.clone()).collect();
+    table
+        .add_columns(NewColumnTransform::SqlExpressions(missing), None)
+        .await?;
+
+    if added_fts {
+        if let Some(old_index) = find_fts_index(table, "content").await? {
+            if let Err(e) = table.drop_index(&old_index).await {
+                warn!("Could not drop legacy FTS index '{}': {}", old_index, e);
+            }
+        }
+    }
+    info!("Migrated table: added {:?}; rebuild the index to fill them for existing files", names);
+    Ok(())
+}
+
+pub async fn update_fts_index(table: &Table) -> Result<()> {
+    ensure_columns(table).await?;
+    let Some(index_name) = find_fts_index(table, FTS_COLUMN).await? else {
+        return build_fts_index(table).await;
+    };
+
+    let needs_rebuild = match table.index_stats(&index_name).await? {
+        Some(stats) => {
+            stats.num_unindexed_rows as f64 > stats.num_indexed_rows.max(1) as f64 * FTS_REBUILD_RATIO
+        }
+        None => true,
+    };
+
+    if needs_rebuild {
+        return build_fts_index(table).await;
+    }
+
+    table
+        .optimize(OptimizeAction::Index(OptimizeOptions::default()))
+        .await?;
+    debug!("FTS index '{}' updated incrementally", index_name);
     Ok(())
 }
 
@@ -110,6 +248,73 @@ pub async fn get_indexed_mtimes(table: &Table) -> Result<HashMap<String, i64>> {
     Ok(mtimes)
 }
 
+/// Indexed file count per language code, most common first. Files whose
+/// language couldn't be detected are left out.
+pub async fn get_language_counts(table: &Table) -> Result<Vec<(String, usize)>> {
+    if table.schema().await?.field_with_name(LANG_COLUMN).is_err() {
+        return Ok(vec![]);
+    }
+
+    let results = table
+        .query()
+        .only_if(format!("{} != ''", LANG_COLUMN))
+        .select(lancedb::query::Select::Columns(vec![
+            "path".to_string(),
+            LANG_COLUMN.to_string(),
+        ]))
+        .execute()
+        .await?
+        .try_collect::<Vec<_>>()
+        .await?;
+
+    let mut files: HashMap<String, std::collections::HashSet<String>> = HashMap::new();
+    for batch in results {
+        let paths = batch.column_by_name("path").and_then(|c| c.as_any().downcast_ref::<StringArray>());
+        let langs = batch.column_by_name(LANG_COLUMN).and_then(|c| c.as_any().downcast_ref::<StringArray>());
+        if let (Some(paths), Some(langs)) = (paths, langs) {
+            for i in 0..batch.num_rows() {
+                files
+                    .entry(langs.value(i).to_string())
+                    .or_default()
+                    .insert(paths.value(i).to_string());
+            }
+        }
+    }
+
+    let mut counts: Vec<(String, usize)> = files.into_iter().map(|(lang, paths)| (lang, paths.len())).collect();
+    counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    Ok(counts)
+}
+
+/// Indexed file count per extension, lowercase and without the dot, most
+/// common first. Files without one are left out.
+pub async fn get_extension_counts(table: &Table) -> Result<Vec<(String, usize)>> {
+    let results = table
+        .query()
+        .select(lancedb::query::Select::Columns(vec!["path".to_string()]))
+        .execute()
+        .await?
+        .try_collect::<Vec<_>>()
+        .await?;
+
+    let mut files: std::collections::HashSet<String> = std::collections::HashSet::new();
+    for batch in results {
+        if let Some(paths) = batch.column_by_name("path").and_then(|c| c.as_any().downcast_ref::<StringArray>()) {
+            files.extend(paths.iter().flatten().map(str::to_string));
+        }
+    }
+
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for path in &files {
+        if let Some(ext) = std::path::Path::new(path).extension().and_then(|e| e.to_str()) {
+            *counts.entry(ext.to_lowercase()).or_default() += 1;
+        }
+    }
+    let mut counts: Vec<(String, usize)> = counts.into_iter().collect();
+    counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    Ok(counts)
+}
+
 pub async fn get_or_create_table(db: &Connection, table_name: &str, dim: usize) -> Result<Table> {
     if let Ok(table) = db.open_table(table_name).execute().await {
         let schema = table.schema().await?;
@@ -117,12 +322,13 @@ pub async fn get_or_create_table(db: &Connection, table_name: &str, dim: usize)
         if let Ok(field) = schema.field_with_name("vector") {
             if let DataType::FixedSizeList(_, size) = field.data_type() {
                 if *size == dim as i32 && has_mtime {
+                    ensure_columns(&table).await?;
                     return Ok(table);
                 }
             }
         }
         warn!("Table '{}' schema mismatch (dim or mtime), recreating", table_name);
-        let _ = db.drop_table(table_name, &[]).await;
+        reset_index(db, table_name).await?;
     }
 
     let schema = Arc::new(make_schema(dim));
@@ -150,6 +356,13 @@ fn make_schema(dim: usize) -> Schema {
             false,
         ),
         Field::new("mtime", DataType::Int64, false),
+        Field::new(FTS_COLUMN, DataType::Utf8, true),
+        Field::new(LANG_COLUMN, DataType::Utf8, true),
+        Field::new(CONTEXT_COLUMN, DataType::Utf8, true),
+        Field::new(CHUNK_HASH_COLUMN, DataType::Utf8, true),
+        Field::new(SIZE_COLUMN, DataType::Int64, true),
+        Field::new(LINES_COLUMN, DataType::Int64, true),
+        Field::new(CHUNK_START_COLUMN, DataType::Int64, true),
     ])
 }
 
@@ -164,6 +377,13 @@ pub fn create_record_batch(records: Vec<Record>) -> Result<RecordBatch> {
     let paths: Vec<String> = records.iter().map(|r| r.path.clone()).collect();
     let contents: Vec<String> = records.iter().map(|r| r.content.clone()).collect();
     let mtimes: Vec<i64> = records.iter().map(|r| r.mtime).collect();
+    let fts_contents: Vec<String> = records.iter().map(|r| cjk::segment_for_index(&r.content)).collect();
+    let langs: Vec<String> = records.iter().map(|r| r.lang.clone()).collect();
+    let contexts: Vec<String> = records.iter().map(|r| r.context.clone()).collect();
+    let hashes: Vec<String> = records.iter().map(|r| r.chunk_hash.clone()).collect();
+    let sizes: Vec<i64> = records.iter().map(|r| r.size).collect();
+    let lines: Vec<i64> = records.iter().map(|r| r.lines).collect();
+    let starts: Vec<i64> = records.iter().map(|r| r.start).collect();
 
     let mut flat_vectors = Vec::with_capacity(records.len() * dim);
     for r in &records {
@@ -184,6 +404,13 @@ pub fn create_record_batch(records: Vec<Record>) -> Result<RecordBatch> {
             Arc::new(StringArray::from(contents)),
             Arc::new(vector_array),
             Arc::new(Int64Array::from(mtimes)),
+            Arc::new(StringArray::from(fts_contents)),
+            Arc::new(StringArray::from(langs)),
+            Arc::new(StringArray::from(contexts)),
+            Arc::new(StringArray::from(hashes)),
+            Arc::new(Int64Array::from(sizes)),
+            Arc::new(Int64Array::from(lines)),
+            Arc::new(Int64Array::from(starts)),
         ],
     )
     .map_err(|e| anyhow!(e))
@@ -0,0 +1,262 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use anyhow::Result;
+use arrow_array::{Int64Array, RecordBatch, RecordBatchIterator, StringArray};
+use arrow_schema::{DataType, Field, Schema};
+use futures::TryStreamExt;
+use lancedb::connection::Connection;
+use lancedb::query::{ExecutableQuery, QueryBase};
+use lancedb::Table;
+use log::{debug, info};
+
+use super::db::CHUNK_HASH_COLUMN;
+use super::{entities, file_vectors};
+
+/// Values per `IN (...)` list, to keep filters a sane size.
+const FILTER_BATCH: usize = 200;
+
+/// Identical chunks (license headers, generated boilerplate) are embedded and
+/// stored once, under the first file they were seen in. This table records
+/// every file containing each chunk, so the row can move to another file
+/// when its owner goes and files without rows of their own still have an
+/// mtime for change detection.
+pub fn refs_table_name(container_table: &str) -> String {
+    format!("{}_chunk_refs", container_table)
+}
+
+fn make_refs_schema() -> Schema {
+    Schema::new(vec![
+        Field::new("hash", DataType::Utf8, false),
+        Field::new("path", DataType::Utf8, false),
+        Field::new("mtime", DataType::Int64, false),
+    ])
+}
+
+/// Chunks differ only in their "File:"/"Context:" header when the same text
+/// appears in two files, so the hash covers what follows it.
+pub fn chunk_hash(content: &str) -> String {
+    let mut body = content
+        .strip_prefix("File: ")
+        .map_or(content, |rest| rest.split_once('\n').map_or("", |(_, b)| b));
+    if let Some(rest) = body.strip_prefix("Context: ") {
+        body = rest.split_once('\n').map_or("", |(_, b)| b);
+    }
+    blake3::hash(body.as_bytes()).to_hex()[..32].to_string()
+}
+
+fn quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+/// `column IN (...)` filters over `values`, split into batches.
+pub fn in_filters(column: &str, values: &[String]) -> Vec<String> {
+    values
+        .chunks(FILTER_BATCH)
+        .map(|batch| {
+            let list: Vec<String> = batch.iter().map(|v| quote(v)).collect();
+            format!("{} IN ({})", column, list.join(", "))
+        })
+        .collect()
+}
+
+async fn open_refs(db: &Connection, container_table: &str) -> Option<Table> {
+    db.open_table(refs_table_name(container_table)).execute().await.ok()
+}
+
+async fn get_or_create_refs(db: &Connection, container_table: &str) -> Result<Table> {
+    if let Some(table) = open_refs(db, container_table).await {
+        return Ok(table);
+    }
+    let schema = Arc::new(make_refs_schema());
+    let table = db
+        .create_table(refs_table_name(container_table), RecordBatchIterator::new(vec![], schema))
+        .execute()
+        .await?;
+    info!("Chunk refs table for '{}' created", container_table);
+    Ok(table)
+}
+
+async fn collect(table: &Table, filter: &str, columns: &[&str]) -> Result<Vec<RecordBatch>> {
+    Ok(table
+        .query()
+        .only_if(filter)
+        .select(lancedb::query::Select::Columns(columns.iter().map(|c| c.to_string()).collect()))
+        .execute()
+        .await?
+        .try_collect::<Vec<_>>()
+        .await?)
+}
+
+fn strings<'a>(batch: &'a RecordBatch, column: &str) -> Option<&'a StringArray> {
+    batch.column_by_name(column).and_then(|c| c.as_any().downcast_ref::<StringArray>())
+}
+
+/// Which of `hashes` already have a row in the index.
+pub async fn stored_hashes(table: &Table, hashes: &[String]) -> Result<HashSet<String>> {
+    let mut stored = HashSet::new();
+    for filter in in_filters(CHUNK_HASH_COLUMN, hashes) {
+        for batch in collect(table, &filter, &[CHUNK_HASH_COLUMN]).await? {
+            if let Some(col) = strings(&batch, CHUNK_HASH_COLUMN) {
+                stored.extend(col.iter().flatten().map(str::to_string));
+            }
+        }
+    }
+    Ok(stored)
+}
+
+/// Records that each `(hash, path, mtime)` file contains that chunk.
+pub async fn add_refs(db: &Connection, container_table: &str, refs: Vec<(String, String, i64)>) -> Result<()> {
+    if refs.is_empty() {
+        return Ok(());
+    }
+    let table = get_or_create_refs(db, container_table).await?;
+    let schema = Arc::new(make_refs_schema());
+    let (hashes, rest): (Vec<String>, Vec<(String, i64)>) = refs.into_iter().map(|(h, p, m)| (h, (p, m))).unzip();
+    let (paths, mtimes): (Vec<String>, Vec<i64>) = rest.into_iter().unzip();
+    let batch = RecordBatch::try_new(
+        schema.clone(),
+        vec![
+            Arc::new(StringArray::from(hashes)),
+            Arc::new(StringArray::from(paths)),
+            Arc::new(Int64Array::from(mtimes)),
+        ],
+    )?;
+    table.add(RecordBatchIterator::new(vec![Ok(batch)], schema)).execute().await?;
+    Ok(())
+}
+
+/// Mtime of every file with chunk refs, including files whose chunks are
+/// all stored under other files.
+pub async fn ref_mtimes(db: &Connection, container_table: &str) -> Result<HashMap<String, i64>> {
+    let Some(table) = open_refs(db, container_table).await else {
+        return Ok(HashMap::new());
+    };
+    let batches = table
+        .query()
+        .select(lancedb::query::Select::Columns(vec!["path".to_string(), "mtime".to_string()]))
+        .execute()
+        .await?
+        .try_collect::<Vec<_>>()
+        .await?;
+    let mut mtimes = HashMap::new();
+    for batch in batches {
+        let mtime_col = batch.column_by_name("mtime").and_then(|c| c.as_any().downcast_ref::<Int64Array>());
+        if let (Some(paths), Some(mtime_col)) = (strings(&batch, "path"), mtime_col) {
+            for i in 0..batch.num_rows() {
+                mtimes.insert(paths.value(i).to_string(), mtime_col.value(i));
+            }
+        }
+    }
+    Ok(mtimes)
+}
+
+pub async fn ref_mtime(db: &Connection, container_table: &str, path: &str) -> Result<Option<i64>> {
+    let Some(table) = open_refs(db, container_table).await else {
+        return Ok(None);
+    };
+    let batches = table
+        .query()
+        .only_if(format!("path = {}", quote(path)))
+        .select(lancedb::query::Select::Columns(vec!["mtime".to_string()]))
+        .limit(1)
+        .execute()
+        .await?
+        .try_collect::<Vec<_>>()
+        .await?;
+    Ok(batches.iter().find_map(|batch| {
+        let col = batch.column_by_name("mtime").and_then(|c| c.as_any().downcast_ref::<Int64Array>())?;
+        (batch.num_rows() > 0).then(|| col.value(0))
+    }))
+}
+
+/// Removes the rows, refs and file vectors of every file matching `filter`
+/// (an expression on `path`). Chunks another file still contains are handed
+/// to that file instead of being deleted, so they stay searchable without
+/// re-embedding.
+pub async fn remove(db: &Connection, table: &Table, container_table: &str, filter: &str) -> Result<()> {
+    file_vectors::remove(db, container_table, filter).await?;
+    entities::remove(db, container_table, filter).await?;
+    let Some(refs) = open_refs(db, container_table).await else {
+        table.delete(filter).await?;
+        return Ok(());
+    };
+
+    let mut owned: Vec<String> = Vec::new();
+    for batch in collect(table, &format!("({}) AND {} != ''", filter, CHUNK_HASH_COLUMN), &[CHUNK_HASH_COLUMN]).await? {
+        if let Some(col) = strings(&batch, CHUNK_HASH_COLUMN) {
+            owned.extend(col.iter().flatten().map(str::to_string));
+        }
+    }
+    refs.delete(filter).await?;
+
+    // Next owner per orphaned chunk, grouped so each file takes over its
+    // chunks in one update.
+    let mut heirs: HashMap<(String, i64), Vec<String>> = HashMap::new();
+    let mut seen = HashSet::new();
+    for hash_filter in in_filters("hash", &owned) {
+        for batch in collect(&refs, &hash_filter, &["hash", "path", "mtime"]).await? {
+            let mtime_col = batch.column_by_name("mtime").and_then(|c| c.as_any().downcast_ref::<Int64Array>());
+            if let (Some(hashes), Some(paths), Some(mtime_col)) = (strings(&batch, "hash"), strings(&batch, "path"), mtime_col) {
+                for i in 0..batch.num_rows() {
+                    if seen.insert(hashes.value(i).to_string()) {
+                        heirs
+                            .entry((paths.value(i).to_string(), mtime_col.value(i)))
+                            .or_default()
+                            .push(hashes.value(i).to_string());
+                    }
+                }
+            }
+        }
+    }
+
+    for ((path, mtime), hashes) in &heirs {
+        for hash_filter in in_filters(CHUNK_HASH_COLUMN, hashes) {
+            table
+                .update()
+                .only_if(format!("({}) AND {}", filter, hash_filter))
+                .column("path", quote(path))
+                .column("mtime", mtime.to_string())
+                .execute()
+                .await?;
+        }
+    }
+    table.delete(filter).await?;
+    if !heirs.is_empty() {
+        debug!("Dedup: {} shared chunks moved to {} other files", seen.len(), heirs.len());
+        let heir_paths: Vec<String> = heirs.into_keys().map(|(path, _)| path).collect();
+        file_vectors::update(db, table, container_table, &heir_paths).await?;
+        if entities::is_built(db, container_table).await {
+            entities::update(db, table, container_table, &heir_paths).await?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chunk_hash_ignores_header() {
+        let license = "Licensed under the Apache License, Version 2.0";
+        let a = chunk_hash(&format!("File: a.rs\n{}", license));
+        let b = chunk_hash(&format!("File: b.rs\nContext: mod tests\n{}", license));
+        assert_eq!(a, b);
+        assert_eq!(a, chunk_hash(license));
+        assert_eq!(a.len(), 32);
+        assert_ne!(a, chunk_hash("File: a.rs\nLicensed under the MIT License"));
+        assert_ne!(chunk_hash("File: a.rs\n"), chunk_hash("File: a.rs\nx"));
+    }
+
+    #[test]
+    fn test_in_filters() {
+        let values: Vec<String> = (0..FILTER_BATCH + 1).map(|i| format!("h{}", i)).collect();
+        let filters = in_filters("hash", &values);
+        assert_eq!(filters.len(), 2);
+        assert!(filters[0].starts_with("hash IN ('h0', 'h1'"));
+        assert_eq!(filters[1], format!("hash IN ('h{}')", FILTER_BATCH));
+        assert_eq!(in_filters("path", &["it's".to_string()]), vec!["path IN ('it''s')"]);
+        assert!(in_filters("hash", &[]).is_empty());
+    }
+}
@@ -8,7 +8,36 @@ use fastembed::{RerankInitOptions, RerankResult, RerankerModel, TextRerank};
 const QUERY_PREFIX: &str = "query: ";
 const PASSAGE_PREFIX: &str = "passage: ";
 
+/// The Hugging Face repo fastembed downloads `model` from.
+pub fn model_repo(model: &EmbeddingModel) -> Option<&'static str> {
+    match model {
+        EmbeddingModel::AllMiniLML6V2 => Some("Qdrant/all-MiniLM-L6-v2-onnx"),
+        EmbeddingModel::MultilingualE5Small => Some("intfloat/multilingual-e5-small"),
+        EmbeddingModel::MultilingualE5Base => Some("intfloat/multilingual-e5-base"),
+        _ => None,
+    }
+}
+
+pub const RERANKER_REPO: &str = "jinaai/jina-reranker-v2-base-multilingual";
+
+/// Whether hf-hub has a snapshot of `repo` in `cache_dir`, so loading it
+/// doesn't download anything.
+pub fn is_cached(cache_dir: &std::path::Path, repo: &str) -> bool {
+    let snapshots = cache_dir.join(format!("models--{}", repo.replace('/', "--"))).join("snapshots");
+    std::fs::read_dir(snapshots).is_ok_and(|mut entries| entries.next().is_some())
+}
+
+/// Offline, a model that isn't downloaded yet fails here rather than
+/// trying to fetch it.
+fn check_offline(cache_dir: &std::path::Path, repo: Option<&str>) -> Result<()> {
+    if crate::config::is_offline() && repo.is_some_and(|repo| !is_cached(cache_dir, repo)) {
+        return Err(anyhow!(crate::config::offline_error("Downloading the model")));
+    }
+    Ok(())
+}
+
 pub fn load_model(model: EmbeddingModel, cache_dir: std::path::PathBuf) -> Result<TextEmbedding> {
+    check_offline(&cache_dir, model_repo(&model))?;
     let mut options = InitOptions::default();
     options.model_name = model;
     options.cache_dir = cache_dir;
@@ -17,6 +46,7 @@ pub fn load_model(model: EmbeddingModel, cache_dir: std::path::PathBuf) -> Resul
 }
 
 pub fn load_reranker(cache_dir: std::path::PathBuf) -> Result<TextRerank> {
+    check_offline(&cache_dir, Some(RERANKER_REPO))?;
     let mut options = RerankInitOptions::default();
     options.model_name = RerankerModel::JINARerankerV2BaseMultiligual;
     options.cache_dir = cache_dir;
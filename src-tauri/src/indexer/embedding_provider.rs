@@ -1,29 +1,83 @@
 use anyhow::{anyhow, Result};
 use async_trait::async_trait;
-use log::{debug, error, trace};
+use fastembed::{EmbeddingModel, TextEmbedding};
+use log::{debug, error, info, trace};
 use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
 use std::sync::Arc;
-use tokio::sync::Mutex;
+use std::time::{Duration, Instant};
+use tokio::sync::{Mutex, MutexGuard};
 
 use super::embedding;
 use crate::state::ModelState;
 
+/// How long a reachability check waits for the endpoint.
+const PING_TIMEOUT: Duration = Duration::from_secs(5);
+
 #[async_trait]
 pub trait EmbeddingProvider: Send + Sync {
     async fn embed_passages(&self, texts: Vec<String>) -> Result<Vec<Vec<f32>>>;
     async fn embed_query(&self, query: &str) -> Result<Vec<f32>>;
     async fn get_dimension(&self) -> Result<usize>;
     fn provider_id(&self) -> String;
+
+    async fn is_resident(&self) -> bool {
+        true
+    }
+
+    async fn unload_if_idle(&self, _idle: Duration) -> bool {
+        false
+    }
+
+    /// Whether embeddings come from an endpoint over the network.
+    fn is_remote(&self) -> bool {
+        false
+    }
+
+    /// Whether the endpoint answers at all; None when there isn't one.
+    async fn check_reachable(&self) -> Option<Result<()>> {
+        None
+    }
 }
 
 pub struct LocalProvider {
     pub model_state: Arc<Mutex<ModelState>>,
+    model_name: EmbeddingModel,
+    cache_dir: PathBuf,
+}
+
+impl LocalProvider {
+    pub fn new(model: TextEmbedding, model_name: EmbeddingModel, cache_dir: PathBuf) -> Self {
+        Self {
+            model_state: Arc::new(Mutex::new(ModelState {
+                model: Some(model),
+                init_error: None,
+                cached_dim: None,
+                last_used: Instant::now(),
+            })),
+            model_name,
+            cache_dir,
+        }
+    }
+
+    async fn acquire(&self) -> Result<MutexGuard<'_, ModelState>> {
+        let mut guard = self.model_state.lock().await;
+        if guard.model.is_none() {
+            info!("Reloading embedding model after idle unload");
+            let name = self.model_name.clone();
+            let dir = self.cache_dir.clone();
+            let model = tokio::task::spawn_blocking(move || embedding::load_model(name, dir)).await??;
+            guard.model = Some(model);
+        }
+        guard.last_used = Instant::now();
+        Ok(guard)
+    }
 }
 
 #[async_trait]
 impl EmbeddingProvider for LocalProvider {
     async fn embed_passages(&self, texts: Vec<String>) -> Result<Vec<Vec<f32>>> {
-        let mut guard = self.model_state.lock().await;
+        let mut guard = self.acquire().await?;
         let model = guard
             .model
             .as_mut()
@@ -32,7 +86,7 @@ impl EmbeddingProvider for LocalProvider {
     }
 
     async fn embed_query(&self, query: &str) -> Result<Vec<f32>> {
-        let mut guard = self.model_state.lock().await;
+        let mut guard = self.acquire().await?;
         let model = guard
             .model
             .as_mut()
@@ -41,10 +95,10 @@ impl EmbeddingProvider for LocalProvider {
     }
 
     async fn get_dimension(&self) -> Result<usize> {
-        let mut guard = self.model_state.lock().await;
-        if let Some(dim) = guard.cached_dim {
+        if let Some(dim) = self.model_state.lock().await.cached_dim {
             return Ok(dim);
         }
+        let mut guard = self.acquire().await?;
         let model = guard
             .model
             .as_mut()
@@ -65,6 +119,19 @@ impl EmbeddingProvider for LocalProvider {
                 .unwrap_or("pending")
         )
     }
+
+    async fn is_resident(&self) -> bool {
+        self.model_state.lock().await.model.is_some()
+    }
+
+    async fn unload_if_idle(&self, idle: Duration) -> bool {
+        let mut guard = self.model_state.lock().await;
+        if guard.model.is_some() && guard.last_used.elapsed() >= idle {
+            guard.model = None;
+            return true;
+        }
+        false
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -111,6 +178,9 @@ impl EmbeddingProvider for RemoteProvider {
         if texts.is_empty() {
             return Ok(vec![]);
         }
+        if crate::config::is_offline() {
+            return Err(anyhow!(crate::config::offline_error("Remote embedding")));
+        }
 
         let mut all_embeddings = Vec::with_capacity(texts.len());
         for chunk in texts.chunks(64) {
@@ -172,4 +242,25 @@ impl EmbeddingProvider for RemoteProvider {
     fn provider_id(&self) -> String {
         format!("remote:{}:{}", self.config.endpoint, self.config.model)
     }
+
+    fn is_remote(&self) -> bool {
+        true
+    }
+
+    /// A plain GET, which costs nothing unlike an embedding. Any answer short
+    /// of a server error counts, even 405 or 401: the endpoint is up, and a
+    /// bad key shows up as soon as something is embedded.
+    async fn check_reachable(&self) -> Option<Result<()>> {
+        if crate::config::is_offline() {
+            return None;
+        }
+        let response = self.client.get(&self.config.endpoint).timeout(PING_TIMEOUT).send().await;
+        Some(match response {
+            Ok(response) if response.status().is_server_error() => {
+                Err(anyhow!("{} returned {}", self.config.endpoint, response.status()))
+            }
+            Ok(_) => Ok(()),
+            Err(e) => Err(anyhow!("{}", e)),
+        })
+    }
 }
@@ -0,0 +1,97 @@
+//! Reading a file's bytes as text whatever it was saved as: UTF-8, UTF-16
+//! with a byte order mark, or a legacy code page like Windows-1254 guessed
+//! from the bytes. Files that aren't text at all are told apart first.
+
+use chardetng::EncodingDetector;
+use encoding_rs::{Encoding, UTF_8};
+
+/// How far into a file to look for a NUL byte, like git does.
+const BINARY_SNIFF: usize = 8000;
+
+#[derive(Debug, PartialEq)]
+pub enum Decoded {
+    /// The text, and the encoding it was in when that wasn't UTF-8.
+    Text { text: String, encoding: Option<&'static str> },
+    Binary,
+}
+
+/// `bytes` as text. A byte order mark decides the encoding; otherwise a
+/// NUL byte early on means a binary file, valid UTF-8 is taken as is, and
+/// anything else goes by chardetng's guess.
+pub fn decode(bytes: &[u8]) -> Decoded {
+    if let Some((encoding, bom)) = Encoding::for_bom(bytes) {
+        let (text, _) = encoding.decode_without_bom_handling(&bytes[bom..]);
+        let name = (encoding != UTF_8).then(|| encoding.name());
+        return Decoded::Text { text: text.into_owned(), encoding: name };
+    }
+    if bytes[..bytes.len().min(BINARY_SNIFF)].contains(&0) {
+        return Decoded::Binary;
+    }
+    if let Ok(text) = std::str::from_utf8(bytes) {
+        return Decoded::Text { text: text.to_string(), encoding: None };
+    }
+    let mut detector = EncodingDetector::new();
+    detector.feed(bytes, true);
+    let encoding = detector.guess(None, false);
+    let (text, _) = encoding.decode_without_bom_handling(bytes);
+    Decoded::Text { text: text.into_owned(), encoding: Some(encoding.name()) }
+}
+
+/// `bytes` as offset, hex and printable ASCII, 16 to a line.
+pub fn hexdump(bytes: &[u8]) -> String {
+    let mut out = String::new();
+    for (row, line) in bytes.chunks(16).enumerate() {
+        let mut hex = String::new();
+        for (i, byte) in line.iter().enumerate() {
+            if i == 8 {
+                hex.push(' ');
+            }
+            hex.push_str(&format!("{:02x} ", byte));
+        }
+        let ascii: String = line
+            .iter()
+            .map(|&b| if b.is_ascii_graphic() || b == b' ' { b as char } else { '.' })
+            .collect();
+        out.push_str(&format!("{:08x}  {:<49} |{}|\n", row * 16, hex, ascii));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode() {
+        assert_eq!(decode(b"plain"), Decoded::Text { text: "plain".to_string(), encoding: None });
+        assert_eq!(
+            decode("\u{feff}bom".as_bytes()),
+            Decoded::Text { text: "bom".to_string(), encoding: None }
+        );
+        assert_eq!(
+            decode(&[0xff, 0xfe, b'h', 0, b'i', 0]),
+            Decoded::Text { text: "hi".to_string(), encoding: Some("UTF-16LE") }
+        );
+        assert_eq!(decode(b"\x7fELF\x02\x01\x01\x00\x00"), Decoded::Binary);
+
+        // Turkish in Windows-1254: "Kullanıcı şifresi değiştirildi".
+        let (bytes, _, _) = encoding_rs::WINDOWS_1254.encode("Kullanıcı şifresi değiştirildi, lütfen tekrar giriş yapın.");
+        match decode(&bytes) {
+            Decoded::Text { text, encoding } => {
+                assert!(text.contains("şifresi değiştirildi"), "{}", text);
+                assert!(encoding.is_some());
+            }
+            Decoded::Binary => panic!("decoded as binary"),
+        }
+    }
+
+    #[test]
+    fn test_hexdump() {
+        let dump = hexdump(b"\x7fELF\x02\x01\x01\x00\x00\x00\x00\x00\x00\x00\x00\x00abc");
+        assert_eq!(
+            dump,
+            "00000000  7f 45 4c 46 02 01 01 00  00 00 00 00 00 00 00 00  |.ELF............|\n\
+             00000010  61 62 63                                          |abc|\n"
+        );
+    }
+}
@@ -0,0 +1,426 @@
+//! Names that files mention: people, projects and tools, picked out of
+//! prose with a few patterns rather than a model. One row per entity and
+//! file, so files sharing an entity and entities sharing files are both a
+//! filter away.
+
+use std::collections::HashMap;
+use std::sync::{Arc, LazyLock};
+
+use anyhow::Result;
+use arrow_array::{Int64Array, RecordBatch, RecordBatchIterator, StringArray};
+use arrow_schema::{DataType, Field, Schema};
+use futures::TryStreamExt;
+use lancedb::connection::Connection;
+use lancedb::query::{ExecutableQuery, QueryBase};
+use lancedb::Table;
+use log::info;
+use regex::Regex;
+use serde::Serialize;
+
+use super::chunking::{is_code_ext, STOP_WORDS};
+use super::dedup::in_filters;
+
+/// Entities kept per file, the most mentioned ones.
+const MAX_PER_FILE: usize = 40;
+const MAX_NAME_LEN: usize = 64;
+/// Entities listed as seen alongside another.
+const MAX_RELATED: usize = 10;
+
+/// `owner/repo` of a link to a code host.
+static REPO: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)\b(?:github\.com|gitlab\.com|codeberg\.org|bitbucket\.org)/([a-z0-9][\w.-]*/[a-z0-9][\w.-]*)").unwrap()
+});
+/// `@handle` with a space or bracket before it, so emails don't count.
+static HANDLE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?:^|[\s(\[,;])@([A-Za-z][A-Za-z0-9_-]{1,38})\b").unwrap());
+/// Two or three capitalized words in a row: "Ada Lovelace".
+static FULL_NAME: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\b\p{Lu}\p{Ll}+(?: \p{Lu}\p{Ll}+){1,2}\b").unwrap());
+/// A capital inside a word: "GitHub", "PostgreSQL", "macOS".
+static MIXED_CASE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\b(?:\p{Lu}\p{Ll}+|\p{Ll}{1,3})\p{Lu}[\p{L}\d]*\b").unwrap());
+
+/// Capitalized words that come before names without being part of them.
+const NOT_NAMES: &[&str] = &[
+    "january", "february", "march", "april", "june", "july", "august", "september", "october",
+    "november", "december", "monday", "tuesday", "wednesday", "thursday", "friday", "saturday",
+    "sunday", "dear", "hello", "thanks", "best", "regards", "see", "note", "new", "next", "last",
+];
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum EntityKind {
+    Person,
+    Project,
+    Tool,
+}
+
+impl EntityKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Person => "person",
+            Self::Project => "project",
+            Self::Tool => "tool",
+        }
+    }
+
+    fn parse(s: &str) -> Self {
+        match s {
+            "person" => Self::Person,
+            "project" => Self::Project,
+            _ => Self::Tool,
+        }
+    }
+}
+
+/// An entity a file mentions, with how many indexed files mention it.
+#[derive(Serialize, Clone, Debug)]
+pub struct Entity {
+    pub name: String,
+    pub kind: EntityKind,
+    pub mentions: usize,
+    pub files: usize,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct FileMention {
+    pub path: String,
+    pub mentions: usize,
+}
+
+/// Another entity seen in files that mention the one looked up.
+#[derive(Serialize, Clone, Debug)]
+pub struct Related {
+    pub name: String,
+    pub kind: EntityKind,
+    pub shared_files: usize,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct EntityFiles {
+    pub name: String,
+    pub kind: EntityKind,
+    /// Most mentions first.
+    pub files: Vec<FileMention>,
+    pub related: Vec<Related>,
+}
+
+pub fn entities_table_name(container_table: &str) -> String {
+    format!("{}_entities", container_table)
+}
+
+fn make_schema() -> Schema {
+    Schema::new(vec![
+        Field::new("entity", DataType::Utf8, false),
+        // Lowercased, for looking an entity up however it's typed.
+        Field::new("key", DataType::Utf8, false),
+        Field::new("kind", DataType::Utf8, false),
+        Field::new("path", DataType::Utf8, false),
+        Field::new("mentions", DataType::Int64, false),
+    ])
+}
+
+async fn open(db: &Connection, container_table: &str) -> Option<Table> {
+    db.open_table(entities_table_name(container_table)).execute().await.ok()
+}
+
+/// Whether `container_table` has had its entities picked out.
+pub async fn is_built(db: &Connection, container_table: &str) -> bool {
+    open(db, container_table).await.is_some()
+}
+
+type Mentions = HashMap<(String, EntityKind), usize>;
+
+fn is_filler(word: &str) -> bool {
+    let lower = word.to_lowercase();
+    STOP_WORDS.contains(&lower.as_str()) || NOT_NAMES.contains(&lower.as_str())
+}
+
+/// The full name in a run of capitalized words found in `line`, without
+/// the words ahead of it: "The", "Dear", or a sentence's first word when
+/// that leaves two.
+fn full_name(line: &str, m: regex::Match) -> Option<String> {
+    let mut words: Vec<&str> = m.as_str().split(' ').collect();
+    let fillers = words.iter().take_while(|w| is_filler(w)).count();
+    words.drain(..fillers);
+    let before = line[..m.start()].trim_end();
+    if fillers == 0 && words.len() > 2 && (before.is_empty() || before.ends_with(['.', '!', '?', ':'])) {
+        words.remove(0);
+    }
+    (words.len() >= 2 && !words.iter().any(|w| is_filler(w))).then(|| words.join(" "))
+}
+
+/// Adds the entities `text` mentions to `counts`. Markdown headings are
+/// skipped, being title case throughout.
+fn extract(text: &str, counts: &mut Mentions) {
+    let mut add = |name: &str, kind: EntityKind| {
+        if name.chars().count() <= MAX_NAME_LEN {
+            *counts.entry((name.to_string(), kind)).or_default() += 1;
+        }
+    };
+    for line in text.lines().filter(|l| !l.trim_start().starts_with('#')) {
+        for cap in REPO.captures_iter(line) {
+            add(cap[1].trim_end_matches('.').trim_end_matches(".git"), EntityKind::Project);
+        }
+        for cap in HANDLE.captures_iter(line) {
+            add(&format!("@{}", &cap[1]), EntityKind::Person);
+        }
+        for name in FULL_NAME.find_iter(line).filter_map(|m| full_name(line, m)) {
+            add(&name, EntityKind::Person);
+        }
+        for m in MIXED_CASE.find_iter(line).filter(|m| m.as_str().chars().count() >= 4) {
+            add(m.as_str(), EntityKind::Tool);
+        }
+    }
+}
+
+fn strings<'a>(batch: &'a RecordBatch, column: &str) -> Option<&'a StringArray> {
+    batch.column_by_name(column).and_then(|c| c.as_any().downcast_ref::<StringArray>())
+}
+
+fn int64s<'a>(batch: &'a RecordBatch, column: &str) -> Option<&'a Int64Array> {
+    batch.column_by_name(column).and_then(|c| c.as_any().downcast_ref::<Int64Array>())
+}
+
+fn is_code(path: &str) -> bool {
+    std::path::Path::new(path)
+        .extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| is_code_ext(&e.to_lowercase()))
+}
+
+/// Entities of every non-code file in `table` matching `filter`, or of all
+/// of them without one. Identifiers in source code would drown the rest.
+async fn scan(table: &Table, filter: Option<&str>) -> Result<HashMap<String, Mentions>> {
+    let mut query = table
+        .query()
+        .select(lancedb::query::Select::Columns(vec!["path".to_string(), "content".to_string()]));
+    if let Some(filter) = filter {
+        query = query.only_if(filter);
+    }
+    let mut stream = query.execute().await?;
+    let mut files: HashMap<String, Mentions> = HashMap::new();
+    while let Some(batch) = stream.try_next().await? {
+        let (Some(paths), Some(contents)) = (strings(&batch, "path"), strings(&batch, "content")) else {
+            continue;
+        };
+        for i in 0..batch.num_rows() {
+            let path = paths.value(i);
+            if !is_code(path) {
+                extract(super::snippet::body(contents.value(i)), files.entry(path.to_string()).or_default());
+            }
+        }
+    }
+    Ok(files)
+}
+
+fn make_batch(files: HashMap<String, Mentions>) -> Result<Option<RecordBatch>> {
+    let (mut names, mut keys, mut kinds, mut paths, mut mentions) = (vec![], vec![], vec![], vec![], vec![]);
+    for (path, counts) in files {
+        let mut counts: Vec<_> = counts.into_iter().collect();
+        counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        for ((name, kind), n) in counts.into_iter().take(MAX_PER_FILE) {
+            keys.push(name.to_lowercase());
+            names.push(name);
+            kinds.push(kind.as_str());
+            paths.push(path.clone());
+            mentions.push(n as i64);
+        }
+    }
+    if names.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(RecordBatch::try_new(
+        Arc::new(make_schema()),
+        vec![
+            Arc::new(StringArray::from(names)),
+            Arc::new(StringArray::from(keys)),
+            Arc::new(StringArray::from(kinds)),
+            Arc::new(StringArray::from(paths)),
+            Arc::new(Int64Array::from(mentions)),
+        ],
+    )?))
+}
+
+/// Picks the entities of `paths` out of their rows in `table` again.
+/// Without an entity table yet, every file is scanned, so containers
+/// indexed before it existed have it after their next index run.
+pub async fn update(db: &Connection, table: &Table, container_table: &str, paths: &[String]) -> Result<()> {
+    let Some(entity_table) = open(db, container_table).await else {
+        let batch = make_batch(scan(table, None).await?)?;
+        let rows = batch.as_ref().map_or(0, |b| b.num_rows());
+        let schema = Arc::new(make_schema());
+        db.create_table(
+            entities_table_name(container_table),
+            RecordBatchIterator::new(batch.into_iter().map(Ok), schema),
+        )
+        .execute()
+        .await?;
+        info!("Entities for '{}' created ({} mentions)", container_table, rows);
+        return Ok(());
+    };
+    for filter in in_filters("path", paths) {
+        entity_table.delete(&filter).await?;
+        if let Some(batch) = make_batch(scan(table, Some(&filter)).await?)? {
+            let schema = batch.schema();
+            entity_table.add(RecordBatchIterator::new(vec![Ok(batch)], schema)).execute().await?;
+        }
+    }
+    Ok(())
+}
+
+/// Drops the entities of files matching `filter` (an expression on `path`).
+pub async fn remove(db: &Connection, container_table: &str, filter: &str) -> Result<()> {
+    if let Some(entity_table) = open(db, container_table).await {
+        entity_table.delete(filter).await?;
+    }
+    Ok(())
+}
+
+async fn rows(entity_table: &Table, filter: &str) -> Result<Vec<RecordBatch>> {
+    Ok(entity_table.query().only_if(filter).execute().await?.try_collect().await?)
+}
+
+/// How many files mention each of `keys`.
+async fn file_counts(entity_table: &Table, keys: &[String]) -> Result<HashMap<String, usize>> {
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for filter in in_filters("key", keys) {
+        for batch in rows(entity_table, &filter).await? {
+            if let Some(col) = strings(&batch, "key") {
+                for key in col.iter().flatten() {
+                    *counts.entry(key.to_string()).or_default() += 1;
+                }
+            }
+        }
+    }
+    Ok(counts)
+}
+
+/// The entities `path` mentions, most mentioned first.
+pub async fn in_file(db: &Connection, container_table: &str, path: &str) -> Result<Vec<Entity>> {
+    let Some(entity_table) = open(db, container_table).await else {
+        return Ok(Vec::new());
+    };
+    let mut found: Vec<(String, String, EntityKind, usize)> = Vec::new();
+    for filter in in_filters("path", &[path.to_string()]) {
+        for batch in rows(&entity_table, &filter).await? {
+            let (Some(names), Some(keys), Some(kinds), Some(mentions)) =
+                (strings(&batch, "entity"), strings(&batch, "key"), strings(&batch, "kind"), int64s(&batch, "mentions"))
+            else {
+                continue;
+            };
+            for i in 0..batch.num_rows() {
+                found.push((
+                    names.value(i).to_string(),
+                    keys.value(i).to_string(),
+                    EntityKind::parse(kinds.value(i)),
+                    mentions.value(i) as usize,
+                ));
+            }
+        }
+    }
+    let keys: Vec<String> = found.iter().map(|(_, key, _, _)| key.clone()).collect();
+    let files = file_counts(&entity_table, &keys).await?;
+    let mut entities: Vec<Entity> = found
+        .into_iter()
+        .map(|(name, key, kind, mentions)| Entity { files: files.get(&key).copied().unwrap_or(1), name, kind, mentions })
+        .collect();
+    entities.sort_by(|a, b| b.mentions.cmp(&a.mentions).then_with(|| a.name.cmp(&b.name)));
+    Ok(entities)
+}
+
+/// Files mentioning `name` (any case) and the entities most often seen in
+/// them too, or None when no file does.
+pub async fn files_with(db: &Connection, container_table: &str, name: &str, limit: usize) -> Result<Option<EntityFiles>> {
+    let Some(entity_table) = open(db, container_table).await else {
+        return Ok(None);
+    };
+    let key = name.to_lowercase();
+    let mut found: Option<(String, EntityKind)> = None;
+    let mut files: Vec<FileMention> = Vec::new();
+    for filter in in_filters("key", std::slice::from_ref(&key)) {
+        for batch in rows(&entity_table, &filter).await? {
+            let (Some(names), Some(kinds), Some(paths), Some(mentions)) =
+                (strings(&batch, "entity"), strings(&batch, "kind"), strings(&batch, "path"), int64s(&batch, "mentions"))
+            else {
+                continue;
+            };
+            for i in 0..batch.num_rows() {
+                found.get_or_insert_with(|| (names.value(i).to_string(), EntityKind::parse(kinds.value(i))));
+                files.push(FileMention { path: paths.value(i).to_string(), mentions: mentions.value(i) as usize });
+            }
+        }
+    }
+    let Some((name, kind)) = found else {
+        return Ok(None);
+    };
+    files.sort_by(|a, b| b.mentions.cmp(&a.mentions).then_with(|| a.path.cmp(&b.path)));
+
+    let paths: Vec<String> = files.iter().map(|f| f.path.clone()).collect();
+    let mut shared: HashMap<String, (String, EntityKind, usize)> = HashMap::new();
+    for filter in in_filters("path", &paths) {
+        for batch in rows(&entity_table, &filter).await? {
+            let (Some(names), Some(keys), Some(kinds)) = (strings(&batch, "entity"), strings(&batch, "key"), strings(&batch, "kind")) else {
+                continue;
+            };
+            for i in (0..batch.num_rows()).filter(|&i| keys.value(i) != key) {
+                shared
+                    .entry(keys.value(i).to_string())
+                    .or_insert_with(|| (names.value(i).to_string(), EntityKind::parse(kinds.value(i)), 0))
+                    .2 += 1;
+            }
+        }
+    }
+    let mut related: Vec<Related> = shared
+        .into_values()
+        .map(|(name, kind, shared_files)| Related { name, kind, shared_files })
+        .collect();
+    related.sort_by(|a, b| b.shared_files.cmp(&a.shared_files).then_with(|| a.name.cmp(&b.name)));
+    related.truncate(MAX_RELATED);
+
+    files.truncate(limit);
+    Ok(Some(EntityFiles { name, kind, files, related }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entities(text: &str) -> Mentions {
+        let mut counts = Mentions::new();
+        extract(text, &mut counts);
+        counts
+    }
+
+    #[test]
+    fn test_extract() {
+        let text = "Met Ada Lovelace about the PostgreSQL migration, cc @grace_h.\n\
+                    See https://github.com/lancedb/lancedb.git and mail bob@example.com.\n\
+                    # Getting Started Guide\n\
+                    Ada Lovelace runs it on macOS. Next Monday then.";
+        let found = entities(text);
+        let count = |name: &str, kind| found.get(&(name.to_string(), kind)).copied();
+        assert_eq!(count("Ada Lovelace", EntityKind::Person), Some(2));
+        assert_eq!(count("@grace_h", EntityKind::Person), Some(1));
+        assert_eq!(count("lancedb/lancedb", EntityKind::Project), Some(1));
+        assert_eq!(count("PostgreSQL", EntityKind::Tool), Some(1));
+        assert_eq!(count("macOS", EntityKind::Tool), Some(1));
+        assert_eq!(found.len(), 5, "{:?}", found);
+        assert_eq!(count("Grace Hopper", EntityKind::Person), None);
+        let found = entities("Dear Grace Hopper, hello.");
+        assert_eq!(found.keys().map(|(name, _)| name.as_str()).collect::<Vec<_>>(), ["Grace Hopper"]);
+    }
+
+    #[test]
+    fn test_make_batch() {
+        let mut counts = Mentions::new();
+        for i in 0..MAX_PER_FILE + 5 {
+            counts.insert((format!("Tool{}X", i), EntityKind::Tool), i + 1);
+        }
+        let batch = make_batch(HashMap::from([("notes.md".to_string(), counts)])).unwrap().unwrap();
+        assert_eq!(batch.num_rows(), MAX_PER_FILE);
+        assert_eq!(strings(&batch, "key").unwrap().value(0), format!("tool{}x", MAX_PER_FILE + 4));
+        assert_eq!(strings(&batch, "kind").unwrap().value(0), "tool");
+        assert!(make_batch(HashMap::new()).unwrap().is_none());
+        assert!(is_code("src/main.rs") && !is_code("notes/Plan.MD"));
+    }
+}
@@ -0,0 +1,195 @@
+use std::path::Path;
+
+use serde::{Deserialize, Deserializer, Serialize};
+
+/// One query with the file(s) a good search should turn up for it.
+#[derive(Debug, Clone, Deserialize)]
+pub struct EvalCase {
+    pub query: String,
+    /// Absolute paths, or trailing parts of them like `src/config.rs`.
+    #[serde(alias = "path", deserialize_with = "one_or_many")]
+    pub expected: Vec<String>,
+}
+
+fn one_or_many<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<String>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany {
+        One(String),
+        Many(Vec<String>),
+    }
+    Ok(match OneOrMany::deserialize(deserializer)? {
+        OneOrMany::One(path) => vec![path],
+        OneOrMany::Many(paths) => paths,
+    })
+}
+
+/// Reads cases from a JSON array or a JSON Lines file, one case per line.
+pub fn load_cases(path: &Path) -> Result<Vec<EvalCase>, String> {
+    let text = std::fs::read_to_string(path).map_err(|e| format!("Can't read {}: {}", path.display(), e))?;
+    parse_cases(&text).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+pub fn parse_cases(text: &str) -> Result<Vec<EvalCase>, String> {
+    let cases: Vec<EvalCase> = if text.trim_start().starts_with('[') {
+        serde_json::from_str(text).map_err(|e| e.to_string())?
+    } else {
+        text.lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(i, line)| serde_json::from_str(line).map_err(|e| format!("line {}: {}", i + 1, e)))
+            .collect::<Result<_, _>>()?
+    };
+    if cases.is_empty() {
+        return Err("no cases".to_string());
+    }
+    if let Some(case) = cases.iter().find(|c| c.expected.is_empty()) {
+        return Err(format!("\"{}\" has no expected path", case.query));
+    }
+    Ok(cases)
+}
+
+fn normalize(path: &str) -> String {
+    path.replace('\\', "/").trim_end_matches('/').to_lowercase()
+}
+
+/// A result counts as the expected file when it is that path or ends with it
+/// at a path boundary, so cases can use paths relative to the indexed folder.
+pub fn path_matches(result: &str, expected: &str) -> bool {
+    let (result, expected) = (normalize(result), normalize(expected));
+    let expected = expected.trim_start_matches("./");
+    result == expected || result.ends_with(&format!("/{}", expected))
+}
+
+#[derive(Debug, Serialize)]
+pub struct CaseResult {
+    pub query: String,
+    /// 1-based rank of the first expected file, None when it's not in the top k.
+    pub rank: Option<usize>,
+    /// Share of the expected files found in the top k.
+    pub recall: f64,
+    pub top: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct EvalReport {
+    pub k: usize,
+    pub cases: usize,
+    pub recall_at_k: f64,
+    pub mrr: f64,
+    /// Cases with at least one expected file in the top k.
+    pub hits: usize,
+    pub results: Vec<CaseResult>,
+}
+
+pub fn score_case(case: &EvalCase, results: &[String], k: usize) -> CaseResult {
+    let top = &results[..results.len().min(k)];
+    let rank = top
+        .iter()
+        .position(|path| case.expected.iter().any(|e| path_matches(path, e)))
+        .map(|i| i + 1);
+    let found = case
+        .expected
+        .iter()
+        .filter(|e| top.iter().any(|path| path_matches(path, e)))
+        .count();
+    CaseResult {
+        query: case.query.clone(),
+        rank,
+        recall: found as f64 / case.expected.len() as f64,
+        top: top.to_vec(),
+    }
+}
+
+impl EvalReport {
+    pub fn new(k: usize, results: Vec<CaseResult>) -> Self {
+        let n = results.len().max(1) as f64;
+        Self {
+            k,
+            cases: results.len(),
+            recall_at_k: results.iter().map(|r| r.recall).sum::<f64>() / n,
+            mrr: results.iter().filter_map(|r| r.rank).map(|rank| 1.0 / rank as f64).sum::<f64>() / n,
+            hits: results.iter().filter(|r| r.rank.is_some()).count(),
+            results,
+        }
+    }
+
+    /// Plain-text summary for the terminal: totals first, then the misses,
+    /// which are what's worth looking at.
+    pub fn summary(&self) -> String {
+        let mut out = format!(
+            "{} cases, recall@{} {:.3}, MRR {:.3}, {} hits\n",
+            self.cases, self.k, self.recall_at_k, self.mrr, self.hits
+        );
+        for r in &self.results {
+            match r.rank {
+                Some(rank) => out.push_str(&format!("  #{:<3} {}\n", rank, r.query)),
+                None => {
+                    out.push_str(&format!("  miss {}\n", r.query));
+                    if let Some(first) = r.top.first() {
+                        out.push_str(&format!("       got {}\n", first));
+                    }
+                }
+            }
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_cases() {
+        let jsonl = "{\"query\": \"load config\", \"expected\": \"src/config.rs\"}\n\n{\"query\": \"watcher\", \"path\": [\"a.rs\", \"b.rs\"]}\n";
+        let cases = parse_cases(jsonl).unwrap();
+        assert_eq!(cases.len(), 2);
+        assert_eq!(cases[0].expected, vec!["src/config.rs"]);
+        assert_eq!(cases[1].expected, vec!["a.rs", "b.rs"]);
+
+        let json = r#"[{"query": "q", "expected": ["x.md"]}]"#;
+        assert_eq!(parse_cases(json).unwrap().len(), 1);
+
+        assert!(parse_cases("").is_err());
+        assert!(parse_cases(r#"[{"query": "q", "expected": []}]"#).is_err());
+        assert!(parse_cases("{\"query\": \"q\"}").unwrap_err().starts_with("line 1"));
+    }
+
+    #[test]
+    fn test_path_matches() {
+        assert!(path_matches("C:\\repo\\src\\config.rs", "src/config.rs"));
+        assert!(path_matches("/home/u/repo/src/config.rs", "/home/u/repo/src/config.rs"));
+        assert!(path_matches("/repo/README.md", "./readme.md"));
+        assert!(!path_matches("/repo/src/myconfig.rs", "config.rs"));
+        assert!(!path_matches("/repo/src/config.rs", "lib/config.rs"));
+    }
+
+    #[test]
+    fn test_report() {
+        let cases = parse_cases(
+            "{\"query\": \"a\", \"expected\": \"a.rs\"}\n{\"query\": \"b\", \"expected\": [\"b.rs\", \"c.rs\"]}\n{\"query\": \"z\", \"expected\": \"z.rs\"}",
+        )
+        .unwrap();
+        let results = [
+            vec!["/r/a.rs".to_string(), "/r/x.rs".to_string()],
+            vec!["/r/x.rs".to_string(), "/r/b.rs".to_string(), "/r/c.rs".to_string()],
+            vec!["/r/x.rs".to_string(), "/r/z.rs".to_string()],
+        ];
+        let report = EvalReport::new(
+            2,
+            cases.iter().zip(&results).map(|(case, res)| score_case(case, res, 2)).collect(),
+        );
+        assert_eq!(report.cases, 3);
+        assert_eq!(report.hits, 3);
+        assert_eq!(report.results[1].rank, Some(2));
+        assert_eq!(report.results[1].recall, 0.5);
+        assert!((report.recall_at_k - (1.0 + 0.5 + 1.0) / 3.0).abs() < 1e-9);
+        assert!((report.mrr - (1.0 + 0.5 + 0.5) / 3.0).abs() < 1e-9);
+
+        let strict = score_case(&cases[2], &results[2], 1);
+        assert_eq!(strict.rank, None);
+        assert_eq!(strict.recall, 0.0);
+        assert!(EvalReport::new(1, vec![strict]).summary().contains("miss z"));
+    }
+}
@@ -0,0 +1,207 @@
+//! Extractors from config, for formats rememex can't read itself: an
+//! external command per file extension whose stdout is indexed as the
+//! file's text (`pandoc`, `pdftotext`, a script of your own), or a WASI
+//! plugin doing the same in a sandbox (see `wasm`).
+
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+
+use super::quarantine::{self, EXTRACT_TIMEOUT};
+use super::wasm;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Extractors by file extension, read from `indexing`. They come ahead of
+/// the built-in readers, a plugin ahead of a command for the same
+/// extension.
+#[derive(Serialize, Deserialize, Clone, Default, Debug)]
+pub struct Extractors {
+    /// Extension to external command.
+    #[serde(default, rename = "extractors")]
+    pub commands: HashMap<String, String>,
+    /// Extension to `.wasm` plugin, absolute or in `plugins/`.
+    #[serde(default, rename = "wasm_extractors")]
+    pub wasm: HashMap<String, String>,
+}
+
+impl Extractors {
+    /// Whether files with extension `ext` go to an extractor.
+    pub fn handles(&self, ext: &str) -> bool {
+        find(&self.wasm, ext).is_some() || find(&self.commands, ext).is_some()
+    }
+
+    /// Text of `path` from the extractor for `ext`; None when it fails or
+    /// there isn't one.
+    pub fn extract(&self, ext: &str, path: &Path) -> Option<String> {
+        if let Some(plugin) = find(&self.wasm, ext) {
+            return wasm::extract(plugin, path);
+        }
+        extract(find(&self.commands, ext)?, path)
+    }
+}
+
+/// The entry for `ext` in an extension map. Keys match with or without the
+/// dot and in any case; an empty value is no entry.
+fn find<'a>(map: &'a HashMap<String, String>, ext: &str) -> Option<&'a str> {
+    map.iter()
+        .find(|(key, _)| key.trim_start_matches('.').eq_ignore_ascii_case(ext))
+        .map(|(_, value)| value.as_str())
+        .filter(|value| !value.trim().is_empty())
+}
+
+/// Splits `command` into program and arguments, double quotes keeping
+/// spaces together, and puts `path` in place of `{path}`, or last when
+/// there's no `{path}`. No shell is involved, so the path needs no quoting.
+fn command_args(command: &str, path: &str) -> Vec<String> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+    let mut in_arg = false;
+    for c in command.chars() {
+        match c {
+            '"' => {
+                quoted = !quoted;
+                in_arg = true;
+            }
+            c if c.is_whitespace() && !quoted => {
+                if in_arg {
+                    args.push(std::mem::take(&mut current));
+                    in_arg = false;
+                }
+            }
+            c => {
+                current.push(c);
+                in_arg = true;
+            }
+        }
+    }
+    if in_arg {
+        args.push(current);
+    }
+
+    if args.iter().any(|arg| arg.contains("{path}")) {
+        args.iter().map(|arg| arg.replace("{path}", path)).collect()
+    } else {
+        args.push(path.to_string());
+        args
+    }
+}
+
+pub(crate) fn spawn(args: &[String]) -> std::io::Result<std::process::Child> {
+    let mut cmd = Command::new(&args[0]);
+    cmd.args(&args[1..]).stdin(Stdio::null()).stdout(Stdio::piped()).stderr(Stdio::piped());
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+        cmd.creation_flags(CREATE_NO_WINDOW);
+    }
+    cmd.spawn()
+}
+
+pub(crate) fn read_all(pipe: Option<impl Read + Send + 'static>) -> std::thread::JoinHandle<Vec<u8>> {
+    std::thread::spawn(move || {
+        let mut out = Vec::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut out);
+        }
+        out
+    })
+}
+
+/// Runs `command` on `path` and returns what it printed. A command that
+/// fails gives nothing; one that runs past `EXTRACT_TIMEOUT` is killed and
+/// the file goes on the skip list like a PDF that hangs the parser.
+fn extract(command: &str, path: &Path) -> Option<String> {
+    let path_str = path.to_string_lossy().to_string();
+    let mtime = super::file_io::get_file_mtime(path);
+    if quarantine::is_skipped(&path_str, mtime) {
+        debug!("{} is on the skip list", path_str);
+        return None;
+    }
+    let args = command_args(command, &path_str);
+    let mut child = match spawn(&args) {
+        Ok(child) => child,
+        Err(e) => {
+            warn!("Failed to run extractor '{}': {}", args[0], e);
+            return None;
+        }
+    };
+
+    // Read on the side so a chatty command can't fill a pipe and stall.
+    let stdout = read_all(child.stdout.take());
+    let stderr = read_all(child.stderr.take());
+    let started = Instant::now();
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) if started.elapsed() < EXTRACT_TIMEOUT => std::thread::sleep(POLL_INTERVAL),
+            Ok(None) => {
+                let _ = child.kill();
+                let _ = child.wait();
+                quarantine::skip(&path_str, mtime, &format!("'{}' took over {}s", args[0], EXTRACT_TIMEOUT.as_secs()));
+                return None;
+            }
+            Err(e) => {
+                warn!("Extractor '{}' on {}: {}", args[0], path_str, e);
+                return None;
+            }
+        }
+    };
+    let out = stdout.join().unwrap_or_default();
+
+    if !status.success() {
+        let err = stderr.join().unwrap_or_default();
+        warn!("Extractor '{}' failed on {} ({}): {}", args[0], path_str, status, String::from_utf8_lossy(&err).trim());
+        return None;
+    }
+    Some(String::from_utf8_lossy(&out).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_command_args() {
+        assert_eq!(command_args("pandoc -t plain", "/n/a b.docx"), ["pandoc", "-t", "plain", "/n/a b.docx"]);
+        assert_eq!(
+            command_args("pdftotext -layout {path} -", "/n/x.pdf"),
+            ["pdftotext", "-layout", "/n/x.pdf", "-"]
+        );
+        assert_eq!(
+            command_args(r#""C:\Program Files\tool.exe"  --in={path}"#, "C:\\d.odt"),
+            [r"C:\Program Files\tool.exe", "--in=C:\\d.odt"]
+        );
+        assert_eq!(command_args(r#"tool "" x"#, "p"), ["tool", "", "x", "p"]);
+    }
+
+    #[test]
+    fn test_find() {
+        let extractors = HashMap::from([
+            (".DOCX".to_string(), "pandoc -t plain".to_string()),
+            ("epub".to_string(), " ".to_string()),
+        ]);
+        assert_eq!(find(&extractors, "docx"), Some("pandoc -t plain"));
+        assert_eq!(find(&extractors, "epub"), None);
+        assert_eq!(find(&extractors, "odt"), None);
+    }
+
+    #[test]
+    fn test_extractors_config() {
+        let config: Extractors = serde_json::from_str(
+            r#"{ "extractors": { "docx": "pandoc -t plain" }, "wasm_extractors": { "epub": "epub2txt.wasm" } }"#,
+        )
+        .unwrap();
+        assert!(config.handles("docx"));
+        assert!(config.handles("epub"));
+        assert!(!config.handles("odt"));
+        assert!(!serde_json::from_str::<Extractors>("{}").unwrap().handles("docx"));
+    }
+}
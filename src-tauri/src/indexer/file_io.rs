@@ -140,7 +140,7 @@ pub fn is_text_extension_with_config(ext: &str, config: &IndexingConfig) -> bool
     if config.excluded_extensions.iter().any(|e| e == ext) {
         return false;
     }
-    if is_text_extension(ext) {
+    if is_text_extension(ext) || config.extractors.handles(ext) {
         return true;
     }
     config.extra_extensions.iter().any(|e| e == ext)
@@ -148,6 +148,17 @@ pub fn is_text_extension_with_config(ext: &str, config: &IndexingConfig) -> bool
 
 const MAX_FILE_SIZE: u64 = 10 * 1024 * 1024;
 
+/// Pages joined with `PAGE_BREAK` so chunking can keep them apart. A PDF
+/// the parser chokes on goes on the skip list instead of taking the app
+/// down with it.
+fn read_pdf(path: &Path) -> Option<String> {
+    let owned = path.to_path_buf();
+    super::quarantine::guarded(path, move || {
+        let pages = pdf_extract::extract_text_by_pages(&owned).ok()?;
+        Some(pages.join(&super::chunking::PAGE_BREAK.to_string()))
+    })
+}
+
 pub fn read_file_content(path: &Path) -> Option<String> {
     if let Ok(meta) = fs::metadata(path) {
         if meta.len() > MAX_FILE_SIZE {
@@ -175,7 +186,7 @@ pub fn read_file_content(path: &Path) -> Option<String> {
     if is_text_extension(&ext) || is_dotfile {
         fs::read_to_string(path).ok()
     } else if ext == "pdf" {
-        pdf_extract::extract_text(path).ok()
+        read_pdf(path)
     } else {
         None
     }
@@ -208,11 +219,14 @@ pub fn read_file_content_with_config(path: &Path, config: &IndexingConfig) -> Op
     if config.excluded_extensions.iter().any(|e| e == &ext) {
         return None;
     }
+    if config.extractors.handles(&ext) {
+        return config.extractors.extract(&ext, path);
+    }
 
     if is_text_extension_with_config(&ext, config) || is_dotfile {
         fs::read_to_string(path).ok()
     } else if ext == "pdf" {
-        pdf_extract::extract_text(path).ok()
+        read_pdf(path)
     } else {
         None
     }
@@ -226,12 +240,18 @@ pub async fn read_file_content_with_ocr(path: &Path) -> Option<String> {
         .to_lowercase();
 
     if super::ocr::is_image_extension(&ext) {
-        super::ocr::extract_text_from_image(path).await.ok()
+        let runtime = tokio::runtime::Handle::current();
+        let owned = path.to_path_buf();
+        super::quarantine::guarded_async(path, move || runtime.block_on(super::ocr::extract_text_from_image(&owned)).ok()).await
     } else {
         read_file_content(path)
     }
 }
 
+pub fn get_file_size(path: &Path) -> i64 {
+    fs::metadata(path).map(|m| m.len() as i64).unwrap_or(0)
+}
+
 pub fn get_file_mtime(path: &Path) -> i64 {
     fs::metadata(path)
         .and_then(|m| m.modified())
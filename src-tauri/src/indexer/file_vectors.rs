@@ -0,0 +1,257 @@
+//! One vector per file, the mean of its chunks, for a coarse pass over a big
+//! container: the files closest to the query are picked here first and the
+//! chunk search only looks at theirs.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use anyhow::Result;
+use arrow_array::{FixedSizeListArray, Float32Array, Int64Array, RecordBatch, RecordBatchIterator, StringArray};
+use arrow_schema::{DataType, Field, Schema};
+use futures::TryStreamExt;
+use lancedb::connection::Connection;
+use lancedb::query::{ExecutableQuery, QueryBase};
+use lancedb::{DistanceType, Table};
+use log::{debug, info};
+
+use super::dedup::in_filters;
+
+/// Chunks a container needs before searches take the coarse pass. Below
+/// this a flat chunk search is cheap enough, and exact.
+pub const COARSE_SEARCH_THRESHOLD: usize = 1_000_000;
+/// Fewest files the coarse pass keeps, however few results were asked for.
+const MIN_SHORTLIST: usize = 200;
+
+pub fn file_vectors_table_name(container_table: &str) -> String {
+    format!("{}_file_vectors", container_table)
+}
+
+fn make_schema(dim: usize) -> Schema {
+    Schema::new(vec![
+        Field::new("path", DataType::Utf8, false),
+        Field::new("chunks", DataType::Int64, false),
+        Field::new(
+            "vector",
+            DataType::FixedSizeList(Arc::new(Field::new("item", DataType::Float32, true)), dim as i32),
+            false,
+        ),
+    ])
+}
+
+async fn open(db: &Connection, container_table: &str) -> Option<Table> {
+    db.open_table(file_vectors_table_name(container_table)).execute().await.ok()
+}
+
+/// Running sum and chunk count per file.
+type Sums = HashMap<String, (Vec<f32>, usize)>;
+
+fn accumulate(sums: &mut Sums, path: &str, vector: &[f32]) {
+    match sums.get_mut(path) {
+        Some((sum, count)) => {
+            for (a, v) in sum.iter_mut().zip(vector) {
+                *a += v;
+            }
+            *count += 1;
+        }
+        None => {
+            sums.insert(path.to_string(), (vector.to_vec(), 1));
+        }
+    }
+}
+
+fn means_batch(sums: Sums) -> Result<Option<RecordBatch>> {
+    let Some(dim) = sums.values().next().map(|(sum, _)| sum.len()) else {
+        return Ok(None);
+    };
+    let mut paths = Vec::with_capacity(sums.len());
+    let mut counts = Vec::with_capacity(sums.len());
+    let mut flat = Vec::with_capacity(sums.len() * dim);
+    for (path, (sum, count)) in sums {
+        paths.push(path);
+        counts.push(count as i64);
+        flat.extend(sum.into_iter().map(|v| v / count as f32));
+    }
+    let vectors = FixedSizeListArray::try_new(
+        Arc::new(Field::new("item", DataType::Float32, true)),
+        dim as i32,
+        Arc::new(Float32Array::from(flat)),
+        None,
+    )?;
+    Ok(Some(RecordBatch::try_new(
+        Arc::new(make_schema(dim)),
+        vec![Arc::new(StringArray::from(paths)), Arc::new(Int64Array::from(counts)), Arc::new(vectors)],
+    )?))
+}
+
+/// Mean chunk vector of every file in `table` matching `filter`, or of all
+/// of them without one. Streams the rows, so only the sums are held.
+async fn means(table: &Table, filter: Option<&str>) -> Result<Option<RecordBatch>> {
+    let mut query = table
+        .query()
+        .select(lancedb::query::Select::Columns(vec!["path".to_string(), "vector".to_string()]));
+    if let Some(filter) = filter {
+        query = query.only_if(filter);
+    }
+    let mut stream = query.execute().await?;
+    let mut sums = Sums::new();
+    while let Some(batch) = stream.try_next().await? {
+        let paths = batch.column_by_name("path").and_then(|c| c.as_any().downcast_ref::<StringArray>());
+        let vectors = batch.column_by_name("vector").and_then(|c| c.as_any().downcast_ref::<FixedSizeListArray>());
+        let (Some(paths), Some(vectors)) = (paths, vectors) else {
+            continue;
+        };
+        for i in 0..batch.num_rows() {
+            let values = vectors.value(i);
+            if let Some(floats) = values.as_any().downcast_ref::<Float32Array>() {
+                accumulate(&mut sums, paths.value(i), floats.values());
+            }
+        }
+    }
+    means_batch(sums)
+}
+
+fn push_rows(batch: &RecordBatch, out: &mut Vec<(String, Vec<f32>)>) {
+    let paths = batch.column_by_name("path").and_then(|c| c.as_any().downcast_ref::<StringArray>());
+    let vectors = batch.column_by_name("vector").and_then(|c| c.as_any().downcast_ref::<FixedSizeListArray>());
+    let (Some(paths), Some(vectors)) = (paths, vectors) else {
+        return;
+    };
+    for i in 0..batch.num_rows() {
+        let values = vectors.value(i);
+        if let Some(floats) = values.as_any().downcast_ref::<Float32Array>() {
+            out.push((paths.value(i).to_string(), floats.values().to_vec()));
+        }
+    }
+}
+
+pub(super) async fn all(db: &Connection, table: &Table, container_table: &str) -> Result<Vec<(String, Vec<f32>)>> {
+    let mut out = Vec::new();
+    let Some(file_table) = open(db, container_table).await else {
+        if let Some(batch) = means(table, None).await? {
+            push_rows(&batch, &mut out);
+        }
+        return Ok(out);
+    };
+    let mut stream = file_table
+        .query()
+        .select(lancedb::query::Select::Columns(vec!["path".to_string(), "vector".to_string()]))
+        .execute()
+        .await?;
+    while let Some(batch) = stream.try_next().await? {
+        push_rows(&batch, &mut out);
+    }
+    Ok(out)
+}
+
+/// Recomputes the vectors of `paths` from their rows in `table`. Without a
+/// file table yet, every file gets one, so containers indexed before it
+/// existed have it after their next index run.
+pub async fn update(db: &Connection, table: &Table, container_table: &str, paths: &[String]) -> Result<()> {
+    let Some(file_table) = open(db, container_table).await else {
+        let Some(batch) = means(table, None).await? else {
+            return Ok(());
+        };
+        let (rows, schema) = (batch.num_rows(), batch.schema());
+        db.create_table(file_vectors_table_name(container_table), RecordBatchIterator::new(vec![Ok(batch)], schema))
+            .execute()
+            .await?;
+        info!("File vectors for '{}' created ({} files)", container_table, rows);
+        return Ok(());
+    };
+    for filter in in_filters("path", paths) {
+        file_table.delete(&filter).await?;
+        if let Some(batch) = means(table, Some(&filter)).await? {
+            let schema = batch.schema();
+            file_table.add(RecordBatchIterator::new(vec![Ok(batch)], schema)).execute().await?;
+        }
+    }
+    Ok(())
+}
+
+/// Drops the vectors of files matching `filter` (an expression on `path`).
+pub async fn remove(db: &Connection, container_table: &str, filter: &str) -> Result<()> {
+    if let Some(file_table) = open(db, container_table).await {
+        file_table.delete(filter).await?;
+    }
+    Ok(())
+}
+
+/// How many files the coarse pass keeps for a search after `limit` results.
+pub fn shortlist_size(limit: usize) -> usize {
+    (limit * 10).max(MIN_SHORTLIST)
+}
+
+/// A filter matching any of `paths`.
+pub fn paths_filter(paths: &[String]) -> Option<String> {
+    let filters = in_filters("path", paths);
+    match filters.len() {
+        0 => None,
+        1 => filters.into_iter().next(),
+        _ => Some(filters.iter().map(|f| format!("({})", f)).collect::<Vec<_>>().join(" OR ")),
+    }
+}
+
+/// The `size` files whose mean vector is closest to `query_vector`, or None
+/// when `container_table` has no file vectors for the query's model.
+pub async fn shortlist(db: &Connection, container_table: &str, query_vector: &[f32], size: usize) -> Option<Vec<String>> {
+    let file_table = open(db, container_table).await?;
+    let batches = file_table
+        .vector_search(query_vector)
+        .ok()?
+        .distance_type(DistanceType::Cosine)
+        .select(lancedb::query::Select::Columns(vec!["path".to_string()]))
+        .limit(size)
+        .execute()
+        .await
+        .ok()?
+        .try_collect::<Vec<_>>()
+        .await
+        .map_err(|e| debug!("File vector search failed: {}", e))
+        .ok()?;
+    let paths: Vec<String> = batches
+        .iter()
+        .filter_map(|b| b.column_by_name("path").and_then(|c| c.as_any().downcast_ref::<StringArray>()))
+        .flat_map(|col| col.iter().flatten().map(str::to_string))
+        .collect();
+    (!paths.is_empty()).then_some(paths)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_means_batch() {
+        let mut sums = Sums::new();
+        accumulate(&mut sums, "a.rs", &[1.0, 0.0]);
+        accumulate(&mut sums, "a.rs", &[0.0, 1.0]);
+        accumulate(&mut sums, "b.rs", &[2.0, 4.0]);
+        let batch = means_batch(sums).unwrap().unwrap();
+        assert_eq!(batch.num_rows(), 2);
+        let paths = batch.column_by_name("path").unwrap().as_any().downcast_ref::<StringArray>().unwrap();
+        let counts = batch.column_by_name("chunks").unwrap().as_any().downcast_ref::<Int64Array>().unwrap();
+        let vectors = batch.column_by_name("vector").unwrap().as_any().downcast_ref::<FixedSizeListArray>().unwrap();
+        for i in 0..2 {
+            let values = vectors.value(i);
+            let floats = values.as_any().downcast_ref::<Float32Array>().unwrap().values().to_vec();
+            match paths.value(i) {
+                "a.rs" => assert_eq!((counts.value(i), floats), (2, vec![0.5, 0.5])),
+                _ => assert_eq!((counts.value(i), floats), (1, vec![2.0, 4.0])),
+            }
+        }
+        assert!(means_batch(Sums::new()).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_paths_filter() {
+        assert_eq!(paths_filter(&[]), None);
+        assert_eq!(paths_filter(&["a.rs".to_string()]).unwrap(), "path IN ('a.rs')");
+        let many: Vec<String> = (0..1000).map(|i| format!("f{}", i)).collect();
+        let filter = paths_filter(&many).unwrap();
+        assert!(filter.starts_with("(path IN ('f0'"));
+        assert!(filter.contains(") OR (path IN ("));
+        assert!(filter.ends_with("'f999'))"));
+        assert_eq!(shortlist_size(5), MIN_SHORTLIST);
+        assert_eq!(shortlist_size(50), 500);
+    }
+}
@@ -0,0 +1,193 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::Result;
+use arrow_array::{Int64Array, RecordBatch, RecordBatchIterator, StringArray};
+use arrow_schema::{DataType, Field, Schema};
+use futures::TryStreamExt;
+use lancedb::connection::Connection;
+use lancedb::query::{ExecutableQuery, QueryBase};
+use lancedb::Table;
+use log::{debug, info};
+
+const DAY: i64 = 24 * 60 * 60;
+const RETENTION_SECS: i64 = 180 * DAY;
+
+pub struct RecentFile {
+    pub path: String,
+    pub snippet: String,
+    pub frecency: f32,
+}
+
+pub fn history_table_name(container_table: &str) -> String {
+    format!("{}_history", container_table)
+}
+
+fn make_history_schema() -> Schema {
+    Schema::new(vec![
+        Field::new("path", DataType::Utf8, false),
+        Field::new("snippet", DataType::Utf8, false),
+        Field::new("opened_at", DataType::Int64, false),
+    ])
+}
+
+fn now_unix() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs() as i64
+}
+
+/// Firefox-style frecency: every visit counts, but recent visits count for more.
+pub fn frecency_score(visits: &[i64], now: i64) -> f32 {
+    visits
+        .iter()
+        .map(|&opened_at| match (now - opened_at) / DAY {
+            0..=3 => 100.0,
+            4..=13 => 70.0,
+            14..=30 => 50.0,
+            31..=90 => 30.0,
+            _ => 10.0,
+        })
+        .sum()
+}
+
+async fn get_or_create_history_table(db: &Connection, container_table: &str) -> Result<Table> {
+    let table_name = history_table_name(container_table);
+    if let Ok(table) = db.open_table(&table_name).execute().await {
+        return Ok(table);
+    }
+
+    let schema = Arc::new(make_history_schema());
+    let table = db
+        .create_table(&table_name, RecordBatchIterator::new(vec![], schema))
+        .execute()
+        .await?;
+
+    info!("History table '{}' created", table_name);
+    Ok(table)
+}
+
+pub async fn record_open(
+    db: &Connection,
+    container_table: &str,
+    path: &str,
+    snippet: &str,
+) -> Result<()> {
+    let table = get_or_create_history_table(db, container_table).await?;
+    let now = now_unix();
+
+    let schema = Arc::new(make_history_schema());
+    let batch = RecordBatch::try_new(
+        schema.clone(),
+        vec![
+            Arc::new(StringArray::from(vec![path])),
+            Arc::new(StringArray::from(vec![snippet])),
+            Arc::new(Int64Array::from(vec![now])),
+        ],
+    )?;
+    table
+        .add(RecordBatchIterator::new(vec![Ok(batch)], schema))
+        .execute()
+        .await?;
+
+    table
+        .delete(&format!("opened_at < {}", now - RETENTION_SECS))
+        .await?;
+
+    debug!("History: recorded open of {}", path);
+    Ok(())
+}
+
+/// Returns up to `limit` previously opened files, best frecency first. Each entry
+/// carries the snippet from its most recent open.
+pub async fn get_recent(
+    db: &Connection,
+    container_table: &str,
+    limit: usize,
+) -> Result<Vec<RecentFile>> {
+    let table_name = history_table_name(container_table);
+    let table = match db.open_table(&table_name).execute().await {
+        Ok(t) => t,
+        Err(_) => return Ok(vec![]),
+    };
+
+    let results = table
+        .query()
+        .select(lancedb::query::Select::Columns(vec![
+            "path".to_string(),
+            "snippet".to_string(),
+            "opened_at".to_string(),
+        ]))
+        .execute()
+        .await?
+        .try_collect::<Vec<_>>()
+        .await?;
+
+    let mut visits: HashMap<String, (Vec<i64>, i64, String)> = HashMap::new();
+    for batch in results {
+        let path_arr = batch.column_by_name("path").and_then(|c| c.as_any().downcast_ref::<StringArray>());
+        let snippet_arr = batch.column_by_name("snippet").and_then(|c| c.as_any().downcast_ref::<StringArray>());
+        let ts_arr = batch.column_by_name("opened_at").and_then(|c| c.as_any().downcast_ref::<Int64Array>());
+
+        if let (Some(paths), Some(snippets), Some(timestamps)) = (path_arr, snippet_arr, ts_arr) {
+            for i in 0..batch.num_rows() {
+                let opened_at = timestamps.value(i);
+                let entry = visits
+                    .entry(paths.value(i).to_string())
+                    .or_insert_with(|| (Vec::new(), i64::MIN, String::new()));
+                entry.0.push(opened_at);
+                if opened_at >= entry.1 {
+                    entry.1 = opened_at;
+                    entry.2 = snippets.value(i).to_string();
+                }
+            }
+        }
+    }
+
+    let now = now_unix();
+    let mut recent: Vec<(RecentFile, i64)> = visits
+        .into_iter()
+        .map(|(path, (opens, last, snippet))| {
+            let frecency = frecency_score(&opens, now);
+            (RecentFile { path, snippet, frecency }, last)
+        })
+        .collect();
+    recent.sort_by(|a, b| {
+        b.0.frecency
+            .partial_cmp(&a.0.frecency)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then(b.1.cmp(&a.1))
+    });
+
+    Ok(recent.into_iter().take(limit).map(|(r, _)| r).collect())
+}
+
+pub async fn clear_history(db: &Connection, container_table: &str) -> Result<()> {
+    let _ = db.drop_table(&history_table_name(container_table), &[]).await;
+    info!("History cleared for '{}'", container_table);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frecency_prefers_recent_visits() {
+        let now = 1_000 * DAY;
+        let recent_once = frecency_score(&[now - DAY], now);
+        let old_once = frecency_score(&[now - 60 * DAY], now);
+        assert!(recent_once > old_once);
+    }
+
+    #[test]
+    fn test_frecency_accumulates_visits() {
+        let now = 1_000 * DAY;
+        let often = frecency_score(&[now - 20 * DAY, now - 21 * DAY, now - 22 * DAY], now);
+        let once = frecency_score(&[now - DAY], now);
+        assert!(often > once);
+        assert_eq!(frecency_score(&[], now), 0.0);
+    }
+}
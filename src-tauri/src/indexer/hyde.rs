@@ -6,7 +6,9 @@ use serde::{Deserialize, Serialize};
 pub struct HydeConfig {
     #[serde(default)]
     pub enabled: bool,
+    #[serde(default)]
     pub endpoint: String,
+    #[serde(default)]
     pub model: String,
     pub api_key: Option<String>,
 }
@@ -46,52 +48,57 @@ or document passage that would be a perfect search result for this query. \
 Write ONLY the code/text, no explanations. Keep it under 200 words. \
 Match the language if the query implies one.";
 
-pub async fn generate_hypothetical_document(
-    config: &HydeConfig,
-    query: &str,
+pub(crate) async fn chat(
+    endpoint: &str,
+    model: &str,
+    api_key: Option<&str>,
+    system: &str,
+    user: &str,
+    max_tokens: u32,
+    timeout: std::time::Duration,
 ) -> Result<String> {
     let client = reqwest::Client::new();
 
     let request = ChatRequest {
-        model: config.model.clone(),
+        model: model.to_string(),
         messages: vec![
             ChatMessage {
                 role: "system".to_string(),
-                content: SYSTEM_PROMPT.to_string(),
+                content: system.to_string(),
             },
             ChatMessage {
                 role: "user".to_string(),
-                content: query.to_string(),
+                content: user.to_string(),
             },
         ],
-        max_tokens: 300,
+        max_tokens,
         temperature: 0.3,
     };
 
-    let mut req = client.post(&config.endpoint).json(&request);
+    let mut req = client.post(endpoint).json(&request);
 
-    if let Some(ref key) = config.api_key {
+    if let Some(key) = api_key {
         if !key.is_empty() {
             req = req.bearer_auth(key);
         }
     }
 
     let response = req
-        .timeout(std::time::Duration::from_secs(10))
+        .timeout(timeout)
         .send()
         .await
-        .map_err(|e| anyhow!("HyDE LLM request failed: {}", e))?;
+        .map_err(|e| anyhow!("LLM request failed: {}", e))?;
 
     if !response.status().is_success() {
         let status = response.status();
         let body = response.text().await.unwrap_or_default();
-        return Err(anyhow!("HyDE LLM returned {}: {}", status, body));
+        return Err(anyhow!("LLM returned {}: {}", status, body));
     }
 
     let resp: ChatResponse = response
         .json()
         .await
-        .map_err(|e| anyhow!("HyDE: failed to parse LLM response: {}", e))?;
+        .map_err(|e| anyhow!("failed to parse LLM response: {}", e))?;
 
     let content = resp
         .choices
@@ -100,8 +107,26 @@ pub async fn generate_hypothetical_document(
         .unwrap_or_default();
 
     if content.trim().is_empty() {
-        return Err(anyhow!("HyDE: LLM returned empty response"));
+        return Err(anyhow!("LLM returned empty response"));
     }
+    Ok(content)
+}
+
+pub async fn generate_hypothetical_document(
+    config: &HydeConfig,
+    query: &str,
+) -> Result<String> {
+    let content = chat(
+        &config.endpoint,
+        &config.model,
+        config.api_key.as_deref(),
+        SYSTEM_PROMPT,
+        query,
+        300,
+        std::time::Duration::from_secs(10),
+    )
+    .await
+    .map_err(|e| anyhow!("HyDE: {}", e))?;
 
     debug!("HyDE generated {} chars for query: {}", content.len(), query);
     Ok(content)
@@ -116,6 +141,10 @@ pub async fn maybe_generate(
     if !config.enabled || !use_hyde {
         return None;
     }
+    if crate::config::is_offline() {
+        debug!("HyDE skipped in offline mode");
+        return None;
+    }
 
     match generate_hypothetical_document(config, query).await {
         Ok(doc) => Some(doc),
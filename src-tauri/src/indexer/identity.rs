@@ -0,0 +1,196 @@
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+/// Which of the paths leading to the same file is the one indexed and
+/// shown, when a file is reachable as `Notes/a.md` and `notes/A.md` on a
+/// case-insensitive disk, through a hardlink, or under two indexed folders.
+#[derive(Serialize, Deserialize, Clone, Copy, Default, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum DisplayPath {
+    /// The one indexed first; later ones are skipped.
+    #[default]
+    First,
+    /// The shortest, which tends to be the one under the outer folder.
+    Shortest,
+    /// The file's real path, links resolved and with the casing on disk,
+    /// when it's one of them.
+    Canonical,
+}
+
+/// What a file is, whatever path reached it: device and inode on Unix, the
+/// real path in lowercase elsewhere, since those disks ignore case. None
+/// when the file can't be read.
+pub fn file_id(path: &Path) -> Option<String> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        let meta = std::fs::metadata(path).ok()?;
+        Some(format!("{}:{}", meta.dev(), meta.ino()))
+    }
+    #[cfg(not(unix))]
+    {
+        Some(canonical(path)?.to_lowercase())
+    }
+}
+
+/// `path` resolved, without the `\\?\` prefix Windows puts on it.
+fn canonical(path: &Path) -> Option<String> {
+    let real = std::fs::canonicalize(path).ok()?.to_string_lossy().to_string();
+    Some(real.strip_prefix(r"\\?\").map(str::to_string).unwrap_or(real))
+}
+
+/// The path to use out of `paths`, which all lead to one file and come in
+/// the order they were found.
+pub fn pick(paths: &[String], preference: DisplayPath) -> String {
+    let first = || paths.first().cloned().unwrap_or_default();
+    match preference {
+        DisplayPath::First => first(),
+        DisplayPath::Shortest => paths.iter().min_by_key(|p| p.len()).cloned().unwrap_or_default(),
+        DisplayPath::Canonical => paths
+            .first()
+            .and_then(|p| canonical(Path::new(p)))
+            .and_then(|real| paths.iter().find(|p| **p == real).cloned())
+            .unwrap_or_else(first),
+    }
+}
+
+/// Groups `paths` by `id`, groups and the paths in them in the order they
+/// were found. Paths without an id stand alone.
+fn group_by_id<'a>(paths: impl IntoIterator<Item = &'a String>, id: impl Fn(&str) -> Option<String>) -> Vec<Vec<String>> {
+    let mut groups: Vec<Vec<String>> = Vec::new();
+    let mut by_id: HashMap<String, usize> = HashMap::new();
+    for path in paths {
+        match id(path) {
+            Some(id) => match by_id.get(&id) {
+                Some(&i) => groups[i].push(path.clone()),
+                None => {
+                    by_id.insert(id, groups.len());
+                    groups.push(vec![path.clone()]);
+                }
+            },
+            None => groups.push(vec![path.clone()]),
+        }
+    }
+    groups
+}
+
+/// One path per file out of a folder walk, the preferred one, in walk order.
+pub fn dedupe_files(files: Vec<PathBuf>, preference: DisplayPath) -> Vec<PathBuf> {
+    let paths: Vec<String> = files.iter().map(|p| p.to_string_lossy().to_string()).collect();
+    let groups = group_by_id(&paths, |p| file_id(Path::new(p)));
+    if groups.len() == paths.len() {
+        return files;
+    }
+    groups.iter().map(|group| PathBuf::from(pick(group, preference))).collect()
+}
+
+/// Files a walk found under a path the index doesn't have, that it has
+/// under another one. Returns the new paths to leave out, where the indexed
+/// one is preferred, and the indexed paths to drop, where the new one is.
+pub fn indexed_elsewhere<'a>(
+    new_paths: &[String],
+    indexed: impl IntoIterator<Item = &'a String>,
+    preference: DisplayPath,
+) -> (HashSet<String>, Vec<String>) {
+    let mut skip = HashSet::new();
+    let mut superseded = Vec::new();
+    if new_paths.is_empty() {
+        return (skip, superseded);
+    }
+    let indexed_ids: HashMap<String, &String> =
+        indexed.into_iter().filter_map(|p| Some((file_id(Path::new(p))?, p))).collect();
+    for new in new_paths {
+        let Some(old) = file_id(Path::new(new)).and_then(|id| indexed_ids.get(&id)) else {
+            continue;
+        };
+        if pick(&[(*old).clone(), new.clone()], preference) == *new {
+            superseded.push((*old).clone());
+        } else {
+            skip.insert(new.clone());
+        }
+    }
+    (skip, superseded)
+}
+
+/// Search results with every path of a file replaced by the preferred one
+/// and a chunk found under two paths kept once, at its best rank.
+pub fn dedupe_results(results: &mut Vec<(String, String, f32)>, preference: DisplayPath) {
+    dedupe_results_by(results, preference, |p| file_id(Path::new(p)));
+}
+
+fn dedupe_results_by(
+    results: &mut Vec<(String, String, f32)>,
+    preference: DisplayPath,
+    id: impl Fn(&str) -> Option<String>,
+) {
+    let mut distinct: Vec<String> = Vec::new();
+    for (path, _, _) in results.iter() {
+        if !distinct.contains(path) {
+            distinct.push(path.clone());
+        }
+    }
+    let groups = group_by_id(&distinct, id);
+    if groups.len() == distinct.len() {
+        return;
+    }
+    let display: HashMap<&String, String> = groups
+        .iter()
+        .flat_map(|group| {
+            let chosen = pick(group, preference);
+            group.iter().map(move |path| (path, chosen.clone()))
+        })
+        .collect();
+    let mut seen = HashSet::new();
+    let mut deduped = Vec::with_capacity(results.len());
+    for (path, snippet, score) in results.drain(..) {
+        let path = display.get(&path).cloned().unwrap_or(path);
+        if seen.insert((path.clone(), snippet.clone())) {
+            deduped.push((path, snippet, score));
+        }
+    }
+    *results = deduped;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn case_insensitive(path: &str) -> Option<String> {
+        Some(path.to_lowercase())
+    }
+
+    #[test]
+    fn test_pick() {
+        let paths = vec!["/home/u/notes/deep/a.md".to_string(), "/home/u/a.md".to_string()];
+        assert_eq!(pick(&paths, DisplayPath::First), "/home/u/notes/deep/a.md");
+        assert_eq!(pick(&paths, DisplayPath::Shortest), "/home/u/a.md");
+        // A path that doesn't resolve stays as it is.
+        assert_eq!(pick(&paths, DisplayPath::Canonical), "/home/u/notes/deep/a.md");
+    }
+
+    #[test]
+    fn test_dedupe_results() {
+        let mut results = vec![
+            ("C:/Notes/Plan.md".to_string(), "budget".to_string(), 0.1),
+            ("c:/notes/plan.md".to_string(), "budget".to_string(), 0.2),
+            ("c:/notes/plan.md".to_string(), "timeline".to_string(), 0.3),
+            ("C:/Other.md".to_string(), "budget".to_string(), 0.4),
+        ];
+        dedupe_results_by(&mut results, DisplayPath::First, case_insensitive);
+        assert_eq!(
+            results,
+            vec![
+                ("C:/Notes/Plan.md".to_string(), "budget".to_string(), 0.1),
+                ("C:/Notes/Plan.md".to_string(), "timeline".to_string(), 0.3),
+                ("C:/Other.md".to_string(), "budget".to_string(), 0.4),
+            ]
+        );
+
+        let mut unrelated = vec![("a.md".to_string(), "x".to_string(), 0.1), ("b.md".to_string(), "x".to_string(), 0.2)];
+        let before = unrelated.clone();
+        dedupe_results_by(&mut unrelated, DisplayPath::First, case_insensitive);
+        assert_eq!(unrelated, before);
+    }
+}
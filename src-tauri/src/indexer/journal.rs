@@ -0,0 +1,172 @@
+use std::path::PathBuf;
+use std::sync::LazyLock;
+
+use anyhow::Result;
+use lancedb::connection::Connection;
+use lancedb::Table;
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+
+use super::dedup;
+
+/// A journal started before this is from a run of the app that died mid-job.
+static SESSION_START: LazyLock<i64> = LazyLock::new(|| chrono::Utc::now().timestamp());
+
+/// What an indexing job planned and how far it got. A finished job leaves
+/// no journal behind.
+#[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq)]
+pub struct Journal {
+    pub folders: Vec<String>,
+    #[serde(default)]
+    pub folders_done: usize,
+    #[serde(default)]
+    pub started_at: i64,
+    #[serde(default)]
+    pub files: Vec<String>,
+    #[serde(default)]
+    pub files_done: usize,
+    /// Files the watcher changed in the live table while this rebuild ran.
+    #[serde(default)]
+    pub watched: Vec<String>,
+}
+
+impl Journal {
+    pub fn remaining_folders(&self) -> &[String] {
+        self.folders.get(self.folders_done..).unwrap_or_default()
+    }
+
+    pub fn is_interrupted(&self) -> bool {
+        self.is_stale() && !self.remaining_folders().is_empty()
+    }
+
+    pub fn is_stale(&self) -> bool {
+        self.started_at < *SESSION_START
+    }
+
+    pub fn partial_files(&self) -> &[String] {
+        self.files.get(self.files_done..).unwrap_or_default()
+    }
+}
+
+fn journal_path(table_name: &str) -> PathBuf {
+    crate::config::app_data_dir().join("journal").join(format!("{}.json", table_name))
+}
+
+pub fn load(table_name: &str) -> Option<Journal> {
+    std::fs::read_to_string(journal_path(table_name))
+        .ok()
+        .and_then(|json| serde_json::from_str(&json).ok())
+}
+
+fn save(table_name: &str, journal: &Journal) {
+    let path = journal_path(table_name);
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).ok();
+    }
+    match serde_json::to_string(journal) {
+        Ok(json) => {
+            if let Err(e) = std::fs::write(&path, json) {
+                warn!("Failed to write indexing journal {:?}: {}", path, e);
+            }
+        }
+        Err(e) => warn!("Failed to serialize indexing journal: {}", e),
+    }
+}
+
+/// Half-written files of an interrupted job carry over, so the new job
+/// still cleans them up.
+pub fn begin(table_name: &str, folders: &[String]) {
+    LazyLock::force(&SESSION_START);
+    let journal = Journal {
+        folders: folders.to_vec(),
+        started_at: chrono::Utc::now().timestamp(),
+        files: load(table_name).map(|old| old.partial_files().to_vec()).unwrap_or_default(),
+        ..Default::default()
+    };
+    save(table_name, &journal);
+}
+
+pub fn finish(table_name: &str) {
+    std::fs::remove_file(journal_path(table_name)).ok();
+}
+
+/// Does nothing outside a job, e.g. for files the watcher reindexes.
+fn update(table_name: &str, edit: impl FnOnce(&mut Journal)) {
+    if let Some(mut journal) = load(table_name) {
+        edit(&mut journal);
+        save(table_name, &journal);
+    }
+}
+
+pub fn plan_files(table_name: &str, files: &[String]) {
+    update(table_name, |journal| {
+        journal.files = files.to_vec();
+        journal.files_done = 0;
+    });
+}
+
+pub fn files_written(table_name: &str, count: usize) {
+    update(table_name, |journal| journal.files_done = count.min(journal.files.len()));
+}
+
+pub fn folder_done(table_name: &str) {
+    update(table_name, |journal| {
+        journal.folders_done = (journal.folders_done + 1).min(journal.folders.len());
+        journal.files.clear();
+        journal.files_done = 0;
+    });
+}
+
+pub fn note_watched(table_name: &str, paths: &[String]) {
+    update(table_name, |journal| {
+        for path in paths {
+            if !journal.watched.contains(path) {
+                journal.watched.push(path.clone());
+            }
+        }
+    });
+}
+
+/// Half-written files would otherwise be skipped as up to date.
+pub async fn drop_partial(db: &Connection, table: &Table, table_name: &str) -> Result<()> {
+    let Some(journal) = load(table_name) else {
+        return Ok(());
+    };
+    let partial = journal.partial_files();
+    if partial.is_empty() {
+        return Ok(());
+    }
+    info!("Removing {} files an interrupted job left half indexed", partial.len());
+    for filter in dedup::in_filters("path", partial) {
+        dedup::remove(db, table, table_name, &filter).await?;
+    }
+    update(table_name, |journal| journal.files.truncate(journal.files_done));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_progress() {
+        let journal = Journal {
+            folders: vec!["/home/u/notes".to_string(), "/home/u/code".to_string()],
+            folders_done: 1,
+            started_at: 0,
+            files: vec!["a.md".to_string(), "b.md".to_string(), "c.md".to_string()],
+            files_done: 1,
+            ..Default::default()
+        };
+        assert_eq!(journal.remaining_folders(), ["/home/u/code".to_string()]);
+        assert_eq!(journal.partial_files(), ["b.md".to_string(), "c.md".to_string()]);
+
+        let old: Journal = serde_json::from_str(r#"{ "folders": ["/home/u/notes"] }"#).unwrap();
+        assert_eq!(old.remaining_folders().len(), 1);
+        assert!(old.partial_files().is_empty());
+
+        let past_end = Journal { folders_done: 5, files_done: 9, ..journal };
+        assert!(past_end.remaining_folders().is_empty());
+        assert!(past_end.partial_files().is_empty());
+    }
+}
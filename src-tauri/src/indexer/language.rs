@@ -0,0 +1,73 @@
+use whatlang::detect;
+
+/// Enough text for a stable guess without paying for whole-file detection.
+const SAMPLE_BYTES: usize = 4096;
+
+/// ISO 639-3 (what whatlang reports) to ISO 639-1 where one exists, so the UI
+/// can name languages with `Intl.DisplayNames` and MCP callers can pass "en".
+fn to_iso639_1(code: &str) -> &str {
+    match code {
+        "afr" => "af", "aka" => "ak", "amh" => "am", "arb" => "ar", "aze" => "az",
+        "bel" => "be", "ben" => "bn", "bul" => "bg", "cat" => "ca", "ces" => "cs",
+        "cmn" => "zh", "dan" => "da", "deu" => "de", "ell" => "el", "eng" => "en",
+        "epo" => "eo", "est" => "et", "fin" => "fi", "fra" => "fr", "guj" => "gu",
+        "heb" => "he", "hin" => "hi", "hrv" => "hr", "hun" => "hu", "hye" => "hy",
+        "ind" => "id", "ita" => "it", "jav" => "jv", "jpn" => "ja", "kan" => "kn",
+        "kat" => "ka", "khm" => "km", "kor" => "ko", "lat" => "la", "lav" => "lv",
+        "lit" => "lt", "mal" => "ml", "mar" => "mr", "mkd" => "mk", "mya" => "my",
+        "nep" => "ne", "nld" => "nl", "nob" => "nb", "ori" => "or", "pan" => "pa",
+        "pes" => "fa", "pol" => "pl", "por" => "pt", "ron" => "ro", "rus" => "ru",
+        "sin" => "si", "slk" => "sk", "slv" => "sl", "sna" => "sn", "spa" => "es",
+        "srp" => "sr", "swe" => "sv", "tam" => "ta", "tel" => "te", "tgl" => "tl",
+        "tha" => "th", "tuk" => "tk", "tur" => "tr", "ukr" => "uk", "urd" => "ur",
+        "uzb" => "uz", "vie" => "vi", "yid" => "yi", "zul" => "zu",
+        other => other,
+    }
+}
+
+fn sample(text: &str) -> &str {
+    if text.len() <= SAMPLE_BYTES {
+        return text;
+    }
+    let mut end = SAMPLE_BYTES;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    &text[..end]
+}
+
+/// Predominant language of a file's text as an ISO 639-1 code, or an empty
+/// string when the guess isn't reliable (short files, mostly code, mixed text).
+pub fn detect_language(text: &str) -> String {
+    match detect(sample(text)) {
+        Some(info) if info.is_reliable() => to_iso639_1(info.lang().code()).to_string(),
+        _ => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_language() {
+        assert_eq!(
+            detect_language("This document explains how the search engine works and how the indexes are built from your files."),
+            "en"
+        );
+        assert_eq!(
+            detect_language("Bu dosya, arama motorunun nasıl çalıştığını ve dizinlerin nasıl oluşturulduğunu anlatıyor."),
+            "tr"
+        );
+        assert_eq!(
+            detect_language("Dieses Dokument beschreibt, wie die Suchmaschine funktioniert und wie Indizes erstellt werden."),
+            "de"
+        );
+    }
+
+    #[test]
+    fn test_detect_language_unreliable() {
+        assert_eq!(detect_language(""), "");
+        assert_eq!(detect_language("ok"), "");
+    }
+}
@@ -0,0 +1,126 @@
+use std::sync::LazyLock;
+
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime, Timelike};
+use regex::Regex;
+
+/// Lines are grouped into windows of this many seconds, aligned to the clock.
+const WINDOW_SECS: i64 = 300;
+/// How many leading lines to look at when deciding whether a file is a log.
+const DETECT_LINES: usize = 100;
+
+/// `2024-05-01T12:00:00`, `2024/05/01 12:00`, optionally inside `[...]`.
+static ISO_TIMESTAMP: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^\[?(\d{4})[-/](\d{2})[-/](\d{2})[T ](\d{2}):(\d{2})(?::(\d{2}))?").unwrap()
+});
+/// Syslog's `May  1 12:00:00`, which has no year.
+static SYSLOG_TIMESTAMP: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^([A-Z][a-z]{2}) +(\d{1,2}) (\d{2}):(\d{2}):(\d{2})").unwrap());
+
+const MONTHS: [&str; 12] = ["Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec"];
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+struct Stamp {
+    at: NaiveDateTime,
+    has_year: bool,
+}
+
+fn leading_timestamp(line: &str) -> Option<Stamp> {
+    let num = |s: Option<regex::Match>| s.map_or(Some(0), |m| m.as_str().parse::<u32>().ok());
+    if let Some(c) = ISO_TIMESTAMP.captures(line) {
+        let date = NaiveDate::from_ymd_opt(c[1].parse().ok()?, c[2].parse().ok()?, c[3].parse().ok()?)?;
+        let time = NaiveTime::from_hms_opt(c[4].parse().ok()?, c[5].parse().ok()?, num(c.get(6))?)?;
+        return Some(Stamp { at: date.and_time(time), has_year: true });
+    }
+    if let Some(c) = SYSLOG_TIMESTAMP.captures(line) {
+        let month = MONTHS.iter().position(|m| *m == &c[1])? as u32 + 1;
+        // Leap year so Feb 29 entries still parse.
+        let date = NaiveDate::from_ymd_opt(2000, month, c[2].parse().ok()?)?;
+        let time = NaiveTime::from_hms_opt(c[3].parse().ok()?, c[4].parse().ok()?, c[5].parse().ok()?)?;
+        return Some(Stamp { at: date.and_time(time), has_year: false });
+    }
+    None
+}
+
+fn window_start(stamp: Stamp) -> Stamp {
+    let secs = stamp.at.num_seconds_from_midnight() as i64;
+    let aligned = secs - secs % WINDOW_SECS;
+    let time = NaiveTime::from_num_seconds_from_midnight_opt(aligned as u32, 0).unwrap_or(stamp.at.time());
+    Stamp { at: stamp.at.date().and_time(time), has_year: stamp.has_year }
+}
+
+fn window_label(start: Stamp) -> String {
+    let end = start.at + chrono::Duration::seconds(WINDOW_SECS);
+    let day = if start.has_year { "%Y-%m-%d" } else { "%b %d" };
+    format!("{} {}–{}", start.at.format(day), start.at.format("%H:%M"), end.format("%H:%M"))
+}
+
+/// A `.log` file counts as a log when at least half its first lines start
+/// with a timestamp; anything else falls back to normal chunking.
+fn looks_like_log(text: &str) -> bool {
+    let lines: Vec<&str> = text.lines().filter(|l| !l.trim().is_empty()).take(DETECT_LINES).collect();
+    !lines.is_empty() && lines.iter().filter(|l| leading_timestamp(l).is_some()).count() * 2 >= lines.len()
+}
+
+/// Splits a log into clock-aligned time windows instead of byte ranges, each
+/// paired with its window label ("2024-05-01 12:00–12:05"). Lines without a
+/// timestamp (stack traces, continuations) stay with the window before them.
+/// Windows larger than `max_bytes` are split further under the same label.
+/// None when the file isn't a timestamped log.
+pub fn time_slices(text: &str, ext: &str, max_bytes: usize) -> Option<Vec<(String, String)>> {
+    if ext != "log" || !looks_like_log(text) {
+        return None;
+    }
+
+    let mut windows: Vec<(Option<Stamp>, String)> = Vec::new();
+    for line in text.split_inclusive('\n') {
+        let start = leading_timestamp(line).map(window_start);
+        match windows.last_mut() {
+            Some((current, body)) if start.is_none() || start == *current => body.push_str(line),
+            _ => windows.push((start, line.to_string())),
+        }
+    }
+
+    let mut slices = Vec::new();
+    for (start, body) in windows {
+        if body.trim().is_empty() {
+            continue;
+        }
+        let label = start.map(window_label).unwrap_or_default();
+        if body.len() > max_bytes {
+            for part in super::chunking::chunk_with_overlap(&body, max_bytes, 0) {
+                slices.push((part, label.clone()));
+            }
+        } else {
+            slices.push((body, label));
+        }
+    }
+    Some(slices)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_leading_timestamp_formats() {
+        assert!(leading_timestamp("2024-05-01T12:03:44.120Z INFO started").is_some());
+        assert!(leading_timestamp("[2024/05/01 12:03] warn").is_some());
+        assert!(leading_timestamp("May  1 12:03:44 host sshd[1]: ok").is_some());
+        assert!(leading_timestamp("    at com.example.Main").is_none());
+    }
+
+    #[test]
+    fn test_time_slices_by_window() {
+        let text = "2024-05-01 12:01:00 INFO a\n2024-05-01 12:04:59 ERROR b\n  at trace\n2024-05-01 12:05:00 INFO c\n2024-05-01 13:00:00 INFO d\n";
+        let slices = time_slices(text, "log", 10_000).unwrap();
+        let labels: Vec<&str> = slices.iter().map(|(_, l)| l.as_str()).collect();
+        assert_eq!(labels, vec!["2024-05-01 12:00–12:05", "2024-05-01 12:05–12:10", "2024-05-01 13:00–13:05"]);
+        assert!(slices[0].0.contains("at trace"));
+    }
+
+    #[test]
+    fn test_time_slices_skips_non_logs() {
+        assert_eq!(time_slices("just some notes\nmore notes\n", "log", 800), None);
+        assert_eq!(time_slices("2024-05-01 12:01:00 INFO a\n", "txt", 800), None);
+    }
+}
@@ -0,0 +1,191 @@
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use arrow_array::{FixedSizeListArray, Float32Array, RecordBatch, RecordBatchIterator, StringArray};
+use arrow_schema::{DataType, Field, Schema};
+use futures::TryStreamExt;
+use lancedb::connection::Connection;
+use lancedb::query::ExecutableQuery;
+use lancedb::Table;
+use log::{info, warn};
+use tokio::sync::Mutex;
+
+use super::rebuild::{rebuild_table_name, replace, swap_in};
+use super::{annotations, db, embed_batch, get_provider_dim, progress, EMBED_BATCH_SIZE};
+use crate::power;
+use crate::state::{IndexingProgress, ProviderState};
+
+fn vector_field(dim: usize) -> Field {
+    Field::new(
+        "vector",
+        DataType::FixedSizeList(Arc::new(Field::new("item", DataType::Float32, true)), dim as i32),
+        false,
+    )
+}
+
+/// `schema` with its `vector` column resized to `dim`.
+fn with_dim(schema: &Schema, dim: usize) -> Schema {
+    Schema::new(
+        schema
+            .fields()
+            .iter()
+            .map(|f| if f.name() == "vector" { vector_field(dim) } else { f.as_ref().clone() })
+            .collect::<Vec<_>>(),
+    )
+}
+
+/// `batch` with `vectors` in place of its `vector` column, every other
+/// column as it was.
+fn with_vectors(batch: &RecordBatch, schema: Arc<Schema>, vectors: Vec<Vec<f32>>, dim: usize) -> Result<RecordBatch> {
+    let index = schema.index_of("vector")?;
+    let flat: Vec<f32> = vectors.into_iter().flatten().collect();
+    let array = FixedSizeListArray::try_new(
+        Arc::new(Field::new("item", DataType::Float32, true)),
+        dim as i32,
+        Arc::new(Float32Array::from(flat)),
+        None,
+    )?;
+    let mut columns = batch.columns().to_vec();
+    columns[index] = Arc::new(array);
+    Ok(RecordBatch::try_new(schema, columns)?)
+}
+
+/// Embeds `text_column` of every row of `source` again with the current
+/// provider. Each re-embedded batch is added to `dest` as it's done, or
+/// returned with the others when there's no `dest`. `progress` gets the
+/// rows and text bytes of every batch.
+async fn reembed_rows(
+    source: &Table,
+    text_column: &str,
+    schema: Arc<Schema>,
+    dim: usize,
+    provider_state: &Arc<Mutex<ProviderState>>,
+    dest: Option<&Table>,
+    progress: &(dyn Fn(usize, u64) + Send + Sync),
+) -> Result<Vec<RecordBatch>> {
+    let mut kept = Vec::new();
+    let mut stream = source.query().execute().await?;
+    while let Some(batch) = stream.try_next().await? {
+        let mut offset = 0;
+        while offset < batch.num_rows() {
+            let size = if power::is_low_power() { power::LOW_POWER_BATCH_SIZE } else { EMBED_BATCH_SIZE };
+            let slice = batch.slice(offset, size.min(batch.num_rows() - offset));
+            let texts: Vec<String> = slice
+                .column_by_name(text_column)
+                .and_then(|c| c.as_any().downcast_ref::<StringArray>())
+                .ok_or_else(|| anyhow!("Missing or invalid '{}' column", text_column))?
+                .iter()
+                .map(|t| t.unwrap_or_default().to_string())
+                .collect();
+            let bytes = texts.iter().map(|t| t.len() as u64).sum();
+            let vectors = embed_batch(provider_state, texts).await?;
+            let batch = with_vectors(&slice, schema.clone(), vectors, dim)?;
+            match dest {
+                Some(table) => table.add(RecordBatchIterator::new(vec![Ok(batch)], schema.clone())).execute().await?,
+                None => kept.push(batch),
+            }
+
+            offset += slice.num_rows();
+            progress(slice.num_rows(), bytes);
+            if power::is_low_power() {
+                tokio::time::sleep(power::LOW_POWER_BATCH_PAUSE).await;
+            }
+        }
+    }
+    Ok(kept)
+}
+
+/// Re-embeds the container's stored chunks with the current embedding
+/// provider instead of reading and chunking every file again, for after a
+/// model change. Everything but the vectors stays as it was: mtimes, chunk
+/// context, language, dedup refs. The new vectors go into the rebuild table
+/// and are swapped in at the end, annotations after them. Returns how many
+/// chunks were re-embedded.
+pub async fn reembed<F>(
+    db: &Connection,
+    container_table: &str,
+    provider_state: &Arc<Mutex<ProviderState>>,
+    progress_callback: F,
+) -> Result<usize>
+where
+    F: Fn(IndexingProgress) + Send + Sync,
+{
+    let dim = get_provider_dim(provider_state).await?;
+    let table = db
+        .open_table(container_table)
+        .execute()
+        .await
+        .map_err(|_| anyhow!("No index found for '{}'. Index some folders first.", container_table))?;
+    db::ensure_columns(&table).await?;
+    let total = table.count_rows(None).await?;
+    info!("Re-embedding {} chunks of '{}' at {} dimensions", total, container_table, dim);
+
+    let rebuild_name = rebuild_table_name(container_table);
+    db::reset_index(db, &rebuild_name).await?;
+    let schema = Arc::new(with_dim(table.schema().await?.as_ref(), dim));
+    let rebuilt = db
+        .create_table(&rebuild_name, RecordBatchIterator::new(vec![], schema.clone()))
+        .execute()
+        .await?;
+
+    let meter = std::sync::Mutex::new(progress::Meter::new(total));
+    let progress = |rows: usize, bytes: u64| {
+        let mut meter = meter.lock().unwrap_or_else(|e| e.into_inner());
+        meter.written(rows, rows, bytes);
+        let done = meter.processed();
+        progress_callback(meter.report(done, total, format!("Re-embedding {} of {} chunks", done, total)));
+    };
+    if let Err(e) = reembed_rows(&table, "content", schema, dim, provider_state, Some(&rebuilt), &progress).await {
+        let _ = db::reset_index(db, &rebuild_name).await;
+        return Err(e);
+    }
+
+    progress_callback(IndexingProgress::new(total, total, "Swapping in the new vectors..."));
+    swap_in(db, container_table).await?;
+
+    if let Err(e) = reembed_annotations(db, container_table, dim, provider_state).await {
+        warn!("Re-embedding annotations of '{}' failed: {}", container_table, e);
+    }
+    info!("Re-embedded {} chunks of '{}'", total, container_table);
+    Ok(total)
+}
+
+/// Annotations carry their own vectors for search; there are few enough
+/// to re-embed in memory and replace in one go.
+async fn reembed_annotations(
+    db: &Connection,
+    container_table: &str,
+    dim: usize,
+    provider_state: &Arc<Mutex<ProviderState>>,
+) -> Result<()> {
+    let name = annotations::annotations_table_name(container_table);
+    let Ok(table) = db.open_table(&name).execute().await else {
+        return Ok(());
+    };
+    let schema = Arc::new(with_dim(table.schema().await?.as_ref(), dim));
+    let batches = reembed_rows(&table, "note", schema.clone(), dim, provider_state, None, &|_, _| {}).await?;
+    let count: usize = batches.iter().map(|b| b.num_rows()).sum();
+    replace(db, &name, schema, batches).await?;
+    info!("Re-embedded {} annotations of '{}'", count, container_table);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_with_dim() {
+        let schema = Schema::new(vec![
+            Field::new("path", DataType::Utf8, false),
+            vector_field(384),
+            Field::new("mtime", DataType::Int64, false),
+        ]);
+        let resized = with_dim(&schema, 1024);
+        assert_eq!(resized.fields().len(), 3);
+        assert_eq!(resized.field(0), schema.field(0));
+        assert_eq!(resized.field(2), schema.field(2));
+        assert_eq!(resized.field_with_name("vector").unwrap(), &vector_field(1024));
+        assert_eq!(with_dim(&schema, 384), schema);
+    }
+}
@@ -1,42 +1,200 @@
 pub mod annotations;
+pub mod ask;
+pub mod bundle;
+pub mod centroid;
+pub mod change_journal;
 pub mod chunking;
+pub mod cjk;
+pub mod completion;
 pub mod db;
+pub mod dedup;
 pub mod embedding;
 pub mod embedding_provider;
+pub mod entities;
+pub mod encoding;
+pub mod eval;
+pub mod external;
 pub mod file_io;
+pub mod file_vectors;
 pub mod git;
+pub mod history;
 pub mod ocr;
+pub mod outline;
 pub mod hyde;
+pub mod identity;
+pub mod journal;
+pub mod language;
+pub mod logs;
+pub mod migrate;
 pub mod pipeline;
+pub mod progress;
+pub mod quarantine;
+pub mod query_filters;
 pub mod query_router;
+pub mod quota;
+pub mod rebuild;
 pub mod search;
-
+pub mod secrets;
+pub mod snapshot;
+pub mod snippet;
+pub mod structured;
+pub mod summaries;
+pub mod tabular;
+pub mod spelling;
+pub mod timeline;
+pub mod translate;
+pub mod tombstones;
+pub mod topics;
+pub mod versions;
+pub mod wasm;
+
+use std::collections::HashMap;
+use std::path::PathBuf;
 use std::sync::Arc;
 
 use anyhow::{anyhow, Result};
 use arrow_array::RecordBatchIterator;
+use futures::StreamExt;
 use lancedb::connection::Connection;
-use log::{info, debug};
+use log::{info, debug, warn};
 use rayon::prelude::*;
 use tokio::sync::Mutex;
 
-use crate::config::IndexingConfig;
-use crate::state::ProviderState;
+use crate::config::{is_path_excluded, IndexingConfig};
+use crate::power;
+use crate::state::{IndexingProgress, ProviderState};
 
 use ignore::WalkBuilder;
 
-pub use chunking::expand_query;
+pub use chunking::{expand_query, QueryExpansion};
 pub use db::reset_index;
 pub use embedding::{embed_query, load_model, load_reranker, rerank_results, safe_rerank};
-pub use search::{build_filter_expr, hybrid_merge, search_files, search_fts, search_pipeline};
+pub use query_filters::QueryFilters;
+pub use search::{build_filter_expr, build_folder_filter, hybrid_merge, search_files, search_fts, search_fts_only, search_pipeline, SearchScope};
+
+/// How long the rows of a deleted file stay findable: in the version history
+/// for `as_of` searches and as a tombstone for "include deleted".
+#[derive(Clone, Copy, Default)]
+pub struct Retention {
+    pub history_days: u32,
+    pub deleted_days: u32,
+}
+
+impl From<&IndexingConfig> for Retention {
+    fn from(config: &IndexingConfig) -> Self {
+        Self { history_days: config.version_history_days, deleted_days: config.deleted_retention_days }
+    }
+}
 
 const ANN_INDEX_THRESHOLD: usize = 256;
 const EMBED_BATCH_SIZE: usize = 256;
 
 struct ExtractedFile {
     path: String,
-    chunks: Vec<String>,
+    /// (content, context, start) per chunk, see `label_chunks` and
+    /// `chunk_starts`.
+    chunks: Vec<(String, String, i64)>,
     mtime: i64,
+    lang: String,
+    /// Length of the extracted text.
+    bytes: u64,
+    /// Size of the file on disk.
+    size: i64,
+    /// Lines in the extracted text.
+    lines: i64,
+    /// Secrets `secrets::screen` found, already dealt with in `chunks`.
+    secrets: Option<secrets::Flagged>,
+}
+
+/// Prefixes each chunk with its file name and, when the file has structure,
+/// where in the file it sits, so the embedding and the reranker both see
+/// that. The context is returned separately too for its own column.
+fn label_chunks(text: &str, ext: &str, file_name: &str, chunks: Vec<String>) -> Vec<(String, String, i64)> {
+    let contexts = outline::chunk_contexts(text, &chunks, ext);
+    let starts = chunk_starts(text, &chunks);
+    chunks
+        .into_iter()
+        .zip(contexts)
+        .zip(starts)
+        .map(|((chunk, context), start)| (with_header(file_name, &context, &chunk), context, start))
+        .collect()
+}
+
+/// Byte offset in `text` each chunk starts at, or -1 for one that isn't a
+/// piece of it as is. Chunks come in order and overlap, so each is looked
+/// for from just past the start of the one before.
+fn chunk_starts(text: &str, chunks: &[String]) -> Vec<i64> {
+    let mut from = 0;
+    chunks
+        .iter()
+        .map(|chunk| match text.get(from..).and_then(|rest| rest.find(chunk.as_str())) {
+            Some(at) => {
+                let start = from + at;
+                from = start + chunk.chars().next().map_or(0, char::len_utf8);
+                start as i64
+            }
+            None => -1,
+        })
+        .collect()
+}
+
+fn with_header(file_name: &str, context: &str, chunk: &str) -> String {
+    if context.is_empty() {
+        format!("File: {}\n{}", file_name, chunk)
+    } else {
+        format!("File: {}\nContext: {}\n{}", file_name, context, chunk)
+    }
+}
+
+/// Everything indexed for one file: its text (plus git history) chunked by
+/// type and labeled, and for config files a flattened key listing chunked
+/// on its own so snippets show the full key path. Large CSV/TSV files are
+/// replaced by a summary of their columns and a row sample.
+fn chunk_file(
+    text: &str,
+    git_ctx: Option<String>,
+    ext: &str,
+    file_name: &str,
+    chunk_size: Option<usize>,
+    chunk_overlap: Option<usize>,
+) -> Vec<(String, String, i64)> {
+    let max_bytes = chunk_size.unwrap_or(chunking::get_chunk_config(ext).max_bytes).max(100);
+
+    let slices = if ext == "pdf" {
+        chunking::page_slices(text, ext, chunk_size, chunk_overlap)
+    } else {
+        logs::time_slices(text, ext, max_bytes)
+    };
+    if let Some(slices) = slices {
+        let pieces: Vec<String> = slices.iter().map(|(chunk, _)| chunk.clone()).collect();
+        let mut labeled: Vec<(String, String, i64)> = slices
+            .into_iter()
+            .zip(chunk_starts(text, &pieces))
+            .map(|((chunk, context), start)| (with_header(file_name, &context, &chunk), context, start))
+            .collect();
+        if let Some(ctx) = git_ctx {
+            labeled.push((with_header(file_name, "", ctx.trim_start()), String::new(), -1));
+        }
+        return labeled;
+    }
+
+    let keys = structured::flatten(text, ext);
+
+    let mut full = tabular::summarize(text, ext).unwrap_or_else(|| text.to_string());
+    if let Some(ctx) = git_ctx {
+        full.push_str(&ctx);
+    }
+    let chunks = chunking::semantic_chunk_with_overrides(&full, ext, chunk_size, chunk_overlap);
+    let mut labeled = label_chunks(&full, ext, file_name, chunks);
+
+    if let Some(keys) = keys {
+        labeled.extend(
+            chunking::chunk_with_overlap(&keys, max_bytes, 0)
+                .into_iter()
+                .map(|chunk| (format!("File: {}\n{}", file_name, chunk), String::new(), -1)),
+        );
+    }
+    labeled
 }
 
 async fn embed_batch(
@@ -60,101 +218,188 @@ async fn get_provider_dim(provider_state: &Arc<Mutex<ProviderState>>) -> Result<
     provider.get_dimension().await
 }
 
-pub async fn index_directory<F>(
-    root_dir: &str,
-    table_name: &str,
+/// A folder's new and changed files, read and chunked, ready to embed.
+struct Extraction {
+    root_dir: String,
+    /// Files found under the folder, changed or not.
+    total_files: usize,
+    image_files: usize,
+    files: Vec<ExtractedFile>,
+    /// Indexed paths of files found here under a path that's preferred.
+    superseded: Vec<String>,
+    /// Saved once the folder is written, so the next pass only walks what
+    /// changed after this one started.
+    checkpoint: Option<change_journal::Checkpoint>,
+}
+
+/// Indexing stopped in `folder`; the ones before it are done.
+#[derive(Debug)]
+pub struct FolderError {
+    pub folder: String,
+    pub error: anyhow::Error,
+}
+
+/// Opens the table a job writes to, drops what an interrupted job left half
+/// written and returns the mtime of every file in it, which is how the job
+/// tells what changed.
+async fn prepare_table(
     db: &Connection,
+    table_name: &str,
     provider_state: &Arc<Mutex<ProviderState>>,
-    indexing_config: &IndexingConfig,
-    progress_callback: F,
-) -> Result<usize>
-where
-    F: Fn(usize, usize, String) + Send + Sync + 'static,
-{
+) -> Result<(lancedb::Table, HashMap<String, i64>)> {
     let dim = get_provider_dim(provider_state).await?;
     let table = db::get_or_create_table(db, table_name, dim).await?;
+    if let Err(e) = journal::drop_partial(db, &table, table_name).await {
+        warn!("Removing half-indexed files failed: {}", e);
+    }
 
-    let existing_mtimes = db::get_indexed_mtimes(&table).await.unwrap_or_default();
+    let mut existing_mtimes = db::get_indexed_mtimes(&table).await.unwrap_or_default();
+    existing_mtimes.extend(dedup::ref_mtimes(db, table_name).await.unwrap_or_default());
+    Ok((table, existing_mtimes))
+}
 
+/// Walks `root_dir`, or only the part of it in `scope`, and reads and
+/// chunks the text files that are new or changed, on every core (a couple
+/// in low-power mode). Returns the images found for OCR alongside.
+fn extract_text_files(
+    root_dir: &str,
+    scope: Option<change_journal::Scope>,
+    existing_mtimes: &HashMap<String, i64>,
+    indexing_config: &IndexingConfig,
+    excluded_paths: &[String],
+) -> (usize, Vec<PathBuf>, Vec<ExtractedFile>, Vec<String>) {
     info!("Indexing directory: {}", root_dir);
 
-    let all_files: Vec<_> = WalkBuilder::new(root_dir)
+    let mut walker = WalkBuilder::new(root_dir);
+    walker
         .hidden(true)
         .git_ignore(true)
         .git_global(true)
         .git_exclude(true)
-        .add_custom_ignore_filename(".rcignore")
+        .add_custom_ignore_filename(".rcignore");
+    if let Some(scope) = scope {
+        walker.filter_entry(move |entry| match entry.file_type() {
+            Some(ft) if ft.is_dir() => scope.wants_dir(entry.path()),
+            _ => scope.wants_file(entry.path()),
+        });
+    }
+    let all_files: Vec<_> = walker
         .build()
         .filter_map(|e| e.ok())
         .filter(|e| e.file_type().is_some_and(|ft| ft.is_file()))
         .map(|e| e.into_path())
+        .filter(|p| !is_path_excluded(excluded_paths, &p.to_string_lossy()))
         .collect();
-    let total_files = all_files.len();
-    debug!("Found {} files ({} image, {} text)", total_files, all_files.iter().filter(|p| ocr::is_image_extension(&p.extension().and_then(|s| s.to_str()).unwrap_or("").to_lowercase())).count(), all_files.iter().filter(|p| !ocr::is_image_extension(&p.extension().and_then(|s| s.to_str()).unwrap_or("").to_lowercase())).count());
-
-    progress_callback(0, total_files, "Scanning files...".to_string());
-
-    let image_files: Vec<_> = all_files
+    let all_files = identity::dedupe_files(all_files, indexing_config.display_path);
+    let new_paths: Vec<String> = all_files
         .iter()
-        .filter(|p| {
-            let ext = p.extension().and_then(|s| s.to_str()).unwrap_or("").to_lowercase();
-            ocr::is_image_extension(&ext)
-        })
-        .cloned()
+        .map(|p| p.to_string_lossy().to_string())
+        .filter(|p| !existing_mtimes.contains_key(p))
         .collect();
+    let (elsewhere, superseded) = identity::indexed_elsewhere(&new_paths, existing_mtimes.keys(), indexing_config.display_path);
+    if !elsewhere.is_empty() || !superseded.is_empty() {
+        info!("{} files here are indexed under another path, {} move to their path here", elsewhere.len(), superseded.len());
+    }
+    let all_files: Vec<PathBuf> =
+        all_files.into_iter().filter(|p| !elsewhere.contains(p.to_string_lossy().as_ref())).collect();
+    let total_files = all_files.len();
 
-    let non_image_files: Vec<_> = all_files
-        .iter()
-        .filter(|p| {
-            let ext = p.extension().and_then(|s| s.to_str()).unwrap_or("").to_lowercase();
-            !ocr::is_image_extension(&ext)
-        })
-        .cloned()
-        .collect();
-
-    let extracted: Vec<ExtractedFile> = non_image_files
-        .par_iter()
-        .filter_map(|path| {
-            let path_str = path.to_string_lossy().to_string();
-            let mtime = file_io::get_file_mtime(path);
-
-            if let Some(&existing_mtime) = existing_mtimes.get(&path_str) {
-                if existing_mtime == mtime {
-                    return None;
+    // An extractor from config takes images away from OCR too. With OCR
+    // off they go with the rest, where nothing reads them.
+    let (image_files, non_image_files): (Vec<_>, Vec<_>) = all_files.into_iter().partition(|p| {
+        let ext = p.extension().and_then(|s| s.to_str()).unwrap_or("").to_lowercase();
+        indexing_config.ocr && ocr::is_image_extension(&ext) && !indexing_config.extractors.handles(&ext)
+    });
+    debug!("Found {} files ({} image, {} text)", total_files, image_files.len(), non_image_files.len());
+
+    // On battery or under load, extract on a couple of threads instead of all cores.
+    let low_power_pool = power::is_low_power()
+        .then(|| rayon::ThreadPoolBuilder::new().num_threads(power::LOW_POWER_THREADS).build().ok())
+        .flatten();
+    let extract_all = || -> Vec<ExtractedFile> {
+        non_image_files
+            .par_iter()
+            .filter_map(|path| {
+                let path_str = path.to_string_lossy().to_string();
+                let mtime = file_io::get_file_mtime(path);
+
+                if let Some(&existing_mtime) = existing_mtimes.get(&path_str) {
+                    if existing_mtime == mtime {
+                        return None;
+                    }
                 }
-            }
 
-            let mut text = file_io::read_file_content_with_config(path, indexing_config)?;
-            if text.trim().is_empty() {
-                return None;
-            }
-            if indexing_config.use_git_history {
-                if let Some(git_ctx) = git::get_commit_context(path) {
-                    text.push_str(&git_ctx);
+                let text = file_io::read_file_content_with_config(path, indexing_config)?;
+                if text.trim().is_empty() {
+                    return None;
                 }
-            }
-
-            let ext = path
-                .extension()
-                .and_then(|s| s.to_str())
-                .unwrap_or("")
-                .to_lowercase();
-            let chunks = chunking::semantic_chunk_with_overrides(
-                &text,
-                &ext,
-                indexing_config.chunk_size,
-                indexing_config.chunk_overlap,
-            );
-            let file_name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
-            let chunks: Vec<String> = chunks.into_iter().map(|c| format!("File: {}\n{}", file_name, c)).collect();
-
-            Some(ExtractedFile {
-                path: path_str,
-                chunks,
-                mtime,
+                let lang = language::detect_language(&text);
+                let git_ctx = if indexing_config.use_git_history {
+                    git::get_commit_context(path)
+                } else {
+                    None
+                };
+
+                let ext = path
+                    .extension()
+                    .and_then(|s| s.to_str())
+                    .unwrap_or("")
+                    .to_lowercase();
+                let file_name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+                let mut chunks = chunk_file(
+                    &text,
+                    git_ctx,
+                    &ext,
+                    file_name,
+                    indexing_config.chunk_size,
+                    indexing_config.chunk_overlap,
+                );
+                let secrets = secrets::screen(&mut chunks, indexing_config.secrets);
+
+                Some(ExtractedFile {
+                    path: path_str,
+                    chunks,
+                    mtime,
+                    lang,
+                    bytes: text.len() as u64,
+                    size: file_io::get_file_size(path),
+                    lines: text.lines().count() as i64,
+                    secrets,
+                })
             })
+            .collect()
+    };
+    let extracted = match &low_power_pool {
+        Some(pool) => pool.install(extract_all),
+        None => extract_all(),
+    };
+    (total_files, image_files, extracted, superseded)
+}
+
+/// Everything new or changed under `root_dir`, read and chunked. Runs off
+/// the async threads, so several folders can be extracted at once.
+async fn extract_directory(
+    root_dir: String,
+    table_name: String,
+    existing_mtimes: Arc<HashMap<String, i64>>,
+    indexing_config: Arc<IndexingConfig>,
+    excluded_paths: Arc<Vec<String>>,
+) -> Result<Extraction> {
+    let (checkpoint, (total_files, image_files, extracted, superseded)) = {
+        let root_dir = root_dir.clone();
+        let existing_mtimes = existing_mtimes.clone();
+        let indexing_config = indexing_config.clone();
+        tokio::task::spawn_blocking(move || {
+            let (checkpoint, scope) = if indexing_config.change_journal {
+                let settings = change_journal::settings_key(&indexing_config, &excluded_paths);
+                change_journal::plan(&table_name, &root_dir, &settings)
+            } else {
+                (None, None)
+            };
+            (checkpoint, extract_text_files(&root_dir, scope, &existing_mtimes, &indexing_config, &excluded_paths))
         })
-        .collect();
+        .await?
+    };
 
     let mut image_futures = Vec::new();
     for path in &image_files {
@@ -171,26 +416,29 @@ where
         let use_git = indexing_config.use_git_history;
         let chunk_size = indexing_config.chunk_size;
         let chunk_overlap = indexing_config.chunk_overlap;
+        let secret_policy = indexing_config.secrets;
         image_futures.push(tokio::spawn(async move {
-            if let Some(mut text) = file_io::read_file_content_with_ocr(&path_clone).await {
+            if let Some(text) = file_io::read_file_content_with_ocr(&path_clone).await {
                 if !text.trim().is_empty() {
-                    if use_git {
-                        if let Some(git_ctx) = git::get_commit_context(&path_clone) {
-                            text.push_str(&git_ctx);
-                        }
-                    }
+                    let lang = language::detect_language(&text);
+                    let git_ctx = if use_git { git::get_commit_context(&path_clone) } else { None };
                     let ext = path_clone
                         .extension()
                         .and_then(|s| s.to_str())
                         .unwrap_or("")
                         .to_lowercase();
-                    let chunks = chunking::semantic_chunk_with_overrides(&text, &ext, chunk_size, chunk_overlap);
                     let file_name = path_clone.file_name().and_then(|n| n.to_str()).unwrap_or("");
-                    let chunks: Vec<String> = chunks.into_iter().map(|c| format!("File: {}\n{}", file_name, c)).collect();
+                    let mut chunks = chunk_file(&text, git_ctx, &ext, file_name, chunk_size, chunk_overlap);
+                    let secrets = secrets::screen(&mut chunks, secret_policy);
                     return Some(ExtractedFile {
                         path: path_clone.to_string_lossy().to_string(),
                         chunks,
                         mtime,
+                        lang,
+                        bytes: text.len() as u64,
+                        size: file_io::get_file_size(&path_clone),
+                        lines: text.lines().count() as i64,
+                        secrets,
                     });
                 }
             }
@@ -204,44 +452,212 @@ where
         .filter_map(|r| r.ok().flatten())
         .collect();
 
-    let mut all_extracted = extracted;
-    all_extracted.extend(image_extracted);
+    let mut files = extracted;
+    files.extend(image_extracted);
+    Ok(Extraction { root_dir, total_files, image_files: image_files.len(), files, superseded, checkpoint })
+}
+
+/// How many folders a multi-folder job extracts at once: `parallel_folders`
+/// from config, or a quarter of the cores (up to 4) when that's 0. Each
+/// extraction already spreads over every core, so more mostly helps folders
+/// with few files and keeps the writer fed. One in low-power mode.
+fn folder_parallelism(indexing_config: &IndexingConfig) -> usize {
+    if power::is_low_power() {
+        return 1;
+    }
+    match indexing_config.parallel_folders {
+        0 => std::thread::available_parallelism().map_or(1, |n| (n.get() / 4).clamp(1, 4)),
+        n => n,
+    }
+}
+
+pub async fn index_directory<F>(
+    root_dir: &str,
+    table_name: &str,
+    db: &Connection,
+    provider_state: &Arc<Mutex<ProviderState>>,
+    indexing_config: &IndexingConfig,
+    excluded_paths: &[String],
+    progress_callback: F,
+) -> Result<usize>
+where
+    F: Fn(IndexingProgress) + Send + Sync + 'static,
+{
+    let (table, existing_mtimes) = prepare_table(db, table_name, provider_state).await?;
+    progress_callback(IndexingProgress::new(0, 0, "Scanning files..."));
+    let existing_mtimes = Arc::new(existing_mtimes);
+    let extraction = extract_directory(
+        root_dir.to_string(),
+        table_name.to_string(),
+        existing_mtimes.clone(),
+        Arc::new(indexing_config.clone()),
+        Arc::new(excluded_paths.to_vec()),
+    )
+    .await?;
+    write_extraction(extraction, &table, table_name, db, provider_state, indexing_config, &existing_mtimes, &progress_callback).await
+}
+
+/// Indexes `roots` into one table, in order. The next few folders are
+/// walked, read and chunked in the background (see `folder_parallelism`)
+/// while the current one is embedded and written, so the single writer
+/// rarely waits on the disk. Each folder is marked done in the journal as
+/// it finishes.
+pub async fn index_directories<F>(
+    roots: &[String],
+    table_name: &str,
+    db: &Connection,
+    provider_state: &Arc<Mutex<ProviderState>>,
+    indexing_config: &IndexingConfig,
+    excluded_paths: &[String],
+    progress_callback: F,
+) -> Result<usize, FolderError>
+where
+    F: Fn(IndexingProgress) + Send + Sync + 'static,
+{
+    let Some(first) = roots.first() else {
+        return Ok(0);
+    };
+    let (table, existing_mtimes) = prepare_table(db, table_name, provider_state)
+        .await
+        .map_err(|error| FolderError { folder: first.clone(), error })?;
+    progress_callback(IndexingProgress::new(0, 0, "Scanning files..."));
+
+    let parallel = folder_parallelism(indexing_config);
+    debug!("Extracting up to {} of {} folders at once", parallel, roots.len());
+    let existing_mtimes = Arc::new(existing_mtimes);
+    let shared_config = Arc::new(indexing_config.clone());
+    let excluded = Arc::new(excluded_paths.to_vec());
+    let mut extractions = futures::stream::iter(roots.to_vec())
+        .map(|root| {
+            tokio::spawn(extract_directory(
+                root,
+                table_name.to_string(),
+                existing_mtimes.clone(),
+                shared_config.clone(),
+                excluded.clone(),
+            ))
+        })
+        .buffered(parallel);
+
+    let mut total = 0;
+    for root in roots {
+        let failed = |error: anyhow::Error| FolderError { folder: root.clone(), error };
+        let extraction = match extractions.next().await {
+            Some(Ok(extraction)) => extraction.map_err(failed)?,
+            Some(Err(e)) => return Err(failed(e.into())),
+            None => break,
+        };
+        total += write_extraction(extraction, &table, table_name, db, provider_state, indexing_config, &existing_mtimes, &progress_callback)
+            .await
+            .map_err(failed)?;
+        journal::folder_done(table_name);
+    }
+    Ok(total)
+}
+
+/// Embeds and writes one folder's extraction: old rows of changed files
+/// out (into the version history if it's on), new chunks in, in batches
+/// that each leave the files before them complete.
+#[allow(clippy::too_many_arguments)]
+async fn write_extraction<F>(
+    extraction: Extraction,
+    table: &lancedb::Table,
+    table_name: &str,
+    db: &Connection,
+    provider_state: &Arc<Mutex<ProviderState>>,
+    indexing_config: &IndexingConfig,
+    existing_mtimes: &HashMap<String, i64>,
+    progress_callback: &F,
+) -> Result<usize>
+where
+    F: Fn(IndexingProgress),
+{
+    let report = |current: usize, total: usize, path: &str| progress_callback(IndexingProgress::new(current, total, path));
+    let Extraction { root_dir, total_files, image_files, files: all_extracted, superseded, checkpoint } = extraction;
     let files_indexed = all_extracted.len();
 
     if files_indexed == 0 {
         info!("No new files to index in {}", root_dir);
-        progress_callback(total_files, total_files, "Done -- no new files".to_string());
+        if let Err(e) = summaries::refresh(db, table, table_name, &indexing_config.summaries, provider_state, &root_dir, &[]).await {
+            warn!("Updating summaries failed: {}", e);
+        }
+        if let Some(checkpoint) = checkpoint {
+            change_journal::save(table_name, &root_dir, checkpoint);
+        }
+        report(total_files, total_files, "Done -- no new files");
         return Ok(0);
     }
 
-    progress_callback(
-        0,
-        files_indexed,
-        format!("Extracted {} files, starting embedding...", files_indexed),
-    );
+    report(0, files_indexed, &format!("Extracted {} files, starting embedding...", files_indexed));
+
+    // Old rows go first so chunks they held are known to be gone (or moved
+    // to another file) before deciding what still needs embedding.
+    let changed_paths: Vec<String> = all_extracted.iter().map(|ef| ef.path.clone()).collect();
+    journal::plan_files(table_name, &changed_paths);
+    let replaced: HashMap<String, i64> = all_extracted
+        .iter()
+        .filter(|ef| existing_mtimes.contains_key(&ef.path))
+        .map(|ef| (ef.path.clone(), ef.mtime))
+        .collect();
+    if let Err(e) = versions::archive(db, table, table_name, &replaced, indexing_config.version_history_days).await {
+        warn!("Keeping old versions failed: {}", e);
+    }
+    for filter in dedup::in_filters("path", &[changed_paths.as_slice(), &superseded].concat()) {
+        if let Err(e) = dedup::remove(db, table, table_name, &filter).await {
+            warn!("Removing old rows failed: {}", e);
+        }
+    }
+    let hashes: Vec<Vec<String>> = all_extracted
+        .iter()
+        .map(|ef| ef.chunks.iter().map(|(content, _, _)| dedup::chunk_hash(content)).collect())
+        .collect();
+    let mut stored = {
+        let mut distinct: Vec<String> = hashes.iter().flatten().cloned().collect();
+        distinct.sort();
+        distinct.dedup();
+        dedup::stored_hashes(table, &distinct).await?
+    };
 
     let mut pending_chunks: Vec<db::PendingChunk> = Vec::new();
+    let mut refs: Vec<(String, String, i64)> = Vec::new();
     let mut batches_written = 0;
-
-    for (idx, ef) in all_extracted.iter().enumerate() {
-        let safe_path = ef.path.replace('\'', "''");
-        let _ = table.delete(&format!("path = '{}'", safe_path)).await;
-
-        for chunk in &ef.chunks {
+    let mut deduped = 0usize;
+    let chunks_total = hashes
+        .iter()
+        .map(|h| h.iter().collect::<std::collections::HashSet<_>>().len())
+        .sum();
+    let mut meter = progress::Meter::new(chunks_total);
+    let mut pending_bytes = 0;
+
+    for (idx, (ef, ef_hashes)) in all_extracted.iter().zip(&hashes).enumerate() {
+        let mut file_hashes = std::collections::HashSet::new();
+        pending_bytes += ef.bytes;
+        for ((content, context, start), hash) in ef.chunks.iter().zip(ef_hashes) {
+            if !file_hashes.insert(hash) {
+                continue;
+            }
+            refs.push((hash.clone(), ef.path.clone(), ef.mtime));
+            if !stored.insert(hash.clone()) {
+                deduped += 1;
+                continue;
+            }
             pending_chunks.push(db::PendingChunk {
                 path: ef.path.clone(),
-                content: chunk.clone(),
+                content: content.clone(),
+                context: context.clone(),
                 mtime: ef.mtime,
+                lang: ef.lang.clone(),
+                chunk_hash: hash.clone(),
+                size: ef.size,
+                lines: ef.lines,
+                start: *start,
             });
         }
 
-        if pending_chunks.len() >= EMBED_BATCH_SIZE {
+        let batch_size = if power::is_low_power() { power::LOW_POWER_BATCH_SIZE } else { EMBED_BATCH_SIZE };
+        if pending_chunks.len() >= batch_size {
             batches_written += 1;
-            progress_callback(
-                idx + 1,
-                files_indexed,
-                format!("Embedding batch {}", batches_written),
-            );
+            progress_callback(meter.report(idx + 1, files_indexed, format!("Embedding batch {}", batches_written)));
 
             let batch_chunks: Vec<db::PendingChunk> = std::mem::take(&mut pending_chunks);
             let texts: Vec<String> = batch_chunks.iter().map(|c| c.content.clone()).collect();
@@ -253,27 +669,40 @@ where
                 .map(|(chunk, vector)| db::Record {
                     path: chunk.path,
                     content: chunk.content,
+                    context: chunk.context,
                     vector,
                     mtime: chunk.mtime,
+                    lang: chunk.lang,
+                    chunk_hash: chunk.chunk_hash,
+                    size: chunk.size,
+                    lines: chunk.lines,
+                    start: chunk.start,
                 })
                 .collect();
 
+            let records_len = records.len();
             let batch = db::create_record_batch(records)?;
             let schema = batch.schema();
             table
                 .add(RecordBatchIterator::new(vec![Ok(batch)], schema))
                 .execute()
                 .await?;
+            // Refs go with each batch so every file up to this one is
+            // complete if the job stops here.
+            let batch_refs = std::mem::take(&mut refs);
+            meter.written(batch_refs.len(), records_len, std::mem::take(&mut pending_bytes));
+            dedup::add_refs(db, table_name, batch_refs).await?;
+            journal::files_written(table_name, idx + 1);
+
+            if power::is_low_power() {
+                tokio::time::sleep(power::LOW_POWER_BATCH_PAUSE).await;
+            }
         }
     }
 
     if !pending_chunks.is_empty() {
         batches_written += 1;
-        progress_callback(
-            files_indexed,
-            files_indexed,
-            format!("Embedding batch {}", batches_written),
-        );
+        progress_callback(meter.report(files_indexed, files_indexed, format!("Embedding batch {}", batches_written)));
 
         let texts: Vec<String> = pending_chunks.iter().map(|c| c.content.clone()).collect();
         let embeddings = embed_batch(provider_state, texts).await?;
@@ -284,41 +713,90 @@ where
             .map(|(chunk, vector)| db::Record {
                 path: chunk.path,
                 content: chunk.content,
+                context: chunk.context,
                 vector,
                 mtime: chunk.mtime,
+                lang: chunk.lang,
+                chunk_hash: chunk.chunk_hash,
+                size: chunk.size,
+                lines: chunk.lines,
+                start: chunk.start,
             })
             .collect();
 
+        let records_len = records.len();
         let batch = db::create_record_batch(records)?;
         let schema = batch.schema();
         table
             .add(RecordBatchIterator::new(vec![Ok(batch)], schema))
             .execute()
             .await?;
+        meter.written(refs.len(), records_len, pending_bytes);
+    }
+
+    dedup::add_refs(db, table_name, refs).await?;
+    journal::files_written(table_name, files_indexed);
+    if let Err(e) = file_vectors::update(db, table, table_name, &changed_paths).await {
+        warn!("Updating file vectors failed: {}", e);
+    }
+    if indexing_config.entities {
+        if let Err(e) = entities::update(db, table, table_name, &changed_paths).await {
+            warn!("Updating entities failed: {}", e);
+        }
+    }
+    if let Err(e) = tombstones::unbury(db, table_name, &changed_paths).await {
+        warn!("Clearing tombstones failed: {}", e);
+    }
+    secrets::record(table_name, all_extracted.iter().map(|ef| (ef.path.clone(), ef.secrets.clone())));
+    if deduped > 0 {
+        info!("Skipped {} chunks already in the index", deduped);
+    }
+
+    if let Err(e) = versions::prune(db, table_name, indexing_config.version_history_days).await {
+        warn!("Pruning old versions failed: {}", e);
     }
 
-    let total_indexed = total_files - image_files.len() + files_indexed;
+    let total_indexed = total_files - image_files + files_indexed;
 
     if total_indexed >= ANN_INDEX_THRESHOLD {
-        progress_callback(files_indexed, files_indexed, "Building vector index...".to_string());
-        let _ = db::build_ann_index(&table).await;
+        progress_callback(meter.report(files_indexed, files_indexed, "Building vector index...".to_string()));
+        let _ = db::build_ann_index(table).await;
     }
 
-    progress_callback(files_indexed, files_indexed, "Building search index...".to_string());
-    let _ = db::build_fts_index(&table).await;
+    progress_callback(meter.report(files_indexed, files_indexed, "Updating search index...".to_string()));
+    if let Err(e) = db::update_fts_index(table).await {
+        warn!("FTS index update failed: {}", e);
+    }
 
+    if indexing_config.summaries.is_active() {
+        progress_callback(meter.report(files_indexed, files_indexed, "Summarizing folders...".to_string()));
+        let changed = [changed_paths.as_slice(), &superseded].concat();
+        if let Err(e) = summaries::refresh(db, table, table_name, &indexing_config.summaries, provider_state, &root_dir, &changed).await {
+            warn!("Updating summaries failed: {}", e);
+        }
+    }
+
+    if let Some(checkpoint) = checkpoint {
+        change_journal::save(table_name, &root_dir, checkpoint);
+    }
     info!("Indexing complete: {} files indexed in {}", files_indexed, root_dir);
     Ok(files_indexed)
 }
 
+#[allow(clippy::too_many_arguments)]
 pub async fn index_single_file(
     file_path: &std::path::Path,
     table_name: &str,
     db: &Connection,
     provider_state: &Arc<Mutex<ProviderState>>,
     use_git_history: bool,
+    ocr: bool,
     chunk_size: Option<usize>,
     chunk_overlap: Option<usize>,
+    version_history_days: u32,
+    extract_entities: bool,
+    extractors: &external::Extractors,
+    secret_policy: secrets::SecretPolicy,
 ) -> Result<bool> {
     debug!("index_single_file: {}", file_path.display());
     if !file_path.is_file() {
@@ -330,14 +808,22 @@ pub async fn index_single_file(
     let path_str = file_path.to_string_lossy().to_string();
     let mtime = file_io::get_file_mtime(file_path);
 
-    if let Ok(Some(existing_mtime)) = db::get_single_file_mtime(&table, &path_str).await {
-        if existing_mtime == mtime {
-            return Ok(false);
-        }
+    let existing_mtime = match db::get_single_file_mtime(&table, &path_str).await {
+        Ok(Some(existing)) => Some(existing),
+        _ => dedup::ref_mtime(db, table_name, &path_str).await.ok().flatten(),
+    };
+    if existing_mtime == Some(mtime) {
+        return Ok(false);
     }
 
+    if existing_mtime.is_some() {
+        let replaced = std::collections::HashMap::from([(path_str.clone(), mtime)]);
+        if let Err(e) = versions::archive(db, &table, table_name, &replaced, version_history_days).await {
+            warn!("Keeping old version of {} failed: {}", path_str, e);
+        }
+    }
     let safe_path = path_str.replace('\'', "''");
-    let _ = table.delete(&format!("path = '{}'", safe_path)).await;
+    let _ = dedup::remove(db, &table, table_name, &format!("path = '{}'", safe_path)).await;
 
     let ext = file_path
         .extension()
@@ -345,59 +831,134 @@ pub async fn index_single_file(
         .unwrap_or("")
         .to_lowercase();
 
-    let text = if ocr::is_image_extension(&ext) {
+    let text = if extractors.handles(&ext) {
+        let (extractors, ext, path) = (extractors.clone(), ext.clone(), file_path.to_path_buf());
+        tokio::task::spawn_blocking(move || extractors.extract(&ext, &path)).await.ok().flatten()
+    } else if ocr && ocr::is_image_extension(&ext) {
         file_io::read_file_content_with_ocr(file_path).await
     } else {
         file_io::read_file_content(file_path)
     };
 
-    let mut text = match text {
+    let text = match text {
         Some(t) if !t.trim().is_empty() => t,
         _ => return Ok(false),
     };
-    if use_git_history {
-        if let Some(git_ctx) = git::get_commit_context(file_path) {
-            text.push_str(&git_ctx);
-        }
-    }
+    let lang = language::detect_language(&text);
+    let (size, lines) = (file_io::get_file_size(file_path), text.lines().count() as i64);
+    let git_ctx = if use_git_history { git::get_commit_context(file_path) } else { None };
 
-    let chunks = chunking::semantic_chunk_with_overrides(&text, &ext, chunk_size, chunk_overlap);
-    if chunks.is_empty() {
+    let file_name = file_path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+    let mut labeled = chunk_file(&text, git_ctx, &ext, file_name, chunk_size, chunk_overlap);
+    let flagged = secrets::screen(&mut labeled, secret_policy);
+    secrets::record(table_name, [(path_str.clone(), flagged)]);
+    if labeled.is_empty() {
         return Ok(false);
     }
-    let file_name = file_path.file_name().and_then(|n| n.to_str()).unwrap_or("");
-    let texts: Vec<String> = chunks.into_iter().map(|c| format!("File: {}\n{}", file_name, c)).collect();
-    let embeddings = embed_batch(provider_state, texts.clone()).await?;
-
-    let records: Vec<db::Record> = texts
+    let mut hashes: Vec<String> = labeled.iter().map(|(content, _, _)| dedup::chunk_hash(content)).collect();
+    let stored = dedup::stored_hashes(&table, &hashes).await?;
+    let mut seen = std::collections::HashSet::new();
+    let (labeled, new_hashes): (Vec<_>, Vec<_>) = labeled
         .into_iter()
-        .zip(embeddings)
-        .map(|(content, vector)| db::Record {
-            path: path_str.clone(),
-            content,
-            vector,
-            mtime,
-        })
-        .collect();
+        .zip(hashes.iter().cloned())
+        .filter(|(_, hash)| !stored.contains(hash) && seen.insert(hash.clone()))
+        .unzip();
+
+    if !labeled.is_empty() {
+        let texts: Vec<String> = labeled.iter().map(|(content, _, _)| content.clone()).collect();
+        let embeddings = embed_batch(provider_state, texts).await?;
 
-    let batch = db::create_record_batch(records)?;
-    let schema = batch.schema();
-    table
-        .add(RecordBatchIterator::new(vec![Ok(batch)], schema))
-        .execute()
-        .await?;
+        let records: Vec<db::Record> = labeled
+            .into_iter()
+            .zip(embeddings)
+            .zip(new_hashes)
+            .map(|(((content, context, start), vector), chunk_hash)| db::Record {
+                path: path_str.clone(),
+                content,
+                context,
+                vector,
+                mtime,
+                lang: lang.clone(),
+                chunk_hash,
+                size,
+                lines,
+                start,
+            })
+            .collect();
+
+        let batch = db::create_record_batch(records)?;
+        let schema = batch.schema();
+        table
+            .add(RecordBatchIterator::new(vec![Ok(batch)], schema))
+            .execute()
+            .await?;
+    }
+
+    hashes.sort();
+    hashes.dedup();
+    dedup::add_refs(db, table_name, hashes.into_iter().map(|h| (h, path_str.clone(), mtime)).collect()).await?;
+    if let Err(e) = tombstones::unbury(db, table_name, std::slice::from_ref(&path_str)).await {
+        warn!("Clearing tombstone of {} failed: {}", path_str, e);
+    }
+    if let Err(e) = file_vectors::update(db, &table, table_name, std::slice::from_ref(&path_str)).await {
+        warn!("Updating file vector of {} failed: {}", path_str, e);
+    }
+    if extract_entities {
+        if let Err(e) = entities::update(db, &table, table_name, std::slice::from_ref(&path_str)).await {
+            warn!("Updating entities of {} failed: {}", path_str, e);
+        }
+    }
 
     Ok(true)
 }
 
+/// The file's rows stay findable for as long as `retention` says; callers
+/// removing a file that wasn't deleted pass `Retention::default()`.
 pub async fn delete_file_from_index(
     file_path: &str,
     table_name: &str,
     db: &Connection,
+    retention: Retention,
 ) -> Result<()> {
     debug!("delete_file_from_index: {}", file_path);
     let table = db.open_table(table_name).execute().await?;
+    let gone = std::collections::HashMap::from([(file_path.to_string(), chrono::Utc::now().timestamp())]);
+    if let Err(e) = versions::archive(db, &table, table_name, &gone, retention.history_days).await {
+        warn!("Keeping old version of {} failed: {}", file_path, e);
+    }
+    if let Err(e) = tombstones::bury(db, &table, table_name, file_path, retention.deleted_days).await {
+        warn!("Keeping deleted {} failed: {}", file_path, e);
+    }
     let safe_path = file_path.replace('\'', "''");
-    table.delete(&format!("path = '{}'", safe_path)).await?;
+    dedup::remove(db, &table, table_name, &format!("path = '{}'", safe_path)).await?;
+    secrets::forget(table_name, Some(file_path));
     Ok(())
 }
+
+/// Drops every row under `dir` that is not also covered by one of `other_roots`.
+/// Returns false when nothing was deleted because another root still covers `dir`.
+pub async fn delete_folder_from_index(
+    dir: &str,
+    other_roots: &[String],
+    table_name: &str,
+    db: &Connection,
+) -> Result<bool> {
+    let Some(filter) = build_folder_filter(dir, other_roots) else {
+        debug!("delete_folder_from_index: {} is inside another indexed folder, keeping rows", dir);
+        return Ok(false);
+    };
+    info!("delete_folder_from_index: {}", dir);
+    change_journal::forget(table_name, Some(dir));
+    secrets::forget(table_name, Some(dir));
+    let table = match db.open_table(table_name).execute().await {
+        Ok(t) => t,
+        Err(_) => return Ok(false),
+    };
+    dedup::remove(db, &table, table_name, &filter).await?;
+    Ok(true)
+}
+
+pub async fn refresh_fts_index(table_name: &str, db: &Connection) -> Result<()> {
+    let table = db.open_table(table_name).execute().await?;
+    db::update_fts_index(&table).await
+}
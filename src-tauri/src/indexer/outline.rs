@@ -0,0 +1,178 @@
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+static MARKDOWN_HEADING: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^(#{1,6})\s+(.+?)\s*#*\s*$").unwrap());
+
+/// A declaration at the start of a line, modifiers skipped. Captures the
+/// indentation and the declared name; for `impl X for Y` the name is the whole
+/// "X for Y", for Go methods the receiver is skipped.
+static SYMBOL: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"^(\s*)(?:(?:export|default|pub(?:\([^)]*\))?|public|private|protected|internal|static|async|abstract|final|override|unsafe|extern|open|sealed|data|inline|suspend|local)\s+)*(?:fn|def|defp|defmodule|function|func|class|struct|enum|trait|interface|impl|mod|module|object|protocol|extension|sub|proc)(?:<[^>]*>)?\s+(?:\([^)]*\)\s*)?([A-Za-z_$][\w$]*(?:<[^>]*>)?(?:\s+for\s+[A-Za-z_$][\w$:]*)?)",
+    )
+    .unwrap()
+});
+
+#[derive(Clone, Copy, PartialEq)]
+enum Kind {
+    Markdown,
+    Code,
+}
+
+fn kind_for(ext: &str) -> Option<Kind> {
+    match ext {
+        "md" | "markdown" => Some(Kind::Markdown),
+        _ if super::chunking::is_code_ext(ext) => Some(Kind::Code),
+        _ => None,
+    }
+}
+
+/// Byte offset of each line start paired with the context in effect from that
+/// line on. Only lines that change the context get an entry.
+fn context_events(text: &str, kind: Kind) -> Vec<(usize, String)> {
+    let mut events = Vec::new();
+    let mut offset = 0;
+    let mut headings: Vec<(usize, String)> = Vec::new();
+    let mut symbols: Vec<(usize, String)> = Vec::new();
+    let mut in_fence = false;
+
+    for line in text.split_inclusive('\n') {
+        let start = offset;
+        offset += line.len();
+        let line = line.trim_end();
+
+        match kind {
+            Kind::Markdown => {
+                let trimmed = line.trim_start();
+                if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+                    in_fence = !in_fence;
+                    continue;
+                }
+                if in_fence {
+                    continue;
+                }
+                if let Some(caps) = MARKDOWN_HEADING.captures(line) {
+                    let level = caps[1].len();
+                    headings.retain(|(l, _)| *l < level);
+                    headings.push((level, caps[2].to_string()));
+                    let path: Vec<&str> = headings.iter().map(|(_, h)| h.as_str()).collect();
+                    events.push((start, path.join(" > ")));
+                }
+            }
+            Kind::Code => {
+                let indent = line.len() - line.trim_start().len();
+                let closer = {
+                    let t = line.trim_start();
+                    t.starts_with('}') || t == "end"
+                };
+                if let Some(caps) = SYMBOL.captures(line) {
+                    let indent = caps[1].len();
+                    symbols.retain(|(i, _)| *i < indent);
+                    symbols.push((indent, caps[2].to_string()));
+                } else if closer && symbols.last().is_some_and(|(i, _)| *i >= indent) {
+                    symbols.retain(|(i, _)| *i < indent);
+                } else {
+                    continue;
+                }
+                let path: Vec<&str> = symbols.iter().map(|(_, s)| s.as_str()).collect();
+                events.push((start, path.join(" > ")));
+            }
+        }
+    }
+    events
+}
+
+/// Where each chunk sits in its file: the markdown heading path
+/// ("Setup > Windows") or the enclosing declarations ("Config > load") at the
+/// chunk's opening lines. Empty for file types without structure or chunks
+/// before the first heading or declaration.
+pub fn chunk_contexts(text: &str, chunks: &[String], ext: &str) -> Vec<String> {
+    let Some(kind) = kind_for(ext) else {
+        return vec![String::new(); chunks.len()];
+    };
+    let events = context_events(text, kind);
+    if events.is_empty() {
+        return vec![String::new(); chunks.len()];
+    }
+
+    let mut cursor = 0;
+    chunks
+        .iter()
+        .map(|chunk| {
+            let mut probe_end = chunk.len().min(64);
+            while !chunk.is_char_boundary(probe_end) {
+                probe_end -= 1;
+            }
+            if let Some(pos) = text[cursor..].find(&chunk[..probe_end]) {
+                cursor += pos;
+            }
+            // Semantic chunks repeat the previous chunk's last line, so the
+            // declaration a chunk starts with can be on its second line.
+            let mut anchor = cursor;
+            let mut lines_seen = 0;
+            for line in chunk.split_inclusive('\n') {
+                anchor += line.len();
+                if !line.trim().is_empty() {
+                    lines_seen += 1;
+                    if lines_seen == 2 {
+                        break;
+                    }
+                }
+            }
+            let context = events
+                .iter()
+                .take_while(|(pos, _)| *pos < anchor)
+                .last()
+                .map(|(_, c)| c.clone())
+                .unwrap_or_default();
+            if let Some(c) = text[cursor..].chars().next() {
+                cursor += c.len_utf8();
+            }
+            context
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_markdown_heading_path() {
+        let text = "# Guide\nintro\n## Setup\n```\n# not a heading\n```\n### Windows\nrun it\n## Usage\nsearch\n";
+        let chunks = vec![
+            "# Guide\nintro\n".to_string(),
+            "### Windows\nrun it\n".to_string(),
+            "## Usage\nsearch\n".to_string(),
+        ];
+        assert_eq!(chunk_contexts(text, &chunks, "md"), vec!["Guide", "Guide > Setup > Windows", "Guide > Usage"]);
+    }
+
+    #[test]
+    fn test_code_symbols() {
+        let text = "use std::fs;\n\nimpl Config {\n    pub fn load() {\n        read();\n    }\n}\n\npub async fn run() {\n    go();\n}\n";
+        let chunks = vec![
+            "use std::fs;\n".to_string(),
+            "    pub fn load() {\n        read();\n".to_string(),
+            "}\n\npub async fn run() {\n    go();\n}\n".to_string(),
+        ];
+        assert_eq!(chunk_contexts(text, &chunks, "rs"), vec!["", "Config > load", "run"]);
+    }
+
+    #[test]
+    fn test_symbol_names() {
+        let name = |line: &str| SYMBOL.captures(line).map(|c| c[2].to_string());
+        assert_eq!(name("impl<T> Display for Wrapper<T> {").as_deref(), Some("Display for Wrapper"));
+        assert_eq!(name("func (s *Server) Start() error {").as_deref(), Some("Start"));
+        assert_eq!(name("export default class App extends X {").as_deref(), Some("App"));
+        assert_eq!(name("    def __init__(self):").as_deref(), Some("__init__"));
+        assert_eq!(name("// fn commented()"), None);
+    }
+
+    #[test]
+    fn test_unstructured_ext() {
+        assert_eq!(chunk_contexts("a\nb", &["a\nb".to_string()], "txt"), vec![""]);
+    }
+}
@@ -8,6 +8,10 @@ pub struct ScoredResult {
     pub score: f32,
 }
 
+/// Pinned files jump to the top as long as they clear this score; below it they
+/// are probably unrelated to the query and would only get in the way.
+pub const PIN_SCORE_FLOOR: f32 = 25.0;
+
 pub fn score_results(
     final_results: Vec<(String, String, f32)>,
     used_reranker: bool,
@@ -92,6 +96,29 @@ pub fn score_results(
     scored
 }
 
+/// Takes the "Context:" line written under the "File:" header at index time
+/// out of a snippet so it can be shown on its own. Returns the context (empty
+/// if there is none) and the snippet without that line.
+pub fn split_context(snippet: &str) -> (String, String) {
+    if let Some((header, rest)) = snippet.split_once('\n') {
+        if header.starts_with("File: ") {
+            if let Some(after) = rest.strip_prefix("Context: ") {
+                let (context, body) = after.split_once('\n').unwrap_or((after, ""));
+                return (context.to_string(), format!("{}\n{}", header, body));
+            }
+        }
+    }
+    (String::new(), snippet.to_string())
+}
+
+/// The page a PDF chunk was taken from, read back from its "p.14" context.
+pub fn page_number(path: &str, context: &str) -> Option<u32> {
+    if !path.to_lowercase().ends_with(".pdf") {
+        return None;
+    }
+    context.strip_prefix("p.")?.parse().ok()
+}
+
 fn snippet_similarity(a: &str, b: &str) -> f32 {
     let set_a: std::collections::HashSet<&str> = a.split_whitespace().collect();
     let set_b: std::collections::HashSet<&str> = b.split_whitespace().collect();
@@ -159,10 +186,50 @@ pub fn mmr_select(candidates: Vec<ScoredResult>, k: usize, lambda: f32) -> Vec<S
     result
 }
 
+/// Splits `candidates` into pinned hits scoring at least `floor` (one per path, in
+/// score order) and everything else.
+pub fn split_pinned(
+    candidates: Vec<ScoredResult>,
+    pinned: &[String],
+    floor: f32,
+) -> (Vec<ScoredResult>, Vec<ScoredResult>) {
+    let mut hits: Vec<ScoredResult> = Vec::new();
+    let mut rest = Vec::with_capacity(candidates.len());
+    for candidate in candidates {
+        let is_pinned = candidate.score >= floor && pinned.contains(&candidate.path);
+        if is_pinned && !hits.iter().any(|h| h.path == candidate.path) {
+            hits.push(candidate);
+        } else if !is_pinned {
+            rest.push(candidate);
+        }
+    }
+    if !hits.is_empty() {
+        debug!("split_pinned: {} pinned hits above {:.0}", hits.len(), floor);
+    }
+    (hits, rest)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_split_context() {
+        assert_eq!(
+            split_context("File: a.md\nContext: Setup > Windows\nrun it"),
+            ("Setup > Windows".to_string(), "File: a.md\nrun it".to_string())
+        );
+        assert_eq!(split_context("File: a.txt\nplain"), (String::new(), "File: a.txt\nplain".to_string()));
+        assert_eq!(split_context("[annotation] Context: x"), (String::new(), "[annotation] Context: x".to_string()));
+    }
+
+    #[test]
+    fn test_page_number() {
+        assert_eq!(page_number("C:\\docs\\report.PDF", "p.14"), Some(14));
+        assert_eq!(page_number("notes.md", "p.14"), None);
+        assert_eq!(page_number("report.pdf", "Setup"), None);
+    }
+
     #[test]
     fn test_mmr_preserves_order_with_lambda_one() {
         let candidates = vec![
@@ -338,6 +405,22 @@ mod tests {
         assert!(scored[0].score <= 100.0);
     }
 
+    #[test]
+    fn test_split_pinned() {
+        let candidates = vec![
+            ScoredResult { path: "a".into(), snippet: "a".into(), score: 90.0 },
+            ScoredResult { path: "b".into(), snippet: "b".into(), score: 60.0 },
+            ScoredResult { path: "b".into(), snippet: "b2".into(), score: 55.0 },
+            ScoredResult { path: "c".into(), snippet: "c".into(), score: 10.0 },
+        ];
+        let pinned = vec!["b".to_string(), "c".to_string()];
+        let (hits, rest) = split_pinned(candidates, &pinned, PIN_SCORE_FLOOR);
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].snippet, "b");
+        let rest: Vec<&str> = rest.iter().map(|r| r.path.as_str()).collect();
+        assert_eq!(rest, vec!["a", "c"]);
+    }
+
     #[test]
     fn test_score_results_empty() {
         let scored = score_results(vec![], false, true, 10);
@@ -0,0 +1,75 @@
+use std::time::{Duration, Instant};
+
+use crate::state::IndexingProgress;
+
+/// Too little to go on before this; an early guess swings wildly between
+/// the first batches.
+const MIN_SAMPLE: Duration = Duration::from_secs(2);
+
+/// Counts a job's embedding work and turns it into progress events with a
+/// rate and a time left estimate.
+pub struct Meter {
+    started: Instant,
+    chunks_total: usize,
+    processed: usize,
+    embedded: usize,
+    bytes: u64,
+}
+
+impl Meter {
+    /// Starts the clock for `chunks_total` chunks to go through.
+    pub fn new(chunks_total: usize) -> Self {
+        Self { started: Instant::now(), chunks_total, processed: 0, embedded: 0, bytes: 0 }
+    }
+
+    /// Notes `chunks` more as written, from files holding `bytes` of text:
+    /// `embedded` of them needed the model, the rest were already stored.
+    pub fn written(&mut self, chunks: usize, embedded: usize, bytes: u64) {
+        self.processed += chunks;
+        self.embedded += embedded;
+        self.bytes += bytes;
+    }
+
+    /// Chunks written so far.
+    pub fn processed(&self) -> usize {
+        self.processed
+    }
+
+    pub fn report(&self, current: usize, total: usize, path: String) -> IndexingProgress {
+        let (chunks_per_sec, eta_secs) = rate_and_eta(self.processed, self.chunks_total, self.started.elapsed());
+        IndexingProgress {
+            current,
+            total,
+            path,
+            bytes: self.bytes,
+            chunks: self.embedded,
+            chunks_total: self.chunks_total,
+            chunks_per_sec,
+            eta_secs,
+        }
+    }
+}
+
+/// Chunks per second so far, and seconds left for the rest at that rate.
+pub fn rate_and_eta(done: usize, total: usize, elapsed: Duration) -> (f32, Option<u64>) {
+    if done == 0 || elapsed < MIN_SAMPLE {
+        return (0.0, None);
+    }
+    let rate = done as f32 / elapsed.as_secs_f32();
+    let left = total.saturating_sub(done) as f32 / rate;
+    (rate, Some(left.ceil() as u64))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rate_and_eta() {
+        assert_eq!(rate_and_eta(0, 1000, Duration::from_secs(10)), (0.0, None));
+        assert_eq!(rate_and_eta(100, 1000, Duration::from_secs(1)), (0.0, None));
+        assert_eq!(rate_and_eta(200, 1000, Duration::from_secs(4)), (50.0, Some(16)));
+        assert_eq!(rate_and_eta(1000, 1000, Duration::from_secs(4)), (250.0, Some(0)));
+        assert_eq!(rate_and_eta(1200, 1000, Duration::from_secs(4)), (300.0, Some(0)));
+    }
+}
@@ -0,0 +1,139 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, LazyLock, Mutex};
+use std::time::Duration;
+
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+
+use super::file_io;
+
+/// Longest a single PDF or image gets. Nothing legitimate under
+/// `MAX_FILE_SIZE` comes close; a file that does is stuck in a loop.
+pub const EXTRACT_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// A file extraction gave up on, and why. It's skipped until it changes.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Skipped {
+    pub mtime: i64,
+    pub reason: String,
+    /// Unix seconds it was skipped.
+    pub at: i64,
+}
+
+static SKIPPED: LazyLock<Mutex<HashMap<String, Skipped>>> = LazyLock::new(|| Mutex::new(load()));
+
+fn skip_list_path() -> PathBuf {
+    crate::config::app_data_dir().join("skipped_files.json")
+}
+
+fn load() -> HashMap<String, Skipped> {
+    std::fs::read_to_string(skip_list_path())
+        .ok()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+fn save(skipped: &HashMap<String, Skipped>) {
+    let path = skip_list_path();
+    match serde_json::to_string_pretty(skipped) {
+        Ok(json) => {
+            if let Err(e) = std::fs::write(&path, json) {
+                warn!("Failed to write skip list {:?}: {}", path, e);
+            }
+        }
+        Err(e) => warn!("Failed to serialize skip list: {}", e),
+    }
+}
+
+pub(super) fn is_skipped(path: &str, mtime: i64) -> bool {
+    SKIPPED
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .get(path)
+        .is_some_and(|s| s.mtime == mtime)
+}
+
+pub(super) fn skip(path: &str, mtime: i64, reason: &str) {
+    warn!("Skipping {} until it changes: {}", path, reason);
+    let mut skipped = SKIPPED.lock().unwrap_or_else(|e| e.into_inner());
+    skipped.insert(
+        path.to_string(),
+        Skipped { mtime, reason: reason.to_string(), at: chrono::Utc::now().timestamp() },
+    );
+    save(&skipped);
+}
+
+#[derive(Debug, PartialEq)]
+enum Outcome<T> {
+    Done(T),
+    Panicked,
+    TimedOut,
+}
+
+/// Runs `extract` on a thread of its own. A panic ends only that thread; a
+/// hang leaves it behind to finish or spin on its own, since a thread can't
+/// be killed, but the caller moves on.
+fn watch<T: Send + 'static>(timeout: Duration, extract: impl FnOnce() -> T + Send + 'static) -> Outcome<T> {
+    let (tx, rx) = mpsc::channel();
+    let spawned = std::thread::Builder::new().name("extract".to_string()).spawn(move || {
+        let _ = tx.send(extract());
+    });
+    if let Err(e) = spawned {
+        warn!("Failed to start an extraction thread: {}", e);
+        return Outcome::Panicked;
+    }
+    match rx.recv_timeout(timeout) {
+        Ok(value) => Outcome::Done(value),
+        Err(mpsc::RecvTimeoutError::Disconnected) => Outcome::Panicked,
+        Err(mpsc::RecvTimeoutError::Timeout) => Outcome::TimedOut,
+    }
+}
+
+/// Extracts `path` with `extract` unless it's on the skip list, puts it on
+/// the list when extraction panics or takes longer than `EXTRACT_TIMEOUT`.
+/// For parsers that can hang or crash on a malformed file.
+pub fn guarded<T: Send + 'static>(path: &Path, extract: impl FnOnce() -> Option<T> + Send + 'static) -> Option<T> {
+    let path_str = path.to_string_lossy().to_string();
+    let mtime = file_io::get_file_mtime(path);
+    if is_skipped(&path_str, mtime) {
+        debug!("{} is on the skip list", path_str);
+        return None;
+    }
+    match watch(EXTRACT_TIMEOUT, extract) {
+        Outcome::Done(value) => value,
+        Outcome::Panicked => {
+            skip(&path_str, mtime, "extraction crashed");
+            None
+        }
+        Outcome::TimedOut => {
+            skip(&path_str, mtime, &format!("extraction took over {}s", EXTRACT_TIMEOUT.as_secs()));
+            None
+        }
+    }
+}
+
+/// `guarded` for async callers, waiting without holding up a runtime
+/// thread. `extract` still gets a thread of its own, where it may block.
+pub async fn guarded_async<T: Send + 'static>(
+    path: &Path,
+    extract: impl FnOnce() -> Option<T> + Send + 'static,
+) -> Option<T> {
+    let path = path.to_path_buf();
+    tokio::task::spawn_blocking(move || guarded(&path, extract)).await.ok().flatten()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_watch() {
+        assert_eq!(watch(Duration::from_secs(5), || 42), Outcome::Done(42));
+        assert_eq!(watch(Duration::from_secs(5), || -> i32 { panic!("malformed xref") }), Outcome::Panicked);
+        assert_eq!(
+            watch(Duration::from_millis(50), || std::thread::sleep(Duration::from_secs(2))),
+            Outcome::TimedOut
+        );
+    }
+}
@@ -0,0 +1,204 @@
+//! Filters typed into the query instead of picked from chips: `in:` for a
+//! folder, `size:` and `lines:` for how big a file is. They come out of the
+//! query before it's embedded or matched, and go into the search's filter.
+
+use super::db::{LINES_COLUMN, SIZE_COLUMN};
+use super::search::escape_like;
+
+const KB: f64 = 1024.0;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct QueryFilters {
+    /// Part of a folder path, see `path_has_folder`.
+    pub folder: Option<String>,
+    pub comparisons: Vec<Comparison>,
+}
+
+/// A numeric column compared with a value, like `size > 10485760`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Comparison {
+    pub column: &'static str,
+    pub op: &'static str,
+    pub value: i64,
+}
+
+impl Comparison {
+    /// `expr` for `column`, like `>10mb` for size or `<=200` for lines; no
+    /// operator means `=`. Sizes take b, kb, mb and gb, in powers of 1024,
+    /// and are bytes without one; line counts take k for thousands.
+    pub fn parse(column: &'static str, expr: &str) -> Option<Self> {
+        let expr = expr.trim();
+        let (op, value) = [">=", "<=", ">", "<", "="]
+            .iter()
+            .find_map(|op| expr.strip_prefix(op).map(|rest| (*op, rest)))
+            .unwrap_or(("=", expr));
+        let value = value.trim().to_lowercase();
+        let (number, unit) = value.split_at(value.find(|c: char| !c.is_ascii_digit() && c != '.').unwrap_or(value.len()));
+        let number: f64 = number.parse().ok()?;
+        let scale = match (column, unit) {
+            (_, "") => 1.0,
+            (SIZE_COLUMN, "b") => 1.0,
+            (SIZE_COLUMN, "kb" | "k") => KB,
+            (SIZE_COLUMN, "mb" | "m") => KB * KB,
+            (SIZE_COLUMN, "gb" | "g") => KB * KB * KB,
+            (LINES_COLUMN, "k") => 1000.0,
+            _ => return None,
+        };
+        Some(Self { column, op, value: (number * scale).round() as i64 })
+    }
+
+    /// Rows indexed before the column was kept have 0 in it; they're left
+    /// out rather than counted as tiny files.
+    fn clause(&self) -> String {
+        format!("({col} > 0 AND {col} {} {})", self.op, self.value, col = self.column)
+    }
+}
+
+impl QueryFilters {
+    pub fn is_empty(&self) -> bool {
+        self.folder.is_none() && self.comparisons.is_empty()
+    }
+
+    /// SQL clauses for `build_filter_expr`. A folder starts at a folder
+    /// name but may end partway through one, under either separator since
+    /// paths keep the one of the OS that indexed them.
+    pub fn clauses(&self) -> Vec<String> {
+        let mut clauses = Vec::new();
+        let parts = self.folder.as_deref().map(fragment_parts).unwrap_or_default();
+        if !parts.is_empty() {
+            let escaped: Vec<String> = parts.iter().map(|part| escape_like(part)).collect();
+            clauses.push(format!(
+                "(path LIKE '%/{}%' ESCAPE '\\' OR path LIKE '%\\\\{}%' ESCAPE '\\')",
+                escaped.join("/"),
+                escaped.join("\\\\")
+            ));
+        }
+        clauses.extend(self.comparisons.iter().map(Comparison::clause));
+        clauses
+    }
+
+    /// Whether a result that isn't a row of the table, like an annotation,
+    /// passes. There's no size or line count to check for one, so it only
+    /// does without those filters.
+    pub fn matches_path(&self, path: &str) -> bool {
+        self.comparisons.is_empty() && self.folder.as_deref().is_none_or(|folder| path_has_folder(path, folder))
+    }
+}
+
+/// The folder names in an `in:` fragment, without the separators between
+/// them, so `src/indexer` and `src\indexer` are the same fragment.
+fn fragment_parts(fragment: &str) -> Vec<&str> {
+    fragment.split(['/', '\\']).filter(|part| !part.is_empty()).collect()
+}
+
+/// Whether `path` matches `fragment` the way the folder clause does.
+pub fn path_has_folder(path: &str, fragment: &str) -> bool {
+    let parts = fragment_parts(fragment);
+    if parts.is_empty() {
+        return true;
+    }
+    let wanted = format!("/{}", parts.join("/"));
+    path.replace('\\', "/").contains(&wanted)
+}
+
+/// `query` without the filters typed into it, and the filters, as in
+/// `in:src/indexer size:>10kb lock contention`. Quotes allow spaces,
+/// `in:"meeting notes"`; with several folders, the last wins. A `size:` or
+/// `lines:` that doesn't parse stays part of the query.
+pub fn parse(query: &str) -> (String, QueryFilters) {
+    let mut filters = QueryFilters::default();
+    let typed = |word: &str| ["in:", "size:", "lines:"].iter().any(|key| word.starts_with(key));
+    if !query.split_whitespace().any(typed) {
+        return (query.to_string(), filters);
+    }
+    let mut words = Vec::new();
+    let mut rest = query.trim_start();
+    while !rest.is_empty() {
+        if let Some(value) = rest.strip_prefix("in:") {
+            let (value, next) = match value.strip_prefix('"') {
+                Some(quoted) => quoted.split_once('"').unwrap_or((quoted, "")),
+                None => value.split_once(char::is_whitespace).unwrap_or((value, "")),
+            };
+            if !value.trim().is_empty() {
+                filters.folder = Some(value.trim().to_string());
+            }
+            rest = next.trim_start();
+            continue;
+        }
+        let (word, next) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+        let comparison = word.split_once(':').and_then(|(key, expr)| match key {
+            "size" => Comparison::parse(SIZE_COLUMN, expr),
+            "lines" => Comparison::parse(LINES_COLUMN, expr),
+            _ => None,
+        });
+        match comparison {
+            Some(comparison) => filters.comparisons.push(comparison),
+            None => words.push(word),
+        }
+        rest = next.trim_start();
+    }
+    (words.join(" "), filters)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_folder() {
+        let (query, filters) = parse("in:src/indexer lock contention");
+        assert_eq!(query, "lock contention");
+        assert_eq!(filters.folder.as_deref(), Some("src/indexer"));
+
+        let (query, filters) = parse("budget in:\"meeting notes\" march");
+        assert_eq!(query, "budget march");
+        assert_eq!(filters.folder.as_deref(), Some("meeting notes"));
+
+        assert_eq!(parse("login:admin  in"), ("login:admin  in".to_string(), QueryFilters::default()));
+        assert_eq!(parse("in: docs"), ("docs".to_string(), QueryFilters::default()));
+    }
+
+    #[test]
+    fn test_parse_comparisons() {
+        let (query, filters) = parse("quarterly report size:>10mb lines:<=2k");
+        assert_eq!(query, "quarterly report");
+        assert_eq!(
+            filters.comparisons,
+            vec![
+                Comparison { column: SIZE_COLUMN, op: ">", value: 10 * 1024 * 1024 },
+                Comparison { column: LINES_COLUMN, op: "<=", value: 2000 },
+            ]
+        );
+        assert_eq!(Comparison::parse(SIZE_COLUMN, "1.5kb").map(|c| (c.op, c.value)), Some(("=", 1536)));
+        assert_eq!(Comparison::parse(LINES_COLUMN, ">10mb"), None);
+
+        // Not a filter after all, so it's searched for.
+        let (query, filters) = parse("size:large shirts");
+        assert_eq!(query, "size:large shirts");
+        assert!(filters.is_empty());
+    }
+
+    #[test]
+    fn test_clauses() {
+        let (_, filters) = parse("in:/src\\my_mod/ lines:>1000 x");
+        assert_eq!(
+            filters.clauses(),
+            vec![
+                "(path LIKE '%/src/my\\_mod%' ESCAPE '\\' OR path LIKE '%\\\\src\\\\my\\_mod%' ESCAPE '\\')".to_string(),
+                "(lines > 0 AND lines > 1000)".to_string(),
+            ]
+        );
+        assert!(parse("in:/ x").1.clauses().is_empty());
+    }
+
+    #[test]
+    fn test_path_has_folder() {
+        assert!(path_has_folder("/repo/src/indexer/lock.rs", "src/indexer"));
+        assert!(path_has_folder("C:\\repo\\src\\indexer\\lock.rs", "src/index"));
+        assert!(!path_has_folder("/repo/mysrc/indexer/lock.rs", "src/indexer"));
+        assert!(path_has_folder("/repo/a.rs", ""));
+
+        let (_, filters) = parse("in:src size:>1mb x");
+        assert!(!filters.matches_path("/repo/src/a.rs"));
+    }
+}
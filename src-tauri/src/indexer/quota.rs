@@ -0,0 +1,238 @@
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use arrow_array::{Int64Array, StringArray};
+use futures::TryStreamExt;
+use lancedb::connection::Connection;
+use lancedb::query::{ExecutableQuery, QueryBase};
+use lancedb::table::{CompactionOptions, OptimizeAction};
+use log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
+
+/// Headroom so the next few changes don't evict again straight away.
+const EVICT_TARGET: f64 = 0.9;
+pub const MB: u64 = 1024 * 1024;
+
+/// Kept next to the config rather than in the index, so a reader never
+/// writes it.
+#[derive(Serialize, Deserialize, Default)]
+pub struct QuotaLog {
+    #[serde(default)]
+    pub last_matched: HashMap<String, i64>,
+    /// Files evicted to stay under the quota. Folder indexing skips them
+    /// until one is edited and the watcher picks it up again.
+    #[serde(default)]
+    pub evicted: BTreeSet<String>,
+}
+
+fn log_path(table_name: &str) -> PathBuf {
+    crate::config::app_data_dir().join("quota").join(format!("{}.json", table_name))
+}
+
+pub fn load_log(table_name: &str) -> QuotaLog {
+    std::fs::read_to_string(log_path(table_name))
+        .ok()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+fn save_log(table_name: &str, log: &QuotaLog) {
+    let path = log_path(table_name);
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).ok();
+    }
+    match serde_json::to_string(log) {
+        Ok(json) => {
+            if let Err(e) = std::fs::write(&path, json) {
+                warn!("Failed to write quota log {:?}: {}", path, e);
+            }
+        }
+        Err(e) => warn!("Failed to serialize quota log: {}", e),
+    }
+}
+
+pub fn clear_log(table_name: &str) {
+    std::fs::remove_file(log_path(table_name)).ok();
+}
+
+pub fn record_matches<'a>(table_name: &str, paths: impl IntoIterator<Item = &'a str>) {
+    let now = chrono::Utc::now().timestamp();
+    let mut log = load_log(table_name);
+    for path in paths {
+        log.last_matched.insert(path.to_string(), now);
+    }
+    save_log(table_name, &log);
+}
+
+pub fn unevict<'a>(table_name: &str, paths: impl IntoIterator<Item = &'a str>) {
+    let mut log = load_log(table_name);
+    let before = log.evicted.len();
+    for path in paths {
+        log.evicted.remove(path);
+    }
+    if log.evicted.len() != before {
+        save_log(table_name, &log);
+    }
+}
+
+fn dir_size(path: &Path) -> u64 {
+    let Ok(entries) = std::fs::read_dir(path) else {
+        return 0;
+    };
+    entries
+        .filter_map(|e| e.ok())
+        .map(|e| match e.file_type() {
+            Ok(ft) if ft.is_dir() => dir_size(&e.path()),
+            _ => e.metadata().map(|m| m.len()).unwrap_or(0),
+        })
+        .sum()
+}
+
+/// Old versions included. None for object stores.
+pub fn index_size(db_uri: &str, table_name: &str) -> Option<u64> {
+    if db_uri.contains("://") {
+        return None;
+    }
+    Some(dir_size(&Path::new(db_uri).join(format!("{}.lance", table_name))))
+}
+
+pub fn to_mb(bytes: u64) -> u64 {
+    bytes.div_ceil(MB)
+}
+
+pub fn over_quota(db_uri: &str, table_name: &str, quota_mb: Option<u64>) -> Option<(u64, u64)> {
+    let quota = quota_mb? * MB;
+    let size = index_size(db_uri, table_name)?;
+    (size > quota).then_some((size, quota))
+}
+
+/// Never matched before matched long ago, and among equals the file that
+/// changed longest ago.
+pub fn pick_evictions(
+    files: &HashMap<String, (usize, i64)>,
+    last_matched: &HashMap<String, i64>,
+    rows_to_drop: usize,
+) -> Vec<String> {
+    let mut order: Vec<(&String, &(usize, i64))> = files.iter().collect();
+    order.sort_by_key(|(path, (_, mtime))| (last_matched.get(*path).copied().unwrap_or(0), *mtime, *path));
+
+    let mut dropped = 0;
+    order
+        .into_iter()
+        .take_while(|(_, (rows, _))| {
+            let more = dropped < rows_to_drop;
+            dropped += rows;
+            more
+        })
+        .map(|(path, _)| path.clone())
+        .collect()
+}
+
+async fn file_rows(table: &lancedb::Table) -> Result<HashMap<String, (usize, i64)>> {
+    let batches = table
+        .query()
+        .select(lancedb::query::Select::Columns(vec!["path".to_string(), "mtime".to_string()]))
+        .execute()
+        .await?
+        .try_collect::<Vec<_>>()
+        .await?;
+
+    let mut files: HashMap<String, (usize, i64)> = HashMap::new();
+    for batch in batches {
+        let paths = batch.column_by_name("path").and_then(|c| c.as_any().downcast_ref::<StringArray>());
+        let mtimes = batch.column_by_name("mtime").and_then(|c| c.as_any().downcast_ref::<Int64Array>());
+        if let (Some(paths), Some(mtimes)) = (paths, mtimes) {
+            for i in 0..batch.num_rows() {
+                let entry = files.entry(paths.value(i).to_string()).or_insert((0, mtimes.value(i)));
+                entry.0 += 1;
+            }
+        }
+    }
+    Ok(files)
+}
+
+/// Compacts and prunes old versions afterwards so the space is actually
+/// freed.
+pub async fn evict(db: &Connection, db_uri: &str, table_name: &str, quota: u64) -> Result<usize> {
+    let Some(size) = index_size(db_uri, table_name).filter(|&size| size > quota) else {
+        return Ok(0);
+    };
+    let table = db.open_table(table_name).execute().await?;
+    let files = file_rows(&table).await?;
+    let rows: usize = files.values().map(|(n, _)| n).sum();
+    let keep = (rows as f64 * (quota as f64 * EVICT_TARGET / size as f64)) as usize;
+
+    let mut log = load_log(table_name);
+    let victims = pick_evictions(&files, &log.last_matched, rows.saturating_sub(keep));
+    for filter in super::dedup::in_filters("path", &victims) {
+        super::dedup::remove(db, &table, table_name, &filter).await?;
+    }
+    for path in &victims {
+        log.last_matched.remove(path);
+        log.evicted.insert(path.clone());
+    }
+    save_log(table_name, &log);
+
+    table
+        .optimize(OptimizeAction::Compact { options: CompactionOptions::default(), remap_options: None })
+        .await?;
+    table
+        .optimize(OptimizeAction::Prune {
+            older_than: Some(chrono::TimeDelta::zero()),
+            delete_unverified: Some(false),
+            error_if_tagged_old_versions: Some(false),
+        })
+        .await?;
+
+    let after = index_size(db_uri, table_name).unwrap_or(0);
+    info!(
+        "Quota: evicted {} files from {} ({} MB -> {} MB)",
+        victims.len(),
+        table_name,
+        to_mb(size),
+        to_mb(after)
+    );
+    debug!("Quota: evicted {:?}", victims);
+    Ok(victims.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pick_evictions() {
+        let files = HashMap::from([
+            ("/a.rs".to_string(), (10, 300)),
+            ("/b.rs".to_string(), (10, 100)),
+            ("/c.rs".to_string(), (10, 200)),
+            ("/d.rs".to_string(), (10, 50)),
+        ]);
+        let last_matched = HashMap::from([("/d.rs".to_string(), 1_000), ("/a.rs".to_string(), 2_000)]);
+
+        assert_eq!(pick_evictions(&files, &last_matched, 15), vec!["/b.rs", "/c.rs"]);
+        assert_eq!(pick_evictions(&files, &last_matched, 25), vec!["/b.rs", "/c.rs", "/d.rs"]);
+        assert_eq!(pick_evictions(&files, &last_matched, 10), vec!["/b.rs"]);
+        assert!(pick_evictions(&files, &last_matched, 0).is_empty());
+        assert_eq!(pick_evictions(&files, &last_matched, 100).len(), 4);
+    }
+
+    #[test]
+    fn test_index_size() {
+        let dir = std::env::temp_dir().join(format!("rememex_quota_{}", std::process::id()));
+        let table = dir.join("c_Work.lance").join("data");
+        std::fs::create_dir_all(&table).unwrap();
+        std::fs::write(table.join("a.lance"), vec![0u8; 3000]).unwrap();
+        std::fs::write(dir.join("c_Work.lance").join("_latest.manifest"), vec![0u8; 100]).unwrap();
+        let uri = dir.to_string_lossy().to_string();
+
+        assert_eq!(index_size(&uri, "c_Work"), Some(3100));
+        assert_eq!(index_size(&uri, "c_Other"), Some(0));
+        assert_eq!(index_size("s3://bucket/index", "c_Work"), None);
+        assert_eq!(over_quota(&uri, "c_Work", None), None);
+        assert_eq!(over_quota(&uri, "c_Work", Some(1)), None);
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -0,0 +1,163 @@
+use std::sync::Arc;
+
+use anyhow::{anyhow, bail, Result};
+use arrow_array::{RecordBatch, RecordBatchIterator};
+use arrow_schema::{DataType, Schema};
+use futures::TryStreamExt;
+use lancedb::connection::Connection;
+use lancedb::query::ExecutableQuery;
+use lancedb::table::AddDataMode;
+use lancedb::Table;
+use log::{info, warn};
+
+use super::{change_journal, db, dedup, entities, file_vectors, secrets, snapshot, summaries, tombstones, versions};
+
+/// A full rebuild indexes into this table and only replaces the container's
+/// table once it's done, so search keeps working on the old index the whole
+/// time and a rebuild that fails leaves it as it was.
+pub fn rebuild_table_name(container_table: &str) -> String {
+    format!("{}_rebuild", container_table)
+}
+
+fn vector_dim(schema: &Schema) -> Option<i32> {
+    match schema.field_with_name("vector").ok()?.data_type() {
+        DataType::FixedSizeList(_, size) => Some(*size),
+        _ => None,
+    }
+}
+
+/// Makes `batches` the whole content of the table `name`. An existing table
+/// with the same columns is overwritten in a single commit, so readers see
+/// either the old rows or the new ones; anything else is recreated.
+pub(super) async fn replace(db: &Connection, name: &str, schema: Arc<Schema>, batches: Vec<RecordBatch>) -> Result<Table> {
+    let rows = RecordBatchIterator::new(batches.into_iter().map(Ok), schema.clone());
+    if let Ok(table) = db.open_table(name).execute().await {
+        if table.schema().await?.as_ref() == schema.as_ref() {
+            table.add(rows).mode(AddDataMode::Overwrite).execute().await?;
+            return Ok(table);
+        }
+        db.drop_table(name, &[]).await?;
+    }
+    Ok(db.create_table(name, rows).execute().await?)
+}
+
+/// Copies `source` into the table `name` a batch at a time, replacing what
+/// was there, and checks the row count before returning it. The table and
+/// the version it had go into `undo` first.
+async fn copy(db: &Connection, name: &str, source: &Table, undo: &mut Vec<(Table, u64)>) -> Result<(Table, usize)> {
+    let schema = source.schema().await?;
+    if let Ok(live) = db.open_table(name).execute().await {
+        if live.schema().await?.as_ref() == schema.as_ref() {
+            let version = live.version().await?;
+            undo.push((live, version));
+        }
+    }
+    let mut stream = source.query().execute().await?;
+    let first = stream.try_next().await?;
+    let mut rows = first.as_ref().map_or(0, |batch| batch.num_rows());
+    let table = replace(db, name, schema.clone(), first.into_iter().collect()).await?;
+    while let Some(batch) = stream.try_next().await? {
+        rows += batch.num_rows();
+        table.add(RecordBatchIterator::new(vec![Ok(batch)], schema.clone())).execute().await?;
+    }
+    let copied = table.count_rows(None).await?;
+    if copied != rows {
+        bail!("'{}' has {} rows after copying {}", name, copied, rows);
+    }
+    Ok((table, rows))
+}
+
+async fn restore(undo: Vec<(Table, u64)>) {
+    for (table, version) in undo.into_iter().rev() {
+        let restored = async {
+            table.checkout(version).await?;
+            table.restore().await
+        };
+        if let Err(e) = restored.await {
+            warn!("Restoring '{}' to version {} failed: {}", table.name(), version, e);
+        }
+    }
+}
+
+/// Swaps the finished rebuild of `container_table` in and drops it. Searches
+/// stay on the old version until the new one has its vector and keyword
+/// indexes. History and tombstones are kept unless the rebuild used a model
+/// with another dimension, which can't search them anymore.
+///
+/// A swap that fails puts the tables it overwrote back at their old version
+/// and leaves the rebuild for the resumed reindex to swap in again.
+pub async fn swap_in(db: &Connection, container_table: &str) -> Result<()> {
+    let rebuilt_name = rebuild_table_name(container_table);
+    swap(db, container_table, &rebuilt_name)
+        .await
+        .map_err(|e| anyhow!("Swapping in the rebuilt index failed: {}. The rebuild is still in '{}'", e, rebuilt_name))?;
+    db::reset_index(db, &rebuilt_name).await?;
+    Ok(())
+}
+
+async fn swap(db: &Connection, container_table: &str, rebuilt_name: &str) -> Result<()> {
+    let rebuilt = db.open_table(rebuilt_name).execute().await?;
+    let schema = rebuilt.schema().await?;
+
+    let old_dim = match db.open_table(container_table).execute().await {
+        Ok(table) => vector_dim(table.schema().await?.as_ref()),
+        Err(_) => None,
+    };
+    let resized = old_dim.is_some_and(|dim| Some(dim) != vector_dim(&schema));
+
+    // A table with another vector size is dropped and created again, and
+    // its version numbers start over.
+    let snapshot = if resized {
+        snapshot::clear(container_table);
+        None
+    } else {
+        snapshot::pin(db, container_table).await
+    };
+    let mut undo = Vec::new();
+    let (table, rows) = match copy_all(db, container_table, rebuilt_name, &rebuilt, &mut undo).await {
+        Ok(copied) => copied,
+        Err(e) => {
+            restore(undo).await;
+            return Err(e);
+        }
+    };
+    if resized {
+        warn!("Rebuild of '{}' changed the vector size, dropping its history and tombstones", container_table);
+        let _ = db.drop_table(&versions::versions_table_name(container_table), &[]).await;
+        let _ = db.drop_table(&tombstones::tombstones_table_name(container_table), &[]).await;
+    }
+    if rows >= super::ANN_INDEX_THRESHOLD {
+        let _ = db::build_ann_index(&table).await;
+    }
+    if let Err(e) = db::update_fts_index(&table).await {
+        warn!("FTS index update failed: {}", e);
+    }
+    drop(snapshot);
+
+    change_journal::rename(rebuilt_name, container_table);
+    secrets::rename(rebuilt_name, container_table);
+    info!("Rebuild of '{}' swapped in ({} chunks)", container_table, rows);
+    Ok(())
+}
+
+async fn copy_all(
+    db: &Connection,
+    container_table: &str,
+    rebuilt_name: &str,
+    rebuilt: &Table,
+    undo: &mut Vec<(Table, u64)>,
+) -> Result<(Table, usize)> {
+    let copied = copy(db, container_table, rebuilt, undo).await?;
+    let side_tables: [fn(&str) -> String; 4] = [
+        dedup::refs_table_name,
+        file_vectors::file_vectors_table_name,
+        summaries::summaries_table_name,
+        entities::entities_table_name,
+    ];
+    for side_table in side_tables {
+        if let Ok(rebuilt_side) = db.open_table(side_table(rebuilt_name)).execute().await {
+            copy(db, &side_table(container_table), &rebuilt_side, undo).await?;
+        }
+    }
+    Ok(copied)
+}
@@ -1,47 +1,54 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use anyhow::{anyhow, Result};
-use arrow_array::{Float32Array, StringArray};
+use arrow_array::{Array, FixedSizeListArray, Float32Array, Int64Array, RecordBatch, StringArray};
 use futures::TryStreamExt;
 use lancedb::connection::Connection;
 use lancedb::index::scalar::FullTextSearchQuery;
 use lancedb::query::{ExecutableQuery, QueryBase};
-use lancedb::DistanceType;
+use lancedb::{DistanceType, Table};
 use log::{debug, warn};
 
+use super::query_filters::QueryFilters;
+
+/// `text` for the inside of a LIKE pattern with `\` as the escape character.
+pub(super) fn escape_like(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('\'', "''")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+
 pub fn build_filter_expr(
     path_prefix: Option<&str>,
+    query_filters: Option<&QueryFilters>,
     file_extensions: Option<&[String]>,
+    language: Option<&str>,
 ) -> Option<String> {
     let mut clauses = Vec::new();
 
     if let Some(prefix) = path_prefix {
-        let escaped = prefix
-            .replace('\\', "\\\\")
-            .replace('\'', "''")
-            .replace('%', "\\%")
-            .replace('_', "\\_");
-        clauses.push(format!("path LIKE '{}%' ESCAPE '\\'", escaped));
+        clauses.push(format!("path LIKE '{}%' ESCAPE '\\'", escape_like(prefix)));
+    }
+
+    if let Some(filters) = query_filters {
+        clauses.extend(filters.clauses());
     }
 
     if let Some(exts) = file_extensions {
         if !exts.is_empty() {
             let ext_clauses: Vec<String> = exts
                 .iter()
-                .map(|ext| {
-                    let clean = ext
-                        .trim_start_matches('.')
-                        .replace('\\', "\\\\")
-                        .replace('\'', "''")
-                        .replace('%', "\\%")
-                        .replace('_', "\\_");
-                    format!("path LIKE '%.{}' ESCAPE '\\'", clean)
-                })
+                .map(|ext| format!("path LIKE '%.{}' ESCAPE '\\'", escape_like(ext.trim_start_matches('.'))))
                 .collect();
             clauses.push(format!("({})", ext_clauses.join(" OR ")));
         }
     }
 
+    if let Some(lang) = language.filter(|l| !l.is_empty()) {
+        clauses.push(format!("{} = '{}'", super::db::LANG_COLUMN, lang.replace('\'', "''")));
+    }
+
     if clauses.is_empty() {
         None
     } else {
@@ -49,19 +56,65 @@ pub fn build_filter_expr(
     }
 }
 
+pub(super) fn with_trailing_separator(dir: &str) -> String {
+    if dir.ends_with('/') || dir.ends_with('\\') {
+        dir.to_string()
+    } else {
+        format!("{}{}", dir, std::path::MAIN_SEPARATOR)
+    }
+}
+
+/// Filter matching every row under `dir`, minus rows that belong to another indexed
+/// root nested inside it. Returns `None` when `dir` itself sits under another root,
+/// since those rows are still wanted.
+pub fn build_folder_filter(dir: &str, other_roots: &[String]) -> Option<String> {
+    let prefix = with_trailing_separator(dir);
+    let nested: Vec<String> = other_roots
+        .iter()
+        .map(|root| with_trailing_separator(root))
+        .filter(|root| root != &prefix)
+        .collect();
+
+    if nested.iter().any(|root| prefix.starts_with(root.as_str())) {
+        return None;
+    }
+
+    let mut clauses = vec![build_filter_expr(Some(&prefix), None, None, None)?];
+    for root in nested.iter().filter(|root| root.starts_with(prefix.as_str())) {
+        clauses.push(format!("NOT ({})", build_filter_expr(Some(root), None, None, None)?));
+    }
+    Some(clauses.join(" AND "))
+}
+
+#[allow(clippy::too_many_arguments)]
 pub async fn search_files(
     db: &Connection,
     table_name: &str,
     query_vector: &[f32],
     limit: usize,
     path_prefix: Option<&str>,
+    query_filters: Option<&QueryFilters>,
     file_extensions: Option<&[String]>,
+    language: Option<&str>,
     multi_chunk: bool,
 ) -> Result<Vec<(String, String, f32)>> {
-    let table = match db.open_table(table_name).execute().await {
-        Ok(t) => t,
-        Err(_) => return Err(anyhow!("No index found for '{}'. Index some folders first.", table_name)),
-    };
+    let table = open_for_search(db, table_name, query_vector).await?;
+    let search_limit = if multi_chunk { limit * 3 } else { limit * 2 };
+    let filter = build_filter_expr(path_prefix, query_filters, file_extensions, language);
+    let mut matches = nearest_chunks(&table, query_vector, search_limit, filter).await?;
+
+    if multi_chunk {
+        matches.truncate(limit);
+        Ok(matches)
+    } else {
+        Ok(best_per_file(matches, limit))
+    }
+}
+
+/// Opens the container's table, refusing when its vectors were made by a
+/// model with another dimension than `query_vector`.
+pub(super) async fn open_for_search(db: &Connection, table_name: &str, query_vector: &[f32]) -> Result<Table> {
+    let table = super::snapshot::open(db, table_name).await?;
 
     let schema = table.schema().await?;
     if let Ok(field) = schema.field_with_name("vector") {
@@ -75,16 +128,58 @@ pub async fn search_files(
             }
         }
     }
+    Ok(table)
+}
 
-    let search_limit = if multi_chunk { limit * 3 } else { limit * 2 };
+/// A chunk a search matched and where it starts in its file's text, see
+/// `db::CHUNK_START_COLUMN`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ChunkMatch {
+    pub path: String,
+    pub content: String,
+    pub start: i64,
+}
 
+/// The chunks in `batch`, with -1 as the start for tables without the column.
+fn chunk_rows(batch: &RecordBatch) -> Result<Vec<ChunkMatch>> {
+    let paths = batch
+        .column_by_name("path")
+        .and_then(|c| c.as_any().downcast_ref::<StringArray>())
+        .ok_or_else(|| anyhow!("Missing or invalid 'path' column"))?;
+    let contents = batch
+        .column_by_name("content")
+        .and_then(|c| c.as_any().downcast_ref::<StringArray>())
+        .ok_or_else(|| anyhow!("Missing or invalid 'content' column"))?;
+    let starts = batch
+        .column_by_name(super::db::CHUNK_START_COLUMN)
+        .and_then(|c| c.as_any().downcast_ref::<Int64Array>());
+    Ok((0..batch.num_rows())
+        .map(|i| ChunkMatch {
+            path: paths.value(i).to_string(),
+            content: contents.value(i).to_string(),
+            start: starts.filter(|s| !s.is_null(i)).map_or(-1, |s| s.value(i)),
+        })
+        .collect())
+}
+
+/// Closest `limit` chunks in `table` with their distance, nearest first.
+pub(super) async fn nearest_matches(
+    table: &Table,
+    query_vector: &[f32],
+    limit: usize,
+    filter: Option<String>,
+) -> Result<Vec<(ChunkMatch, f32)>> {
+    let mut columns = vec!["path".to_string(), "content".to_string()];
+    if table.schema().await?.field_with_name(super::db::CHUNK_START_COLUMN).is_ok() {
+        columns.push(super::db::CHUNK_START_COLUMN.to_string());
+    }
     let mut query = table
         .vector_search(query_vector)?
         .distance_type(DistanceType::Cosine)
-        .select(lancedb::query::Select::Columns(vec!["path".to_string(), "content".to_string()]))
-        .limit(search_limit);
+        .select(lancedb::query::Select::Columns(columns))
+        .limit(limit);
 
-    if let Some(filter) = build_filter_expr(path_prefix, file_extensions) {
+    if let Some(filter) = filter {
         query = query.only_if(filter);
     }
 
@@ -94,104 +189,81 @@ pub async fn search_files(
         .try_collect::<Vec<_>>()
         .await?;
 
-    if multi_chunk {
-        let mut matches = Vec::new();
-
-        for batch in results {
-            let path_array = batch
-                .column_by_name("path")
-                .and_then(|c| c.as_any().downcast_ref::<StringArray>())
-                .ok_or_else(|| anyhow!("Missing or invalid 'path' column"))?;
-
-            let content_array = batch
-                .column_by_name("content")
-                .and_then(|c| c.as_any().downcast_ref::<StringArray>())
-                .ok_or_else(|| anyhow!("Missing or invalid 'content' column"))?;
-
-            let dist_array = batch
-                .column_by_name("_distance")
-                .and_then(|c| c.as_any().downcast_ref::<Float32Array>())
-                .ok_or_else(|| anyhow!("Missing or invalid '_distance' column"))?;
-
-            for i in 0..batch.num_rows() {
-                matches.push((
-                    path_array.value(i).to_string(),
-                    content_array.value(i).to_string(),
-                    dist_array.value(i),
-                ));
-            }
-        }
-
-        matches.sort_by(|a, b| a.2.partial_cmp(&b.2).unwrap_or(std::cmp::Ordering::Equal));
-        matches.truncate(limit);
-        Ok(matches)
-    } else {
-        let mut best_per_file: HashMap<String, (String, f32)> = HashMap::new();
-
-        for batch in results {
-            let path_array = batch
-                .column_by_name("path")
-                .and_then(|c| c.as_any().downcast_ref::<StringArray>())
-                .ok_or_else(|| anyhow!("Missing or invalid 'path' column"))?;
-
-            let content_array = batch
-                .column_by_name("content")
-                .and_then(|c| c.as_any().downcast_ref::<StringArray>())
-                .ok_or_else(|| anyhow!("Missing or invalid 'content' column"))?;
+    let mut matches = Vec::new();
+    for batch in results {
+        let dist_array = batch
+            .column_by_name("_distance")
+            .and_then(|c| c.as_any().downcast_ref::<Float32Array>())
+            .ok_or_else(|| anyhow!("Missing or invalid '_distance' column"))?;
+        matches.extend(chunk_rows(&batch)?.into_iter().zip(dist_array.values().iter().copied()));
+    }
 
-            let dist_array = batch
-                .column_by_name("_distance")
-                .and_then(|c| c.as_any().downcast_ref::<Float32Array>())
-                .ok_or_else(|| anyhow!("Missing or invalid '_distance' column"))?;
+    matches.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal));
+    Ok(matches)
+}
 
-            for i in 0..batch.num_rows() {
-                let path = path_array.value(i).to_string();
-                let content = content_array.value(i).to_string();
-                let dist = dist_array.value(i);
+/// Closest `limit` chunks in `table` as (path, content, distance), nearest first.
+pub(super) async fn nearest_chunks(
+    table: &Table,
+    query_vector: &[f32],
+    limit: usize,
+    filter: Option<String>,
+) -> Result<Vec<(String, String, f32)>> {
+    Ok(nearest_matches(table, query_vector, limit, filter)
+        .await?
+        .into_iter()
+        .map(|(m, dist)| (m.path, m.content, dist))
+        .collect())
+}
 
-                match best_per_file.get(&path) {
-                    Some((_, existing_dist)) if *existing_dist <= dist => {}
-                    _ => {
-                        best_per_file.insert(path, (content, dist));
-                    }
-                }
+/// Keeps the closest chunk of each file, nearest files first.
+pub(super) fn best_per_file(matches: Vec<(String, String, f32)>, limit: usize) -> Vec<(String, String, f32)> {
+    let mut best: HashMap<String, (String, f32)> = HashMap::new();
+    for (path, content, dist) in matches {
+        match best.get(&path) {
+            Some((_, existing_dist)) if *existing_dist <= dist => {}
+            _ => {
+                best.insert(path, (content, dist));
             }
         }
+    }
 
-        let mut matches: Vec<(String, String, f32)> = best_per_file
-            .into_iter()
-            .map(|(path, (content, dist))| (path, content, dist))
-            .collect();
-
-        matches.sort_by(|a, b| a.2.partial_cmp(&b.2).unwrap_or(std::cmp::Ordering::Equal));
-        matches.truncate(limit);
+    let mut matches: Vec<(String, String, f32)> = best
+        .into_iter()
+        .map(|(path, (content, dist))| (path, content, dist))
+        .collect();
 
-        Ok(matches)
-    }
+    matches.sort_by(|a, b| a.2.partial_cmp(&b.2).unwrap_or(std::cmp::Ordering::Equal));
+    matches.truncate(limit);
+    matches
 }
 
-pub async fn search_fts(
+/// Full-text matches for `query`, best first, up to `limit` chunks.
+#[allow(clippy::too_many_arguments)]
+async fn search_fts_chunks(
     db: &Connection,
     table_name: &str,
     query: &str,
     limit: usize,
     path_prefix: Option<&str>,
+    query_filters: Option<&QueryFilters>,
     file_extensions: Option<&[String]>,
-    multi_chunk: bool,
-) -> Result<Vec<(String, String)>> {
-    let table = match db.open_table(table_name).execute().await {
-        Ok(t) => t,
-        Err(_) => return Err(anyhow!("No index found for '{}'. Index some folders first.", table_name)),
-    };
-
-    let fts_query = FullTextSearchQuery::new(query.to_string());
-    let search_limit = if multi_chunk { limit * 3 } else { limit * 2 };
+    language: Option<&str>,
+) -> Result<Vec<ChunkMatch>> {
+    let table = super::snapshot::open(db, table_name).await?;
+
+    // The index is over bigram-segmented text, so the query has to be segmented
+    // the same way. Tables not yet migrated still have their index on `content`.
+    let mut fts_query = FullTextSearchQuery::new(super::cjk::segment(query));
+    if table.schema().await?.field_with_name(super::db::FTS_COLUMN).is_ok() {
+        fts_query = fts_query.with_column(super::db::FTS_COLUMN.to_string())?;
+    }
     let mut q = table
         .query()
         .full_text_search(fts_query)
-        .limit(search_limit);
+        .limit(limit);
 
-    if let Some(filter) = build_filter_expr(path_prefix, file_extensions) {
+    if let Some(filter) = build_filter_expr(path_prefix, query_filters, file_extensions, language) {
         q = q.only_if(filter);
     }
 
@@ -202,53 +274,36 @@ pub async fn search_fts(
         .await?;
 
     let mut matches = Vec::new();
-
-    if multi_chunk {
-        for batch in results {
-            let path_array = batch
-                .column_by_name("path")
-                .and_then(|c| c.as_any().downcast_ref::<StringArray>());
-            let content_array = batch
-                .column_by_name("content")
-                .and_then(|c| c.as_any().downcast_ref::<StringArray>());
-
-            if let (Some(paths), Some(contents)) = (path_array, content_array) {
-                for i in 0..batch.num_rows() {
-                    matches.push((paths.value(i).to_string(), contents.value(i).to_string()));
-                    if matches.len() >= limit {
-                        return Ok(matches);
-                    }
-                }
-            }
-        }
-    } else {
-        let mut seen_paths = std::collections::HashSet::new();
-
-        for batch in results {
-            let path_array = batch
-                .column_by_name("path")
-                .and_then(|c| c.as_any().downcast_ref::<StringArray>());
-            let content_array = batch
-                .column_by_name("content")
-                .and_then(|c| c.as_any().downcast_ref::<StringArray>());
-
-            if let (Some(paths), Some(contents)) = (path_array, content_array) {
-                for i in 0..batch.num_rows() {
-                    let path = paths.value(i).to_string();
-                    if seen_paths.insert(path.clone()) {
-                        matches.push((path, contents.value(i).to_string()));
-                    }
-                    if matches.len() >= limit {
-                        return Ok(matches);
-                    }
-                }
-            }
-        }
+    for batch in results {
+        matches.extend(chunk_rows(&batch)?);
     }
-
     Ok(matches)
 }
 
+#[allow(clippy::too_many_arguments)]
+pub async fn search_fts(
+    db: &Connection,
+    table_name: &str,
+    query: &str,
+    limit: usize,
+    path_prefix: Option<&str>,
+    query_filters: Option<&QueryFilters>,
+    file_extensions: Option<&[String]>,
+    language: Option<&str>,
+    multi_chunk: bool,
+) -> Result<Vec<(String, String)>> {
+    let search_limit = if multi_chunk { limit * 3 } else { limit * 2 };
+    let chunks = search_fts_chunks(db, table_name, query, search_limit, path_prefix, query_filters, file_extensions, language).await?;
+
+    let mut seen_paths = HashSet::new();
+    Ok(chunks
+        .into_iter()
+        .filter(|m| multi_chunk || seen_paths.insert(m.path.clone()))
+        .take(limit)
+        .map(|m| (m.path, m.content))
+        .collect())
+}
+
 pub fn hybrid_merge(
     vector_results: &[(String, String, f32)],
     fts_results: &[(String, String)],
@@ -283,6 +338,235 @@ pub fn hybrid_merge(
     merged
 }
 
+/// Chunks of one file that overlap or meet, as one stretch of its text.
+/// Chunking repeats the end of each chunk at the start of the next, so
+/// neighbours tend to match the same query together.
+#[derive(Debug)]
+struct Hit {
+    path: String,
+    /// The "File:" and "Context:" lines of its first chunk.
+    header: String,
+    text: String,
+    start: i64,
+    end: i64,
+}
+
+impl Hit {
+    fn new(chunk: &ChunkMatch) -> Self {
+        let body = super::snippet::body(&chunk.content);
+        Hit {
+            path: chunk.path.clone(),
+            header: chunk.content[..chunk.content.len() - body.len()].to_string(),
+            text: body.to_string(),
+            start: chunk.start,
+            end: chunk.start + body.len() as i64,
+        }
+    }
+
+    /// Whether `chunk`, which starts no earlier in the same file, is part
+    /// of this hit. Without offsets only the same text is.
+    fn takes(&self, chunk: &ChunkMatch) -> bool {
+        if self.path != chunk.path {
+            return false;
+        }
+        if self.start < 0 || chunk.start < 0 {
+            return self.start == chunk.start && self.text == super::snippet::body(&chunk.content);
+        }
+        chunk.start <= self.end
+    }
+
+    /// Adds the part of `chunk` past the end of the hit.
+    fn extend(&mut self, chunk: &ChunkMatch) {
+        let body = super::snippet::body(&chunk.content);
+        let end = chunk.start + body.len() as i64;
+        if self.start < 0 || end <= self.end {
+            return;
+        }
+        match body.get((self.end - chunk.start) as usize..) {
+            Some(rest) => self.text.push_str(rest),
+            None => self.text = super::snippet::stitch(&[&self.text, body]),
+        }
+        self.end = end;
+    }
+
+    fn snippet(&self) -> String {
+        format!("{}{}", self.header, self.text)
+    }
+}
+
+/// Folds `chunks` into hits, and says which hit each chunk went into.
+fn fold(chunks: &[&ChunkMatch]) -> (Vec<Hit>, Vec<usize>) {
+    let mut order: Vec<usize> = (0..chunks.len()).collect();
+    order.sort_by(|&a, &b| (&chunks[a].path, chunks[a].start).cmp(&(&chunks[b].path, chunks[b].start)));
+    let mut hits: Vec<Hit> = Vec::new();
+    let mut hit_of = vec![0; chunks.len()];
+    for i in order {
+        match hits.last_mut() {
+            Some(hit) if hit.takes(chunks[i]) => hit.extend(chunks[i]),
+            _ => hits.push(Hit::new(chunks[i])),
+        }
+        hit_of[i] = hits.len() - 1;
+    }
+    (hits, hit_of)
+}
+
+/// Ranks files by their best hit, see `Hit`. Overlapping chunks fold into
+/// one hit before scoring, so they take one place in each ranking instead
+/// of pushing other files down, and show their shared text once. Vector
+/// and full-text rankings of hits are fused with RRF; without full-text
+/// matches the scores stay vector distances, like `search_files`.
+pub fn fuse_chunks(
+    vector_chunks: &[(ChunkMatch, f32)],
+    fts_chunks: &[ChunkMatch],
+    limit: usize,
+    vector_weight: f32,
+    fts_weight: f32,
+) -> Vec<(String, String, f32)> {
+    let all: Vec<&ChunkMatch> = vector_chunks.iter().map(|(chunk, _)| chunk).chain(fts_chunks).collect();
+    let (hits, hit_of) = fold(&all);
+    let ranked = |from: usize, to: usize| -> Vec<usize> {
+        let mut seen = HashSet::new();
+        hit_of[from..to].iter().copied().filter(|&hit| seen.insert(hit)).collect()
+    };
+    let vector_hits = ranked(0, vector_chunks.len());
+
+    let scored: Vec<(usize, f32)> = if fts_chunks.is_empty() {
+        // Chunks come nearest first, so a hit's first one is its nearest.
+        let mut dist = vec![f32::MAX; hits.len()];
+        for (i, (_, d)) in vector_chunks.iter().enumerate() {
+            dist[hit_of[i]] = dist[hit_of[i]].min(*d);
+        }
+        vector_hits.into_iter().map(|hit| (hit, dist[hit])).collect()
+    } else {
+        let k = 60.0_f32;
+        let mut scores = vec![0.0_f32; hits.len()];
+        for (rank, hit) in vector_hits.into_iter().enumerate() {
+            scores[hit] += vector_weight * (1.0 / (k + rank as f32 + 1.0));
+        }
+        for (rank, hit) in ranked(vector_chunks.len(), all.len()).into_iter().enumerate() {
+            scores[hit] += fts_weight * (1.0 / (k + rank as f32 + 1.0));
+        }
+        let mut scored: Vec<(usize, f32)> = scores.into_iter().enumerate().filter(|(_, s)| *s > 0.0).collect();
+        scored.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+        scored
+    };
+
+    let mut seen_paths = HashSet::new();
+    scored
+        .into_iter()
+        .filter(|(hit, _)| seen_paths.insert(hits[*hit].path.as_str()))
+        .take(limit)
+        .map(|(hit, score)| (hits[hit].path.clone(), hits[hit].snippet(), score))
+        .collect()
+}
+
+pub async fn file_centroid(table: &Table, path: &str) -> Result<Option<Vec<f32>>> {
+    let safe_path = path.replace('\'', "''");
+    let chunks = table
+        .query()
+        .only_if(format!("path = '{}'", safe_path))
+        .select(lancedb::query::Select::Columns(vec!["vector".to_string()]))
+        .execute()
+        .await?
+        .try_collect::<Vec<_>>()
+        .await?;
+
+    Ok(mean_vector(&chunks))
+}
+
+/// The mean of the `vector` column over `batches`; None with no rows.
+pub fn mean_vector(batches: &[RecordBatch]) -> Option<Vec<f32>> {
+    let mut sum: Option<Vec<f32>> = None;
+    let mut count = 0usize;
+    for batch in batches {
+        let Some(fsl) = batch
+            .column_by_name("vector")
+            .and_then(|c| c.as_any().downcast_ref::<FixedSizeListArray>())
+        else {
+            continue;
+        };
+        for i in 0..batch.num_rows() {
+            let values = fsl.value(i);
+            let Some(floats) = values.as_any().downcast_ref::<Float32Array>() else {
+                continue;
+            };
+            match &mut sum {
+                Some(acc) => {
+                    for (a, v) in acc.iter_mut().zip(floats.values().iter()) {
+                        *a += v;
+                    }
+                }
+                None => sum = Some(floats.values().to_vec()),
+            }
+            count += 1;
+        }
+    }
+
+    sum.map(|mut acc| {
+        for v in acc.iter_mut() {
+            *v /= count as f32;
+        }
+        acc
+    })
+}
+
+pub async fn find_related(
+    table: &Table,
+    path: &str,
+    top_k: usize,
+) -> Result<Option<Vec<(String, String, f32)>>> {
+    let Some(query_vector) = file_centroid(table, path).await? else {
+        return Ok(None);
+    };
+
+    let results = table
+        .vector_search(query_vector.as_slice())?
+        .distance_type(DistanceType::Cosine)
+        .select(lancedb::query::Select::Columns(vec!["path".to_string(), "content".to_string()]))
+        .limit((top_k + 1) * 3)
+        .execute()
+        .await?
+        .try_collect::<Vec<_>>()
+        .await?;
+
+    let mut best_per_file: HashMap<String, (String, f32)> = HashMap::new();
+    for batch in results {
+        let path_array = batch
+            .column_by_name("path")
+            .and_then(|c| c.as_any().downcast_ref::<StringArray>());
+        let content_array = batch
+            .column_by_name("content")
+            .and_then(|c| c.as_any().downcast_ref::<StringArray>());
+        let dist_array = batch
+            .column_by_name("_distance")
+            .and_then(|c| c.as_any().downcast_ref::<Float32Array>());
+
+        if let (Some(paths), Some(contents), Some(dists)) = (path_array, content_array, dist_array) {
+            for i in 0..batch.num_rows() {
+                let p = paths.value(i);
+                if p == path {
+                    continue;
+                }
+                let dist = dists.value(i);
+                match best_per_file.get(p) {
+                    Some((_, existing)) if *existing <= dist => {}
+                    _ => {
+                        best_per_file.insert(p.to_string(), (contents.value(i).to_string(), dist));
+                    }
+                }
+            }
+        }
+    }
+
+    let mut related: Vec<(String, String, f32)> = best_per_file
+        .into_iter()
+        .map(|(p, (snippet, dist))| (p, snippet, dist))
+        .collect();
+    related.sort_by(|a, b| a.2.partial_cmp(&b.2).unwrap_or(std::cmp::Ordering::Equal));
+    related.truncate(top_k);
+    Ok(Some(related))
+}
+
 #[allow(clippy::too_many_arguments)]
 pub async fn search_pipeline(
     db: &Connection,
@@ -291,54 +575,178 @@ pub async fn search_pipeline(
     query_vector: &[f32],
     search_limit: usize,
     path_prefix: Option<&str>,
+    query_filters: Option<&QueryFilters>,
     file_extensions: Option<&[String]>,
+    language: Option<&str>,
+    expansion: &super::chunking::QueryExpansion,
     vector_weight: f32,
     fts_weight: f32,
+    scope: SearchScope,
 ) -> Result<(Vec<(String, String, f32)>, bool)> {
-    let query_variants = super::chunking::expand_query(query);
-
-    let vector_fut = search_files(db, table_name, query_vector, search_limit, path_prefix, file_extensions, false);
-
-    let fts_db = db.clone();
-    let fts_table = table_name.to_string();
-    let fe_clone: Option<Vec<String>> = file_extensions.map(|s| s.to_vec());
-    let pp_clone: Option<String> = path_prefix.map(|s| s.to_string());
-    let fts_fut = async move {
-        let pp_ref = pp_clone.as_deref();
-        let fe_ref = fe_clone.as_deref();
-        let futs: Vec<_> = query_variants
-            .iter()
-            .map(|v| search_fts(&fts_db, &fts_table, v, 30, pp_ref, fe_ref, false))
-            .collect();
-        let results = futures::future::join_all(futs).await;
-        let mut all: Vec<(String, String)> = Vec::new();
-        let mut seen = std::collections::HashSet::new();
-        for result in results.into_iter().flatten() {
-            for item in result {
-                if seen.insert(item.0.clone()) {
-                    all.push(item);
-                }
+    let query_variants = super::chunking::expand_query(query, expansion, language);
+
+    // Filtering on `lang`, `size` or `lines` needs the column, which tables
+    // indexed before it existed don't have until their next index run.
+    if language.is_some() || query_filters.is_some_and(|f| !f.comparisons.is_empty()) {
+        if let Ok(table) = db.open_table(table_name).execute().await {
+            super::db::ensure_columns(&table).await?;
+        }
+    }
+
+    let filter = build_filter_expr(path_prefix, query_filters, file_extensions, language);
+    if let Some(as_of) = scope.as_of {
+        let mut results = super::versions::search_as_of(db, table_name, query_vector, search_limit, filter.clone(), as_of).await?;
+        debug!("Search pipeline: {} results as of {}", results.len(), as_of);
+        if scope.include_deleted {
+            add_deleted(db, table_name, query_vector, search_limit, filter, &mut results, false).await;
+        }
+        return Ok((results, false));
+    }
+
+    // Chunks rather than files, so neighbours can be folded into one hit
+    // before they're ranked.
+    let vector_fut = async {
+        let table = open_for_search(db, table_name, query_vector).await?;
+        // A filtered search is narrow already, and the shortlist could miss
+        // every file it allows.
+        let shortlist = if filter.is_none() {
+            coarse_filter(db, &table, table_name, query_vector, search_limit).await
+        } else {
+            None
+        };
+        if let Some(shortlist) = shortlist {
+            let matches = nearest_matches(&table, query_vector, search_limit * 3, Some(shortlist)).await?;
+            // Too few files' chunks came back to fill the page.
+            if matches.len() >= search_limit {
+                return Ok(matches);
             }
         }
-        all
+        nearest_matches(&table, query_vector, search_limit * 3, filter.clone()).await
     };
+    let fts_fut = search_fts_variants(db, table_name, &query_variants, path_prefix, query_filters, file_extensions, language);
 
     let (vector_result, fts_results) = tokio::join!(vector_fut, fts_fut);
     let vector_results = vector_result?;
 
-    debug!("Search pipeline: {} vector results, {} FTS results, weights: vector={:.1} fts={:.1}",
+    debug!("Search pipeline: {} vector chunks, {} FTS chunks, weights: vector={:.1} fts={:.1}",
         vector_results.len(), fts_results.len(), vector_weight, fts_weight);
 
     let used_hybrid = !fts_results.is_empty();
-    let merged = if fts_results.is_empty() {
-        vector_results
-    } else {
-        hybrid_merge(&vector_results, &fts_results, search_limit, vector_weight, fts_weight)
-    };
+    let mut merged = fuse_chunks(&vector_results, &fts_results, search_limit, vector_weight, fts_weight);
+    if scope.include_deleted {
+        add_deleted(db, table_name, query_vector, search_limit, filter, &mut merged, used_hybrid).await;
+    }
 
     Ok((merged, used_hybrid))
 }
 
+/// The files a big container's chunk search should stay within, picked by
+/// their mean vectors. None searches every chunk: the container is small,
+/// or has no file vectors yet.
+async fn coarse_filter(db: &Connection, table: &Table, table_name: &str, query_vector: &[f32], limit: usize) -> Option<String> {
+    if table.count_rows(None).await.ok()? < super::file_vectors::COARSE_SEARCH_THRESHOLD {
+        return None;
+    }
+    let size = super::file_vectors::shortlist_size(limit);
+    let paths = super::file_vectors::shortlist(db, table_name, query_vector, size).await?;
+    debug!("Search pipeline: coarse pass kept {} files", paths.len());
+    super::file_vectors::paths_filter(&paths)
+}
+
+/// Full-text matches for every variant of the query, each chunk once.
+async fn search_fts_variants(
+    db: &Connection,
+    table_name: &str,
+    query_variants: &[String],
+    path_prefix: Option<&str>,
+    query_filters: Option<&QueryFilters>,
+    file_extensions: Option<&[String]>,
+    language: Option<&str>,
+) -> Vec<ChunkMatch> {
+    let futs: Vec<_> = query_variants
+        .iter()
+        .map(|v| search_fts_chunks(db, table_name, v, 60, path_prefix, query_filters, file_extensions, language))
+        .collect();
+    let results = futures::future::join_all(futs).await;
+    let mut all: Vec<ChunkMatch> = Vec::new();
+    let mut seen = HashSet::new();
+    for result in results.into_iter().flatten() {
+        for item in result {
+            if seen.insert((item.path.clone(), item.content.clone())) {
+                all.push(item);
+            }
+        }
+    }
+    all
+}
+
+/// `search_pipeline` without a query vector, for when the embedding
+/// endpoint can't be reached. Scores are RRF like a hybrid search's.
+#[allow(clippy::too_many_arguments)]
+pub async fn search_fts_only(
+    db: &Connection,
+    table_name: &str,
+    query: &str,
+    search_limit: usize,
+    path_prefix: Option<&str>,
+    query_filters: Option<&QueryFilters>,
+    file_extensions: Option<&[String]>,
+    language: Option<&str>,
+    expansion: &super::chunking::QueryExpansion,
+) -> Result<Vec<(String, String, f32)>> {
+    let query_variants = super::chunking::expand_query(query, expansion, language);
+    if language.is_some() || query_filters.is_some_and(|f| !f.comparisons.is_empty()) {
+        if let Ok(table) = db.open_table(table_name).execute().await {
+            super::db::ensure_columns(&table).await?;
+        }
+    }
+    let fts_results = search_fts_variants(db, table_name, &query_variants, path_prefix, query_filters, file_extensions, language).await;
+    debug!("Search pipeline: {} FTS chunks, no query vector", fts_results.len());
+    Ok(fuse_chunks(&[], &fts_results, search_limit, 0.0, 1.0))
+}
+
+/// Which rows besides the current index a search looks at.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SearchScope {
+    /// Unix seconds to search the files as of, see `versions`.
+    pub as_of: Option<i64>,
+    /// Also search files the watcher saw deleted, see `tombstones`.
+    pub include_deleted: bool,
+}
+
+async fn add_deleted(
+    db: &Connection,
+    table_name: &str,
+    query_vector: &[f32],
+    limit: usize,
+    filter: Option<String>,
+    merged: &mut Vec<(String, String, f32)>,
+    used_hybrid: bool,
+) {
+    match super::tombstones::search(db, table_name, query_vector, limit, filter).await {
+        Ok(deleted) => {
+            debug!("Search pipeline: {} deleted files", deleted.len());
+            merge_ranked(merged, deleted, used_hybrid);
+        }
+        Err(e) => warn!("Searching deleted files failed: {}", e),
+    }
+}
+
+/// Adds vector-only matches (distances, nearest first) to `merged`. Hybrid
+/// results are RRF scores where higher is better, so the extras get a score
+/// from their rank instead of their distance.
+pub fn merge_ranked(merged: &mut Vec<(String, String, f32)>, extra: Vec<(String, String, f32)>, used_hybrid: bool) {
+    if used_hybrid {
+        for (rank, (path, content, _dist)) in extra.into_iter().enumerate() {
+            merged.push((path, content, 1.0 / (60.0 + rank as f32 + 1.0)));
+        }
+        merged.sort_by(|a, b| b.2.partial_cmp(&a.2).unwrap_or(std::cmp::Ordering::Equal));
+    } else {
+        merged.extend(extra);
+        merged.sort_by(|a, b| a.2.partial_cmp(&b.2).unwrap_or(std::cmp::Ordering::Equal));
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -358,56 +766,140 @@ mod tests {
         assert_eq!(merged[0].0, "b.txt");
     }
 
+    fn chunk(path: &str, start: i64, body: &str) -> ChunkMatch {
+        ChunkMatch { path: path.to_string(), content: format!("File: {}\n{}", path, body), start }
+    }
+
+    #[test]
+    fn test_fuse_chunks_folds_overlap() {
+        let text = "Alpha beta gamma. Delta epsilon zeta. Eta theta iota.";
+        let first = chunk("a.md", 0, &text[..36]);
+        let second = chunk("a.md", 18, &text[18..]);
+        let vector = vec![(first.clone(), 0.1), (second.clone(), 0.2), (chunk("b.md", 0, "Other text."), 0.3)];
+
+        let merged = fuse_chunks(&vector, &[], 10, 1.0, 1.0);
+        assert_eq!(merged.len(), 2);
+        assert_eq!(merged[0], ("a.md".to_string(), format!("File: a.md\n{}", text), 0.1));
+        assert_eq!(merged[1].0, "b.md");
+
+        // The neighbour full-text matched is the same hit as the vector's,
+        // so it adds to that hit instead of ranking the file a second time.
+        let fts = vec![second, chunk("c.md", -1, "Eta theta.")];
+        let merged = fuse_chunks(&vector[..1], &fts, 10, 1.0, 1.0);
+        assert_eq!(merged[0].0, "a.md");
+        assert!((merged[0].2 - 2.0 / 61.0).abs() < 1e-6);
+        assert!((merged[1].2 - 1.0 / 62.0).abs() < 1e-6, "c.md ranks second in full text, not third");
+    }
+
+    #[test]
+    fn test_fuse_chunks_keeps_distant_chunks_apart() {
+        let near = chunk("a.md", 0, "Setup takes a minute.");
+        let far = chunk("a.md", 5000, "Uninstalling is manual.");
+        let unknown = chunk("a.md", -1, "Key: value");
+        let (hits, hit_of) = fold(&[&near, &far, &unknown, &unknown.clone()]);
+        assert_eq!(hits.len(), 3);
+        assert_eq!(hit_of[2], hit_of[3]);
+        assert_ne!(hit_of[0], hit_of[1]);
+
+        let merged = fuse_chunks(&[(far, 0.1), (near, 0.2)], &[], 10, 1.0, 1.0);
+        assert_eq!(merged, vec![("a.md".to_string(), "File: a.md\nUninstalling is manual.".to_string(), 0.1)]);
+    }
+
     #[test]
     fn test_build_filter_expr_none() {
-        assert_eq!(build_filter_expr(None, None), None);
+        assert_eq!(build_filter_expr(None, None, None, None), None);
     }
 
     #[test]
     fn test_build_filter_expr_prefix_only() {
-        let result = build_filter_expr(Some("src/indexer"), None);
+        let result = build_filter_expr(Some("src/indexer"), None, None, None);
         assert_eq!(result, Some("path LIKE 'src/indexer%' ESCAPE '\\'".to_string()));
     }
 
     #[test]
     fn test_build_filter_expr_extensions_only() {
         let exts = vec!["rs".to_string(), "ts".to_string()];
-        let result = build_filter_expr(None, Some(&exts));
+        let result = build_filter_expr(None, None, Some(&exts), None);
         assert_eq!(result, Some("(path LIKE '%.rs' ESCAPE '\\' OR path LIKE '%.ts' ESCAPE '\\')".to_string()));
     }
 
     #[test]
     fn test_build_filter_expr_both() {
         let exts = vec!["py".to_string()];
-        let result = build_filter_expr(Some("lib/"), Some(&exts));
+        let result = build_filter_expr(Some("lib/"), None, Some(&exts), None);
         assert_eq!(result, Some("path LIKE 'lib/%' ESCAPE '\\' AND (path LIKE '%.py' ESCAPE '\\')".to_string()));
     }
 
     #[test]
     fn test_build_filter_expr_dot_prefix_stripped() {
         let exts = vec![".rs".to_string()];
-        let result = build_filter_expr(None, Some(&exts));
+        let result = build_filter_expr(None, None, Some(&exts), None);
         assert_eq!(result, Some("(path LIKE '%.rs' ESCAPE '\\')".to_string()));
     }
 
     #[test]
     fn test_build_filter_expr_empty_extensions() {
         let exts: Vec<String> = vec![];
-        assert_eq!(build_filter_expr(None, Some(&exts)), None);
+        assert_eq!(build_filter_expr(None, None, Some(&exts), None), None);
     }
 
     #[test]
     fn test_build_filter_expr_underscore_escaped() {
-        let result = build_filter_expr(Some("src/my_module"), None);
+        let result = build_filter_expr(Some("src/my_module"), None, None, None);
         assert_eq!(result, Some("path LIKE 'src/my\\_module%' ESCAPE '\\'".to_string()));
     }
 
     #[test]
     fn test_build_filter_expr_percent_escaped() {
-        let result = build_filter_expr(Some("100%done"), None);
+        let result = build_filter_expr(Some("100%done"), None, None, None);
         assert_eq!(result, Some("path LIKE '100\\%done%' ESCAPE '\\'".to_string()));
     }
 
+    #[test]
+    fn test_build_filter_expr_language() {
+        let exts = vec!["md".to_string()];
+        let result = build_filter_expr(None, None, Some(&exts), Some("de")).unwrap();
+        assert!(result.ends_with(" AND lang = 'de'"));
+        assert_eq!(build_filter_expr(None, None, None, Some("")), None);
+    }
+
+    #[test]
+    fn test_build_folder_filter() {
+        let result = build_folder_filter("docs/", &[]);
+        assert_eq!(result, Some("path LIKE 'docs/%' ESCAPE '\\'".to_string()));
+
+        let nested = vec!["docs/".to_string(), "docs/api/".to_string()];
+        let result = build_folder_filter("docs/", &nested);
+        assert_eq!(
+            result,
+            Some("path LIKE 'docs/%' ESCAPE '\\' AND NOT (path LIKE 'docs/api/%' ESCAPE '\\')".to_string())
+        );
+
+        let parent = vec!["docs/".to_string()];
+        assert_eq!(build_folder_filter("docs/api/", &parent), None);
+        assert!(build_folder_filter("docs-old/", &parent).is_some());
+    }
+
+    #[test]
+    fn test_merge_ranked() {
+        let extra = vec![("gone.md".to_string(), "deleted".to_string(), 0.15)];
+
+        let mut vector = vec![
+            ("a.md".to_string(), "a".to_string(), 0.1),
+            ("b.md".to_string(), "b".to_string(), 0.2),
+        ];
+        merge_ranked(&mut vector, extra.clone(), false);
+        assert_eq!(vector[1].0, "gone.md");
+
+        let mut hybrid = vec![
+            ("a.md".to_string(), "a".to_string(), 0.03),
+            ("b.md".to_string(), "b".to_string(), 0.01),
+        ];
+        merge_ranked(&mut hybrid, extra, true);
+        assert_eq!(hybrid[1].0, "gone.md");
+        assert!((hybrid[1].2 - 1.0 / 61.0).abs() < 1e-6);
+    }
+
     #[test]
     fn test_hybrid_merge_vector_heavy() {
         let vector = vec![
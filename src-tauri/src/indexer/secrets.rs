@@ -0,0 +1,184 @@
+//! Credentials in files being indexed: keys in a known format, private key
+//! blocks and the values of `password = ...`-style settings. Depending on
+//! `indexing.secrets` they're masked before a chunk is embedded, the chunk
+//! is left out, or the file is only noted. Files they turn up in are listed
+//! per container, next to the config rather than in the index.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+
+use crate::redact::{self, Detector};
+
+/// What indexing does with a chunk holding a secret.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SecretPolicy {
+    /// Don't look.
+    #[default]
+    Off,
+    /// Index it as is, list the file.
+    Report,
+    /// Swap each secret for `[redacted:<kind>]` before it's embedded.
+    Mask,
+    /// Leave the chunk out of the index.
+    Skip,
+}
+
+/// The detectors sure enough to act on unseen. Emails, phone numbers and
+/// random-looking strings are left to redaction over MCP.
+const DETECTORS: [Detector; 2] = [Detector::Key, Detector::Password];
+
+/// A file secrets were found in.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Flagged {
+    /// Detector names, "key" or "password".
+    pub kinds: Vec<String>,
+    /// Chunks they were found in.
+    pub chunks: usize,
+    /// What indexing did about them.
+    pub policy: SecretPolicy,
+    /// Unix seconds the file was indexed.
+    pub at: i64,
+}
+
+/// Applies `policy` to a file's chunks, see `label_chunks`. What was found,
+/// None when nothing was or the policy is off.
+pub(super) fn screen(chunks: &mut Vec<(String, String, i64)>, policy: SecretPolicy) -> Option<Flagged> {
+    if policy == SecretPolicy::Off {
+        return None;
+    }
+    let mut kinds = BTreeSet::new();
+    let mut flagged = 0;
+    chunks.retain_mut(|(content, _, _)| {
+        let found = redact::find(content, &DETECTORS);
+        if found.is_empty() {
+            return true;
+        }
+        flagged += 1;
+        kinds.extend(found.iter().map(|f| f.detector.name()));
+        match policy {
+            SecretPolicy::Skip => false,
+            SecretPolicy::Mask => {
+                *content = redact::mask(content, &found);
+                true
+            }
+            SecretPolicy::Off | SecretPolicy::Report => true,
+        }
+    });
+    (flagged > 0).then(|| Flagged {
+        kinds: kinds.into_iter().map(String::from).collect(),
+        chunks: flagged,
+        policy,
+        at: chrono::Utc::now().timestamp(),
+    })
+}
+
+fn report_path(table_name: &str) -> PathBuf {
+    crate::config::app_data_dir().join("secrets").join(format!("{}.json", table_name))
+}
+
+/// Files secrets were found in, by path.
+pub fn report(table_name: &str) -> BTreeMap<String, Flagged> {
+    std::fs::read_to_string(report_path(table_name))
+        .ok()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+fn store(table_name: &str, report: &BTreeMap<String, Flagged>) {
+    let path = report_path(table_name);
+    if report.is_empty() {
+        std::fs::remove_file(&path).ok();
+        return;
+    }
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).ok();
+    }
+    match serde_json::to_string_pretty(report) {
+        Ok(json) => {
+            if let Err(e) = std::fs::write(&path, json) {
+                warn!("Failed to write secrets report {:?}: {}", path, e);
+            }
+        }
+        Err(e) => warn!("Failed to serialize secrets report: {}", e),
+    }
+}
+
+/// Notes what `screen` made of files just indexed. A file it found clean
+/// comes off the list.
+pub(super) fn record(table_name: &str, files: impl IntoIterator<Item = (String, Option<Flagged>)>) {
+    let mut report = report(table_name);
+    let mut changed = false;
+    for (path, flagged) in files {
+        match flagged {
+            Some(flagged) => {
+                info!("{} holds {} ({} chunks, {:?})", path, flagged.kinds.join(", "), flagged.chunks, flagged.policy);
+                report.insert(path, flagged);
+                changed = true;
+            }
+            None => changed |= report.remove(&path).is_some(),
+        }
+    }
+    if changed {
+        store(table_name, &report);
+    }
+}
+
+/// Drops the files under `path`, or all of them.
+pub fn forget(table_name: &str, path: Option<&str>) {
+    let Some(path) = path else {
+        std::fs::remove_file(report_path(table_name)).ok();
+        return;
+    };
+    let mut report = report(table_name);
+    let before = report.len();
+    report.retain(|file, _| !Path::new(file).starts_with(path));
+    if report.len() != before {
+        store(table_name, &report);
+    }
+}
+
+/// Hands the list of a table that's replacing another one over to it.
+pub fn rename(from: &str, to: &str) {
+    store(to, &report(from));
+    forget(from, None);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chunks() -> Vec<(String, String, i64)> {
+        vec![
+            ("File: .env\nDB_HOST=localhost\nDB_PASSWORD=hunter2hunter2".to_string(), String::new(), 0),
+            ("File: .env\nDEBUG=true".to_string(), String::new(), 40),
+        ]
+    }
+
+    #[test]
+    fn test_screen() {
+        let mut off = chunks();
+        assert_eq!(screen(&mut off, SecretPolicy::Off), None);
+        assert_eq!(off, chunks());
+
+        let mut report = chunks();
+        let flagged = screen(&mut report, SecretPolicy::Report).unwrap();
+        assert_eq!((flagged.kinds, flagged.chunks), (vec!["password".to_string()], 1));
+        assert_eq!(report, chunks());
+
+        let mut masked = chunks();
+        screen(&mut masked, SecretPolicy::Mask).unwrap();
+        assert_eq!(masked[0].0, "File: .env\nDB_HOST=localhost\nDB_PASSWORD=[redacted:password]");
+        assert_eq!(masked[1], chunks()[1]);
+
+        let mut skipped = chunks();
+        screen(&mut skipped, SecretPolicy::Skip).unwrap();
+        assert_eq!(skipped, chunks()[1..]);
+
+        let mut clean = vec![("File: notes.md\nthe password policy says twelve characters".to_string(), String::new(), 0)];
+        assert_eq!(screen(&mut clean, SecretPolicy::Mask), None);
+    }
+}
@@ -0,0 +1,111 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{LazyLock, Mutex};
+
+use anyhow::{anyhow, Result};
+use lancedb::connection::Connection;
+use lancedb::Table;
+use log::{debug, warn};
+
+use super::journal;
+use super::rebuild::rebuild_table_name;
+
+/// Table version searches read while a job writes to the table, with how
+/// many jobs hold it. A job deletes a file's old rows before adding the new
+/// ones, so reading the latest version mid-job finds files missing or half
+/// there; the version from before the job has every file whole.
+static PINNED: LazyLock<Mutex<HashMap<String, (u64, usize)>>> = LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Keeps searches on the table's current version until dropped, which is
+/// when the job's changes show up all at once.
+pub struct Snapshot {
+    table_name: String,
+}
+
+impl Drop for Snapshot {
+    fn drop(&mut self) {
+        let mut pinned = PINNED.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some((version, holders)) = pinned.get_mut(&self.table_name) {
+            *holders -= 1;
+            if *holders == 0 {
+                debug!("Snapshot: '{}' released version {}", self.table_name, version);
+                pinned.remove(&self.table_name);
+                std::fs::remove_file(pin_path(&self.table_name)).ok();
+            }
+        }
+    }
+}
+
+/// Written next to the indexing journal so rememex-mcp, which reads the
+/// table from another process, stays on the same version.
+fn pin_path(table_name: &str) -> PathBuf {
+    crate::config::app_data_dir().join("journal").join(format!("{}.pin", table_name))
+}
+
+/// The version a job in another process, or one a crash interrupted, pinned.
+/// Only counts while that job's journal is still there.
+fn persisted_version(table_name: &str) -> Option<u64> {
+    let version = std::fs::read_to_string(pin_path(table_name)).ok()?.trim().parse().ok()?;
+    let job_running = journal::load(table_name).is_some() || journal::load(&rebuild_table_name(table_name)).is_some();
+    job_running.then_some(version)
+}
+
+/// Pins the version `table_name` is at for the length of a job. A second
+/// job on the same table shares the first one's version, and a resumed job
+/// the interrupted one's. None when the table doesn't exist yet, since
+/// there's nothing older to show.
+pub async fn pin(db: &Connection, table_name: &str) -> Option<Snapshot> {
+    let table = db.open_table(table_name).execute().await.ok()?;
+    let version = match persisted_version(table_name) {
+        Some(version) => version,
+        None => match table.version().await {
+            Ok(version) => version,
+            Err(e) => {
+                warn!("Snapshot: can't read the version of '{}': {}", table_name, e);
+                return None;
+            }
+        },
+    };
+    let mut pinned = PINNED.lock().unwrap_or_else(|e| e.into_inner());
+    let entry = pinned.entry(table_name.to_string()).or_insert((version, 0));
+    entry.1 += 1;
+    if entry.1 == 1 {
+        let path = pin_path(table_name);
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).ok();
+        }
+        if let Err(e) = std::fs::write(&path, entry.0.to_string()) {
+            warn!("Snapshot: can't write {:?}: {}", path, e);
+        }
+    }
+    debug!("Snapshot: searches on '{}' stay on version {} until the job is done", table_name, entry.0);
+    Some(Snapshot { table_name: table_name.to_string() })
+}
+
+pub fn pinned_version(table_name: &str) -> Option<u64> {
+    let pinned = PINNED.lock().unwrap_or_else(|e| e.into_inner()).get(table_name).map(|(version, _)| *version);
+    pinned.or_else(|| persisted_version(table_name))
+}
+
+/// Unpins `table_name` before it's dropped, since its version numbers start
+/// over once it's created again.
+pub fn clear(table_name: &str) {
+    PINNED.lock().unwrap_or_else(|e| e.into_inner()).remove(table_name);
+    std::fs::remove_file(pin_path(table_name)).ok();
+}
+
+/// Opens `table_name` for reading, at the pinned version while a job runs.
+/// Falls back to the latest version if the pinned one was cleaned up.
+pub async fn open(db: &Connection, table_name: &str) -> Result<Table> {
+    let table = db
+        .open_table(table_name)
+        .execute()
+        .await
+        .map_err(|_| anyhow!("No index found for '{}'. Index some folders first.", table_name))?;
+    if let Some(version) = pinned_version(table_name) {
+        if let Err(e) = table.checkout(version).await {
+            warn!("Snapshot: version {} of '{}' is gone, reading the latest: {}", version, table_name, e);
+        }
+    }
+    Ok(table)
+}
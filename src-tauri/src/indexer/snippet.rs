@@ -0,0 +1,285 @@
+//! The piece of a chunk shown for a result: the sentence that matches the
+//! query best with as many neighbours as fit, instead of the chunk from its
+//! first line.
+
+use super::chunking::{expand_query, QueryExpansion, STOP_WORDS};
+use super::cjk;
+
+/// Marks text cut off before or after an excerpt.
+const ELLIPSIS: &str = "…";
+/// Shortest run taken for the overlap chunking leaves between neighbouring
+/// chunks (100 bytes and up) rather than a chance match.
+const MIN_OVERLAP: usize = 16;
+
+/// The words a result's sentences are matched against: the query's own,
+/// stemmed and with synonyms when those are on, lowercased and without
+/// stop words.
+pub fn query_terms(query: &str, expansion: &QueryExpansion, language: Option<&str>) -> Vec<String> {
+    let mut terms: Vec<String> = Vec::new();
+    for variant in expand_query(query, expansion, language) {
+        for word in words(&variant.to_lowercase()) {
+            if !STOP_WORDS.contains(&word) && !terms.iter().any(|t| t == word) {
+                terms.push(word.to_string());
+            }
+        }
+    }
+    terms
+}
+
+/// A chunk's text without the "[annotation] " or "[summary] " mark and the
+/// "File:" and "Context:" lines put in front of it for the embedding.
+pub fn body(snippet: &str) -> &str {
+    let mut text = snippet
+        .strip_prefix("[annotation] ")
+        .or_else(|| snippet.strip_prefix("[summary] "))
+        .unwrap_or(snippet);
+    if let Some(rest) = text.strip_prefix("File: ") {
+        text = rest.split_once('\n').map_or("", |(_, rest)| rest);
+        if let Some(rest) = text.strip_prefix("Context: ") {
+            text = rest.split_once('\n').map_or("", |(_, rest)| rest);
+        }
+    }
+    text
+}
+
+/// At most `max_chars` of `text` around the sentence with the most of
+/// `terms` in it, widened a sentence at a time on both sides, with "…"
+/// where it was cut. With no match it's the start of the text; with
+/// `max_chars` 0, all of it.
+pub fn excerpt(text: &str, terms: &[String], max_chars: usize) -> String {
+    let text = text.trim();
+    if max_chars == 0 || text.chars().count() <= max_chars {
+        return text.to_string();
+    }
+    let sentences = sentences(text);
+    let Some(best) = (0..sentences.len()).max_by_key(|&i| {
+        let (start, end) = sentences[i];
+        (matched(&text[start..end], terms), std::cmp::Reverse(i))
+    }) else {
+        return String::new();
+    };
+
+    let span = |start: usize, end: usize| text[start..end].chars().count();
+    let (mut start, mut end) = sentences[best];
+    let (mut before, mut after) = (best, best + 1);
+    loop {
+        let mut grew = false;
+        if after < sentences.len() && span(start, sentences[after].1) <= max_chars {
+            end = sentences[after].1;
+            after += 1;
+            grew = true;
+        }
+        if before > 0 && span(sentences[before - 1].0, end) <= max_chars {
+            before -= 1;
+            start = sentences[before].0;
+            grew = true;
+        }
+        if !grew {
+            break;
+        }
+    }
+    if span(start, end) > max_chars {
+        let focus = first_match(&text[start..end], terms).map_or(start, |at| start + at);
+        (start, end) = crop(text, start, end, focus, max_chars);
+    }
+
+    let mut out = String::new();
+    if start > 0 {
+        out.push_str(ELLIPSIS);
+    }
+    out.push_str(text[start..end].trim());
+    if end < text.len() {
+        out.push_str(ELLIPSIS);
+    }
+    out
+}
+
+/// Chunks of one file, given in file order, as one text: the overlap
+/// between neighbours kept once, a chunk already inside the one before left
+/// out, and a "…" line between chunks that don't meet.
+pub fn stitch(chunks: &[&str]) -> String {
+    let mut out = String::new();
+    let mut last = "";
+    for chunk in chunks {
+        let chunk = chunk.trim();
+        if chunk.is_empty() || last.contains(chunk) {
+            continue;
+        }
+        if out.is_empty() {
+            out.push_str(chunk);
+        } else if let Some(shared) = overlap(last, chunk) {
+            out.push_str(&chunk[shared..]);
+        } else {
+            out.push('\n');
+            out.push_str(ELLIPSIS);
+            out.push('\n');
+            out.push_str(chunk);
+        }
+        last = chunk;
+    }
+    out
+}
+
+/// Length of the longest start of `b` that `a` ends with.
+fn overlap(a: &str, b: &str) -> Option<usize> {
+    (MIN_OVERLAP..=a.len().min(b.len()))
+        .rev()
+        .find(|&len| b.is_char_boundary(len) && a.ends_with(&b[..len]))
+}
+
+fn words(text: &str) -> impl Iterator<Item = &str> {
+    text.split(|c: char| !c.is_alphanumeric()).filter(|w| !w.is_empty())
+}
+
+/// Where `term` is in `word`: all of it, or for terms of three letters or
+/// more its start, so the stem "index" finds "indexing". CJK runs have no
+/// spaces to split words at, so a CJK term is found anywhere in one.
+fn find_term(word: &str, term: &str) -> Option<usize> {
+    if cjk::contains_cjk(term) {
+        word.find(term)
+    } else {
+        (word == term || (term.chars().count() >= 3 && word.starts_with(term))).then_some(0)
+    }
+}
+
+/// How many different `terms` are in `sentence`.
+fn matched(sentence: &str, terms: &[String]) -> usize {
+    let lower = sentence.to_lowercase();
+    terms.iter().filter(|term| words(&lower).any(|word| find_term(word, term).is_some())).count()
+}
+
+/// Byte offset in `sentence` of the first word matching one of `terms`.
+fn first_match(sentence: &str, terms: &[String]) -> Option<usize> {
+    let mut at = 0;
+    for piece in sentence.split_inclusive(|c: char| !c.is_alphanumeric()) {
+        let word = piece.trim_end_matches(|c: char| !c.is_alphanumeric()).to_lowercase();
+        if let Some(offset) = terms.iter().find_map(|term| find_term(&word, term)) {
+            return Some(at + offset);
+        }
+        at += piece.len();
+    }
+    None
+}
+
+/// Sentence spans in `text` as byte ranges, trimmed. A sentence ends at a
+/// line break, at `.`, `!` or `?` before a space, or at CJK sentence
+/// punctuation anywhere.
+fn sentences(text: &str) -> Vec<(usize, usize)> {
+    let mut spans = Vec::new();
+    let mut start = 0;
+    let mut chars = text.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        let next = chars.peek().map(|&(_, n)| n);
+        let ends = match c {
+            '\n' => true,
+            '.' | '!' | '?' => next.is_none_or(char::is_whitespace),
+            c => cjk::SENTENCE_TERMINATORS.contains(&c),
+        };
+        if ends {
+            spans.push((start, i + c.len_utf8()));
+            start = i + c.len_utf8();
+        }
+    }
+    spans.push((start, text.len()));
+    spans
+        .into_iter()
+        .filter_map(|(start, end)| {
+            let piece = &text[start..end];
+            let lead = piece.len() - piece.trim_start().len();
+            let trimmed = piece.trim();
+            (!trimmed.is_empty()).then(|| (start + lead, start + lead + trimmed.len()))
+        })
+        .collect()
+}
+
+/// `max_chars` of `text[start..end]` with `focus` a third of the way in,
+/// moved to word breaks where there's one to move to.
+fn crop(text: &str, start: usize, end: usize, focus: usize, max_chars: usize) -> (usize, usize) {
+    let offsets: Vec<usize> = text[start..end].char_indices().map(|(i, _)| start + i).collect();
+    let focus_at = offsets.partition_point(|&o| o < focus);
+    let to_at = (focus_at.saturating_sub(max_chars / 3) + max_chars).min(offsets.len());
+    let from_at = to_at.saturating_sub(max_chars);
+    let (mut from, mut to) = (offsets[from_at], offsets.get(to_at).copied().unwrap_or(end));
+
+    if from > start && !text[..from].ends_with(char::is_whitespace) {
+        if let Some(gap) = text[from..to].find(char::is_whitespace) {
+            if from + gap <= focus {
+                from += gap;
+            }
+        }
+    }
+    if to < end && !text[to..].starts_with(char::is_whitespace) {
+        if let Some(gap) = text[from..to].rfind(char::is_whitespace) {
+            if from + gap > focus {
+                to = from + gap;
+            }
+        }
+    }
+    (from, to)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn terms(words: &[&str]) -> Vec<String> {
+        words.iter().map(|w| w.to_string()).collect()
+    }
+
+    #[test]
+    fn test_body() {
+        assert_eq!(body("File: a.md\nContext: Setup\nInstall it."), "Install it.");
+        assert_eq!(body("File: a.md\nInstall it."), "Install it.");
+        assert_eq!(body("[annotation] ask Ana"), "ask Ana");
+        assert_eq!(body("[summary] Trip notes.\nCovers: day1.md"), "Trip notes.\nCovers: day1.md");
+        assert_eq!(body("plain text"), "plain text");
+    }
+
+    #[test]
+    fn test_excerpt_centers_on_match() {
+        let text = "The project started in spring. Nobody knew the scope. \
+                    The budget was approved in May. Hiring began right after. Offices moved twice.";
+        let out = excerpt(text, &terms(&["budget"]), 70);
+        assert!(out.contains("The budget was approved in May."), "{}", out);
+        assert!(out.starts_with('…') && out.ends_with('…'), "{}", out);
+        assert!(out.chars().count() <= 72, "{}", out);
+
+        assert_eq!(excerpt(text, &[], 0), text);
+        assert!(excerpt(text, &[], 40).starts_with("The project started in spring."));
+        assert!(!excerpt(text, &[], 40).starts_with('…'));
+    }
+
+    #[test]
+    fn test_excerpt_cjk() {
+        let text = "日本語の文です。予算は五月に承認された。その後採用が始まった。";
+        assert_eq!(excerpt(text, &terms(&["予算"]), 12), "…予算は五月に承認された。…");
+    }
+
+    #[test]
+    fn test_excerpt_long_sentence() {
+        let text = format!("{} deadline {}", "word ".repeat(60), "word ".repeat(60));
+        let out = excerpt(&text, &terms(&["deadline"]), 60);
+        assert!(out.contains("deadline"), "{}", out);
+        assert!(out.starts_with("…word") && out.ends_with("word…"), "{}", out);
+        assert!(out.chars().count() <= 62, "{}", out);
+    }
+
+    #[test]
+    fn test_stitch() {
+        let a = "fn open() {\n    let file = File::open(path)?;\n    read(file)\n}";
+        let b = "    let file = File::open(path)?;\n    read(file)\n}\n\nfn close() {}";
+        assert_eq!(stitch(&[a, b]), format!("{}\n\nfn close() {{}}", a));
+        assert_eq!(stitch(&[a, "read(file)"]), a);
+        assert_eq!(stitch(&["First part.", "", "Far away."]), "First part.\n…\nFar away.");
+    }
+
+    #[test]
+    fn test_query_terms_stemmed() {
+        let expansion = QueryExpansion::new(true, &[]);
+        let terms = query_terms("the indexing configs?", &expansion, None);
+        assert!(terms.contains(&"indexing".to_string()));
+        assert!(terms.contains(&"index".to_string()));
+        assert!(!terms.contains(&"the".to_string()));
+        assert!(matched("Index config lives here.", &terms) >= 2);
+    }
+}
@@ -0,0 +1,163 @@
+use std::collections::HashMap;
+
+use anyhow::Result;
+use arrow_array::StringArray;
+use futures::TryStreamExt;
+use lancedb::query::{ExecutableQuery, QueryBase};
+use lancedb::Table;
+
+/// Shorter words are too easy to "correct" into something unrelated.
+const MIN_WORD_LEN: usize = 4;
+const MAX_WORD_LEN: usize = 32;
+
+/// Every word in a container's indexed text with how many chunks it appears
+/// in. Built from the same `content` the keyword index covers, so a word
+/// missing here can't match anything in keyword search either.
+#[derive(Debug, Default)]
+pub struct TermDictionary {
+    terms: HashMap<String, u32>,
+}
+
+fn words(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|w| (MIN_WORD_LEN..=MAX_WORD_LEN).contains(&w.chars().count()))
+        .filter(|w| !w.chars().any(|c| c.is_ascii_digit() || super::cjk::is_cjk(c)))
+        .map(str::to_lowercase)
+}
+
+impl TermDictionary {
+    pub fn from_texts<'a>(texts: impl IntoIterator<Item = &'a str>) -> Self {
+        let mut terms: HashMap<String, u32> = HashMap::new();
+        for text in texts {
+            let mut seen = std::collections::HashSet::new();
+            for word in words(text) {
+                if seen.insert(word.clone()) {
+                    *terms.entry(word).or_default() += 1;
+                }
+            }
+        }
+        Self { terms }
+    }
+
+    pub async fn build(table: &Table) -> Result<Self> {
+        let batches = table
+            .query()
+            .select(lancedb::query::Select::Columns(vec!["content".to_string()]))
+            .execute()
+            .await?
+            .try_collect::<Vec<_>>()
+            .await?;
+
+        let texts = batches.iter().filter_map(|b| {
+            b.column_by_name("content").and_then(|c| c.as_any().downcast_ref::<StringArray>())
+        });
+        Ok(Self::from_texts(texts.flat_map(|col| col.iter().flatten())))
+    }
+
+    pub fn len(&self) -> usize {
+        self.terms.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.terms.is_empty()
+    }
+
+    /// Closest known word within one edit for short words, two for longer
+    /// ones. Ties go to the more common word.
+    fn correct(&self, word: &str) -> Option<&str> {
+        let len = word.chars().count();
+        let max = if len <= 5 { 1 } else { 2 };
+        self.terms
+            .iter()
+            .filter(|(term, _)| term.chars().count().abs_diff(len) <= max)
+            .filter_map(|(term, &freq)| edit_distance(word, term, max).map(|d| (term, d, freq)))
+            .min_by(|a, b| a.1.cmp(&b.1).then(b.2.cmp(&a.2)).then(a.0.cmp(b.0)))
+            .map(|(term, _, _)| term.as_str())
+    }
+
+    /// The query with every unknown word swapped for its closest indexed
+    /// word, or None when every word is already known or nothing is close.
+    pub fn suggest(&self, query: &str) -> Option<String> {
+        if self.terms.is_empty() {
+            return None;
+        }
+        let mut changed = false;
+        let corrected: Vec<String> = query
+            .split_whitespace()
+            .map(|token| {
+                let lower = token.to_lowercase();
+                let eligible = lower.chars().all(char::is_alphabetic)
+                    && (MIN_WORD_LEN..=MAX_WORD_LEN).contains(&lower.chars().count())
+                    && !super::cjk::contains_cjk(&lower);
+                if !eligible || self.terms.contains_key(&lower) {
+                    return token.to_string();
+                }
+                match self.correct(&lower) {
+                    Some(fix) => {
+                        changed = true;
+                        fix.to_string()
+                    }
+                    None => token.to_string(),
+                }
+            })
+            .collect();
+        changed.then(|| corrected.join(" "))
+    }
+}
+
+/// Optimal string alignment distance (Levenshtein plus adjacent swaps), or
+/// None once it's certain to exceed `max`.
+fn edit_distance(a: &str, b: &str, max: usize) -> Option<usize> {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut prev2: Vec<usize> = vec![0; b.len() + 1];
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    let mut cur: Vec<usize> = vec![0; b.len() + 1];
+
+    for i in 1..=a.len() {
+        cur[0] = i;
+        let mut row_min = cur[0];
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            cur[j] = (prev[j] + 1).min(cur[j - 1] + 1).min(prev[j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                cur[j] = cur[j].min(prev2[j - 2] + 1);
+            }
+            row_min = row_min.min(cur[j]);
+        }
+        if row_min > max {
+            return None;
+        }
+        std::mem::swap(&mut prev2, &mut prev);
+        std::mem::swap(&mut prev, &mut cur);
+    }
+
+    let d = prev[b.len()];
+    (d <= max).then_some(d)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("kubernetes", "kubernetes", 2), Some(0));
+        assert_eq!(edit_distance("kuberentes", "kubernetes", 2), Some(1));
+        assert_eq!(edit_distance("kubrnetes", "kubernetes", 2), Some(1));
+        assert_eq!(edit_distance("search", "index", 2), None);
+    }
+
+    #[test]
+    fn test_suggest() {
+        let dict = TermDictionary::from_texts([
+            "kubernetes deployment guide",
+            "kubernetes pods and services",
+            "authentication flow",
+        ]);
+        assert_eq!(dict.suggest("kuberentes deploymnet").as_deref(), Some("kubernetes deployment"));
+        assert_eq!(dict.suggest("kubernetes pods"), None);
+        assert_eq!(dict.suggest("a zzzzzzzz"), None);
+        assert_eq!(dict.suggest("the kubrnetes"), Some("the kubernetes".to_string()));
+    }
+}
@@ -0,0 +1,119 @@
+use serde::Deserialize;
+use serde_json::Value;
+
+/// Keeps a huge lockfile-like JSON from turning into thousands of key chunks.
+const MAX_KEYS: usize = 5000;
+/// Long strings (embedded certs, minified blobs) say nothing about the key.
+const MAX_VALUE_CHARS: usize = 200;
+
+fn parse(text: &str, ext: &str) -> Option<Vec<Value>> {
+    match ext {
+        "json" => serde_json::from_str(text).ok().map(|v| vec![v]),
+        "yaml" | "yml" => {
+            let docs: Result<Vec<Value>, _> = serde_yaml::Deserializer::from_str(text)
+                .map(Value::deserialize)
+                .collect();
+            docs.ok()
+        }
+        "toml" => toml::from_str(text).ok().map(|v| vec![v]),
+        _ => None,
+    }
+}
+
+fn scalar(value: &Value) -> Option<String> {
+    let s = match value {
+        Value::String(s) => s.clone(),
+        Value::Number(n) => n.to_string(),
+        Value::Bool(b) => b.to_string(),
+        Value::Null => "null".to_string(),
+        Value::Array(_) | Value::Object(_) => return None,
+    };
+    let s = s.replace('\n', " ");
+    Some(match s.char_indices().nth(MAX_VALUE_CHARS) {
+        Some((i, _)) => format!("{}…", &s[..i]),
+        None => s,
+    })
+}
+
+fn walk(value: &Value, path: &mut String, out: &mut Vec<String>) {
+    if out.len() >= MAX_KEYS {
+        return;
+    }
+    match value {
+        Value::Object(map) => {
+            for (key, child) in map {
+                let len = path.len();
+                if !path.is_empty() {
+                    path.push('.');
+                }
+                path.push_str(key);
+                walk(child, path, out);
+                path.truncate(len);
+            }
+        }
+        Value::Array(items) => {
+            for (i, child) in items.iter().enumerate() {
+                let len = path.len();
+                path.push_str(&format!("[{}]", i));
+                walk(child, path, out);
+                path.truncate(len);
+            }
+        }
+        _ => {
+            if let Some(v) = scalar(value) {
+                if !path.is_empty() {
+                    out.push(format!("{}: {}", path, v));
+                }
+            }
+        }
+    }
+}
+
+/// One `key.path: value` line per leaf of a JSON, YAML or TOML file, so a
+/// search for a setting name lands on the line that sets it even when the
+/// raw text splits the path across indentation levels. None for other file
+/// types and for files that don't parse.
+pub fn flatten(text: &str, ext: &str) -> Option<String> {
+    let docs = parse(text, ext)?;
+    let mut lines = Vec::new();
+    for doc in &docs {
+        walk(doc, &mut String::new(), &mut lines);
+    }
+    (!lines.is_empty()).then(|| lines.join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Key order follows serde_json's map, which may or may not preserve it.
+    fn sorted(text: String) -> Vec<String> {
+        let mut lines: Vec<String> = text.lines().map(str::to_string).collect();
+        lines.sort();
+        lines
+    }
+
+    #[test]
+    fn test_flatten_json() {
+        let text = r#"{"server": {"port": 8080, "hosts": ["a", "b"]}, "debug": true}"#;
+        assert_eq!(
+            sorted(flatten(text, "json").unwrap()),
+            vec!["debug: true", "server.hosts[0]: a", "server.hosts[1]: b", "server.port: 8080"]
+        );
+    }
+
+    #[test]
+    fn test_flatten_yaml_and_toml() {
+        let yaml = "database:\n  pool:\n    max_size: 20\n---\nname: second\n";
+        assert_eq!(flatten(yaml, "yaml").unwrap(), "database.pool.max_size: 20\nname: second");
+        let toml = "[package]\nname = \"rememex\"\n\n[dependencies]\nregex = \"1\"\n";
+        assert_eq!(sorted(flatten(toml, "toml").unwrap()), vec!["dependencies.regex: 1", "package.name: rememex"]);
+    }
+
+    #[test]
+    fn test_flatten_rejects_invalid() {
+        assert_eq!(flatten("{ not json", "json"), None);
+        assert_eq!(flatten("key: value", "txt"), None);
+        assert_eq!(flatten("[]", "json"), None);
+    }
+}
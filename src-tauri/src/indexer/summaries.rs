@@ -0,0 +1,387 @@
+//! LLM-written summaries over the index: one per folder from the start of
+//! its files, one per indexed folder from the summaries under it.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use arrow_array::{FixedSizeListArray, Float32Array, Int64Array, RecordBatch, RecordBatchIterator, StringArray};
+use arrow_schema::{DataType, Field, Schema};
+use futures::TryStreamExt;
+use lancedb::connection::Connection;
+use lancedb::query::{ExecutableQuery, QueryBase};
+use lancedb::{DistanceType, Table};
+use log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+
+use super::dedup::in_filters;
+use crate::state::ProviderState;
+
+/// Off by default: every changed folder sends the start of its files.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct SummaryConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default)]
+    pub endpoint: String,
+    #[serde(default)]
+    pub model: String,
+    pub api_key: Option<String>,
+}
+
+impl SummaryConfig {
+    pub fn is_active(&self) -> bool {
+        self.enabled && !self.endpoint.is_empty()
+    }
+}
+
+/// A folder's files.
+const FOLDER_LEVEL: i64 = 1;
+/// An indexed folder's summaries.
+const ROOT_LEVEL: i64 = 2;
+const MAX_CHILDREN: usize = 40;
+const EXCERPT_CHARS: usize = 400;
+pub const SEARCH_HITS: usize = 3;
+const LISTED_CHILDREN: usize = 8;
+const MAX_TOKENS: u32 = 200;
+const TIMEOUT: Duration = Duration::from_secs(60);
+
+const FOLDER_PROMPT: &str = "\
+You summarize folders for a search index. Given the start of each file in a folder, \
+write 2-3 sentences on what the folder is for and what it covers. Name the concrete \
+topics, projects or components. No preamble, no lists.";
+
+const ROOT_PROMPT: &str = "\
+You summarize folders for a search index. Given summaries of the folders under one \
+folder, write 3-4 sentences on what it is as a whole and how its parts fit together. \
+No preamble, no lists.";
+
+pub fn summaries_table_name(container_table: &str) -> String {
+    format!("{}_summaries", container_table)
+}
+
+fn make_schema(dim: usize) -> Schema {
+    Schema::new(vec![
+        Field::new("path", DataType::Utf8, false),
+        Field::new("level", DataType::Int64, false),
+        Field::new("summary", DataType::Utf8, false),
+        // One path per line.
+        Field::new("children", DataType::Utf8, false),
+        Field::new(
+            "vector",
+            DataType::FixedSizeList(Arc::new(Field::new("item", DataType::Float32, true)), dim as i32),
+            false,
+        ),
+    ])
+}
+
+async fn open(db: &Connection, container_table: &str) -> Option<Table> {
+    db.open_table(summaries_table_name(container_table)).execute().await.ok()
+}
+
+fn quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+fn parent_dir(path: &str) -> String {
+    Path::new(path).parent().map(|p| p.to_string_lossy().into_owned()).unwrap_or_default()
+}
+
+fn name(path: &str) -> String {
+    Path::new(path).file_name().map_or_else(|| path.to_string(), |n| n.to_string_lossy().into_owned())
+}
+
+fn strings<'a>(batch: &'a RecordBatch, column: &str) -> Option<&'a StringArray> {
+    batch.column_by_name(column).and_then(|c| c.as_any().downcast_ref::<StringArray>())
+}
+
+async fn excerpts(table: &Table, dir: &str) -> Result<BTreeMap<String, Vec<(String, String)>>> {
+    let prefix = super::search::build_filter_expr(Some(&super::search::with_trailing_separator(dir)), None, None, None)
+        .unwrap_or_default();
+    let filter = format!("{} AND {} < 1", prefix, super::db::CHUNK_START_COLUMN);
+    let batches = table
+        .query()
+        .only_if(filter)
+        .select(lancedb::query::Select::Columns(vec!["path".to_string(), "content".to_string()]))
+        .execute()
+        .await?
+        .try_collect::<Vec<_>>()
+        .await?;
+    let mut seen = BTreeSet::new();
+    let mut folders: BTreeMap<String, Vec<(String, String)>> = BTreeMap::new();
+    for batch in &batches {
+        let (Some(paths), Some(contents)) = (strings(batch, "path"), strings(batch, "content")) else {
+            continue;
+        };
+        for i in 0..batch.num_rows() {
+            let path = paths.value(i);
+            if seen.insert(path.to_string()) {
+                let excerpt: String = contents.value(i).chars().take(EXCERPT_CHARS).collect();
+                folders.entry(parent_dir(path)).or_default().push((path.to_string(), excerpt));
+            }
+        }
+    }
+    Ok(folders)
+}
+
+fn folder_prompt(dir: &str, files: &[(String, String)]) -> String {
+    let mut prompt = format!("Folder: {}\n", name(dir));
+    for (_, excerpt) in files.iter().take(MAX_CHILDREN) {
+        prompt.push_str(&format!("\n---\n{}\n", excerpt.trim()));
+    }
+    if files.len() > MAX_CHILDREN {
+        prompt.push_str(&format!("\n---\n({} more files)\n", files.len() - MAX_CHILDREN));
+    }
+    prompt
+}
+
+fn root_prompt(root: &str, folders: &[(String, String)]) -> String {
+    let mut prompt = format!("Folder: {}\n", name(root));
+    for (dir, summary) in folders.iter().take(MAX_CHILDREN) {
+        let relative = dir.strip_prefix(root).unwrap_or(dir).trim_start_matches(['/', '\\']);
+        let label = if relative.is_empty() { "(top level)" } else { relative };
+        prompt.push_str(&format!("\n{}: {}\n", label, summary.trim()));
+    }
+    prompt
+}
+
+async fn summarize(config: &SummaryConfig, system: &str, prompt: &str) -> Result<String> {
+    let summary = super::hyde::chat(
+        &config.endpoint,
+        &config.model,
+        config.api_key.as_deref(),
+        system,
+        prompt,
+        MAX_TOKENS,
+        TIMEOUT,
+    )
+    .await?;
+    Ok(summary.trim().to_string())
+}
+
+async fn write_nodes(
+    db: &Connection,
+    container_table: &str,
+    provider_state: &Arc<Mutex<ProviderState>>,
+    level: i64,
+    nodes: Vec<(String, String, Vec<String>)>,
+) -> Result<()> {
+    if nodes.is_empty() {
+        return Ok(());
+    }
+    let paths: Vec<String> = nodes.iter().map(|(path, _, _)| path.clone()).collect();
+    let vectors = super::embed_batch(provider_state, nodes.iter().map(|(_, summary, _)| summary.clone()).collect()).await?;
+    let dim = vectors.first().map_or(0, Vec::len);
+    let schema = Arc::new(make_schema(dim));
+    let batch = RecordBatch::try_new(
+        schema.clone(),
+        vec![
+            Arc::new(StringArray::from(paths.clone())),
+            Arc::new(Int64Array::from(vec![level; nodes.len()])),
+            Arc::new(StringArray::from(nodes.iter().map(|(_, summary, _)| summary.clone()).collect::<Vec<_>>())),
+            Arc::new(StringArray::from(nodes.iter().map(|(_, _, children)| children.join("\n")).collect::<Vec<_>>())),
+            Arc::new(FixedSizeListArray::try_new(
+                Arc::new(Field::new("item", DataType::Float32, true)),
+                dim as i32,
+                Arc::new(Float32Array::from(vectors.concat())),
+                None,
+            )?),
+        ],
+    )?;
+    let rows = RecordBatchIterator::new(vec![Ok(batch)], schema);
+    match open(db, container_table).await {
+        Some(table) => {
+            for filter in in_filters("path", &paths) {
+                table.delete(&format!("level = {} AND {}", level, filter)).await?;
+            }
+            table.add(rows).execute().await?;
+        }
+        None => {
+            db.create_table(summaries_table_name(container_table), rows).execute().await?;
+            info!("Summaries table for '{}' created", container_table);
+        }
+    }
+    Ok(())
+}
+
+async fn folder_summaries(table: &Table, root: &str) -> Result<Vec<(String, String)>> {
+    let prefix = super::search::build_filter_expr(Some(&super::search::with_trailing_separator(root)), None, None, None)
+        .unwrap_or_default();
+    let batches = table
+        .query()
+        .only_if(format!("level = {} AND (path = {} OR {})", FOLDER_LEVEL, quote(root), prefix))
+        .select(lancedb::query::Select::Columns(vec!["path".to_string(), "summary".to_string(), "children".to_string()]))
+        .execute()
+        .await?
+        .try_collect::<Vec<_>>()
+        .await?;
+    let mut folders: Vec<(usize, String, String)> = Vec::new();
+    for batch in &batches {
+        if let (Some(paths), Some(summaries), Some(children)) =
+            (strings(batch, "path"), strings(batch, "summary"), strings(batch, "children"))
+        {
+            for i in 0..batch.num_rows() {
+                let files = children.value(i).lines().count();
+                folders.push((files, paths.value(i).to_string(), summaries.value(i).to_string()));
+            }
+        }
+    }
+    folders.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.cmp(&b.1)));
+    Ok(folders.into_iter().map(|(_, path, summary)| (path, summary)).collect())
+}
+
+/// With no summary for `root` yet, every folder under it is done. A folder
+/// the endpoint fails on keeps its old summary.
+pub async fn refresh(
+    db: &Connection,
+    table: &Table,
+    container_table: &str,
+    config: &SummaryConfig,
+    provider_state: &Arc<Mutex<ProviderState>>,
+    root: &str,
+    changed: &[String],
+) -> Result<()> {
+    if !config.is_active() {
+        return Ok(());
+    }
+    if crate::config::is_offline() {
+        debug!("Summaries skipped in offline mode");
+        return Ok(());
+    }
+    let has_root = match open(db, container_table).await {
+        Some(summaries) => summaries.count_rows(Some(format!("path = {} AND level = {}", quote(root), ROOT_LEVEL))).await? > 0,
+        None => false,
+    };
+    let folders = if has_root {
+        let dirs: BTreeSet<String> = changed.iter().map(|path| parent_dir(path)).collect();
+        let mut folders = BTreeMap::new();
+        for dir in dirs {
+            let files = excerpts(table, &dir).await?.remove(&dir).unwrap_or_default();
+            folders.insert(dir, files);
+        }
+        folders
+    } else {
+        excerpts(table, root).await?
+    };
+    if folders.is_empty() {
+        return Ok(());
+    }
+
+    let mut nodes = Vec::new();
+    let mut written = 0;
+    for (dir, files) in &folders {
+        if files.is_empty() {
+            if let Some(summaries) = open(db, container_table).await {
+                summaries.delete(&format!("path = {} AND level = {}", quote(dir), FOLDER_LEVEL)).await?;
+            }
+            continue;
+        }
+        match summarize(config, FOLDER_PROMPT, &folder_prompt(dir, files)).await {
+            Ok(summary) => nodes.push((dir.clone(), summary, files.iter().map(|(path, _)| path.clone()).collect())),
+            Err(e) => warn!("Summarizing {} failed: {}", dir, e),
+        }
+        if nodes.len() >= super::EMBED_BATCH_SIZE {
+            written += nodes.len();
+            write_nodes(db, container_table, provider_state, FOLDER_LEVEL, std::mem::take(&mut nodes)).await?;
+        }
+    }
+    written += nodes.len();
+    write_nodes(db, container_table, provider_state, FOLDER_LEVEL, nodes).await?;
+
+    let Some(summaries) = open(db, container_table).await else {
+        return Ok(());
+    };
+    let under_root = folder_summaries(&summaries, root).await?;
+    if under_root.is_empty() {
+        return Ok(());
+    }
+    match summarize(config, ROOT_PROMPT, &root_prompt(root, &under_root)).await {
+        Ok(summary) => {
+            let children = under_root.iter().map(|(dir, _)| dir.clone()).collect();
+            write_nodes(db, container_table, provider_state, ROOT_LEVEL, vec![(root.to_string(), summary, children)]).await?;
+        }
+        Err(e) => warn!("Summarizing {} failed: {}", root, e),
+    }
+    info!("Summaries: {} folders under {} summarized", written, root);
+    Ok(())
+}
+
+fn snippet(summary: &str, children: &str) -> String {
+    let names: Vec<String> = children.lines().map(name).collect();
+    let mut listed = names.iter().take(LISTED_CHILDREN).cloned().collect::<Vec<_>>().join(", ");
+    if names.len() > LISTED_CHILDREN {
+        listed.push_str(&format!(" and {} more", names.len() - LISTED_CHILDREN));
+    }
+    format!("[summary] {}\nCovers: {}", summary, listed)
+}
+
+pub async fn search_summaries(
+    db: &Connection,
+    container_table: &str,
+    query_vector: &[f32],
+    limit: usize,
+) -> Result<Vec<(String, String, f32)>> {
+    let Some(table) = open(db, container_table).await else {
+        return Ok(vec![]);
+    };
+    let results = table
+        .vector_search(query_vector)?
+        .distance_type(DistanceType::Cosine)
+        .select(lancedb::query::Select::Columns(vec!["path".to_string(), "summary".to_string(), "children".to_string()]))
+        .limit(limit)
+        .execute()
+        .await?
+        .try_collect::<Vec<_>>()
+        .await?;
+
+    let mut matches = Vec::new();
+    for batch in &results {
+        let dists = batch.column_by_name("_distance").and_then(|c| c.as_any().downcast_ref::<Float32Array>());
+        if let (Some(paths), Some(summaries), Some(children), Some(dists)) =
+            (strings(batch, "path"), strings(batch, "summary"), strings(batch, "children"), dists)
+        {
+            for i in 0..batch.num_rows() {
+                matches.push((paths.value(i).to_string(), snippet(summaries.value(i), children.value(i)), dists.value(i)));
+            }
+        }
+    }
+    matches.sort_by(|a, b| a.2.partial_cmp(&b.2).unwrap_or(std::cmp::Ordering::Equal));
+    Ok(matches)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prompts() {
+        let files: Vec<(String, String)> = (0..MAX_CHILDREN + 2)
+            .map(|i| (format!("/notes/trip/day{}.md", i), format!("File: day{}.md\nDay {} in Lisbon", i, i)))
+            .collect();
+        let prompt = folder_prompt("/notes/trip", &files);
+        assert!(prompt.starts_with("Folder: trip\n"));
+        assert!(prompt.contains("Day 0 in Lisbon"));
+        assert!(!prompt.contains(&format!("Day {} in Lisbon", MAX_CHILDREN)));
+        assert!(prompt.ends_with("(2 more files)\n"));
+
+        let folders = vec![
+            ("/notes".to_string(), "Loose notes.".to_string()),
+            ("/notes/trip".to_string(), "A week in Lisbon.".to_string()),
+        ];
+        let prompt = root_prompt("/notes", &folders);
+        assert!(prompt.contains("(top level): Loose notes."));
+        assert!(prompt.contains("trip: A week in Lisbon."));
+    }
+
+    #[test]
+    fn test_snippet() {
+        let children: Vec<String> = (0..LISTED_CHILDREN + 3).map(|i| format!("/notes/trip/day{}.md", i)).collect();
+        let text = snippet("A week in Lisbon.", &children.join("\n"));
+        assert!(text.starts_with("[summary] A week in Lisbon.\nCovers: day0.md, day1.md"));
+        assert!(text.ends_with(" and 3 more"));
+        assert_eq!(snippet("Loose notes.", "/notes/a.md"), "[summary] Loose notes.\nCovers: a.md");
+    }
+}
@@ -0,0 +1,182 @@
+use std::collections::HashMap;
+
+/// Files with more data rows than this get summarized instead of chunked whole.
+const SUMMARIZE_ABOVE_ROWS: usize = 200;
+/// Rows kept in the summary: the first few, then evenly spaced through the rest.
+const SAMPLE_ROWS: usize = 60;
+const HEAD_ROWS: usize = 10;
+/// Distinct values tracked per column before giving up on exact counts.
+const MAX_DISTINCT: usize = 10_000;
+const TOP_VALUES: usize = 5;
+const MAX_VALUE_CHARS: usize = 60;
+
+fn delimiter_for(ext: &str) -> Option<char> {
+    match ext {
+        "csv" => Some(','),
+        "tsv" => Some('\t'),
+        _ => None,
+    }
+}
+
+/// RFC 4180-ish: quoted fields may contain the delimiter, newlines and "".
+fn parse_rows(text: &str, delimiter: char) -> Vec<Vec<String>> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    field.push('"');
+                    chars.next();
+                }
+                '"' => in_quotes = false,
+                _ => field.push(c),
+            }
+        } else if c == '"' && field.is_empty() {
+            in_quotes = true;
+        } else if c == delimiter {
+            row.push(std::mem::take(&mut field));
+        } else if c == '\n' || c == '\r' {
+            if c == '\r' && chars.peek() == Some(&'\n') {
+                chars.next();
+            }
+            row.push(std::mem::take(&mut field));
+            if row.iter().any(|f| !f.is_empty()) {
+                rows.push(std::mem::take(&mut row));
+            } else {
+                row.clear();
+            }
+        } else {
+            field.push(c);
+        }
+    }
+    row.push(field);
+    if row.iter().any(|f| !f.is_empty()) {
+        rows.push(row);
+    }
+    rows
+}
+
+fn clip(value: &str) -> String {
+    let value = value.trim();
+    match value.char_indices().nth(MAX_VALUE_CHARS) {
+        Some((i, _)) => format!("{}…", &value[..i]),
+        None => value.to_string(),
+    }
+}
+
+fn column_stats(name: &str, values: &[&str]) -> String {
+    let present: Vec<&str> = values.iter().map(|v| v.trim()).filter(|v| !v.is_empty()).collect();
+    let empty = values.len() - present.len();
+    let empty_note = if empty > 0 { format!(", {} empty", empty) } else { String::new() };
+
+    if present.is_empty() {
+        return format!("- {}: always empty", name);
+    }
+
+    let numbers: Vec<f64> = present.iter().filter_map(|v| v.parse::<f64>().ok()).collect();
+    if numbers.len() == present.len() {
+        let min = numbers.iter().cloned().fold(f64::INFINITY, f64::min);
+        let max = numbers.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+        return format!("- {}: number, {} to {}{}", name, min, max, empty_note);
+    }
+
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for v in &present {
+        if counts.len() < MAX_DISTINCT || counts.contains_key(v) {
+            *counts.entry(v).or_default() += 1;
+        }
+    }
+    let distinct = if counts.len() >= MAX_DISTINCT {
+        format!("{}+", MAX_DISTINCT)
+    } else {
+        counts.len().to_string()
+    };
+    let mut top: Vec<(&str, usize)> = counts.into_iter().collect();
+    top.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+    let examples: Vec<String> = top.iter().take(TOP_VALUES).map(|(v, _)| clip(v)).collect();
+    format!("- {}: text, {} distinct{}, e.g. {}", name, distinct, empty_note, examples.join(", "))
+}
+
+fn sample_indices(total: usize) -> Vec<usize> {
+    if total <= SAMPLE_ROWS {
+        return (0..total).collect();
+    }
+    let mut indices: Vec<usize> = (0..HEAD_ROWS).collect();
+    let rest = SAMPLE_ROWS - HEAD_ROWS;
+    let span = total - HEAD_ROWS;
+    indices.extend((0..rest).map(|i| HEAD_ROWS + i * span / rest));
+    indices
+}
+
+/// For a large CSV or TSV: its columns, per-column stats and a spread-out
+/// sample of rows, so the file is findable by what it holds without
+/// indexing every row. None for other file types and for files small enough
+/// to index whole.
+pub fn summarize(text: &str, ext: &str) -> Option<String> {
+    let delimiter = delimiter_for(ext)?;
+    let rows = parse_rows(text, delimiter);
+    let (header, data) = rows.split_first()?;
+    if data.len() <= SUMMARIZE_ABOVE_ROWS {
+        return None;
+    }
+
+    let sep = if delimiter == '\t' { "\t" } else { ", " };
+    let mut out = format!("Columns: {}\nRows: {}\n\nColumn stats:\n", header.join(", "), data.len());
+    for (i, name) in header.iter().enumerate() {
+        let values: Vec<&str> = data.iter().map(|r| r.get(i).map_or("", String::as_str)).collect();
+        out.push_str(&column_stats(name, &values));
+        out.push('\n');
+    }
+
+    out.push_str("\nSample rows:\n");
+    out.push_str(&header.join(sep));
+    out.push('\n');
+    for i in sample_indices(data.len()) {
+        let row: Vec<String> = data[i].iter().map(|v| clip(v)).collect();
+        out.push_str(&row.join(sep));
+        out.push('\n');
+    }
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_rows_quotes() {
+        let rows = parse_rows("a,b\n\"x, y\",\"say \"\"hi\"\"\"\n\"multi\nline\",2\n", ',');
+        assert_eq!(rows, vec![
+            vec!["a".to_string(), "b".to_string()],
+            vec!["x, y".to_string(), "say \"hi\"".to_string()],
+            vec!["multi\nline".to_string(), "2".to_string()],
+        ]);
+    }
+
+    #[test]
+    fn test_summarize_large_csv() {
+        let mut text = String::from("id,city,note\n");
+        for i in 0..1000 {
+            let city = ["Istanbul", "Berlin", "Tokyo"][i % 3];
+            let note = if i % 10 == 0 { "" } else { "ok" };
+            text.push_str(&format!("{},{},{}\n", i, city, note));
+        }
+        let summary = summarize(&text, "csv").unwrap();
+        assert!(summary.starts_with("Columns: id, city, note\nRows: 1000\n"));
+        assert!(summary.contains("- id: number, 0 to 999"));
+        assert!(summary.contains("- city: text, 3 distinct, e.g. Istanbul, Berlin, Tokyo"));
+        assert!(summary.contains("- note: text, 1 distinct, 100 empty, e.g. ok"));
+        assert_eq!(summary.split("Sample rows:\n").nth(1).unwrap().lines().count(), SAMPLE_ROWS + 1);
+    }
+
+    #[test]
+    fn test_summarize_skips_small_and_other() {
+        assert_eq!(summarize("a,b\n1,2\n", "csv"), None);
+        assert_eq!(summarize("a,b\n1,2\n", "txt"), None);
+    }
+}
@@ -0,0 +1,177 @@
+//! What happened when: indexed files by modification time and annotations
+//! by when they were written, grouped into days or weeks of local time.
+
+use std::collections::HashMap;
+
+use anyhow::Result;
+use arrow_array::{Int64Array, StringArray};
+use chrono::{Datelike, TimeZone};
+use futures::TryStreamExt;
+use lancedb::connection::Connection;
+use lancedb::query::{ExecutableQuery, QueryBase};
+use serde::{Deserialize, Serialize};
+
+/// Items listed per period; the rest are only counted.
+const MAX_ITEMS_PER_PERIOD: usize = 100;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Period {
+    #[default]
+    Day,
+    /// Monday to Sunday.
+    Week,
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct TimelineItem {
+    pub path: String,
+    /// Unix seconds: the file's mtime, or when the annotation was written.
+    pub time: i64,
+    /// The annotation's note; None for a file.
+    pub note: Option<String>,
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct TimelinePeriod {
+    /// Unix seconds of local midnight the period starts at.
+    pub start: i64,
+    /// Newest first.
+    pub items: Vec<TimelineItem>,
+    /// Items past the ones listed.
+    pub more: usize,
+}
+
+/// Start of the day or week `time` falls in, in `tz`.
+fn period_start<Tz: TimeZone>(tz: &Tz, time: i64, period: Period) -> i64 {
+    let Some(local) = tz.timestamp_opt(time, 0).earliest() else {
+        return time;
+    };
+    let mut date = local.date_naive();
+    if period == Period::Week {
+        date -= chrono::Duration::days(date.weekday().num_days_from_monday() as i64);
+    }
+    date.and_hms_opt(0, 0, 0)
+        .and_then(|midnight| tz.from_local_datetime(&midnight).earliest())
+        .map_or(time, |start| start.timestamp())
+}
+
+/// Whether every word of `query` is in the item's path or note.
+fn matches(item: &TimelineItem, words: &[String]) -> bool {
+    let path = item.path.to_lowercase();
+    let note = item.note.as_deref().unwrap_or_default().to_lowercase();
+    words.iter().all(|w| path.contains(w.as_str()) || note.contains(w.as_str()))
+}
+
+/// The newest `periods` periods `items` fall in, empty ones left out.
+fn group<Tz: TimeZone>(mut items: Vec<TimelineItem>, tz: &Tz, period: Period, periods: usize) -> Vec<TimelinePeriod> {
+    items.sort_by(|a, b| b.time.cmp(&a.time).then_with(|| a.path.cmp(&b.path)));
+    let mut grouped: Vec<TimelinePeriod> = Vec::new();
+    for item in items {
+        let start = period_start(tz, item.time, period);
+        if let Some(last) = grouped.last_mut().filter(|last| last.start == start) {
+            if last.items.len() < MAX_ITEMS_PER_PERIOD {
+                last.items.push(item);
+            } else {
+                last.more += 1;
+            }
+        } else if grouped.len() == periods {
+            break;
+        } else {
+            grouped.push(TimelinePeriod { start, items: vec![item], more: 0 });
+        }
+    }
+    grouped
+}
+
+/// The newest `periods` periods of `table_name` up to `until` (unix
+/// seconds, inclusive), keeping only items matching every word of `query`
+/// and outside `excluded`.
+pub async fn build(
+    db: &Connection,
+    table_name: &str,
+    period: Period,
+    query: Option<&str>,
+    until: Option<i64>,
+    periods: usize,
+    excluded: &[String],
+) -> Result<Vec<TimelinePeriod>> {
+    let Ok(table) = super::snapshot::open(db, table_name).await else {
+        return Ok(Vec::new());
+    };
+    let until = until.unwrap_or(i64::MAX);
+    let mut stream = table
+        .query()
+        .only_if(format!("mtime <= {}", until))
+        .select(lancedb::query::Select::Columns(vec!["path".to_string(), "mtime".to_string()]))
+        .execute()
+        .await?;
+    let mut mtimes: HashMap<String, i64> = HashMap::new();
+    while let Some(batch) = stream.try_next().await? {
+        let paths = batch.column_by_name("path").and_then(|c| c.as_any().downcast_ref::<StringArray>());
+        let times = batch.column_by_name("mtime").and_then(|c| c.as_any().downcast_ref::<Int64Array>());
+        let (Some(paths), Some(times)) = (paths, times) else {
+            continue;
+        };
+        for i in 0..batch.num_rows() {
+            let time = mtimes.entry(paths.value(i).to_string()).or_insert(times.value(i));
+            *time = (*time).max(times.value(i));
+        }
+    }
+
+    let mut items: Vec<TimelineItem> = mtimes
+        .into_iter()
+        .map(|(path, time)| TimelineItem { path, time, note: None })
+        .collect();
+    items.extend(
+        super::annotations::get_annotations(db, table_name, None)
+            .await?
+            .into_iter()
+            .filter(|a| a.created_at <= until)
+            .map(|a| TimelineItem { path: a.path, time: a.created_at, note: Some(a.note) }),
+    );
+    let words: Vec<String> = query.unwrap_or_default().split_whitespace().map(str::to_lowercase).collect();
+    items.retain(|item| matches(item, &words) && !crate::config::is_path_excluded(excluded, &item.path));
+    Ok(group(items, &chrono::Local, period, periods))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file(path: &str, time: i64) -> TimelineItem {
+        TimelineItem { path: path.to_string(), time, note: None }
+    }
+
+    #[test]
+    fn test_period_start() {
+        // 2024-05-16 (a Thursday) 01:30 at UTC+3 is still the 15th in UTC.
+        let tz = chrono::FixedOffset::east_opt(3 * 3600).unwrap();
+        let time = tz.with_ymd_and_hms(2024, 5, 16, 1, 30, 0).unwrap().timestamp();
+        let day = tz.with_ymd_and_hms(2024, 5, 16, 0, 0, 0).unwrap().timestamp();
+        let monday = tz.with_ymd_and_hms(2024, 5, 13, 0, 0, 0).unwrap().timestamp();
+        assert_eq!(period_start(&tz, time, Period::Day), day);
+        assert_eq!(period_start(&tz, time, Period::Week), monday);
+        assert_eq!(period_start(&tz, monday, Period::Week), monday);
+    }
+
+    #[test]
+    fn test_group() {
+        let tz = chrono::Utc;
+        let day = 86_400;
+        let items = vec![file("a.md", 10), file("b.md", day + 10), file("c.md", day + 20), file("d.md", 2 * day)];
+        let grouped = group(items.clone(), &tz, Period::Day, 2);
+        assert_eq!(grouped.len(), 2);
+        assert_eq!(grouped[0].start, 2 * day);
+        assert_eq!(grouped[1].items, vec![file("c.md", day + 20), file("b.md", day + 10)]);
+
+        let many: Vec<TimelineItem> = (0..MAX_ITEMS_PER_PERIOD as i64 + 3).map(|i| file("x.md", i)).collect();
+        let grouped = group(many, &tz, Period::Week, 5);
+        assert_eq!((grouped.len(), grouped[0].items.len(), grouped[0].more), (1, MAX_ITEMS_PER_PERIOD, 3));
+
+        let note = TimelineItem { path: "/notes/plan.md".to_string(), time: 0, note: Some("Ask about the Budget".to_string()) };
+        assert!(matches(&note, &["plan".to_string(), "budget".to_string()]));
+        assert!(!matches(&note, &["plan".to_string(), "invoice".to_string()]));
+        assert!(matches(&items[0], &[]));
+    }
+}
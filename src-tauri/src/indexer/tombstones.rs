@@ -0,0 +1,90 @@
+use std::collections::{HashMap, HashSet};
+
+use anyhow::Result;
+use arrow_array::StringArray;
+use futures::TryStreamExt;
+use lancedb::connection::Connection;
+use lancedb::query::{ExecutableQuery, QueryBase};
+use log::debug;
+
+use super::search::{best_per_file, nearest_chunks, open_for_search};
+use super::versions::{copy_rows, expire};
+
+/// Chunks of files the watcher saw deleted, kept with their vectors for a
+/// while so a note deleted by accident can still be found with "include
+/// deleted". Same columns as the version history; `valid_to` is when the
+/// file went.
+pub fn tombstones_table_name(container_table: &str) -> String {
+    format!("{}_deleted", container_table)
+}
+
+/// Keeps the rows of the deleted `path` before they're removed from the
+/// index, and purges files deleted longer than `retention_days` ago.
+/// Does nothing but purge with 0.
+pub async fn bury(db: &Connection, table: &lancedb::Table, container_table: &str, path: &str, retention_days: u32) -> Result<()> {
+    if retention_days > 0 {
+        let deleted_at = HashMap::from([(path.to_string(), chrono::Utc::now().timestamp())]);
+        let rows = copy_rows(db, table, &tombstones_table_name(container_table), &deleted_at).await?;
+        if rows > 0 {
+            debug!("Tombstones: kept {} chunks of deleted {}", rows, path);
+        }
+    }
+    purge(db, container_table, retention_days).await
+}
+
+/// Forgets the tombstones of files that are back, restored from the trash
+/// or recreated, since the index has them again.
+pub async fn unbury(db: &Connection, container_table: &str, paths: &[String]) -> Result<()> {
+    let Ok(table) = db.open_table(tombstones_table_name(container_table)).execute().await else {
+        return Ok(());
+    };
+    for filter in super::dedup::in_filters("path", paths) {
+        table.delete(&filter).await?;
+    }
+    Ok(())
+}
+
+/// Drops files deleted more than `retention_days` ago, and every tombstone
+/// once keeping them is turned off.
+pub async fn purge(db: &Connection, container_table: &str, retention_days: u32) -> Result<()> {
+    expire(db, &tombstones_table_name(container_table), retention_days).await
+}
+
+/// Closest deleted files to `query_vector`, one chunk each.
+pub async fn search(
+    db: &Connection,
+    table_name: &str,
+    query_vector: &[f32],
+    limit: usize,
+    filter: Option<String>,
+) -> Result<Vec<(String, String, f32)>> {
+    let name = tombstones_table_name(table_name);
+    if db.open_table(&name).execute().await.is_err() {
+        return Ok(vec![]);
+    }
+    let table = open_for_search(db, &name, query_vector).await?;
+    Ok(best_per_file(nearest_chunks(&table, query_vector, limit * 2, filter).await?, limit))
+}
+
+/// Distinct paths with tombstones, for showing the "include deleted" toggle
+/// only when there's something to include.
+pub async fn deleted_files(db: &Connection, container_table: &str) -> Result<HashSet<String>> {
+    let Ok(table) = db.open_table(tombstones_table_name(container_table)).execute().await else {
+        return Ok(HashSet::new());
+    };
+    let batches = table
+        .query()
+        .select(lancedb::query::Select::Columns(vec!["path".to_string()]))
+        .execute()
+        .await?
+        .try_collect::<Vec<_>>()
+        .await?;
+    let mut paths = HashSet::new();
+    for batch in batches {
+        if let Some(col) = batch.column_by_name("path").and_then(|c| c.as_any().downcast_ref::<StringArray>()) {
+            paths.extend(col.iter().flatten().map(str::to_string));
+        }
+    }
+    debug!("Tombstones: {} deleted files in '{}'", paths.len(), container_table);
+    Ok(paths)
+}
@@ -0,0 +1,308 @@
+//! A container's files clustered by their mean vector and labelled with the
+//! words that set each cluster apart.
+
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+
+use anyhow::Result;
+use arrow_array::StringArray;
+use futures::TryStreamExt;
+use lancedb::query::{ExecutableQuery, QueryBase};
+use lancedb::Connection;
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+
+use super::chunking::STOP_WORDS;
+
+const VERSION: u32 = 1;
+const MAX_TOPICS: usize = 30;
+/// Files the clusters are fitted on; the rest are only assigned.
+const FIT_SAMPLE: usize = 20_000;
+const ITERATIONS: usize = 15;
+const LABEL_FILES: usize = 200;
+const LABEL_CHARS: usize = 2_000;
+const LABEL_TERMS: usize = 4;
+const MIN_WORD_LEN: usize = 3;
+const MAX_WORD_LEN: usize = 32;
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Topic {
+    pub id: usize,
+    pub terms: Vec<String>,
+    pub files: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct TopicMap {
+    pub version: u32,
+    pub built_at: i64,
+    pub table_version: u64,
+    pub topics: Vec<Topic>,
+}
+
+fn cache_path(table_name: &str) -> PathBuf {
+    crate::config::app_data_dir().join("topics").join(format!("{}.json", table_name))
+}
+
+pub fn load(table_name: &str) -> Option<TopicMap> {
+    std::fs::read_to_string(cache_path(table_name))
+        .ok()
+        .and_then(|json| serde_json::from_str::<TopicMap>(&json).ok())
+        .filter(|map| map.version == VERSION)
+}
+
+fn save(table_name: &str, map: &TopicMap) {
+    let path = cache_path(table_name);
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).ok();
+    }
+    match serde_json::to_string(map) {
+        Ok(json) => {
+            if let Err(e) = std::fs::write(&path, json) {
+                warn!("Failed to write topics {:?}: {}", path, e);
+            }
+        }
+        Err(e) => warn!("Failed to serialize topics: {}", e),
+    }
+}
+
+pub fn forget(table_name: &str) {
+    std::fs::remove_file(cache_path(table_name)).ok();
+}
+
+pub async fn is_stale(db: &Connection, table_name: &str, map: &TopicMap) -> bool {
+    let Ok(table) = super::snapshot::open(db, table_name).await else {
+        return true;
+    };
+    !matches!(table.version().await, Ok(v) if v == map.table_version)
+}
+
+fn normalize(v: &mut [f32]) {
+    let norm = v.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm > 0.0 {
+        v.iter_mut().for_each(|x| *x /= norm);
+    }
+}
+
+fn dot(a: &[f32], b: &[f32]) -> f32 {
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+/// Roughly √(n/2), capped.
+fn topic_count(files: usize) -> usize {
+    ((files as f64 / 2.0).sqrt().round() as usize).clamp(2, MAX_TOPICS).min(files)
+}
+
+fn nearest(centroids: &[Vec<f32>], v: &[f32]) -> (usize, f32) {
+    centroids
+        .iter()
+        .enumerate()
+        .map(|(i, c)| (i, dot(c, v)))
+        .fold((0, f32::MIN), |best, cur| if cur.1 > best.1 { cur } else { best })
+}
+
+/// Seeded with the vector furthest from every centroid so far, so the same
+/// index always gives the same topics.
+fn kmeans(vectors: &[Vec<f32>], k: usize) -> Vec<Vec<f32>> {
+    let Some(first) = vectors.first() else {
+        return Vec::new();
+    };
+    let mut centroids = vec![first.clone()];
+    let mut closest: Vec<f32> = vectors.iter().map(|v| dot(first, v)).collect();
+    while centroids.len() < k {
+        let (far, _) = closest
+            .iter()
+            .enumerate()
+            .fold((0, f32::MAX), |best, (i, &s)| if s < best.1 { (i, s) } else { best });
+        let pick = vectors[far].clone();
+        for (c, v) in closest.iter_mut().zip(vectors) {
+            *c = c.max(dot(&pick, v));
+        }
+        centroids.push(pick);
+    }
+
+    let mut assignment = vec![usize::MAX; vectors.len()];
+    for _ in 0..ITERATIONS {
+        let mut changed = false;
+        for (a, v) in assignment.iter_mut().zip(vectors) {
+            let (best, _) = nearest(&centroids, v);
+            changed |= *a != best;
+            *a = best;
+        }
+        if !changed {
+            break;
+        }
+        let mut sums = vec![vec![0.0f32; first.len()]; k];
+        for (&a, v) in assignment.iter().zip(vectors) {
+            sums[a].iter_mut().zip(v).for_each(|(s, x)| *s += x);
+        }
+        for (c, mut sum) in centroids.iter_mut().zip(sums) {
+            if sum.iter().any(|&x| x != 0.0) {
+                normalize(&mut sum);
+                *c = sum;
+            }
+        }
+    }
+    centroids
+}
+
+fn cluster(mut vectors: Vec<Vec<f32>>) -> Vec<(usize, f32)> {
+    vectors.iter_mut().for_each(|v| normalize(v));
+    let k = topic_count(vectors.len());
+    let step = vectors.len().div_ceil(FIT_SAMPLE).max(1);
+    let sample: Vec<Vec<f32>> = vectors.iter().step_by(step).cloned().collect();
+    let centroids = kmeans(&sample, k);
+    vectors.iter().map(|v| nearest(&centroids, v)).collect()
+}
+
+fn words(text: &str) -> HashSet<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|w| (MIN_WORD_LEN..=MAX_WORD_LEN).contains(&w.chars().count()))
+        .filter(|w| !w.chars().all(|c| c.is_ascii_digit()))
+        .map(str::to_lowercase)
+        .filter(|w| !STOP_WORDS.contains(&w.as_str()))
+        .collect()
+}
+
+/// Class-based TF-IDF.
+fn label(files: &[Vec<HashSet<String>>]) -> Vec<Vec<String>> {
+    let counts: Vec<HashMap<&str, usize>> = files
+        .iter()
+        .map(|topic| {
+            let mut counts = HashMap::new();
+            for word in topic.iter().flatten() {
+                *counts.entry(word.as_str()).or_default() += 1;
+            }
+            counts
+        })
+        .collect();
+    let mut total: HashMap<&str, usize> = HashMap::new();
+    for (word, n) in counts.iter().flatten() {
+        *total.entry(word).or_default() += n;
+    }
+    let average = counts.iter().map(|c| c.values().sum::<usize>()).sum::<usize>() as f64 / counts.len().max(1) as f64;
+
+    counts
+        .iter()
+        .zip(files)
+        .map(|(topic, labelled)| {
+            // A word only one file uses says little about the rest.
+            let min_files = if labelled.len() > 1 { 2 } else { 1 };
+            let mut scored: Vec<(&str, f64)> = topic
+                .iter()
+                .filter(|(_, &n)| n >= min_files)
+                .map(|(&word, &n)| (word, n as f64 * (1.0 + average / total[word] as f64).ln()))
+                .collect();
+            scored.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(b.0)));
+            scored.into_iter().take(LABEL_TERMS).map(|(w, _)| w.to_string()).collect()
+        })
+        .collect()
+}
+
+pub async fn build(db: &Connection, table_name: &str) -> Result<TopicMap> {
+    let table = super::snapshot::open(db, table_name).await?;
+    let table_version = table.version().await?;
+    let files = super::file_vectors::all(db, &table, table_name).await?;
+    if files.len() < 2 {
+        let map = TopicMap { version: VERSION, built_at: chrono::Utc::now().timestamp(), table_version, topics: Vec::new() };
+        save(table_name, &map);
+        return Ok(map);
+    }
+    let (paths, vectors): (Vec<String>, Vec<Vec<f32>>) = files.into_iter().unzip();
+    let assigned = tokio::task::spawn_blocking(move || cluster(vectors)).await?;
+
+    let mut groups: Vec<Vec<(String, f32)>> = Vec::new();
+    for (path, (topic, closeness)) in paths.into_iter().zip(assigned) {
+        if groups.len() <= topic {
+            groups.resize_with(topic + 1, Vec::new);
+        }
+        groups[topic].push((path, closeness));
+    }
+    groups.retain(|g| !g.is_empty());
+    groups.sort_by(|a, b| b.len().cmp(&a.len()));
+    for group in &mut groups {
+        group.sort_by(|a, b| b.1.total_cmp(&a.1));
+    }
+
+    let labelled: HashMap<&str, usize> = groups
+        .iter()
+        .enumerate()
+        .flat_map(|(i, g)| g.iter().take(LABEL_FILES).map(move |(p, _)| (p.as_str(), i)))
+        .collect();
+    let mut texts: Vec<Vec<HashSet<String>>> = vec![Vec::new(); groups.len()];
+    let mut seen = HashSet::new();
+    let mut stream = table
+        .query()
+        .only_if(format!("{} < 1", super::db::CHUNK_START_COLUMN))
+        .select(lancedb::query::Select::Columns(vec!["path".to_string(), "content".to_string()]))
+        .execute()
+        .await?;
+    while let Some(batch) = stream.try_next().await? {
+        let paths = batch.column_by_name("path").and_then(|c| c.as_any().downcast_ref::<StringArray>());
+        let contents = batch.column_by_name("content").and_then(|c| c.as_any().downcast_ref::<StringArray>());
+        let (Some(paths), Some(contents)) = (paths, contents) else {
+            continue;
+        };
+        for i in 0..batch.num_rows() {
+            let path = paths.value(i);
+            if let Some(&topic) = labelled.get(path) {
+                if seen.insert(path.to_string()) {
+                    let text: String = super::snippet::body(contents.value(i)).chars().take(LABEL_CHARS).collect();
+                    texts[topic].push(words(&text));
+                }
+            }
+        }
+    }
+    let labels = label(&texts);
+
+    let topics: Vec<Topic> = groups
+        .into_iter()
+        .zip(labels)
+        .enumerate()
+        .map(|(id, (group, terms))| Topic { id, terms, files: group.into_iter().map(|(p, _)| p).collect() })
+        .collect();
+    info!("Topics for '{}': {} over {} files", table_name, topics.len(), topics.iter().map(|t| t.files.len()).sum::<usize>());
+    let map = TopicMap { version: VERSION, built_at: chrono::Utc::now().timestamp(), table_version, topics };
+    save(table_name, &map);
+    Ok(map)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cluster() {
+        let mut vectors = Vec::new();
+        for i in 0..4 {
+            let jitter = i as f32 * 0.01;
+            vectors.push(vec![1.0, jitter, 0.0]);
+            vectors.push(vec![0.0, jitter, 1.0]);
+        }
+        let assigned = cluster(vectors);
+        assert_eq!(topic_count(8), 2);
+        let (a, b) = (assigned[0].0, assigned[1].0);
+        assert_ne!(a, b);
+        for (i, (topic, closeness)) in assigned.iter().enumerate() {
+            assert_eq!(*topic, if i % 2 == 0 { a } else { b });
+            assert!(*closeness > 0.99);
+        }
+        assert_eq!(topic_count(1), 1);
+        assert_eq!(topic_count(1_000_000), MAX_TOPICS);
+    }
+
+    #[test]
+    fn test_label() {
+        let file = |text: &str| words(text);
+        let texts = vec![
+            vec![file("The invoice total and tax"), file("Invoice due, tax paid"), file("Meeting notes")],
+            vec![file("Recipe: bake the bread"), file("Bread recipe with rye"), file("Meeting notes")],
+        ];
+        let labels = label(&texts);
+        assert_eq!(labels[0][..2], ["invoice", "tax"]);
+        assert_eq!(labels[1][..2], ["bread", "recipe"]);
+        assert!(labels.iter().all(|l| !l.contains(&"meeting".to_string())));
+        assert!(!file("The 2024 and the API").contains("2024"));
+        assert!(file("The 2024 and the API").contains("api"));
+    }
+}
@@ -0,0 +1,97 @@
+//! Result snippets put into the reader's language by the chat endpoint, for
+//! collections in languages the user searches across but doesn't read well.
+//! A local model behind an OpenAI-compatible endpoint (ollama) keeps the
+//! text on the machine.
+
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+
+use super::ask::ChatEndpoint;
+
+/// Longer text is cut: a snippet is a few sentences, not a document.
+const MAX_CHARS: usize = 2000;
+const MAX_TOKENS: u32 = 600;
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct Translation {
+    pub text: String,
+    /// Language the snippet was detected in (ISO 639-1), empty when unsure.
+    pub source: String,
+    /// Whether the endpoint was asked; not when the snippet already is in
+    /// the target language.
+    pub translated: bool,
+}
+
+fn system_prompt(target: &str, target_name: &str) -> String {
+    format!(
+        "Translate the user's text into {} ({}). Keep names, code, numbers and file paths \
+         as they are. Reply with the translation only, no notes or quotes.",
+        target_name, target
+    )
+}
+
+/// `text` in the language with ISO 639-1 code `target`, named `target_name`
+/// in English for the prompt.
+pub async fn translate(endpoint: &ChatEndpoint, text: &str, target: &str, target_name: &str) -> Result<Translation> {
+    let source = super::language::detect_language(text);
+    if source == target {
+        return Ok(Translation { text: text.to_string(), source, translated: false });
+    }
+    if crate::config::is_offline() {
+        return Err(anyhow!(crate::config::offline_error("Translating a snippet")));
+    }
+    let text: String = text.chars().take(MAX_CHARS).collect();
+    let translated = super::hyde::chat(
+        &endpoint.endpoint,
+        &endpoint.model,
+        endpoint.api_key.as_deref(),
+        &system_prompt(target, target_name),
+        &text,
+        MAX_TOKENS,
+        TIMEOUT,
+    )
+    .await?;
+    Ok(Translation { text: translated.trim().to_string(), source, translated: true })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{body_string_contains, method};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn endpoint(server: &MockServer) -> ChatEndpoint {
+        ChatEndpoint { endpoint: format!("{}/v1/chat/completions", server.uri()), model: "test".to_string(), api_key: None }
+    }
+
+    #[tokio::test]
+    async fn test_translate() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(body_string_contains("into English (en)"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "choices": [{ "message": { "role": "assistant", "content": " The rent is due on the first. \n" } }]
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let turkish = "Kira her ayın birinde ödenir ve depozito iki aylık kira tutarındadır, sözleşme bir yıl sürer.";
+        let result = translate(&endpoint(&server), turkish, "en", "English").await.unwrap();
+        assert_eq!(result, Translation { text: "The rent is due on the first.".to_string(), source: "tr".to_string(), translated: true });
+    }
+
+    #[tokio::test]
+    async fn test_translate_same_language() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST")).respond_with(ResponseTemplate::new(500)).expect(0).mount(&server).await;
+
+        let english = "This document explains how the search engine works and how the indexes are built from your files.";
+        let result = translate(&endpoint(&server), english, "en", "English").await.unwrap();
+        assert!(!result.translated);
+        assert_eq!(result.text, english);
+    }
+}
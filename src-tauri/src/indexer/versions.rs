@@ -0,0 +1,201 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use arrow_array::{Int64Array, RecordBatch, RecordBatchIterator, StringArray};
+use arrow_schema::{DataType, Field, Schema};
+use futures::TryStreamExt;
+use lancedb::connection::Connection;
+use lancedb::query::{ExecutableQuery, QueryBase};
+use lancedb::Table;
+use log::{debug, info, warn};
+
+use super::search::{best_per_file, nearest_chunks, open_for_search};
+
+/// Unix seconds a superseded chunk stopped being in its file: the mtime of
+/// the version that replaced it, or when the file was deleted.
+pub const VALID_TO_COLUMN: &str = "valid_to";
+const DAY_SECS: i64 = 24 * 60 * 60;
+
+/// Rows a reindex replaces, or that go with a deleted file, are copied here
+/// with their vectors so `as_of` searches still find text a file no longer
+/// has. A row's `mtime` is when its content appeared, `valid_to` when it
+/// went; rows in the main table are valid from their `mtime` on.
+pub fn versions_table_name(container_table: &str) -> String {
+    format!("{}_versions", container_table)
+}
+
+fn versions_schema(table_schema: &Schema) -> Schema {
+    let mut fields: Vec<Field> = table_schema.fields().iter().map(|f| f.as_ref().clone()).collect();
+    fields.push(Field::new(VALID_TO_COLUMN, DataType::Int64, false));
+    Schema::new(fields)
+}
+
+fn same_columns(a: &Schema, b: &Schema) -> bool {
+    a.fields().len() == b.fields().len()
+        && a.fields().iter().zip(b.fields()).all(|(x, y)| x.name() == y.name() && x.data_type() == y.data_type())
+}
+
+/// Rows that were a file's content at `as_of`. Superseded rows also need
+/// to have still been current then.
+pub fn as_of_filter(as_of: i64, superseded: bool) -> String {
+    if superseded {
+        format!("mtime <= {} AND {} > {}", as_of, VALID_TO_COLUMN, as_of)
+    } else {
+        format!("mtime <= {}", as_of)
+    }
+}
+
+async fn open_versions(db: &Connection, container_table: &str) -> Option<Table> {
+    db.open_table(versions_table_name(container_table)).execute().await.ok()
+}
+
+/// Old rows can't be appended once the index has gained a column, so copies
+/// from before then are dropped rather than kept unreadable.
+async fn get_or_create_copy(db: &Connection, name: &str, table_schema: &Schema) -> Result<Table> {
+    let wanted = versions_schema(table_schema);
+    if let Ok(table) = db.open_table(name).execute().await {
+        if same_columns(table.schema().await?.as_ref(), &wanted) {
+            return Ok(table);
+        }
+        warn!("'{}' predates the index schema, starting over", name);
+        db.drop_table(name, &[]).await?;
+    }
+    let table = db
+        .create_table(name, RecordBatchIterator::new(vec![], Arc::new(wanted)))
+        .execute()
+        .await?;
+    info!("Table '{}' created", name);
+    Ok(table)
+}
+
+/// Copies the rows of every file in `valid_to` (path to the unix seconds its
+/// content stopped being current) into the table `dest`, which gets the
+/// index's columns plus `valid_to`. Returns how many rows were copied.
+pub(super) async fn copy_rows(db: &Connection, table: &Table, dest: &str, valid_to: &HashMap<String, i64>) -> Result<usize> {
+    let paths: Vec<String> = valid_to.keys().cloned().collect();
+    let mut batches = Vec::new();
+    for filter in super::dedup::in_filters("path", &paths) {
+        batches.extend(table.query().only_if(filter).execute().await?.try_collect::<Vec<_>>().await?);
+    }
+    let rows: usize = batches.iter().map(|b| b.num_rows()).sum();
+    if rows == 0 {
+        return Ok(0);
+    }
+    let copies = get_or_create_copy(db, dest, table.schema().await?.as_ref()).await?;
+    let schema = copies.schema().await?;
+    let now = chrono::Utc::now().timestamp();
+    let mut out = Vec::with_capacity(batches.len());
+    for batch in batches {
+        let batch_paths = batch
+            .column_by_name("path")
+            .and_then(|c| c.as_any().downcast_ref::<StringArray>())
+            .ok_or_else(|| anyhow!("Missing or invalid 'path' column"))?;
+        let ends: Vec<i64> = batch_paths.iter().map(|p| p.and_then(|p| valid_to.get(p).copied()).unwrap_or(now)).collect();
+        let mut columns = batch.columns().to_vec();
+        columns.push(Arc::new(Int64Array::from(ends)));
+        out.push(RecordBatch::try_new(schema.clone(), columns)?);
+    }
+    copies.add(RecordBatchIterator::new(out.into_iter().map(Ok), schema)).execute().await?;
+    Ok(rows)
+}
+
+/// Drops rows of the copy table `name` whose content went out of date more
+/// than `retention_days` ago, and the whole table at 0.
+pub(super) async fn expire(db: &Connection, name: &str, retention_days: u32) -> Result<()> {
+    let Ok(copies) = db.open_table(name).execute().await else {
+        return Ok(());
+    };
+    if retention_days == 0 {
+        db.drop_table(name, &[]).await?;
+        info!("Table '{}' dropped", name);
+        return Ok(());
+    }
+    let cutoff = chrono::Utc::now().timestamp() - i64::from(retention_days) * DAY_SECS;
+    copies.delete(&format!("{} < {}", VALID_TO_COLUMN, cutoff)).await?;
+    Ok(())
+}
+
+/// `copy_rows` into the version history, before the rows are removed.
+/// Does nothing while history is off.
+pub async fn archive(
+    db: &Connection,
+    table: &Table,
+    container_table: &str,
+    valid_to: &HashMap<String, i64>,
+    retention_days: u32,
+) -> Result<()> {
+    if retention_days == 0 || valid_to.is_empty() {
+        return Ok(());
+    }
+    let rows = copy_rows(db, table, &versions_table_name(container_table), valid_to).await?;
+    if rows > 0 {
+        debug!("Versions: kept {} superseded chunks of {} files", rows, valid_to.len());
+    }
+    prune(db, container_table, retention_days).await
+}
+
+/// Drops versions that went out of date more than `retention_days` ago, and
+/// the whole history once it's turned off.
+pub async fn prune(db: &Connection, container_table: &str, retention_days: u32) -> Result<()> {
+    expire(db, &versions_table_name(container_table), retention_days).await
+}
+
+/// Vector search over the index as it was at `as_of` (unix seconds): rows
+/// of files unchanged since then plus the versions current at the time.
+/// Old versions have no FTS index, so there's no keyword half to merge.
+pub async fn search_as_of(
+    db: &Connection,
+    table_name: &str,
+    query_vector: &[f32],
+    limit: usize,
+    filter: Option<String>,
+    as_of: i64,
+) -> Result<Vec<(String, String, f32)>> {
+    let table = open_for_search(db, table_name, query_vector).await?;
+    let with = |clause: String| match &filter {
+        Some(f) => format!("({}) AND {}", f, clause),
+        None => clause,
+    };
+    let mut matches = nearest_chunks(&table, query_vector, limit * 2, Some(with(as_of_filter(as_of, false)))).await?;
+    if let Some(versions) = open_versions(db, table_name).await {
+        // History from before a column was added can't take filters on it
+        // until the next archive recreates the table.
+        match nearest_chunks(&versions, query_vector, limit * 2, Some(with(as_of_filter(as_of, true)))).await {
+            Ok(old) => matches.extend(old),
+            Err(e) => warn!("Searching old versions failed: {}", e),
+        }
+    }
+    Ok(best_per_file(matches, limit))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_versions_schema() {
+        let main = Schema::new(vec![
+            Field::new("path", DataType::Utf8, false),
+            Field::new("mtime", DataType::Int64, false),
+        ]);
+        let versions = versions_schema(&main);
+        assert_eq!(versions.fields().len(), 3);
+        assert_eq!(versions.field(2).name(), VALID_TO_COLUMN);
+        assert!(same_columns(&versions, &versions_schema(&main)));
+        assert!(!same_columns(&versions, &main));
+
+        let grown = Schema::new(vec![
+            Field::new("path", DataType::Utf8, false),
+            Field::new("mtime", DataType::Int64, false),
+            Field::new("lang", DataType::Utf8, true),
+        ]);
+        assert!(!same_columns(&versions, &versions_schema(&grown)));
+    }
+
+    #[test]
+    fn test_as_of_filter() {
+        assert_eq!(as_of_filter(1_700_000_000, false), "mtime <= 1700000000");
+        assert_eq!(as_of_filter(1_700_000_000, true), "mtime <= 1700000000 AND valid_to > 1700000000");
+    }
+}
@@ -0,0 +1,134 @@
+//! WASI extractor plugins: a `.wasm` command per file extension that gets
+//! the file's bytes on stdin and writes its text to stdout. Unlike an
+//! external command it runs inside rememex with no files, network or
+//! environment, and within fixed CPU and memory limits.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{LazyLock, Mutex};
+use std::time::SystemTime;
+
+use anyhow::{anyhow, Result};
+use log::{debug, warn};
+use wasmtime::{Config, Engine, Linker, Module, Store, StoreLimits, StoreLimitsBuilder, Trap};
+use wasmtime_wasi::pipe::{MemoryInputPipe, MemoryOutputPipe};
+use wasmtime_wasi::preview1::{self, WasiP1Ctx};
+use wasmtime_wasi::{I32Exit, WasiCtxBuilder};
+
+use super::quarantine;
+
+/// Memory a plugin may grow to.
+const MAX_MEMORY: usize = 256 * 1024 * 1024;
+/// Instructions a plugin may run per file, roughly. Plenty for parsing a
+/// document under `MAX_FILE_SIZE`; a plugin that gets there is stuck.
+const FUEL: u64 = 20_000_000_000;
+/// Text kept from a plugin's stdout.
+const MAX_OUTPUT: usize = 32 * 1024 * 1024;
+
+struct PluginState {
+    wasi: WasiP1Ctx,
+    limits: StoreLimits,
+}
+
+static ENGINE: LazyLock<Result<Engine, String>> = LazyLock::new(|| {
+    let mut config = Config::new();
+    config.consume_fuel(true);
+    Engine::new(&config).map_err(|e| e.to_string())
+});
+
+/// Compiled plugins with the mtime they were compiled at, so an updated
+/// `.wasm` is picked up without a restart.
+static MODULES: LazyLock<Mutex<HashMap<PathBuf, (SystemTime, Module)>>> = LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Where a configured plugin lives: as given when absolute, otherwise in
+/// `plugins/` in the app data folder.
+pub fn plugin_path(configured: &str) -> PathBuf {
+    let path = Path::new(configured);
+    if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        crate::config::app_data_dir().join("plugins").join(path)
+    }
+}
+
+fn engine() -> Result<&'static Engine> {
+    ENGINE.as_ref().map_err(|e| anyhow!("WASM runtime unavailable: {}", e))
+}
+
+fn module(path: &Path) -> Result<Module> {
+    let mtime = std::fs::metadata(path)
+        .and_then(|m| m.modified())
+        .map_err(|e| anyhow!("Can't read plugin {}: {}", path.display(), e))?;
+    let mut modules = MODULES.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some((compiled_at, module)) = modules.get(path) {
+        if *compiled_at == mtime {
+            return Ok(module.clone());
+        }
+    }
+    debug!("Compiling extractor plugin {}", path.display());
+    let module = Module::from_file(engine()?, path)?;
+    modules.insert(path.to_path_buf(), (mtime, module.clone()));
+    Ok(module)
+}
+
+/// Runs `module` as a WASI command with `input` on stdin and `file_name` as
+/// its only argument, and returns its stdout.
+fn run(module: &Module, file_name: &str, input: Vec<u8>) -> Result<Vec<u8>> {
+    let engine = engine()?;
+    let stdout = MemoryOutputPipe::new(MAX_OUTPUT);
+    let wasi = WasiCtxBuilder::new()
+        .stdin(MemoryInputPipe::new(input))
+        .stdout(stdout.clone())
+        .args(&["extractor", file_name])
+        .build_p1();
+    let limits = StoreLimitsBuilder::new().memory_size(MAX_MEMORY).instances(1).build();
+    let mut store = Store::new(engine, PluginState { wasi, limits });
+    store.limiter(|state| &mut state.limits);
+    store.set_fuel(FUEL)?;
+
+    let mut linker: Linker<PluginState> = Linker::new(engine);
+    preview1::add_to_linker_sync(&mut linker, |state| &mut state.wasi)?;
+    let instance = linker.instantiate(&mut store, module)?;
+    let start = instance.get_typed_func::<(), ()>(&mut store, "_start")?;
+    if let Err(e) = start.call(&mut store, ()) {
+        match e.downcast_ref::<I32Exit>() {
+            Some(I32Exit(0)) => {}
+            Some(I32Exit(code)) => return Err(anyhow!("exited with {}", code)),
+            None => return Err(e),
+        }
+    }
+    Ok(stdout.contents().to_vec())
+}
+
+/// Text of `path` from the plugin configured as `plugin`. A plugin that
+/// runs out of fuel puts the file on the skip list; any other failure just
+/// leaves it out, with the reason in the log.
+pub fn extract(plugin: &str, path: &Path) -> Option<String> {
+    let path_str = path.to_string_lossy().to_string();
+    let mtime = super::file_io::get_file_mtime(path);
+    if quarantine::is_skipped(&path_str, mtime) {
+        debug!("{} is on the skip list", path_str);
+        return None;
+    }
+    let plugin_file = plugin_path(plugin);
+    let module = match module(&plugin_file) {
+        Ok(module) => module,
+        Err(e) => {
+            warn!("Extractor plugin {} failed to load: {}", plugin_file.display(), e);
+            return None;
+        }
+    };
+    let input = std::fs::read(path).ok()?;
+    let file_name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    match run(&module, &file_name, input) {
+        Ok(out) => Some(String::from_utf8_lossy(&out).into_owned()),
+        Err(e) if e.downcast_ref::<Trap>() == Some(&Trap::OutOfFuel) => {
+            quarantine::skip(&path_str, mtime, &format!("plugin {} ran out of fuel", plugin));
+            None
+        }
+        Err(e) => {
+            warn!("Extractor plugin {} failed on {}: {}", plugin, path_str, e);
+            None
+        }
+    }
+}
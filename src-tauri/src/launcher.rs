@@ -0,0 +1,165 @@
+//! Search results in the JSON launchers read (`rememex-mcp search --format`),
+//! so Alfred, PowerToys Run and friends can query the index directly.
+
+use std::path::Path;
+use std::str::FromStr;
+
+use serde_json::{json, Value};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LauncherFormat {
+    /// Alfred Script Filter: `{"items": [...]}`.
+    Alfred,
+    /// The fields of a PowerToys Run / Wox result (`Title`, `SubTitle`,
+    /// `IcoPath`, ...) under `result`, which Flow Launcher's JSON-RPC plugins
+    /// take as is.
+    PowerToys,
+    /// A plain array, for ulauncher extensions and scripts.
+    Json,
+}
+
+impl FromStr for LauncherFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "alfred" => Ok(Self::Alfred),
+            "powertoys" | "wox" | "flow" => Ok(Self::PowerToys),
+            "json" | "ulauncher" => Ok(Self::Json),
+            other => Err(format!("unknown format '{}', expected alfred, powertoys or json", other)),
+        }
+    }
+}
+
+pub struct LauncherItem {
+    pub path: String,
+    pub snippet: String,
+    pub context: String,
+    pub score: f32,
+    pub line: Option<usize>,
+}
+
+fn file_name(path: &str) -> &str {
+    Path::new(path).file_name().and_then(|n| n.to_str()).unwrap_or(path)
+}
+
+fn parent(path: &str) -> &str {
+    Path::new(path).parent().and_then(|p| p.to_str()).unwrap_or("")
+}
+
+/// First line of the snippet with text in it, to fit a one-line subtitle.
+fn preview(snippet: &str) -> &str {
+    snippet.lines().map(str::trim).find(|l| !l.is_empty()).unwrap_or("")
+}
+
+fn subtitle(item: &LauncherItem) -> String {
+    match (item.context.is_empty(), preview(&item.snippet)) {
+        (true, "") => parent(&item.path).to_string(),
+        (true, text) => text.to_string(),
+        (false, _) => format!("{} · {}", item.context, parent(&item.path)),
+    }
+}
+
+fn alfred_item(item: &LauncherItem) -> Value {
+    json!({
+        "uid": item.path,
+        "type": "file",
+        "title": file_name(&item.path),
+        "subtitle": subtitle(item),
+        "arg": item.path,
+        "autocomplete": file_name(&item.path),
+        "icon": { "type": "fileicon", "path": item.path },
+        "quicklookurl": item.path,
+        "text": { "copy": item.path, "largetype": item.snippet },
+        "mods": {
+            "alt": { "arg": item.snippet, "subtitle": "Copy snippet" },
+            "cmd": { "arg": parent(&item.path), "subtitle": format!("Reveal {}", parent(&item.path)) },
+        },
+        "variables": { "line": item.line.map_or(String::new(), |l| l.to_string()) },
+    })
+}
+
+fn powertoys_item(item: &LauncherItem) -> Value {
+    json!({
+        "Title": file_name(&item.path),
+        "SubTitle": subtitle(item),
+        "IcoPath": item.path,
+        "Score": item.score.round() as i64,
+        "ContextData": item.path,
+        "ToolTipData": { "Title": item.path, "Text": item.snippet },
+    })
+}
+
+fn json_item(item: &LauncherItem) -> Value {
+    json!({
+        "path": item.path,
+        "name": file_name(&item.path),
+        "context": item.context,
+        "snippet": item.snippet,
+        "line": item.line,
+        "score": item.score,
+    })
+}
+
+pub fn render(format: LauncherFormat, items: &[LauncherItem]) -> Value {
+    match format {
+        LauncherFormat::Alfred if items.is_empty() => message(format, "No results", ""),
+        LauncherFormat::Alfred => json!({ "items": items.iter().map(alfred_item).collect::<Vec<_>>() }),
+        LauncherFormat::PowerToys => json!({ "result": items.iter().map(powertoys_item).collect::<Vec<_>>() }),
+        LauncherFormat::Json => Value::Array(items.iter().map(json_item).collect()),
+    }
+}
+
+/// A single row that can't be actioned, for errors and empty results, since
+/// launchers show stdout and drop stderr.
+pub fn message(format: LauncherFormat, title: &str, detail: &str) -> Value {
+    match format {
+        LauncherFormat::Alfred => json!({ "items": [{ "title": title, "subtitle": detail, "valid": false }] }),
+        LauncherFormat::PowerToys => json!({ "result": [{ "Title": title, "SubTitle": detail, "Score": 0 }] }),
+        LauncherFormat::Json => json!({ "error": title, "detail": detail }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(path: &str, context: &str, snippet: &str) -> LauncherItem {
+        LauncherItem { path: path.to_string(), snippet: snippet.to_string(), context: context.to_string(), score: 87.6, line: Some(12) }
+    }
+
+    #[test]
+    fn test_format_from_str() {
+        assert_eq!("Alfred".parse(), Ok(LauncherFormat::Alfred));
+        assert_eq!("flow".parse(), Ok(LauncherFormat::PowerToys));
+        assert_eq!("ulauncher".parse(), Ok(LauncherFormat::Json));
+        assert!("raycast".parse::<LauncherFormat>().is_err());
+    }
+
+    #[test]
+    fn test_render() {
+        let items = [
+            item("/home/u/notes/deploy.md", "Release > Staging", "push the tag\nthen wait"),
+            item("/home/u/notes/todo.txt", "", "\n  buy milk\n"),
+        ];
+
+        let alfred = render(LauncherFormat::Alfred, &items);
+        assert_eq!(alfred["items"][0]["title"], "deploy.md");
+        assert_eq!(alfred["items"][0]["arg"], "/home/u/notes/deploy.md");
+        assert_eq!(alfred["items"][0]["subtitle"], "Release > Staging · /home/u/notes");
+        assert_eq!(alfred["items"][1]["subtitle"], "buy milk");
+        assert_eq!(alfred["items"][0]["variables"]["line"], "12");
+
+        let powertoys = render(LauncherFormat::PowerToys, &items);
+        assert_eq!(powertoys["result"][0]["Title"], "deploy.md");
+        assert_eq!(powertoys["result"][0]["Score"], 88);
+        assert_eq!(powertoys["result"][1]["ContextData"], "/home/u/notes/todo.txt");
+
+        let plain = render(LauncherFormat::Json, &items);
+        assert_eq!(plain[1]["name"], "todo.txt");
+        assert_eq!(plain[0]["line"], 12);
+
+        assert_eq!(render(LauncherFormat::Alfred, &[])["items"][0]["valid"], false);
+        assert_eq!(render(LauncherFormat::Json, &[]), json!([]));
+    }
+}
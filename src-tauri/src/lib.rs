@@ -1,8 +1,38 @@
+mod appearance;
+mod calculator;
 mod commands;
+mod container_templates;
+mod context_block;
 pub mod config;
+mod deep_link;
+mod desktop_search;
+mod diagnostics;
+mod digest;
+mod endpoint_health;
+pub mod editor;
+mod hotkey;
+mod idle;
+#[cfg(target_os = "linux")]
+mod linux;
+mod locales;
+mod model_download;
 pub mod indexer;
+pub mod launcher;
+mod notifications;
+pub mod os_auth;
+mod placement;
+mod post_index;
+mod power;
+mod privacy_report;
+pub mod redact;
+pub mod remote;
+pub mod shared_index;
 pub mod state;
+mod sync;
+pub mod tokens;
+mod tray;
 mod watcher;
+mod webhooks;
 
 use std::sync::Arc;
 
@@ -10,38 +40,65 @@ use log::{info, error, debug, warn};
 
 
 use tauri::{Emitter, Manager};
-use tauri::menu::{Menu, MenuItem, MenuEvent};
-use tauri::tray::{TrayIcon, TrayIconBuilder, TrayIconEvent};
+use tauri::menu::MenuEvent;
+use tauri::tray::{MouseButton, MouseButtonState, TrayIcon, TrayIconBuilder, TrayIconEvent};
 use tauri_plugin_global_shortcut::ShortcutState;
 use tokio::sync::Mutex;
 
 use config::{ConfigState, EmbeddingProviderConfig, get_embedding_model, parse_hotkey};
-use state::{DbState, ModelState, ProviderState, RerankerState};
+use state::{CompletionState, DbState, ProviderState, RerankerState, ResultCache, SpellState};
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
-    let config_dir = std::path::PathBuf::from(
-        std::env::var("APPDATA").expect("APPDATA not set")
-    ).join("com.rememex.app");
-    std::fs::create_dir_all(&config_dir).ok();
-    let config_path = config_dir.join("config.json");
+    diagnostics::install_panic_hook();
+
+    config::apply_read_only_flag();
+    let app_data = config::app_data_dir();
+    std::fs::create_dir_all(&app_data).ok();
+    let config_path = app_data.join("config.json");
     let config = config::load_config(&config_path);
+    config::set_offline(config.offline);
+    let log_target = match config::portable_log_dir() {
+        Some(path) => tauri_plugin_log::TargetKind::Folder { path, file_name: Some("rememex".into()) },
+        None => tauri_plugin_log::TargetKind::LogDir { file_name: Some("rememex".into()) },
+    };
 
     let shortcut = parse_hotkey(&config.hotkey);
     let always_on_top = config.always_on_top;
     let launch_at_startup = config.launch_at_startup;
     let use_reranker = config.use_reranker;
+    let theme = config.appearance.theme;
+    let window_layout = config.window.clone();
+    #[cfg(target_os = "linux")]
+    let (linux_config, linux_hotkey) = (config.linux.clone(), config.hotkey.clone());
 
     tauri::Builder::default()
+        .plugin(tauri_plugin_single_instance::init(|app, argv, _cwd| {
+            if let Some(dir) = config::index_flag_value(&argv) {
+                index_from_command_line(app, dir);
+                return;
+            }
+            if argv.iter().any(|arg| arg == config::TOGGLE_FLAG) {
+                toggle_main_window(app);
+                return;
+            }
+            if argv.iter().any(|arg| deep_link::is_link(arg)) {
+                // The deep-link plugin gets these too and decides whether
+                // the window should show.
+                return;
+            }
+            info!("Second instance launched, showing existing window");
+            show_main_window(app);
+        }))
         .plugin(
             tauri_plugin_log::Builder::new()
                 .targets([
-                    tauri_plugin_log::Target::new(tauri_plugin_log::TargetKind::LogDir { file_name: Some("rememex".into()) }),
+                    tauri_plugin_log::Target::new(log_target),
                     tauri_plugin_log::Target::new(tauri_plugin_log::TargetKind::Stdout),
                     tauri_plugin_log::Target::new(tauri_plugin_log::TargetKind::Webview),
                 ])
                 .max_file_size(5_000_000)
-                .rotation_strategy(tauri_plugin_log::RotationStrategy::KeepAll)
+                .rotation_strategy(tauri_plugin_log::RotationStrategy::KeepSome(5))
                 .level(log::LevelFilter::Warn)
                 .level_for("rememex_lib", log::LevelFilter::Debug)
                 .level_for("rememex", log::LevelFilter::Debug)
@@ -51,6 +108,8 @@ pub fn run() {
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_opener::init())
+        .plugin(tauri_plugin_notification::init())
+        .plugin(tauri_plugin_deep_link::init())
 
         .plugin(
             tauri_plugin_global_shortcut::Builder::new()
@@ -58,50 +117,45 @@ pub fn run() {
                 .unwrap()
                 .with_handler(|app, _shortcut, event| {
                     if event.state() == ShortcutState::Pressed {
-                        if let Some(window) = app.get_webview_window("main") {
-                            if window.is_minimized().unwrap_or(false) {
-                                let _ = window.unminimize();
-                                let _ = window.show();
-                                let _ = window.set_focus();
-                            } else if window.is_visible().unwrap_or(false) {
-                                let _ = window.hide();
-                            } else {
-                                let _ = window.show();
-                                let _ = window.set_focus();
-                            }
-                        }
+                        toggle_main_window(app);
                     }
                 })
                 .build(),
         )
+        .on_window_event(|window, event| {
+            match event {
+                tauri::WindowEvent::Moved(position) => {
+                    placement::remember_position(window.app_handle(), window, *position);
+                }
+                tauri::WindowEvent::Resized(size) => {
+                    placement::remember_size(window.app_handle(), window, *size);
+                }
+                _ => {}
+            }
+        })
         .setup(move |app| {
-            let app_data = app
-                .path()
-                .app_data_dir()
-                .expect("Failed to get app data dir");
-
-            std::fs::create_dir_all(&app_data).ok();
+            // A menu bar app: the tray item and the hotkey, no Dock icon.
+            #[cfg(target_os = "macos")]
+            app.set_activation_policy(tauri::ActivationPolicy::Accessory);
 
+            if config::portable_data_dir().is_some() {
+                info!("Portable mode, data in {:?}", app_data);
+            }
             let db_path = app_data.join("lancedb");
-            let db_path_str = db_path.to_string_lossy().to_string();
-
-            debug!("Connecting to LanceDB at {:?}", db_path);
-            let db = tauri::async_runtime::block_on(async {
-                lancedb::connect(&db_path_str)
-                    .execute()
-                    .await
-                    .expect("Failed to connect to LanceDB")
-            });
-            info!("LanceDB connected");
-
-            #[cfg(target_os = "windows")]
-            {
-                use window_vibrancy::apply_mica;
-                if let Some(window) = app.get_webview_window("main") {
-                    let _ = apply_mica(&window, Some(true));
-                    let _ = window.set_always_on_top(always_on_top);
+            watcher::set_status_file(app_data.join(state::WATCHER_STATUS_FILE));
+
+            let mut vibrancy_active = false;
+            if let Some(window) = app.get_webview_window("main") {
+                // A layer surface can't have a title bar or a taskbar entry.
+                #[cfg(target_os = "linux")]
+                if window_layout.mode == config::WindowMode::Popup {
+                    linux::apply_layer_shell(&window, &linux_config);
                 }
+                vibrancy_active = appearance::apply_window_effects(&window, theme);
+                appearance::apply_window_layout(&window, &window_layout);
+                appearance::apply_window_mode(&window, window_layout.mode, always_on_top);
             }
+            app.manage(appearance::VibrancyState(std::sync::atomic::AtomicBool::new(vibrancy_active)));
 
             {
                 use tauri_plugin_autostart::ManagerExt;
@@ -113,50 +167,73 @@ pub fn run() {
                 }
             }
 
-            let show_i = MenuItem::with_id(app, "show", "Show Rememex", true, None::<&str>)?;
-            let quit_i = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?;
-            let menu = Menu::with_items(app, &[&show_i, &quit_i])?;
+            let (container_names, active_container) = tray::container_names(&config);
+            let menu = tray::build_menu(app, &container_names, &active_container)?;
 
-            let _tray = TrayIconBuilder::with_id("tray")
+            let _tray = TrayIconBuilder::with_id(tray::TRAY_ID)
                 .menu(&menu)
                 .icon(app.default_window_icon().unwrap().clone())
+                .tooltip("Rememex")
                 .show_menu_on_left_click(false)
                 .on_menu_event(move |app: &tauri::AppHandle, event: MenuEvent| {
-                    match event.id().as_ref() {
-                        "quit" => app.exit(0),
-                        "show" => {
-                            if let Some(window) = app.get_webview_window("main") {
-                                let _ = window.show();
-                                let _ = window.set_focus();
-                            }
-                        }
-                        _ => {}
-                    }
+                    tray::handle_menu_event(app, event, show_main_window);
                 })
                 .on_tray_icon_event(|tray: &TrayIcon, event: TrayIconEvent| {
-                    if let TrayIconEvent::Click { .. } = event {
+                    // Click comes for press and release of either button; the
+                    // right one opens the menu.
+                    if let TrayIconEvent::Click {
+                        button: MouseButton::Left,
+                        button_state: MouseButtonState::Up,
+                        rect,
+                        ..
+                    } = event
+                    {
                         let app = tray.app_handle();
                         if let Some(window) = app.get_webview_window("main") {
                             if window.is_visible().unwrap_or(false) {
                                 let _ = window.hide();
                             } else {
-                                let _ = window.show();
-                                let _ = window.set_focus();
+                                #[cfg(target_os = "macos")]
+                                {
+                                    placement::place_under_tray(&window, rect);
+                                    let _ = window.show();
+                                    let _ = window.set_focus();
+                                }
+                                #[cfg(not(target_os = "macos"))]
+                                {
+                                    let _ = rect;
+                                    show_main_window(app);
+                                }
                             }
                         }
                     }
                 })
                 .build(app)?;
+            tray::track_progress(app.handle());
 
             let provider_state = Arc::new(Mutex::new(ProviderState { provider: None, init_error: None }));
             app.manage(provider_state.clone());
 
-            let reranker_state = Arc::new(Mutex::new(RerankerState { reranker: None, init_error: None }));
+            let reranker_state = Arc::new(Mutex::new(RerankerState {
+                reranker: None,
+                init_error: None,
+                last_used: std::time::Instant::now(),
+                unloaded: false,
+            }));
             app.manage(reranker_state.clone());
-            app.manage(Arc::new(Mutex::new(DbState { db, path: db_path })));
+            app.manage(Arc::new(Mutex::new(DbState {
+                db: None,
+                path: db_path,
+                uri: String::new(),
+                init_error: None,
+            })));
+
+            app.manage(Arc::new(Mutex::new(SpellState::default())));
+            app.manage(Arc::new(Mutex::new(CompletionState::default())));
+            app.manage(Arc::new(Mutex::new(ResultCache::default())));
 
             let watcher_state = watcher::new_state();
-            app.manage(watcher_state.clone());
+            app.manage(watcher_state);
 
             let models_path = app_data.join("models");
             std::fs::create_dir_all(&models_path).ok();
@@ -166,9 +243,6 @@ pub fn run() {
             let app_handle = app.handle().clone();
 
             let reranker_models_path = models_path.clone();
-            let watcher_provider_state = provider_state.clone();
-            let watcher_state_for_model = watcher_state.clone();
-            let watcher_app = app.handle().clone();
 
             let embedding_provider_config = config.embedding_provider.clone();
             let is_first_run = config.first_run;
@@ -178,15 +252,35 @@ pub fn run() {
                 path: config_path,
             });
 
-            let watcher_config: ConfigState = {
-                let cs: tauri::State<ConfigState> = app.state();
-                ConfigState { config: cs.config.clone(), path: cs.path.clone() }
-            };
-            let watcher_db = {
-                let guard: tauri::State<Arc<Mutex<state::DbState>>> = app.state();
-                let g = guard.blocking_lock();
-                g.db.clone()
-            };
+            let db_handle = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                connect_active_db(&db_handle).await;
+                restart_watcher_when_ready(&db_handle).await;
+            });
+            shared_index::spawn_heartbeat();
+            if let Some(dir) = config::index_flag_value(&std::env::args().collect::<Vec<_>>()) {
+                index_from_command_line(app.handle(), dir);
+            }
+            listen_for_deep_links(app);
+            if std::env::args().any(|arg| arg == config::TOGGLE_FLAG) {
+                show_main_window(app.handle());
+            }
+            #[cfg(target_os = "linux")]
+            {
+                linux::register_portal_hotkey(app.handle(), &linux_hotkey, &linux_config);
+                linux::check_tray(app.handle());
+            }
+
+            idle::spawn_reaper(
+                app.state::<ConfigState>().config.clone(),
+                provider_state.clone(),
+                reranker_state.clone(),
+                app.handle().clone(),
+            );
+            power::spawn_monitor(app.state::<ConfigState>().config.clone());
+            endpoint_health::spawn_monitor(provider_state.clone(), app.handle().clone());
+            digest::spawn_scheduler(app.handle().clone());
+            sync::spawn_scheduler(app.handle().clone());
 
             if is_first_run {
                 info!("First run detected — deferring provider init until user configures settings");
@@ -194,6 +288,7 @@ pub fn run() {
                 match embedding_provider_config {
                     EmbeddingProviderConfig::Local { ref model } => {
                         let model_enum = get_embedding_model(model);
+                        let model_name = model.clone();
 
                         tauri::async_runtime::spawn(async move {
                             info!("Loading local model to: {:?}", models_path);
@@ -205,15 +300,17 @@ pub fn run() {
 
                             while attempts < max_attempts {
                                 attempts += 1;
-                                match indexer::load_model(model_enum.clone(), models_path.clone()) {
+                                let download = model_download::watch(&app_handle, &model_name, models_path.clone());
+                                let load_result = indexer::load_model(model_enum.clone(), models_path.clone());
+                                drop(download);
+                                match load_result {
                                     Ok(model) => {
                                         info!("Local embedding model loaded successfully");
-                                        let model_state = Arc::new(Mutex::new(ModelState {
-                                            model: Some(model),
-                                            init_error: None,
-                                            cached_dim: None,
-                                        }));
-                                        let local_provider = indexer::embedding_provider::LocalProvider { model_state };
+                                        let local_provider = indexer::embedding_provider::LocalProvider::new(
+                                            model,
+                                            model_enum.clone(),
+                                            models_path.clone(),
+                                        );
                                         let mut guard = provider_state.lock().await;
                                         guard.provider = Some(Box::new(local_provider));
                                         guard.init_error = None;
@@ -221,13 +318,7 @@ pub fn run() {
                                         let _ = app_handle.emit("model-loaded", ());
                                         loaded = true;
 
-                                        watcher::restart(
-                                            &watcher_state_for_model,
-                                            &watcher_config,
-                                            watcher_db.clone(),
-                                            watcher_provider_state.clone(),
-                                            watcher_app.clone(),
-                                        ).await;
+                                        restart_watcher_when_ready(&app_handle).await;
 
                                         break;
                                     }
@@ -258,22 +349,20 @@ pub fn run() {
                         let _ = app_handle.emit("model-loaded", ());
 
                         tauri::async_runtime::spawn(async move {
-                            watcher::restart(
-                                &watcher_state_for_model,
-                                &watcher_config,
-                                watcher_db.clone(),
-                                watcher_provider_state.clone(),
-                                watcher_app.clone(),
-                            ).await;
+                            restart_watcher_when_ready(&app_handle).await;
                         });
                     }
                 }
             }
 
             if use_reranker {
+                let reranker_app = app.handle().clone();
                 tauri::async_runtime::spawn(async move {
                     info!("Loading reranker model...");
-                    match indexer::load_reranker(reranker_models_path) {
+                    let download = model_download::watch(&reranker_app, model_download::RERANKER, reranker_models_path.clone());
+                    let load_result = indexer::load_reranker(reranker_models_path);
+                    drop(download);
+                    match load_result {
                         Ok(reranker) => {
                             info!("Reranker loaded successfully");
                             let mut state = reranker_state.lock().await;
@@ -305,19 +394,366 @@ pub fn run() {
         })
         .invoke_handler(tauri::generate_handler![
             commands::search,
+            commands::route_container,
+            commands::take_deep_link_search,
             commands::index_folder,
             commands::reset_index,
             commands::reindex_all,
+            commands::get_interrupted_job,
+            commands::resume_indexing,
+            commands::discard_interrupted_job,
+            commands::reembed_index,
+            commands::remove_indexed_folder,
+            commands::reindex_folder,
             commands::get_containers,
             commands::create_container,
+            commands::get_container_templates,
             commands::delete_container,
+            commands::unlock_container,
+            commands::set_container_locked,
             commands::set_active_container,
             commands::get_config,
             commands::update_config,
             commands::add_annotation,
             commands::get_annotations,
-            commands::delete_annotation
+            commands::delete_annotation,
+            commands::get_topics,
+            commands::get_timeline,
+            commands::get_entities,
+            commands::get_entity_files,
+            commands::ask_file,
+            commands::translate_snippet,
+            commands::find_similar,
+            commands::get_languages,
+            commands::get_extensions,
+            commands::get_deleted_count,
+            commands::suggest_query,
+            commands::open_at_page,
+            commands::complete_query,
+            commands::complete_path,
+            commands::reset_window_position,
+            commands::get_endpoint_health,
+            commands::get_privacy_report,
+            commands::get_secret_report,
+            commands::prefetch_models,
+            commands::get_watcher_status,
+            commands::exclude_path,
+            commands::toggle_pin,
+            commands::record_open,
+            commands::get_recent,
+            commands::clear_recent,
+            commands::quick_answer,
+            commands::build_context_block,
+            commands::is_vibrancy_active,
+            commands::set_hotkey_paused,
+            commands::export_diagnostics,
+            commands::export_search_bundle,
+            commands::sync_now,
+            commands::get_user_locales
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
+
+/// Points the database at the active container's index: its `db_uri` when
+/// it has one, the app's own otherwise. Writers of a shared index also take
+/// its writer lock. Only reconnects when the location changed.
+pub(crate) async fn connect_active_db(app: &tauri::AppHandle) {
+    let (shared_uri, storage_options, writer) = {
+        let config = app.state::<ConfigState>().config.lock().await;
+        let info = config.containers.get(&config.active_container);
+        (
+            info.and_then(|info| info.db_uri.clone()),
+            info.map(|info| info.storage_options.clone()).unwrap_or_default(),
+            config.can_write(&config.active_container),
+        )
+    };
+    match &shared_uri {
+        Some(uri) if writer => {
+            let _ = shared_index::acquire(uri);
+        }
+        _ => shared_index::release(),
+    }
+
+    let db_state = app.state::<Arc<Mutex<DbState>>>();
+    let uri = {
+        let guard = db_state.lock().await;
+        let uri = shared_uri.clone().unwrap_or_else(|| guard.path.to_string_lossy().to_string());
+        if guard.db.is_some() && guard.uri == uri {
+            return;
+        }
+        uri
+    };
+
+    debug!("Connecting to LanceDB at {}", uri);
+    let result = match shared_uri {
+        Some(_) if config::is_offline() && uri.contains("://") => Err(config::offline_error(&format!("The index at {}", uri))),
+        Some(_) => match config::resolve_storage_options(&storage_options) {
+            Ok(options) => indexer::db::connect_shared(&uri, options).await.map_err(|e| e.to_string()),
+            Err(e) => Err(e),
+        },
+        None => indexer::db::connect(&uri).await.map_err(|e| e.to_string()),
+    };
+    match result {
+        Ok(db) => {
+            info!("LanceDB connected: {}", uri);
+            let mut guard = db_state.lock().await;
+            guard.db = Some(db);
+            guard.uri = uri;
+            guard.init_error = None;
+            drop(guard);
+            let _ = app.emit("db-connected", ());
+        }
+        Err(e) => {
+            error!("Failed to connect to LanceDB at {}: {}", uri, e);
+            let mut guard = db_state.lock().await;
+            guard.db = None;
+            guard.uri = uri;
+            guard.init_error = Some(e.clone());
+            drop(guard);
+            let _ = app.emit("db-error", e);
+        }
+    }
+}
+
+/// Only the stop policy blocks indexing.
+pub(crate) async fn quota_block(app: &tauri::AppHandle) -> Option<String> {
+    let (name, quota_mb, policy) = {
+        let config = app.state::<ConfigState>().config.lock().await;
+        let info = config.containers.get(&config.active_container)?;
+        (config.active_container.clone(), info.quota_mb, info.quota_policy)
+    };
+    if policy != config::QuotaPolicy::Stop {
+        return None;
+    }
+    let uri = app.state::<Arc<Mutex<DbState>>>().lock().await.uri.clone();
+    let (size, quota) = indexer::quota::over_quota(&uri, &config::get_table_name(&name), quota_mb)?;
+    Some(format!(
+        "{} is over its index quota ({} MB of {} MB). Raise the quota, remove a folder or let it evict old files",
+        name,
+        indexer::quota::to_mb(size),
+        indexer::quota::to_mb(quota)
+    ))
+}
+
+pub(crate) async fn enforce_quota(app: &tauri::AppHandle) {
+    let (name, quota_mb, policy, writable) = {
+        let config = app.state::<ConfigState>().config.lock().await;
+        let Some(info) = config.containers.get(&config.active_container) else {
+            return;
+        };
+        (
+            config.active_container.clone(),
+            info.quota_mb,
+            info.quota_policy,
+            config.can_write(&config.active_container),
+        )
+    };
+    let Some(quota_mb) = quota_mb.filter(|_| writable) else {
+        return;
+    };
+    if policy == config::QuotaPolicy::Stop {
+        if let Some(message) = quota_block(app).await {
+            warn!("{}", message);
+            let _ = app.emit("quota-exceeded", message);
+        }
+        return;
+    }
+
+    let (db, uri) = {
+        let guard = app.state::<Arc<Mutex<DbState>>>().lock().await;
+        (guard.db.clone(), guard.uri.clone())
+    };
+    let Some(db) = db else {
+        return;
+    };
+    match indexer::quota::evict(&db, &uri, &config::get_table_name(&name), quota_mb * indexer::quota::MB).await {
+        Ok(0) => {}
+        Ok(count) => {
+            let _ = app.emit("quota-evicted", count);
+        }
+        Err(e) => error!("Evicting files from {} failed: {}", name, e),
+    }
+}
+
+/// Updates the active container's centroid for auto container after it
+/// changed, in the background, and with auto container on, computes the
+/// ones other containers it can pick don't have yet.
+pub(crate) fn refresh_centroid(app: &tauri::AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let (active, model, others) = {
+            let config = app.state::<ConfigState>().config.lock().await;
+            let active = config.active_container.clone();
+            let others = if config.auto_container { config.auto_container_candidates() } else { Vec::new() };
+            (active.clone(), config.provider_label(&active), others)
+        };
+        let db = app.state::<Arc<Mutex<DbState>>>().lock().await.db.clone();
+        let Some(db) = db else {
+            return;
+        };
+        let missing = others.into_iter().filter(|name| *name != active && !indexer::centroid::has(name, &model));
+        for name in std::iter::once(active.clone()).chain(missing) {
+            if let Err(e) = indexer::centroid::refresh(&db, &name, &model).await {
+                warn!("Updating the centroid of {} failed: {}", name, e);
+            }
+        }
+    });
+}
+
+pub(crate) async fn restart_watcher_when_ready(app: &tauri::AppHandle) {
+    let db = {
+        let guard = app.state::<Arc<Mutex<DbState>>>().inner().lock().await;
+        guard.db.clone()
+    };
+    let Some(db) = db else {
+        debug!("Database not connected yet, deferring watcher start");
+        return;
+    };
+    let provider_state = app.state::<Arc<Mutex<ProviderState>>>().inner().clone();
+    if provider_state.lock().await.provider.is_none() {
+        debug!("Embedding provider not ready yet, deferring watcher start");
+        return;
+    }
+    watcher::restart(
+        app.state::<watcher::WatcherState>().inner(),
+        app.state::<ConfigState>().inner(),
+        db,
+        provider_state,
+        app.clone(),
+    ).await;
+}
+
+/// Waits for the database and the embedding model when the flag started the app.
+fn index_from_command_line(app: &tauri::AppHandle, dir: String) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let path = std::path::Path::new(&dir);
+        if !path.is_absolute() || !path.is_dir() {
+            warn!("--index: {} is not an absolute path to a folder", dir);
+            return;
+        }
+        for _ in 0..600 {
+            let db_ready = app.state::<Arc<Mutex<DbState>>>().lock().await.db.is_some();
+            let provider_ready = app.state::<Arc<Mutex<ProviderState>>>().lock().await.provider.is_some();
+            if db_ready && provider_ready {
+                info!("--index: indexing {}", dir);
+                let result = commands::index_folder(
+                    app.clone(),
+                    dir.clone(),
+                    app.state(),
+                    app.state(),
+                    app.state(),
+                    app.state(),
+                )
+                .await;
+                match result {
+                    Ok(message) => info!("--index: {}", message),
+                    Err(e) => error!("--index: indexing {} failed: {}", dir, e),
+                }
+                return;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+        }
+        warn!("--index: gave up on {}, the embedding model never loaded", dir);
+    });
+}
+
+/// Registers the `rememex://` scheme and handles links, including the one
+/// the app was launched with.
+fn listen_for_deep_links(app: &tauri::App) {
+    use tauri_plugin_deep_link::DeepLinkExt;
+
+    app.manage(deep_link::PendingSearch::default());
+    // Installers register the scheme on Windows and Linux, but the portable
+    // build and AppImages never run one.
+    #[cfg(any(windows, target_os = "linux"))]
+    {
+        if let Err(e) = app.deep_link().register_all() {
+            warn!("Could not register the {}:// scheme: {}", deep_link::SCHEME, e);
+        }
+    }
+
+    let handle = app.handle().clone();
+    app.deep_link().on_open_url(move |event| {
+        for url in event.urls() {
+            handle_deep_link(&handle, url.as_str());
+        }
+    });
+    if let Ok(Some(urls)) = app.deep_link().get_current() {
+        for url in urls {
+            handle_deep_link(app.handle(), url.as_str());
+        }
+    }
+}
+
+fn handle_deep_link(app: &tauri::AppHandle, link: &str) {
+    match deep_link::parse(link) {
+        Ok(search @ deep_link::DeepLink::Search { .. }) => {
+            info!("Deep link: {:?}", search);
+            if let Ok(mut pending) = app.state::<deep_link::PendingSearch>().0.lock() {
+                *pending = Some(search);
+            }
+            show_main_window(app);
+            let _ = app.emit("deep-link-search", ());
+        }
+        Ok(deep_link::DeepLink::Open { path, page }) => {
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                use tauri_plugin_opener::OpenerExt;
+
+                let allowed = {
+                    let config = app.state::<ConfigState>().config.lock().await;
+                    deep_link::check_openable(&config, &path)
+                };
+                if let Err(e) = allowed {
+                    warn!("Deep link: {}", e);
+                    return;
+                }
+                info!("Deep link: opening {}", path);
+                let result = match page {
+                    Some(page) => commands::open_at_page(app.clone(), path.clone(), page).await,
+                    None => app.opener().open_path(&path, None::<&str>).map_err(|e| e.to_string()),
+                };
+                if let Err(e) = result {
+                    warn!("Deep link: opening {} failed: {}", path, e);
+                }
+            });
+        }
+        Err(e) => warn!("Deep link: {}", e),
+    }
+}
+
+fn toggle_main_window(app: &tauri::AppHandle) {
+    if let Some(window) = app.get_webview_window("main") {
+        let docked = app
+            .state::<ConfigState>()
+            .config
+            .try_lock()
+            .is_ok_and(|config| config.window.mode == config::WindowMode::Docked);
+        if window.is_minimized().unwrap_or(false) {
+            show_main_window(app);
+        } else if docked && !window.is_focused().unwrap_or(false) {
+            // Docked, it's open behind something else; bring it forward.
+            show_main_window(app);
+        } else if docked {
+            let _ = window.minimize();
+        } else if window.is_visible().unwrap_or(false) {
+            let _ = window.hide();
+        } else {
+            show_main_window(app);
+        }
+    }
+}
+
+fn show_main_window(app: &tauri::AppHandle) {
+    if let Some(window) = app.get_webview_window("main") {
+        if window.is_minimized().unwrap_or(false) {
+            let _ = window.unminimize();
+        } else if !window.is_visible().unwrap_or(false) {
+            placement::place_window(app, &window);
+        }
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+}
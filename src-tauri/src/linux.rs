@@ -0,0 +1,139 @@
+//! Linux desktop fallbacks: a layer-shell popup on Wayland, the
+//! GlobalShortcuts portal where an app can't grab the hotkey itself, and a
+//! taskbar entry when there's no tray to sit in.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use log::{debug, info, warn};
+use tauri::{AppHandle, Manager, WebviewWindow};
+use tauri_plugin_global_shortcut::GlobalShortcutExt;
+use tauri_plugin_notification::NotificationExt;
+
+use crate::config::{portal_trigger, HotkeyFallback, LinuxConfig};
+
+const SHORTCUT_ID: &str = "toggle";
+const TRAY_WATCHER: &str = "org.kde.StatusNotifierWatcher";
+/// Left in the data folder once the no-tray hint was shown, so it's shown once.
+const TRAY_HINT_MARKER: &str = "tray-hint-shown";
+
+static LAYER_SHELL: AtomicBool = AtomicBool::new(false);
+
+pub fn is_wayland() -> bool {
+    std::env::var_os("WAYLAND_DISPLAY").is_some()
+}
+
+/// Whether the window is a layer surface, which the compositor places; moving
+/// it does nothing.
+pub fn layer_shell_active() -> bool {
+    LAYER_SHELL.load(Ordering::Relaxed)
+}
+
+/// Makes the window an overlay layer surface: above everything, centered,
+/// with the keyboard while it's shown. Only works before GTK has realized
+/// the window, and only on compositors with wlr-layer-shell (not GNOME).
+pub fn apply_layer_shell(window: &WebviewWindow, config: &LinuxConfig) {
+    use gtk::prelude::WidgetExt;
+    use gtk_layer_shell::{KeyboardMode, Layer, LayerShell};
+
+    if !config.layer_shell || !is_wayland() || !gtk_layer_shell::is_supported() {
+        return;
+    }
+    let Ok(gtk_window) = window.gtk_window() else {
+        return;
+    };
+    if gtk_window.is_realized() {
+        warn!("Window already realized, can't make it a layer-shell overlay");
+        return;
+    }
+    gtk_window.init_layer_shell();
+    gtk_window.set_layer(Layer::Overlay);
+    gtk_window.set_keyboard_mode(KeyboardMode::Exclusive);
+    gtk_window.set_namespace("rememex");
+    LAYER_SHELL.store(true, Ordering::Relaxed);
+    info!("Window is a layer-shell overlay");
+}
+
+/// On Wayland the global-shortcut plugin can't grab keys, so the hotkey is
+/// asked for through the portal instead. The desktop may show a dialog to
+/// confirm it, and lets the user change it in its own settings afterwards.
+pub fn register_portal_hotkey(app: &AppHandle, hotkey: &str, config: &LinuxConfig) {
+    if !is_wayland() || config.hotkey_fallback != HotkeyFallback::Portal {
+        return;
+    }
+    let trigger = portal_trigger(hotkey);
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = listen_portal_hotkey(&app, &trigger).await {
+            warn!(
+                "Global shortcut portal unavailable ({}). Bind `rememex {}` to a shortcut in your desktop settings instead",
+                e,
+                crate::config::TOGGLE_FLAG
+            );
+        }
+    });
+}
+
+async fn listen_portal_hotkey(app: &AppHandle, trigger: &str) -> ashpd::Result<()> {
+    use ashpd::desktop::global_shortcuts::{GlobalShortcuts, NewShortcut};
+    use futures::StreamExt;
+
+    let portal = GlobalShortcuts::new().await?;
+    let session = portal.create_session().await?;
+    let shortcut = NewShortcut::new(SHORTCUT_ID, "Show or hide Rememex").preferred_trigger(trigger);
+    portal.bind_shortcuts(&session, &[shortcut], None).await?.response()?;
+    info!("Hotkey {} registered through the GlobalShortcuts portal", trigger);
+    // The plugin's X11 grab only fires while an XWayland window has focus,
+    // and both firing would toggle the window twice.
+    let _ = app.global_shortcut().unregister_all();
+
+    let mut activated = portal.receive_activated().await?;
+    while let Some(event) = activated.next().await {
+        if event.shortcut_id() == SHORTCUT_ID {
+            crate::toggle_main_window(app);
+        }
+    }
+    debug!("GlobalShortcuts portal stream ended");
+    Ok(())
+}
+
+/// Whether anything on the session bus shows StatusNotifierItem icons.
+/// GNOME only does with the AppIndicator extension.
+async fn has_tray_host() -> bool {
+    let Ok(connection) = zbus::Connection::session().await else {
+        return false;
+    };
+    let Ok(dbus) = zbus::fdo::DBusProxy::new(&connection).await else {
+        return false;
+    };
+    let Ok(name) = zbus::names::BusName::try_from(TRAY_WATCHER) else {
+        return false;
+    };
+    dbus.name_has_owner(name).await.unwrap_or(false)
+}
+
+/// Without a tray host the icon never shows, so the window gets a taskbar
+/// entry instead and a one-time hint says how to bring it back.
+pub fn check_tray(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        if has_tray_host().await {
+            return;
+        }
+        warn!("No StatusNotifier host on the session bus, the tray icon won't show");
+        if let Some(window) = app.get_webview_window("main") {
+            let _ = window.set_skip_taskbar(false);
+        }
+        let marker = crate::config::app_data_dir().join(TRAY_HINT_MARKER);
+        if marker.exists() {
+            return;
+        }
+        let _ = std::fs::write(&marker, "");
+        let body = format!(
+            "No tray on this desktop. Use the hotkey, the taskbar, or bind `rememex {}` to a shortcut.",
+            crate::config::TOGGLE_FLAG
+        );
+        if let Err(e) = app.notification().builder().title("Rememex is running").body(body).show() {
+            debug!("Failed to show notification: {}", e);
+        }
+    });
+}
@@ -0,0 +1,86 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use log::{debug, warn};
+use serde::Serialize;
+
+pub type LocaleStrings = HashMap<String, String>;
+
+#[derive(Serialize)]
+pub struct UserLocales {
+    pub dir: String,
+    pub locales: HashMap<String, LocaleStrings>,
+}
+
+/// `locales/` next to config.json. Dropping `xx.json` here adds or overrides
+/// language `xx` without rebuilding the app.
+pub fn user_locales_dir(config_path: &Path) -> PathBuf {
+    config_path
+        .parent()
+        .map(|p| p.join("locales"))
+        .unwrap_or_else(|| PathBuf::from("locales"))
+}
+
+fn parse_locale(raw: &str) -> Option<LocaleStrings> {
+    let value: serde_json::Value = serde_json::from_str(raw).ok()?;
+    let map = value.as_object()?;
+    Some(
+        map.iter()
+            .filter_map(|(k, v)| v.as_str().map(|s| (k.clone(), s.to_string())))
+            .collect(),
+    )
+}
+
+/// Reads every `*.json` in `dir`, keyed by lowercase file stem. Broken files are
+/// logged and skipped so one typo doesn't take every language down with it.
+pub fn load_user_locales(dir: &Path) -> HashMap<String, LocaleStrings> {
+    let mut locales = HashMap::new();
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return locales;
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("json") {
+            continue;
+        }
+        let Some(code) = path.file_stem().and_then(|s| s.to_str()).map(|s| s.to_lowercase()) else {
+            continue;
+        };
+        match std::fs::read_to_string(&path).ok().and_then(|raw| parse_locale(&raw)) {
+            Some(strings) => {
+                debug!("Loaded user locale '{}' ({} keys)", code, strings.len());
+                locales.insert(code, strings);
+            }
+            None => warn!("Skipping invalid locale file {}", path.display()),
+        }
+    }
+    locales
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_user_locales() {
+        let dir = std::env::temp_dir().join(format!("rememex_locales_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("PT.json"), r#"{"locale_name": "Português", "count": 3}"#).unwrap();
+        std::fs::write(dir.join("broken.json"), "{ not json").unwrap();
+        std::fs::write(dir.join("notes.txt"), "ignored").unwrap();
+
+        let locales = load_user_locales(&dir);
+        std::fs::remove_dir_all(&dir).ok();
+
+        assert_eq!(locales.len(), 1);
+        let pt = &locales["pt"];
+        assert_eq!(pt["locale_name"], "Português");
+        assert!(!pt.contains_key("count"));
+    }
+
+    #[test]
+    fn test_missing_dir_is_empty() {
+        assert!(load_user_locales(Path::new("/definitely/not/here")).is_empty());
+    }
+}
@@ -0,0 +1,93 @@
+//! Progress for model downloads. fastembed fetches a model the first time
+//! it's loaded and only reports that on a terminal, so this watches the
+//! models folder grow while a load runs and tells the window how far along
+//! it is.
+
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use log::{debug, info};
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Rough download sizes in bytes, ONNX weights plus tokenizer files. Only
+/// good for a percentage; the bytes shown are what actually arrived.
+fn expected_bytes(model: &str) -> Option<u64> {
+    const MB: u64 = 1024 * 1024;
+    match model {
+        "AllMiniLML6V2" => Some(90 * MB),
+        "MultilingualE5Small" => Some(470 * MB),
+        "MultilingualE5Base" => Some(1110 * MB),
+        RERANKER => Some(1120 * MB),
+        _ => None,
+    }
+}
+
+/// What `watch` calls the reranker, which has no name in config.
+pub const RERANKER: &str = "reranker";
+
+#[derive(Serialize, Clone, Debug)]
+pub struct DownloadProgress {
+    /// The embedding model's config name, or `reranker`.
+    pub model: String,
+    pub bytes: u64,
+    pub total: Option<u64>,
+}
+
+fn dir_size(path: &Path) -> u64 {
+    let Ok(entries) = std::fs::read_dir(path) else {
+        return 0;
+    };
+    entries
+        .filter_map(|e| e.ok())
+        .map(|e| match e.file_type() {
+            Ok(t) if t.is_dir() => dir_size(&e.path()),
+            Ok(t) if t.is_file() => e.metadata().map(|m| m.len()).unwrap_or(0),
+            _ => 0,
+        })
+        .sum()
+}
+
+/// Stops watching when dropped, so it lives as long as the load it's for.
+pub struct DownloadWatch {
+    stop: Arc<AtomicBool>,
+}
+
+impl Drop for DownloadWatch {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Emits `model-download-progress` while `models_dir` grows, and
+/// `model-download-done` once the watch is dropped after anything arrived.
+/// A model that's already there never emits either.
+pub fn watch(app: &AppHandle, model: &str, models_dir: PathBuf) -> DownloadWatch {
+    let stop = Arc::new(AtomicBool::new(false));
+    let watch = DownloadWatch { stop: stop.clone() };
+    let app = app.clone();
+    let model = model.to_string();
+    std::thread::spawn(move || {
+        let start = dir_size(&models_dir);
+        let total = expected_bytes(&model);
+        let mut last = 0;
+        while !stop.load(Ordering::Relaxed) {
+            std::thread::sleep(POLL_INTERVAL);
+            let bytes = dir_size(&models_dir).saturating_sub(start);
+            if bytes > last {
+                last = bytes;
+                debug!("Downloading {}: {} bytes", model, bytes);
+                let _ = app.emit("model-download-progress", DownloadProgress { model: model.clone(), bytes, total });
+            }
+        }
+        if last > 0 {
+            info!("Downloaded {} ({} bytes)", model, last);
+            let _ = app.emit("model-download-done", model);
+        }
+    });
+    watch
+}
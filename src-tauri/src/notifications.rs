@@ -0,0 +1,105 @@
+use std::time::Duration;
+
+use log::{debug, warn};
+use tauri::{AppHandle, Manager};
+use tauri_plugin_notification::NotificationExt;
+
+use crate::config::ConfigState;
+
+pub fn format_duration(elapsed: Duration) -> String {
+    let secs = elapsed.as_secs();
+    match secs {
+        0 => format!("{}ms", elapsed.as_millis()),
+        1..=59 => format!("{}s", secs),
+        60..=3599 => format!("{}m {:02}s", secs / 60, secs % 60),
+        _ => format!("{}h {:02}m", secs / 3600, (secs % 3600) / 60),
+    }
+}
+
+fn folder_label(dir: &str) -> &str {
+    dir.trim_end_matches(['/', '\\'])
+        .rsplit(['/', '\\'])
+        .next()
+        .filter(|name| !name.is_empty())
+        .unwrap_or(dir)
+}
+
+/// Only worth a toast when the user can't already see the status bar.
+fn should_notify(app: &AppHandle) -> bool {
+    let enabled = app
+        .state::<ConfigState>()
+        .config
+        .try_lock()
+        .map(|config| config.notify_on_complete)
+        .unwrap_or(true);
+    if !enabled {
+        return false;
+    }
+    app.get_webview_window("main").is_none_or(|window| {
+        !window.is_visible().unwrap_or(false) || window.is_minimized().unwrap_or(false)
+    })
+}
+
+fn show(app: &AppHandle, title: &str, body: &str) {
+    if !should_notify(app) {
+        debug!("Window visible or notifications off, skipping toast: {}", title);
+        return;
+    }
+    if let Err(e) = app.notification().builder().title(title).body(body).show() {
+        warn!("Failed to show notification: {}", e);
+    }
+}
+
+/// `dirs` are the folders the job covered; the toast names the first one.
+pub fn job_finished(app: &AppHandle, dirs: &[String], files: usize, elapsed: Duration) {
+    let target = match dirs {
+        [single] => folder_label(single).to_string(),
+        _ => format!("{} folders", dirs.len()),
+    };
+    show(
+        app,
+        "Indexing finished",
+        &format!("{} files from {} in {}", files, target, format_duration(elapsed)),
+    );
+}
+
+pub fn job_failed(app: &AppHandle, dir: &str, error: &str, elapsed: Duration) {
+    show(
+        app,
+        "Indexing failed",
+        &format!("{} after {}: {}", folder_label(dir), format_duration(elapsed), error),
+    );
+}
+
+/// Shown whether or not the window is up: nothing else says the digest is
+/// ready. `digest.notify` turns it off.
+pub fn digest_written(app: &AppHandle, files: usize) {
+    let body = match files {
+        0 => "No indexed files changed in the last day".to_string(),
+        1 => "1 changed file, searchable in the digest container".to_string(),
+        _ => format!("{} changed files, searchable in the digest container", files),
+    };
+    if let Err(e) = app.notification().builder().title("Daily digest ready").body(&body).show() {
+        warn!("Failed to show notification: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(Duration::from_millis(450)), "450ms");
+        assert_eq!(format_duration(Duration::from_secs(42)), "42s");
+        assert_eq!(format_duration(Duration::from_secs(125)), "2m 05s");
+        assert_eq!(format_duration(Duration::from_secs(3725)), "1h 02m");
+    }
+
+    #[test]
+    fn test_folder_label() {
+        assert_eq!(folder_label("C:\\Projects\\notes\\"), "notes");
+        assert_eq!(folder_label("/home/me/docs"), "docs");
+        assert_eq!(folder_label("C:\\"), "C:");
+    }
+}
@@ -0,0 +1,219 @@
+//! Containers locked behind the OS's own sign-in check: Windows Hello on
+//! Windows, Touch ID (or the login password) on macOS. Unlocking one holds
+//! for `unlock_timeout_mins`. When that runs out is kept in a file next to
+//! the config, so an unlock in the app lets the MCP server in too. The file
+//! is signed with a key in the OS keychain; one that doesn't check out
+//! unlocks nothing, so writing it isn't enough to get in.
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+use anyhow::{anyhow, Result};
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+
+use crate::config::{app_data_dir, Config, KEYCHAIN_SERVICE};
+
+const UNLOCKED_FILE: &str = "unlocked.json";
+const KEY_ENTRY: &str = "unlock-signing-key";
+
+static KEY: OnceLock<[u8; 32]> = OnceLock::new();
+
+#[derive(Serialize, Deserialize)]
+struct Signed {
+    unlocked: BTreeMap<String, i64>,
+    mac: String,
+}
+
+/// Whether this machine has a check to ask. A container can't be locked
+/// without one.
+pub fn available() -> bool {
+    platform::available()
+}
+
+/// Asks the user to prove it's them, with `reason` in the prompt. Errors
+/// when they cancel or fail.
+pub async fn verify(reason: &str) -> Result<()> {
+    let reason = reason.to_string();
+    let verified = tokio::task::spawn_blocking(move || platform::verify(&reason)).await??;
+    if !verified {
+        return Err(anyhow!("Not verified"));
+    }
+    Ok(())
+}
+
+fn unlocked_path() -> PathBuf {
+    app_data_dir().join(UNLOCKED_FILE)
+}
+
+/// The signing key, made on the first unlock. None until then, or when the
+/// keychain can't be reached, which leaves every locked container locked.
+fn key(create: bool) -> Option<[u8; 32]> {
+    if let Some(key) = KEY.get() {
+        return Some(*key);
+    }
+    let entry = keyring::Entry::new(KEYCHAIN_SERVICE, KEY_ENTRY).map_err(|e| warn!("Keychain unavailable: {}", e)).ok()?;
+    let key = match entry.get_password() {
+        Ok(hex) => blake3::Hash::from_hex(hex.trim()).ok()?.into(),
+        Err(keyring::Error::NoEntry) if create => {
+            let mut key = [0u8; 32];
+            getrandom::getrandom(&mut key).map_err(|e| warn!("No randomness for the unlock key: {}", e)).ok()?;
+            entry.set_password(&blake3::Hash::from(key).to_hex()).map_err(|e| warn!("Failed to store the unlock key: {}", e)).ok()?;
+            key
+        }
+        Err(keyring::Error::NoEntry) => return None,
+        Err(e) => {
+            warn!("Failed to read the unlock key: {}", e);
+            return None;
+        }
+    };
+    Some(*KEY.get_or_init(|| key))
+}
+
+fn mac(key: &[u8; 32], unlocked: &BTreeMap<String, i64>) -> blake3::Hash {
+    blake3::keyed_hash(key, serde_json::to_string(unlocked).unwrap_or_default().as_bytes())
+}
+
+/// When each unlocked container locks again, in Unix seconds.
+fn load() -> BTreeMap<String, i64> {
+    let Some(signed) = std::fs::read_to_string(unlocked_path()).ok().and_then(|json| serde_json::from_str::<Signed>(&json).ok()) else {
+        return BTreeMap::new();
+    };
+    match key(false) {
+        Some(key) if blake3::Hash::from_hex(&signed.mac).is_ok_and(|given| given == mac(&key, &signed.unlocked)) => signed.unlocked,
+        _ => BTreeMap::new(),
+    }
+}
+
+fn store(unlocked: &BTreeMap<String, i64>) {
+    let path = unlocked_path();
+    let now = chrono::Utc::now().timestamp();
+    let live: BTreeMap<String, i64> = unlocked.iter().filter(|(_, &until)| until > now).map(|(name, &until)| (name.clone(), until)).collect();
+    if live.is_empty() {
+        std::fs::remove_file(&path).ok();
+        return;
+    }
+    let Some(key) = key(true) else {
+        warn!("Unlock not kept: no signing key in the keychain");
+        return;
+    };
+    let signed = Signed { mac: mac(&key, &live).to_hex().to_string(), unlocked: live };
+    match serde_json::to_string(&signed) {
+        Ok(json) => {
+            if let Err(e) = std::fs::write(&path, json) {
+                warn!("Failed to write {:?}: {}", path, e);
+            }
+        }
+        Err(e) => warn!("Failed to serialize unlocked containers: {}", e),
+    }
+}
+
+/// Whether `container` was unlocked and that hasn't run out.
+pub fn is_unlocked(container: &str) -> bool {
+    let now = chrono::Utc::now().timestamp();
+    load().get(container).is_some_and(|&until| until > now)
+}
+
+/// Keeps `container` open for `minutes`.
+pub fn unlock(container: &str, minutes: u64) {
+    let mut unlocked = load();
+    let until = chrono::Utc::now().timestamp() + i64::try_from(minutes.saturating_mul(60)).unwrap_or(i64::MAX / 2);
+    unlocked.insert(container.to_string(), until);
+    store(&unlocked);
+    info!("Unlocked \"{}\" for {} min", container, minutes);
+}
+
+/// Drops an unlock before it runs out, when the container is deleted or
+/// no longer locked.
+pub fn forget(container: &str) {
+    let mut unlocked = load();
+    if unlocked.remove(container).is_some() {
+        store(&unlocked);
+    }
+}
+
+/// Errors when `container` is locked and not unlocked right now.
+pub fn ensure_unlocked(config: &Config, container: &str) -> Result<(), String> {
+    if config.containers.get(container).is_some_and(|info| info.locked) && !is_unlocked(container) {
+        return Err(format!("Container \"{}\" is locked", container));
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use anyhow::Result;
+    use windows::core::HSTRING;
+    use windows::Security::Credentials::UI::{
+        UserConsentVerificationResult, UserConsentVerifier, UserConsentVerifierAvailability,
+    };
+
+    pub fn available() -> bool {
+        UserConsentVerifier::CheckAvailabilityAsync()
+            .and_then(|op| op.get())
+            .is_ok_and(|availability| availability == UserConsentVerifierAvailability::Available)
+    }
+
+    pub fn verify(reason: &str) -> Result<bool> {
+        let result = UserConsentVerifier::RequestVerificationAsync(&HSTRING::from(reason))?.get()?;
+        Ok(result == UserConsentVerificationResult::Verified)
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use std::sync::mpsc;
+
+    use anyhow::{anyhow, Result};
+    use block2::RcBlock;
+    use objc2::runtime::Bool;
+    use objc2_foundation::{NSError, NSString};
+    use objc2_local_authentication::{LAContext, LAPolicy};
+
+    /// Touch ID where there is one, the login password otherwise.
+    const POLICY: LAPolicy = LAPolicy::DeviceOwnerAuthentication;
+
+    pub fn available() -> bool {
+        unsafe { LAContext::new().canEvaluatePolicy_error(POLICY) }.is_ok()
+    }
+
+    pub fn verify(reason: &str) -> Result<bool> {
+        let (tx, rx) = mpsc::channel();
+        let reply = RcBlock::new(move |success: Bool, _error: *mut NSError| {
+            let _ = tx.send(success.as_bool());
+        });
+        // Held until the reply comes; the check is cancelled when it goes.
+        let context = unsafe { LAContext::new() };
+        unsafe { context.evaluatePolicy_localizedReason_reply(POLICY, &NSString::from_str(reason), &reply) };
+        rx.recv().map_err(|_| anyhow!("The sign-in check gave no answer"))
+    }
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+mod platform {
+    use anyhow::{anyhow, Result};
+
+    pub fn available() -> bool {
+        false
+    }
+
+    pub fn verify(_reason: &str) -> Result<bool> {
+        Err(anyhow!("No Windows Hello or Touch ID on this system"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mac_covers_expiry() {
+        let key = [7u8; 32];
+        let unlocked = BTreeMap::from([("Work".to_string(), 1_700_000_000)]);
+        let extended = BTreeMap::from([("Work".to_string(), 1_800_000_000)]);
+        assert_eq!(mac(&key, &unlocked), mac(&key, &unlocked));
+        assert_ne!(mac(&key, &unlocked), mac(&key, &extended));
+        assert_ne!(mac(&key, &unlocked), mac(&[8u8; 32], &unlocked));
+    }
+}
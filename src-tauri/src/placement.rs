@@ -0,0 +1,234 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use log::{debug, warn};
+use tauri::{AppHandle, Manager, Monitor, PhysicalPosition, PhysicalSize, WebviewWindow, Window};
+
+use crate::config::{ConfigState, WindowMode, WindowPlacement};
+
+static MOVE_GENERATION: AtomicU64 = AtomicU64::new(0);
+static RESIZE_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+fn monitor_key(monitor: &Monitor) -> String {
+    let pos = monitor.position();
+    let size = monitor.size();
+    match monitor.name() {
+        Some(name) => format!("{} {}x{}", name, size.width, size.height),
+        None => format!("{},{} {}x{}", pos.x, pos.y, size.width, size.height),
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn foreground_window_center() -> Option<(f64, f64)> {
+    use windows::Win32::Foundation::RECT;
+    use windows::Win32::UI::WindowsAndMessaging::{GetForegroundWindow, GetWindowRect};
+
+    unsafe {
+        let hwnd = GetForegroundWindow();
+        if hwnd.is_invalid() {
+            return None;
+        }
+        let mut rect = RECT::default();
+        GetWindowRect(hwnd, &mut rect).ok()?;
+        Some((
+            f64::from(rect.left + rect.right) / 2.0,
+            f64::from(rect.top + rect.bottom) / 2.0,
+        ))
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+fn foreground_window_center() -> Option<(f64, f64)> {
+    None
+}
+
+fn target_monitor(app: &AppHandle, placement: WindowPlacement) -> Option<Monitor> {
+    let from_cursor = || {
+        let cursor = app.cursor_position().ok()?;
+        app.monitor_from_point(cursor.x, cursor.y).ok().flatten()
+    };
+    match placement {
+        WindowPlacement::Keep => None,
+        WindowPlacement::Primary => app.primary_monitor().ok().flatten(),
+        WindowPlacement::Cursor => from_cursor(),
+        WindowPlacement::Focused => foreground_window_center()
+            .and_then(|(x, y)| app.monitor_from_point(x, y).ok().flatten())
+            .or_else(from_cursor),
+    }
+}
+
+/// For `keep`: the monitor the window was last left on when remembering
+/// that, and the primary one when the window is on a monitor that's gone.
+/// None leaves the window where it is.
+fn kept_monitor(app: &AppHandle, window: &WebviewWindow, last: Option<&str>) -> Option<Monitor> {
+    let monitors = app.available_monitors().unwrap_or_default();
+    if let Some(monitor) = last.and_then(|last| monitors.into_iter().find(|m| monitor_key(m) == last)) {
+        return Some(monitor);
+    }
+    let lost = last.is_some() || window.current_monitor().ok().flatten().is_none();
+    if lost {
+        app.primary_monitor().ok().flatten()
+    } else {
+        None
+    }
+}
+
+/// Moves the hidden window onto the monitor picked by `window.placement`, either at
+/// its remembered spot for that monitor or centered. Called right before showing.
+pub fn place_window(app: &AppHandle, window: &WebviewWindow) {
+    #[cfg(target_os = "linux")]
+    if crate::linux::layer_shell_active() {
+        return;
+    }
+    let (placement, remembered, last) = {
+        let Ok(config) = app.state::<ConfigState>().config.try_lock() else {
+            return;
+        };
+        let remember = config.window.remember_per_monitor;
+        (
+            config.window.placement,
+            remember.then(|| config.window.monitor_positions.clone()),
+            config.window.last_monitor.clone().filter(|_| remember),
+        )
+    };
+
+    let monitor = match placement {
+        WindowPlacement::Keep => kept_monitor(app, window, last.as_deref()),
+        _ => target_monitor(app, placement),
+    };
+    let Some(monitor) = monitor else {
+        return;
+    };
+    let Ok(size) = window.outer_size() else {
+        return;
+    };
+    let origin = monitor.position();
+    let area = monitor.size();
+    let max_x = (area.width.saturating_sub(size.width)) as i32;
+    let max_y = (area.height.saturating_sub(size.height)) as i32;
+
+    let offset = remembered
+        .and_then(|positions| positions.get(&monitor_key(&monitor)).copied())
+        .map(|[x, y]| [x.clamp(0, max_x), y.clamp(0, max_y)])
+        .unwrap_or([max_x / 2, max_y / 2]);
+
+    debug!("Placing window on {} at offset {:?}", monitor_key(&monitor), offset);
+    if let Err(e) = window.set_position(PhysicalPosition::new(origin.x + offset[0], origin.y + offset[1])) {
+        warn!("Failed to position window: {}", e);
+    }
+}
+
+/// Drops the window from the menu bar item it was opened from, centered on
+/// the icon and kept on that screen, like other menu bar apps.
+#[cfg(target_os = "macos")]
+pub fn place_under_tray(window: &WebviewWindow, icon: tauri::Rect) {
+    let scale = window.scale_factor().unwrap_or(1.0);
+    let position = icon.position.to_physical::<f64>(scale);
+    let icon_size = icon.size.to_physical::<f64>(scale);
+    let Ok(size) = window.outer_size() else {
+        return;
+    };
+    let mut x = position.x + icon_size.width / 2.0 - f64::from(size.width) / 2.0;
+    let y = position.y + icon_size.height + 4.0 * scale;
+    if let Ok(Some(monitor)) = window.monitor_from_point(position.x, position.y) {
+        let left = f64::from(monitor.position().x);
+        let right = left + f64::from(monitor.size().width.saturating_sub(size.width));
+        x = x.clamp(left, right.max(left));
+    }
+    debug!("Placing window under the menu bar item at {:.0},{:.0}", x, y);
+    if let Err(e) = window.set_position(PhysicalPosition::new(x as i32, y as i32)) {
+        warn!("Failed to position window: {}", e);
+    }
+}
+
+/// Records where the user left the window on its current monitor. Saving is
+/// debounced so dragging the title bar does not rewrite config.json per pixel.
+pub fn remember_position(app: &AppHandle, window: &Window, position: PhysicalPosition<i32>) {
+    let config_state = app.state::<ConfigState>();
+    {
+        let Ok(config) = config_state.config.try_lock() else {
+            return;
+        };
+        if !config.window.remember_per_monitor || !window.is_visible().unwrap_or(false) {
+            return;
+        }
+    }
+    let Ok(Some(monitor)) = window.current_monitor() else {
+        return;
+    };
+    let origin = monitor.position();
+    let key = monitor_key(&monitor);
+    let offset = [position.x - origin.x, position.y - origin.y];
+
+    let generation = MOVE_GENERATION.fetch_add(1, Ordering::SeqCst) + 1;
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(800)).await;
+        if MOVE_GENERATION.load(Ordering::SeqCst) != generation {
+            return;
+        }
+        let config_state = app.state::<ConfigState>();
+        {
+            let mut config = config_state.config.lock().await;
+            config.window.monitor_positions.insert(key.clone(), offset);
+            config.window.last_monitor = Some(key);
+        }
+        if let Err(e) = config_state.save().await {
+            warn!("Failed to save window position: {}", e);
+        }
+    });
+}
+
+/// Forgets every remembered spot and centers the window on the monitor it's
+/// on, for when it ended up somewhere awkward.
+pub async fn reset_position(app: &AppHandle) -> Result<(), String> {
+    let config_state = app.state::<ConfigState>();
+    {
+        let mut config = config_state.config.lock().await;
+        config.window.monitor_positions.clear();
+        config.window.last_monitor = None;
+    }
+    config_state.save().await?;
+    if let Some(window) = app.get_webview_window("main") {
+        window.center().map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// Keeps the size the user dragged a docked window to as `window.width` and
+/// `window.height`, debounced like `remember_position`. The popup only
+/// changes size from settings.
+pub fn remember_size(app: &AppHandle, window: &Window, size: PhysicalSize<u32>) {
+    let config_state = app.state::<ConfigState>();
+    {
+        let Ok(config) = config_state.config.try_lock() else {
+            return;
+        };
+        if config.window.mode != WindowMode::Docked
+            || !window.is_visible().unwrap_or(false)
+            || window.is_minimized().unwrap_or(false)
+        {
+            return;
+        }
+    }
+    let logical = size.to_logical::<f64>(window.scale_factor().unwrap_or(1.0));
+
+    let generation = RESIZE_GENERATION.fetch_add(1, Ordering::SeqCst) + 1;
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(800)).await;
+        if RESIZE_GENERATION.load(Ordering::SeqCst) != generation {
+            return;
+        }
+        let config_state = app.state::<ConfigState>();
+        {
+            let mut config = config_state.config.lock().await;
+            config.window.width = logical.width.round() as u32;
+            config.window.height = logical.height.round() as u32;
+            config.window = config.window.clamped();
+        }
+        if let Err(e) = config_state.save().await {
+            warn!("Failed to save window size: {}", e);
+        }
+    });
+}
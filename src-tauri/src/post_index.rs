@@ -0,0 +1,126 @@
+//! Runs `post_index_command` after each indexing job, for workflows like
+//! syncing the Lance directory to a backup. The job's details go in
+//! `REMEMEX_*` environment variables.
+
+use std::process::{Command, Stdio};
+use std::sync::Arc;
+
+use log::{debug, info, warn};
+use tauri::{AppHandle, Manager};
+use tokio::sync::Mutex;
+
+use crate::config::{get_table_name, ConfigState};
+use crate::state::DbState;
+use crate::webhooks::IndexEvent;
+
+/// Variables describing the job itself; the container and database ones
+/// are added when the command runs.
+fn job_vars(event: &IndexEvent) -> Vec<(&'static str, String)> {
+    let folders = |dirs: &[String]| {
+        std::env::join_paths(dirs).map_or_else(|_| dirs.join("\n"), |joined| joined.to_string_lossy().to_string())
+    };
+    match event {
+        IndexEvent::Complete { folders: dirs, files, elapsed } => vec![
+            ("REMEMEX_STATUS", "ok".to_string()),
+            ("REMEMEX_FOLDERS", folders(dirs)),
+            ("REMEMEX_FILES", files.to_string()),
+            ("REMEMEX_DURATION_MS", elapsed.as_millis().to_string()),
+        ],
+        IndexEvent::Error { folder, error, elapsed } => vec![
+            ("REMEMEX_STATUS", "failed".to_string()),
+            ("REMEMEX_FOLDERS", folder.clone()),
+            ("REMEMEX_FILES", "0".to_string()),
+            ("REMEMEX_DURATION_MS", elapsed.as_millis().to_string()),
+            ("REMEMEX_ERROR", error.clone()),
+        ],
+        IndexEvent::FilesChanged { .. } => Vec::new(),
+    }
+}
+
+#[cfg(windows)]
+fn shell(command: &str) -> Command {
+    use std::os::windows::process::CommandExt;
+    const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+
+    let mut cmd = Command::new("cmd");
+    // Passed through untouched, so the command can quote its own arguments.
+    cmd.arg("/C").raw_arg(command).creation_flags(CREATE_NO_WINDOW);
+    cmd
+}
+
+#[cfg(not(windows))]
+fn shell(command: &str) -> Command {
+    let mut cmd = Command::new("sh");
+    cmd.arg("-c").arg(command);
+    cmd
+}
+
+/// Starts the command in the background if one is set. Indexing doesn't
+/// wait for it; a failing command only shows up in the log.
+pub fn run(app: &AppHandle, event: &IndexEvent) {
+    let vars = job_vars(event);
+    if vars.is_empty() {
+        return;
+    }
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let (command, container) = {
+            let config = app.state::<ConfigState>().config.lock().await;
+            (config.post_index_command.trim().to_string(), config.active_container.clone())
+        };
+        if command.is_empty() {
+            return;
+        }
+        let db_uri = app.state::<Arc<Mutex<DbState>>>().lock().await.uri.clone();
+
+        std::thread::spawn(move || {
+            info!("Running post-index command for {}", container);
+            let output = shell(&command)
+                .envs(vars)
+                .env("REMEMEX_TABLE", get_table_name(&container))
+                .env("REMEMEX_CONTAINER", container)
+                .env("REMEMEX_DB_URI", db_uri)
+                .stdin(Stdio::null())
+                .output();
+            match output {
+                Ok(output) if output.status.success() => debug!("Post-index command finished"),
+                Ok(output) => warn!(
+                    "Post-index command exited with {}: {}",
+                    output.status,
+                    String::from_utf8_lossy(&output.stderr).trim()
+                ),
+                Err(e) => warn!("Failed to run post-index command: {}", e),
+            }
+        });
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_job_vars() {
+        let done = IndexEvent::Complete {
+            folders: vec!["/home/u/notes".to_string()],
+            files: 12,
+            elapsed: Duration::from_millis(2500),
+        };
+        let vars = job_vars(&done);
+        assert!(vars.contains(&("REMEMEX_STATUS", "ok".to_string())));
+        assert!(vars.contains(&("REMEMEX_FOLDERS", "/home/u/notes".to_string())));
+        assert!(vars.contains(&("REMEMEX_FILES", "12".to_string())));
+        assert!(vars.contains(&("REMEMEX_DURATION_MS", "2500".to_string())));
+
+        let failed = IndexEvent::Error {
+            folder: "/home/u/notes".to_string(),
+            error: "disk full".to_string(),
+            elapsed: Duration::from_secs(1),
+        };
+        assert!(job_vars(&failed).contains(&("REMEMEX_ERROR", "disk full".to_string())));
+
+        let changed = IndexEvent::FilesChanged { changed: Vec::new(), deleted: Vec::new(), errors: 0 };
+        assert!(job_vars(&changed).is_empty());
+    }
+}
@@ -0,0 +1,166 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use log::info;
+use tokio::sync::Mutex;
+
+use crate::config::Config;
+
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(15);
+/// Rayon threads used for text extraction while throttled.
+pub const LOW_POWER_THREADS: usize = 2;
+/// Smaller embedding batches with a pause after each keep the CPU from
+/// pinning at 100% for the whole run.
+pub const LOW_POWER_BATCH_SIZE: usize = 32;
+pub const LOW_POWER_BATCH_PAUSE: Duration = Duration::from_millis(250);
+
+static LOW_POWER: AtomicBool = AtomicBool::new(false);
+
+/// True while indexing should hold back: on battery or with the CPU busy,
+/// depending on config. Always false in processes that never start the
+/// monitor, like the MCP server.
+pub fn is_low_power() -> bool {
+    LOW_POWER.load(Ordering::Relaxed)
+}
+
+#[cfg(target_os = "windows")]
+fn on_battery() -> bool {
+    use windows::Win32::System::Power::{GetSystemPowerStatus, SYSTEM_POWER_STATUS};
+
+    let mut status = SYSTEM_POWER_STATUS::default();
+    unsafe { GetSystemPowerStatus(&mut status) }.is_ok() && status.ACLineStatus == 0
+}
+
+#[cfg(target_os = "linux")]
+fn on_battery() -> bool {
+    let Ok(entries) = std::fs::read_dir("/sys/class/power_supply") else {
+        return false;
+    };
+    entries.filter_map(|e| e.ok()).any(|e| {
+        let read = |name: &str| std::fs::read_to_string(e.path().join(name)).unwrap_or_default();
+        read("type").trim() == "Battery" && read("status").trim() == "Discharging"
+    })
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+fn on_battery() -> bool {
+    false
+}
+
+/// Cumulative CPU time counters, all in the same OS-specific unit. Our own
+/// time is tracked so indexing doesn't count as the load that throttles it.
+#[derive(Clone, Copy)]
+struct CpuTimes {
+    idle: u64,
+    total: u64,
+    own: u64,
+}
+
+#[cfg(target_os = "windows")]
+fn cpu_times() -> Option<CpuTimes> {
+    use windows::Win32::Foundation::FILETIME;
+    use windows::Win32::System::Threading::{GetCurrentProcess, GetProcessTimes, GetSystemTimes};
+
+    let value = |t: FILETIME| (u64::from(t.dwHighDateTime) << 32) | u64::from(t.dwLowDateTime);
+    let (mut idle, mut kernel, mut user) = (FILETIME::default(), FILETIME::default(), FILETIME::default());
+    let (mut created, mut exited, mut own_kernel, mut own_user) =
+        (FILETIME::default(), FILETIME::default(), FILETIME::default(), FILETIME::default());
+    unsafe {
+        GetSystemTimes(Some(&mut idle as *mut _), Some(&mut kernel as *mut _), Some(&mut user as *mut _)).ok()?;
+        GetProcessTimes(GetCurrentProcess(), &mut created, &mut exited, &mut own_kernel, &mut own_user).ok()?;
+    }
+    // Kernel time already includes idle time.
+    Some(CpuTimes {
+        idle: value(idle),
+        total: value(kernel) + value(user),
+        own: value(own_kernel) + value(own_user),
+    })
+}
+
+#[cfg(target_os = "linux")]
+fn cpu_times() -> Option<CpuTimes> {
+    let stat = std::fs::read_to_string("/proc/stat").ok()?;
+    let fields: Vec<u64> = stat
+        .lines()
+        .next()?
+        .split_whitespace()
+        .skip(1)
+        .filter_map(|v| v.parse().ok())
+        .collect();
+    // utime and stime, counted after the parenthesised command name.
+    let own_stat = std::fs::read_to_string("/proc/self/stat").ok()?;
+    let own: Vec<u64> = own_stat
+        .rsplit_once(')')?
+        .1
+        .split_whitespace()
+        .skip(11)
+        .take(2)
+        .filter_map(|v| v.parse().ok())
+        .collect();
+    Some(CpuTimes {
+        idle: fields.get(3)? + fields.get(4).unwrap_or(&0),
+        total: fields.iter().sum(),
+        own: own.iter().sum(),
+    })
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+fn cpu_times() -> Option<CpuTimes> {
+    None
+}
+
+/// Share of CPU time between two samples spent on other processes.
+fn busy_percent(before: CpuTimes, after: CpuTimes) -> Option<u64> {
+    let total = after.total.saturating_sub(before.total);
+    let idle = after.idle.saturating_sub(before.idle);
+    let own = after.own.saturating_sub(before.own);
+    (total > 0).then(|| (total.saturating_sub(idle + own) * 100 / total).min(100))
+}
+
+/// Re-evaluates low-power mode every few seconds from the battery state and
+/// the CPU load since the last sample.
+pub fn spawn_monitor(config: Arc<Mutex<Config>>) {
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(SAMPLE_INTERVAL);
+        let mut last_times = cpu_times();
+        loop {
+            interval.tick().await;
+
+            let (on_battery_enabled, cpu_limit) = {
+                let config = config.lock().await;
+                (config.throttle_on_battery, config.throttle_cpu_percent)
+            };
+
+            let times = cpu_times();
+            let cpu = last_times.zip(times).and_then(|(before, after)| busy_percent(before, after));
+            last_times = times;
+
+            let battery = on_battery_enabled && on_battery();
+            let busy = cpu_limit > 0 && cpu.is_some_and(|c| c >= u64::from(cpu_limit));
+            let low_power = battery || busy;
+            if LOW_POWER.swap(low_power, Ordering::Relaxed) != low_power {
+                info!(
+                    "Low-power indexing {} (battery: {}, cpu: {:?}%)",
+                    if low_power { "on" } else { "off" },
+                    battery,
+                    cpu
+                );
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_busy_percent() {
+        let at = |idle, total, own| CpuTimes { idle, total, own };
+        assert_eq!(busy_percent(at(100, 1000, 0), at(150, 1200, 0)), Some(75));
+        assert_eq!(busy_percent(at(100, 1000, 0), at(150, 1200, 100)), Some(25));
+        assert_eq!(busy_percent(at(100, 1000, 0), at(300, 1200, 0)), Some(0));
+        assert_eq!(busy_percent(at(100, 1000, 0), at(100, 1000, 0)), None);
+    }
+}
@@ -0,0 +1,236 @@
+//! What leaves the machine with the current config: which endpoints get
+//! called and what they're sent. Worked out from config alone, so it can be
+//! checked before anything is indexed or searched. The app words each kind
+//! of payload from its locale files by id.
+
+use std::path::Path;
+
+use serde::Serialize;
+
+use crate::config::{get_embedding_model, Config, EmbeddingProviderConfig, WebhookEvent};
+use crate::indexer::embedding::{is_cached, model_repo, RERANKER_REPO};
+
+const HUGGING_FACE: &str = "https://huggingface.co";
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum FlowKind {
+    Embeddings,
+    Hyde,
+    Summaries,
+    ObjectStore,
+    Webhook,
+    ModelDownload,
+}
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Payload {
+    /// File text in chunks, each under a `File:` line with the file's name.
+    ChunkText,
+    /// Notes added to results.
+    Annotations,
+    /// What's typed into search.
+    Queries,
+    /// The whole index: chunk text, vectors, paths and file details.
+    Index,
+    /// Paths of files that changed or were deleted.
+    ChangedPaths,
+    /// Folder paths and error messages from failed indexing.
+    IndexingErrors,
+    /// How many folders and files were indexed, and how long it took.
+    IndexingTotals,
+    /// Which model to download; nothing from any file.
+    ModelName,
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct OutboundFlow {
+    pub kind: FlowKind,
+    pub destination: String,
+    pub payloads: Vec<Payload>,
+    /// The containers it happens for; empty for app-wide ones like webhooks.
+    pub containers: Vec<String>,
+    /// Whether an API key or other credential goes along.
+    pub credentials: bool,
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct PrivacyReport {
+    /// Nothing in `flows` is sent while this is on.
+    pub offline: bool,
+    pub flows: Vec<OutboundFlow>,
+}
+
+impl PrivacyReport {
+    /// Adds `container` to a flow going to the same place with the same
+    /// payloads, or starts one.
+    fn add(&mut self, kind: FlowKind, destination: &str, payloads: Vec<Payload>, container: Option<&str>, credentials: bool) {
+        let existing = self.flows.iter().position(|flow| {
+            flow.kind == kind && flow.destination == destination && flow.payloads == payloads && flow.credentials == credentials
+        });
+        let index = existing.unwrap_or_else(|| {
+            self.flows.push(OutboundFlow {
+                kind,
+                destination: destination.to_string(),
+                payloads,
+                containers: Vec::new(),
+                credentials,
+            });
+            self.flows.len() - 1
+        });
+        if let Some(container) = container {
+            self.flows[index].containers.push(container.to_string());
+        }
+    }
+}
+
+fn has_key(key: &Option<String>) -> bool {
+    key.as_deref().is_some_and(|key| !key.is_empty())
+}
+
+/// Storage options that look like a secret rather than a region or an
+/// endpoint.
+fn has_storage_credentials(options: &std::collections::HashMap<String, String>) -> bool {
+    options.keys().any(|key| {
+        let key = key.to_lowercase();
+        ["key", "secret", "token", "password", "credential"].iter().any(|word| key.contains(word))
+    })
+}
+
+/// Everything `config` would send out. Model downloads are only listed for
+/// models that aren't in `models_dir` yet.
+pub fn build(config: &Config, models_dir: &Path) -> PrivacyReport {
+    let mut report = PrivacyReport { offline: config.offline, flows: Vec::new() };
+    let mut containers: Vec<&String> = config.containers.keys().collect();
+    containers.sort();
+
+    let mut local_repos = Vec::new();
+    let mut wants_reranker = false;
+    for name in containers {
+        let info = &config.containers[name];
+        let provider = info.embedding_provider.as_ref().unwrap_or(&config.embedding_provider);
+        match provider {
+            EmbeddingProviderConfig::Remote(rc) => {
+                // A reader of a shared index only ever embeds what it searches for.
+                let payloads = if config.can_write(name) {
+                    vec![Payload::ChunkText, Payload::Annotations, Payload::Queries]
+                } else {
+                    vec![Payload::Queries]
+                };
+                report.add(FlowKind::Embeddings, &rc.endpoint, payloads, Some(name), has_key(&rc.api_key));
+            }
+            EmbeddingProviderConfig::Local { model } => {
+                if let Some(repo) = model_repo(&get_embedding_model(model)) {
+                    local_repos.push(repo);
+                }
+            }
+        }
+
+        let retrieval = config.retrieval_for(name);
+        wants_reranker |= retrieval.use_reranker;
+        if let Some(hyde) = retrieval.hyde.filter(|hyde| hyde.enabled && !hyde.endpoint.is_empty()) {
+            report.add(FlowKind::Hyde, &hyde.endpoint, vec![Payload::Queries], Some(name), has_key(&hyde.api_key));
+        }
+        let summaries = &config.indexing.summaries;
+        if summaries.is_active() && config.can_write(name) {
+            report.add(FlowKind::Summaries, &summaries.endpoint, vec![Payload::ChunkText], Some(name), has_key(&summaries.api_key));
+        }
+
+        if let Some(uri) = info.db_uri.as_deref().filter(|uri| uri.contains("://")) {
+            report.add(FlowKind::ObjectStore, uri, vec![Payload::Index], Some(name), has_storage_credentials(&info.storage_options));
+        }
+    }
+
+    for hook in &config.webhooks {
+        let payloads: Vec<Payload> = [
+            (WebhookEvent::IndexingComplete, Payload::IndexingTotals),
+            (WebhookEvent::IndexingError, Payload::IndexingErrors),
+            (WebhookEvent::FilesChanged, Payload::ChangedPaths),
+        ]
+        .into_iter()
+        .filter(|(event, _)| hook.wants(*event))
+        .map(|(_, payload)| payload)
+        .collect();
+        report.add(FlowKind::Webhook, &hook.url, payloads, None, false);
+    }
+
+    if wants_reranker {
+        local_repos.push(RERANKER_REPO);
+    }
+    local_repos.sort();
+    local_repos.dedup();
+    for repo in local_repos {
+        if !is_cached(models_dir, repo) {
+            report.add(FlowKind::ModelDownload, &format!("{}/{}", HUGGING_FACE, repo), vec![Payload::ModelName], None, false);
+        }
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{ContainerRole, Webhook};
+    use crate::indexer::embedding_provider::RemoteProviderConfig;
+
+    fn remote(endpoint: &str, api_key: Option<&str>) -> EmbeddingProviderConfig {
+        EmbeddingProviderConfig::Remote(RemoteProviderConfig {
+            endpoint: endpoint.to_string(),
+            api_key: api_key.map(str::to_string),
+            model: "text-embedding-3-small".to_string(),
+            dimensions: 1536,
+        })
+    }
+
+    #[test]
+    fn test_local_only_sends_nothing_once_downloaded() {
+        let dir = std::env::temp_dir().join(format!("rememex_privacy_{}", std::process::id()));
+        let mut config = Config::default();
+        config.use_reranker = false;
+        let report = build(&config, &dir);
+        assert_eq!(report.flows.len(), 1);
+        assert_eq!(report.flows[0].kind, FlowKind::ModelDownload);
+        assert_eq!(report.flows[0].payloads, vec![Payload::ModelName]);
+
+        let repo = model_repo(&get_embedding_model(&crate::config::get_local_model_name(&config))).unwrap();
+        let snapshot = dir.join(format!("models--{}", repo.replace('/', "--"))).join("snapshots").join("abc");
+        std::fs::create_dir_all(snapshot).unwrap();
+        assert!(build(&config, &dir).flows.is_empty());
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_remote_flows() {
+        let mut config = Config::default();
+        config.use_reranker = false;
+        config.embedding_provider = remote("https://api.example.com/v1/embeddings", Some("sk-1"));
+        let mut team = config.containers["Default"].clone();
+        team.role = ContainerRole::Reader;
+        team.db_uri = Some("s3://bucket/index".to_string());
+        team.storage_options = [("aws_secret_access_key".to_string(), "env:SECRET".to_string())].into();
+        config.containers.insert("Team".to_string(), team);
+        config.webhooks = vec![Webhook { url: "https://hooks.example.com".to_string(), events: vec![WebhookEvent::FilesChanged] }];
+        config.indexing.summaries.enabled = true;
+        config.indexing.summaries.endpoint = "https://llm.example.com/v1/chat/completions".to_string();
+
+        let report = build(&config, Path::new("/nonexistent"));
+        let embeddings: Vec<&OutboundFlow> = report.flows.iter().filter(|f| f.kind == FlowKind::Embeddings).collect();
+        assert_eq!(embeddings.len(), 2);
+        assert_eq!(embeddings[0].containers, vec!["Default".to_string()]);
+        assert!(embeddings[0].payloads.contains(&Payload::ChunkText));
+        assert!(embeddings[0].credentials);
+        assert_eq!(embeddings[1].containers, vec!["Team".to_string()]);
+        assert_eq!(embeddings[1].payloads, vec![Payload::Queries]);
+
+        let store = report.flows.iter().find(|f| f.kind == FlowKind::ObjectStore).unwrap();
+        assert_eq!((store.destination.as_str(), store.credentials), ("s3://bucket/index", true));
+        // A reader never indexes, so only the writer sends files to be summarized.
+        let summaries = report.flows.iter().find(|f| f.kind == FlowKind::Summaries).unwrap();
+        assert_eq!(summaries.containers, vec!["Default".to_string()]);
+        assert_eq!((summaries.payloads.as_slice(), summaries.credentials), ([Payload::ChunkText].as_slice(), false));
+        let hook = report.flows.iter().find(|f| f.kind == FlowKind::Webhook).unwrap();
+        assert_eq!(hook.payloads, vec![Payload::ChangedPaths]);
+        assert!(!report.flows.iter().any(|f| f.kind == FlowKind::ModelDownload));
+    }
+}
@@ -0,0 +1,93 @@
+//! Containers on other machines (`remote_containers`), searched through the
+//! gRPC service of the rememex there (`rememex-mcp --grpc`) while the local
+//! index is, e.g. the desktop at home from a laptop.
+
+use std::time::{Duration, Instant};
+
+use tonic::metadata::{Ascii, MetadataValue};
+
+use crate::config::RemoteContainer;
+
+/// Code generated from `proto/rememex.proto` by build.rs.
+pub mod proto {
+    tonic::include_proto!("rememex.v1");
+}
+
+/// How long a search waits on a remote before leaving it out.
+const TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Where a search result came from when it wasn't this machine.
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct RemoteSource {
+    pub name: String,
+    pub container: String,
+    /// The whole round trip, connecting included.
+    pub latency_ms: u64,
+}
+
+/// Searches `remote` for `query`, best first, scored 0-100 like local
+/// results.
+pub async fn search(
+    remote: &RemoteContainer,
+    query: &str,
+    top_k: usize,
+    file_extensions: Vec<String>,
+) -> Result<(RemoteSource, Vec<proto::SearchResult>), String> {
+    let token = crate::config::resolve_secret(&remote.name, &remote.token)?;
+    let authorization: Option<MetadataValue<Ascii>> = if token.is_empty() {
+        None
+    } else {
+        Some(format!("Bearer {}", token).parse().map_err(|_| format!("The token for {} has characters a header can't carry", remote.name))?)
+    };
+    let started = Instant::now();
+    let channel = tonic::transport::Endpoint::from_shared(remote.url.clone())
+        .map_err(|e| format!("{} isn't an address for {}: {}", remote.url, remote.name, e))?
+        .connect_timeout(TIMEOUT)
+        .timeout(TIMEOUT)
+        .connect()
+        .await
+        .map_err(|e| format!("{} can't be reached at {}: {}", remote.name, remote.url, e))?;
+    let mut client = proto::rememex_service_client::RememexServiceClient::with_interceptor(channel, move |mut request: tonic::Request<()>| {
+        if let Some(authorization) = &authorization {
+            request.metadata_mut().insert("authorization", authorization.clone());
+        }
+        Ok(request)
+    });
+    let response = client
+        .search(proto::SearchRequest {
+            query: query.to_string(),
+            container: remote.container.clone(),
+            top_k: top_k as u32,
+            path_prefix: String::new(),
+            file_extensions,
+        })
+        .await
+        .map_err(|e| format!("{} answered: {}", remote.name, e.message()))?
+        .into_inner();
+    let source = RemoteSource {
+        name: remote.name.clone(),
+        container: response.container,
+        latency_ms: started.elapsed().as_millis() as u64,
+    };
+    Ok((source, response.results))
+}
+
+/// Whether `authorization` metadata carries `token`, for the server side.
+pub fn authorized(authorization: Option<&str>, token: &str) -> bool {
+    authorization
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .is_some_and(|given| given.len() == token.len() && given.bytes().zip(token.bytes()).fold(0, |acc, (a, b)| acc | (a ^ b)) == 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_authorized() {
+        assert!(authorized(Some("Bearer s3cret"), "s3cret"));
+        assert!(!authorized(Some("Bearer s3cre"), "s3cret"));
+        assert!(!authorized(Some("s3cret"), "s3cret"));
+        assert!(!authorized(None, "s3cret"));
+    }
+}
@@ -0,0 +1,143 @@
+use std::path::{Path, PathBuf};
+use std::sync::{LazyLock, Mutex};
+use std::time::Duration;
+
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+
+/// Written into a shared index directory by the machine that writes to it.
+/// Advisory only: LanceDB doesn't look at it, every rememex does.
+const LOCK_FILE: &str = ".rememex-writer.lock";
+/// A lock nobody refreshed for this long belongs to a machine that went
+/// away without releasing it.
+const LOCK_STALE_SECS: u64 = 600;
+const HEARTBEAT: Duration = Duration::from_secs(60);
+
+#[derive(Serialize, Deserialize)]
+struct WriterLock {
+    host: String,
+    updated_at: u64,
+}
+
+static HELD: LazyLock<Mutex<Option<PathBuf>>> = LazyLock::new(|| Mutex::new(None));
+static BLOCKED_BY: LazyLock<Mutex<Option<String>>> = LazyLock::new(|| Mutex::new(None));
+
+fn now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+pub fn host_name() -> String {
+    std::env::var("COMPUTERNAME")
+        .or_else(|_| std::env::var("HOSTNAME"))
+        .ok()
+        .or_else(|| std::fs::read_to_string("/etc/hostname").ok())
+        .map(|h| h.trim().to_string())
+        .filter(|h| !h.is_empty())
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+/// Object-store URIs have nowhere to put a lock file, so only filesystem
+/// paths (local or UNC) get one.
+fn lock_file(uri: &str) -> Option<PathBuf> {
+    (!uri.contains("://")).then(|| Path::new(uri).join(LOCK_FILE))
+}
+
+fn read_lock(path: &Path) -> Option<WriterLock> {
+    serde_json::from_str(&std::fs::read_to_string(path).ok()?).ok()
+}
+
+fn write_lock(path: &Path) -> std::io::Result<()> {
+    let lock = WriterLock { host: host_name(), updated_at: now() };
+    std::fs::write(path, serde_json::to_string(&lock).unwrap_or_default())
+}
+
+/// Another machine that wrote to the index at `uri` recently, if any.
+pub fn other_writer(uri: &str) -> Option<String> {
+    let lock = read_lock(&lock_file(uri)?)?;
+    let fresh = now().saturating_sub(lock.updated_at) < LOCK_STALE_SECS;
+    (fresh && lock.host != host_name()).then_some(lock.host)
+}
+
+/// Takes the writer lock for the shared index at `uri`, dropping whichever
+/// one was held before. Fails with the other machine's name while it still
+/// holds the lock; the container is then searchable but not writable here.
+pub fn acquire(uri: &str) -> Result<(), String> {
+    release();
+    let Some(path) = lock_file(uri) else {
+        return Ok(());
+    };
+    if let Some(host) = other_writer(uri) {
+        warn!("Shared index {} is being written by {}, staying read-only", uri, host);
+        *BLOCKED_BY.lock().unwrap() = Some(host.clone());
+        return Err(host);
+    }
+    std::fs::create_dir_all(path.parent().unwrap_or(Path::new(uri))).ok();
+    write_lock(&path).map_err(|e| e.to_string())?;
+    info!("Took writer lock for shared index {}", uri);
+    *HELD.lock().unwrap() = Some(path);
+    Ok(())
+}
+
+/// Gives up the lock this machine holds, if any.
+pub fn release() {
+    *BLOCKED_BY.lock().unwrap() = None;
+    let Some(path) = HELD.lock().unwrap().take() else {
+        return;
+    };
+    if read_lock(&path).is_some_and(|lock| lock.host == host_name()) {
+        std::fs::remove_file(&path).ok();
+        info!("Released writer lock {:?}", path);
+    }
+}
+
+/// The machine holding the active container's shared index, when it isn't
+/// this one.
+pub fn blocked_by() -> Option<String> {
+    BLOCKED_BY.lock().unwrap().clone()
+}
+
+/// Keeps the held lock fresh so other machines don't take it as stale.
+pub fn spawn_heartbeat() {
+    tauri::async_runtime::spawn(async {
+        let mut interval = tokio::time::interval(HEARTBEAT);
+        loop {
+            interval.tick().await;
+            let held = HELD.lock().unwrap().clone();
+            if let Some(path) = held {
+                if let Err(e) = write_lock(&path) {
+                    warn!("Refreshing writer lock {:?} failed: {}", path, e);
+                }
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_other_writer() {
+        let dir = std::env::temp_dir().join(format!("rememex_shared_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let uri = dir.to_string_lossy().to_string();
+        let write = |host: &str, updated_at: u64| {
+            let lock = WriterLock { host: host.to_string(), updated_at };
+            std::fs::write(dir.join(LOCK_FILE), serde_json::to_string(&lock).unwrap()).unwrap();
+        };
+
+        assert_eq!(other_writer(&uri), None);
+        write("build-server", now());
+        assert_eq!(other_writer(&uri).as_deref(), Some("build-server"));
+        write("build-server", now() - LOCK_STALE_SECS - 1);
+        assert_eq!(other_writer(&uri), None);
+        write(&host_name(), now());
+        assert_eq!(other_writer(&uri), None);
+        assert_eq!(other_writer("s3://bucket/index"), None);
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -1,21 +1,45 @@
-use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
 
+use serde::{Deserialize, Serialize};
+
+use crate::config::ContainerRole;
+use crate::indexer::completion::CompletionIndex;
 use crate::indexer::embedding_provider::EmbeddingProvider;
+use crate::indexer::spelling::TermDictionary;
 
 pub struct DbState {
-    pub db: lancedb::Connection,
+    pub db: Option<lancedb::Connection>,
+    /// The app's own database, for containers without a `db_uri`.
     pub path: std::path::PathBuf,
+    /// What `db` is connected to: `path` or the active container's `db_uri`.
+    pub uri: String,
+    pub init_error: Option<String>,
+}
+
+impl DbState {
+    pub fn connection(&self) -> Result<lancedb::Connection, String> {
+        if let Some(err) = &self.init_error {
+            return Err(format!("Database connection failed: {}", err));
+        }
+        self.db
+            .clone()
+            .ok_or_else(|| "Database is connecting... Please wait a moment.".to_string())
+    }
 }
 
 pub struct ModelState {
     pub model: Option<fastembed::TextEmbedding>,
     pub init_error: Option<String>,
     pub cached_dim: Option<usize>,
+    pub last_used: std::time::Instant,
 }
 
 pub struct RerankerState {
     pub reranker: Option<fastembed::TextRerank>,
     pub init_error: Option<String>,
+    pub last_used: std::time::Instant,
+    pub unloaded: bool,
 }
 
 pub struct ProviderState {
@@ -23,18 +47,119 @@ pub struct ProviderState {
     pub init_error: Option<String>,
 }
 
+/// Did-you-mean dictionaries per table, tagged with the table version they
+/// were built from so any index change rebuilds them on next use.
+#[derive(Default)]
+pub struct SpellState {
+    pub dictionaries: HashMap<String, (u64, Arc<TermDictionary>)>,
+}
+
+/// Typeahead indexes per table, rebuilt the same way as [`SpellState`].
+#[derive(Default)]
+pub struct CompletionState {
+    pub indexes: HashMap<String, (u64, Arc<CompletionIndex>)>,
+}
+
+/// Index and annotation table versions a search ran against. Any write to
+/// either bumps it, which is what invalidates cached results.
+pub type IndexVersion = (u64, Option<u64>);
+
+const RESULT_CACHE_ENTRIES: usize = 64;
+
+/// Final results of recent searches, so going back to an earlier query is
+/// instant. Keys cover the table, the normalized query and every setting
+/// that shapes results; entries only count while the index is unchanged.
+#[derive(Default)]
+pub struct ResultCache {
+    entries: HashMap<String, (IndexVersion, Vec<SearchResult>)>,
+    order: VecDeque<String>,
+}
+
+impl ResultCache {
+    pub fn get(&self, key: &str, version: IndexVersion) -> Option<Vec<SearchResult>> {
+        self.entries.get(key).filter(|(v, _)| *v == version).map(|(_, r)| r.clone())
+    }
+
+    pub fn insert(&mut self, key: String, version: IndexVersion, results: Vec<SearchResult>) {
+        if self.entries.insert(key.clone(), (version, results)).is_none() {
+            self.order.push_back(key);
+        }
+        while self.order.len() > RESULT_CACHE_ENTRIES {
+            if let Some(oldest) = self.order.pop_front() {
+                self.entries.remove(&oldest);
+            }
+        }
+    }
+}
+
+/// File in the app data directory the watcher mirrors its status to.
+pub const WATCHER_STATUS_FILE: &str = "watcher_status.json";
+
+/// What the file watcher is doing, for the settings panel and, through the
+/// status file the app keeps next to the config, for `rememex_watch_status`.
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct WatcherStatus {
+    pub running: bool,
+    pub paused: bool,
+    /// Low-power mode is holding reindexes back until power or CPU frees up.
+    pub low_power: bool,
+    pub roots: Vec<String>,
+    /// Debounced file system events received since the watcher started.
+    pub events: u64,
+    pub files_indexed: u64,
+    pub files_removed: u64,
+    pub errors: u64,
+    /// Unix seconds of the last event, None until one arrives.
+    pub last_event_at: Option<i64>,
+    /// Files waiting behind the reindex currently running. Stays high when
+    /// changes arrive faster than they can be indexed.
+    pub queued_files: usize,
+    /// Unix seconds this status was last written; the watcher refreshes it
+    /// every minute while running.
+    pub updated_at: i64,
+}
+
 #[derive(Serialize, Clone)]
 pub struct SearchResult {
     pub path: String,
     pub snippet: String,
+    /// The part of `snippet` shown in the list, cut around the best match.
+    pub excerpt: String,
+    pub context: String,
+    /// PDF page the snippet came from, for opening the file at that page.
+    pub page: Option<u32>,
     pub score: f32,
+    pub pinned: bool,
+    /// The file is gone from disk; the match came from its tombstone.
+    pub deleted: bool,
+    /// Found by desktop search outside the container's folders; only its
+    /// name matched.
+    pub unindexed: bool,
+    /// Found in one of the `remote_containers`; the path is on that machine.
+    pub remote: Option<crate::remote::RemoteSource>,
 }
 
-#[derive(Serialize, Clone)]
+#[derive(Serialize, Clone, Default)]
 pub struct IndexingProgress {
     pub current: usize,
     pub total: usize,
     pub path: String,
+    /// Text read from the files written so far.
+    pub bytes: u64,
+    /// Chunks sent to the embedding model so far.
+    pub chunks: usize,
+    /// Chunks the job goes through, embedded or already stored.
+    pub chunks_total: usize,
+    pub chunks_per_sec: f32,
+    /// Seconds left at the current rate, once there's enough to go on.
+    pub eta_secs: Option<u64>,
+}
+
+impl IndexingProgress {
+    /// A step with no embedding figures to report.
+    pub fn new(current: usize, total: usize, path: impl Into<String>) -> Self {
+        Self { current, total, path: path.into(), ..Default::default() }
+    }
 }
 
 #[derive(Serialize, Clone)]
@@ -43,4 +168,7 @@ pub struct ContainerListItem {
     pub description: String,
     pub indexed_paths: Vec<String>,
     pub provider_label: String,
+    pub db_uri: Option<String>,
+    pub role: ContainerRole,
+    pub locked: bool,
 }
@@ -0,0 +1,507 @@
+//! Container definitions and annotations shared between devices through a
+//! folder the user keeps in sync (Dropbox, Syncthing, a network share).
+//!
+//! Every device writes only its own `<device>.json` there, so sync tools
+//! never have two edits of one file to reconcile.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::sync::Mutex;
+
+use crate::config::{app_data_dir, get_table_name, ConfigState, ContainerInfo, EmbeddingProviderConfig};
+use crate::indexer;
+use crate::indexer::annotations::{self, Annotation};
+use crate::state::{DbState, ProviderState};
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct SyncConfig {
+    #[serde(default)]
+    pub folder: String,
+}
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(5 * 60);
+const STATE_FILE: &str = "sync_state.json";
+/// Bumped when a device file changes in a way an older app can't read.
+const FORMAT: u32 = 1;
+
+static RUNNING: Mutex<()> = Mutex::const_new(());
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+struct Entry {
+    updated_at: i64,
+    #[serde(default)]
+    definition: Option<Value>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+struct DeviceFile {
+    format: u32,
+    device: String,
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    containers: BTreeMap<String, Entry>,
+    #[serde(default)]
+    annotations: BTreeMap<String, Vec<Annotation>>,
+    #[serde(default)]
+    deleted_annotations: BTreeMap<String, BTreeSet<String>>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+struct SyncState {
+    device: String,
+    #[serde(default)]
+    folder: String,
+    #[serde(default)]
+    containers: BTreeMap<String, Entry>,
+    #[serde(default)]
+    known_annotations: BTreeMap<String, BTreeSet<String>>,
+    #[serde(default)]
+    deleted_annotations: BTreeMap<String, BTreeSet<String>>,
+    #[serde(default)]
+    pending: BTreeMap<String, Vec<String>>,
+}
+
+#[derive(Serialize, Clone, Debug, Default)]
+pub struct SyncReport {
+    pub devices: usize,
+    pub containers_updated: usize,
+    pub containers_removed: usize,
+    pub annotations_added: usize,
+    pub annotations_removed: usize,
+}
+
+impl SyncReport {
+    fn changed(&self) -> bool {
+        self.containers_updated + self.containers_removed + self.annotations_added + self.annotations_removed > 0
+    }
+}
+
+fn state_path() -> PathBuf {
+    app_data_dir().join(STATE_FILE)
+}
+
+fn load_state() -> SyncState {
+    let mut state: SyncState = std::fs::read_to_string(state_path())
+        .ok()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default();
+    if state.device.is_empty() {
+        let seed = format!("{}{:?}", device_name(), std::time::SystemTime::now());
+        state.device = blake3::hash(seed.as_bytes()).to_hex()[..16].to_string();
+    }
+    state
+}
+
+fn store_state(state: &SyncState) {
+    let path = state_path();
+    match serde_json::to_string_pretty(state) {
+        Ok(json) => {
+            if let Err(e) = std::fs::write(&path, json) {
+                warn!("Failed to write {:?}: {}", path, e);
+            }
+        }
+        Err(e) => warn!("Failed to serialize sync state: {}", e),
+    }
+}
+
+fn device_name() -> String {
+    std::env::var("COMPUTERNAME").or_else(|_| std::env::var("HOSTNAME")).unwrap_or_default()
+}
+
+fn fold_home(path: &str, home: Option<&Path>) -> String {
+    let Some(rest) = home.and_then(|home| Path::new(path).strip_prefix(home).ok()) else {
+        return path.to_string();
+    };
+    let parts: Vec<_> = rest.components().map(|c| c.as_os_str().to_string_lossy()).collect();
+    format!("~/{}", parts.join("/")).trim_end_matches('/').to_string()
+}
+
+fn expand_home(path: &str, home: Option<&Path>) -> String {
+    let (Some(home), Some(rest)) = (home, path.strip_prefix('~')) else {
+        return path.to_string();
+    };
+    rest.split('/').filter(|part| !part.is_empty()).fold(home.to_path_buf(), |dir, part| dir.join(part)).to_string_lossy().into_owned()
+}
+
+fn shareable(info: &ContainerInfo, home: Option<&Path>) -> Option<Value> {
+    let mut info = info.clone();
+    if let Some(EmbeddingProviderConfig::Remote(remote)) = &mut info.embedding_provider {
+        remote.api_key = None;
+    }
+    info.storage_options.retain(|_, value| value.starts_with("env:") || value.starts_with("keychain:"));
+    for paths in [&mut info.indexed_paths, &mut info.excluded_paths, &mut info.pinned_paths] {
+        for path in paths.iter_mut() {
+            *path = fold_home(path, home);
+        }
+    }
+    serde_json::to_value(info).ok()
+}
+
+fn restore(definition: &Value, local: Option<&ContainerInfo>, home: Option<&Path>) -> Option<ContainerInfo> {
+    let mut info: ContainerInfo = serde_json::from_value(definition.clone()).ok()?;
+    for paths in [&mut info.indexed_paths, &mut info.excluded_paths, &mut info.pinned_paths] {
+        for path in paths.iter_mut() {
+            *path = expand_home(path, home);
+        }
+    }
+    if let Some(local) = local {
+        if let (Some(EmbeddingProviderConfig::Remote(remote)), Some(EmbeddingProviderConfig::Remote(mine))) =
+            (&mut info.embedding_provider, &local.embedding_provider)
+        {
+            remote.api_key = mine.api_key.clone();
+        }
+        for (key, value) in &local.storage_options {
+            info.storage_options.entry(key.clone()).or_insert_with(|| value.clone());
+        }
+    }
+    Some(info)
+}
+
+fn note_local_changes(tracked: &mut BTreeMap<String, Entry>, local: &BTreeMap<String, Value>, now: i64) {
+    for (name, definition) in local {
+        if tracked.get(name).and_then(|entry| entry.definition.as_ref()) != Some(definition) {
+            tracked.insert(name.clone(), Entry { updated_at: now, definition: Some(definition.clone()) });
+        }
+    }
+    for (name, entry) in tracked.iter_mut() {
+        if entry.definition.is_some() && !local.contains_key(name) {
+            *entry = Entry { updated_at: now, definition: None };
+        }
+    }
+}
+
+fn newer_elsewhere<'a>(
+    device: &str,
+    tracked: &BTreeMap<String, Entry>,
+    others: &'a [DeviceFile],
+) -> BTreeMap<String, (&'a str, &'a Entry)> {
+    let mut newer: BTreeMap<String, (&str, &Entry)> = BTreeMap::new();
+    for other in others {
+        for (name, entry) in &other.containers {
+            let best = newer
+                .get(name)
+                .map(|&(by, best)| (best.updated_at, by))
+                .or_else(|| tracked.get(name).map(|mine| (mine.updated_at, device)));
+            if best.is_none_or(|best| (entry.updated_at, other.device.as_str()) > best) {
+                newer.insert(name.clone(), (other.device.as_str(), entry));
+            }
+        }
+    }
+    newer.retain(|name, (_, entry)| tracked.get(name) != Some(*entry));
+    newer
+}
+
+fn annotation_changes<'a>(
+    local: &[Annotation],
+    remote: impl IntoIterator<Item = &'a Annotation>,
+    deleted: &BTreeSet<String>,
+) -> (Vec<Annotation>, Vec<String>) {
+    let mut seen: BTreeSet<&str> = local.iter().map(|a| a.id.as_str()).collect();
+    let added = remote
+        .into_iter()
+        .filter(|a| !deleted.contains(&a.id) && seen.insert(a.id.as_str()))
+        .cloned()
+        .collect();
+    let removed = local.iter().filter(|a| deleted.contains(&a.id)).map(|a| a.id.clone()).collect();
+    (added, removed)
+}
+
+fn read_devices(folder: &Path, device: &str) -> Vec<DeviceFile> {
+    let Ok(entries) = std::fs::read_dir(folder) else {
+        return vec![];
+    };
+    entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .filter_map(|path| {
+            let file: DeviceFile = serde_json::from_str(&std::fs::read_to_string(&path).ok()?).ok()?;
+            if file.format > FORMAT {
+                warn!("Sync: {:?} is from a newer rememex, skipped", path);
+                return None;
+            }
+            (!file.device.is_empty() && file.device != device).then_some(file)
+        })
+        .collect()
+}
+
+/// Through a temporary file so a sync tool never picks up half of it.
+fn write_device(folder: &Path, file: &DeviceFile) -> Result<()> {
+    let path = folder.join(format!("{}.json", file.device));
+    let json = serde_json::to_string_pretty(file)?;
+    if std::fs::read_to_string(&path).is_ok_and(|old| old == json) {
+        return Ok(());
+    }
+    let tmp = folder.join(format!("{}.json.tmp", file.device));
+    std::fs::write(&tmp, json)?;
+    std::fs::rename(&tmp, &path)?;
+    Ok(())
+}
+
+pub async fn run(app: &AppHandle) -> Result<SyncReport, String> {
+    let _running = RUNNING.lock().await;
+    let config_state = app.state::<ConfigState>();
+    let config = config_state.config.lock().await.clone();
+    let folder = PathBuf::from(config.sync.folder.trim());
+    if folder.as_os_str().is_empty() {
+        return Err("No sync folder set".to_string());
+    }
+    std::fs::create_dir_all(&folder).map_err(|e| format!("Can't use {}: {}", folder.display(), e))?;
+    let home = app.path().home_dir().ok();
+    let home = home.as_deref();
+
+    let mut state = load_state();
+    if state.folder != folder.to_string_lossy() {
+        state = SyncState { device: state.device, folder: folder.to_string_lossy().into_owned(), ..Default::default() };
+    }
+    let others = read_devices(&folder, &state.device);
+    let mut local: BTreeMap<String, Value> =
+        config.containers.iter().filter_map(|(name, info)| Some((name.clone(), shareable(info, home)?))).collect();
+    // A device joining takes the containers the others already have.
+    if state.containers.is_empty() {
+        local.retain(|name, _| !others.iter().any(|other| other.containers.contains_key(name)));
+    }
+    note_local_changes(&mut state.containers, &local, chrono::Utc::now().timestamp());
+
+    let mut report = SyncReport { devices: others.len(), ..Default::default() };
+    let mut removed = Vec::new();
+    let newer = newer_elsewhere(&state.device, &state.containers, &others);
+    if !newer.is_empty() {
+        let mut config = config_state.config.lock().await;
+        for (name, (device, entry)) in newer {
+            match &entry.definition {
+                // The active container goes once the user has moved off it.
+                None if name == "Default" || name == config.active_container => continue,
+                None => {
+                    if let Some(info) = config.containers.remove(&name) {
+                        info!("Sync: '{}' deleted on {}", name, device);
+                        removed.push((name.clone(), info));
+                    }
+                    state.pending.remove(&name);
+                }
+                Some(definition) => {
+                    let Some(info) = restore(definition, config.containers.get(&name), home) else {
+                        warn!("Sync: couldn't read '{}' from {}", name, device);
+                        continue;
+                    };
+                    let before = config.containers.get(&name).map(|info| info.indexed_paths.clone()).unwrap_or_default();
+                    let added: Vec<String> = info
+                        .indexed_paths
+                        .iter()
+                        .filter(|path| !before.contains(path) && Path::new(path).is_dir())
+                        .cloned()
+                        .collect();
+                    if !added.is_empty() {
+                        state.pending.entry(name.clone()).or_default().extend(added);
+                    }
+                    info!("Sync: '{}' updated from {}", name, device);
+                    config.containers.insert(name.clone(), info);
+                    report.containers_updated += 1;
+                }
+            }
+            state.containers.insert(name, entry.clone());
+        }
+        report.containers_removed = removed.len();
+    }
+    if report.containers_updated + report.containers_removed > 0 {
+        config_state.save().await?;
+        crate::tray::refresh(app).await;
+    }
+
+    let (db_path, active) = {
+        let db_path = app.state::<Arc<Mutex<DbState>>>().lock().await.path.clone();
+        (db_path, config_state.config.lock().await.active_container.clone())
+    };
+    let db = indexer::db::connect(&db_path.to_string_lossy()).await.map_err(|e| e.to_string())?;
+    for (name, info) in &removed {
+        crate::os_auth::forget(name);
+        if info.db_uri.is_none() {
+            crate::commands::drop_container_tables(&db, name).await;
+        }
+    }
+
+    let containers = config_state.config.lock().await.containers.clone();
+    let mut own_annotations = BTreeMap::new();
+    for (name, info) in &containers {
+        if info.db_uri.is_some() {
+            continue;
+        }
+        let table_name = get_table_name(name);
+        let current = annotations::get_annotations(&db, &table_name, None).await.unwrap_or_default();
+        let ids: BTreeSet<String> = current.iter().map(|a| a.id.clone()).collect();
+        let deleted_here = state.deleted_annotations.entry(name.clone()).or_default();
+        if let Some(known) = state.known_annotations.get(name) {
+            deleted_here.extend(known.difference(&ids).cloned());
+        }
+        let mut deleted = deleted_here.clone();
+        for other in &others {
+            deleted.extend(other.deleted_annotations.get(name).into_iter().flatten().cloned());
+        }
+        let remote = others.iter().flat_map(|other| other.annotations.get(name).into_iter().flatten());
+        let (added, dropped) = annotation_changes(&current, remote, &deleted);
+
+        let mut kept: Vec<Annotation> = current.into_iter().filter(|a| !dropped.contains(&a.id)).collect();
+        for id in &dropped {
+            match annotations::delete_annotation(&db, &table_name, id).await {
+                Ok(()) => report.annotations_removed += 1,
+                Err(e) => warn!("Sync: couldn't delete annotation {}: {}", id, e),
+            }
+        }
+        if *name == active {
+            let provider_state = app.state::<Arc<Mutex<ProviderState>>>();
+            for annotation in added {
+                match annotations::insert_annotation(&db, &table_name, &provider_state, &annotation).await {
+                    Ok(()) => {
+                        kept.push(annotation);
+                        report.annotations_added += 1;
+                    }
+                    Err(e) => warn!("Sync: couldn't add annotation {}: {}", annotation.id, e),
+                }
+            }
+        }
+        state.known_annotations.insert(name.clone(), kept.iter().map(|a| a.id.clone()).collect());
+        own_annotations.insert(name.clone(), kept);
+    }
+
+    let file = DeviceFile {
+        format: FORMAT,
+        device: state.device.clone(),
+        name: device_name(),
+        containers: state.containers.clone(),
+        annotations: own_annotations,
+        deleted_annotations: state.deleted_annotations.clone(),
+    };
+    write_device(&folder, &file).map_err(|e| format!("Can't write to {}: {}", folder.display(), e))?;
+    let pending = state.pending.get(&active).cloned().unwrap_or_default();
+    store_state(&state);
+
+    if !pending.is_empty() {
+        index_pending(app.clone(), active, pending);
+    }
+    if report.changed() {
+        info!("Sync: {:?}", report);
+        let _ = app.emit("sync-applied", report.clone());
+    }
+    Ok(report)
+}
+
+fn index_pending(app: AppHandle, container: String, paths: Vec<String>) {
+    tauri::async_runtime::spawn(async move {
+        info!("Sync: indexing {} new folders of '{}'", paths.len(), container);
+        match crate::commands::index_synced_folders(&app, &paths).await {
+            Ok(_) => {
+                let mut state = load_state();
+                if let Some(pending) = state.pending.get_mut(&container) {
+                    pending.retain(|path| !paths.contains(path));
+                    if pending.is_empty() {
+                        state.pending.remove(&container);
+                    }
+                }
+                store_state(&state);
+            }
+            Err(e) => warn!("Sync: indexing '{}' failed, tried again on the next sync: {}", container, e),
+        }
+    });
+}
+
+pub fn spawn_scheduler(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(CHECK_INTERVAL);
+        loop {
+            interval.tick().await;
+
+            let config = app.state::<ConfigState>().config.lock().await.clone();
+            if config.sync.folder.trim().is_empty() || config.is_read_only() {
+                continue;
+            }
+            debug!("Syncing with {}", config.sync.folder);
+            if let Err(e) = run(&app).await {
+                warn!("Sync failed: {}", e);
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(updated_at: i64, note: Option<&str>) -> Entry {
+        Entry { updated_at, definition: note.map(|note| serde_json::json!({ "description": note })) }
+    }
+
+    fn device(id: &str, containers: &[(&str, Entry)]) -> DeviceFile {
+        DeviceFile {
+            format: FORMAT,
+            device: id.to_string(),
+            containers: containers.iter().map(|(name, entry)| (name.to_string(), entry.clone())).collect(),
+            ..Default::default()
+        }
+    }
+
+    fn annotation(id: &str) -> Annotation {
+        Annotation { id: id.to_string(), path: "/a.md".to_string(), note: id.to_string(), source: "user".to_string(), created_at: 0 }
+    }
+
+    #[test]
+    fn test_note_local_changes() {
+        let mut tracked = BTreeMap::from([("Work".to_string(), entry(5, Some("work"))), ("Old".to_string(), entry(5, Some("old")))]);
+        let local = BTreeMap::from([
+            ("Work".to_string(), serde_json::json!({ "description": "work" })),
+            ("New".to_string(), serde_json::json!({ "description": "new" })),
+        ]);
+        note_local_changes(&mut tracked, &local, 9);
+        assert_eq!(tracked["Work"], entry(5, Some("work")));
+        assert_eq!(tracked["New"], entry(9, Some("new")));
+        assert_eq!(tracked["Old"], entry(9, None));
+    }
+
+    #[test]
+    fn test_newer_elsewhere() {
+        let tracked = BTreeMap::from([
+            ("Work".to_string(), entry(10, Some("mine"))),
+            ("Notes".to_string(), entry(10, Some("mine"))),
+            ("Tie".to_string(), entry(10, Some("mine"))),
+        ]);
+        let others = [
+            device("b", &[("Work", entry(20, Some("b"))), ("Notes", entry(5, Some("b"))), ("Tie", entry(10, Some("b")))]),
+            device("c", &[("Work", entry(30, None)), ("Extra", entry(1, Some("c")))]),
+        ];
+        let newer = newer_elsewhere("a", &tracked, &others);
+        let winners: Vec<_> = newer.iter().map(|(name, (by, entry))| (name.as_str(), *by, entry.updated_at)).collect();
+        assert_eq!(winners, vec![("Extra", "c", 1), ("Tie", "b", 10), ("Work", "c", 30)]);
+        let mut tracked = tracked;
+        tracked.extend(newer.into_iter().map(|(name, (_, entry))| (name, entry.clone())));
+        assert!(newer_elsewhere("a", &tracked, &others).is_empty());
+    }
+
+    #[test]
+    fn test_annotation_changes() {
+        let local = [annotation("1"), annotation("2")];
+        let remote = [annotation("2"), annotation("3"), annotation("4"), annotation("3")];
+        let deleted = BTreeSet::from(["2".to_string(), "4".to_string()]);
+        let (added, removed) = annotation_changes(&local, &remote, &deleted);
+        assert_eq!(added.iter().map(|a| a.id.as_str()).collect::<Vec<_>>(), vec!["3"]);
+        assert_eq!(removed, vec!["2".to_string()]);
+    }
+
+    #[test]
+    fn test_home_paths() {
+        let home = PathBuf::from("/home/ana");
+        let home = Some(home.as_path());
+        assert_eq!(fold_home("/home/ana/notes/work", home), "~/notes/work");
+        assert_eq!(fold_home("/home/ana", home), "~");
+        assert_eq!(fold_home("/srv/docs", home), "/srv/docs");
+        assert_eq!(expand_home("~/notes/work", Some(Path::new("/Users/ana"))), "/Users/ana/notes/work");
+        assert_eq!(expand_home("/srv/docs", home), "/srv/docs");
+    }
+}
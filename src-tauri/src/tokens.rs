@@ -0,0 +1,116 @@
+//! Token counts for agents budgeting their context: tiktoken's cl100k_base,
+//! the GPT-4 encoding. Other models split text differently, but rarely by
+//! more than a fifth, unlike a characters-per-token guess on code or CJK.
+
+use serde_json::Value;
+use tiktoken_rs::{cl100k_base_singleton, CoreBPE};
+
+pub const TRUNCATED: &str = "[... truncated]";
+
+/// Below this many tokens of room, an item that doesn't fit whole is left
+/// out rather than cut down to a few words.
+const MIN_PARTIAL_TOKENS: usize = 50;
+
+fn bpe() -> &'static CoreBPE {
+    cl100k_base_singleton()
+}
+
+pub fn count(text: &str) -> usize {
+    bpe().encode_ordinary(text).len()
+}
+
+/// The start of `text` that's at most `max_tokens` tokens, ending at a line
+/// break when there's one to end at.
+pub fn truncate(text: &str, max_tokens: usize) -> &str {
+    let tokens = bpe().encode_ordinary(text);
+    if tokens.len() <= max_tokens {
+        return text;
+    }
+    // Tokens can split a character; back off until the prefix decodes.
+    let mut n = max_tokens;
+    let prefix = loop {
+        match bpe().decode(tokens[..n].to_vec()) {
+            Ok(prefix) => break prefix,
+            Err(_) if n > 0 => n -= 1,
+            Err(_) => break String::new(),
+        }
+    };
+    let cut = &text[..prefix.len()];
+    match cut.rfind('\n') {
+        Some(i) if i > 0 => &cut[..i],
+        _ => cut,
+    }
+}
+
+fn pretty_count(items: &[Value]) -> usize {
+    count(&serde_json::to_string_pretty(items).unwrap_or_default())
+}
+
+/// The leading `items` that fit in `max_tokens` as a pretty-printed JSON
+/// array. The first one that doesn't fit gets its `field` cut short and
+/// `"truncated": true` instead, when there's enough room left for that to
+/// be worth reading.
+pub fn fit_items(items: Vec<Value>, field: &str, max_tokens: usize) -> Vec<Value> {
+    let mut kept: Vec<Value> = Vec::new();
+    for mut item in items {
+        kept.push(item.clone());
+        if pretty_count(&kept) <= max_tokens {
+            continue;
+        }
+        kept.pop();
+
+        let text = item[field].as_str().unwrap_or_default().to_string();
+        item[field] = Value::String(TRUNCATED.to_string());
+        item["truncated"] = Value::Bool(true);
+        kept.push(item.clone());
+        let room = max_tokens.saturating_sub(pretty_count(&kept));
+        kept.pop();
+        if room >= MIN_PARTIAL_TOKENS {
+            // Escaping in JSON can add a little; leave it some slack.
+            let cut = truncate(&text, room - room / 10);
+            item[field] = Value::String(format!("{}\n{}", cut, TRUNCATED));
+            kept.push(item);
+        }
+        break;
+    }
+    kept
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_count_and_truncate() {
+        assert_eq!(count("hello world"), 2);
+        let text: String = (0..100).map(|i| format!("line {} of the file\n", i)).collect();
+        let cut = truncate(&text, 50);
+        assert!(count(cut) <= 50);
+        assert!(cut.ends_with("of the file"));
+        assert!(text.starts_with(cut));
+        assert_eq!(truncate("short", 10), "short");
+
+        let cjk = "日本語の文章です。".repeat(20);
+        let cut = truncate(&cjk, 15);
+        assert!(count(cut) <= 15 && !cut.is_empty());
+    }
+
+    #[test]
+    fn test_fit_items() {
+        let long: String = (0..300).map(|i| format!("line {} of the file\n", i)).collect();
+        let items = vec![
+            json!({ "path": "/r/a.rs", "snippet": "fn a() {}", "score": 90.0 }),
+            json!({ "path": "/r/b.rs", "snippet": long, "score": 80.0 }),
+            json!({ "path": "/r/c.rs", "snippet": "fn c() {}", "score": 70.0 }),
+        ];
+        let fitted = fit_items(items.clone(), "snippet", 300);
+        assert_eq!(fitted.len(), 2);
+        assert!(pretty_count(&fitted) <= 300);
+        assert_eq!(fitted[1]["truncated"], json!(true));
+        assert!(fitted[1]["snippet"].as_str().unwrap().ends_with(TRUNCATED));
+
+        assert_eq!(fit_items(items.clone(), "snippet", 100).len(), 1);
+        assert_eq!(fit_items(items, "snippet", 100_000).len(), 3);
+    }
+}
@@ -0,0 +1,144 @@
+use log::{debug, info, warn};
+use tauri::menu::{CheckMenuItem, IsMenuItem, Menu, MenuEvent, MenuItem, PredefinedMenuItem, Submenu};
+use tauri::{AppHandle, Emitter, Listener, Manager, Runtime};
+
+use crate::config::ConfigState;
+use crate::watcher;
+
+pub const TRAY_ID: &str = "tray";
+const CONTAINER_PREFIX: &str = "container:";
+
+/// Builds the tray menu: show, a containers submenu with the active one checked,
+/// the watcher pause toggle, and quit.
+pub fn build_menu<R: Runtime, M: Manager<R>>(
+    manager: &M,
+    containers: &[String],
+    active: &str,
+) -> tauri::Result<Menu<R>> {
+    let show = MenuItem::with_id(manager, "show", "Show Rememex", true, None::<&str>)?;
+
+    let container_items = containers
+        .iter()
+        .map(|name| {
+            CheckMenuItem::with_id(
+                manager,
+                format!("{}{}", CONTAINER_PREFIX, name),
+                name,
+                true,
+                name == active,
+                None::<&str>,
+            )
+        })
+        .collect::<tauri::Result<Vec<_>>>()?;
+    let container_refs: Vec<&dyn IsMenuItem<R>> =
+        container_items.iter().map(|item| item as &dyn IsMenuItem<R>).collect();
+    let containers_menu = Submenu::with_items(manager, "Containers", true, &container_refs)?;
+
+    let pause = CheckMenuItem::with_id(
+        manager,
+        "pause_watcher",
+        "Pause file watcher",
+        true,
+        watcher::is_paused(),
+        None::<&str>,
+    )?;
+    let separator = PredefinedMenuItem::separator(manager)?;
+    let quit = MenuItem::with_id(manager, "quit", "Quit", true, None::<&str>)?;
+
+    Menu::with_items(manager, &[&show, &containers_menu, &pause, &separator, &quit])
+}
+
+/// Sorted container names plus the active one, in the order the submenu shows them.
+pub fn container_names(config: &crate::config::Config) -> (Vec<String>, String) {
+    let mut names: Vec<String> = config.containers.keys().cloned().collect();
+    names.sort_by_key(|name| name.to_lowercase());
+    (names, config.active_container.clone())
+}
+
+/// Rebuilds the menu so the checked container and pause state match reality.
+pub async fn refresh(app: &AppHandle) {
+    let (names, active) = {
+        let config = app.state::<ConfigState>().config.lock().await;
+        container_names(&config)
+    };
+    let Some(tray) = app.tray_by_id(TRAY_ID) else {
+        return;
+    };
+    match build_menu(app, &names, &active) {
+        Ok(menu) => {
+            if let Err(e) = tray.set_menu(Some(menu)) {
+                warn!("Failed to update tray menu: {}", e);
+            }
+        }
+        Err(e) => warn!("Failed to build tray menu: {}", e),
+    }
+    set_tooltip(app, None);
+}
+
+fn set_tooltip(app: &AppHandle, progress: Option<(u64, u64)>) {
+    let Some(tray) = app.tray_by_id(TRAY_ID) else {
+        return;
+    };
+    let text = match progress {
+        Some((current, total)) if total > 0 => format!(
+            "Rememex: indexing {}/{} ({}%)",
+            current,
+            total,
+            current * 100 / total
+        ),
+        _ if watcher::is_paused() => "Rememex: file watcher paused".to_string(),
+        _ => "Rememex".to_string(),
+    };
+    let _ = tray.set_tooltip(Some(text));
+}
+
+/// Mirrors indexing progress into the tray tooltip so a long job can be checked
+/// without opening the window.
+pub fn track_progress(app: &AppHandle) {
+    let handle = app.clone();
+    app.listen_any("indexing-progress", move |event| {
+        let Ok(payload) = serde_json::from_str::<serde_json::Value>(event.payload()) else {
+            return;
+        };
+        let current = payload["current"].as_u64().unwrap_or(0);
+        let total = payload["total"].as_u64().unwrap_or(0);
+        set_tooltip(&handle, Some((current, total)));
+    });
+    let handle = app.clone();
+    app.listen_any("indexing-complete", move |_| set_tooltip(&handle, None));
+    let handle = app.clone();
+    app.listen_any("indexing-failed", move |_| set_tooltip(&handle, None));
+}
+
+pub fn handle_menu_event(app: &AppHandle, event: MenuEvent, show: fn(&AppHandle)) {
+    let id = event.id().as_ref();
+    match id {
+        "quit" => {
+            crate::shared_index::release();
+            app.exit(0);
+        }
+        "show" => show(app),
+        "pause_watcher" => {
+            let paused = !watcher::is_paused();
+            watcher::set_paused(paused);
+            info!("File watcher {} from tray", if paused { "paused" } else { "resumed" });
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                if paused {
+                    *app.state::<watcher::WatcherState>().lock().await = None;
+                } else {
+                    crate::restart_watcher_when_ready(&app).await;
+                }
+                refresh(&app).await;
+            });
+        }
+        _ => {
+            if let Some(name) = id.strip_prefix(CONTAINER_PREFIX) {
+                debug!("Tray requested container switch to \"{}\"", name);
+                let _ = app.emit("tray-switch-container", name.to_string());
+                let app = app.clone();
+                tauri::async_runtime::spawn(async move { refresh(&app).await });
+            }
+        }
+    }
+}
@@ -1,18 +1,22 @@
 use std::collections::HashSet;
-use std::path::PathBuf;
-use std::sync::Arc;
-use std::time::Duration;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::RecvTimeoutError;
+use std::sync::{Arc, LazyLock, OnceLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
-use log::{info, error, debug};
+use log::{info, error, debug, warn};
 
 use notify_debouncer_full::{new_debouncer, DebounceEventResult, Debouncer, RecommendedCache};
 use notify_debouncer_full::notify::{self, RecursiveMode};
 use tauri::{AppHandle, Emitter};
-use tokio::sync::Mutex;
+use tokio::sync::{Mutex, MutexGuard};
 
-use crate::config::{get_table_name, ConfigState};
+use crate::config::{get_table_name, is_in_ignored_dir, is_path_excluded, Config, ConfigState};
 use crate::indexer;
-use crate::state::{IndexingProgress, ProviderState};
+use crate::power;
+use crate::state::{IndexingProgress, ProviderState, WatcherStatus};
+use crate::webhooks::{self, IndexEvent};
 
 fn build_gitignore(roots: &[String]) -> Option<ignore::gitignore::Gitignore> {
     if roots.is_empty() { return None; }
@@ -40,6 +44,72 @@ pub fn new_state() -> WatcherState {
     Arc::new(Mutex::new(None))
 }
 
+static PAUSED: AtomicBool = AtomicBool::new(false);
+
+/// While paused, `restart` leaves the watcher stopped so nothing is reindexed in
+/// the background. Not persisted; every launch starts unpaused.
+pub fn is_paused() -> bool {
+    PAUSED.load(Ordering::Relaxed)
+}
+
+pub fn set_paused(paused: bool) {
+    PAUSED.store(paused, Ordering::Relaxed);
+}
+
+static STATUS: LazyLock<std::sync::Mutex<WatcherStatus>> = LazyLock::new(Default::default);
+static STATUS_FILE: OnceLock<PathBuf> = OnceLock::new();
+/// How often an idle watcher rewrites the status file, so readers can tell a
+/// quiet watcher from an app that is no longer running.
+const HEARTBEAT: Duration = Duration::from_secs(60);
+/// How often a reindex held back by low-power mode checks whether it can run.
+const LOW_POWER_RECHECK: Duration = Duration::from_secs(30);
+/// Held while the watcher writes, and while a rebuild is swapped in, so no
+/// change lands in the old table after it's been copied over.
+pub(crate) static WRITING: Mutex<()> = Mutex::const_new(());
+
+fn now_unix() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+/// Where to mirror the status for the MCP server, which runs in its own
+/// process and can't ask the watcher directly.
+pub fn set_status_file(path: PathBuf) {
+    let _ = STATUS_FILE.set(path);
+}
+
+pub fn status() -> WatcherStatus {
+    let mut status = STATUS.lock().unwrap_or_else(|e| e.into_inner()).clone();
+    status.paused = is_paused();
+    status.low_power = power::is_low_power();
+    status
+}
+
+fn record(f: impl FnOnce(&mut WatcherStatus)) {
+    f(&mut STATUS.lock().unwrap_or_else(|e| e.into_inner()));
+}
+
+fn publish() {
+    let snapshot = {
+        let mut status = STATUS.lock().unwrap_or_else(|e| e.into_inner());
+        status.paused = is_paused();
+        status.low_power = power::is_low_power();
+        status.updated_at = now_unix();
+        status.clone()
+    };
+    let Some(path) = STATUS_FILE.get() else { return };
+    match serde_json::to_string_pretty(&snapshot) {
+        Ok(json) => {
+            if let Err(e) = std::fs::write(path, json) {
+                debug!("Failed to write watcher status: {}", e);
+            }
+        }
+        Err(e) => debug!("Failed to serialize watcher status: {}", e),
+    }
+}
+
 pub async fn restart(
     watcher_state: &WatcherState,
     config_state: &ConfigState,
@@ -47,6 +117,18 @@ pub async fn restart(
     provider_state: Arc<Mutex<ProviderState>>,
     app: AppHandle,
 ) {
+    let read_only = {
+        let config = config_state.config.lock().await;
+        !config.can_write(&config.active_container) || crate::shared_index::blocked_by().is_some()
+    };
+    if is_paused() || read_only {
+        debug!("File watcher paused or read-only, not restarting");
+        *watcher_state.lock().await = None;
+        record(|s| *s = WatcherStatus::default());
+        publish();
+        return;
+    }
+
     let handle = {
         let config = config_state.config.lock().await;
         let table_name = get_table_name(&config.active_container);
@@ -55,13 +137,18 @@ pub async fn restart(
             .get(&config.active_container)
             .map(|info| info.indexed_paths.clone())
             .unwrap_or_default();
-        let wc = WatcherConfig {
-            use_git_history: config.indexing.use_git_history,
-            chunk_size: config.indexing.chunk_size,
-            chunk_overlap: config.indexing.chunk_overlap,
-        };
+        let wc = WatcherConfig::for_active(&config);
         drop(config);
-        start_watcher(paths, db, provider_state, table_name, app, wc)
+        let handle = start_watcher(paths.clone(), db, provider_state, table_name, app, wc);
+        record(|s| {
+            *s = WatcherStatus::default();
+            if handle.is_some() {
+                s.running = true;
+                s.roots = paths;
+            }
+        });
+        publish();
+        handle
     };
 
     info!("File watcher restarted");
@@ -69,10 +156,94 @@ pub async fn restart(
     *guard = handle;
 }
 
+/// Folders whose real path differs from the configured one, e.g. behind a
+/// symlink or `/var` → `/private/var` on macOS. FSEvents reports the real
+/// path, which wouldn't match exclusions or what's in the index.
+fn root_aliases(roots: &[String]) -> Vec<(PathBuf, PathBuf)> {
+    roots
+        .iter()
+        .filter_map(|root| {
+            let real = std::fs::canonicalize(root).ok()?;
+            (real != Path::new(root)).then(|| (real, PathBuf::from(root)))
+        })
+        .collect()
+}
+
+fn configured_path(aliases: &[(PathBuf, PathBuf)], path: &Path) -> PathBuf {
+    aliases
+        .iter()
+        .find_map(|(real, root)| path.strip_prefix(real).ok().map(|rest| root.join(rest)))
+        .unwrap_or_else(|| path.to_path_buf())
+}
+
+/// `._name` files macOS leaves next to every file on FAT, exFAT and network
+/// drives. Indexing skips them as hidden files; the watcher should too.
+fn is_apple_double(path: &Path) -> bool {
+    path.file_name().and_then(|n| n.to_str()).is_some_and(|n| n.starts_with("._"))
+}
+
 struct WatcherConfig {
     use_git_history: bool,
+    ocr: bool,
     chunk_size: Option<usize>,
     chunk_overlap: Option<usize>,
+    excluded_paths: Vec<String>,
+    ignored_dirs: Vec<String>,
+    retention: indexer::Retention,
+    entities: bool,
+    extractors: indexer::external::Extractors,
+    secrets: indexer::secrets::SecretPolicy,
+}
+
+impl WatcherConfig {
+    fn for_active(config: &Config) -> Self {
+        let indexing = config.indexing_for(&config.active_container);
+        WatcherConfig {
+            use_git_history: indexing.use_git_history,
+            ocr: indexing.ocr,
+            chunk_size: indexing.chunk_size,
+            chunk_overlap: indexing.chunk_overlap,
+            excluded_paths: config
+                .containers
+                .get(&config.active_container)
+                .map(|info| info.excluded_paths.clone())
+                .unwrap_or_default(),
+            ignored_dirs: indexing.watch_ignored_dirs.clone(),
+            retention: indexer::Retention::from(&indexing),
+            entities: indexing.entities,
+            extractors: indexing.extractors.clone(),
+            secrets: indexing.secrets,
+        }
+    }
+}
+
+/// Redoes in the rebuild `target` what the watcher changed in the live table
+/// since the rebuild began, and keeps the watcher from writing until the
+/// returned guard is dropped.
+pub async fn replay(
+    db: &lancedb::Connection,
+    target: &str,
+    provider_state: &Arc<Mutex<ProviderState>>,
+    config_state: &ConfigState,
+) -> MutexGuard<'static, ()> {
+    let guard = WRITING.lock().await;
+    let watched = indexer::journal::load(target).map(|job| job.watched).unwrap_or_default();
+    if watched.is_empty() {
+        return guard;
+    }
+    info!("Replaying {} files the watcher changed during the rebuild", watched.len());
+    let wc = WatcherConfig::for_active(&*config_state.config.lock().await);
+    for path in &watched {
+        let result = if Path::new(path).exists() {
+            indexer::index_single_file(Path::new(path), target, db, provider_state, wc.use_git_history, wc.ocr, wc.chunk_size, wc.chunk_overlap, wc.retention.history_days, wc.entities, &wc.extractors, wc.secrets).await.map(|_| ())
+        } else {
+            indexer::delete_file_from_index(path, target, db, wc.retention).await
+        };
+        if let Err(e) = result {
+            warn!("Failed to replay {} into the rebuild: {}", path, e);
+        }
+    }
+    guard
 }
 
 fn start_watcher(
@@ -110,32 +281,54 @@ fn start_watcher(
     }
 
     let gitignore = build_gitignore(&paths);
+    let aliases = root_aliases(&paths);
 
     let rt = tokio::runtime::Handle::current();
-    let indexing_lock = Arc::new(Mutex::new(()));
     std::thread::spawn(move || {
-        while let Ok(events) = rx.recv() {
+        loop {
+            let events = match rx.recv_timeout(HEARTBEAT) {
+                Ok(events) => events,
+                Err(RecvTimeoutError::Timeout) => {
+                    publish();
+                    continue;
+                }
+                Err(RecvTimeoutError::Disconnected) => break,
+            };
+            record(|s| {
+                s.events += events.len() as u64;
+                s.last_event_at = Some(now_unix());
+            });
+
             let mut changed: HashSet<PathBuf> = HashSet::new();
             let mut deleted: HashSet<PathBuf> = HashSet::new();
 
             for event in &events {
+                use notify::event::ModifyKind;
                 use notify::EventKind;
                 match event.kind {
                     EventKind::Create(_) | EventKind::Modify(_) => {
                         for p in &event.paths {
+                            let p = &configured_path(&aliases, p);
                             let dominated = gitignore.as_ref().is_some_and(|gi| {
                                 gi.matched_path_or_any_parents(p, false).is_ignore()
-                            });
+                            }) || is_path_excluded(&wc.excluded_paths, &p.to_string_lossy())
+                                || is_in_ignored_dir(&wc.ignored_dirs, &paths, p)
+                                || is_apple_double(p);
                             if p.is_file() && !dominated {
                                 changed.insert(p.clone());
+                            } else if matches!(event.kind, EventKind::Modify(ModifyKind::Name(_))) && !p.exists() && !dominated {
+                                // Moved away: FSEvents and a rename out of the
+                                // folder only ever report the old name this way.
+                                deleted.insert(p.clone());
                             }
                         }
                     }
                     EventKind::Remove(_) => {
                         for p in &event.paths {
+                            let p = &configured_path(&aliases, p);
                             let dominated = gitignore.as_ref().is_some_and(|gi| {
                                 gi.matched_path_or_any_parents(p, false).is_ignore()
-                            });
+                            }) || is_in_ignored_dir(&wc.ignored_dirs, &paths, p);
                             if !dominated {
                                 deleted.insert(p.clone());
                             }
@@ -146,6 +339,7 @@ fn start_watcher(
             }
 
             if changed.is_empty() && deleted.is_empty() {
+                publish();
                 continue;
             }
 
@@ -153,48 +347,95 @@ fn start_watcher(
             let ms = provider_state.clone();
             let tn = table_name.clone();
             let app = app.clone();
-            let lock = indexing_lock.clone();
             let changed: Vec<PathBuf> = changed.into_iter().collect();
             let deleted: Vec<PathBuf> = deleted.into_iter().collect();
             let total = changed.len() + deleted.len();
+            record(|s| s.queued_files += total);
+            publish();
 
             rt.spawn(async move {
-                let _guard = lock.lock().await;
+                let _guard = WRITING.lock().await;
+
+                if power::is_low_power() {
+                    debug!("Low-power mode, holding {} changed files until it ends", total);
+                    while power::is_low_power() {
+                        tokio::time::sleep(LOW_POWER_RECHECK).await;
+                    }
+                }
 
                 debug!("Auto-reindexing {} changed, {} deleted files", changed.len(), deleted.len());
 
-                let _ = app.emit("indexing-progress", IndexingProgress {
-                    current: 0,
-                    total,
-                    path: format!("Auto-reindexing {} files...", total),
-                });
+                let _ = app.emit("indexing-progress", IndexingProgress::new(0, total, format!("Auto-reindexing {} files...", total)));
 
                 let mut count = 0usize;
+                let mut errors = 0usize;
 
                 for path in &deleted {
                     let path_str = path.to_string_lossy().to_string();
-                    if let Err(e) = indexer::delete_file_from_index(&path_str, &tn, &db).await {
+                    let result = indexer::delete_file_from_index(&path_str, &tn, &db, wc.retention).await;
+                    if let Err(e) = &result {
                         error!("Failed to remove {} from index: {}", path_str, e);
+                        errors += 1;
                     }
+                    record(|s| {
+                        s.queued_files = s.queued_files.saturating_sub(1);
+                        if result.is_ok() { s.files_removed += 1 } else { s.errors += 1 }
+                    });
                     count += 1;
                 }
 
-                for path in &changed {
-                    if let Err(e) = indexer::index_single_file(path, &tn, &db, &ms, wc.use_git_history, wc.chunk_size, wc.chunk_overlap).await {
+                let quota_block = crate::quota_block(&app).await;
+                if let Some(message) = &quota_block {
+                    warn!("Skipping {} changed files: {}", changed.len(), message);
+                    record(|s| s.queued_files = s.queued_files.saturating_sub(changed.len()));
+                    let _ = app.emit("quota-exceeded", message.clone());
+                }
+                let changed: &[PathBuf] = if quota_block.is_some() { &[] } else { &changed };
+
+                for path in changed {
+                    let result = indexer::index_single_file(path, &tn, &db, &ms, wc.use_git_history, wc.ocr, wc.chunk_size, wc.chunk_overlap, wc.retention.history_days, wc.entities, &wc.extractors, wc.secrets).await;
+                    if let Err(e) = &result {
                         error!("Failed to index {}: {}", path.display(), e);
+                        errors += 1;
                     }
-                    count += 1;
-                    let _ = app.emit("indexing-progress", IndexingProgress {
-                        current: count,
-                        total,
-                        path: path.to_string_lossy().to_string(),
+                    record(|s| {
+                        s.queued_files = s.queued_files.saturating_sub(1);
+                        if result.is_ok() { s.files_indexed += 1 } else { s.errors += 1 }
                     });
+                    count += 1;
+                    let _ = app.emit("indexing-progress", IndexingProgress::new(count, total, path.to_string_lossy()));
+                }
+
+                if let Err(e) = indexer::refresh_fts_index(&tn, &db).await {
+                    error!("Failed to update search index: {}", e);
                 }
+                indexer::quota::unevict(&tn, changed.iter().filter_map(|p| p.to_str()));
+                let touched: Vec<String> = deleted.iter().chain(changed).map(|p| p.to_string_lossy().to_string()).collect();
+                indexer::journal::note_watched(&indexer::rebuild::rebuild_table_name(&tn), &touched);
+                crate::enforce_quota(&app).await;
+                crate::refresh_centroid(&app);
+                publish();
 
                 let _ = app.emit("indexing-complete", format!("{} files auto-reindexed", count));
+                let strings = |list: &[PathBuf]| -> Vec<String> { list.iter().map(|p| p.to_string_lossy().to_string()).collect() };
+                webhooks::send(&app, IndexEvent::FilesChanged { changed: strings(changed), deleted: strings(&deleted), errors });
             });
         }
     });
 
     Some(WatcherHandle { _debouncer: debouncer })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_configured_path() {
+        let aliases = vec![(PathBuf::from("/private/var/notes"), PathBuf::from("/var/notes"))];
+        assert_eq!(configured_path(&aliases, Path::new("/private/var/notes/a/b.md")), PathBuf::from("/var/notes/a/b.md"));
+        assert_eq!(configured_path(&aliases, Path::new("/home/u/c.md")), PathBuf::from("/home/u/c.md"));
+        assert!(is_apple_double(Path::new("/Volumes/usb/._report.pdf")));
+        assert!(!is_apple_double(Path::new("/Volumes/usb/report.pdf")));
+    }
+}
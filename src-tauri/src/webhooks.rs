@@ -0,0 +1,148 @@
+//! POSTs index events to the URLs under `webhooks`, so automations (n8n,
+//! Home Assistant, CI) hear when the index changed without polling it.
+
+use std::time::Duration;
+
+use log::{debug, warn};
+use serde_json::{json, Value};
+use tauri::{AppHandle, Manager};
+
+use crate::config::{ConfigState, WebhookEvent};
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Paths listed per kind in a `files-changed` payload. A branch switch can
+/// touch thousands, and the counts are always there.
+const MAX_PATHS: usize = 200;
+
+pub enum IndexEvent {
+    Complete { folders: Vec<String>, files: usize, elapsed: Duration },
+    Error { folder: String, error: String, elapsed: Duration },
+    /// A batch the file watcher picked up and reindexed.
+    FilesChanged { changed: Vec<String>, deleted: Vec<String>, errors: usize },
+}
+
+impl IndexEvent {
+    fn kind(&self) -> WebhookEvent {
+        match self {
+            Self::Complete { .. } => WebhookEvent::IndexingComplete,
+            Self::Error { .. } => WebhookEvent::IndexingError,
+            Self::FilesChanged { .. } => WebhookEvent::FilesChanged,
+        }
+    }
+
+    fn payload(&self, container: &str, timestamp: i64) -> Value {
+        let mut body = match self {
+            Self::Complete { folders, files, elapsed } => json!({
+                "folders": folders,
+                "files": files,
+                "duration_ms": elapsed.as_millis() as u64,
+            }),
+            Self::Error { folder, error, elapsed } => json!({
+                "folder": folder,
+                "error": error,
+                "duration_ms": elapsed.as_millis() as u64,
+            }),
+            Self::FilesChanged { changed, deleted, errors } => json!({
+                "changed": &changed[..changed.len().min(MAX_PATHS)],
+                "deleted": &deleted[..deleted.len().min(MAX_PATHS)],
+                "changed_count": changed.len(),
+                "deleted_count": deleted.len(),
+                "errors": errors,
+                "truncated": changed.len() > MAX_PATHS || deleted.len() > MAX_PATHS,
+            }),
+        };
+        body["event"] = json!(self.kind());
+        body["container"] = json!(container);
+        body["timestamp"] = json!(timestamp);
+        body
+    }
+}
+
+/// Sends `event` to every webhook that wants it, in the background. A hook
+/// that's down only gets a warning in the log; indexing never waits on it.
+/// Nothing is sent in offline mode.
+pub fn send(app: &AppHandle, event: IndexEvent) {
+    if crate::config::is_offline() {
+        return;
+    }
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let (urls, container) = {
+            let config = app.state::<ConfigState>().config.lock().await;
+            let urls: Vec<String> = config
+                .webhooks
+                .iter()
+                .filter(|hook| hook.wants(event.kind()))
+                .map(|hook| hook.url.clone())
+                .collect();
+            (urls, config.active_container.clone())
+        };
+        if urls.is_empty() {
+            return;
+        }
+
+        let body = event.payload(&container, chrono::Utc::now().timestamp());
+        let client = match reqwest::Client::builder().timeout(TIMEOUT).build() {
+            Ok(client) => client,
+            Err(e) => {
+                warn!("Failed to create webhook client: {}", e);
+                return;
+            }
+        };
+        for url in urls {
+            match client.post(&url).json(&body).send().await {
+                Ok(response) if response.status().is_success() => debug!("Webhook {} got {}", url, body["event"]),
+                Ok(response) => warn!("Webhook {} answered {}", url, response.status()),
+                Err(e) => warn!("Webhook {} failed: {}", url, e),
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Webhook;
+
+    #[test]
+    fn test_payload() {
+        let complete = IndexEvent::Complete {
+            folders: vec!["/home/u/notes".to_string()],
+            files: 42,
+            elapsed: Duration::from_millis(1500),
+        };
+        let body = complete.payload("Work", 1_700_000_000);
+        assert_eq!(body["event"], "indexing-complete");
+        assert_eq!(body["container"], "Work");
+        assert_eq!(body["files"], 42);
+        assert_eq!(body["duration_ms"], 1500);
+        assert_eq!(body["timestamp"], 1_700_000_000);
+
+        let changed = IndexEvent::FilesChanged {
+            changed: (0..250).map(|i| format!("/home/u/notes/{}.md", i)).collect(),
+            deleted: vec!["/home/u/notes/old.md".to_string()],
+            errors: 1,
+        };
+        let body = changed.payload("Work", 0);
+        assert_eq!(body["event"], "files-changed");
+        assert_eq!(body["changed"].as_array().unwrap().len(), MAX_PATHS);
+        assert_eq!(body["changed_count"], 250);
+        assert_eq!(body["deleted"][0], "/home/u/notes/old.md");
+        assert_eq!(body["truncated"], true);
+    }
+
+    #[test]
+    fn test_wants() {
+        let hooks: Vec<Webhook> = serde_json::from_str(
+            r#"[
+                { "url": "http://localhost:5678/webhook/rememex" },
+                { "url": "http://ha.local/api/webhook/x", "events": ["indexing-error"] }
+            ]"#,
+        )
+        .unwrap();
+        assert!(hooks[0].wants(WebhookEvent::FilesChanged));
+        assert!(hooks[1].wants(WebhookEvent::IndexingError));
+        assert!(!hooks[1].wants(WebhookEvent::IndexingComplete));
+    }
+}
@@ -1,18 +1,31 @@
-import { useRef, useEffect, useState, useMemo } from "react";
+import { useRef, useEffect, useState, useMemo, useCallback } from "react";
 import type { ListImperativeAPI } from "react-window";
 import { invoke } from "@tauri-apps/api/core";
-import { openPath } from "@tauri-apps/plugin-opener";
+import { openPath, revealItemInDir } from "@tauri-apps/plugin-opener";
 import { open as openDialog } from "@tauri-apps/plugin-dialog";
 import { listen } from "@tauri-apps/api/event";
+import { getCurrentWebview } from "@tauri-apps/api/webview";
 import { useModal, ModalProvider } from "./Modal";
 import { useLocale } from "./i18n";
 import Sidebar from "./components/Sidebar";
 import SearchBar from "./components/SearchBar";
-import ResultsList from "./components/ResultsList";
+import ResultsList, { translationKey } from "./components/ResultsList";
 import StatusBar from "./components/StatusBar";
 import TitleBar from "./components/TitleBar";
+import { isPathQuery, resolvePath } from "./pathQuery";
 import Settings from "./components/Settings";
-import type { SearchResult, IndexingProgress, ContainerItem } from "./types";
+import ResultContextMenu, { type ResultContextMenuState } from "./components/ResultContextMenu";
+import QuickAnswer, { type QuickAnswerData } from "./components/QuickAnswer";
+import DidYouMean from "./components/DidYouMean";
+import TopicsView from "./components/TopicsView";
+import TimelineView from "./components/TimelineView";
+import AskFileView from "./components/AskFileView";
+import EntityChips from "./components/EntityChips";
+import EntityView from "./components/EntityView";
+import type { SearchResult, IndexingProgress, ContainerItem, EndpointHealth, ModelDownload, Translation } from "./types";
+import { defaultResultActions, isComposingKey, jumpIndexFromEvent, matchesBinding, type ResultActions } from "./keybindings";
+import { recordQuery } from "./queryHistory";
+import { applyAppearance, type Appearance } from "./theme";
 import logoSrc from "./assets/rememex.png";
 import "./App.css";
 
@@ -20,6 +33,10 @@ function getFileName(path: string): string {
   return path.split(/[\\/]/).pop() || path;
 }
 
+function cleanSnippet(snippet: string): string {
+  return snippet.replace(/^\[annotation\] /, "").replace(/^File: [^\n]*\n/, "");
+}
+
 function App() {
   const [query, setQuery] = useState("");
   const [results, setResults] = useState<SearchResult[]>([]);
@@ -33,10 +50,40 @@ function App() {
   const [sidebarOpen, setSidebarOpen] = useState(true);
   const [settingsOpen, setSettingsOpen] = useState(false);
   const [hotkey, setHotkey] = useState("Alt + Space");
+  // Docked, the native title bar has its own buttons.
+  const [docked, setDocked] = useState(false);
+  const [resultActions, setResultActions] = useState<ResultActions>(defaultResultActions);
+  const [searchInput, setSearchInput] = useState({ debounceMs: 300, minChars: 1 });
+  const [endpointHealth, setEndpointHealth] = useState<EndpointHealth>({ state: "none", error: null });
+  const [modelDownload, setModelDownload] = useState<ModelDownload | null>(null);
   const [annotations, setAnnotations] = useState<{ id: string; path: string; note: string; source: string; created_at: number }[]>([]);
   const [selectedAnnotationId, setSelectedAnnotationId] = useState<string | null>(null);
+  const [contextMenu, setContextMenu] = useState<ResultContextMenuState | null>(null);
+  const [showingRecent, setShowingRecent] = useState(false);
+  const [browseView, setBrowseView] = useState<"topics" | "timeline" | null>(null);
+  const [entityName, setEntityName] = useState<string | null>(null);
+  const [askPath, setAskPath] = useState<string | null>(null);
+  const [translations, setTranslations] = useState<Record<string, string>>({});
+  const [historyVersion, setHistoryVersion] = useState(0);
+  const [quickAnswer, setQuickAnswer] = useState<QuickAnswerData | null>(null);
+  const [suggestion, setSuggestion] = useState<string | null>(null);
+  const closeContextMenu = useCallback(() => setContextMenu(null), []);
+  const [isDragOver, setIsDragOver] = useState(false);
+  const [languages, setLanguages] = useState<[string, number][]>([]);
+  const [language, setLanguage] = useState<string | null>(null);
+  const [historyEnabled, setHistoryEnabled] = useState(false);
+  const [asOf, setAsOf] = useState<string | null>(null);
+  const [deletedCount, setDeletedCount] = useState(0);
+  const [includeDeleted, setIncludeDeleted] = useState(false);
+  const [extensions, setExtensions] = useState<[string, number][]>([]);
+  const [extensionFilter, setExtensionFilter] = useState<string[]>([]);
+  const [folderFilter, setFolderFilter] = useState<string | null>(null);
+  // Container auto container sent the query to, and the query the user
+  // asked to search the active container for instead.
+  const [routedContainer, setRoutedContainer] = useState<string | null>(null);
+  const [routeDismissedFor, setRouteDismissedFor] = useState<string | null>(null);
   const modal = useModal();
-  const { t } = useLocale();
+  const { t, locale } = useLocale();
 
   const searchInputRef = useRef<HTMLInputElement>(null);
   const listRef = useRef<ListImperativeAPI>(null);
@@ -44,8 +91,13 @@ function App() {
 
   useEffect(() => {
     fetchContainers();
-    invoke<{ first_run: boolean; provider_type: string; hotkey: string }>("get_config").then((c) => {
+    invoke<{ first_run: boolean; provider_type: string; hotkey: string; result_actions: ResultActions; search_debounce_ms: number; search_min_query_chars: number; version_history_days: number; window_mode: string } & Appearance>("get_config").then(async (c) => {
       setHotkey(c.hotkey);
+      setDocked(c.window_mode === "docked");
+      setResultActions(c.result_actions);
+      setSearchInput({ debounceMs: c.search_debounce_ms, minChars: c.search_min_query_chars });
+      setHistoryEnabled(c.version_history_days > 0);
+      applyAppearance(c, await invoke<boolean>("is_vibrancy_active"));
       if (c.first_run) {
         isFirstRunRef.current = true;
         setSettingsOpen(true);
@@ -81,6 +133,68 @@ function App() {
     return () => clearInterval(interval);
   }, [activeContainer]);
 
+  async function fetchLanguages() {
+    try {
+      const list = await invoke<[string, number][]>("get_languages");
+      setLanguages(list);
+      setLanguage(prev => (prev && list.some(([code]) => code === prev) ? prev : null));
+    } catch {
+      setLanguages([]);
+      setLanguage(null);
+    }
+  }
+
+  async function fetchExtensions() {
+    try {
+      const list = await invoke<[string, number][]>("get_extensions");
+      setExtensions(list);
+      setExtensionFilter(prev => prev.filter(ext => list.some(([e]) => e === ext)));
+    } catch {
+      setExtensions([]);
+      setExtensionFilter([]);
+    }
+  }
+
+  async function fetchDeletedCount() {
+    try {
+      const count = await invoke<number>("get_deleted_count");
+      setDeletedCount(count);
+      if (count === 0) setIncludeDeleted(false);
+    } catch {
+      setDeletedCount(0);
+      setIncludeDeleted(false);
+    }
+  }
+
+  useEffect(() => {
+    fetchLanguages();
+    fetchExtensions();
+    fetchDeletedCount();
+    setFolderFilter(null);
+  }, [activeContainer]);
+
+  // An indexing job the last run didn't get to finish: pick it up where it
+  // stopped, or clean up the files it left half indexed.
+  useEffect(() => {
+    async function offerResume() {
+      const job = await invoke<{ folders: string[]; started_at: number } | null>("get_interrupted_job");
+      if (!job) return;
+      const result = await modal.confirm({
+        title: t("dialog_resume_title"),
+        message: t("dialog_resume_message", { count: String(job.folders.length), name: activeContainer }),
+        icon: "info",
+        confirmText: t("dialog_resume_confirm"),
+        cancelText: t("dialog_resume_discard"),
+      });
+      if (result.confirmed) {
+        await handleResumeIndexing();
+      } else {
+        await invoke("discard_interrupted_job");
+      }
+    }
+    offerResume().catch(() => { });
+  }, [activeContainer]);
+
   async function fetchContainers() {
     try {
       const [list, active] = await invoke<[ContainerItem[], string]>("get_containers");
@@ -92,12 +206,39 @@ function App() {
   }
 
   async function handleCreateContainer() {
+    // A template fills in the description here and brings its own indexing
+    // settings along.
+    const templates = await invoke<{ id: string }[]>("get_container_templates").catch(() => []);
+    let template = "";
+    if (templates.length > 0) {
+      const step0 = await modal.prompt({
+        title: t("dialog_new_container"),
+        icon: "info",
+        fields: [
+          {
+            key: "template", label: t("dialog_field_template"), type: "select" as const,
+            defaultValue: "",
+            options: [
+              { value: "", label: t("dialog_template_blank") },
+              ...templates.map(({ id }) => ({ value: id, label: t(`dialog_template_${id}`) })),
+            ],
+          },
+        ],
+        confirmText: t("dialog_next"),
+      });
+      if (!step0.confirmed) return;
+      template = step0.values?.template || "";
+    }
+
     const step1 = await modal.prompt({
       title: t("dialog_new_container"),
       icon: "info",
       fields: [
         { key: "name", label: t("dialog_field_name"), placeholder: t("dialog_field_name_placeholder") },
-        { key: "description", label: t("dialog_field_description"), placeholder: t("dialog_field_description_placeholder") },
+        {
+          key: "description", label: t("dialog_field_description"), placeholder: t("dialog_field_description_placeholder"),
+          defaultValue: template ? t(`dialog_template_${template}_desc`) : undefined,
+        },
         {
           key: "provider_type", label: "Provider", type: "select" as const,
           defaultValue: "local",
@@ -106,6 +247,15 @@ function App() {
             { value: "remote", label: "Remote (API)" },
           ],
         },
+        { key: "db_uri", label: t("dialog_field_db_uri"), placeholder: t("dialog_field_db_uri_placeholder") },
+        {
+          key: "role", label: t("dialog_field_role"), type: "select" as const,
+          defaultValue: "writer",
+          options: [
+            { value: "writer", label: t("dialog_role_writer") },
+            { value: "reader", label: t("dialog_role_reader") },
+          ],
+        },
       ],
       confirmText: t("dialog_next"),
     });
@@ -169,6 +319,9 @@ function App() {
         remoteApiKey: remoteApiKey || null,
         remoteModel: remoteModel || null,
         remoteDimensions: remoteDimensions || null,
+        dbUri: (step1.values.db_uri || "").trim() || null,
+        role: step1.values.role || "writer",
+        template: template || null,
       });
       await fetchContainers();
       await handleSwitchContainer(step1.values.name.trim());
@@ -199,6 +352,27 @@ function App() {
     }
   }
 
+  async function handleUnlock(name: string) {
+    try {
+      await invoke("unlock_container", { name });
+      unlockOfferedRef.current = null;
+      setStatus(t("status_unlocked", { name }));
+      setHistoryVersion(v => v + 1);
+    } catch (err) {
+      setStatus(String(err));
+    }
+  }
+
+  async function handleSetLocked(name: string, locked: boolean) {
+    try {
+      await invoke("set_container_locked", { name, locked });
+      await fetchContainers();
+      setStatus(t(locked ? "status_locked_set" : "status_lock_removed", { name }));
+    } catch (e) {
+      await modal.confirm({ title: "Error", message: String(e), icon: "warning", confirmText: t("modal_ok") });
+    }
+  }
+
   async function handleSwitchContainer(name: string) {
     if (name === activeContainer) return;
     setActiveContainer(name);
@@ -217,8 +391,61 @@ function App() {
   useEffect(() => {
     searchInputRef.current?.focus();
     const handleKeyDown = (e: KeyboardEvent) => {
-      const tag = (e.target as HTMLElement)?.tagName;
-      if (tag === "INPUT" || tag === "TEXTAREA" || tag === "SELECT") return;
+      if (isComposingKey(e)) return;
+      const target = e.target as HTMLElement;
+      const inSearchInput = target === searchInputRef.current;
+      const tag = target?.tagName;
+      if (!inSearchInput && (tag === "INPUT" || tag === "TEXTAREA" || tag === "SELECT")) return;
+
+      const selected = results[selectedIndex];
+      if (selected) {
+        if (matchesBinding(e, resultActions.open)) {
+          e.preventDefault();
+          handleOpenFile(selected.path);
+          return;
+        }
+        if (matchesBinding(e, resultActions.reveal)) {
+          e.preventDefault();
+          handleRevealFile(selected.path);
+          return;
+        }
+        const input = searchInputRef.current;
+        const hasTextSelection = inSearchInput && !!input && input.selectionStart !== input.selectionEnd;
+        if (!hasTextSelection && matchesBinding(e, resultActions.copy_path)) {
+          e.preventDefault();
+          handleCopy(selected.path, t("status_copied_path"));
+          return;
+        }
+        if (!hasTextSelection && matchesBinding(e, resultActions.copy_snippet)) {
+          e.preventDefault();
+          handleCopy(cleanSnippet(selected.snippet), t("status_copied_snippet"));
+          return;
+        }
+        if (!hasTextSelection && matchesBinding(e, resultActions.copy_context)) {
+          e.preventDefault();
+          handleCopyContext();
+          return;
+        }
+      }
+      const jump = jumpIndexFromEvent(e);
+      if (jump !== null && jump < results.length) {
+        e.preventDefault();
+        setSelectedIndex(jump);
+        handleOpenFile(results[jump].path);
+        return;
+      }
+      // A path typed into the bar opens or reveals as it is.
+      if (inSearchInput && !selected && isPathQuery(query)) {
+        if (matchesBinding(e, resultActions.open) || matchesBinding(e, resultActions.reveal)) {
+          e.preventDefault();
+          const reveal = matchesBinding(e, resultActions.reveal);
+          resolvePath(query)
+            .then((path) => (reveal ? revealItemInDir(path) : openPath(path)))
+            .catch((err) => setStatus(`Failed to open: ${String(err)}`));
+          return;
+        }
+      }
+      if (inSearchInput) return;
 
       if (e.key === "ArrowDown") {
         e.preventDefault();
@@ -226,11 +453,6 @@ function App() {
       } else if (e.key === "ArrowUp") {
         e.preventDefault();
         setSelectedIndex(prev => Math.max(prev - 1, 0));
-      } else if (e.key === "Enter") {
-        e.preventDefault();
-        if (results[selectedIndex]) {
-          handleOpenFile(results[selectedIndex].path);
-        }
       } else if ((e.ctrlKey || e.metaKey) && e.key === "o") {
         e.preventDefault();
         handlePickFolder();
@@ -251,7 +473,68 @@ function App() {
     };
     globalThis.addEventListener("keydown", handleKeyDown);
     return () => globalThis.removeEventListener("keydown", handleKeyDown);
-  }, [results, selectedIndex, query, activeContainer]);
+  }, [results, selectedIndex, query, activeContainer, resultActions]);
+
+  useEffect(() => {
+    invoke<EndpointHealth>("get_endpoint_health").then(setEndpointHealth).catch(() => { });
+    const unlisten = listen<EndpointHealth>("endpoint-health", (event) => setEndpointHealth(event.payload));
+    return () => { unlisten.then((f) => f()); };
+  }, []);
+
+  // Containers or annotations another device changed came in.
+  useEffect(() => {
+    const unlisten = listen("sync-applied", () => {
+      fetchContainers();
+      fetchAnnotations();
+    });
+    return () => { unlisten.then((f) => f()); };
+  }, []);
+
+  useEffect(() => {
+    const unlisten = listen<string>("tray-switch-container", (event) => {
+      handleSwitchContainer(event.payload).catch(() => { });
+    });
+    return () => { unlisten.then((f) => f()); };
+  }, [activeContainer]);
+
+  // rememex://search links. The backend keeps the latest one until it's
+  // taken, so a link that launched the app waits for the containers to load.
+  useEffect(() => {
+    if (containers.length === 0) return;
+    async function takeDeepLink() {
+      const link = await invoke<{ query: string; container: string | null } | null>("take_deep_link_search");
+      if (!link) return;
+      if (link.container && link.container !== activeContainer) {
+        if (containers.some(c => c.name === link.container)) {
+          await handleSwitchContainer(link.container);
+        } else {
+          setStatus(t("status_unknown_container", { name: link.container }));
+        }
+      }
+      setQuery(link.query);
+      searchInputRef.current?.focus();
+    }
+    takeDeepLink().catch(() => { });
+    const unlisten = listen("deep-link-search", () => {
+      takeDeepLink().catch(() => { });
+    });
+    return () => { unlisten.then((f) => f()); };
+  }, [activeContainer, containers]);
+
+  useEffect(() => {
+    const unlistenDragDrop = getCurrentWebview().onDragDropEvent((event) => {
+      const { type } = event.payload;
+      if (type === "enter" || type === "over") {
+        setIsDragOver(true);
+      } else if (type === "leave") {
+        setIsDragOver(false);
+      } else if (type === "drop") {
+        setIsDragOver(false);
+        handleDroppedPaths(event.payload.paths).catch(() => { });
+      }
+    });
+    return () => { unlistenDragDrop.then((f) => f()); };
+  }, [activeContainer, isIndexing]);
 
   useEffect(() => {
     const unlistenProgress = listen<IndexingProgress>("indexing-progress", (event) => {
@@ -264,16 +547,57 @@ function App() {
       setStatus(t("status_done", { message: event.payload }));
       setIsIndexing(false);
       setIndexProgress(null);
+      fetchLanguages();
+      fetchExtensions();
+      fetchDeletedCount();
       setTimeout(() => setStatus(""), 5000);
     });
 
+    const unlistenDownloadProgress = listen<ModelDownload>("model-download-progress", (event) => {
+      setModelDownload(event.payload);
+    });
+
+    const unlistenDownloadDone = listen("model-download-done", () => {
+      setModelDownload(null);
+    });
+
     const unlistenModelLoaded = listen("model-loaded", () => {
       setStatus("");
       setIsIndexing(false);
       setIndexProgress(null);
     });
 
+    const unlistenDbConnected = listen("db-connected", () => {
+      setStatus("");
+      fetchAnnotations();
+      setHistoryVersion(v => v + 1);
+      fetchLanguages();
+      fetchExtensions();
+      fetchDeletedCount();
+    });
+
+    const unlistenDbError = listen<string>("db-error", (event) => {
+      setStatus(t("status_db_error", { error: event.payload }));
+    });
+
+    const unlistenQuotaExceeded = listen<string>("quota-exceeded", (event) => {
+      setStatus(event.payload);
+      setIsIndexing(false);
+      setIndexProgress(null);
+    });
+
+    const unlistenQuotaEvicted = listen<number>("quota-evicted", (event) => {
+      setStatus(t("status_quota_evicted", { count: String(event.payload) }));
+      setTimeout(() => setStatus(""), 5000);
+    });
+
+    const unlistenModelWarming = listen("model-warming", () => {
+      warmingRef.current = true;
+      setStatus(t("status_warming_up"));
+    });
+
     const unlistenModelError = listen<string>("model-load-error", (event) => {
+      setModelDownload(null);
       setStatus(t("status_model_error", { error: event.payload }));
       setIsIndexing(false);
       setIndexProgress(null);
@@ -282,37 +606,116 @@ function App() {
     return () => {
       unlistenProgress.then((f) => f());
       unlistenComplete.then((f) => f());
+      unlistenDownloadProgress.then((f) => f());
+      unlistenDownloadDone.then((f) => f());
       unlistenModelLoaded.then((f) => f());
       unlistenModelError.then((f) => f());
+      unlistenModelWarming.then((f) => f());
+      unlistenDbConnected.then((f) => f());
+      unlistenDbError.then((f) => f());
+      unlistenQuotaExceeded.then((f) => f());
+      unlistenQuotaEvicted.then((f) => f());
     };
   }, []);
 
+  // Typing a query leaves the topic map, timeline, entity or file question for the results.
+  useEffect(() => {
+    if (query.trim()) {
+      setBrowseView(null);
+      setEntityName(null);
+      setAskPath(null);
+    }
+  }, [query]);
+
   const searchGenRef = useRef(0);
+  const reembedOfferedRef = useRef<string | null>(null);
+  const unlockOfferedRef = useRef<string | null>(null);
+  const warmingRef = useRef(false);
 
   useEffect(() => {
-    if (!query.trim()) {
+    const gen = ++searchGenRef.current;
+    setSuggestion(null);
+    // Until the query is long enough to be worth a search, recent files stay.
+    if (query.trim().length < searchInput.minChars) {
+      setRoutedContainer(null);
+      invoke<SearchResult[]>("get_recent").then((res) => {
+        if (searchGenRef.current !== gen) return;
+        setResults(res);
+        setShowingRecent(true);
+        setSelectedIndex(0);
+      }).catch(() => {
+        if (searchGenRef.current === gen) setResults([]);
+      });
+      return;
+    }
+    if (isPathQuery(query)) {
+      setRoutedContainer(null);
       setResults([]);
+      setShowingRecent(false);
       return;
     }
-    const gen = ++searchGenRef.current;
     const timer = setTimeout(async () => {
       try {
-        const res = await invoke<SearchResult[]>("search", { query });
+        // End of the picked day in local time, so edits made that day count.
+        const asOfSecs = asOf ? Math.floor(new Date(`${asOf}T23:59:59`).getTime() / 1000) : null;
+        const routed = routeDismissedFor === query
+          ? null
+          : await invoke<string | null>("route_container", { query }).catch(() => null);
+        if (searchGenRef.current !== gen) return;
+        const res = await invoke<SearchResult[]>("search", {
+          query, language, asOf: asOfSecs, includeDeleted, container: routed,
+          pathPrefix: folderFilter, fileExtensions: extensionFilter.length > 0 ? extensionFilter : null,
+        });
         if (searchGenRef.current !== gen) return;
+        setRoutedContainer(routed);
         setResults(res);
+        setShowingRecent(false);
         setSelectedIndex(0);
+        invoke<string | null>("suggest_query", { query })
+          .then((s) => { if (searchGenRef.current === gen) setSuggestion(s); })
+          .catch(() => { });
+        if (warmingRef.current) {
+          warmingRef.current = false;
+          setStatus("");
+        }
       } catch (err) {
         if (searchGenRef.current !== gen) return;
+        setRoutedContainer(null);
         const msg = String(err);
-        if (msg.includes("rebuild") || msg.includes("Model changed")) {
+        const locked = /Container "(.+)" is locked/.exec(msg)?.[1];
+        if (locked) {
+          setStatus(t("status_locked", { name: locked }));
+          // Asked once until it's unlocked; the sidebar has it after that.
+          if (unlockOfferedRef.current !== locked) {
+            unlockOfferedRef.current = locked;
+            handleUnlock(locked).catch(() => { });
+          }
+        } else if (msg.includes("rebuild") || msg.includes("Model changed")) {
           setStatus(t("status_rebuild_needed"));
+          // Offered once per container; the sidebar has it after that.
+          if (reembedOfferedRef.current !== activeContainer && !isIndexing) {
+            reembedOfferedRef.current = activeContainer;
+            handleReembed().catch(() => { });
+          }
         } else {
           setStatus(msg);
         }
       }
-    }, 300);
+    }, searchInput.debounceMs);
     return () => clearTimeout(timer);
-  }, [query, activeContainer]);
+  }, [query, activeContainer, historyVersion, language, asOf, includeDeleted, routeDismissedFor, folderFilter, extensionFilter, searchInput]);
+
+  useEffect(() => {
+    let cancelled = false;
+    if (!query.trim() || isPathQuery(query)) {
+      setQuickAnswer(null);
+      return;
+    }
+    invoke<QuickAnswerData | null>("quick_answer", { query })
+      .then((answer) => { if (!cancelled) setQuickAnswer(answer); })
+      .catch(() => { if (!cancelled) setQuickAnswer(null); });
+    return () => { cancelled = true; };
+  }, [query]);
 
   async function handleResetIndex() {
     try {
@@ -354,6 +757,92 @@ function App() {
     }
   }
 
+  async function handleReembed() {
+    const result = await modal.confirm({
+      title: t("dialog_reembed_title"),
+      message: t("dialog_reembed_message", { name: activeContainer }),
+      icon: "info",
+      confirmText: t("dialog_reembed_confirm"),
+    });
+    if (!result.confirmed) return;
+
+    try {
+      setStatus(t("status_reembedding"));
+      setIsIndexing(true);
+      setResults([]);
+      const msg = await invoke<string>("reembed_index");
+      setStatus(msg);
+      setIsIndexing(false);
+    } catch (err) {
+      setStatus(String(err));
+      setIsIndexing(false);
+    }
+  }
+
+  async function handleReindexFolder(dir: string) {
+    try {
+      setStatus(t("status_rebuilding"));
+      setIsIndexing(true);
+      setResults([]);
+      const msg = await invoke<string>("reindex_folder", { dir });
+      setStatus(msg);
+      setIsIndexing(false);
+    } catch (err) {
+      setStatus(String(err));
+      setIsIndexing(false);
+    }
+  }
+
+  async function handleResumeIndexing() {
+    try {
+      setStatus(t("status_resuming"));
+      setIsIndexing(true);
+      const msg = await invoke<string>("resume_indexing");
+      setStatus(msg);
+      setIsIndexing(false);
+    } catch (err) {
+      setStatus(String(err));
+      setIsIndexing(false);
+    }
+  }
+
+  async function handleRemoveFolder(dir: string) {
+    const result = await modal.confirm({
+      title: t("dialog_remove_folder_title"),
+      message: t("dialog_remove_folder_message", { path: dir, name: activeContainer }),
+      icon: "warning",
+      confirmText: t("dialog_remove_folder_confirm"),
+      confirmVariant: "danger",
+    });
+    if (!result.confirmed) return;
+
+    try {
+      const msg = await invoke<string>("remove_indexed_folder", { dir });
+      setResults([]);
+      setStatus(msg);
+      await fetchContainers();
+    } catch (err) {
+      setStatus(String(err));
+    }
+  }
+
+  async function indexPaths(paths: string[]) {
+    try {
+      setStatus(t("status_starting"));
+      setIsIndexing(true);
+      let msg = "";
+      for (const dir of paths) {
+        msg = await invoke<string>("index_folder", { dir });
+      }
+      setStatus(msg);
+      setIsIndexing(false);
+      await fetchContainers();
+    } catch (err) {
+      setStatus(String(err));
+      setIsIndexing(false);
+    }
+  }
+
   async function handlePickFolder() {
     try {
       const selected = await openDialog({
@@ -361,29 +850,157 @@ function App() {
         multiple: false,
         title: t("index_folder_title", { container: activeContainer }),
       });
-      if (selected) {
-        setStatus(t("status_starting"));
-        setIsIndexing(true);
-        const msg = await invoke<string>("index_folder", { dir: selected });
-        setStatus(msg);
-        setIsIndexing(false);
-        await fetchContainers();
-      }
+      if (selected) await indexPaths([selected]);
     } catch (err) {
       setStatus(String(err));
-      setIsIndexing(false);
     }
   }
 
+  async function handleDroppedPaths(paths: string[]) {
+    if (paths.length === 0 || isIndexing) return;
+
+    const result = await modal.confirm({
+      title: t("dialog_drop_title"),
+      message: paths.length === 1
+        ? t("dialog_drop_message_one", { name: getFileName(paths[0]), container: activeContainer })
+        : t("dialog_drop_message_many", { count: String(paths.length), container: activeContainer }),
+      icon: "info",
+      confirmText: t("dialog_drop_confirm"),
+    });
+    if (result.confirmed) await indexPaths(paths);
+  }
+
+  function recordOpen(path: string) {
+    recordQuery(activeContainer, query);
+    const snippet = results.find(r => r.path === path)?.snippet ?? "";
+    invoke("record_open", { path, snippet })
+      .then(() => { if (!query.trim()) setHistoryVersion(v => v + 1); })
+      .catch(() => { });
+  }
+
   async function handleOpenFile(path: string) {
+    const remote = results.find(r => r.path === path)?.remote;
+    if (remote) {
+      await handleCopy(path, t("results_remote_hint", { name: remote.name }));
+      return;
+    }
     try {
-      await openPath(path);
+      const page = results.find(r => r.path === path)?.page;
+      if (page) await invoke("open_at_page", { path, page });
+      else await openPath(path);
+      recordOpen(path);
     } catch (e) {
       console.error("Failed to open file:", path, e);
       setStatus(`Failed to open: ${String(e)}`);
     }
   }
 
+  async function handleRevealFile(path: string) {
+    try {
+      await revealItemInDir(path);
+      recordOpen(path);
+    } catch (e) {
+      console.error("Failed to reveal file:", path, e);
+      setStatus(`Failed to reveal: ${String(e)}`);
+    }
+  }
+
+  async function handleCopy(text: string, message: string) {
+    try {
+      await navigator.clipboard.writeText(text);
+      setStatus(message);
+      setTimeout(() => setStatus(""), 2000);
+    } catch (e) {
+      setStatus(String(e));
+    }
+  }
+
+  async function handleCopyContext() {
+    if (results.length === 0) return;
+    try {
+      const items = results.map(r => ({ path: r.path, context: r.context, snippet: r.snippet }));
+      const block = await invoke<{ text: string; included: number; tokens: number }>("build_context_block", { query, items });
+      await handleCopy(block.text, t("status_copied_context", { count: block.included, tokens: block.tokens }));
+    } catch (e) {
+      setStatus(String(e));
+    }
+  }
+
+  async function handleFindSimilar(path: string) {
+    try {
+      searchGenRef.current++;
+      setSuggestion(null);
+      const res = await invoke<SearchResult[]>("find_similar", { path });
+      setResults(res);
+      setShowingRecent(false);
+      setSelectedIndex(0);
+      setStatus(t("status_similar_to", { name: getFileName(path) }));
+    } catch (e) {
+      setStatus(String(e));
+    }
+  }
+
+  async function handleTranslate(result: SearchResult) {
+    const key = translationKey(result);
+    if (translations[key]) {
+      setTranslations(prev => {
+        const next = { ...prev };
+        delete next[key];
+        return next;
+      });
+      return;
+    }
+    const target = locale.split("-")[0];
+    const targetName = new Intl.DisplayNames(["en"], { type: "language" }).of(target) ?? target;
+    try {
+      setStatus(t("status_translating"));
+      const res = await invoke<Translation>("translate_snippet", {
+        text: result.excerpt || cleanSnippet(result.snippet),
+        target,
+        targetName,
+      });
+      if (!res.translated) {
+        setStatus(t("status_translate_same"));
+        return;
+      }
+      setTranslations(prev => ({ ...prev, [key]: res.text }));
+      setStatus(t("status_translated", { name: getFileName(result.path) }));
+    } catch (e) {
+      setStatus(String(e));
+    }
+  }
+
+  async function handleTogglePin(path: string) {
+    try {
+      const pinned = await invoke<boolean>("toggle_pin", { path });
+      setResults(prev => prev.map(r => r.path === path ? { ...r, pinned } : r));
+      setStatus(t(pinned ? "status_pinned" : "status_unpinned", { name: getFileName(path) }));
+    } catch (e) {
+      setStatus(String(e));
+    }
+  }
+
+  async function handleExcludePath(path: string) {
+    const result = await modal.confirm({
+      title: t("dialog_exclude_title"),
+      message: t("dialog_exclude_message", { name: getFileName(path), container: activeContainer }),
+      icon: "warning",
+      confirmText: t("dialog_exclude_confirm"),
+      confirmVariant: "danger",
+    });
+    if (!result.confirmed) return;
+
+    try {
+      await invoke("exclude_path", { path });
+      const excluded = (p: string) => p === path || p.startsWith(`${path}/`) || p.startsWith(`${path}\\`);
+      setResults(prev => prev.filter(r => !excluded(r.path)));
+      setSelectedIndex(0);
+      setStatus(t("status_excluded", { name: getFileName(path) }));
+    } catch (e) {
+      setStatus(String(e));
+    }
+  }
+
   async function handleAnnotate(path: string) {
     const result = await modal.prompt({
       title: t("annotation_add"),
@@ -420,7 +1037,7 @@ function App() {
   return (
     <>
       <div className="app-container" style={{ '--logo-url': `url(${logoSrc})` } as React.CSSProperties}>
-        <TitleBar />
+        {!docked && <TitleBar />}
         <Sidebar
           containers={containers}
           activeContainer={activeContainer}
@@ -432,9 +1049,16 @@ function App() {
           onCreateContainer={handleCreateContainer}
           onDeleteContainer={handleDeleteContainer}
           onReindexAll={handleReindexAll}
+          onReembed={handleReembed}
+          onReindexFolder={handleReindexFolder}
+          onRemoveFolder={handleRemoveFolder}
           onOpenSettings={() => setSettingsOpen(true)}
           onDeleteAnnotation={handleDeleteAnnotation}
-          onSelectAnnotation={(id) => { setSelectedAnnotationId(id); setQuery(""); }}
+          onSelectAnnotation={(id) => { setSelectedAnnotationId(id); setBrowseView(null); setQuery(""); }}
+          onBrowseTopics={() => { setBrowseView("topics"); setEntityName(null); setAskPath(null); setSelectedAnnotationId(null); }}
+          onBrowseTimeline={() => { setBrowseView("timeline"); setEntityName(null); setAskPath(null); setSelectedAnnotationId(null); }}
+          onUnlock={handleUnlock}
+          onSetLocked={handleSetLocked}
         />
         <div className="main-content">
           <SearchBar
@@ -444,6 +1068,23 @@ function App() {
             isIndexing={isIndexing}
             onPickFolder={handlePickFolder}
             inputRef={searchInputRef}
+            languages={languages}
+            language={language}
+            onLanguageChange={setLanguage}
+            historyEnabled={historyEnabled}
+            asOf={asOf}
+            onAsOfChange={setAsOf}
+            deletedCount={deletedCount}
+            includeDeleted={includeDeleted}
+            onIncludeDeletedChange={setIncludeDeleted}
+            routedContainer={routedContainer}
+            onDismissRoute={() => setRouteDismissedFor(query)}
+            extensions={extensions}
+            extensionFilter={extensionFilter}
+            onExtensionFilterChange={setExtensionFilter}
+            folders={activeInfo?.indexed_paths ?? []}
+            folderFilter={folderFilter}
+            onFolderFilterChange={setFolderFilter}
           />
           {selectedAnnotation ? (
             <div className="annotation-detail-view">
@@ -467,18 +1108,65 @@ function App() {
                 </button>
               </div>
             </div>
-          ) : (
-            <ResultsList
-              results={results}
-              selectedIndex={selectedIndex}
-              setSelectedIndex={setSelectedIndex}
+          ) : askPath ? (
+            <AskFileView
+              path={askPath}
+              onOpenFile={(p) => { handleOpenFile(p).catch(() => { }); }}
+              onClose={() => setAskPath(null)}
+            />
+          ) : entityName ? (
+            <EntityView
+              name={entityName}
+              onSelectEntity={setEntityName}
+              onOpenFile={(p) => { handleOpenFile(p).catch(() => { }); }}
+              onClose={() => setEntityName(null)}
+            />
+          ) : browseView === "topics" ? (
+            <TopicsView
+              activeContainer={activeContainer}
+              onOpenFile={(p) => { handleOpenFile(p).catch(() => { }); }}
+              onClose={() => setBrowseView(null)}
+            />
+          ) : browseView === "timeline" ? (
+            <TimelineView
               activeContainer={activeContainer}
-              query={query}
               onOpenFile={(p) => { handleOpenFile(p).catch(() => { }); }}
-              onAnnotate={(p) => { handleAnnotate(p).catch(() => { }); }}
-              listRef={listRef}
-              hotkey={hotkey}
+              onClose={() => setBrowseView(null)}
             />
+          ) : (
+            <>
+              {quickAnswer && (
+                <QuickAnswer
+                  answer={quickAnswer}
+                  onCopy={(v) => { handleCopy(v, t("status_copied_answer")).catch(() => { }); }}
+                />
+              )}
+              {suggestion && (
+                <DidYouMean suggestion={suggestion} onAccept={setQuery} />
+              )}
+              <ResultsList
+                results={results}
+                selectedIndex={selectedIndex}
+                setSelectedIndex={setSelectedIndex}
+                activeContainer={activeContainer}
+                query={query}
+                onOpenFile={(p) => { handleOpenFile(p).catch(() => { }); }}
+                onAnnotate={(p) => { handleAnnotate(p).catch(() => { }); }}
+                onTogglePin={(p) => { handleTogglePin(p).catch(() => { }); }}
+                onIndexFolder={(dir) => { indexPaths([dir]).catch(() => { }); }}
+                onContextMenu={(result, x, y) => setContextMenu({ result, x, y })}
+                listRef={listRef}
+                hotkey={hotkey}
+                recent={showingRecent}
+                translations={translations}
+              />
+              <EntityChips path={results[selectedIndex]?.path ?? null} onSelect={setEntityName} />
+            </>
+          )}
+          {isDragOver && (
+            <div className="drop-overlay">
+              <span>{t("drop_to_index", { container: activeContainer })}</span>
+            </div>
           )}
           <StatusBar
             status={status}
@@ -487,12 +1175,41 @@ function App() {
             activeContainer={activeContainer}
             indexedFolderCount={activeInfo?.indexed_paths.length || 0}
             resultCount={results.length}
+            resultActions={resultActions}
+            endpointHealth={endpointHealth}
+            modelDownload={modelDownload}
           />
         </div>
       </div>
+      {contextMenu && (
+        <ResultContextMenu
+          menu={contextMenu}
+          onClose={closeContextMenu}
+          onOpen={(p) => { handleOpenFile(p).catch(() => { }); }}
+          onReveal={(p) => { handleRevealFile(p).catch(() => { }); }}
+          onCopyPath={(p) => { handleCopy(p, t("status_copied_path")).catch(() => { }); }}
+          onCopySnippet={(r) => { handleCopy(cleanSnippet(r.snippet), t("status_copied_snippet")).catch(() => { }); }}
+          onCopyContext={() => { handleCopyContext().catch(() => { }); }}
+          onAnnotate={(p) => { handleAnnotate(p).catch(() => { }); }}
+          onTogglePin={(p) => { handleTogglePin(p).catch(() => { }); }}
+          onExclude={(p) => { handleExcludePath(p).catch(() => { }); }}
+          onFindSimilar={(p) => { handleFindSimilar(p).catch(() => { }); }}
+          onAsk={setAskPath}
+          translated={!!translations[translationKey(contextMenu.result)]}
+          onTranslate={(r) => { handleTranslate(r).catch(() => { }); }}
+        />
+      )}
       <ModalProvider />
       <Settings open={settingsOpen} onClose={() => {
         setSettingsOpen(false);
+        invoke<{ hotkey: string; result_actions: ResultActions; search_debounce_ms: number; search_min_query_chars: number; version_history_days: number; window_mode: string }>("get_config").then((c) => {
+          setHotkey(c.hotkey);
+          setDocked(c.window_mode === "docked");
+          setResultActions(c.result_actions);
+          setSearchInput({ debounceMs: c.search_debounce_ms, minChars: c.search_min_query_chars });
+          setHistoryEnabled(c.version_history_days > 0);
+          if (c.version_history_days === 0) setAsOf(null);
+        }).catch(() => { });
         if (isFirstRunRef.current) {
           isFirstRunRef.current = false;
           invoke<{ provider_type: string; remote_endpoint: string; remote_api_key: string; remote_model: string; remote_dimensions: number; embedding_model: string }>("get_config").then((c) => {
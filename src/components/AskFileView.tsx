@@ -0,0 +1,84 @@
+import { useEffect, useRef, useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { MessageSquareText, RefreshCw } from "lucide-react";
+import type { FileAnswer } from "../types";
+import { useLocale } from "../i18n";
+
+interface Props {
+    path: string;
+    onOpenFile: (path: string) => void;
+    onClose: () => void;
+}
+
+export default function AskFileView({ path, onOpenFile, onClose }: Readonly<Props>) {
+    const { t } = useLocale();
+    const [question, setQuestion] = useState("");
+    const [found, setFound] = useState<FileAnswer | null>(null);
+    const [asking, setAsking] = useState(false);
+    const [error, setError] = useState<string | null>(null);
+    const inputRef = useRef<HTMLInputElement>(null);
+    const genRef = useRef(0);
+
+    useEffect(() => {
+        genRef.current++;
+        setQuestion("");
+        setFound(null);
+        setError(null);
+        setAsking(false);
+        inputRef.current?.focus();
+    }, [path]);
+
+    const ask = () => {
+        if (!question.trim() || asking) return;
+        const gen = ++genRef.current;
+        setAsking(true);
+        setError(null);
+        invoke<FileAnswer>("ask_file", { path, question: question.trim() })
+            .then((res) => { if (genRef.current === gen) setFound(res); })
+            .catch((e) => { if (genRef.current === gen) setError(String(e)); })
+            .finally(() => { if (genRef.current === gen) setAsking(false); });
+    };
+
+    return (
+        <div className="topics-view">
+            <div className="annotation-detail-header">
+                <button type="button" className="annotation-detail-close" onClick={onClose}>✕</button>
+                <MessageSquareText size={14} />
+                <span className="annotation-detail-path" title={path}>{path.split(/[\\/]/).pop()}</span>
+            </div>
+            <form className="ask-file-form" onSubmit={(e) => { e.preventDefault(); ask(); }}>
+                <input
+                    ref={inputRef}
+                    type="text"
+                    className="ask-file-input"
+                    placeholder={t("ask_placeholder")}
+                    value={question}
+                    onChange={(e) => setQuestion(e.target.value)}
+                />
+                <button type="submit" className="ask-file-submit" disabled={asking || !question.trim()}>
+                    {asking ? <RefreshCw size={12} className="reindex-spin" /> : t("ask_submit")}
+                </button>
+            </form>
+            {error && <div className="topics-empty">{error}</div>}
+            {found?.answer && <div className="annotation-detail-note">{found.answer}</div>}
+            {found && !found.answer && (
+                <div className="annotation-detail-meta">
+                    {found.passages.length === 0 ? t("ask_no_text") : t("ask_no_endpoint")}
+                </div>
+            )}
+            {found && found.passages.length > 0 && (
+                <div className="topic-files">
+                    <div className="annotation-detail-meta">{t("ask_passages")}</div>
+                    {found.passages.map(p => (
+                        <button key={p.n} type="button" className="ask-file-passage" onClick={() => onOpenFile(path)}>
+                            <span className="ask-file-passage-label">
+                                [{p.n}] {p.page ?? (p.line === null ? "" : t("ask_line", { line: String(p.line) }))}
+                            </span>
+                            <span className="ask-file-passage-text">{p.text.trim()}</span>
+                        </button>
+                    ))}
+                </div>
+            )}
+        </div>
+    );
+}
@@ -0,0 +1,21 @@
+import { SpellCheck } from "lucide-react";
+import { useLocale } from "../i18n";
+
+interface Props {
+    suggestion: string;
+    onAccept: (query: string) => void;
+}
+
+export default function DidYouMean({ suggestion, onAccept }: Readonly<Props>) {
+    const { t } = useLocale();
+
+    return (
+        <div className="px-3 mt-2">
+            <button type="button" className="did-you-mean" onClick={() => onAccept(suggestion)}>
+                <SpellCheck size={14} className="did-you-mean-icon" />
+                <span>{t("did_you_mean")}</span>
+                <span className="did-you-mean-query">{suggestion}</span>
+            </button>
+        </div>
+    );
+}
@@ -0,0 +1,58 @@
+import { useEffect, useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { Box, User, Wrench } from "lucide-react";
+import type { Entity, EntityKind } from "../types";
+import { useLocale } from "../i18n";
+
+// Chips shown at most, the most mentioned first.
+const MAX_CHIPS = 8;
+
+export function EntityIcon({ kind }: Readonly<{ kind: EntityKind }>) {
+    if (kind === "person") return <User size={11} />;
+    if (kind === "project") return <Box size={11} />;
+    return <Wrench size={11} />;
+}
+
+interface Props {
+    path: string | null;
+    onSelect: (name: string) => void;
+}
+
+export default function EntityChips({ path, onSelect }: Readonly<Props>) {
+    const { t } = useLocale();
+    const [entities, setEntities] = useState<Entity[]>([]);
+
+    useEffect(() => {
+        setEntities([]);
+        if (!path) return;
+        let cancelled = false;
+        // Waits for arrowing through results to settle before asking.
+        const timer = setTimeout(() => {
+            invoke<Entity[]>("get_entities", { path })
+                .then((list) => { if (!cancelled) setEntities(list.filter(e => e.files > 1)); })
+                .catch(() => { });
+        }, 150);
+        return () => { cancelled = true; clearTimeout(timer); };
+    }, [path]);
+
+    if (entities.length === 0) return null;
+
+    return (
+        <div className="entity-chips" aria-label={t("entities_label")}>
+            {entities.slice(0, MAX_CHIPS).map(entity => (
+                <button
+                    key={entity.name}
+                    type="button"
+                    className="entity-chip"
+                    data-kind={entity.kind}
+                    title={t("entities_chip_hint", { count: String(entity.files - 1) })}
+                    onClick={() => onSelect(entity.name)}
+                >
+                    <EntityIcon kind={entity.kind} />
+                    <span>{entity.name}</span>
+                    <span className="entity-chip-count">{entity.files - 1}</span>
+                </button>
+            ))}
+        </div>
+    );
+}
@@ -0,0 +1,60 @@
+import { useEffect, useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import type { EntityFiles } from "../types";
+import { useLocale } from "../i18n";
+import { EntityIcon } from "./EntityChips";
+
+interface Props {
+    name: string;
+    onSelectEntity: (name: string) => void;
+    onOpenFile: (path: string) => void;
+    onClose: () => void;
+}
+
+export default function EntityView({ name, onSelectEntity, onOpenFile, onClose }: Readonly<Props>) {
+    const { t } = useLocale();
+    const [found, setFound] = useState<EntityFiles | null>(null);
+    const [loading, setLoading] = useState(true);
+
+    useEffect(() => {
+        setLoading(true);
+        invoke<EntityFiles | null>("get_entity_files", { name })
+            .then(setFound)
+            .catch(() => setFound(null))
+            .finally(() => setLoading(false));
+    }, [name]);
+
+    return (
+        <div className="topics-view">
+            <div className="annotation-detail-header">
+                <button type="button" className="annotation-detail-close" onClick={onClose}>✕</button>
+                {found && <EntityIcon kind={found.kind} />}
+                <span className="annotation-detail-path">{found?.name ?? name}</span>
+            </div>
+            {!loading && !found && <div className="topics-empty">{t("entities_none", { name })}</div>}
+            {found && found.related.length > 0 && (
+                <div className="entity-chips">
+                    <span className="entity-chips-label">{t("entities_related")}</span>
+                    {found.related.map(r => (
+                        <button key={r.name} type="button" className="entity-chip" data-kind={r.kind} onClick={() => onSelectEntity(r.name)}>
+                            <EntityIcon kind={r.kind} />
+                            <span>{r.name}</span>
+                            <span className="entity-chip-count">{r.shared_files}</span>
+                        </button>
+                    ))}
+                </div>
+            )}
+            {found && (
+                <div className="topic-files">
+                    <div className="annotation-detail-meta">{t("entities_files", { count: String(found.files.length) })}</div>
+                    {found.files.map(f => (
+                        <button key={f.path} type="button" className="topic-file" title={f.path} onClick={() => onOpenFile(f.path)}>
+                            <span className="topic-file-name">{f.path.split(/[\\/]/).pop()}</span>
+                            <span className="topic-file-path">{f.path}</span>
+                        </button>
+                    ))}
+                </div>
+            )}
+        </div>
+    );
+}
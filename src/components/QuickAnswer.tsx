@@ -0,0 +1,32 @@
+import { Calculator, Copy } from "lucide-react";
+import { useLocale } from "../i18n";
+
+export interface QuickAnswerData {
+    expression: string;
+    value: string;
+}
+
+interface Props {
+    answer: QuickAnswerData;
+    onCopy: (value: string) => void;
+}
+
+export default function QuickAnswer({ answer, onCopy }: Readonly<Props>) {
+    const { t } = useLocale();
+
+    return (
+        <div className="px-3 mt-2">
+            <button
+                type="button"
+                className="quick-answer"
+                title={t("quick_answer_copy")}
+                onClick={() => onCopy(answer.value)}
+            >
+                <Calculator size={16} className="quick-answer-icon" />
+                <span className="quick-answer-expression">{answer.expression} =</span>
+                <span className="quick-answer-value">{answer.value}</span>
+                <Copy size={12} className="quick-answer-copy" />
+            </button>
+        </div>
+    );
+}
@@ -0,0 +1,111 @@
+import { useEffect, useRef } from "react";
+import { ExternalLink, FolderOpen, Copy, TextQuote, ClipboardList, MessageSquarePlus, EyeOff, FolderX, Sparkles, Star, StarOff, MessageSquareText, Languages } from "lucide-react";
+import type { SearchResult } from "../types";
+import { useLocale } from "../i18n";
+import "./ResultContextMenu.css";
+
+function parentFolder(path: string): string {
+    return path.replace(/[\\/][^\\/]*$/, "");
+}
+
+export interface ResultContextMenuState {
+    x: number;
+    y: number;
+    result: SearchResult;
+}
+
+interface Props {
+    menu: ResultContextMenuState;
+    onClose: () => void;
+    onOpen: (path: string) => void;
+    onReveal: (path: string) => void;
+    onCopyPath: (path: string) => void;
+    onCopySnippet: (result: SearchResult) => void;
+    onCopyContext: () => void;
+    onAnnotate: (path: string) => void;
+    onTogglePin: (path: string) => void;
+    onExclude: (path: string) => void;
+    onFindSimilar: (path: string) => void;
+    onAsk: (path: string) => void;
+    translated: boolean;
+    onTranslate: (result: SearchResult) => void;
+}
+
+export default function ResultContextMenu({
+    menu, onClose, onOpen, onReveal, onCopyPath, onCopySnippet, onCopyContext, onAnnotate, onTogglePin, onExclude, onFindSimilar, onAsk,
+    translated, onTranslate,
+}: Readonly<Props>) {
+    const { t } = useLocale();
+    const ref = useRef<HTMLDivElement>(null);
+
+    useEffect(() => {
+        const handlePointer = (e: MouseEvent) => {
+            if (ref.current && !ref.current.contains(e.target as Node)) onClose();
+        };
+        const handleKey = (e: KeyboardEvent) => {
+            if (e.key === "Escape") {
+                e.stopPropagation();
+                onClose();
+            }
+        };
+        globalThis.addEventListener("mousedown", handlePointer);
+        globalThis.addEventListener("keydown", handleKey, true);
+        globalThis.addEventListener("blur", onClose);
+        return () => {
+            globalThis.removeEventListener("mousedown", handlePointer);
+            globalThis.removeEventListener("keydown", handleKey, true);
+            globalThis.removeEventListener("blur", onClose);
+        };
+    }, [onClose]);
+
+    useEffect(() => {
+        const el = ref.current;
+        if (!el) return;
+        const rect = el.getBoundingClientRect();
+        const x = Math.min(menu.x, globalThis.innerWidth - rect.width - 4);
+        const y = Math.min(menu.y, globalThis.innerHeight - rect.height - 4);
+        el.style.left = `${Math.max(4, x)}px`;
+        el.style.top = `${Math.max(4, y)}px`;
+    }, [menu]);
+
+    const path = menu.result.path;
+    const items = [
+        { key: "open", icon: <ExternalLink size={13} />, label: t("menu_open"), action: () => onOpen(path) },
+        { key: "reveal", icon: <FolderOpen size={13} />, label: t("menu_reveal"), action: () => onReveal(path) },
+        { key: "copy_path", icon: <Copy size={13} />, label: t("menu_copy_path"), action: () => onCopyPath(path) },
+        { key: "copy_snippet", icon: <TextQuote size={13} />, label: t("menu_copy_snippet"), action: () => onCopySnippet(menu.result) },
+        { key: "copy_context", icon: <ClipboardList size={13} />, label: t("menu_copy_context"), action: onCopyContext },
+        { key: "annotate", icon: <MessageSquarePlus size={13} />, label: t("annotation_add"), action: () => onAnnotate(path) },
+        menu.result.pinned
+            ? { key: "pin", icon: <StarOff size={13} />, label: t("menu_unpin"), action: () => onTogglePin(path) }
+            : { key: "pin", icon: <Star size={13} />, label: t("menu_pin"), action: () => onTogglePin(path) },
+        { key: "similar", icon: <Sparkles size={13} />, label: t("menu_find_similar"), action: () => onFindSimilar(path) },
+        { key: "ask", icon: <MessageSquareText size={13} />, label: t("menu_ask_file"), action: () => onAsk(path) },
+        { key: "translate", icon: <Languages size={13} />, label: t(translated ? "menu_show_original" : "menu_translate"), action: () => onTranslate(menu.result) },
+        { key: "exclude", icon: <EyeOff size={13} />, label: t("menu_exclude"), action: () => onExclude(path), danger: true },
+        { key: "exclude_folder", icon: <FolderX size={13} />, label: t("menu_exclude_folder"), action: () => onExclude(parentFolder(path)), danger: true },
+    ];
+
+    return (
+        <div
+            ref={ref}
+            className="result-context-menu"
+            role="menu"
+            style={{ left: menu.x, top: menu.y }}
+            onContextMenu={(e) => e.preventDefault()}
+        >
+            {items.map((item) => (
+                <button
+                    key={item.key}
+                    type="button"
+                    role="menuitem"
+                    className={`result-context-item${item.danger ? " danger" : ""}`}
+                    onClick={() => { onClose(); item.action(); }}
+                >
+                    {item.icon}
+                    <span>{item.label}</span>
+                </button>
+            ))}
+        </div>
+    );
+}
@@ -1,10 +1,12 @@
 import { useRef, useEffect, useState } from "react";
 import { List, type ListImperativeAPI } from "react-window";
 import {
-    FileText, FileCode, FileJson, Image as ImageIcon, File, Box, MessageSquarePlus,
+    FileText, FileCode, FileJson, FileCog, Image as ImageIcon, File, Globe, Box, MessageSquarePlus, Star, History, FolderPlus, FolderTree, Languages,
 } from "lucide-react";
 import type { SearchResult } from "../types";
 import { useLocale } from "../i18n";
+import { getExtension, getFileKind, type FileKind } from "../fileTypes";
+import { JUMP_RESULTS } from "../keybindings";
 
 function getScoreColor(score: number): string {
     if (score > 80) return "bg-green-500/10 text-green-400";
@@ -16,17 +18,29 @@ function getFileName(path: string): string {
     return path.split(/[\\/]/).pop() || path;
 }
 
-function getFileIcon(path: string) {
-    const ext = path.split(".").pop()?.toLowerCase() || "";
-    const props = { className: "w-5 h-5 text-gray-400" };
-    switch (ext) {
-        case "pdf": return <FileText {...props} />;
-        case "txt": case "md": return <FileText {...props} />;
-        case "rs": case "ts": case "js": case "py": return <FileCode {...props} />;
-        case "json": return <FileJson {...props} />;
-        case "png": case "jpg": return <ImageIcon {...props} />;
-        default: return <File {...props} />;
-    }
+function getParentDir(path: string): string {
+    return path.replace(/[\\/][^\\/]*$/, "");
+}
+
+/** Which translation belongs to a result: the same file can show another excerpt for the next query. */
+export function translationKey(result: SearchResult): string {
+    return `${result.path}\n${result.excerpt}`;
+}
+
+const kindIcons: Record<FileKind, typeof File> = {
+    code: FileCode,
+    web: Globe,
+    doc: FileText,
+    pdf: FileText,
+    data: FileJson,
+    config: FileCog,
+    image: ImageIcon,
+    other: File,
+};
+
+function getFileIcon(kind: FileKind) {
+    const Icon = kindIcons[kind];
+    return <Icon className="w-5 h-5 file-kind-icon" data-kind={kind} />;
 }
 
 interface RowData {
@@ -35,13 +49,34 @@ interface RowData {
     setSelectedIndex: (index: number) => void;
     handleOpenFile: (path: string) => void;
     handleAnnotate: (path: string) => void;
+    handleTogglePin: (path: string) => void;
+    handleIndexFolder: (dir: string) => void;
+    handleContextMenu: (index: number, x: number, y: number) => void;
+    translations: Record<string, string>;
+    translatedLabel: string;
     noPreviewText: string;
+    annotationLabel: string;
+    summaryLabel: string;
+    imageOcrLabel: string;
+    deletedLabel: string;
+    unindexedLabel: string;
+    unindexedHint: string;
+    remoteLabel: (name: string, ms: number) => string;
+    indexFolderLabel: string;
+    pinLabel: string;
+    unpinLabel: string;
+    jumpLabel: (n: number) => string;
+    showScore: boolean;
 }
 
-const Row = ({ index, style, results, selectedIndex, setSelectedIndex, handleOpenFile, handleAnnotate, noPreviewText }: { index: number; style: React.CSSProperties } & RowData) => {
+const Row = ({ index, style, results, selectedIndex, setSelectedIndex, handleOpenFile, handleAnnotate, handleTogglePin, handleIndexFolder, handleContextMenu, translations, translatedLabel, noPreviewText, annotationLabel, summaryLabel, imageOcrLabel, deletedLabel, unindexedLabel, unindexedHint, remoteLabel, indexFolderLabel, pinLabel, unpinLabel, jumpLabel, showScore }: { index: number; style: React.CSSProperties } & RowData) => {
     const result = results[index];
     const isSelected = index === selectedIndex;
     const isAnnotation = result.snippet?.startsWith("[annotation]");
+    const isSummary = result.snippet?.startsWith("[summary]");
+    const kind = getFileKind(result.path);
+    const ext = getExtension(result.path);
+    const translation = translations[translationKey(result)];
 
     return (
         <div style={style} className="px-3">
@@ -50,34 +85,78 @@ const Row = ({ index, style, results, selectedIndex, setSelectedIndex, handleOpe
                 key={result.path}
                 data-active={isSelected}
                 onClick={() => { setSelectedIndex(index); handleOpenFile(result.path); }}
+                onContextMenu={(e) => { e.preventDefault(); setSelectedIndex(index); handleContextMenu(index, e.clientX, e.clientY); }}
                 className="result-item w-full text-left flex items-start gap-3 cursor-default outline-none select-none group h-full"
             >
                 <div className="pt-0.5 shrink-0 opacity-80 group-hover:opacity-100 transition-opacity">
-                    {isAnnotation ? <MessageSquarePlus className="w-5 h-5 text-[--color-fill-accent-default]" /> : getFileIcon(result.path)}
+                    {isAnnotation && <MessageSquarePlus className="w-5 h-5 text-[--color-fill-accent-default]" />}
+                    {isSummary && <FolderTree className="w-5 h-5 text-[--color-fill-accent-default]" />}
+                    {!isAnnotation && !isSummary && getFileIcon(kind)}
                 </div>
                 <div className="flex-1 min-w-0">
                     <div className="flex justify-between items-baseline gap-2">
                         <h4 className="text-body truncate leading-tight">
                             {getFileName(result.path)}
-                            {isAnnotation && <span className="annotation-badge">annotation</span>}
+                            {isAnnotation && <span className="annotation-badge">{annotationLabel}</span>}
+                            {isSummary && <span className="annotation-badge">{summaryLabel}</span>}
+                            {!isAnnotation && !isSummary && kind === "image" && <span className="file-kind-badge" data-kind={kind}>{imageOcrLabel}</span>}
+                            {!isAnnotation && !isSummary && kind !== "image" && kind !== "other" && ext && <span className="file-kind-badge" data-kind={kind}>{ext}</span>}
+                            {result.deleted && <span className="deleted-badge">{deletedLabel}</span>}
+                            {result.unindexed && <span className="unindexed-badge">{unindexedLabel}</span>}
+                            {result.remote && <span className="unindexed-badge" title={result.remote.container}>{remoteLabel(result.remote.name, result.remote.latency_ms)}</span>}
+                            {result.context && <span className="result-context" title={result.context}>{result.context}</span>}
                         </h4>
                         <div className="flex items-center gap-1 shrink-0">
                             <button
                                 type="button"
-                                className="annotate-btn"
-                                title="Add annotation"
-                                onClick={(e) => { e.stopPropagation(); handleAnnotate(result.path); }}
+                                className={`pin-btn${result.pinned ? " pinned" : ""}`}
+                                title={result.pinned ? unpinLabel : pinLabel}
+                                aria-label={result.pinned ? unpinLabel : pinLabel}
+                                aria-pressed={result.pinned}
+                                onClick={(e) => { e.stopPropagation(); handleTogglePin(result.path); }}
                             >
-                                <MessageSquarePlus className="w-3.5 h-3.5" />
+                                <Star className="w-3.5 h-3.5" />
                             </button>
-                            <span className={`text-[10px] font-sans px-1.5 rounded-full ${getScoreColor(result.score)} bg-opacity-20`}>
-                                {Math.round(result.score)}%
-                            </span>
+                            {result.unindexed ? (
+                                <button
+                                    type="button"
+                                    className="annotate-btn"
+                                    title={indexFolderLabel}
+                                    aria-label={indexFolderLabel}
+                                    onClick={(e) => { e.stopPropagation(); handleIndexFolder(getParentDir(result.path)); }}
+                                >
+                                    <FolderPlus className="w-3.5 h-3.5" />
+                                </button>
+                            ) : !isSummary && !result.remote && (
+                                <button
+                                    type="button"
+                                    className="annotate-btn"
+                                    title="Add annotation"
+                                    onClick={(e) => { e.stopPropagation(); handleAnnotate(result.path); }}
+                                >
+                                    <MessageSquarePlus className="w-3.5 h-3.5" />
+                                </button>
+                            )}
+                            {index < JUMP_RESULTS && (
+                                <kbd className="result-jump-key" title={jumpLabel(index + 1)}>{index + 1}</kbd>
+                            )}
+                            {showScore && (
+                                <span className={`text-[10px] font-sans px-1.5 rounded-full ${getScoreColor(result.score)} bg-opacity-20`}>
+                                    {Math.round(result.score)}%
+                                </span>
+                            )}
                         </div>
                     </div>
-                    <div className="truncate text-caption mt-0.5 opacity-60">
-                        {isAnnotation ? result.snippet.replace("[annotation] ", "") : (result.snippet || <span className="italic opacity-50">{noPreviewText}</span>)}
-                    </div>
+                    {translation ? (
+                        <div className="truncate text-caption mt-0.5 opacity-60" title={`${translatedLabel}: ${result.excerpt}`}>
+                            <Languages className="w-3 h-3 inline mr-1 align-[-2px]" />
+                            {translation}
+                        </div>
+                    ) : (
+                        <div className="truncate text-caption mt-0.5 opacity-60">
+                            {result.excerpt || <span className="italic opacity-50">{result.unindexed ? unindexedHint : noPreviewText}</span>}
+                        </div>
+                    )}
                     <div className="truncate text-[10px] opacity-40 mt-0.5 font-mono">
                         {result.path}
                     </div>
@@ -95,12 +174,17 @@ interface ResultsListProps {
     query: string;
     onOpenFile: (path: string) => void;
     onAnnotate: (path: string) => void;
+    onTogglePin: (path: string) => void;
+    onIndexFolder: (dir: string) => void;
+    onContextMenu: (result: SearchResult, x: number, y: number) => void;
     listRef: React.RefObject<ListImperativeAPI | null>;
     hotkey: string;
+    recent: boolean;
+    translations: Record<string, string>;
 }
 
 export default function ResultsList({
-    results, selectedIndex, setSelectedIndex, activeContainer, query, onOpenFile, onAnnotate, listRef, hotkey,
+    results, selectedIndex, setSelectedIndex, activeContainer, query, onOpenFile, onAnnotate, onTogglePin, onIndexFolder, onContextMenu, listRef, hotkey, recent, translations,
 }: Readonly<ResultsListProps>) {
     const { t } = useLocale();
     const containerRef = useRef<HTMLDivElement>(null);
@@ -123,41 +207,49 @@ export default function ResultsList({
     }, [selectedIndex, results, listRef]);
 
     return (
-        <div className="flex-1 overflow-hidden min-h-0 mt-2 pb-3" ref={containerRef}>
-            {results.length === 0 && !query && (
-                <div className="h-full flex flex-col items-center justify-center text-[--color-text-muted] select-none opacity-60">
-                    <Box size={40} className="mb-4 opacity-40 text-[--color-fill-accent-default]" strokeWidth={1} />
-                    <p className="text-body font-medium">{activeContainer}</p>
-                    <p className="text-caption mt-1">{t("results_container_active")}</p>
-
-                    <div className="mt-8 flex flex-col gap-2 items-center">
-                        <p className="text-[10px] uppercase tracking-wider opacity-60">{t("results_shortcuts")}</p>
-                        <div className="flex gap-4 opacity-50 text-xs font-mono">
-                            <span>{t("results_shortcut_index")}</span>
-                            <span>{hotkey} : {t("results_shortcut_toggle").split(" : ").pop()}</span>
-                        </div>
-                    </div>
+        <div className="flex-1 flex flex-col overflow-hidden min-h-0 mt-2">
+            {recent && results.length > 0 && (
+                <div className="recent-header">
+                    <History size={11} />
+                    <span>{t("results_recent")}</span>
                 </div>
             )}
+            <div className="flex-1 overflow-hidden min-h-0 pb-3" ref={containerRef}>
+                {results.length === 0 && !query && (
+                    <div className="h-full flex flex-col items-center justify-center text-[--color-text-muted] select-none opacity-60">
+                        <Box size={40} className="mb-4 opacity-40 text-[--color-fill-accent-default]" strokeWidth={1} />
+                        <p className="text-body font-medium">{activeContainer}</p>
+                        <p className="text-caption mt-1">{t("results_container_active")}</p>
 
-            {results.length === 0 && query && (
-                <div className="h-full flex flex-col items-center justify-center text-[--color-text-muted] select-none opacity-60">
-                    <p className="text-body font-medium">{t("results_no_results")}</p>
-                    <p className="text-caption mt-1">{t("results_in_container", { container: activeContainer })}</p>
-                </div>
-            )}
+                        <div className="mt-8 flex flex-col gap-2 items-center">
+                            <p className="text-[10px] uppercase tracking-wider opacity-60">{t("results_shortcuts")}</p>
+                            <div className="flex gap-4 opacity-50 text-xs font-mono">
+                                <span>{t("results_shortcut_index")}</span>
+                                <span>{hotkey} : {t("results_shortcut_toggle").split(" : ").pop()}</span>
+                            </div>
+                        </div>
+                    </div>
+                )}
 
-            {results.length > 0 && dims.height > 0 && (
-                <List<RowData>
-                    listRef={listRef}
-                    style={{ width: dims.width, height: dims.height }}
-                    rowCount={results.length}
-                    rowHeight={78}
-                    rowProps={{ results, selectedIndex, setSelectedIndex, handleOpenFile: (p: string) => { onOpenFile(p); }, handleAnnotate: (p: string) => { onAnnotate(p); }, noPreviewText: t("results_no_preview") }}
-                    className="result-list-virtualized"
-                    rowComponent={Row}
-                />
-            )}
+                {results.length === 0 && query && (
+                    <div className="h-full flex flex-col items-center justify-center text-[--color-text-muted] select-none opacity-60">
+                        <p className="text-body font-medium">{t("results_no_results")}</p>
+                        <p className="text-caption mt-1">{t("results_in_container", { container: activeContainer })}</p>
+                    </div>
+                )}
+
+                {results.length > 0 && dims.height > 0 && (
+                    <List<RowData>
+                        listRef={listRef}
+                        style={{ width: dims.width, height: dims.height }}
+                        rowCount={results.length}
+                        rowHeight={78}
+                        rowProps={{ results, selectedIndex, setSelectedIndex, handleOpenFile: (p: string) => { onOpenFile(p); }, handleAnnotate: (p: string) => { onAnnotate(p); }, handleTogglePin: (p: string) => { onTogglePin(p); }, handleIndexFolder: (d: string) => { onIndexFolder(d); }, handleContextMenu: (i: number, x: number, y: number) => { onContextMenu(results[i], x, y); }, translations, translatedLabel: t("results_translated_from"), noPreviewText: t("results_no_preview"), annotationLabel: t("results_badge_annotation"), summaryLabel: t("results_badge_summary"), imageOcrLabel: t("results_badge_image_ocr"), deletedLabel: t("results_badge_deleted"), unindexedLabel: t("results_badge_unindexed"), unindexedHint: t("results_unindexed_hint"), remoteLabel: (name: string, ms: number) => t("results_badge_remote", { name, ms }), indexFolderLabel: t("results_index_folder"), pinLabel: t("menu_pin"), unpinLabel: t("menu_unpin"), jumpLabel: (n: number) => t("results_jump_hint", { n }), showScore: !recent }}
+                        className="result-list-virtualized"
+                        rowComponent={Row}
+                    />
+                )}
+            </div>
         </div>
     );
 }
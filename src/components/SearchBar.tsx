@@ -1,5 +1,20 @@
-import { Search, FolderPlus, Loader2 } from "lucide-react";
+import { Search, FolderPlus, Loader2, History, File, Braces, Trash2, Compass, Folder } from "lucide-react";
+import { invoke } from "@tauri-apps/api/core";
+import { useEffect, useMemo, useRef, useState } from "react";
 import { useLocale } from "../i18n";
+import { pastQueries } from "../queryHistory";
+import { isPathQuery } from "../pathQuery";
+import { isComposingKey } from "../keybindings";
+import type { Completion } from "../types";
+
+const MAX_SUGGESTIONS = 5;
+
+const KIND_ICONS = {
+    history: History,
+    file: File,
+    symbol: Braces,
+    folder: Folder,
+};
 
 interface SearchBarProps {
     query: string;
@@ -8,12 +23,140 @@ interface SearchBarProps {
     isIndexing: boolean;
     onPickFolder: () => void;
     inputRef: React.RefObject<HTMLInputElement | null>;
+    languages: [string, number][];
+    language: string | null;
+    onLanguageChange: (language: string | null) => void;
+    historyEnabled: boolean;
+    /** Day to search the files as of, `YYYY-MM-DD`, or null for now. */
+    asOf: string | null;
+    onAsOfChange: (day: string | null) => void;
+    /** Deleted files still kept as tombstones; the toggle shows when there are any. */
+    deletedCount: number;
+    includeDeleted: boolean;
+    onIncludeDeletedChange: (include: boolean) => void;
+    /** Container auto container picked for the query, when not the active one. */
+    routedContainer: string | null;
+    /** Search the active container for this query after all. */
+    onDismissRoute: () => void;
+    /** Most common extensions in the container with their file counts. */
+    extensions: [string, number][];
+    extensionFilter: string[];
+    onExtensionFilterChange: (extensions: string[]) => void;
+    /** The container's indexed folders; one can narrow the search to it. */
+    folders: string[];
+    folderFilter: string | null;
+    onFolderFilterChange: (folder: string | null) => void;
+}
+
+function folderName(path: string) {
+    return path.split(/[\\/]/).filter(Boolean).pop() ?? path;
 }
 
 export default function SearchBar({
     query, onQueryChange, activeContainer, isIndexing, onPickFolder, inputRef,
+    languages, language, onLanguageChange, historyEnabled, asOf, onAsOfChange,
+    deletedCount, includeDeleted, onIncludeDeletedChange, routedContainer, onDismissRoute,
+    extensions, extensionFilter, onExtensionFilterChange, folders, folderFilter, onFolderFilterChange,
 }: Readonly<SearchBarProps>) {
-    const { t } = useLocale();
+    const { t, locale } = useLocale();
+    const displayNames = useMemo(() => {
+        try {
+            return new Intl.DisplayNames([locale], { type: "language" });
+        } catch {
+            return null;
+        }
+    }, [locale]);
+    const languageName = (code: string) => displayNames?.of(code) ?? code;
+
+    const [suggestions, setSuggestions] = useState<Completion[]>([]);
+    const [highlighted, setHighlighted] = useState(0);
+    const [focused, setFocused] = useState(true);
+    // Text an input method is still composing, like romaji on its way to
+    // kana. It's shown but isn't the query until it's committed.
+    const [composition, setComposition] = useState<string | null>(null);
+    // The path Tab last put in the bar, so cycling keeps the list it came from.
+    const cycledRef = useRef<string | null>(null);
+
+    useEffect(() => {
+        if (query === cycledRef.current) return;
+        cycledRef.current = null;
+        setHighlighted(0);
+        if (!query.trim()) {
+            setSuggestions([]);
+            return;
+        }
+        let cancelled = false;
+        if (isPathQuery(query)) {
+            setSuggestions([]);
+            const timer = setTimeout(() => {
+                invoke<Completion[]>("complete_path", { partial: query })
+                    .then((found) => { if (!cancelled) setSuggestions(found.filter(s => s.text !== query)); })
+                    .catch(() => { });
+            }, 60);
+            return () => {
+                cancelled = true;
+                clearTimeout(timer);
+            };
+        }
+        const lower = query.toLowerCase();
+        const history: Completion[] = pastQueries(activeContainer)
+            .filter(q => q.toLowerCase().startsWith(lower) && q !== query)
+            .slice(0, MAX_SUGGESTIONS)
+            .map(text => ({ text, kind: "history" }));
+        setSuggestions(history);
+        const timer = setTimeout(() => {
+            invoke<Completion[]>("complete_query", { query })
+                .then((found) => {
+                    if (cancelled) return;
+                    const seen = new Set(history.map(s => s.text));
+                    const merged = [...history, ...found.filter(s => !seen.has(s.text))];
+                    setSuggestions(merged.slice(0, MAX_SUGGESTIONS));
+                })
+                .catch(() => { });
+        }, 120);
+        return () => {
+            cancelled = true;
+            clearTimeout(timer);
+        };
+    }, [query, activeContainer]);
+
+    // Hidden while composing, where the IME's own candidate window goes.
+    const showSuggestions = focused && composition === null && suggestions.length > 0;
+
+    function accept(text: string) {
+        setSuggestions([]);
+        onQueryChange(text);
+        inputRef.current?.focus();
+    }
+
+    function handleKeyDown(e: React.KeyboardEvent<HTMLInputElement>) {
+        if (!showSuggestions || isComposingKey(e.nativeEvent)) return;
+        // Paths: Tab cycles through the entries, and takes a lone one so
+        // the next Tab lists what's inside it.
+        if (e.key === "Tab" && isPathQuery(query) && suggestions.length > 1) {
+            e.preventDefault();
+            const step = e.shiftKey ? -1 : 1;
+            const next = cycledRef.current === query
+                ? (highlighted + step + suggestions.length) % suggestions.length
+                : highlighted;
+            setHighlighted(next);
+            cycledRef.current = suggestions[next].text;
+            onQueryChange(suggestions[next].text);
+            return;
+        }
+        if (e.key === "Tab" && !e.shiftKey) {
+            e.preventDefault();
+            accept(suggestions[highlighted].text);
+        } else if (e.key === "ArrowDown") {
+            e.preventDefault();
+            setHighlighted(i => (i + 1) % suggestions.length);
+        } else if (e.key === "ArrowUp") {
+            e.preventDefault();
+            setHighlighted(i => (i - 1 + suggestions.length) % suggestions.length);
+        } else if (e.key === "Escape") {
+            setSuggestions([]);
+        }
+    }
 
     return (
         <div className="search-wrapper shrink-0">
@@ -22,8 +165,21 @@ export default function SearchBar({
                 <input
                     ref={inputRef}
                     type="text"
-                    value={query}
-                    onChange={(e) => onQueryChange(e.target.value)}
+                    value={composition ?? query}
+                    onChange={(e) => {
+                        if (composition === null) onQueryChange(e.target.value);
+                        else setComposition(e.target.value);
+                    }}
+                    onCompositionStart={(e) => setComposition(e.currentTarget.value)}
+                    onCompositionEnd={(e) => {
+                        setComposition(null);
+                        onQueryChange(e.currentTarget.value);
+                    }}
+                    onKeyDown={handleKeyDown}
+                    onFocus={() => setFocused(true)}
+                    onBlur={() => setFocused(false)}
+                    aria-autocomplete="list"
+                    aria-expanded={showSuggestions}
                     placeholder={t("search_placeholder", { container: activeContainer })}
                     className="search-input"
                     autoFocus
@@ -35,7 +191,126 @@ export default function SearchBar({
                 >
                     {isIndexing ? <Loader2 className="animate-spin" size={18} /> : <FolderPlus size={18} />}
                 </button>
+                {showSuggestions && (
+                    <div className="search-suggestions" role="listbox" aria-label={t("search_suggestions")}>
+                        {suggestions.map((s, i) => {
+                            const Icon = KIND_ICONS[s.kind];
+                            return (
+                                <button
+                                    key={`${s.kind}:${s.text}`}
+                                    type="button"
+                                    role="option"
+                                    aria-selected={i === highlighted}
+                                    className="search-suggestion"
+                                    data-active={i === highlighted}
+                                    onMouseDown={(e) => e.preventDefault()}
+                                    onMouseEnter={() => setHighlighted(i)}
+                                    onClick={() => accept(s.text)}
+                                >
+                                    <Icon size={13} className="search-suggestion-icon" />
+                                    <span className="search-suggestion-text">{s.text}</span>
+                                    {i === highlighted && <kbd className="search-suggestion-key">Tab</kbd>}
+                                </button>
+                            );
+                        })}
+                    </div>
+                )}
             </div>
+            {(languages.length > 1 || historyEnabled || deletedCount > 0 || routedContainer) && (
+                <div className="language-chips">
+                    {routedContainer && (
+                        <button
+                            className="language-chip"
+                            data-active
+                            onClick={onDismissRoute}
+                            title={t("search_routed_desc", { container: activeContainer })}
+                        >
+                            <Compass size={12} className="chip-icon" />
+                            {t("search_routed", { container: routedContainer })}
+                        </button>
+                    )}
+                    {languages.length > 1 && (
+                        <>
+                            <button
+                                className="language-chip"
+                                data-active={language === null}
+                                onClick={() => onLanguageChange(null)}
+                            >
+                                {t("search_language_all")}
+                            </button>
+                            {languages.map(([code, count]) => (
+                                <button
+                                    key={code}
+                                    className="language-chip"
+                                    data-active={language === code}
+                                    onClick={() => onLanguageChange(language === code ? null : code)}
+                                    title={t("search_language_files", { count: String(count) })}
+                                >
+                                    {languageName(code)}
+                                </button>
+                            ))}
+                        </>
+                    )}
+                    {deletedCount > 0 && (
+                        <button
+                            className="language-chip"
+                            data-active={includeDeleted}
+                            aria-pressed={includeDeleted}
+                            onClick={() => onIncludeDeletedChange(!includeDeleted)}
+                            title={t("search_include_deleted_desc", { count: String(deletedCount) })}
+                        >
+                            <Trash2 size={12} className="chip-icon" />
+                            {t("search_include_deleted")}
+                        </button>
+                    )}
+                    {historyEnabled && (
+                        <label className="language-chip as-of-chip" data-active={asOf !== null} title={t("search_as_of_desc")}>
+                            <History size={12} />
+                            <input
+                                type="date"
+                                value={asOf ?? ""}
+                                max={new Date().toISOString().slice(0, 10)}
+                                aria-label={t("search_as_of")}
+                                onChange={(e) => onAsOfChange(e.target.value || null)}
+                            />
+                        </label>
+                    )}
+                </div>
+            )}
+            {(folders.length > 1 || extensions.length > 1) && (
+                <div className="language-chips" role="group" aria-label={t("search_filters")}>
+                    {folders.length > 1 && folders.map((folder) => (
+                        <button
+                            key={folder}
+                            className="language-chip"
+                            data-active={folderFilter === folder}
+                            aria-pressed={folderFilter === folder}
+                            onClick={() => onFolderFilterChange(folderFilter === folder ? null : folder)}
+                            title={folder}
+                        >
+                            <Folder size={12} className="chip-icon" />
+                            {folderName(folder)}
+                        </button>
+                    ))}
+                    {extensions.length > 1 && extensions.map(([ext, count]) => {
+                        const active = extensionFilter.includes(ext);
+                        return (
+                            <button
+                                key={ext}
+                                className="language-chip"
+                                data-active={active}
+                                aria-pressed={active}
+                                onClick={() => onExtensionFilterChange(
+                                    active ? extensionFilter.filter(e => e !== ext) : [...extensionFilter, ext],
+                                )}
+                                title={t("search_language_files", { count: String(count) })}
+                            >
+                                .{ext}
+                            </button>
+                        );
+                    })}
+                </div>
+            )}
         </div>
     );
 }
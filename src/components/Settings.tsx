@@ -1,10 +1,15 @@
 import { useState, useEffect } from "react";
-import { Settings as SettingsIcon, X } from "lucide-react";
+import { Settings as SettingsIcon, X, SlidersHorizontal, FolderTree, Search, Cloud, Keyboard, ShieldCheck } from "lucide-react";
 import { invoke } from "@tauri-apps/api/core";
 import { useLocale } from "../i18n";
 import GeneralSettings from "./settings/GeneralSettings";
 import IndexingSettings from "./settings/IndexingSettings";
 import SearchSettings from "./settings/SearchSettings";
+import ProviderSettings from "./settings/ProviderSettings";
+import HotkeySettings from "./settings/HotkeySettings";
+import PrivacySettings from "./settings/PrivacySettings";
+import type { ResultActions } from "../keybindings";
+import { applyAppearance, type ThemeMode } from "../theme";
 import "./Settings.css";
 
 interface AppConfig {
@@ -17,6 +22,7 @@ interface AppConfig {
     chunk_overlap: number | null;
     extra_extensions: string[];
     excluded_extensions: string[];
+    watch_ignored_dirs: string[];
     provider_type: string;
     remote_endpoint: string;
     remote_api_key: string;
@@ -29,10 +35,53 @@ interface AppConfig {
     hyde_model: string;
     hyde_api_key: string;
     query_router_enabled: boolean;
+    auto_container: boolean;
     mmr_enabled: boolean;
     mmr_lambda: number;
+    fts_stemming: boolean;
+    synonyms: string[];
+    model_idle_timeout_mins: number;
+    throttle_on_battery: boolean;
+    throttle_cpu_percent: number;
+    result_actions: ResultActions;
+    search_debounce_ms: number;
+    search_min_query_chars: number;
+    context_copy_tokens: number;
+    theme: ThemeMode;
+    accent_color: string;
+    window_width: number;
+    window_height: number;
+    max_visible_results: number;
+    ui_scale: number;
+    window_placement: "keep" | "primary" | "cursor" | "focused";
+    remember_window_per_monitor: boolean;
+    window_mode: "popup" | "docked";
+    recent_files_enabled: boolean;
+    notify_on_complete: boolean;
+    webhooks: { url: string; events: ("indexing-complete" | "indexing-error" | "files-changed")[] }[];
+    post_index_command: string;
+    read_only: boolean;
+    read_only_session: boolean;
+    offline: boolean;
+    sync_folder: string;
+    portable: boolean;
+    index_quota_mb: number;
+    index_quota_policy: "stop" | "evict";
+    index_size_mb: number | null;
+    version_history_days: number;
 }
 
+type SettingsTab = "general" | "indexing" | "search" | "providers" | "privacy" | "hotkeys";
+
+const tabs: { id: SettingsTab; icon: React.ReactNode }[] = [
+    { id: "general", icon: <SlidersHorizontal size={13} /> },
+    { id: "indexing", icon: <FolderTree size={13} /> },
+    { id: "search", icon: <Search size={13} /> },
+    { id: "providers", icon: <Cloud size={13} /> },
+    { id: "privacy", icon: <ShieldCheck size={13} /> },
+    { id: "hotkeys", icon: <Keyboard size={13} /> },
+];
+
 interface SettingsProps {
     open: boolean;
     onClose: () => void;
@@ -50,28 +99,46 @@ export function SettingsButton({ onClick }: Readonly<{ onClick: () => void }>) {
 export default function Settings({ open, onClose }: Readonly<SettingsProps>) {
     const { t } = useLocale();
     const [config, setConfig] = useState<AppConfig | null>(null);
-    const [hotkeyDraft, setHotkeyDraft] = useState("");
-    const [hotkeyDirty, setHotkeyDirty] = useState(false);
+    const [tab, setTab] = useState<SettingsTab>("general");
+    const [error, setError] = useState<string | null>(null);
     const [extraExtDraft, setExtraExtDraft] = useState("");
     const [excludedExtDraft, setExcludedExtDraft] = useState("");
+    const [ignoredDirsDraft, setIgnoredDirsDraft] = useState("");
+    const [remoteEndpointDraft, setRemoteEndpointDraft] = useState("");
+    const [remoteApiKeyDraft, setRemoteApiKeyDraft] = useState("");
+    const [remoteModelDraft, setRemoteModelDraft] = useState("");
+    const [remoteDimsDraft, setRemoteDimsDraft] = useState("");
+    const [providerChanged, setProviderChanged] = useState(false);
 
     useEffect(() => {
         if (open) {
+            setError(null);
+            setProviderChanged(false);
             invoke<AppConfig>("get_config").then((c) => {
                 setConfig(c);
-                setHotkeyDraft(c.hotkey);
-                setHotkeyDirty(false);
                 setExtraExtDraft(c.extra_extensions.join(", "));
                 setExcludedExtDraft(c.excluded_extensions.join(", "));
+                setIgnoredDirsDraft(c.watch_ignored_dirs.join(", "));
+                setRemoteEndpointDraft(c.remote_endpoint);
+                setRemoteApiKeyDraft(c.remote_api_key);
+                setRemoteModelDraft(c.remote_model);
+                setRemoteDimsDraft(String(c.remote_dimensions));
             });
         }
     }, [open]);
 
     async function updateField(updates: Record<string, unknown>) {
-        await invoke("update_config", { updates });
+        try {
+            await invoke("update_config", { updates });
+            setError(null);
+        } catch (e) {
+            setError(String(e));
+        }
         const updated = await invoke<AppConfig>("get_config");
         setConfig(updated);
-        setHotkeyDraft(updated.hotkey);
+        if ("theme" in updates || "accent_color" in updates) {
+            applyAppearance(updated, await invoke<boolean>("is_vibrancy_active"));
+        }
     }
 
     if (!open || !config) return null;
@@ -86,33 +153,85 @@ export default function Settings({ open, onClose }: Readonly<SettingsProps>) {
                     </button>
                 </div>
 
-                <div className="settings-body">
-                    <GeneralSettings
-                        config={config}
-                        hotkeyDraft={hotkeyDraft}
-                        hotkeyDirty={hotkeyDirty}
-                        onHotkeyChange={setHotkeyDraft}
-                        updateField={updateField}
-                        setHotkeyDirty={setHotkeyDirty}
-                    />
-
-                    <div className="settings-group">
-                        <div className="settings-section-title">{t("settings_section_indexing")}</div>
-
-                        <IndexingSettings
-                            config={config}
-                            extraExtDraft={extraExtDraft}
-                            excludedExtDraft={excludedExtDraft}
-                            setExtraExtDraft={setExtraExtDraft}
-                            setExcludedExtDraft={setExcludedExtDraft}
-                            updateField={updateField}
-                        />
-                    </div>
+                <div className="settings-tabs" role="tablist">
+                    {tabs.map(({ id, icon }) => (
+                        <button
+                            key={id}
+                            type="button"
+                            role="tab"
+                            aria-selected={tab === id}
+                            className={`settings-tab${tab === id ? " active" : ""}`}
+                            onClick={() => setTab(id)}
+                        >
+                            {icon}
+                            {t(`settings_tab_${id}`)}
+                        </button>
+                    ))}
+                </div>
 
-                    <div className="settings-group">
-                        <div className="settings-section-title">{t("settings_section_search")}</div>
-                        <SearchSettings config={config} updateField={updateField} />
+                {error && (
+                    <div className="settings-error" role="alert">
+                        <span>{error}</span>
+                        <button type="button" onClick={() => setError(null)}>
+                            <X size={12} />
+                        </button>
                     </div>
+                )}
+
+                <div className="settings-body" role="tabpanel">
+                    {tab === "general" && (
+                        <GeneralSettings config={config} updateField={updateField} />
+                    )}
+
+                    {tab === "indexing" && (
+                        <div className="settings-group">
+                            <IndexingSettings
+                                config={config}
+                                extraExtDraft={extraExtDraft}
+                                excludedExtDraft={excludedExtDraft}
+                                setExtraExtDraft={setExtraExtDraft}
+                                setExcludedExtDraft={setExcludedExtDraft}
+                                ignoredDirsDraft={ignoredDirsDraft}
+                                setIgnoredDirsDraft={setIgnoredDirsDraft}
+                                updateField={updateField}
+                            />
+                        </div>
+                    )}
+
+                    {tab === "search" && (
+                        <div className="settings-group">
+                            <SearchSettings config={config} updateField={updateField} />
+                        </div>
+                    )}
+
+                    {tab === "providers" && (
+                        <div className="settings-group">
+                            <ProviderSettings
+                                config={config}
+                                remoteEndpointDraft={remoteEndpointDraft}
+                                remoteApiKeyDraft={remoteApiKeyDraft}
+                                remoteModelDraft={remoteModelDraft}
+                                remoteDimsDraft={remoteDimsDraft}
+                                providerChanged={providerChanged}
+                                setRemoteEndpointDraft={setRemoteEndpointDraft}
+                                setRemoteApiKeyDraft={setRemoteApiKeyDraft}
+                                setRemoteModelDraft={setRemoteModelDraft}
+                                setRemoteDimsDraft={setRemoteDimsDraft}
+                                setProviderChanged={setProviderChanged}
+                                updateField={updateField}
+                            />
+                        </div>
+                    )}
+
+                    {tab === "privacy" && (
+                        <div className="settings-group">
+                            <PrivacySettings config={config} />
+                        </div>
+                    )}
+
+                    {tab === "hotkeys" && (
+                        <HotkeySettings config={config} updateField={updateField} />
+                    )}
                 </div>
             </div>
         </div>
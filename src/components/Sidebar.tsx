@@ -1,17 +1,13 @@
 import {
-    Box, Plus, Trash2, FolderOpen, Folder, RefreshCw,
-    PanelLeftClose, PanelLeftOpen, Globe, MessageSquarePlus, ChevronDown, ChevronRight, Search,
+    Box, Plus, Trash2, FolderOpen, Folder, RefreshCw, Cpu,
+    PanelLeftClose, PanelLeftOpen, Globe, MessageSquarePlus, ChevronDown, ChevronRight, Search, X, Shapes, CalendarDays,
+    Lock, LockOpen, KeyRound,
 } from "lucide-react";
 import { SettingsButton } from "./Settings";
 import type { ContainerItem } from "../types";
 import { useLocale } from "../i18n";
 import { useState, useMemo } from "react";
 
-const localeLabels: Record<string, string> = {
-    en: "English",
-    tr: "Türkçe",
-};
-
 interface Annotation {
     id: string;
     path: string;
@@ -31,17 +27,25 @@ interface SidebarProps {
     onCreateContainer: () => void;
     onDeleteContainer: () => void;
     onReindexAll: () => void;
+    onReembed: () => void;
+    onReindexFolder: (path: string) => void;
+    onRemoveFolder: (path: string) => void;
     onOpenSettings: () => void;
     onDeleteAnnotation: (id: string) => void;
     onSelectAnnotation: (id: string) => void;
+    onBrowseTopics: () => void;
+    onBrowseTimeline: () => void;
+    onUnlock: (name: string) => void;
+    onSetLocked: (name: string, locked: boolean) => void;
 }
 
 export default function Sidebar({
     containers, activeContainer, isIndexing, sidebarOpen, annotations,
     onToggleSidebar, onSwitchContainer, onCreateContainer,
-    onDeleteContainer, onReindexAll, onOpenSettings, onDeleteAnnotation: _onDeleteAnnotation, onSelectAnnotation,
+    onDeleteContainer, onReindexAll, onReembed, onReindexFolder, onRemoveFolder, onOpenSettings, onDeleteAnnotation: _onDeleteAnnotation, onSelectAnnotation,
+    onBrowseTopics, onBrowseTimeline, onUnlock, onSetLocked,
 }: Readonly<SidebarProps>) {
-    const { t, locale, setLocale, availableLocales } = useLocale();
+    const { t, locale, setLocale, availableLocales, localeName } = useLocale();
     const [annotationsOpen, setAnnotationsOpen] = useState(false);
     const [annotationFilter, setAnnotationFilter] = useState("");
     const [annotationLimit, setAnnotationLimit] = useState(20);
@@ -93,7 +97,7 @@ export default function Sidebar({
                                     onClick={() => !isIndexing && onSwitchContainer(c.name)}
                                     disabled={isIndexing}
                                 >
-                                    <Box size={14} className="icon" />
+                                    {c.locked ? <Lock size={14} className="icon" /> : <Box size={14} className="icon" />}
                                     <div className="container-item-content">
                                         <span className="container-item-name">{c.name}</span>
                                         {c.description && (
@@ -102,6 +106,11 @@ export default function Sidebar({
                                         {c.provider_label && (
                                             <span className="container-item-desc" style={{ opacity: 0.3, fontSize: '9px' }}>{c.provider_label}</span>
                                         )}
+                                        {c.db_uri && (
+                                            <span className="container-item-desc" style={{ opacity: 0.3, fontSize: '9px' }} title={c.db_uri}>
+                                                {t(c.role === "reader" ? "sidebar_shared_reader" : "sidebar_shared_writer")}
+                                            </span>
+                                        )}
                                     </div>
                                 </button>
                                 {activeContainer === c.name && (
@@ -117,24 +126,77 @@ export default function Sidebar({
                                                         <div key={p} className="indexed-path-item" title={p}>
                                                             <FolderOpen size={10} className="indexed-path-icon" />
                                                             <span className="indexed-path-text">{p.split(/[\\/]/).slice(-2).join('/')}</span>
+                                                            <span className="indexed-path-actions">
+                                                                <button
+                                                                    type="button"
+                                                                    className="indexed-path-action"
+                                                                    onClick={() => onReindexFolder(p)}
+                                                                    disabled={isIndexing}
+                                                                    title={t('sidebar_reindex_folder')}
+                                                                    aria-label={t('sidebar_reindex_folder')}
+                                                                >
+                                                                    <RefreshCw size={10} />
+                                                                </button>
+                                                                <button
+                                                                    type="button"
+                                                                    className="indexed-path-action danger"
+                                                                    onClick={() => onRemoveFolder(p)}
+                                                                    disabled={isIndexing}
+                                                                    title={t('sidebar_remove_folder')}
+                                                                    aria-label={t('sidebar_remove_folder')}
+                                                                >
+                                                                    <X size={10} />
+                                                                </button>
+                                                            </span>
                                                         </div>
                                                     ))}
                                                 </div>
-                                                <button
-                                                    className="reindex-btn"
-                                                    onClick={onReindexAll}
-                                                    disabled={isIndexing}
-                                                    title={t('sidebar_rebuild_tooltip')}
-                                                >
-                                                    <RefreshCw size={10} className={isIndexing ? 'reindex-spin' : ''} />
-                                                    <span>{t('sidebar_rebuild')}</span>
-                                                </button>
+                                                <div className="reindex-actions">
+                                                    <button
+                                                        className="reindex-btn"
+                                                        onClick={onReindexAll}
+                                                        disabled={isIndexing}
+                                                        title={t('sidebar_rebuild_tooltip')}
+                                                    >
+                                                        <RefreshCw size={10} className={isIndexing ? 'reindex-spin' : ''} />
+                                                        <span>{t('sidebar_rebuild')}</span>
+                                                    </button>
+                                                    <button
+                                                        className="reindex-btn"
+                                                        onClick={onReembed}
+                                                        disabled={isIndexing}
+                                                        title={t('sidebar_reembed_tooltip')}
+                                                    >
+                                                        <Cpu size={10} />
+                                                        <span>{t('sidebar_reembed')}</span>
+                                                    </button>
+                                                </div>
                                             </>
                                         ) : (
                                             <div className="indexed-paths-empty">
                                                 {t('sidebar_no_folders')}
                                             </div>
                                         )}
+                                        <div className="reindex-actions">
+                                            {c.locked && (
+                                                <button
+                                                    className="reindex-btn"
+                                                    onClick={() => onUnlock(c.name)}
+                                                    title={t('sidebar_unlock_tooltip')}
+                                                >
+                                                    <KeyRound size={10} />
+                                                    <span>{t('sidebar_unlock')}</span>
+                                                </button>
+                                            )}
+                                            <button
+                                                className="reindex-btn"
+                                                onClick={() => onSetLocked(c.name, !c.locked)}
+                                                title={t(c.locked ? 'sidebar_remove_lock_tooltip' : 'sidebar_lock_tooltip')}
+                                            >
+                                                {c.locked ? <LockOpen size={10} /> : <Lock size={10} />}
+                                                <span>{t(c.locked ? 'sidebar_remove_lock' : 'sidebar_lock')}</span>
+                                            </button>
+                                        </div>
                                     </div>
                                 )}
                             </div>
@@ -226,7 +288,15 @@ export default function Sidebar({
                             </div>
                         )}
                     </div>
-                    <button className="locale-switcher" onClick={cycleLocale} title={localeLabels[locale] ?? locale}>
+                    <button type="button" className="annotations-toggle" onClick={onBrowseTopics}>
+                        <Shapes size={10} />
+                        <span>{t('sidebar_topics')}</span>
+                    </button>
+                    <button type="button" className="annotations-toggle" onClick={onBrowseTimeline}>
+                        <CalendarDays size={10} />
+                        <span>{t('sidebar_timeline')}</span>
+                    </button>
+                    <button className="locale-switcher" onClick={cycleLocale} title={localeName(locale)}>
                         <Globe size={12} />
                         <span>{locale.toUpperCase()}</span>
                     </button>
@@ -1,6 +1,7 @@
 import { Loader2 } from "lucide-react";
-import type { IndexingProgress } from "../types";
+import type { EndpointHealth, IndexingProgress, ModelDownload } from "../types";
 import { useLocale } from "../i18n";
+import { formatBinding, type ResultActions } from "../keybindings";
 
 interface StatusBarProps {
     status: string;
@@ -9,10 +10,24 @@ interface StatusBarProps {
     activeContainer: string;
     indexedFolderCount: number;
     resultCount: number;
+    resultActions: ResultActions;
+    endpointHealth: EndpointHealth;
+    modelDownload: ModelDownload | null;
+}
+
+function formatEta(secs: number): string {
+    if (secs < 60) return `${secs}s`;
+    const minutes = Math.round(secs / 60);
+    if (minutes < 60) return `${minutes} min`;
+    return `${Math.floor(minutes / 60)} h ${minutes % 60} min`;
+}
+
+function formatMegabytes(bytes: number): string {
+    return `${Math.round(bytes / (1024 * 1024))} MB`;
 }
 
 export default function StatusBar({
-    status, isIndexing, indexProgress, activeContainer, indexedFolderCount, resultCount,
+    status, isIndexing, indexProgress, activeContainer, indexedFolderCount, resultCount, resultActions, endpointHealth, modelDownload,
 }: Readonly<StatusBarProps>) {
     const { t } = useLocale();
 
@@ -20,9 +35,33 @@ export default function StatusBar({
         ? Math.round((indexProgress.current / indexProgress.total) * 100)
         : 0;
 
+    const rate = indexProgress && indexProgress.chunks_per_sec > 0
+        ? ` · ${t("status_throughput", { rate: indexProgress.chunks_per_sec.toFixed(1) })}`
+            + (indexProgress.eta_secs != null ? ` · ${t("status_eta", { time: formatEta(indexProgress.eta_secs) })}` : "")
+        : "";
+
+    // Bytes can run past the rough total; the bar waits at 99% for the load.
+    const downloadPct = modelDownload?.total
+        ? Math.min(99, Math.round((modelDownload.bytes / modelDownload.total) * 100))
+        : null;
+    const downloadText = modelDownload && t("status_downloading_model", {
+        model: modelDownload.model,
+        size: modelDownload.total
+            ? `${formatMegabytes(modelDownload.bytes)} / ~${formatMegabytes(modelDownload.total)}`
+            : formatMegabytes(modelDownload.bytes),
+    });
+
     return (
         <div className="status-bar shrink-0 px-6 flex flex-col justify-center select-none text-[--color-text-secondary]">
-            {isIndexing && indexProgress && indexProgress.total > 0 && (
+            {downloadPct !== null && (
+                <div className="progress-bar-track">
+                    <div
+                        className="progress-bar-fill"
+                        style={{ width: `${downloadPct}%` }}
+                    />
+                </div>
+            )}
+            {!modelDownload && isIndexing && indexProgress && indexProgress.total > 0 && (
                 <div className="progress-bar-track">
                     <div
                         className="progress-bar-fill"
@@ -33,12 +72,27 @@ export default function StatusBar({
             <div className="flex items-center justify-between text-[11px] h-8">
                 <div className="flex items-center gap-3 overflow-hidden">
                     <span className="font-semibold text-[--color-fill-accent-default] opacity-90">{activeContainer}</span>
+                    {endpointHealth.state !== "none" && (
+                        <span
+                            className="endpoint-health flex items-center gap-1.5 shrink-0"
+                            data-state={endpointHealth.state}
+                            title={endpointHealth.error ?? t("status_endpoint_reachable")}
+                        >
+                            <span className="endpoint-health-dot" />
+                            {endpointHealth.state === "unreachable" && t("status_endpoint_unreachable")}
+                        </span>
+                    )}
                     <span className="w-px h-3 bg-[--color-stroke-divider-default]"></span>
-                    {status ? (
+                    {downloadText ? (
+                        <span className="flex items-center gap-2 truncate">
+                            <Loader2 className="animate-spin" size={10} />
+                            {downloadPct === null ? downloadText : `${downloadPct}% · ${downloadText}`}
+                        </span>
+                    ) : status ? (
                         <span className="flex items-center gap-2 truncate">
                             {isIndexing && <Loader2 className="animate-spin" size={10} />}
                             {indexProgress && indexProgress.total > 0
-                                ? `${pct}% · ${status}`
+                                ? `${pct}% · ${status}${rate}`
                                 : status
                             }
                         </span>
@@ -48,7 +102,13 @@ export default function StatusBar({
                 </div>
                 <div className="flex items-center gap-4 opacity-80 px-2">
                     <span className="flex items-center gap-1.5"><span className="font-mono text-[10px] bg-[--color-control-fill-secondary] px-1.5 py-0.5 rounded">↑↓</span> {t("results_navigate")}</span>
-                    <span className="flex items-center gap-1.5"><span className="font-mono text-[10px] bg-[--color-control-fill-secondary] px-1.5 py-0.5 rounded">↵</span> {t("results_open")}</span>
+                    <span className="flex items-center gap-1.5"><span className="font-mono text-[10px] bg-[--color-control-fill-secondary] px-1.5 py-0.5 rounded">{formatBinding(resultActions.open)}</span> {t("results_open")}</span>
+                    {resultCount > 0 && (
+                        <>
+                            <span className="flex items-center gap-1.5"><span className="font-mono text-[10px] bg-[--color-control-fill-secondary] px-1.5 py-0.5 rounded">{formatBinding(resultActions.reveal)}</span> {t("results_reveal")}</span>
+                            <span className="flex items-center gap-1.5"><span className="font-mono text-[10px] bg-[--color-control-fill-secondary] px-1.5 py-0.5 rounded">{formatBinding(resultActions.copy_path)}</span> {t("results_copy_path")}</span>
+                        </>
+                    )}
                 </div>
             </div>
         </div>
@@ -0,0 +1,133 @@
+import { useEffect, useRef, useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { FileText, MessageSquarePlus, Search } from "lucide-react";
+import type { TimelinePeriod } from "../types";
+import { useLocale } from "../i18n";
+
+interface Props {
+    activeContainer: string;
+    onOpenFile: (path: string) => void;
+    onClose: () => void;
+}
+
+const PERIODS = 14;
+
+type Period = "day" | "week";
+
+export default function TimelineView({ activeContainer, onOpenFile, onClose }: Readonly<Props>) {
+    const { t, locale } = useLocale();
+    const [period, setPeriod] = useState<Period>("day");
+    const [filter, setFilter] = useState("");
+    // End of the picked day in local time; null is now.
+    const [jumpTo, setJumpTo] = useState<string | null>(null);
+    const [periods, setPeriods] = useState<TimelinePeriod[]>([]);
+    const [loading, setLoading] = useState(false);
+    const [exhausted, setExhausted] = useState(false);
+    const genRef = useRef(0);
+
+    const fetchPeriods = (until: number | null, append: boolean) => {
+        const gen = ++genRef.current;
+        setLoading(true);
+        invoke<TimelinePeriod[]>("get_timeline", { period, query: filter.trim() || null, until, periods: PERIODS })
+            .then((res) => {
+                if (genRef.current !== gen) return;
+                setPeriods(prev => append ? [...prev, ...res] : res);
+                setExhausted(res.length < PERIODS);
+            })
+            .catch(() => { if (genRef.current === gen && !append) setPeriods([]); })
+            .finally(() => { if (genRef.current === gen) setLoading(false); });
+    };
+
+    useEffect(() => {
+        const timer = setTimeout(() => {
+            const until = jumpTo ? Math.floor(new Date(`${jumpTo}T23:59:59`).getTime() / 1000) : null;
+            fetchPeriods(until, false);
+        }, 200);
+        return () => clearTimeout(timer);
+    }, [activeContainer, period, filter, jumpTo]);
+
+    const loadOlder = () => {
+        const last = periods.at(-1);
+        if (last) fetchPeriods(last.start - 1, true);
+    };
+
+    const heading = (start: number) => {
+        const date = new Date(start * 1000);
+        if (period === "day") {
+            return date.toLocaleDateString(locale, { weekday: "long", year: "numeric", month: "long", day: "numeric" });
+        }
+        return t("timeline_week_of", { date: date.toLocaleDateString(locale, { year: "numeric", month: "long", day: "numeric" }) });
+    };
+
+    return (
+        <div className="topics-view">
+            <div className="annotation-detail-header">
+                <button type="button" className="annotation-detail-close" onClick={onClose}>✕</button>
+                <span className="annotation-detail-path">{t("timeline_title", { container: activeContainer })}</span>
+            </div>
+            <div className="timeline-controls">
+                <div className="annotations-source-tabs">
+                    {(["day", "week"] as const).map(p => (
+                        <button
+                            key={p}
+                            type="button"
+                            className={`annotations-tab ${period === p ? "active" : ""}`}
+                            onClick={() => setPeriod(p)}
+                        >
+                            {t(p === "day" ? "timeline_by_day" : "timeline_by_week")}
+                        </button>
+                    ))}
+                </div>
+                <div className="annotations-search timeline-filter">
+                    <Search size={10} className="annotations-search-icon" />
+                    <input
+                        type="text"
+                        className="annotations-search-input"
+                        placeholder={t("timeline_filter")}
+                        value={filter}
+                        onChange={(e) => setFilter(e.target.value)}
+                    />
+                </div>
+                <input
+                    type="date"
+                    className="timeline-jump"
+                    value={jumpTo ?? ""}
+                    max={new Date().toISOString().slice(0, 10)}
+                    aria-label={t("timeline_jump")}
+                    title={t("timeline_jump")}
+                    onChange={(e) => setJumpTo(e.target.value || null)}
+                />
+            </div>
+            {!loading && periods.length === 0 && <div className="topics-empty">{t("timeline_empty")}</div>}
+            {periods.map(p => (
+                <div key={p.start} className="timeline-period">
+                    <div className="timeline-heading">{heading(p.start)}</div>
+                    <div className="topic-files">
+                        {p.items.map(item => (
+                            <button
+                                key={`${item.path}:${item.time}:${item.note ?? ""}`}
+                                type="button"
+                                className="topic-file"
+                                title={item.note ?? item.path}
+                                onClick={() => onOpenFile(item.path)}
+                            >
+                                {item.note === null ? <FileText size={11} /> : <MessageSquarePlus size={11} />}
+                                <span className="topic-file-name">{item.path.split(/[\\/]/).pop()}</span>
+                                <span className="topic-file-path">{item.note ?? item.path}</span>
+                                <span className="timeline-time">
+                                    {new Date(item.time * 1000).toLocaleTimeString(locale, { hour: "2-digit", minute: "2-digit" })}
+                                </span>
+                            </button>
+                        ))}
+                        {p.more > 0 && <div className="annotation-detail-meta">{t("timeline_more", { count: String(p.more) })}</div>}
+                    </div>
+                </div>
+            ))}
+            {periods.length > 0 && !exhausted && (
+                <button type="button" className="annotations-show-more" onClick={loadOlder} disabled={loading}>
+                    {t("timeline_older")}
+                </button>
+            )}
+        </div>
+    );
+}
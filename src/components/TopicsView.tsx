@@ -0,0 +1,92 @@
+import { useEffect, useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { ChevronDown, ChevronRight, RefreshCw } from "lucide-react";
+import type { TopicMap } from "../types";
+import { useLocale } from "../i18n";
+
+interface Props {
+    activeContainer: string;
+    onOpenFile: (path: string) => void;
+    onClose: () => void;
+}
+
+const FILES_SHOWN = 20;
+
+export default function TopicsView({ activeContainer, onOpenFile, onClose }: Readonly<Props>) {
+    const { t } = useLocale();
+    const [map, setMap] = useState<TopicMap | null>(null);
+    const [building, setBuilding] = useState(false);
+    const [error, setError] = useState<string | null>(null);
+    const [openTopic, setOpenTopic] = useState<number | null>(null);
+    const [fileLimit, setFileLimit] = useState(FILES_SHOWN);
+
+    const load = (refresh: boolean) => {
+        setBuilding(true);
+        setError(null);
+        invoke<TopicMap>("get_topics", { refresh })
+            .then(setMap)
+            .catch((e) => setError(String(e)))
+            .finally(() => setBuilding(false));
+    };
+
+    useEffect(() => {
+        setMap(null);
+        setOpenTopic(null);
+        load(false);
+    }, [activeContainer]);
+
+    const toggleTopic = (id: number) => {
+        setOpenTopic(openTopic === id ? null : id);
+        setFileLimit(FILES_SHOWN);
+    };
+
+    return (
+        <div className="topics-view">
+            <div className="annotation-detail-header">
+                <button type="button" className="annotation-detail-close" onClick={onClose}>✕</button>
+                <span className="annotation-detail-path">{t("topics_title", { container: activeContainer })}</span>
+                <button type="button" className="topics-refresh" onClick={() => load(true)} disabled={building} title={t("topics_refresh")}>
+                    <RefreshCw size={12} className={building ? "reindex-spin" : ""} />
+                </button>
+            </div>
+            {map?.stale && !building && (
+                <div className="annotation-detail-meta">{t("topics_stale")}</div>
+            )}
+            {error && <div className="topics-empty">{error}</div>}
+            {building && !map && <div className="topics-empty">{t("topics_building")}</div>}
+            {map && map.topics.length === 0 && !building && <div className="topics-empty">{t("topics_empty")}</div>}
+            <div className="topics-list">
+                {map?.topics.map(topic => (
+                    <div key={topic.id} className="topic-item">
+                        <button type="button" className="topic-header" onClick={() => toggleTopic(topic.id)}>
+                            {openTopic === topic.id ? <ChevronDown size={12} /> : <ChevronRight size={12} />}
+                            <span className="topic-terms">
+                                {topic.terms.map(term => <span key={term} className="topic-term">{term}</span>)}
+                            </span>
+                            <span className="annotations-count">{topic.files.length}</span>
+                        </button>
+                        {openTopic === topic.id && (
+                            <div className="topic-files">
+                                {topic.files.slice(0, fileLimit).map(path => (
+                                    <button key={path} type="button" className="topic-file" title={path} onClick={() => onOpenFile(path)}>
+                                        <span className="topic-file-name">{path.split(/[\\/]/).pop()}</span>
+                                        <span className="topic-file-path">{path}</span>
+                                    </button>
+                                ))}
+                                {topic.files.length > fileLimit && (
+                                    <button
+                                        type="button"
+                                        className="annotations-show-more"
+                                        onClick={() => setFileLimit(prev => prev + FILES_SHOWN)}
+                                    >
+                                        {t("annotation_show_more", { count: String(topic.files.length - fileLimit) })}
+                                    </button>
+                                )}
+                            </div>
+                        )}
+                    </div>
+                ))}
+            </div>
+        </div>
+    );
+}
@@ -1,44 +1,211 @@
-import { Pin, Rocket, Keyboard, Globe, Layers } from "lucide-react";
+import { useEffect, useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { open, save } from "@tauri-apps/plugin-dialog";
+import { openPath } from "@tauri-apps/plugin-opener";
+import { AppWindow, Pin, Rocket, Globe, Timer, SunMoon, Palette, Maximize2, ListOrdered, ZoomIn, Monitor, MapPin, RotateCcw, Bell, Webhook as WebhookIcon, Terminal, LifeBuoy, FolderOpen, Lock, WifiOff, FolderSync, RefreshCw, X } from "lucide-react";
 import { useLocale } from "../../i18n";
-import { SettingsRow, SettingsToggle } from "./SettingsRow";
+import { SettingsRow, SettingsToggle, commitOnEnter } from "./SettingsRow";
+import type { ThemeMode } from "../../theme";
 import "./GeneralSettings.css";
 
 interface AppConfig {
     always_on_top: boolean;
     launch_at_startup: boolean;
-    hotkey: string;
-    use_reranker: boolean;
+    model_idle_timeout_mins: number;
+    theme: ThemeMode;
+    accent_color: string;
+    window_width: number;
+    window_height: number;
+    max_visible_results: number;
+    ui_scale: number;
+    window_placement: WindowPlacement;
+    remember_window_per_monitor: boolean;
+    window_mode: WindowMode;
+    notify_on_complete: boolean;
+    webhooks: Webhook[];
+    post_index_command: string;
+    read_only: boolean;
+    read_only_session: boolean;
+    offline: boolean;
+    sync_folder: string;
+    portable: boolean;
 }
 
-const localeLabels: Record<string, string> = { en: "English", tr: "Türkçe" };
+type WindowPlacement = "keep" | "primary" | "cursor" | "focused";
+type WindowMode = "popup" | "docked";
+
+interface Webhook {
+    url: string;
+    events: ("indexing-complete" | "indexing-error" | "files-changed")[];
+}
+
+const themeModes: ThemeMode[] = ["dark", "light", "system"];
+const uiScales = [0.75, 0.9, 1, 1.1, 1.25, 1.5, 1.75, 2];
+const placements: WindowPlacement[] = ["keep", "primary", "cursor", "focused"];
+const windowModes: WindowMode[] = ["popup", "docked"];
 
 interface Props {
     config: AppConfig;
-    hotkeyDraft: string;
-    hotkeyDirty: boolean;
-    onHotkeyChange: (v: string) => void;
     updateField: (updates: Partial<AppConfig>) => Promise<void>;
-    setHotkeyDirty: (v: boolean) => void;
 }
 
-export default function GeneralSettings({ config, hotkeyDraft, hotkeyDirty, onHotkeyChange, updateField, setHotkeyDirty }: Readonly<Props>) {
-    const { t, locale, setLocale, availableLocales } = useLocale();
+interface SyncReport {
+    devices: number;
+    containers_updated: number;
+    containers_removed: number;
+    annotations_added: number;
+    annotations_removed: number;
+}
+
+// Containers and annotations go through a folder a sync tool keeps in step
+// across devices; choosing one syncs right away.
+function SyncRow({ folder, updateField }: Readonly<{ folder: string; updateField: Props["updateField"] }>) {
+    const { t } = useLocale();
+    const [busy, setBusy] = useState(false);
+    const [note, setNote] = useState<string | undefined>();
+
+    const syncNow = async () => {
+        setBusy(true);
+        try {
+            const report = await invoke<SyncReport>("sync_now");
+            setNote(t("settings_sync_done", {
+                devices: String(report.devices),
+                containers: String(report.containers_updated + report.containers_removed),
+                annotations: String(report.annotations_added + report.annotations_removed),
+            }));
+        } catch (e) {
+            setNote(String(e));
+        } finally {
+            setBusy(false);
+        }
+    };
+
+    const chooseFolder = async () => {
+        const selected = await open({ directory: true, multiple: false, defaultPath: folder || undefined });
+        if (typeof selected !== "string" || selected === folder) return;
+        await updateField({ sync_folder: selected });
+        await syncNow();
+    };
+
+    return (
+        <SettingsRow
+            icon={<FolderSync size={14} />}
+            label={t("settings_sync")}
+            desc={folder || t("settings_sync_desc")}
+            note={note}
+            control={
+                <div className="language-controls">
+                    <button
+                        type="button"
+                        className="settings-icon-btn"
+                        title={t("settings_sync_choose")}
+                        aria-label={t("settings_sync_choose")}
+                        onClick={chooseFolder}
+                    >
+                        <FolderOpen size={14} />
+                    </button>
+                    {folder && (
+                        <>
+                            <button
+                                type="button"
+                                className="settings-icon-btn"
+                                title={t("settings_sync_now")}
+                                aria-label={t("settings_sync_now")}
+                                disabled={busy}
+                                onClick={syncNow}
+                            >
+                                <RefreshCw size={14} />
+                            </button>
+                            <button
+                                type="button"
+                                className="settings-icon-btn"
+                                title={t("settings_sync_stop")}
+                                aria-label={t("settings_sync_stop")}
+                                onClick={() => { setNote(undefined); updateField({ sync_folder: "" }); }}
+                            >
+                                <X size={14} />
+                            </button>
+                        </>
+                    )}
+                </div>
+            }
+        />
+    );
+}
+
+export default function GeneralSettings({ config, updateField }: Readonly<Props>) {
+    const { t, locale, setLocale, availableLocales, localeName, userLocalesDir } = useLocale();
+    const [diagnosticsState, setDiagnosticsState] = useState<"idle" | "busy" | "done" | "failed">("idle");
+    const [webhooksDraft, setWebhooksDraft] = useState(config.webhooks.map(h => h.url).join("\n"));
+
+    useEffect(() => {
+        setWebhooksDraft(config.webhooks.map(h => h.url).join("\n"));
+    }, [config.webhooks]);
+
+    // One URL per line; event filters set in config.json stay with their URL.
+    function commitWebhooks() {
+        const urls = webhooksDraft.split("\n").map(l => l.trim()).filter(Boolean);
+        if (urls.join("\n") === config.webhooks.map(h => h.url).join("\n")) return;
+        updateField({ webhooks: urls.map(url => config.webhooks.find(h => h.url === url) ?? { url, events: [] }) });
+    }
+
+    const exportDiagnostics = async () => {
+        const stamp = new Date().toISOString().slice(0, 10);
+        const dest = await save({
+            defaultPath: `rememex-diagnostics-${stamp}.zip`,
+            filters: [{ name: "Zip", extensions: ["zip"] }],
+        });
+        if (!dest) return;
+        setDiagnosticsState("busy");
+        try {
+            await invoke("export_diagnostics", { dest });
+            setDiagnosticsState("done");
+        } catch {
+            setDiagnosticsState("failed");
+        }
+    };
+
+    let readOnlyNote: string | undefined;
+    if (config.read_only_session) readOnlyNote = t("settings_read_only_flag");
+    else if (config.portable) readOnlyNote = t("settings_portable_active");
 
     return (
         <div className="settings-group">
             <SettingsRow
-                icon={<Pin size={14} />}
-                label={t("settings_always_on_top")}
-                desc={t("settings_always_on_top_desc")}
+                icon={<AppWindow size={14} />}
+                label={t("settings_window_mode")}
+                desc={t("settings_window_mode_desc")}
                 control={
-                    <SettingsToggle
-                        label={t("settings_always_on_top")}
-                        checked={config.always_on_top}
-                        onChange={(v) => updateField({ always_on_top: v })}
-                    />
+                    <select
+                        className="settings-select"
+                        value={config.window_mode}
+                        aria-label={t("settings_window_mode")}
+                        onChange={(e) => updateField({ window_mode: e.target.value as WindowMode })}
+                    >
+                        {windowModes.map((m) => (
+                            <option key={m} value={m}>
+                                {t(`settings_window_mode_${m}`)}
+                            </option>
+                        ))}
+                    </select>
                 }
             />
 
+            {config.window_mode === "popup" && (
+                <SettingsRow
+                    icon={<Pin size={14} />}
+                    label={t("settings_always_on_top")}
+                    desc={t("settings_always_on_top_desc")}
+                    control={
+                        <SettingsToggle
+                            label={t("settings_always_on_top")}
+                            checked={config.always_on_top}
+                            onChange={(v) => updateField({ always_on_top: v })}
+                        />
+                    }
+                />
+            )}
+
             <SettingsRow
                 icon={<Rocket size={14} />}
                 label={t("settings_launch_startup")}
@@ -53,35 +220,114 @@ export default function GeneralSettings({ config, hotkeyDraft, hotkeyDirty, onHo
             />
 
             <SettingsRow
-                icon={<Keyboard size={14} />}
-                label={t("settings_hotkey")}
-                desc={t("settings_hotkey_desc")}
-                hotkey
-                note={hotkeyDirty ? t("settings_restart_required") : undefined}
+                icon={<Bell size={14} />}
+                label={t("settings_notify_complete")}
+                desc={t("settings_notify_complete_desc")}
                 control={
-                    <div className="hotkey-input-wrapper">
-                        <input
-                            type="text"
-                            className="hotkey-input"
-                            value={hotkeyDraft}
-                            onChange={(e) => {
-                                onHotkeyChange(e.target.value);
-                                setHotkeyDirty(e.target.value !== config.hotkey);
-                            }}
-                            onKeyDown={(e) => {
-                                if (e.key === "Enter" && hotkeyDirty) {
-                                    e.preventDefault();
-                                    updateField({ hotkey: hotkeyDraft }).then(() => setHotkeyDirty(false));
-                                }
-                            }}
-                            spellCheck={false}
-                        />
-                        {hotkeyDirty && (
+                    <SettingsToggle
+                        label={t("settings_notify_complete")}
+                        checked={config.notify_on_complete}
+                        onChange={(v) => updateField({ notify_on_complete: v })}
+                    />
+                }
+            />
+
+            <SettingsRow
+                icon={<WebhookIcon size={14} />}
+                label={t("settings_webhooks")}
+                desc={t("settings_webhooks_desc")}
+                control={
+                    <textarea
+                        className="settings-input settings-textarea"
+                        value={webhooksDraft}
+                        placeholder="http://localhost:5678/webhook/rememex"
+                        onChange={(e) => setWebhooksDraft(e.target.value)}
+                        onBlur={commitWebhooks}
+                        rows={2}
+                        spellCheck={false}
+                        aria-label={t("settings_webhooks")}
+                    />
+                }
+            />
+
+            <SettingsRow
+                icon={<Terminal size={14} />}
+                label={t("settings_post_index_command")}
+                desc={t("settings_post_index_command_desc")}
+                control={
+                    <input
+                        key={`cmd-${config.post_index_command}`}
+                        type="text"
+                        className="settings-input"
+                        defaultValue={config.post_index_command}
+                        placeholder={'rclone sync "$REMEMEX_DB_URI" backup:rememex'}
+                        aria-label={t("settings_post_index_command")}
+                        spellCheck={false}
+                        onKeyDown={commitOnEnter}
+                        onBlur={(e) => {
+                            const v = e.target.value.trim();
+                            if (v !== config.post_index_command) updateField({ post_index_command: v });
+                        }}
+                    />
+                }
+            />
+
+            <SettingsRow
+                icon={<Lock size={14} />}
+                label={t("settings_read_only")}
+                desc={t("settings_read_only_desc")}
+                note={readOnlyNote}
+                control={
+                    <SettingsToggle
+                        label={t("settings_read_only")}
+                        checked={config.read_only || config.read_only_session}
+                        onChange={(v) => updateField({ read_only: v })}
+                    />
+                }
+            />
+
+            <SettingsRow
+                icon={<WifiOff size={14} />}
+                label={t("settings_offline")}
+                desc={t("settings_offline_desc")}
+                control={
+                    <SettingsToggle
+                        label={t("settings_offline")}
+                        checked={config.offline}
+                        onChange={(v) => updateField({ offline: v })}
+                    />
+                }
+            />
+
+            <SyncRow folder={config.sync_folder} updateField={updateField} />
+
+            <SettingsRow
+                icon={<Globe size={14} />}
+                label={t("settings_language")}
+                desc={t("settings_language_desc")}
+                control={
+                    <div className="language-controls">
+                        <select
+                            className="settings-select"
+                            value={locale}
+                            aria-label={t("settings_language")}
+                            onChange={(e) => setLocale(e.target.value)}
+                        >
+                            {availableLocales.map((loc) => (
+                                <option key={loc} value={loc}>
+                                    {localeName(loc)}
+                                </option>
+                            ))}
+                        </select>
+                        {userLocalesDir && (
                             <button
-                                className="hotkey-save"
-                                onClick={() => updateField({ hotkey: hotkeyDraft }).then(() => setHotkeyDirty(false))}
+                                type="button"
+                                className="settings-icon-btn"
+                                title={t("settings_language_folder")}
+                                aria-label={t("settings_language_folder")}
+                                onClick={() => openPath(userLocalesDir).catch(() => { })}
                             >
-                                ↵
+                                <FolderOpen size={14} />
                             </button>
                         )}
                     </div>
@@ -89,19 +335,95 @@ export default function GeneralSettings({ config, hotkeyDraft, hotkeyDirty, onHo
             />
 
             <SettingsRow
-                icon={<Globe size={14} />}
-                label={t("settings_language")}
-                desc={t("settings_language_desc")}
+                icon={<SunMoon size={14} />}
+                label={t("settings_theme")}
+                desc={t("settings_theme_desc")}
+                control={
+                    <select
+                        className="settings-select"
+                        value={config.theme}
+                        aria-label={t("settings_theme")}
+                        onChange={(e) => updateField({ theme: e.target.value as ThemeMode })}
+                    >
+                        {themeModes.map((mode) => (
+                            <option key={mode} value={mode}>
+                                {t(`settings_theme_${mode}`)}
+                            </option>
+                        ))}
+                    </select>
+                }
+            />
+
+            <SettingsRow
+                icon={<Palette size={14} />}
+                label={t("settings_accent_color")}
+                desc={t("settings_accent_color_desc")}
+                control={
+                    <input
+                        type="color"
+                        className="accent-color-input"
+                        value={config.accent_color}
+                        aria-label={t("settings_accent_color")}
+                        onChange={(e) => updateField({ accent_color: e.target.value })}
+                    />
+                }
+            />
+
+            <SettingsRow
+                icon={<Maximize2 size={14} />}
+                label={t("settings_window_size")}
+                desc={t("settings_window_size_desc")}
+                control={
+                    <div className="window-size-inputs">
+                        <input
+                            key={`w-${config.window_width}`}
+                            type="number"
+                            className="settings-number-input"
+                            defaultValue={config.window_width}
+                            aria-label={t("settings_window_width")}
+                            min={480}
+                            max={2560}
+                            step={10}
+                            onKeyDown={commitOnEnter}
+                            onBlur={(e) => {
+                                const v = Number.parseInt(e.target.value, 10);
+                                if (v && v !== config.window_width) updateField({ window_width: v });
+                            }}
+                        />
+                        <span>×</span>
+                        <input
+                            key={`h-${config.window_height}`}
+                            type="number"
+                            className="settings-number-input"
+                            defaultValue={config.window_height}
+                            aria-label={t("settings_window_height")}
+                            min={320}
+                            max={1600}
+                            step={10}
+                            onKeyDown={commitOnEnter}
+                            onBlur={(e) => {
+                                const v = Number.parseInt(e.target.value, 10);
+                                if (v && v !== config.window_height) updateField({ window_height: v });
+                            }}
+                        />
+                    </div>
+                }
+            />
+
+            <SettingsRow
+                icon={<Monitor size={14} />}
+                label={t("settings_window_placement")}
+                desc={t("settings_window_placement_desc")}
                 control={
                     <select
                         className="settings-select"
-                        value={locale}
-                        aria-label={t("settings_language")}
-                        onChange={(e) => setLocale(e.target.value)}
+                        value={config.window_placement}
+                        aria-label={t("settings_window_placement")}
+                        onChange={(e) => updateField({ window_placement: e.target.value as WindowPlacement })}
                     >
-                        {availableLocales.map((loc) => (
-                            <option key={loc} value={loc}>
-                                {localeLabels[loc] ?? loc}
+                        {placements.map((p) => (
+                            <option key={p} value={p}>
+                                {t(`settings_window_placement_${p}`)}
                             </option>
                         ))}
                     </select>
@@ -109,17 +431,110 @@ export default function GeneralSettings({ config, hotkeyDraft, hotkeyDirty, onHo
             />
 
             <SettingsRow
-                icon={<Layers size={14} />}
-                label={t("settings_use_reranker")}
-                desc={t("settings_use_reranker_desc")}
+                icon={<MapPin size={14} />}
+                label={t("settings_remember_per_monitor")}
+                desc={t("settings_remember_per_monitor_desc")}
                 control={
                     <SettingsToggle
-                        label={t("settings_use_reranker")}
-                        checked={config.use_reranker}
-                        onChange={(v) => updateField({ use_reranker: v })}
+                        label={t("settings_remember_per_monitor")}
+                        checked={config.remember_window_per_monitor}
+                        onChange={(v) => updateField({ remember_window_per_monitor: v })}
+                    />
+                }
+            />
+
+            {config.remember_window_per_monitor && (
+                <SettingsRow
+                    icon={<RotateCcw size={14} />}
+                    label={t("settings_reset_window_position")}
+                    desc={t("settings_reset_window_position_desc")}
+                    control={
+                        <button
+                            type="button"
+                            className="settings-action-btn"
+                            onClick={() => { invoke("reset_window_position").catch(console.error); }}
+                        >
+                            {t("settings_reset_window_position_btn")}
+                        </button>
+                    }
+                />
+            )}
+
+            <SettingsRow
+                icon={<ListOrdered size={14} />}
+                label={t("settings_max_results")}
+                desc={t("settings_max_results_desc")}
+                control={
+                    <input
+                        type="number"
+                        className="settings-number-input"
+                        value={config.max_visible_results}
+                        aria-label={t("settings_max_results")}
+                        min={1}
+                        max={50}
+                        onChange={(e) => {
+                            const v = e.target.value ? Number.parseInt(e.target.value, 10) : 1;
+                            updateField({ max_visible_results: Math.max(1, v) });
+                        }}
+                    />
+                }
+            />
+
+            <SettingsRow
+                icon={<ZoomIn size={14} />}
+                label={t("settings_ui_scale")}
+                desc={t("settings_ui_scale_desc")}
+                control={
+                    <select
+                        className="settings-select"
+                        value={String(config.ui_scale)}
+                        aria-label={t("settings_ui_scale")}
+                        onChange={(e) => updateField({ ui_scale: Number(e.target.value) })}
+                    >
+                        {(uiScales.includes(config.ui_scale) ? uiScales : [...uiScales, config.ui_scale].sort((a, b) => a - b)).map((scale) => (
+                            <option key={scale} value={String(scale)}>
+                                {Math.round(scale * 100)}%
+                            </option>
+                        ))}
+                    </select>
+                }
+            />
+
+            <SettingsRow
+                icon={<Timer size={14} />}
+                label={t("settings_idle_unload")}
+                desc={t("settings_idle_unload_desc")}
+                control={
+                    <input
+                        type="number"
+                        className="settings-number-input"
+                        value={config.model_idle_timeout_mins}
+                        aria-label={t("settings_idle_unload")}
+                        min={0}
+                        max={1440}
+                        onChange={(e) => {
+                            const v = e.target.value ? Number.parseInt(e.target.value, 10) : 0;
+                            updateField({ model_idle_timeout_mins: Math.max(0, v) });
+                        }}
                     />
                 }
             />
+
+            <SettingsRow
+                icon={<LifeBuoy size={14} />}
+                label={t("settings_export_diagnostics")}
+                desc={t("settings_export_diagnostics_desc")}
+                control={
+                    <button
+                        type="button"
+                        className="settings-action-btn"
+                        disabled={diagnosticsState === "busy"}
+                        onClick={exportDiagnostics}
+                    >
+                        {t(`settings_export_diagnostics_${diagnosticsState}`)}
+                    </button>
+                }
+            />
         </div>
     );
 }
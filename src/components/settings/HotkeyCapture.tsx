@@ -0,0 +1,73 @@
+import { useEffect, useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { useLocale } from "../../i18n";
+import { bindingFromEvent } from "../../keybindings";
+
+interface Props {
+    value: string;
+    label: string;
+    onCapture: (binding: string) => void;
+    /** Release the global hotkey while listening so pressing it doesn't hide the window. */
+    pauseGlobalHotkey?: boolean;
+    /** Require at least one modifier, as a bare letter can't work as a global hotkey. */
+    requireModifier?: boolean;
+}
+
+const MODIFIER_CODES = new Set([
+    "ControlLeft", "ControlRight", "AltLeft", "AltRight", "ShiftLeft", "ShiftRight", "MetaLeft", "MetaRight",
+]);
+
+export default function HotkeyCapture({ value, label, onCapture, pauseGlobalHotkey, requireModifier }: Readonly<Props>) {
+    const { t } = useLocale();
+    const [capturing, setCapturing] = useState(false);
+    const [hint, setHint] = useState<string | null>(null);
+
+    useEffect(() => {
+        if (!capturing || !pauseGlobalHotkey) return;
+        invoke("set_hotkey_paused", { paused: true }).catch(() => { });
+        return () => { invoke("set_hotkey_paused", { paused: false }).catch(() => { }); };
+    }, [capturing, pauseGlobalHotkey]);
+
+    function handleKeyDown(e: React.KeyboardEvent<HTMLButtonElement>) {
+        if (!capturing) return;
+        e.preventDefault();
+        e.stopPropagation();
+
+        const hasModifier = e.ctrlKey || e.altKey || e.shiftKey || e.metaKey;
+        if (e.code === "Escape" && !hasModifier) {
+            setCapturing(false);
+            setHint(null);
+            return;
+        }
+        if (MODIFIER_CODES.has(e.code)) return;
+
+        const binding = bindingFromEvent(e);
+        if (!binding) {
+            setHint(t("hotkey_unsupported_key"));
+            return;
+        }
+        if (requireModifier && !hasModifier) {
+            setHint(t("hotkey_needs_modifier"));
+            return;
+        }
+        setCapturing(false);
+        setHint(null);
+        if (binding !== value) onCapture(binding);
+    }
+
+    return (
+        <div className="hotkey-capture">
+            <button
+                type="button"
+                className={`hotkey-input${capturing ? " capturing" : ""}`}
+                aria-label={label}
+                onClick={() => { setCapturing(true); setHint(null); }}
+                onBlur={() => { setCapturing(false); setHint(null); }}
+                onKeyDown={handleKeyDown}
+            >
+                {capturing ? t("hotkey_press_keys") : value}
+            </button>
+            {hint && <span className="hotkey-capture-hint">{hint}</span>}
+        </div>
+    );
+}
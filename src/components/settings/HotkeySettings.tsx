@@ -0,0 +1,90 @@
+import { Keyboard, Coins, CornerDownLeft, FolderOpen, Copy, TextQuote, ClipboardList } from "lucide-react";
+import { useLocale } from "../../i18n";
+import type { ResultActions } from "../../keybindings";
+import { SettingsRow, commitOnEnter } from "./SettingsRow";
+import HotkeyCapture from "./HotkeyCapture";
+import "./GeneralSettings.css";
+
+interface HotkeyConfig {
+    hotkey: string;
+    result_actions: ResultActions;
+    context_copy_tokens: number;
+}
+
+interface Props {
+    config: HotkeyConfig;
+    updateField: (updates: Record<string, unknown>) => Promise<void>;
+}
+
+const actionIcons: Record<keyof ResultActions, React.ReactNode> = {
+    open: <CornerDownLeft size={14} />,
+    reveal: <FolderOpen size={14} />,
+    copy_path: <Copy size={14} />,
+    copy_snippet: <TextQuote size={14} />,
+    copy_context: <ClipboardList size={14} />,
+};
+
+export default function HotkeySettings({ config, updateField }: Readonly<Props>) {
+    const { t } = useLocale();
+
+    return (
+        <div className="settings-group">
+            <SettingsRow
+                icon={<Keyboard size={14} />}
+                label={t("settings_hotkey")}
+                desc={t("settings_hotkey_desc")}
+                hotkey
+                control={
+                    <HotkeyCapture
+                        value={config.hotkey}
+                        label={t("settings_hotkey")}
+                        onCapture={(binding) => updateField({ hotkey: binding })}
+                        pauseGlobalHotkey
+                        requireModifier
+                    />
+                }
+            />
+
+            <div className="settings-section-title">{t("settings_section_result_actions")}</div>
+
+            {(Object.keys(actionIcons) as (keyof ResultActions)[]).map((action) => (
+                <SettingsRow
+                    key={action}
+                    icon={actionIcons[action]}
+                    label={t(`settings_action_${action}`)}
+                    desc={t(`settings_action_${action}_desc`)}
+                    control={
+                        <HotkeyCapture
+                            value={config.result_actions[action]}
+                            label={t(`settings_action_${action}`)}
+                            onCapture={(binding) => updateField({ result_actions: { ...config.result_actions, [action]: binding } })}
+                        />
+                    }
+                />
+            ))}
+
+            <SettingsRow
+                icon={<Coins size={14} />}
+                label={t("settings_context_tokens")}
+                desc={t("settings_context_tokens_desc")}
+                control={
+                    <input
+                        key={`context-${config.context_copy_tokens}`}
+                        type="number"
+                        className="settings-number-input"
+                        defaultValue={config.context_copy_tokens}
+                        aria-label={t("settings_context_tokens")}
+                        min={200}
+                        max={200000}
+                        step={500}
+                        onKeyDown={commitOnEnter}
+                        onBlur={(e) => {
+                            const v = Math.min(200000, Math.max(200, Number.parseInt(e.target.value, 10) || 4000));
+                            if (v !== config.context_copy_tokens) updateField({ context_copy_tokens: v });
+                        }}
+                    />
+                }
+            />
+        </div>
+    );
+}
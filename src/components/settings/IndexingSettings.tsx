@@ -1,12 +1,36 @@
-import { GitBranch, Ruler, FilePlus, FileX } from "lucide-react";
+import { useEffect, useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { save } from "@tauri-apps/plugin-dialog";
+import { GitBranch, Ruler, FilePlus, FileX, Activity, FolderX, BatteryLow, Cpu, HardDrive, History, PackageOpen } from "lucide-react";
 import { useLocale } from "../../i18n";
-import { SettingsRow, SettingsToggle } from "./SettingsRow";
+import { SettingsRow, SettingsToggle, commitOnEnter } from "./SettingsRow";
 import "./IndexingSettings.css";
 
 interface IndexingConfig {
     use_git_history: boolean;
     chunk_size: number | null;
     chunk_overlap: number | null;
+    throttle_on_battery: boolean;
+    throttle_cpu_percent: number;
+    index_quota_mb: number;
+    index_quota_policy: "stop" | "evict";
+    index_size_mb: number | null;
+    version_history_days: number;
+}
+
+const quotaPolicies = ["stop", "evict"] as const;
+
+interface WatcherStatus {
+    running: boolean;
+    paused: boolean;
+    roots: string[];
+    events: number;
+    low_power: boolean;
+    files_indexed: number;
+    files_removed: number;
+    errors: number;
+    last_event_at: number | null;
+    queued_files: number;
 }
 
 interface Props {
@@ -15,6 +39,8 @@ interface Props {
     excludedExtDraft: string;
     setExtraExtDraft: (v: string) => void;
     setExcludedExtDraft: (v: string) => void;
+    ignoredDirsDraft: string;
+    setIgnoredDirsDraft: (v: string) => void;
     updateField: (updates: Record<string, unknown>) => Promise<void>;
 }
 
@@ -25,14 +51,204 @@ function parseExtensions(raw: string): string[] {
         .filter((s) => s.length > 0);
 }
 
+function parseDirNames(raw: string): string[] {
+    return raw
+        .split(/[,\s]+/)
+        .map((s) => s.trim().replace(/[/\\]+$/, ""))
+        .filter((s) => s.length > 0);
+}
+
+function WatcherStatusRow() {
+    const { t } = useLocale();
+    const [status, setStatus] = useState<WatcherStatus | null>(null);
+
+    useEffect(() => {
+        const load = () => invoke<WatcherStatus>("get_watcher_status").then(setStatus).catch(() => { });
+        load();
+        const timer = setInterval(load, 2000);
+        return () => clearInterval(timer);
+    }, []);
+
+    if (!status) return null;
+
+    let desc = t("settings_watcher_stopped");
+    if (status.paused) desc = t("settings_watcher_paused");
+    else if (status.running && status.low_power) desc = t("settings_watcher_low_power");
+    else if (status.running) desc = t("settings_watcher_running", { count: String(status.roots.length) });
+
+    const lines = [
+        t("settings_watcher_stats", {
+            events: String(status.events),
+            indexed: String(status.files_indexed + status.files_removed),
+        }),
+    ];
+    if (status.last_event_at) {
+        lines.push(t("settings_watcher_last_event", { time: new Date(status.last_event_at * 1000).toLocaleTimeString() }));
+    }
+    if (status.queued_files > 0) lines.push(t("settings_watcher_queued", { count: String(status.queued_files) }));
+    if (status.errors > 0) lines.push(t("settings_watcher_errors", { count: String(status.errors) }));
+
+    return (
+        <SettingsRow
+            icon={<Activity size={14} />}
+            label={t("settings_watcher")}
+            desc={desc}
+            control={
+                <div className="watcher-stats" title={status.roots.join("\n")}>
+                    {lines.map((line) => <span key={line}>{line}</span>)}
+                </div>
+            }
+        />
+    );
+}
+
+// The active container's index as a folder a colleague can search with
+// `rememex-cli search --bundle`.
+function ExportBundleRow() {
+    const { t } = useLocale();
+    const [state, setState] = useState<"idle" | "busy" | "done" | "failed">("idle");
+    const [desc, setDesc] = useState(t("settings_export_bundle_desc"));
+
+    const exportBundle = async () => {
+        const stamp = new Date().toISOString().slice(0, 10);
+        const dest = await save({ defaultPath: `rememex-bundle-${stamp}` });
+        if (!dest) return;
+        setState("busy");
+        try {
+            const manifest = await invoke<{ files: number; chunks: number }>("export_search_bundle", { dest });
+            setDesc(t("settings_export_bundle_written", { files: String(manifest.files), path: dest }));
+            setState("done");
+        } catch (e) {
+            setDesc(String(e));
+            setState("failed");
+        }
+    };
+
+    return (
+        <SettingsRow
+            icon={<PackageOpen size={14} />}
+            label={t("settings_export_bundle")}
+            desc={desc}
+            control={
+                <button type="button" className="settings-action-btn" disabled={state === "busy"} onClick={exportBundle}>
+                    {t(`settings_export_diagnostics_${state}`)}
+                </button>
+            }
+        />
+    );
+}
+
 export default function IndexingSettings({
-    config, extraExtDraft, excludedExtDraft,
-    setExtraExtDraft, setExcludedExtDraft, updateField,
+    config, extraExtDraft, excludedExtDraft, ignoredDirsDraft,
+    setExtraExtDraft, setExcludedExtDraft, setIgnoredDirsDraft, updateField,
 }: Readonly<Props>) {
     const { t } = useLocale();
 
     return (
         <>
+            <WatcherStatusRow />
+
+            <SettingsRow
+                icon={<BatteryLow size={14} />}
+                label={t("settings_throttle_battery")}
+                desc={t("settings_throttle_battery_desc")}
+                control={
+                    <SettingsToggle
+                        label={t("settings_throttle_battery")}
+                        checked={config.throttle_on_battery}
+                        onChange={(v) => updateField({ throttle_on_battery: v })}
+                    />
+                }
+            />
+
+            <SettingsRow
+                icon={<Cpu size={14} />}
+                label={t("settings_throttle_cpu")}
+                desc={t("settings_throttle_cpu_desc")}
+                control={
+                    <input
+                        key={`cpu-${config.throttle_cpu_percent}`}
+                        type="number"
+                        className="settings-number-input"
+                        defaultValue={config.throttle_cpu_percent}
+                        aria-label={t("settings_throttle_cpu")}
+                        min={0}
+                        max={100}
+                        onKeyDown={commitOnEnter}
+                        onBlur={(e) => {
+                            const v = Math.min(100, Math.max(0, Number.parseInt(e.target.value, 10) || 0));
+                            if (v !== config.throttle_cpu_percent) updateField({ throttle_cpu_percent: v });
+                        }}
+                    />
+                }
+            />
+
+            <SettingsRow
+                icon={<HardDrive size={14} />}
+                label={t("settings_index_quota")}
+                desc={config.index_size_mb === null
+                    ? t("settings_index_quota_desc")
+                    : t("settings_index_quota_used", { size: String(config.index_size_mb) })}
+                control={
+                    <div className="settings-number-group">
+                        <input
+                            key={`quota-${config.index_quota_mb}`}
+                            type="number"
+                            className="settings-number-input"
+                            defaultValue={config.index_quota_mb || ""}
+                            placeholder="MB"
+                            aria-label={t("settings_index_quota")}
+                            min={0}
+                            onKeyDown={commitOnEnter}
+                            onBlur={(e) => {
+                                const v = Math.max(0, Number.parseInt(e.target.value, 10) || 0);
+                                if (v !== config.index_quota_mb) updateField({ index_quota_mb: v });
+                            }}
+                        />
+                        <select
+                            className="settings-select"
+                            value={config.index_quota_policy}
+                            aria-label={t("settings_index_quota_policy")}
+                            onChange={(e) => updateField({ index_quota_policy: e.target.value })}
+                        >
+                            {quotaPolicies.map((policy) => (
+                                <option key={policy} value={policy}>
+                                    {t(`settings_index_quota_${policy}`)}
+                                </option>
+                            ))}
+                        </select>
+                    </div>
+                }
+            />
+
+            <SettingsRow
+                icon={<History size={14} />}
+                label={t("settings_version_history")}
+                desc={t("settings_version_history_desc")}
+                control={
+                    <div className="settings-number-group">
+                        <input
+                            key={`history-${config.version_history_days}`}
+                            type="number"
+                            className="settings-number-input"
+                            defaultValue={config.version_history_days || ""}
+                            placeholder="0"
+                            aria-label={t("settings_version_history")}
+                            min={0}
+                            max={3650}
+                            onKeyDown={commitOnEnter}
+                            onBlur={(e) => {
+                                const v = Math.min(3650, Math.max(0, Number.parseInt(e.target.value, 10) || 0));
+                                if (v !== config.version_history_days) updateField({ version_history_days: v });
+                            }}
+                        />
+                        <span className="settings-number-label">{t("settings_version_history_days")}</span>
+                    </div>
+                }
+            />
+
+            <ExportBundleRow />
+
             <SettingsRow
                 icon={<GitBranch size={14} />}
                 label={t("settings_git_history")}
@@ -53,30 +269,34 @@ export default function IndexingSettings({
                 control={
                     <div className="settings-number-group">
                         <input
+                            key={`size-${config.chunk_size}`}
                             type="number"
                             className="settings-number-input"
-                            value={config.chunk_size ?? ""}
+                            defaultValue={config.chunk_size ?? ""}
                             placeholder="512"
                             aria-label={t("settings_chunk_size")}
-                            min={64}
-                            max={4096}
-                            onChange={(e) => {
+                            min={100}
+                            max={8192}
+                            onKeyDown={commitOnEnter}
+                            onBlur={(e) => {
                                 const v = e.target.value ? Number.parseInt(e.target.value, 10) : null;
-                                updateField({ chunk_size: v });
+                                if (v !== config.chunk_size) updateField({ chunk_size: v });
                             }}
                         />
                         <span className="settings-number-label">{t("settings_chunk_overlap")}</span>
                         <input
+                            key={`overlap-${config.chunk_overlap}`}
                             type="number"
                             className="settings-number-input"
-                            value={config.chunk_overlap ?? ""}
+                            defaultValue={config.chunk_overlap ?? ""}
                             placeholder="64"
                             aria-label={t("settings_chunk_overlap")}
                             min={0}
-                            max={512}
-                            onChange={(e) => {
+                            max={4096}
+                            onKeyDown={commitOnEnter}
+                            onBlur={(e) => {
                                 const v = e.target.value ? Number.parseInt(e.target.value, 10) : null;
-                                updateField({ chunk_overlap: v });
+                                if (v !== config.chunk_overlap) updateField({ chunk_overlap: v });
                             }}
                         />
                     </div>
@@ -124,6 +344,27 @@ export default function IndexingSettings({
                     />
                 }
             />
+
+            <SettingsRow
+                icon={<FolderX size={14} />}
+                label={t("settings_watch_ignored_dirs")}
+                desc={t("settings_watch_ignored_dirs_desc")}
+                control={
+                    <input
+                        type="text"
+                        className="settings-ext-input"
+                        value={ignoredDirsDraft}
+                        placeholder="node_modules, target"
+                        aria-label={t("settings_watch_ignored_dirs")}
+                        spellCheck={false}
+                        onChange={(e) => setIgnoredDirsDraft(e.target.value)}
+                        onBlur={() => updateField({ watch_ignored_dirs: parseDirNames(ignoredDirsDraft) })}
+                        onKeyDown={(e) => {
+                            if (e.key === "Enter") updateField({ watch_ignored_dirs: parseDirNames(ignoredDirsDraft) });
+                        }}
+                    />
+                }
+            />
         </>
     );
 }
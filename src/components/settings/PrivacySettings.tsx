@@ -0,0 +1,126 @@
+import { useEffect, useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { Cloud, Sparkles, FolderTree, Database, Webhook, Download, ShieldCheck, WifiOff, KeyRound, AlertTriangle } from "lucide-react";
+import { useLocale } from "../../i18n";
+import "./PrivacySettings.css";
+
+type FlowKind = "embeddings" | "hyde" | "summaries" | "object_store" | "webhook" | "model_download";
+type Payload =
+    | "chunk_text"
+    | "annotations"
+    | "queries"
+    | "index"
+    | "changed_paths"
+    | "indexing_errors"
+    | "indexing_totals"
+    | "model_name";
+
+interface OutboundFlow {
+    kind: FlowKind;
+    destination: string;
+    payloads: Payload[];
+    containers: string[];
+    credentials: boolean;
+}
+
+interface PrivacyReport {
+    offline: boolean;
+    flows: OutboundFlow[];
+}
+
+interface FlaggedFile {
+    kinds: ("key" | "password")[];
+    chunks: number;
+    policy: "report" | "mask" | "skip";
+    at: number;
+}
+
+const kindIcons: Record<FlowKind, React.ReactNode> = {
+    embeddings: <Cloud size={14} />,
+    hyde: <Sparkles size={14} />,
+    summaries: <FolderTree size={14} />,
+    object_store: <Database size={14} />,
+    webhook: <Webhook size={14} />,
+    model_download: <Download size={14} />,
+};
+
+interface Props {
+    // Only here so the report is built again after a setting changes.
+    config: object;
+}
+
+export default function PrivacySettings({ config }: Readonly<Props>) {
+    const { t } = useLocale();
+    const [report, setReport] = useState<PrivacyReport | null>(null);
+    const [secrets, setSecrets] = useState<Record<string, FlaggedFile>>({});
+
+    useEffect(() => {
+        invoke<PrivacyReport>("get_privacy_report").then(setReport).catch(() => setReport(null));
+        invoke<Record<string, FlaggedFile>>("get_secret_report").then(setSecrets).catch(() => setSecrets({}));
+    }, [config]);
+
+    if (!report) return null;
+
+    let summary = t("privacy_summary");
+    if (report.flows.length === 0) summary = t("privacy_nothing_sent");
+    else if (report.offline) summary = t("privacy_offline");
+
+    return (
+        <div className="privacy-report">
+            <div className="privacy-summary">
+                {report.offline ? <WifiOff size={14} /> : <ShieldCheck size={14} />}
+                <span>{summary}</span>
+            </div>
+
+            {report.flows.map((flow) => (
+                <div key={`${flow.kind}-${flow.destination}-${flow.payloads.join()}`} className="privacy-flow">
+                    <div className="settings-row-info">
+                        <span className="settings-row-icon">{kindIcons[flow.kind]}</span>
+                        <div>
+                            <span className="settings-row-label">{t(`privacy_kind_${flow.kind}`)}</span>
+                            <span className="privacy-destination">{flow.destination}</span>
+                            <ul className="privacy-payloads">
+                                {flow.payloads.map((p) => (
+                                    <li key={p}>{t(`privacy_payload_${p}`)}</li>
+                                ))}
+                            </ul>
+                            {flow.containers.length > 0 && (
+                                <span className="settings-row-desc">
+                                    {t("privacy_containers", { names: flow.containers.join(", ") })}
+                                </span>
+                            )}
+                            {flow.credentials && (
+                                <span className="privacy-credentials">
+                                    <KeyRound size={10} />
+                                    {t("privacy_credentials")}
+                                </span>
+                            )}
+                        </div>
+                    </div>
+                </div>
+            ))}
+
+            {Object.keys(secrets).length > 0 && (
+                <div className="privacy-flow">
+                    <div className="settings-row-info">
+                        <span className="settings-row-icon"><AlertTriangle size={14} /></span>
+                        <div>
+                            <span className="settings-row-label">{t("privacy_secrets_title")}</span>
+                            <span className="settings-row-desc">{t("privacy_secrets_desc")}</span>
+                            <ul className="privacy-payloads">
+                                {Object.entries(secrets).map(([path, file]) => (
+                                    <li key={path}>
+                                        <span className="privacy-destination">{path}</span>
+                                        {t(`privacy_secrets_${file.policy}`, {
+                                            kinds: file.kinds.map((k) => t(`privacy_secrets_kind_${k}`)).join(", "),
+                                        })}
+                                    </li>
+                                ))}
+                            </ul>
+                        </div>
+                    </div>
+                </div>
+            )}
+        </div>
+    );
+}
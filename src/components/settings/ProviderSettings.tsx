@@ -1,4 +1,6 @@
-import { Brain, Cloud, Server, Link, Key, Hash } from "lucide-react";
+import { useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { Brain, Cloud, Server, Link, Key, Hash, Download } from "lucide-react";
 import { useLocale } from "../../i18n";
 import { SettingsRow } from "./SettingsRow";
 import "./ProviderSettings.css";
@@ -35,6 +37,17 @@ export default function ProviderSettings({
     setRemoteDimsDraft, setProviderChanged, updateField,
 }: Readonly<Props>) {
     const { t } = useLocale();
+    const [prefetchState, setPrefetchState] = useState<"idle" | "busy" | "done" | "failed">("idle");
+
+    const prefetchModels = async () => {
+        setPrefetchState("busy");
+        try {
+            await invoke("prefetch_models");
+            setPrefetchState("done");
+        } catch {
+            setPrefetchState("failed");
+        }
+    };
 
     return (
         <>
@@ -98,6 +111,23 @@ export default function ProviderSettings({
             {config.provider_type === "local" && config.embedding_model !== "MultilingualE5Base" && (
                 <span className="settings-row-note">{t("settings_restart_reindex")}</span>
             )}
+            {config.provider_type === "local" && (
+                <SettingsRow
+                    icon={<Download size={14} />}
+                    label={t("settings_prefetch_models")}
+                    desc={t("settings_prefetch_models_desc")}
+                    control={
+                        <button
+                            type="button"
+                            className="settings-action-btn"
+                            disabled={prefetchState === "busy"}
+                            onClick={prefetchModels}
+                        >
+                            {t(`settings_prefetch_models_${prefetchState}`)}
+                        </button>
+                    }
+                />
+            )}
 
             {config.provider_type === "remote" && (
                 <div className="settings-remote-fields">
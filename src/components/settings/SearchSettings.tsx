@@ -1,6 +1,8 @@
-import { Search, Brain, Shuffle, Sparkles } from "lucide-react";
+import { useEffect, useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { Search, Brain, Shuffle, Sparkles, Layers, History, Eraser, Scissors, Replace, Compass, Timer, Type, HardDrive } from "lucide-react";
 import { useLocale } from "../../i18n";
-import { SettingsRow, SettingsToggle } from "./SettingsRow";
+import { SettingsRow, SettingsToggle, commitOnEnter } from "./SettingsRow";
 import "./SearchSettings.css";
 
 interface AppConfig {
@@ -10,10 +12,20 @@ interface AppConfig {
     hyde_model: string;
     hyde_api_key: string;
     query_router_enabled: boolean;
+    auto_container: boolean;
     mmr_enabled: boolean;
     mmr_lambda: number;
+    fts_stemming: boolean;
+    synonyms: string[];
+    recent_files_enabled: boolean;
+    search_debounce_ms: number;
+    search_min_query_chars: number;
+    desktop_search: DesktopSearch;
 }
 
+type DesktopSearch = "off" | "everything" | "windows_search";
+const desktopSearches: DesktopSearch[] = ["off", "everything", "windows_search"];
+
 interface Props {
     config: AppConfig;
     updateField: (updates: Record<string, unknown>) => Promise<void>;
@@ -21,9 +33,107 @@ interface Props {
 
 export default function SearchSettings({ config, updateField }: Readonly<Props>) {
     const { t } = useLocale();
+    const [hydeEndpointDraft, setHydeEndpointDraft] = useState(config.hyde_endpoint);
+    const [hydeModelDraft, setHydeModelDraft] = useState(config.hyde_model);
+    const [hydeApiKeyDraft, setHydeApiKeyDraft] = useState(config.hyde_api_key);
+    const [historyCleared, setHistoryCleared] = useState(false);
+    const [synonymsDraft, setSynonymsDraft] = useState(config.synonyms.join("\n"));
+
+    useEffect(() => {
+        setHydeEndpointDraft(config.hyde_endpoint);
+        setHydeModelDraft(config.hyde_model);
+        setHydeApiKeyDraft(config.hyde_api_key);
+    }, [config.hyde_endpoint, config.hyde_model, config.hyde_api_key]);
+
+    useEffect(() => {
+        setSynonymsDraft(config.synonyms.join("\n"));
+    }, [config.synonyms]);
+
+    function commitSynonyms() {
+        const lines = synonymsDraft.split("\n").map(l => l.trim()).filter(Boolean);
+        if (lines.join("\n") !== config.synonyms.join("\n")) updateField({ synonyms: lines });
+    }
 
     return (
         <>
+            <SettingsRow
+                icon={<Timer size={14} />}
+                label={t("settings_search_debounce")}
+                desc={t("settings_search_debounce_desc")}
+                control={
+                    <input
+                        key={`debounce-${config.search_debounce_ms}`}
+                        type="number"
+                        className="settings-number-input"
+                        defaultValue={config.search_debounce_ms}
+                        aria-label={t("settings_search_debounce")}
+                        min={0}
+                        max={2000}
+                        step={50}
+                        onKeyDown={commitOnEnter}
+                        onBlur={(e) => {
+                            const v = Math.min(2000, Math.max(0, Number.parseInt(e.target.value, 10) || 0));
+                            if (v !== config.search_debounce_ms) updateField({ search_debounce_ms: v });
+                        }}
+                    />
+                }
+            />
+
+            <SettingsRow
+                icon={<Type size={14} />}
+                label={t("settings_search_min_chars")}
+                desc={t("settings_search_min_chars_desc")}
+                control={
+                    <input
+                        key={`min-chars-${config.search_min_query_chars}`}
+                        type="number"
+                        className="settings-number-input"
+                        defaultValue={config.search_min_query_chars}
+                        aria-label={t("settings_search_min_chars")}
+                        min={1}
+                        max={20}
+                        onKeyDown={commitOnEnter}
+                        onBlur={(e) => {
+                            const v = Math.min(20, Math.max(1, Number.parseInt(e.target.value, 10) || 1));
+                            if (v !== config.search_min_query_chars) updateField({ search_min_query_chars: v });
+                        }}
+                    />
+                }
+            />
+
+            <SettingsRow
+                icon={<HardDrive size={14} />}
+                label={t("settings_desktop_search")}
+                desc={t("settings_desktop_search_desc")}
+                control={
+                    <select
+                        className="settings-select"
+                        value={config.desktop_search}
+                        aria-label={t("settings_desktop_search")}
+                        onChange={(e) => updateField({ desktop_search: e.target.value as DesktopSearch })}
+                    >
+                        {desktopSearches.map((s) => (
+                            <option key={s} value={s}>
+                                {t(`settings_desktop_search_${s}`)}
+                            </option>
+                        ))}
+                    </select>
+                }
+            />
+
+            <SettingsRow
+                icon={<Layers size={14} />}
+                label={t("settings_use_reranker")}
+                desc={t("settings_use_reranker_desc")}
+                control={
+                    <SettingsToggle
+                        label={t("settings_use_reranker")}
+                        checked={config.use_reranker}
+                        onChange={(v) => updateField({ use_reranker: v })}
+                    />
+                }
+            />
+
             <SettingsRow
                 icon={<Search size={14} />}
                 label={t("settings_query_router")}
@@ -37,6 +147,19 @@ export default function SearchSettings({ config, updateField }: Readonly<Props>)
                 }
             />
 
+            <SettingsRow
+                icon={<Compass size={14} />}
+                label={t("settings_auto_container")}
+                desc={t("settings_auto_container_desc")}
+                control={
+                    <SettingsToggle
+                        label={t("settings_auto_container")}
+                        checked={config.auto_container}
+                        onChange={(v) => updateField({ auto_container: v })}
+                    />
+                }
+            />
+
             <SettingsRow
                 icon={<Shuffle size={14} />}
                 label={t("settings_mmr")}
@@ -72,6 +195,70 @@ export default function SearchSettings({ config, updateField }: Readonly<Props>)
                 />
             )}
 
+            <SettingsRow
+                icon={<Scissors size={14} />}
+                label={t("settings_fts_stemming")}
+                desc={t("settings_fts_stemming_desc")}
+                control={
+                    <SettingsToggle
+                        label={t("settings_fts_stemming")}
+                        checked={config.fts_stemming}
+                        onChange={(v) => updateField({ fts_stemming: v })}
+                    />
+                }
+            />
+
+            <SettingsRow
+                icon={<Replace size={14} />}
+                label={t("settings_synonyms")}
+                desc={t("settings_synonyms_desc")}
+                control={
+                    <textarea
+                        className="settings-input settings-textarea"
+                        value={synonymsDraft}
+                        placeholder={"auth = authentication, login\nk8s, kubernetes"}
+                        onChange={(e) => setSynonymsDraft(e.target.value)}
+                        onBlur={commitSynonyms}
+                        rows={3}
+                        spellCheck={false}
+                        aria-label={t("settings_synonyms")}
+                    />
+                }
+            />
+
+            <SettingsRow
+                icon={<History size={14} />}
+                label={t("settings_recent_files")}
+                desc={t("settings_recent_files_desc")}
+                control={
+                    <SettingsToggle
+                        label={t("settings_recent_files")}
+                        checked={config.recent_files_enabled}
+                        onChange={(v) => updateField({ recent_files_enabled: v })}
+                    />
+                }
+            />
+
+            {config.recent_files_enabled && (
+                <SettingsRow
+                    icon={<Eraser size={14} />}
+                    label={t("settings_clear_recent")}
+                    desc={t("settings_clear_recent_desc")}
+                    control={
+                        <button
+                            type="button"
+                            className="settings-action-btn"
+                            disabled={historyCleared}
+                            onClick={() => {
+                                invoke("clear_recent").then(() => setHistoryCleared(true)).catch(() => { });
+                            }}
+                        >
+                            {historyCleared ? t("settings_clear_recent_done") : t("settings_clear_recent_button")}
+                        </button>
+                    }
+                />
+            )}
+
             <SettingsRow
                 icon={<Sparkles size={14} />}
                 label={t("settings_hyde")}
@@ -95,9 +282,11 @@ export default function SearchSettings({ config, updateField }: Readonly<Props>)
                             <input
                                 type="text"
                                 className="settings-input"
-                                value={config.hyde_endpoint}
+                                value={hydeEndpointDraft}
                                 placeholder="http://localhost:11434/v1/chat/completions"
-                                onChange={(e) => updateField({ hyde_endpoint: e.target.value })}
+                                onChange={(e) => setHydeEndpointDraft(e.target.value)}
+                                onKeyDown={commitOnEnter}
+                                onBlur={() => { if (hydeEndpointDraft !== config.hyde_endpoint) updateField({ hyde_endpoint: hydeEndpointDraft }); }}
                                 spellCheck={false}
                             />
                         }
@@ -110,9 +299,11 @@ export default function SearchSettings({ config, updateField }: Readonly<Props>)
                             <input
                                 type="text"
                                 className="settings-input"
-                                value={config.hyde_model}
+                                value={hydeModelDraft}
                                 placeholder="llama3.2"
-                                onChange={(e) => updateField({ hyde_model: e.target.value })}
+                                onChange={(e) => setHydeModelDraft(e.target.value)}
+                                onKeyDown={commitOnEnter}
+                                onBlur={() => { if (hydeModelDraft !== config.hyde_model) updateField({ hyde_model: hydeModelDraft }); }}
                                 spellCheck={false}
                             />
                         }
@@ -125,9 +316,11 @@ export default function SearchSettings({ config, updateField }: Readonly<Props>)
                             <input
                                 type="password"
                                 className="settings-input"
-                                value={config.hyde_api_key}
+                                value={hydeApiKeyDraft}
                                 placeholder="sk-..."
-                                onChange={(e) => updateField({ hyde_api_key: e.target.value })}
+                                onChange={(e) => setHydeApiKeyDraft(e.target.value)}
+                                onKeyDown={commitOnEnter}
+                                onBlur={() => { if (hydeApiKeyDraft !== config.hyde_api_key) updateField({ hyde_api_key: hydeApiKeyDraft }); }}
                                 spellCheck={false}
                             />
                         }
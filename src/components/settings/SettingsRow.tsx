@@ -42,3 +42,7 @@ export function SettingsToggle({ label, checked, onChange }: Readonly<SettingsTo
         </label>
     );
 }
+
+export function commitOnEnter(e: React.KeyboardEvent<HTMLInputElement>) {
+    if (e.key === "Enter") e.currentTarget.blur();
+}
@@ -0,0 +1,34 @@
+export type FileKind = "code" | "web" | "doc" | "pdf" | "data" | "config" | "image" | "other";
+
+const KIND_EXTENSIONS: Record<Exclude<FileKind, "other">, string[]> = {
+    code: [
+        "rs", "ts", "tsx", "js", "jsx", "mjs", "cjs", "py", "go", "java", "kt", "kts", "scala", "groovy", "gradle",
+        "clj", "cljs", "cljc", "c", "cpp", "cc", "cxx", "h", "hpp", "hxx", "hh", "cs", "fs", "fsi", "fsx", "vb",
+        "vbs", "swift", "m", "mm", "dart", "php", "rb", "pl", "pm", "lua", "r", "jl", "ex", "exs", "erl", "hrl",
+        "hs", "lhs", "ml", "mli", "elm", "zig", "nim", "v", "d", "sol", "move", "wat", "asm", "s", "pas", "lisp",
+        "el", "rkt", "sql", "graphql", "gql", "proto", "sh", "bash", "zsh", "fish", "ps1", "bat", "cmd",
+    ],
+    web: ["html", "htm", "css", "scss", "sass", "less", "styl", "vue", "svelte", "astro", "pug", "ejs", "hbs", "svg"],
+    doc: ["md", "mdx", "txt", "rst", "adoc", "tex", "bib", "log", "docx", "odt", "rtf"],
+    pdf: ["pdf"],
+    data: ["json", "jsonl", "csv", "tsv", "xml", "lock"],
+    config: [
+        "toml", "yaml", "yml", "ini", "cfg", "conf", "env", "properties", "dockerfile", "makefile", "cmake",
+        "tf", "tfvars", "hcl", "nix",
+    ],
+    image: ["png", "jpg", "jpeg", "bmp", "tiff", "tif", "gif", "webp"],
+};
+
+const EXTENSION_KIND = new Map<string, FileKind>(
+    (Object.entries(KIND_EXTENSIONS) as [FileKind, string[]][]).flatMap(([kind, exts]) => exts.map((ext) => [ext, kind] as const)),
+);
+
+export function getExtension(path: string): string {
+    const name = path.split(/[\\/]/).pop() || path;
+    const dot = name.lastIndexOf(".");
+    return dot > 0 ? name.slice(dot + 1).toLowerCase() : name.toLowerCase();
+}
+
+export function getFileKind(path: string): FileKind {
+    return EXTENSION_KIND.get(getExtension(path)) ?? "other";
+}
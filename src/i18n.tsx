@@ -1,15 +1,35 @@
 import { createContext, useContext, useState, useEffect, useCallback, useMemo } from "react";
+import { invoke } from "@tauri-apps/api/core";
 import en from "./locales/en.json";
-import tr from "./locales/tr.json";
 
 type LocaleKey = keyof typeof en;
 type LocaleMap = Record<LocaleKey, string>;
+type PartialLocale = Partial<LocaleMap>;
 
-const locales: Record<string, LocaleMap> = { en, tr };
+// Every ./locales/*.json is bundled; adding a language is just adding a file.
+const bundled: Record<string, PartialLocale> = Object.fromEntries(
+    Object.entries(
+        import.meta.glob<PartialLocale>("./locales/*.json", { eager: true, import: "default" })
+    ).map(([path, strings]) => [path.slice("./locales/".length, -".json".length), strings])
+);
 
-function getSystemLocale(): string {
+interface UserLocales {
+    dir: string;
+    locales: Record<string, PartialLocale>;
+}
+
+/** User files in %AppData%\com.rememex.app\locales override bundled keys or add new languages. */
+function mergeLocales(user: Record<string, PartialLocale>): Record<string, PartialLocale> {
+    const merged: Record<string, PartialLocale> = { ...bundled };
+    for (const [code, strings] of Object.entries(user)) {
+        merged[code] = { ...merged[code], ...strings };
+    }
+    return merged;
+}
+
+function getSystemLocale(available: Record<string, PartialLocale>): string {
     const lang = navigator.language?.split("-")[0] || "en";
-    return lang in locales ? lang : "en";
+    return lang in available ? lang : "en";
 }
 
 interface LocaleContextType {
@@ -17,27 +37,48 @@ interface LocaleContextType {
     setLocale: (locale: string) => void;
     t: (key: LocaleKey, vars?: Record<string, string | number>) => string;
     availableLocales: string[];
+    localeName: (locale: string) => string;
+    userLocalesDir: string | null;
 }
 
 const LocaleContext = createContext<LocaleContextType>({
     locale: "en",
     setLocale: () => { },
     t: (key) => key,
-    availableLocales: Object.keys(locales),
+    availableLocales: Object.keys(bundled),
+    localeName: (locale) => locale,
+    userLocalesDir: null,
 });
 
 export function LocaleProvider({ children }: Readonly<{ children: React.ReactNode }>) {
-    const [currentLocale, setCurrentLocale] = useState(() => {
-        const saved = localStorage.getItem("rememex-locale");
-        return saved && saved in locales ? saved : getSystemLocale();
-    });
+    const [locales, setLocales] = useState(bundled);
+    const [userLocalesDir, setUserLocalesDir] = useState<string | null>(null);
+    // A saved user-only locale isn't loaded yet on first render; t() falls back to en until it is.
+    const [currentLocale, setCurrentLocale] = useState(
+        () => localStorage.getItem("rememex-locale") || getSystemLocale(bundled)
+    );
+
+    useEffect(() => {
+        const load = () => {
+            invoke<UserLocales>("get_user_locales")
+                .then(({ dir, locales: user }) => {
+                    setUserLocalesDir(dir);
+                    setLocales(mergeLocales(user));
+                })
+                .catch(() => { });
+        };
+        load();
+        // Re-read on focus so a dropped-in or edited file shows up without a restart.
+        window.addEventListener("focus", load);
+        return () => window.removeEventListener("focus", load);
+    }, []);
 
     const setLocale = useCallback((newLocale: string) => {
         if (newLocale in locales) {
             setCurrentLocale(newLocale);
             localStorage.setItem("rememex-locale", newLocale);
         }
-    }, []);
+    }, [locales]);
 
     useEffect(() => {
         document.documentElement.lang = currentLocale;
@@ -45,7 +86,7 @@ export function LocaleProvider({ children }: Readonly<{ children: React.ReactNod
 
     const t = useCallback(
         (key: LocaleKey, vars?: Record<string, string | number>): string => {
-            let str = locales[currentLocale]?.[key] || locales.en[key] || key;
+            let str = locales[currentLocale]?.[key] || locales.en?.[key] || en[key] || key;
             if (vars) {
                 for (const [k, v] of Object.entries(vars)) {
                     str = str.replaceAll(`{{${k}}}`, String(v));
@@ -53,12 +94,24 @@ export function LocaleProvider({ children }: Readonly<{ children: React.ReactNod
             }
             return str;
         },
-        [currentLocale]
+        [locales, currentLocale]
+    );
+
+    const localeName = useCallback(
+        (code: string) => locales[code]?.locale_name || code,
+        [locales]
     );
 
     const value = useMemo(
-        () => ({ locale: currentLocale, setLocale, t, availableLocales: Object.keys(locales) }),
-        [currentLocale, setLocale, t]
+        () => ({
+            locale: currentLocale,
+            setLocale,
+            t,
+            availableLocales: Object.keys(locales).sort((a, b) => a.localeCompare(b)),
+            localeName,
+            userLocalesDir,
+        }),
+        [currentLocale, setLocale, t, locales, localeName, userLocalesDir]
     );
 
     return (
@@ -0,0 +1,115 @@
+export interface ResultActions {
+    open: string;
+    reveal: string;
+    copy_path: string;
+    copy_snippet: string;
+    copy_context: string;
+}
+
+export const defaultResultActions: ResultActions = {
+    open: "Enter",
+    reveal: "Ctrl+Enter",
+    copy_path: "Ctrl+C",
+    copy_snippet: "Ctrl+Shift+C",
+    copy_context: "Ctrl+Alt+C",
+};
+
+const META_NAMES = new Set(["meta", "cmd", "super", "win"]);
+
+const CODE_NAMES: Record<string, string> = {
+    Space: "Space",
+    Enter: "Enter",
+    NumpadEnter: "Enter",
+    Tab: "Tab",
+    Escape: "Escape",
+    Backspace: "Backspace",
+    Delete: "Delete",
+    Insert: "Insert",
+    Home: "Home",
+    End: "End",
+    PageUp: "PageUp",
+    PageDown: "PageDown",
+    ArrowUp: "Up",
+    ArrowDown: "Down",
+    ArrowLeft: "Left",
+    ArrowRight: "Right",
+    Minus: "Minus",
+    Equal: "Equal",
+    BracketLeft: "[",
+    BracketRight: "]",
+    Backslash: "Backslash",
+    Semicolon: "Semicolon",
+    Quote: "Quote",
+    Comma: "Comma",
+    Period: "Period",
+    Slash: "Slash",
+    Backquote: "Backquote",
+};
+
+// Names match what the Rust side's parse_hotkey accepts, so a captured binding
+// can be used for both the global hotkey and in-window result actions.
+export function keyNameFromCode(code: string): string | null {
+    if (/^Key[A-Z]$/.test(code)) return code.slice(3);
+    if (/^Digit\d$/.test(code)) return code.slice(5);
+    if (/^F([1-9]|1[0-2])$/.test(code)) return code;
+    return CODE_NAMES[code] ?? null;
+}
+
+export function bindingFromEvent(e: KeyboardEvent | React.KeyboardEvent): string | null {
+    const key = keyNameFromCode(e.code);
+    if (!key) return null;
+    const parts: string[] = [];
+    if (e.ctrlKey) parts.push("Ctrl");
+    if (e.altKey) parts.push("Alt");
+    if (e.shiftKey) parts.push("Shift");
+    if (e.metaKey) parts.push("Super");
+    parts.push(key);
+    return parts.join("+");
+}
+
+/** A key pressed to pick or confirm input method text, which belongs to
+ *  the IME rather than to shortcuts. Safari reports the confirming Enter
+ *  after composition ends, as key code 229. */
+export function isComposingKey(e: KeyboardEvent): boolean {
+    return e.isComposing || e.keyCode === 229;
+}
+
+export function matchesBinding(e: KeyboardEvent, binding: string): boolean {
+    const parts = binding.split("+").map((p) => p.trim().toLowerCase()).filter(Boolean);
+    const key = parts.pop();
+    if (!key) return false;
+
+    const ctrl = parts.includes("ctrl") || parts.includes("control");
+    const shift = parts.includes("shift");
+    const alt = parts.includes("alt");
+    const meta = parts.some((p) => META_NAMES.has(p));
+    if (e.ctrlKey !== ctrl || e.shiftKey !== shift || e.altKey !== alt || e.metaKey !== meta) return false;
+
+    const pressed = e.key.toLowerCase();
+    if (pressed === key) return true;
+    if (keyNameFromCode(e.code)?.toLowerCase() === key) return true;
+    if (key === "space") return pressed === " ";
+    if (key === "esc") return pressed === "escape";
+    if (key === "del") return pressed === "delete";
+    return e.code.toLowerCase() === `key${key}` || e.code.toLowerCase() === `digit${key}`;
+}
+
+export function formatBinding(binding: string): string {
+    return binding
+        .split("+")
+        .map((p) => p.trim())
+        .filter(Boolean)
+        .map((p) => (p.toLowerCase() === "enter" ? "↵" : p))
+        .join("+");
+}
+
+/** Results that get an Alt+digit to open them, counting from the top. */
+export const JUMP_RESULTS = 9;
+
+/** The index of the result Alt+1 to Alt+9 opens, by the key's position so it
+ *  works on layouts where the digits need Shift. */
+export function jumpIndexFromEvent(e: KeyboardEvent): number | null {
+    if (!e.altKey || e.ctrlKey || e.metaKey || e.shiftKey) return null;
+    const match = /^Digit([1-9])$/.exec(e.code);
+    return match ? Number(match[1]) - 1 : null;
+}
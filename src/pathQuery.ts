@@ -0,0 +1,14 @@
+import { homeDir } from "@tauri-apps/api/path";
+
+/** A query that's a path being typed rather than something to search for. */
+export function isPathQuery(query: string): boolean {
+    return query.startsWith("/") || query.startsWith("~") || /^[A-Za-z]:[\\/]/.test(query);
+}
+
+/** The typed path with a leading `~` swapped for the home folder. */
+export async function resolvePath(query: string): Promise<string> {
+    const path = query.trim();
+    if (!/^~([\\/]|$)/.test(path)) return path;
+    const home = (await homeDir()).replace(/[\\/]+$/, "");
+    return home + path.slice(1);
+}
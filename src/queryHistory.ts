@@ -0,0 +1,22 @@
+const MAX_QUERIES = 100;
+
+function storageKey(container: string): string {
+    return `rememex-queries:${container}`;
+}
+
+/** Queries that led to an opened result in this container, newest first. */
+export function pastQueries(container: string): string[] {
+    try {
+        const stored = JSON.parse(localStorage.getItem(storageKey(container)) ?? "[]");
+        return Array.isArray(stored) ? stored.filter((q): q is string => typeof q === "string") : [];
+    } catch {
+        return [];
+    }
+}
+
+export function recordQuery(container: string, query: string) {
+    const trimmed = query.trim();
+    if (!trimmed) return;
+    const queries = [trimmed, ...pastQueries(container).filter(q => q !== trimmed)].slice(0, MAX_QUERIES);
+    localStorage.setItem(storageKey(container), JSON.stringify(queries));
+}
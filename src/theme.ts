@@ -0,0 +1,54 @@
+export type ThemeMode = "dark" | "light" | "system";
+
+export interface Appearance {
+    theme: ThemeMode;
+    accent_color: string;
+}
+
+const systemDark = globalThis.matchMedia("(prefers-color-scheme: dark)");
+let current: Appearance | null = null;
+
+function resolveTheme(theme: ThemeMode): "dark" | "light" {
+    if (theme === "system") return systemDark.matches ? "dark" : "light";
+    return theme;
+}
+
+function parseHex(hex: string): [number, number, number] | null {
+    const m = /^#([0-9a-f]{2})([0-9a-f]{2})([0-9a-f]{2})$/i.exec(hex.trim());
+    if (!m) return null;
+    return [Number.parseInt(m[1], 16), Number.parseInt(m[2], 16), Number.parseInt(m[3], 16)];
+}
+
+function applyAccent(hex: string) {
+    const rgb = parseHex(hex);
+    const style = document.documentElement.style;
+    if (!rgb) return;
+    const [r, g, b] = rgb;
+    const alpha = (a: number) => `rgba(${r}, ${g}, ${b}, ${a})`;
+
+    style.setProperty("--color-fill-accent-default", hex);
+    style.setProperty("--color-fill-accent-secondary", alpha(0.9));
+    style.setProperty("--color-fill-accent-tertiary", alpha(0.8));
+    style.setProperty("--color-fill-accent-glow", alpha(0.2));
+    style.setProperty("--color-fill-accent-glow-subtle", alpha(0.15));
+    style.setProperty("--color-fill-accent-border", alpha(0.3));
+    style.setProperty("--color-fill-accent-bg-subtle", alpha(0.06));
+    style.setProperty("--color-accent-glow-bar", alpha(0.4));
+
+    const luminance = (0.2126 * r + 0.7152 * g + 0.0722 * b) / 255;
+    style.setProperty("--color-on-accent", luminance > 0.55 ? "#000" : "#fff");
+}
+
+export function applyAppearance(appearance: Appearance, vibrancy: boolean) {
+    current = appearance;
+    const root = document.documentElement;
+    root.dataset.theme = resolveTheme(appearance.theme);
+    root.dataset.vibrancy = vibrancy ? "on" : "off";
+    applyAccent(appearance.accent_color);
+}
+
+systemDark.addEventListener("change", () => {
+    if (current?.theme === "system") {
+        document.documentElement.dataset.theme = resolveTheme("system");
+    }
+});
@@ -1,13 +1,45 @@
 export interface SearchResult {
     path: string;
     snippet: string;
+    excerpt: string;
+    context: string;
+    page: number | null;
     score: number;
+    pinned: boolean;
+    deleted: boolean;
+    unindexed: boolean;
+    /** Found in one of the `remote_containers`; the path is on that machine. */
+    remote: RemoteSource | null;
+}
+
+export interface RemoteSource {
+    name: string;
+    container: string;
+    latency_ms: number;
 }
 
 export interface IndexingProgress {
     current: number;
     total: number;
     path: string;
+    bytes: number;
+    chunks: number;
+    chunks_total: number;
+    chunks_per_sec: number;
+    eta_secs: number | null;
+}
+
+/** A model download under way, `total` a rough size when it's known. */
+export interface ModelDownload {
+    model: string;
+    bytes: number;
+    total: number | null;
+}
+
+/** How the remote embedding endpoint answered last; "none" for a local model. */
+export interface EndpointHealth {
+    state: "none" | "reachable" | "unreachable";
+    error: string | null;
 }
 
 export interface ContainerItem {
@@ -15,4 +47,75 @@ export interface ContainerItem {
     description: string;
     indexed_paths: string[];
     provider_label: string;
+    db_uri: string | null;
+    role: "writer" | "reader";
+    locked: boolean;
+}
+
+export interface Completion {
+    text: string;
+    kind: "file" | "symbol" | "folder" | "history";
+}
+
+export interface Topic {
+    id: number;
+    terms: string[];
+    files: string[];
+}
+
+export interface TopicMap {
+    topics: Topic[];
+    built_at: number;
+    stale: boolean;
+}
+
+export type EntityKind = "person" | "project" | "tool";
+
+export interface Entity {
+    name: string;
+    kind: EntityKind;
+    mentions: number;
+    files: number;
+}
+
+export interface EntityFiles {
+    name: string;
+    kind: EntityKind;
+    files: { path: string; mentions: number }[];
+    related: { name: string; kind: EntityKind; shared_files: number }[];
+}
+
+export interface TimelineItem {
+    path: string;
+    time: number;
+    /** Set for an annotation, null for a file. */
+    note: string | null;
+}
+
+export interface TimelinePeriod {
+    start: number;
+    items: TimelineItem[];
+    more: number;
+}
+
+export interface Passage {
+    n: number;
+    text: string;
+    line: number | null;
+    page: string | null;
+}
+
+export interface Translation {
+    text: string;
+    /** ISO 639-1 code the text was detected in, empty when unsure. */
+    source: string;
+    /** False when the text already was in the target language. */
+    translated: boolean;
+}
+
+export interface FileAnswer {
+    path: string;
+    /** Null without a chat endpoint; the passages still show where to look. */
+    answer: string | null;
+    passages: Passage[];
 }